@@ -6,14 +6,30 @@ import { ScrollArea } from "@/components/ui/scroll-area"
 import { Play, FolderOpen, Loader2, CheckCircle, XCircle, ChevronDown, ChevronUp, Terminal } from "lucide-react"
 import { StreamViewer } from "@/components/StreamViewer"
 import { ProjectSelector } from "@/components/ProjectSelector"
-import { api, type BuildEvent, type BuildProduct, type Simulator, type StreamLogEvent, type ProjectRecord } from "@/lib/api"
+import { api, applySimulatorChange, destinationForSimulator, type BuildDiagnostic, type BuildEvent, type BuildProduct, type Simulator, type StreamLogEvent, type ProjectRecord } from "@/lib/api"
+
+// Languages the app can be launched in, with the matching region format locale
+const LAUNCH_LANGUAGES = [
+  { label: "Simulator default", language: "", locale: "" },
+  { label: "English (US)", language: "en", locale: "en_US" },
+  { label: "English (UK)", language: "en-GB", locale: "en_GB" },
+  { label: "Spanish", language: "es", locale: "es_ES" },
+  { label: "French", language: "fr", locale: "fr_FR" },
+  { label: "German", language: "de", locale: "de_DE" },
+  { label: "Italian", language: "it", locale: "it_IT" },
+  { label: "Portuguese (Brazil)", language: "pt-BR", locale: "pt_BR" },
+  { label: "Japanese", language: "ja", locale: "ja_JP" },
+  { label: "Chinese (Simplified)", language: "zh-Hans", locale: "zh_CN" },
+  { label: "Arabic", language: "ar", locale: "ar_SA" },
+  { label: "Hebrew", language: "he", locale: "he_IL" },
+]
 
 type BuildState =
   | { status: "idle" }
-  | { status: "building"; lines: string[] }
+  | { status: "building"; lines: string[]; progress?: number | null; buildId?: string }
   | { status: "installing" }
   | { status: "streaming"; udid: string }
-  | { status: "error"; message: string }
+  | { status: "error"; message: string; diagnostics?: BuildDiagnostic[] }
   | { status: "success"; products: BuildProduct[] }
 
 export function BuildAndRun() {
@@ -22,6 +38,7 @@ export function BuildAndRun() {
   const [selectedSimulator, setSelectedSimulator] = useState("")
   const [schemes, setSchemes] = useState<string[]>([])
   const [selectedScheme, setSelectedScheme] = useState("")
+  const [launchLanguage, setLaunchLanguage] = useState("")
   const [buildState, setBuildState] = useState<BuildState>({ status: "idle" })
   const [streamLogs, setStreamLogs] = useState<string[]>([])
   const [showLogs, setShowLogs] = useState(true)
@@ -34,7 +51,7 @@ export function BuildAndRun() {
       const data = await api.xcode.discover({ path })
       setSchemes(data.schemes || [])
       if (data.schemes?.length > 0) {
-        setSelectedScheme(data.schemes[0])
+        setSelectedScheme(data.suggestedScheme || data.schemes[0])
       }
     } catch (err) {
       console.error("Failed to discover schemes:", err)
@@ -57,6 +74,13 @@ export function BuildAndRun() {
       .catch((err) => console.error("Failed to fetch simulators:", err))
   }, [])
 
+  // Keep the simulator list current as devices are added, removed, booted or shut down
+  useEffect(() => {
+    return api.simulator.onDeviceChange((change) => {
+      setSimulators((current) => applySimulatorChange(current, change))
+    })
+  }, [])
+
   // Subscribe to simulator logs when streaming starts
   useEffect(() => {
     if (buildState.status !== "streaming") {
@@ -67,14 +91,14 @@ export function BuildAndRun() {
 
     const formatLogEvent = (event: StreamLogEvent): string => {
       const timestamp = new Date().toLocaleTimeString()
-      switch (event.type) {
-        case "info":
+      switch (event.event) {
+        case "log.info":
           return `[${timestamp}] INFO: ${event.message}`
-        case "error":
+        case "log.error":
           return `[${timestamp}] ERROR: ${event.message}`
-        case "debug":
+        case "log.debug":
           return `[${timestamp}] DEBUG: ${event.message}`
-        case "frame":
+        case "log.frame":
           return `[${timestamp}] FRAME: #${event.frameNumber}`
         default:
           return `[${timestamp}] ${JSON.stringify(event)}`
@@ -110,23 +134,23 @@ export function BuildAndRun() {
       return
     }
 
-    setBuildState({ status: "building", lines: [] })
+    setBuildState({ status: "building", lines: [], progress: 0 })
 
     try {
       const lines: string[] = []
 
       // Wait for build to complete using a promise
-      const buildResult = await new Promise<{ success: boolean; products: BuildProduct[]; buildDir?: string; error?: string }>((resolve) => {
+      const buildResult = await new Promise<{ success: boolean; products: BuildProduct[]; buildDir?: string; error?: string; diagnostics?: BuildDiagnostic[] }>((resolve) => {
         // Subscribe to build events
         const unsubscribeBuild = api.xcode.onBuildEvent((event: BuildEvent) => {
           // Log to Chromium DevTools console
-          if (event.type === "output" && event.line) {
+          if (event.event === "build.output" && event.line) {
             console.log("[BUILD]", event.line)
             lines.push(event.line)
-            setBuildState({ status: "building", lines: [...lines] })
-          } else if (event.type === "started") {
+            setBuildState({ status: "building", lines: [...lines], progress: event.progress, buildId: event.buildId })
+          } else if (event.event === "build.started") {
             console.log("[BUILD] Started:", event.scheme)
-          } else if (event.type === "completed") {
+          } else if (event.event === "build.completed") {
             console.log("[BUILD] Completed:", event.success ? "SUCCESS" : "FAILED")
             if (event.products?.length) {
               console.log("[BUILD] Products:", event.products.map(p => p.name).join(", "))
@@ -136,8 +160,9 @@ export function BuildAndRun() {
               success: event.success ?? false,
               products: event.products || [],
               buildDir: event.buildDir,
+              diagnostics: event.diagnostics,
             })
-          } else if (event.type === "error") {
+          } else if (event.event === "build.error") {
             console.error("[BUILD] Error:", event.message)
             unsubscribeBuild()
             resolve({
@@ -149,9 +174,11 @@ export function BuildAndRun() {
         })
 
         // Start the build
+        const simulator = simulators.find((sim) => sim.udid === selectedSimulator)
         api.xcode.startBuild({
           path: projectPath,
           scheme: selectedScheme,
+          destination: destinationForSimulator(simulator),
         })
       })
 
@@ -160,6 +187,7 @@ export function BuildAndRun() {
         setBuildState({
           status: "error",
           message: buildResult.error || "Build failed",
+          diagnostics: buildResult.diagnostics?.filter((diagnostic) => diagnostic.severity === "error"),
         })
         return
       }
@@ -183,17 +211,17 @@ export function BuildAndRun() {
       // Install and launch
       setBuildState({ status: "installing" })
 
+      const language = LAUNCH_LANGUAGES.find((option) => option.language === launchLanguage)
       await api.simulator.launch({
         udid: selectedSimulator,
         appPath: buildProducts[0].path,
+        language: language?.language || undefined,
+        locale: language?.locale || undefined,
       })
 
       // Start streaming
-      await api.simulator.startStream({
-        udid: selectedSimulator,
-        fps: 60,
-        quality: 0.7,
-      })
+      // Frame rate and quality default to the simulator's device class
+      await api.simulator.startStream({ udid: selectedSimulator })
 
       setBuildState({ status: "streaming", udid: selectedSimulator })
     } catch (err) {
@@ -222,7 +250,7 @@ export function BuildAndRun() {
   const getStatusText = () => {
     switch (buildState.status) {
       case "building":
-        return "Building..."
+        return buildState.progress ? `Building... ${Math.round(buildState.progress * 100)}%` : "Building..."
       case "installing":
         return "Installing..."
       case "streaming":
@@ -330,6 +358,22 @@ export function BuildAndRun() {
               </select>
             </div>
 
+            {/* App Language */}
+            <div className="flex flex-col gap-2">
+              <label className="text-sm text-muted-foreground">App language</label>
+              <select
+                className="w-full h-9 px-3 rounded-md border border-input bg-background text-sm"
+                value={launchLanguage}
+                onChange={(e) => setLaunchLanguage(e.target.value)}
+              >
+                {LAUNCH_LANGUAGES.map((option) => (
+                  <option key={option.language} value={option.language}>
+                    {option.label}
+                  </option>
+                ))}
+              </select>
+            </div>
+
             {/* Build & Run Button */}
             <Button
               className="w-full mt-2"
@@ -340,6 +384,12 @@ export function BuildAndRun() {
               <span className="ml-2">{getStatusText()}</span>
             </Button>
 
+            {buildState.status === "building" && buildState.buildId && (
+              <Button variant="outline" className="w-full" onClick={() => api.xcode.cancelBuild(buildState.buildId!)}>
+                Cancel build
+              </Button>
+            )}
+
             {/* Build Output */}
             {buildState.status === "building" && buildState.lines.length > 0 && (
               <div className="flex flex-col gap-2">
@@ -354,6 +404,31 @@ export function BuildAndRun() {
               </div>
             )}
 
+            {/* Build Errors */}
+            {buildState.status === "error" && !!buildState.diagnostics?.length && (
+              <div className="flex flex-col gap-2">
+                <label className="text-sm text-muted-foreground">
+                  Errors ({buildState.diagnostics.length})
+                </label>
+                <ScrollArea className="h-[200px] rounded-md border p-2 bg-black/20">
+                  <ul className="flex flex-col gap-2 text-xs">
+                    {buildState.diagnostics.map((diagnostic, index) => (
+                      <li key={index}>
+                        <div className="text-red-500">{diagnostic.message}</div>
+                        {diagnostic.file && (
+                          <div className="font-mono text-muted-foreground truncate" title={diagnostic.file}>
+                            {diagnostic.file.split("/").pop()}
+                            {diagnostic.line !== null && `:${diagnostic.line}`}
+                            {diagnostic.column !== null && `:${diagnostic.column}`}
+                          </div>
+                        )}
+                      </li>
+                    ))}
+                  </ul>
+                </ScrollArea>
+              </div>
+            )}
+
             {/* Stream Logs */}
             {buildState.status === "streaming" && (
               <div className="flex flex-col gap-2">
@@ -410,7 +485,10 @@ export function BuildAndRun() {
         {/* Right side - Simulator Stream (full height) */}
          <div className="flex-1 flex items-center justify-center min-w-0 min-h-0 overflow-hidden bg-black/20 rounded-xl">
            {buildState.status === "streaming" ? (
-             <StreamViewer udid={(buildState as { udid: string }).udid} />
+             <StreamViewer
+               udid={(buildState as { udid: string }).udid}
+               simulator={simulators.find((sim) => sim.udid === (buildState as { udid: string }).udid)}
+             />
            ) : (
              <div className="flex flex-col items-center justify-center gap-4 text-muted-foreground">
                <div className="w-[200px] h-[400px] border-2 border-dashed border-border rounded-3xl flex items-center justify-center">
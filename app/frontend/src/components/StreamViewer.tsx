@@ -1,11 +1,19 @@
 import { useEffect, useRef, useState, useCallback, type MouseEvent } from "react";
 import { Loader2 } from "lucide-react";
-import { api } from "@/lib/api";
+import { api, type DeviceClass, type Simulator } from "@/lib/api";
 
 interface StreamViewerProps {
   udid: string;
+  simulator?: Simulator;
 }
 
+// Canvas size (in points) shown until the first frame arrives, so the layout
+// doesn't jump when an iPad stream starts
+const PLACEHOLDER_SIZES: Partial<Record<DeviceClass, { width: number; height: number }>> = {
+  phone: { width: 393, height: 852 },
+  tablet: { width: 820, height: 1180 },
+};
+
 interface TouchPoint {
   x: number;
   y: number;
@@ -17,7 +25,10 @@ interface TouchPoint {
  * Frames are received as base64-encoded JPEGs via IPC events.
  * Touch events are sent via IPC to the main process.
  */
-export function StreamViewer({ udid }: StreamViewerProps) {
+export function StreamViewer({ udid, simulator }: StreamViewerProps) {
+  const deviceClass = simulator?.deviceClass;
+  // Simulators without touch input (e.g. visionOS) are view-only
+  const touchEnabled = simulator?.capabilities.touch ?? true;
   const [isLoading, setIsLoading] = useState(true);
   const [dimensions, setDimensions] = useState("");
   const [isPressing, setIsPressing] = useState(false);
@@ -145,6 +156,16 @@ export function StreamViewer({ udid }: StreamViewerProps) {
     [isPressing, getNormalizedCoordinates, sendTouch]
   );
 
+  // Size the canvas for the device class until frames define its size
+  useEffect(() => {
+    const canvas = canvasRef.current;
+    const placeholder = PLACEHOLDER_SIZES[deviceClass ?? "phone"];
+    if (canvas && placeholder && frameCountRef.current === 0) {
+      canvas.width = placeholder.width;
+      canvas.height = placeholder.height;
+    }
+  }, [deviceClass]);
+
   // Subscribe to frame events from IPC
   useEffect(() => {
     const canvas = canvasRef.current;
@@ -191,7 +212,7 @@ export function StreamViewer({ udid }: StreamViewerProps) {
         }
       };
 
-      img.src = `data:image/jpeg;base64,${frame.frame}`;
+      img.src = `data:image/${frame.format || "jpeg"};base64,${frame.frame}`;
     });
 
     return () => {
@@ -238,11 +259,11 @@ export function StreamViewer({ udid }: StreamViewerProps) {
     <div
       ref={wrapperRef}
       className="relative h-full w-full flex items-center justify-center overflow-hidden"
-      onMouseDown={handleMouseDown}
-      onMouseMove={handleMouseMove}
-      onMouseUp={handleMouseUp}
-      onMouseLeave={handleMouseLeave}
-      style={{ cursor: "pointer" }}
+      onMouseDown={touchEnabled ? handleMouseDown : undefined}
+      onMouseMove={touchEnabled ? handleMouseMove : undefined}
+      onMouseUp={touchEnabled ? handleMouseUp : undefined}
+      onMouseLeave={touchEnabled ? handleMouseLeave : undefined}
+      style={{ cursor: touchEnabled ? "pointer" : "default" }}
     >
       {isLoading && (
         <div className="absolute inset-0 flex items-center justify-center bg-black/30 rounded-xl z-10">
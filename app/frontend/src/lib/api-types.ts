@@ -0,0 +1,1681 @@
+// This file is generated by `pnpm generate-types` from app/src/shared/ipc-types.ts.
+// Do not edit it by hand.
+
+// IPC Channel Type Definitions
+// Shared between main process and renderer (via preload)
+// Backend services import these instead of declaring their own, so the compiler checks what the
+// server sends against them. frontend/src/lib/api-types.ts is generated from this file.
+
+// ============================================================================
+// Projects API
+// ============================================================================
+
+export type ProjectType = 'xcode' | 'android';
+export type Platform = 'ios' | 'android';
+
+export interface Project {
+  path: string;
+  name: string;
+  type: ProjectType;
+  platforms: Platform[];
+  valid: boolean;
+}
+
+export interface ProjectRecord {
+  id: number;
+  path: string;
+  name: string;
+  platforms: Platform[];
+  last_opened_at: string | null;
+  created_at: string | null;
+}
+
+export interface ValidateProjectRequest {
+  path: string;
+}
+
+export interface ValidateProjectResponse {
+  project: Project | null;
+  error?: string;
+}
+
+export interface GetRecentProjectsRequest {
+  query?: string;
+  limit?: number;
+}
+
+export type GetRecentProjectsResponse = ProjectRecord[];
+
+export interface UnifiedProject {
+  id: number;
+  // Identifies the project to every instance sharing its database
+  uuid: string;
+  name: string;
+  xcode_path: string | null;
+  android_path: string | null;
+  notes: string | null;
+  last_opened_at: string | null;
+  created_at: string | null;
+}
+
+export interface UpdateProjectNotesRequest {
+  // Markdown; null or an empty string clears the notes
+  notes: string | null;
+}
+
+export interface ProjectNotes {
+  notes: string | null;
+  notesHtml: string | null;
+}
+
+export interface ProjectReadme {
+  // Where the README was found
+  path: string;
+  markdown: string;
+  html: string;
+}
+
+export interface CreateProjectRequest {
+  name: string;
+  xcodePath?: string;
+  androidPath?: string;
+  directory?: string;
+}
+
+export interface CreateProjectResponse {
+  project?: UnifiedProject;
+  error?: string;
+}
+
+export interface XcodeValidationResult {
+  valid: boolean;
+  path: string | null;
+  type: 'workspace' | 'project' | null;
+  error?: string;
+}
+
+export interface AndroidValidationResult {
+  valid: boolean;
+  path: string | null;
+  error?: string;
+}
+
+// ============================================================================
+// Xcode API
+// ============================================================================
+
+export type XcodeProjectType = 'project' | 'workspace';
+
+export interface XcodeProject {
+  path: string;
+  projectType: XcodeProjectType;
+  schemes: string[];
+  targets: string[];
+  configurations: string[];
+  // The scheme most likely to build and run the app: a shared scheme building an .app,
+  // preferably named after the project. Null when there are no schemes.
+  suggestedScheme: string | null;
+}
+
+export interface BuildProduct {
+  name: string;
+  path: string;
+}
+
+export interface DiscoverProjectRequest {
+  path: string;
+}
+
+export type DiscoverProjectResponse = XcodeProject;
+
+export type DestinationPresetId =
+  | 'ios-simulator'
+  | 'visionos-simulator'
+  | 'tvos-simulator'
+  | 'watchos-simulator'
+  | 'macos'
+  | 'mac-catalyst';
+
+export interface DestinationPreset {
+  id: DestinationPresetId;
+  name: string;
+  // Passed as `-sdk`; left out when the destination selects it, like Mac Catalyst's variant
+  sdk?: string;
+  destination: string;
+}
+
+export interface ProjectDestinationResponse {
+  destination: DestinationPresetId;
+  // When the destination was last stored, null if it never was
+  updatedAt: string | null;
+}
+
+export interface SetProjectDestinationRequest {
+  destination: DestinationPresetId;
+  // `updatedAt` of the destination this change was based on. When it no longer matches,
+  // the request fails with 409 and the current destination. Omit to overwrite unconditionally.
+  expectedUpdatedAt?: string | null;
+}
+
+// Build setup last used for a project, restored the next time it's built
+export interface ProjectSettings {
+  scheme: string | null;
+  configuration: string | null;
+  simulatorUdid: string | null;
+  // Environment variables the app is launched with
+  environment: Record<string, string>;
+  // When the settings were last stored, null if they never were
+  updatedAt: string | null;
+}
+
+// Fields that are left out keep their value; null clears one
+export interface UpdateProjectSettingsRequest {
+  scheme?: string | null;
+  configuration?: string | null;
+  simulatorUdid?: string | null;
+  // Replaces all the environment variables
+  environment?: Record<string, string>;
+  // Works like `expectedUpdatedAt` in SetProjectDestinationRequest
+  expectedUpdatedAt?: string | null;
+}
+
+// Body of a 409 response: the error and the current state of the resource
+export type ConflictResponse<T> = T & { error: string };
+
+// An invalid field of a request, e.g. `{ path: 'touches[0].x', message: 'must be from 0 to 1' }`
+export interface FieldError {
+  // Dotted path to the field, with array indexes in brackets; empty for the payload itself
+  path: string;
+  message: string;
+}
+
+// Body of a 400 response to an invalid request; `error` joins the fields' paths and messages
+export interface ValidationErrorResponse {
+  error: string;
+  code: 'invalid_request';
+  fields: FieldError[];
+}
+
+// Which output lines a build subscriber receives:
+// - all:      every line
+// - warnings: warnings and errors
+// - errors:   errors only
+// - phases:   build steps (compile, link, sign...) and target headers, plus errors
+export type BuildOutputLevel = 'all' | 'warnings' | 'errors' | 'phases';
+
+export interface BuildStreamRequest {
+  path: string;
+  // Defaults to the project's suggested scheme
+  scheme?: string;
+  // Defaults to the project's stored destination, then 'ios-simulator'
+  destination?: DestinationPresetId;
+  // Defaults to 'all'. The build log always has every line.
+  output?: BuildOutputLevel;
+  // A start sent again with the same key follows the first one's build instead of starting another
+  idempotencyKey?: string;
+}
+
+// Version of the build and stream event payloads, bumped on incompatible changes
+export const EVENT_SCHEMA_VERSION = 1;
+
+export interface CancelBuildRequest {
+  // From the build's events
+  buildId: string;
+}
+
+export type BuildDiagnosticSeverity = 'error' | 'warning' | 'note';
+
+// A compiler or tool diagnostic parsed from the build output
+export interface BuildDiagnostic {
+  // Null for diagnostics not tied to a file, e.g. from the linker
+  file: string | null;
+  line: number | null;
+  column: number | null;
+  severity: BuildDiagnosticSeverity;
+  message: string;
+}
+
+export type BuildEventName = 'build.started' | 'build.output' | 'build.completed' | 'build.error';
+
+export interface BuildEvent {
+  event: BuildEventName;
+  schemaVersion: number;
+  /** @deprecated Switch on `event` instead */
+  type: 'started' | 'output' | 'completed' | 'error';
+  buildId?: string;
+  scheme?: string;
+  projectPath?: string;
+  line?: string;
+  success?: boolean;
+  buildDir?: string;
+  products?: BuildProduct[];
+  durationMs?: number;
+  message?: string;
+  // On completed events, the build's distinct diagnostics in the order they were printed
+  diagnostics?: BuildDiagnostic[];
+  // On error events of builds stopped with POST /api/xcode/build/cancel
+  cancelled?: boolean;
+  // Estimated fraction done (0-1) on started, output and completed events; null on output
+  // until there's a build plan or a past build of the scheme to estimate from
+  progress?: number | null;
+  // On error events, when the build was refused because the host is short on resources
+  resourcesExhausted?: ResourcesExhaustedResponse;
+}
+
+export interface BuildProductSize {
+  name: string;
+  sizeBytes: number;
+  // SHA-256 of the product as hex, see the provenance docs for how bundles are hashed; absent for
+  // builds recorded before checksums were
+  sha256?: string;
+}
+
+export interface BuildMachine {
+  hostname: string;
+  // Node's process.platform, like `darwin`
+  platform: string;
+  osRelease: string;
+  arch: string;
+  plasmaVersion: string | null;
+}
+
+export interface BuildGitInfo {
+  commit: string;
+  // Null for a detached HEAD
+  branch: string | null;
+  // Whether tracked files had uncommitted changes
+  dirty: boolean;
+}
+
+// What a build ran with; fields are null when they couldn't be read
+export interface BuildEnvironment {
+  machine: BuildMachine | null;
+  xcodeVersion: string | null;
+  // Null outside a git repository
+  git: BuildGitInfo | null;
+}
+
+export interface BuildProvenance extends BuildEnvironment {
+  buildId: string;
+  projectPath: string;
+  scheme: string;
+  configuration: string;
+  destination: DestinationPresetId;
+  status: BuildStatus;
+  artifacts: BuildProductSize[];
+  startedAt: string;
+  finishedAt: string;
+}
+
+export type BuildStatus = 'succeeded' | 'failed' | 'cancelled';
+
+export interface BuildRecord {
+  id: string;
+  // Null when the project was built without being added to Plasma
+  projectId: number | null;
+  projectPath: string;
+  scheme: string;
+  configuration: string;
+  destination: DestinationPresetId;
+  status: BuildStatus;
+  success: boolean;
+  durationMs: number;
+  warnings: number;
+  errors: number;
+  products: BuildProductSize[];
+  // Where the build's log can be downloaded
+  logUrl: string;
+  startedAt: string;
+  finishedAt: string;
+}
+
+export interface NumberDelta {
+  a: number;
+  b: number;
+  // b - a
+  delta: number;
+}
+
+export interface BuildComparison {
+  a: BuildRecord;
+  b: BuildRecord;
+  durationMs: NumberDelta;
+  warnings: NumberDelta;
+  errors: NumberDelta;
+  totalSizeBytes: NumberDelta;
+  // Products of either build; sizes are null for products only one of them has
+  products: Array<{ name: string; aBytes: number | null; bBytes: number | null; deltaBytes: number }>;
+  changedSettings: Array<{ key: string; a: string | null; b: string | null }>;
+}
+
+export interface GetLaunchableProductsRequest {
+  buildDir: string;
+}
+
+export type GetLaunchableProductsResponse = BuildProduct[];
+
+// ============================================================================
+// Simulator API
+// ============================================================================
+
+export type DeviceClass = 'phone' | 'tablet' | 'watch' | 'tv' | 'vision' | 'unknown';
+
+// What the frontend can offer for a simulator
+export interface SimulatorCapabilities {
+  // Frames can be streamed (for visionOS, the 2D window capture where simulator-server supports it)
+  stream: boolean;
+  // Touch, tap and swipe input
+  touch: boolean;
+  // Rotation between portrait and landscape
+  rotate: boolean;
+}
+
+export interface Simulator {
+  udid: string;
+  name: string;
+  state: string;
+  runtime: string;
+  deviceType: string | null;
+  deviceClass: DeviceClass;
+  capabilities: SimulatorCapabilities;
+}
+
+export type ListSimulatorsResponse = Simulator[];
+
+// Commands a simulator-server binary can support; older binaries support fewer
+export type SessionCapability = 'touch' | 'key' | 'rotate' | 'button' | 'audio';
+
+export type SessionCapabilities = Record<SessionCapability, boolean>;
+
+// A simulator's running simulator-server session
+export interface SessionInfo {
+  udid: string;
+  fps: number;
+  quality: number;
+  // 1 for binaries from before capabilities were reported
+  protocolVersion: number;
+  capabilities: SessionCapabilities;
+  // Null until conditions are applied to the simulator
+  conditions: AppliedConditions | null;
+}
+
+export interface SimulatorDeviceType {
+  identifier: string;
+  name: string;
+  // e.g. 'iPhone', 'iPad', 'Apple Watch'
+  productFamily: string | null;
+  deviceClass: DeviceClass;
+}
+
+export interface SimulatorRuntime {
+  identifier: string;
+  name: string;
+  version: string;
+  platform: string | null;
+  // False when the runtime is installed but can't be used, e.g. after an Xcode update
+  available: boolean;
+  // Identifiers of the device types it runs on
+  supportedDeviceTypes: string[];
+}
+
+export interface SimulatorDeviceTypes {
+  deviceTypes: SimulatorDeviceType[];
+  runtimes: SimulatorRuntime[];
+}
+
+export interface CreateSimulatorRequest {
+  name: string;
+  // Identifier or name of a device type, e.g. 'iPhone 16 Pro'
+  deviceType: string;
+  // Identifier or name of a runtime; defaults to the newest one supporting the device type
+  runtime?: string;
+}
+
+export interface CreateSimulatorResponse {
+  udid: string;
+}
+
+export interface EraseSimulatorRequest {
+  udid: string;
+  // Clone the simulator first, so the erase can be undone for a while
+  snapshot?: boolean;
+}
+
+export interface EraseSimulatorResponse {
+  success: true;
+  // Null without a snapshot
+  undo: UndoInfo | null;
+}
+
+export interface UninstallAppRequest {
+  udid: string;
+  bundleId: string;
+  // Copy the app and its data first, so the uninstall can be undone for a while
+  snapshot?: boolean;
+}
+
+export interface UninstallAppResponse {
+  success: true;
+  undo: UndoInfo | null;
+}
+
+export type UndoAction = 'erase' | 'uninstall';
+
+// A snapshot taken before erasing a simulator or uninstalling an app
+export interface UndoInfo {
+  id: string;
+  action: UndoAction;
+  udid: string;
+  // The uninstalled app's, null for erases
+  bundleId: string | null;
+  createdAt: string;
+  // Undoing fails with 404 after this
+  expiresAt: string;
+}
+
+export interface UndoResponse {
+  // The restored simulator; an erased one comes back as its clone, under a new UDID
+  udid: string;
+}
+
+export type SimulatorChangeKind = 'added' | 'removed' | 'booted' | 'shutdown' | 'state_changed';
+
+// Pushed as `simulator:device:change` when the simulator list changes
+export interface SimulatorChange {
+  kind: SimulatorChangeKind;
+  udid: string;
+  // The simulator as it is now; as it was last seen for `removed`
+  simulator: Simulator;
+  // Null for `added`
+  previousState: string | null;
+}
+
+export interface LaunchAppRequest {
+  udid: string;
+  appPath: string;
+  bundleId?: string;
+  // Run the app in this language (e.g. `fr`, `pt-BR`) and region format locale (e.g. `fr_FR`)
+  // for this launch only, without changing the simulator's settings
+  language?: string;
+  locale?: string;
+  // What to do when a different app has the bundle ID, e.g. another variant: install over it
+  // (the default), fail with 409, or uninstall it first. See BundleIdConflict.
+  onBundleIdConflict?: BundleIdConflictPolicy;
+}
+
+export type BundleIdConflictPolicy = 'replace' | 'fail' | 'uninstall';
+
+export interface BundleIdConflict {
+  bundleId: string;
+  // The app that was installed
+  installed: { name: string | null; teamId: string | null; version: string | null };
+  // How it differs from the new build, e.g. another team or display name
+  reasons: string[];
+}
+
+export interface LaunchTimings {
+  boot?: number;
+  install?: number;
+  resolveBundleId?: number;
+  verify?: number;
+  launch?: number;
+  total: number;
+}
+
+// What the check after installing found; a launch with problems fails before launching the app
+export interface InstallVerification {
+  bundleId: string;
+  // Whether `simctl listapps` lists the bundle ID
+  installed: boolean;
+  installedPath: string | null;
+  // CFBundleShortVersionString of the build, and of the installed app
+  expectedVersion: string | null;
+  installedVersion: string | null;
+  // Whether the installed app has its executable
+  launchable: boolean;
+  problems: string[];
+}
+
+export interface LaunchAppResponse {
+  message: string;
+  bundleId: string;
+  timings: LaunchTimings;
+  // Identifies the launch's console output at /api/simulator/launches/:launchId/console; missing
+  // when the device backend launched the app without a console
+  launchId?: string;
+  // Missing when the device backend installed the app, e.g. fake devices
+  verification?: InstallVerification;
+  // Set when the install replaced or uninstalled a different app with the bundle ID
+  bundleIdConflict?: BundleIdConflict & { resolution: BundleIdConflictPolicy };
+}
+
+// A line the app wrote to stdout or stderr, or its exit, on a launch's console
+export interface AppConsoleEvent {
+  event: 'console.output' | 'console.exit';
+  schemaVersion: number;
+  launchId: string;
+  udid: string;
+  bundleId: string;
+  type: 'output' | 'exit';
+  timestamp: string;
+  // For output; stdout and stderr share the console, so they can't be told apart
+  line?: string;
+  // For exit; null when simctl was killed
+  exitCode?: number | null;
+}
+
+export interface LaunchTimingStats {
+  device_type: string;
+  launches: number;
+  avg_boot_ms: number | null;
+  avg_install_ms: number | null;
+  avg_launch_ms: number | null;
+  avg_first_frame_ms: number | null;
+  avg_total_ms: number | null;
+  max_total_ms: number | null;
+  last_launch_at: string;
+}
+
+export interface TouchEvent {
+  udid: string;
+  type: 'began' | 'moved' | 'ended';
+  touches: Array<{ x: number; y: number }>;
+}
+
+export interface TapRequest {
+  udid: string;
+  x: number;
+  y: number;
+  screenWidth: number;
+  screenHeight: number;
+}
+
+export interface SwipeRequest {
+  udid: string;
+  startX: number;
+  startY: number;
+  endX: number;
+  endY: number;
+  screenWidth: number;
+  screenHeight: number;
+  duration?: number;
+}
+
+export interface TypeTextRequest {
+  udid: string;
+  // Printable US keyboard characters, newlines and tabs
+  text: string;
+}
+
+export type SimulatorOrientation = 'portrait' | 'landscape-left' | 'landscape-right' | 'upside-down';
+
+export interface SetOrientationRequest {
+  udid: string;
+  orientation: SimulatorOrientation;
+}
+
+export type Appearance = 'light' | 'dark';
+
+export interface AppearanceResponse {
+  // Null for simulators without appearances, e.g. Apple TV
+  appearance: Appearance | null;
+}
+
+// What `simctl status_bar override` takes; parts left out keep their current value
+export interface StatusBarOverrides {
+  // Shown as is, e.g. `9:41`
+  time?: string;
+  dataNetwork?: 'hide' | 'wifi' | '3g' | '4g' | 'lte' | 'lte-a' | 'lte+' | '5g' | '5g+' | '5g-uwb' | '5g-uc';
+  wifiMode?: 'searching' | 'failed' | 'active';
+  // 0 to 3
+  wifiBars?: number;
+  cellularMode?: 'notSupported' | 'searching' | 'failed' | 'active';
+  // 0 to 4
+  cellularBars?: number;
+  operatorName?: string;
+  batteryState?: 'charging' | 'charged' | 'discharging';
+  // 0 to 100
+  batteryLevel?: number;
+}
+
+export type ContentSizeCategory =
+  | 'extra-small'
+  | 'small'
+  | 'medium'
+  | 'large'
+  | 'extra-large'
+  | 'extra-extra-large'
+  | 'extra-extra-extra-large'
+  | 'accessibility-medium'
+  | 'accessibility-large'
+  | 'accessibility-extra-large'
+  | 'accessibility-extra-extra-large'
+  | 'accessibility-extra-extra-extra-large';
+
+export interface AccessibilitySettings {
+  reduceMotion: boolean;
+  reduceTransparency: boolean;
+  boldText: boolean;
+  increaseContrast: boolean;
+  // The Dynamic Type size; null for simulators without one, e.g. Apple TV
+  contentSize: ContentSizeCategory | null;
+}
+
+// Settings left out keep their current value; Simulator has no VoiceOver, so `voiceOver` is rejected
+export type SetAccessibilityRequest = Partial<Omit<AccessibilitySettings, 'contentSize'>> & {
+  contentSize?: ContentSizeCategory;
+  voiceOver?: boolean;
+};
+
+export type SettingsPaneId =
+  | 'app'
+  | 'notifications'
+  | 'privacy'
+  | 'privacy-photos'
+  | 'privacy-location'
+  | 'privacy-contacts'
+  | 'privacy-calendars'
+  | 'wifi'
+  | 'general'
+  | 'language'
+  | 'accessibility'
+  | 'developer';
+
+export interface SettingsPane {
+  id: SettingsPaneId;
+  name: string;
+  // Panes of an app, like its notification settings
+  requiresBundleId: boolean;
+}
+
+export interface OpenSettingsPaneRequest {
+  pane: SettingsPaneId;
+  bundleId?: string;
+}
+
+export interface OpenSettingsPaneResponse {
+  // The `App-prefs:` URL that was opened
+  url: string;
+}
+
+// Connection shown on the status bar; simulators use the Mac's network
+export type NetworkCondition = 'wifi' | 'lte' | '3g' | 'offline';
+
+export type ThermalState = 'nominal' | 'fair' | 'serious' | 'critical';
+
+export interface DeviceConditions {
+  network: NetworkCondition;
+  thermalState: ThermalState;
+  lowPower: boolean;
+  reduceMotion: boolean;
+  reduceTransparency: boolean;
+}
+
+export type ConditionProfileId = 'default' | 'worst-case' | 'poor-network' | 'offline' | 'low-power' | 'reduced-motion';
+
+export interface ConditionProfile {
+  id: ConditionProfileId;
+  name: string;
+  description: string;
+  conditions: DeviceConditions;
+}
+
+// 'applied' changes the simulator, 'status_bar' is only shown on its status bar, and
+// 'unsupported' is only recorded
+export type ConditionEffect = 'applied' | 'status_bar' | 'unsupported';
+
+export interface AppliedConditions {
+  // Null for conditions applied without a profile
+  profile: ConditionProfileId | null;
+  conditions: DeviceConditions;
+  effects: Record<keyof DeviceConditions, ConditionEffect>;
+  appliedAt: string;
+}
+
+// Either a profile, conditions, or a profile with some of its conditions replaced
+export interface ApplyConditionsRequest {
+  profile?: ConditionProfileId;
+  conditions?: Partial<DeviceConditions>;
+}
+
+export interface LocationWaypoint {
+  latitude: number;
+  longitude: number;
+}
+
+export interface SetLocationRequest extends LocationWaypoint {
+  udid: string;
+}
+
+export interface LocationRouteRequest {
+  udid: string;
+  // Contents of a GPX file; its track points, route points or waypoints are followed in order
+  gpx: string;
+  // Meters per second, 20 by default
+  speed?: number;
+}
+
+export interface LocationRouteResponse {
+  waypoints: number;
+  speed: number;
+  distanceMeters: number;
+  // How long the route takes at `speed`
+  durationMs: number;
+}
+
+export type PrivacyAction = 'grant' | 'revoke' | 'reset';
+
+export type PrivacyService =
+  | 'all'
+  | 'calendar'
+  | 'contacts-limited'
+  | 'contacts'
+  | 'location'
+  | 'location-always'
+  | 'photos-add'
+  | 'photos'
+  | 'media-library'
+  | 'microphone'
+  | 'motion'
+  | 'reminders'
+  | 'siri';
+
+export interface PrivacyRequest {
+  udid: string;
+  action: PrivacyAction;
+  service: PrivacyService;
+  // Required to grant or revoke; resets without one apply to every app
+  bundleId?: string;
+}
+
+export type NamedKey =
+  | 'return'
+  | 'escape'
+  | 'delete'
+  | 'tab'
+  | 'space'
+  | 'home'
+  | 'pageUp'
+  | 'forwardDelete'
+  | 'end'
+  | 'pageDown'
+  | 'right'
+  | 'left'
+  | 'down'
+  | 'up';
+
+export interface KeyRequest {
+  udid: string;
+  // A key name, or a HID keyboard usage code for any other key
+  key: NamedKey | number;
+}
+
+export type HardwareButton = 'home' | 'lock' | 'volume-up' | 'volume-down' | 'siri';
+
+export interface ButtonRequest {
+  udid: string;
+  button: HardwareButton;
+}
+
+// `face` is Face ID, `touch` is Touch ID
+export type BiometricSensor = 'face' | 'touch';
+export type BiometricAction = 'match' | 'nonmatch';
+
+export interface BiometricRequest {
+  action: BiometricAction;
+  // Defaults to `face`
+  sensor?: BiometricSensor;
+}
+
+export interface BiometricEnrollmentRequest {
+  enrolled: boolean;
+}
+
+export interface OpenUrlRequest {
+  // A web URL, to test universal links, or a custom scheme URL
+  url: string;
+}
+
+export interface UniversalLinksCheckRequest {
+  // Built .app whose entitlements to check
+  appPath?: string;
+  // `TEAMID.bundle.id`, instead of the build's application identifier
+  appId?: string;
+  // e.g. `["applinks:example.com"]`, instead of the build's associated domains
+  associatedDomains?: string[];
+}
+
+export interface UniversalLinkDomainReport {
+  // As in the entitlement, e.g. `applinks:example.com?mode=developer`
+  entry: string;
+  domain: string;
+  developerMode: boolean;
+  // Where the association file was fetched from
+  url: string;
+  // HTTP status, null when the request failed
+  status: number | null;
+  // Whether the file routes links to the app
+  ok: boolean;
+  errors: string[];
+  warnings: string[];
+  // Paths (or components) the file routes to the app; `NOT ` marks exclusions
+  paths: string[];
+}
+
+export interface UniversalLinksReport {
+  appId: string | null;
+  associatedDomains: string[];
+  // Problems with the app rather than a domain
+  issues: string[];
+  domains: UniversalLinkDomainReport[];
+}
+
+// Messages on the `/api/simulator/ws` control socket. `udid` defaults to the socket's `?udid=`,
+// and a message with an `id` is acknowledged with a SimulatorControlReply.
+interface SimulatorControlBase {
+  id?: string | number;
+  udid?: string;
+}
+
+export type SimulatorControlMessage =
+  | (SimulatorControlBase & { type: 'touch'; phase: TouchEvent['type']; touches: TouchEvent['touches'] })
+  | (SimulatorControlBase & { type: 'tap' } & Omit<TapRequest, 'udid'>)
+  | (SimulatorControlBase & { type: 'swipe' } & Omit<SwipeRequest, 'udid'>)
+  // `code` is a HID keyboard usage code, e.g. 0x28 for return
+  | (SimulatorControlBase & { type: 'key'; code: number; direction: 'down' | 'up' });
+
+export interface SimulatorControlReply {
+  id: string | number | null;
+  ok: boolean;
+  error: string | null;
+  // The invalid fields, when the message was invalid
+  fields?: FieldError[];
+}
+
+export interface StartStreamRequest {
+  udid: string;
+  fps?: number;
+  quality?: number;
+  // Keep the host Mac from sleeping until the stream stops
+  keepAwake?: boolean;
+  // Watermark template drawn into this stream's frames, e.g. `CONFIDENTIAL {user} {timestamp}`,
+  // where `{user}` is who the connection belongs to. Ignored when the server has one configured.
+  watermark?: string;
+  // Record the stream's frames until it stops (see RecordingSource)
+  record?: boolean;
+  // Draw circles where the simulator is touched into its frames, for every viewer and stream
+  // recording. Defaults to the server's setting.
+  showTouches?: boolean;
+  // Send frames scaled by this fraction, above 0 and at most 1, as PNG
+  scale?: number;
+  // Send frames at most this many pixels wide
+  maxWidth?: number;
+  // Send smaller frames while this client can't keep up, see StreamScaleChange
+  adaptive?: boolean;
+}
+
+// Sent as `simulator:stream:scale` when adaptive scaling changes the size of a client's frames
+export interface StreamScaleChange {
+  udid: string;
+  // 1, 0.5 or 0.25 of the frame size asked for
+  factor: number;
+}
+
+export interface StreamFrame {
+  udid: string;
+  frame: string; // base64 encoded image
+  format?: 'jpeg' | 'png'; // defaults to jpeg
+}
+
+export type StreamLogEventName = 'log.info' | 'log.error' | 'log.debug' | 'log.frame';
+
+export interface StreamLogEvent {
+  // Increasing across all simulators; used to resume from a given event
+  id: number;
+  event: StreamLogEventName;
+  schemaVersion: number;
+  udid: string;
+  /** @deprecated Switch on `event` instead */
+  type: 'info' | 'error' | 'debug' | 'frame';
+  message?: string;
+  frameNumber?: number;
+  timestamp: string;
+}
+
+export interface SessionRestartEvent {
+  udid: string;
+  status: 'scheduled' | 'restarted' | 'failed' | 'gave_up';
+  attempt: number;
+  maxAttempts: number;
+  delayMs?: number;
+  error?: string;
+}
+
+export interface SessionCommandRecord {
+  udid: string;
+  command: string;
+  sentAt: string;
+  // Set when writing to simulator-server's stdin failed
+  error?: string;
+}
+
+export interface AppResourceSample {
+  udid: string;
+  bundleId: string;
+  pid: number;
+  // Percentage of one CPU core, so it can exceed 100 for multithreaded work
+  cpuPercent: number;
+  // Resident memory
+  rssBytes: number;
+  timestamp: string;
+}
+
+// Vision's recognition levels: `fast` skips language correction and is less precise
+export type OcrLevel = 'fast' | 'accurate';
+
+export interface OcrRequest {
+  // Defaults to 'accurate'
+  level?: OcrLevel;
+  // BCP 47 codes in order of preference, e.g. ['en-US', 'fr-FR']; Vision's default otherwise
+  languages?: string[];
+  // Text to look for, ignoring case; the response then says whether it was found
+  find?: string;
+}
+
+export interface OcrObservation {
+  text: string;
+  // 0-1
+  confidence: number;
+  // Normalized to the screen (0-1), origin at the top left, like tap coordinates
+  bounds: { x: number; y: number; width: number; height: number };
+}
+
+export interface OcrResponse {
+  udid: string;
+  // Top to bottom, then left to right
+  observations: OcrObservation[];
+  // With `find`: whether the text is on screen, and where
+  found?: boolean;
+  matches?: OcrObservation[];
+}
+
+export interface PixelColor {
+  // Requested point, normalized to the screen with the origin at the top left
+  x: number;
+  y: number;
+  // The pixel it falls on, in screenshot pixels
+  pixelX: number;
+  pixelY: number;
+  // Pixels averaged around it in each direction, and how many were averaged
+  radius: number;
+  samples: number;
+  // 0-255, in the screenshot's color space
+  r: number;
+  g: number;
+  b: number;
+  a: number;
+  // #rrggbb
+  hex: string;
+}
+
+export interface SyslogEntry {
+  udid: string;
+  timestamp: string;
+  process: string;
+  pid: number | null;
+  subsystem: string | null;
+  category: string | null;
+  level: 'default' | 'info' | 'debug' | 'error' | 'fault';
+  message: string;
+}
+
+export type LogNoiseProfileId = 'system-daemons' | 'framework-chatter' | 'apple-debug';
+
+export interface LogNoiseProfile {
+  id: LogNoiseProfileId;
+  name: string;
+  description: string;
+}
+
+export interface ListNoiseProfilesResponse {
+  profiles: LogNoiseProfile[];
+  // Profiles applied when a subscription doesn't choose
+  defaults: LogNoiseProfileId[];
+}
+
+export type RuntimeIssueKind = 'auto_layout' | 'main_thread_checker' | 'swiftui' | 'core_data' | 'memory';
+
+export interface RuntimeIssue {
+  id: number;
+  udid: string;
+  // App launched on the simulator when the issue was found
+  bundleId: string | null;
+  kind: RuntimeIssueKind;
+  // First line of the log message
+  message: string;
+  // Occurrences of the same message since the launch
+  count: number;
+  firstSeenAt: string;
+  lastSeenAt: string;
+}
+
+export interface FramePacing {
+  udid: string;
+  // Start of the measurement: the stream start or the last reset
+  since: string;
+  targetFps: number;
+  // Frames whose content changed
+  changedFrames: number;
+  // Time spent animating, i.e. with changed frames less than 250 ms apart
+  animatingMs: number;
+  // Changed frames per second while animating, null before anything animated
+  fps: number | null;
+  // Changed frames that arrived more than 1.5 frame intervals late while animating
+  hitches: number;
+  // Milliseconds of lateness per second of animation
+  hitchTimeRatio: number | null;
+  worstIntervalMs: number;
+}
+
+// Maps normalized stream coordinates (0-1) to simulator points:
+// point = normalized * scale + offset
+export interface TapTransform {
+  scaleX: number;
+  scaleY: number;
+  offsetX: number;
+  offsetY: number;
+}
+
+export interface CalibrationProbe {
+  label: string | null;
+  // Normalized position that was probed
+  x: number;
+  y: number;
+  // Whether the probe landed on the expected element
+  hit: boolean;
+}
+
+export interface TapCalibration {
+  deviceType: string;
+  transform: TapTransform;
+  probes: CalibrationProbe[];
+  calibratedAt: string;
+}
+
+// ============================================================================
+// macOS API
+// ============================================================================
+
+// Logs and frames of locally launched Mac apps use this in place of a simulator UDID
+export const MAC_DEVICE_ID = 'macos';
+
+export interface LaunchMacAppRequest {
+  appPath: string;
+  capture?: boolean;
+  captureFps?: number;
+}
+
+export interface LaunchMacAppResponse {
+  message: string;
+  bundleId: string;
+  pid: number;
+}
+
+// ============================================================================
+// Android API
+// ============================================================================
+
+export interface AndroidEmulator {
+  // AVD name, e.g. `Pixel_8_API_34`
+  name: string;
+  // adb serial while it runs, e.g. `emulator-5554`
+  serial: string | null;
+  // `Booting` until adb can talk to it
+  state: 'Booted' | 'Booting' | 'Shutdown';
+}
+
+export interface BootEmulatorRequest {
+  name: string;
+}
+
+export interface InstallApkRequest {
+  serial: string;
+  apkPath: string;
+}
+
+export interface LaunchAndroidAppRequest {
+  serial: string;
+  // e.g. `com.example.app`
+  packageName: string;
+  // Installed first when given
+  apkPath?: string;
+  // e.g. `.MainActivity`, the package's launcher activity when not given
+  activity?: string;
+}
+
+export interface GradleBuildRequest {
+  // The Android project, or its build.gradle(.kts)
+  path: string;
+  // Defaults to `assembleDebug`
+  task?: string;
+}
+
+export interface LaunchAndroidAppResponse {
+  message: string;
+  packageName: string;
+  // The started activity, e.g. `com.example.app/.MainActivity`
+  component: string;
+}
+
+// ============================================================================
+// Notifications API
+// ============================================================================
+
+export type NotificationKind =
+  | 'build_finished'
+  | 'simulator_crashed'
+  | 'disk_low'
+  | 'startup_recovery'
+  | 'stream_degraded';
+export type NotificationLevel = 'info' | 'warning' | 'error';
+
+export interface NotificationRecord {
+  id: number;
+  kind: NotificationKind;
+  level: NotificationLevel;
+  title: string;
+  message: string | null;
+  data: Record<string, unknown> | null;
+  read_at: string | null;
+  created_at: string;
+}
+
+export interface ListNotificationsResponse {
+  notifications: NotificationRecord[];
+  unreadCount: number;
+}
+
+// ============================================================================
+// Stream Health Alerts API
+// ============================================================================
+
+export type StreamAlertRule = 'fps_low' | 'frame_gap' | 'session_restarts';
+
+export interface StreamAlert {
+  rule: StreamAlertRule;
+  udid: string;
+  status: 'firing' | 'resolved';
+  message: string;
+  // Measured value and the threshold it crossed (fps, milliseconds or restart count)
+  value: number;
+  threshold: number;
+  since: string;
+}
+
+export interface ListStreamAlertsResponse {
+  alerts: StreamAlert[];
+}
+
+// ============================================================================
+// Project Notifiers API
+// ============================================================================
+
+export type NotifierKind = 'slack' | 'discord';
+export type NotifierEvent = 'build_succeeded' | 'build_failed';
+
+export interface NotifierRecord {
+  id: number;
+  project_id: number;
+  kind: NotifierKind;
+  // Name of the secret holding the webhook URL, which the API never returns
+  webhook_secret: string;
+  events: NotifierEvent[];
+  template: string | null;
+  enabled: boolean;
+  created_at: string;
+}
+
+export interface CreateNotifierRequest {
+  kind: NotifierKind;
+  webhookUrl: string;
+  events?: NotifierEvent[];
+  template?: string | null;
+  enabled?: boolean;
+}
+
+// ============================================================================
+// Plugins API
+// ============================================================================
+
+export interface PluginInfo {
+  name: string;
+  version: string | null;
+  description: string | null;
+  routes: Array<{ method: string; path: string }>;
+  buildHooks: Array<'build_started' | 'build_completed'>;
+  deviceActions: Array<{ id: string; title: string }>;
+}
+
+export interface RunPluginActionRequest {
+  udid: string;
+  params?: Record<string, unknown>;
+}
+
+// ============================================================================
+// Doctor API
+// ============================================================================
+
+export interface ConfigIssue {
+  // Environment variable the issue is about, e.g. PLASMA_PORT
+  setting: string;
+  message: string;
+  severity: 'error' | 'warning';
+}
+
+export interface DoctorResponse {
+  // False when there is at least one error
+  ok: boolean;
+  issues: ConfigIssue[];
+}
+
+// ============================================================================
+// Host resource guardrails
+// ============================================================================
+
+export interface HostResources {
+  freeMemoryBytes: number;
+  totalMemoryBytes: number;
+  // 1-minute load average
+  loadAverage: number;
+  cpuCount: number;
+  // Load average as a percentage of the CPU count
+  loadPercent: number;
+}
+
+// Returned with status 503 when a guard refuses to boot a simulator or start a build
+export interface ResourcesExhaustedResponse {
+  error: string;
+  code: 'resources_exhausted';
+  action: 'boot' | 'build';
+  reasons: string[];
+  readings: HostResources;
+  limits: { minFreeMemoryMb: number; maxLoadPercent: number };
+}
+
+// ============================================================================
+// Onboarding API
+// ============================================================================
+
+export type OnboardingStepId = 'xcode' | 'simulator_runtime' | 'helpers' | 'first_project' | 'first_build';
+
+export interface OnboardingStep {
+  id: OnboardingStepId;
+  title: string;
+  complete: boolean;
+  // What was found, or what to do when the step isn't complete
+  detail: string | null;
+}
+
+export interface OnboardingStatus {
+  steps: OnboardingStep[];
+  // Every step is complete
+  complete: boolean;
+  dismissed: boolean;
+  dismissedAt: string | null;
+}
+
+export interface SetOnboardingRequest {
+  dismissed: boolean;
+}
+
+// ============================================================================
+// Feature Flags API
+// ============================================================================
+
+export type FeatureFlagName = 'webrtc_streaming' | 'android' | 'proxy';
+
+export interface FeatureFlag {
+  name: FeatureFlagName;
+  description: string;
+  enabled: boolean;
+  // Where the value comes from: off by default, the config, or a value set through the API
+  source: 'default' | 'config' | 'setting';
+  // When the value was set through the API
+  updatedAt: string | null;
+}
+
+// Values of the backend settings, by key
+export type SettingValues = {
+  // When analytics were turned on
+  'analytics.enabled_at': string;
+  'onboarding.dismissed_at': string;
+  'secrets.salt': string;
+  'secrets.check': string;
+} & { [K in FeatureFlagName as `flags.${K}`]: boolean };
+
+export type SettingKey = keyof SettingValues;
+
+// Pushed as `settings:change` when a setting is set or cleared
+export interface SettingChange {
+  key: SettingKey;
+  // Null once cleared, and for private settings like the secrets salt
+  value: SettingValues[SettingKey] | null;
+  updatedAt: string | null;
+}
+
+export interface SetFeatureFlagRequest {
+  // Null clears the value set through the API, going back to the config
+  enabled: boolean | null;
+}
+
+// ============================================================================
+// Analytics API
+// ============================================================================
+
+export type UsageKind = 'build' | 'launch' | 'stream';
+
+export interface UsageSummary {
+  kind: UsageKind;
+  count: number;
+  averageDurationMs: number | null;
+}
+
+// Counts for one UTC day
+export interface UsageDay {
+  date: string;
+  build: number;
+  launch: number;
+  stream: number;
+}
+
+export interface AnalyticsUsage {
+  enabled: boolean;
+  enabledAt: string | null;
+  since: string;
+  totals: UsageSummary[];
+  daily: UsageDay[];
+}
+
+export interface SetAnalyticsRequest {
+  enabled: boolean;
+}
+
+// ============================================================================
+// Instance Export API
+// ============================================================================
+
+export interface ExportedNotifier {
+  kind: NotifierKind;
+  // Only set in bundles written by hand; exports never include plain webhook URLs
+  webhookUrl: string | null;
+  // Set when the export was given a passphrase
+  encryptedWebhookUrl: string | null;
+  events: NotifierEvent[];
+  template: string | null;
+  enabled: boolean;
+}
+
+export interface ExportedProjectSettings {
+  scheme: string | null;
+  configuration: string | null;
+  simulatorUdid: string | null;
+  // Environment variables can hold credentials, so like webhook URLs they're only exported
+  // encrypted. Plain values are only set in bundles written by hand.
+  environment: Record<string, string> | null;
+  // Set when the export was given a passphrase and the project has environment variables
+  encryptedEnvironment: string | null;
+}
+
+export interface ExportedProject {
+  name: string;
+  xcodePath: string | null;
+  androidPath: string | null;
+  destination: string | null;
+  // Null when the project has no stored settings
+  settings: ExportedProjectSettings | null;
+  notifiers: ExportedNotifier[];
+}
+
+export interface InstanceExport {
+  format: 'plasma-export';
+  version: number;
+  exportedAt: string;
+  projects: ExportedProject[];
+  settings: { key: string; value: string }[];
+}
+
+export interface ImportInstanceResponse {
+  // Projects added or updated
+  projects: number;
+  // Notifiers added; ones a project already had are left alone
+  notifiers: number;
+  settings: number;
+  // Parts of the bundle that weren't imported, and why
+  skipped: { item: string; reason: string }[];
+}
+
+// ============================================================================
+// Secrets API
+// ============================================================================
+
+// A stored secret; values are never returned by the API
+export interface SecretInfo {
+  name: string;
+  createdAt: string;
+  updatedAt: string;
+}
+
+export interface SecretsStatus {
+  unlocked: boolean;
+  // Where the encryption key comes from, when unlocked
+  keySource: 'passphrase' | 'keychain' | null;
+  // Why secrets are locked
+  error: string | null;
+}
+
+export interface SetSecretRequest {
+  value: string;
+}
+
+// ============================================================================
+// Request Recording API
+// ============================================================================
+
+// An API request and its response, with credentials redacted
+export interface RecordedRequest {
+  id: number;
+  method: string;
+  path: string;
+  query: Record<string, string>;
+  status: number;
+  durationMs: number;
+  startedAt: string;
+  requestHeaders: Record<string, string>;
+  requestBody: string | null;
+  responseHeaders: Record<string, string>;
+  // Null for binary and streamed (e.g. server-sent events) responses
+  responseBody: string | null;
+  // A body was longer than the recorder keeps
+  truncated: boolean;
+}
+
+export interface RequestRecordingStatus {
+  enabled: boolean;
+  capacity: number;
+  // Exchanges currently recorded
+  count: number;
+}
+
+export interface SetRequestRecordingRequest {
+  enabled: boolean;
+}
+
+// ============================================================================
+// Status API
+// ============================================================================
+
+export interface ActiveBuild {
+  buildId: string;
+  scheme: string;
+  projectPath: string;
+  destination: DestinationPresetId;
+  startedAt: string;
+  // Estimated fraction done (0-1), see BuildEvent.progress
+  progress: number | null;
+}
+
+export interface LastBuildResult {
+  buildId: string;
+  scheme: string;
+  projectPath: string;
+  success: boolean;
+  message?: string;
+  durationMs: number;
+  finishedAt: string;
+  diagnostics: BuildDiagnostic[];
+}
+
+export interface StatusSummary {
+  activeBuilds: ActiveBuild[];
+  // UDIDs with a running simulator-server session
+  activeSessions: string[];
+  bootedSimulators: Array<{ udid: string; name: string }>;
+  // When the simulator list was last refreshed, null if it never was
+  simulatorsUpdatedAt: string | null;
+  lastBuild: LastBuildResult | null;
+  // What is keeping the host awake, e.g. `stream:<udid>`
+  keepAwake: string[];
+}
+
+// ============================================================================
+// Workspace API
+// ============================================================================
+
+export type JobKind = 'build' | 'recording' | 'automation' | 'capture' | 'snapshot';
+
+export interface JobDirInfo {
+  path: string;
+  // Null when the directory has no readable owner file
+  kind: JobKind | null;
+  id: string | null;
+  pid: number | null;
+  createdAt: string | null;
+  // Whether the owning process is still running
+  active: boolean;
+  sizeBytes: number;
+}
+
+// ============================================================================
+// Recordings API
+// ============================================================================
+
+export type RecordingStatus = 'recording' | 'finished' | 'failed';
+
+// - simctl: an MP4 from `simctl io recordVideo`
+// - stream: the frames streamed to the browser, as a multipart MJPEG stream
+export type RecordingSource = 'simctl' | 'stream';
+
+export interface Recording {
+  id: string;
+  udid: string;
+  source: RecordingSource;
+  status: RecordingStatus;
+  // Why a failed recording has no video
+  error: string | null;
+  // Set once the recording is finished
+  sizeBytes: number | null;
+  durationMs: number | null;
+  startedAt: string;
+  finishedAt: string | null;
+}
+
+export interface RecordingRequest {
+  udid: string;
+  // Defaults to simctl
+  source?: RecordingSource;
+}
+
+// ============================================================================
+// Recovery API
+// ============================================================================
+
+export interface OrphanedProcess {
+  pid: number;
+  command: string;
+  kind: string;
+  startedAt: string;
+  // False if it had already exited
+  killed: boolean;
+}
+
+export interface RecoveryReport {
+  recoveredAt: string;
+  orphanedProcesses: OrphanedProcess[];
+  interruptedJobs: Array<{ kind: JobKind; id: string; createdAt: string | null }>;
+  // UDIDs of the simulators cloned to undo erases with, deleted because their undo was lost
+  discardedSnapshots: string[];
+}
+
+// ============================================================================
+// About API
+// ============================================================================
+
+export interface AboutResponse {
+  version: string | null;
+  pid: number;
+  // Port the server listens on, which differs from PLASMA_PORT when it was taken
+  port: number;
+  url: string;
+  publicUrl: string;
+  startedAt: string;
+}
+
+// ============================================================================
+// Admin API
+// ============================================================================
+
+export type LogLevel = 'off' | 'error' | 'warn' | 'info' | 'debug' | 'trace';
+
+export interface LogFilterRequest {
+  // Default level and per-module overrides, e.g. `info,simulator=trace`
+  filter: string;
+}
+
+export interface LogFilterResponse {
+  filter: string;
+}
+
+// ============================================================================
+// IPC Channel Names
+// ============================================================================
+
+export const IpcChannels = {
+  // Projects
+  PROJECTS_VALIDATE: 'projects:validate',
+  PROJECTS_RECENT: 'projects:recent',
+
+  // Xcode
+  XCODE_DISCOVER: 'xcode:discover',
+  XCODE_BUILD_START: 'xcode:build:start',
+  XCODE_BUILD_EVENT: 'xcode:build:event', // Main -> Renderer event
+  XCODE_LAUNCHABLE_PRODUCTS: 'xcode:launchable-products',
+
+  // Simulator
+  SIMULATOR_LIST: 'simulator:list',
+  SIMULATOR_LAUNCH: 'simulator:launch',
+  SIMULATOR_TOUCH: 'simulator:touch',
+  SIMULATOR_TAP: 'simulator:tap',
+  SIMULATOR_SWIPE: 'simulator:swipe',
+  SIMULATOR_STREAM_START: 'simulator:stream:start',
+  SIMULATOR_STREAM_STOP: 'simulator:stream:stop',
+  SIMULATOR_STREAM_FRAME: 'simulator:stream:frame', // Main -> Renderer event
+  SIMULATOR_LOG: 'simulator:log', // Main -> Renderer event
+  SIMULATOR_SESSION_RESTART: 'simulator:session:restart', // Main -> Renderer event
+  SIMULATOR_COMMANDS_SUBSCRIBE: 'simulator:commands:subscribe',
+  SIMULATOR_COMMANDS_UNSUBSCRIBE: 'simulator:commands:unsubscribe',
+  SIMULATOR_SESSION_COMMAND: 'simulator:session:command', // Main -> Renderer event
+  SIMULATOR_RUNTIME_ISSUE: 'simulator:runtime-issue', // Main -> Renderer event
+  SIMULATOR_DEVICE_CHANGE: 'simulator:device:change', // Main -> Renderer event
+} as const;
+
+export type IpcChannel = (typeof IpcChannels)[keyof typeof IpcChannels];
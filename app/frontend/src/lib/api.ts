@@ -2,8 +2,151 @@
  * API client - uses HTTP/WebSocket to communicate with the backend server
  */
 
-const API_BASE = 'http://localhost:3001';
-const WS_BASE = 'ws://localhost:3001';
+import type {
+  AboutResponse,
+  AccessibilitySettings,
+  AnalyticsUsage,
+  AndroidEmulator,
+  AndroidValidationResult,
+  AppConsoleEvent,
+  Appearance,
+  AppearanceResponse,
+  AppliedConditions,
+  ApplyConditionsRequest,
+  AppResourceSample,
+  BiometricEnrollmentRequest,
+  BiometricRequest,
+  BootEmulatorRequest,
+  BuildComparison,
+  BuildDiagnostic,
+  BuildEvent,
+  BuildProduct,
+  BuildProvenance,
+  BuildRecord,
+  BuildStreamRequest,
+  ButtonRequest,
+  CancelBuildRequest,
+  ConditionProfile,
+  ConfigIssue,
+  ConflictResponse,
+  CreateNotifierRequest,
+  CreateProjectRequest,
+  CreateProjectResponse,
+  CreateSimulatorRequest,
+  CreateSimulatorResponse,
+  DestinationPreset,
+  DestinationPresetId,
+  DeviceClass,
+  DiscoverProjectRequest,
+  DiscoverProjectResponse,
+  FramePacing,
+  DoctorResponse,
+  EraseSimulatorRequest,
+  EraseSimulatorResponse,
+  FeatureFlag,
+  FeatureFlagName,
+  FieldError,
+  GetLaunchableProductsRequest,
+  GetLaunchableProductsResponse,
+  GetRecentProjectsRequest,
+  GetRecentProjectsResponse,
+  GradleBuildRequest,
+  HardwareButton,
+  ImportInstanceResponse,
+  InstallApkRequest,
+  InstanceExport,
+  JobDirInfo,
+  KeyRequest,
+  LaunchAndroidAppRequest,
+  LaunchAndroidAppResponse,
+  LaunchAppRequest,
+  LaunchAppResponse,
+  LaunchMacAppRequest,
+  LaunchMacAppResponse,
+  LaunchTimingStats,
+  LocationRouteRequest,
+  LocationRouteResponse,
+  OnboardingStatus,
+  OpenUrlRequest,
+  PrivacyRequest,
+  RecordedRequest,
+  Recording,
+  RecordingRequest,
+  RecordingSource,
+  RequestRecordingStatus,
+  SecretInfo,
+  SecretsStatus,
+  SetAccessibilityRequest,
+  SetLocationRequest,
+  SetOrientationRequest,
+  SettingsPane,
+  SimulatorControlMessage,
+  SimulatorControlReply,
+  SimulatorDeviceTypes,
+  SimulatorOrientation,
+  StatusBarOverrides,
+  ListNoiseProfilesResponse,
+  ListNotificationsResponse,
+  ListStreamAlertsResponse,
+  LogFilterResponse,
+  LogNoiseProfileId,
+  ListSimulatorsResponse,
+  NotificationRecord,
+  NotifierRecord,
+  OcrRequest,
+  OcrResponse,
+  OpenSettingsPaneRequest,
+  OpenSettingsPaneResponse,
+  PixelColor,
+  Platform,
+  PluginInfo,
+  ProjectDestinationResponse,
+  ProjectNotes,
+  ProjectReadme,
+  ProjectSettings,
+  SetProjectDestinationRequest,
+  ProjectRecord,
+  RecoveryReport,
+  RuntimeIssue,
+  RunPluginActionRequest,
+  SessionCommandRecord,
+  SessionInfo,
+  SessionRestartEvent,
+  SettingChange,
+  Simulator,
+  SimulatorChange,
+  StartStreamRequest,
+  StatusSummary,
+  StreamFrame,
+  StreamLogEvent,
+  StreamScaleChange,
+  SwipeRequest,
+  SyslogEntry,
+  TypeTextRequest,
+  TapCalibration,
+  TapRequest,
+  TouchEvent,
+  UndoInfo,
+  UndoResponse,
+  UnifiedProject,
+  UninstallAppRequest,
+  UninstallAppResponse,
+  UniversalLinksCheckRequest,
+  UniversalLinksReport,
+  UpdateProjectNotesRequest,
+  UpdateProjectSettingsRequest,
+  ValidateProjectRequest,
+  ValidateProjectResponse,
+  ValidationErrorResponse,
+  XcodeProject,
+  XcodeValidationResult,
+} from './api-types';
+
+// The Electron app passes the backend's port when it isn't the default one
+const API_PORT =
+  (typeof window !== 'undefined' && new URLSearchParams(window.location.search).get('apiPort')) || '3001';
+const API_BASE = `http://localhost:${API_PORT}`;
+const WS_BASE = `ws://localhost:${API_PORT}`;
 
 // WebSocket connection
 let ws: WebSocket | null = null;
@@ -83,193 +226,144 @@ function sendWsMessage(type: string, payload: unknown): void {
   }
 }
 
-async function httpPost<T>(path: string, body: unknown): Promise<T> {
-  const response = await fetch(`${API_BASE}${path}`, {
-    method: 'POST',
-    headers: { 'Content-Type': 'application/json' },
-    body: JSON.stringify(body),
-  });
-  if (!response.ok) {
-    const error = await response.json().catch(() => ({ error: response.statusText }));
-    throw new Error(error.error || 'Request failed');
-  }
-  return response.json();
-}
+// Control socket for touch and key input, one message per event instead of one request
+let controlSocket: WebSocket | null = null;
 
-async function httpGet<T>(path: string): Promise<T> {
-  const response = await fetch(`${API_BASE}${path}`);
-  if (!response.ok) {
-    const error = await response.json().catch(() => ({ error: response.statusText }));
-    throw new Error(error.error || 'Request failed');
+function sendControlMessage(message: SimulatorControlMessage): void {
+  if (!controlSocket || controlSocket.readyState === WebSocket.CLOSING || controlSocket.readyState === WebSocket.CLOSED) {
+    controlSocket = new WebSocket(`${WS_BASE}/api/simulator/ws`);
+    controlSocket.onmessage = (event) => {
+      const reply = JSON.parse(event.data) as SimulatorControlReply;
+      if (!reply.ok) {
+        console.error('[api] Control message failed:', reply.error);
+      }
+    };
   }
-  return response.json();
-}
-
-// Types
-export type Platform = 'ios' | 'android';
-
-interface ValidateProjectRequest {
-  path: string;
-}
-
-interface Project {
-  path: string;
-  name: string;
-  type: 'xcode' | 'android';
-  platforms: Platform[];
-  valid: boolean;
-}
-
-interface ValidateProjectResponse {
-  project: Project | null;
-  error?: string;
-}
-
-interface GetRecentProjectsRequest {
-  query?: string;
-  limit?: number;
-}
-
-export interface ProjectRecord {
-  id: number;
-  path: string;
-  name: string;
-  platforms: Platform[];
-  last_opened_at: string | null;
-  created_at: string | null;
-}
-
-type GetRecentProjectsResponse = ProjectRecord[];
-
-// New unified project types
-export interface UnifiedProject {
-  id: number;
-  name: string;
-  xcode_path: string | null;
-  android_path: string | null;
-  last_opened_at: string | null;
-  created_at: string | null;
-}
-
-interface CreateProjectRequest {
-  name: string;
-  xcodePath?: string;
-  androidPath?: string;
-  directory?: string;
-}
-
-interface CreateProjectResponse {
-  project?: UnifiedProject;
-  error?: string;
-}
-
-interface XcodeValidationResult {
-  valid: boolean;
-  path: string | null;
-  type: 'workspace' | 'project' | null;
-  error?: string;
-}
-
-interface AndroidValidationResult {
-  valid: boolean;
-  path: string | null;
-  error?: string;
-}
-
-interface DiscoverProjectRequest {
-  path: string;
-}
 
-interface XcodeProject {
-  path: string;
-  projectType: 'project' | 'workspace';
-  schemes: string[];
-  targets: string[];
-  configurations: string[];
-}
-
-type DiscoverProjectResponse = XcodeProject;
-
-interface BuildStreamRequest {
-  path: string;
-  scheme: string;
+  const socket = controlSocket;
+  if (socket.readyState === WebSocket.OPEN) {
+    socket.send(JSON.stringify(message));
+  } else {
+    socket.addEventListener('open', () => socket.send(JSON.stringify(message)), { once: true });
+  }
 }
 
-interface BuildProduct {
-  name: string;
-  path: string;
+/**
+ * Thrown when the server rejected a request's payload. `fields` has a message for every invalid
+ * field by its path, e.g. `touches[0].x`, to show next to the form inputs they belong to.
+ */
+export class RequestValidationError extends Error {
+  constructor(message: string, readonly fields: FieldError[]) {
+    super(message);
+  }
 }
 
-interface BuildEvent {
-  type: 'started' | 'output' | 'completed' | 'error';
-  scheme?: string;
-  projectPath?: string;
-  line?: string;
-  success?: boolean;
-  buildDir?: string;
-  products?: BuildProduct[];
-  message?: string;
+async function responseError(response: Response): Promise<Error> {
+  const error = await response.json().catch(() => ({ error: response.statusText }));
+  if (error.code === 'invalid_request') {
+    return new RequestValidationError(error.error, (error as ValidationErrorResponse).fields);
+  }
+  return new Error(error.error || 'Request failed');
 }
 
-interface GetLaunchableProductsRequest {
-  buildDir: string;
+async function httpPost<T>(path: string, body: unknown, headers: Record<string, string> = {}): Promise<T> {
+  const response = await fetch(`${API_BASE}${path}`, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json', ...headers },
+    body: JSON.stringify(body),
+  });
+  if (!response.ok) {
+    throw await responseError(response);
+  }
+  return response.json();
 }
 
-type GetLaunchableProductsResponse = BuildProduct[];
+// Attempts of requests retried after network errors
+const NETWORK_RETRY_ATTEMPTS = 3;
 
-interface Simulator {
-  udid: string;
-  name: string;
-  state: string;
-  runtime: string;
+/**
+ * POST an expensive operation, retrying when the request fails on the network. Every attempt has
+ * the same Idempotency-Key, so the server runs the operation once even when a response got lost.
+ */
+async function httpPostIdempotent<T>(path: string, body: unknown): Promise<T> {
+  const headers = { 'Idempotency-Key': crypto.randomUUID() };
+  for (let attempt = 1; ; attempt++) {
+    try {
+      return await httpPost<T>(path, body, headers);
+    } catch (err) {
+      // fetch rejects with a TypeError when the request didn't get a response
+      if (!(err instanceof TypeError) || attempt >= NETWORK_RETRY_ATTEMPTS) {
+        throw err;
+      }
+      await new Promise((resolve) => setTimeout(resolve, 500 * attempt));
+    }
+  }
 }
 
-type ListSimulatorsResponse = Simulator[];
-
-interface LaunchAppRequest {
-  udid: string;
-  appPath: string;
-  bundleId?: string;
+/**
+ * Thrown when a conditional update lost against a concurrent change. `current` holds the
+ * server's state, to re-apply the change to before retrying.
+ */
+export class ConflictError<T> extends Error {
+  constructor(message: string, readonly current: T) {
+    super(message);
+  }
 }
 
-interface LaunchAppResponse {
-  message: string;
+async function httpPut<T>(path: string, body: unknown): Promise<T> {
+  const response = await fetch(`${API_BASE}${path}`, {
+    method: 'PUT',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify(body),
+  });
+  if (response.status === 409) {
+    const { error, ...current } = (await response.json()) as ConflictResponse<T>;
+    throw new ConflictError(error, current as T);
+  }
+  if (!response.ok) {
+    throw await responseError(response);
+  }
+  return response.json();
 }
 
-interface TapRequest {
-  udid: string;
-  x: number;
-  y: number;
-  screenWidth: number;
-  screenHeight: number;
+async function httpDelete<T>(path: string): Promise<T> {
+  const response = await fetch(`${API_BASE}${path}`, { method: 'DELETE' });
+  if (!response.ok) {
+    throw await responseError(response);
+  }
+  return response.json();
 }
 
-interface SwipeRequest {
-  udid: string;
-  startX: number;
-  startY: number;
-  endX: number;
-  endY: number;
-  screenWidth: number;
-  screenHeight: number;
-  duration?: number;
+async function httpGet<T>(path: string, headers: Record<string, string> = {}): Promise<T> {
+  const response = await fetch(`${API_BASE}${path}`, { headers });
+  if (!response.ok) {
+    throw await responseError(response);
+  }
+  return response.json();
 }
 
-interface StartStreamRequest {
-  udid: string;
-  fps?: number;
-  quality?: number;
-}
+// Build destination matching a simulator's device class
+const SIMULATOR_DESTINATIONS: Partial<Record<DeviceClass, DestinationPresetId>> = {
+  vision: 'visionos-simulator',
+  tv: 'tvos-simulator',
+  watch: 'watchos-simulator',
+};
 
-interface StreamFrame {
-  udid: string;
-  frame: string;
+export function destinationForSimulator(simulator?: Simulator): DestinationPresetId {
+  return (simulator && SIMULATOR_DESTINATIONS[simulator.deviceClass]) || 'ios-simulator';
 }
 
-interface StreamLogEvent {
-  type: 'info' | 'error' | 'debug' | 'frame';
-  message?: string;
-  frameNumber?: number;
+/**
+ * A simulator list with a pushed change applied
+ */
+export function applySimulatorChange(simulators: Simulator[], change: SimulatorChange): Simulator[] {
+  if (change.kind === 'removed') {
+    return simulators.filter((simulator) => simulator.udid !== change.udid);
+  }
+  if (change.kind === 'added' && !simulators.some((simulator) => simulator.udid === change.udid)) {
+    return [...simulators, change.simulator];
+  }
+  return simulators.map((simulator) => (simulator.udid === change.udid ? change.simulator : simulator));
 }
 
 /**
@@ -338,6 +432,66 @@ export const api = {
       return httpGet(`/api/projects/unified/${id}`);
     },
 
+    getDestination: async (id: number): Promise<ProjectDestinationResponse> => {
+      return httpGet(`/api/projects/${id}/destination`);
+    },
+
+    setDestination: async (
+      id: number,
+      destination: DestinationPresetId,
+      expectedUpdatedAt?: string | null
+    ): Promise<ProjectDestinationResponse> => {
+      const request: SetProjectDestinationRequest = { destination, expectedUpdatedAt };
+      return httpPut(`/api/projects/${id}/destination`, request);
+    },
+
+    // Change the destination based on its current value, retrying when another client changed it in between
+    updateDestination: async (
+      id: number,
+      update: (current: DestinationPresetId) => DestinationPresetId,
+      attempts: number = 3
+    ): Promise<ProjectDestinationResponse> => {
+      let current: ProjectDestinationResponse = await httpGet(`/api/projects/${id}/destination`);
+      for (let attempt = 1; ; attempt++) {
+        try {
+          const request: SetProjectDestinationRequest = {
+            destination: update(current.destination),
+            expectedUpdatedAt: current.updatedAt,
+          };
+          return await httpPut(`/api/projects/${id}/destination`, request);
+        } catch (error) {
+          if (!(error instanceof ConflictError) || attempt >= attempts) {
+            throw error;
+          }
+          current = error.current as ProjectDestinationResponse;
+        }
+      }
+    },
+
+    // Scheme, configuration, simulator and environment last used to build the project
+    getSettings: async (id: number): Promise<ProjectSettings> => {
+      return httpGet(`/api/projects/${id}/settings`);
+    },
+
+    setSettings: async (id: number, request: UpdateProjectSettingsRequest): Promise<ProjectSettings> => {
+      return httpPut(`/api/projects/${id}/settings`, request);
+    },
+
+    // Notes for whoever runs the project's builds, rendered from Markdown
+    getNotes: async (id: number): Promise<ProjectNotes> => {
+      return httpGet(`/api/projects/${id}/notes`);
+    },
+
+    setNotes: async (id: number, notes: string | null): Promise<ProjectNotes> => {
+      const request: UpdateProjectNotesRequest = { notes };
+      return httpPut(`/api/projects/${id}/notes`, request);
+    },
+
+    // Rejects when the project has no README
+    getReadme: async (id: number): Promise<ProjectReadme> => {
+      return httpGet(`/api/projects/${id}/readme`);
+    },
+
     validateXcode: async (path: string): Promise<XcodeValidationResult> => {
       return httpPost('/api/validate/xcode', { path });
     },
@@ -353,10 +507,38 @@ export const api = {
       return httpPost('/api/xcode/discover', request);
     },
 
+    destinations: async (): Promise<DestinationPreset[]> => {
+      return httpGet('/api/xcode/destinations');
+    },
+
+    // Recorded builds, newest first
+    listBuilds: async (projectId?: number): Promise<BuildRecord[]> => {
+      return httpGet(projectId === undefined ? '/api/builds' : `/api/builds?project_id=${projectId}`);
+    },
+
+    compareBuilds: async (a: string, b: string): Promise<BuildComparison> => {
+      return httpGet(`/api/builds/compare?a=${encodeURIComponent(a)}&b=${encodeURIComponent(b)}`);
+    },
+
+    // Checksums of a build's products and the machine, Xcode and commit it was built with
+    buildProvenance: async (buildId: string): Promise<BuildProvenance> => {
+      return httpGet(`/api/builds/${encodeURIComponent(buildId)}/provenance`);
+    },
+
+    // Recorded builds that produced an artifact with this SHA-256
+    findBuildsByChecksum: async (sha256: string): Promise<BuildProvenance[]> => {
+      return httpGet(`/api/builds/provenance?sha256=${sha256}`);
+    },
+
     startBuild: async (request: BuildStreamRequest): Promise<void> => {
       sendWsMessage('xcode:build:start', request);
     },
 
+    cancelBuild: async (buildId: string): Promise<void> => {
+      const request: CancelBuildRequest = { buildId };
+      await httpPost('/api/xcode/build/cancel', request);
+    },
+
     onBuildEvent: (callback: (event: BuildEvent) => void): (() => void) => {
       connectWebSocket();
       return addWsListener('xcode:build:event', callback as (payload: unknown) => void);
@@ -373,18 +555,188 @@ export const api = {
       return httpGet('/api/simulator/list');
     },
 
+    // Device types and runtimes to create simulators with
+    listDeviceTypes: async (): Promise<SimulatorDeviceTypes> => {
+      return httpGet('/api/simulator/devicetypes');
+    },
+
+    create: async (request: CreateSimulatorRequest): Promise<CreateSimulatorResponse> => {
+      return httpPostIdempotent('/api/simulator/create', request);
+    },
+
+    // Shuts the simulator down and erases its content and settings. With `snapshot`, the response
+    // has an undo to pass to `undo` for a while.
+    erase: async (udid: string, options: { snapshot?: boolean } = {}): Promise<EraseSimulatorResponse> => {
+      const request: EraseSimulatorRequest = { udid, ...options };
+      return httpPostIdempotent('/api/simulator/erase', request);
+    },
+
+    uninstall: async (request: UninstallAppRequest): Promise<UninstallAppResponse> => {
+      return httpPostIdempotent('/api/simulator/uninstall', request);
+    },
+
+    listUndos: async (): Promise<UndoInfo[]> => {
+      return httpGet('/api/simulator/undo');
+    },
+
+    // Restores an erased simulator under a new UDID, which the response has
+    undo: async (id: string): Promise<UndoResponse> => {
+      return httpPost(`/api/simulator/undo/${encodeURIComponent(id)}`, {});
+    },
+
+    // Shuts the simulator down first if it's booted
+    delete: async (udid: string): Promise<void> => {
+      await httpDelete(`/api/simulator/${encodeURIComponent(udid)}`);
+    },
+
     launch: async (request: LaunchAppRequest): Promise<LaunchAppResponse> => {
-      return httpPost('/api/simulator/launch', request);
+      return httpPostIdempotent('/api/simulator/launch', request);
+    },
+
+    // Console output of a launch, from its first line, with the `launchId` the launch returned.
+    // `onExit` is called once the app exits.
+    onConsole: (launchId: string, callback: (event: AppConsoleEvent) => void, onExit?: (exitCode: number | null) => void): (() => void) => {
+      const source = new EventSource(`${API_BASE}/api/simulator/launches/${encodeURIComponent(launchId)}/console`);
+      source.addEventListener('console.output', (event) => callback(JSON.parse((event as MessageEvent).data)));
+      // Closed before the server ends the response, so EventSource doesn't reconnect
+      source.addEventListener('console.exit', (event) => {
+        source.close();
+        onExit?.(JSON.parse((event as MessageEvent).data).exitCode);
+      });
+      return () => source.close();
     },
 
     tap: async (request: TapRequest): Promise<void> => {
       await httpPost('/api/simulator/tap', request);
     },
 
+    // Sent over the control socket without waiting for the simulator, so drags keep up
+    touch: async (event: TouchEvent): Promise<void> => {
+      sendControlMessage({ type: 'touch', udid: event.udid, phase: event.type, touches: event.touches });
+    },
+
+    // `code` is a HID keyboard usage code
+    key: async (udid: string, code: number, direction: 'down' | 'up'): Promise<void> => {
+      sendControlMessage({ type: 'key', udid, code, direction });
+    },
+
     swipe: async (request: SwipeRequest): Promise<void> => {
       await httpPost('/api/simulator/swipe', request);
     },
 
+    // Types into the focused field, like a hardware keyboard
+    typeText: async (udid: string, text: string): Promise<void> => {
+      const request: TypeTextRequest = { udid, text };
+      await httpPost('/api/simulator/type', request);
+    },
+
+    // Rotates the device; watches, TVs and Vision Pro don't rotate
+    setOrientation: async (udid: string, orientation: SimulatorOrientation): Promise<void> => {
+      const request: SetOrientationRequest = { udid, orientation };
+      await httpPost('/api/simulator/orientation', request);
+    },
+
+    getAppearance: async (udid: string): Promise<AppearanceResponse> => {
+      return httpGet(`/api/simulator/${udid}/appearance`);
+    },
+
+    setAppearance: async (udid: string, appearance: Appearance): Promise<AppearanceResponse> => {
+      return httpPut(`/api/simulator/${udid}/appearance`, { appearance });
+    },
+
+    // e.g. { time: '9:41', batteryLevel: 100, batteryState: 'charged' } for App Store screenshots
+    overrideStatusBar: async (udid: string, overrides: StatusBarOverrides): Promise<void> => {
+      await httpPut(`/api/simulator/${udid}/status-bar`, overrides);
+    },
+
+    clearStatusBar: async (udid: string): Promise<void> => {
+      await httpDelete(`/api/simulator/${udid}/status-bar`);
+    },
+
+    getAccessibilitySettings: async (udid: string): Promise<AccessibilitySettings> => {
+      return httpGet(`/api/simulator/${udid}/accessibility`);
+    },
+
+    // Settings left out keep their value; resolves with all of them
+    setAccessibilitySettings: async (udid: string, settings: SetAccessibilityRequest): Promise<AccessibilitySettings> => {
+      return httpPut(`/api/simulator/${udid}/accessibility`, settings);
+    },
+
+    getSettingsPanes: async (): Promise<SettingsPane[]> => {
+      return httpGet('/api/simulator/settings/panes');
+    },
+
+    // e.g. { pane: 'notifications', bundleId } to check an app's notification permission
+    openSettingsPane: async (udid: string, request: OpenSettingsPaneRequest): Promise<OpenSettingsPaneResponse> => {
+      return httpPost(`/api/simulator/${udid}/settings`, request);
+    },
+
+    getConditionProfiles: async (): Promise<ConditionProfile[]> => {
+      return httpGet('/api/simulator/conditions/profiles');
+    },
+
+    // e.g. { profile: 'worst-case' }; the network and low power mode are only shown on the status bar
+    applyConditions: async (udid: string, request: ApplyConditionsRequest): Promise<AppliedConditions> => {
+      return httpPost(`/api/simulator/${udid}/conditions`, request);
+    },
+
+    setLocation: async (udid: string, latitude: number, longitude: number): Promise<void> => {
+      const request: SetLocationRequest = { udid, latitude, longitude };
+      await httpPost('/api/simulator/location', request);
+    },
+
+    // Moves along a GPX route in the background until another location is set or cleared
+    startLocationRoute: async (request: LocationRouteRequest): Promise<LocationRouteResponse> => {
+      return httpPost('/api/simulator/location/route', request);
+    },
+
+    clearLocation: async (udid: string): Promise<void> => {
+      await httpDelete(`/api/simulator/location?udid=${encodeURIComponent(udid)}`);
+    },
+
+    // Answers permission dialogs ahead of time, e.g. granting photos before a UI test
+    setPrivacy: async (request: PrivacyRequest): Promise<void> => {
+      await httpPost('/api/simulator/privacy', request);
+    },
+
+    // Presses and releases a key, e.g. 'return' to submit a form
+    pressKey: async (udid: string, key: KeyRequest['key']): Promise<void> => {
+      const request: KeyRequest = { udid, key };
+      await httpPost('/api/simulator/key', request);
+    },
+
+    // e.g. 'home' to leave the app; the stream has no device chrome to click
+    pressButton: async (udid: string, button: HardwareButton): Promise<void> => {
+      const request: ButtonRequest = { udid, button };
+      await httpPost('/api/simulator/button', request);
+    },
+
+    // For an <img> element; defaults to 2 fps at 25% scale
+    thumbnailUrl: (udid: string, options: { fps?: number; scale?: number } = {}): string => {
+      const params = new URLSearchParams({ udid });
+      if (options.fps !== undefined) params.set('fps', String(options.fps));
+      if (options.scale !== undefined) params.set('scale', String(options.scale));
+      return `${API_BASE}/api/simulator/thumbnail?${params}`;
+    },
+
+    // For a <video> element: H.264 at a fraction of MJPEG's bandwidth, for remote clients.
+    // Defaults to 30 fps at 2 Mbit/s.
+    videoUrl: (udid: string, options: { fps?: number; bitrate?: number } = {}): string => {
+      const params = new URLSearchParams({ udid });
+      if (options.fps !== undefined) params.set('fps', String(options.fps));
+      if (options.bitrate !== undefined) params.set('bitrate', String(options.bitrate));
+      return `${API_BASE}/api/simulator/video?${params}`;
+    },
+
+    // What the simulator's simulator-server supports; fails when it has no session
+    getSession: async (udid: string): Promise<SessionInfo> => {
+      return httpGet(`/api/simulator/session/${encodeURIComponent(udid)}`);
+    },
+
+    closeSession: async (udid: string): Promise<void> => {
+      await httpDelete(`/api/simulator/session/${encodeURIComponent(udid)}`);
+    },
+
     startStream: async (request: StartStreamRequest): Promise<void> => {
       sendWsMessage('simulator:stream:start', request);
     },
@@ -398,10 +750,398 @@ export const api = {
       return addWsListener('simulator:stream:frame', callback as (payload: unknown) => void);
     },
 
+    // Adaptive scaling changed the size of a stream's frames; see StartStreamRequest.adaptive
+    onStreamScale: (callback: (change: StreamScaleChange) => void): (() => void) => {
+      connectWebSocket();
+      return addWsListener('simulator:stream:scale', callback as (payload: unknown) => void);
+    },
+
     onLog: (callback: (log: StreamLogEvent) => void): (() => void) => {
       connectWebSocket();
       return addWsListener('simulator:log', callback as (payload: unknown) => void);
     },
+
+    // Entries of a simulator's system log, including the app's own output, without the
+    // noise profiles' chatter. `predicate` is an NSPredicate like `subsystem == "com.example.app"`.
+    onSyslog: (
+      udid: string,
+      callback: (entry: SyslogEntry) => void,
+      options: { predicate?: string; noise?: LogNoiseProfileId[] | 'all' | 'none' } = {}
+    ): (() => void) => {
+      const params = new URLSearchParams({ udid });
+      if (options.predicate) params.set('predicate', options.predicate);
+      if (options.noise) params.set('noise', Array.isArray(options.noise) ? options.noise.join(',') : options.noise);
+      const source = new EventSource(`${API_BASE}/api/simulator/syslog?${params}`);
+      source.addEventListener('syslog', (event) => callback(JSON.parse((event as MessageEvent).data)));
+      return () => source.close();
+    },
+
+    noiseProfiles: async (): Promise<ListNoiseProfilesResponse> => {
+      return httpGet('/api/simulator/syslog/noise-profiles');
+    },
+
+    runtimeIssues: async (udid: string): Promise<RuntimeIssue[]> => {
+      return httpGet(`/api/simulator/${encodeURIComponent(udid)}/runtime-issues`);
+    },
+
+    // Complete (or fail) a Face ID or Touch ID prompt, e.g. Apple Pay's
+    sendBiometric: async (udid: string, request: BiometricRequest): Promise<void> => {
+      await httpPost(`/api/simulator/${encodeURIComponent(udid)}/biometrics`, request);
+    },
+
+    setBiometricEnrollment: async (udid: string, request: BiometricEnrollmentRequest): Promise<void> => {
+      await httpPut(`/api/simulator/${encodeURIComponent(udid)}/biometrics/enrollment`, request);
+    },
+
+    // Open a URL as if tapped, e.g. to test a universal link
+    openUrl: async (udid: string, request: OpenUrlRequest): Promise<void> => {
+      await httpPost(`/api/simulator/${encodeURIComponent(udid)}/open-url`, request);
+    },
+
+    // Simulators added, removed, booted or shut down, as the backend notices them
+    onDeviceChange: (callback: (change: SimulatorChange) => void): (() => void) => {
+      connectWebSocket();
+      return addWsListener('simulator:device:change', callback as (payload: unknown) => void);
+    },
+
+    onRuntimeIssue: (callback: (issue: RuntimeIssue) => void): (() => void) => {
+      connectWebSocket();
+      return addWsListener('simulator:runtime-issue', callback as (payload: unknown) => void);
+    },
+
+    framePacing: async (udid: string): Promise<FramePacing> => {
+      return httpGet(`/api/simulator/${encodeURIComponent(udid)}/frame-pacing`);
+    },
+
+    resetFramePacing: async (udid: string): Promise<FramePacing> => {
+      return httpPost(`/api/simulator/${encodeURIComponent(udid)}/frame-pacing/reset`, {});
+    },
+
+    // Text on screen with where it is, e.g. to check that "Welcome" shows with `{ find: 'Welcome' }`
+    ocr: async (udid: string, request: OcrRequest = {}): Promise<OcrResponse> => {
+      return httpPost(`/api/simulator/${encodeURIComponent(udid)}/ocr`, request);
+    },
+
+    // Color at normalized (x, y), averaged over `radius` pixels around it
+    pixelColor: async (udid: string, x: number, y: number, radius: number = 0): Promise<PixelColor> => {
+      return httpGet(`/api/simulator/${encodeURIComponent(udid)}/pixel?x=${x}&y=${y}&radius=${radius}`);
+    },
+
+    // Live CPU and memory samples of the app on a simulator, by default the one last launched.
+    // `onMissing` is called while the app isn't running.
+    onResourceSample: (
+      udid: string,
+      callback: (sample: AppResourceSample) => void,
+      options: { bundleId?: string; intervalMs?: number; onMissing?: () => void } = {}
+    ): (() => void) => {
+      const params = new URLSearchParams();
+      if (options.bundleId) params.set('bundleId', options.bundleId);
+      if (options.intervalMs) params.set('interval', String(options.intervalMs));
+      const source = new EventSource(`${API_BASE}/api/simulator/${encodeURIComponent(udid)}/resources/stream?${params}`);
+      source.addEventListener('sample', (event) => callback(JSON.parse((event as MessageEvent).data)));
+      source.addEventListener('app_not_running', () => options.onMissing?.());
+      return () => source.close();
+    },
+
+    onSessionRestart: (callback: (event: SessionRestartEvent) => void): (() => void) => {
+      connectWebSocket();
+      return addWsListener('simulator:session:restart', callback as (payload: unknown) => void);
+    },
+
+    calibrate: async (udid: string): Promise<TapCalibration> => {
+      return httpPost(`/api/simulator/${encodeURIComponent(udid)}/calibrate`, {});
+    },
+
+    commands: async (udid: string): Promise<SessionCommandRecord[]> => {
+      return httpGet(`/api/simulator/sessions/${encodeURIComponent(udid)}/commands`);
+    },
+
+    // Echo of every command written to the simulator's session
+    onCommand: (udid: string, callback: (record: SessionCommandRecord) => void): (() => void) => {
+      sendWsMessage('simulator:commands:subscribe', { udid });
+      const removeListener = addWsListener('simulator:session:command', (payload) => {
+        const record = payload as SessionCommandRecord;
+        if (record.udid === udid) {
+          callback(record);
+        }
+      });
+      return () => {
+        removeListener();
+        sendWsMessage('simulator:commands:unsubscribe', { udid });
+      };
+    },
+
+    startRecording: async (udid: string, source?: RecordingSource): Promise<Recording> => {
+      const request: RecordingRequest = { udid, source };
+      return httpPost('/api/simulator/recording/start', request);
+    },
+
+    // Resolves once the video is stored; a recording that couldn't be written comes back `failed`
+    stopRecording: async (udid: string): Promise<Recording> => {
+      return httpPost('/api/simulator/recording/stop', { udid });
+    },
+  },
+
+  // Recordings API
+  recordings: {
+    list: async (filters: { udid?: string; status?: Recording['status'] } = {}): Promise<Recording[]> => {
+      const params = new URLSearchParams(filters as Record<string, string>);
+      return httpGet(`/api/recordings?${params}`);
+    },
+
+    get: async (id: string): Promise<Recording> => {
+      return httpGet(`/api/recordings/${encodeURIComponent(id)}`);
+    },
+
+    // For a <video> element, or a download with `download: true`
+    videoUrl: (id: string, download: boolean = false): string => {
+      return `${API_BASE}/api/recordings/${encodeURIComponent(id)}/video${download ? '?download=true' : ''}`;
+    },
+
+    delete: async (id: string): Promise<void> => {
+      await httpDelete(`/api/recordings/${encodeURIComponent(id)}`);
+    },
+  },
+
+  // macOS API
+  macos: {
+    launch: async (request: LaunchMacAppRequest): Promise<LaunchMacAppResponse> => {
+      return httpPost('/api/macos/launch', request);
+    },
+
+    terminate: async (bundleId: string): Promise<void> => {
+      await httpPost('/api/macos/terminate', { bundleId });
+    },
+  },
+
+  // Android API, needs the `android` feature flag
+  android: {
+    listEmulators: async (): Promise<AndroidEmulator[]> => {
+      return httpGet('/api/android/emulators');
+    },
+
+    boot: async (request: BootEmulatorRequest): Promise<AndroidEmulator> => {
+      return httpPost('/api/android/boot', request);
+    },
+
+    install: async (request: InstallApkRequest): Promise<void> => {
+      await httpPost('/api/android/install', request);
+    },
+
+    launch: async (request: LaunchAndroidAppRequest): Promise<LaunchAndroidAppResponse> => {
+      return httpPost('/api/android/launch', request);
+    },
+
+    // Build with Gradle, `assembleDebug` unless `task` says otherwise. The returned function stops the build.
+    build: (request: GradleBuildRequest, callback: (event: BuildEvent) => void, onEnd?: () => void): (() => void) => {
+      const params = new URLSearchParams({ path: request.path });
+      if (request.task) params.set('task', request.task);
+      const source = new EventSource(`${API_BASE}/api/android/build/stream?${params}`);
+      for (const name of ['build.started', 'build.output', 'build.completed', 'build.error']) {
+        source.addEventListener(name, (event) => callback(JSON.parse((event as MessageEvent).data)));
+      }
+      // Closed before the server ends the response, so EventSource doesn't reconnect to the finished build
+      source.addEventListener('build.end', () => {
+        source.close();
+        onEnd?.();
+      });
+      return () => source.close();
+    },
+
+    // APKs built in an Android project
+    launchableProducts: async (path: string): Promise<BuildProduct[]> => {
+      return httpPost('/api/android/launchable-products', { path });
+    },
+  },
+
+  // Whether an app's universal link domains serve association files that list it
+  checkUniversalLinks: async (request: UniversalLinksCheckRequest): Promise<UniversalLinksReport> => {
+    return httpPost('/api/universal-links/check', request);
+  },
+
+  // Recovery API
+  recovery: async (): Promise<RecoveryReport> => {
+    return httpGet('/api/recovery');
+  },
+
+  // Workspace API
+  workspace: {
+    jobs: async (): Promise<JobDirInfo[]> => {
+      return httpGet('/api/workspace/jobs');
+    },
+  },
+
+  // Status API
+  status: {
+    summary: async (): Promise<StatusSummary> => {
+      return httpGet('/api/status/summary');
+    },
+  },
+
+  // About API
+  about: async (): Promise<AboutResponse> => {
+    return httpGet('/api/about');
+  },
+
+  // Admin API
+  admin: {
+    getLogFilter: async (): Promise<LogFilterResponse> => {
+      return httpGet('/api/admin/log-level');
+    },
+
+    setLogFilter: async (filter: string): Promise<LogFilterResponse> => {
+      return httpPut('/api/admin/log-level', { filter });
+    },
+
+    recordedRequests: async (): Promise<RecordedRequest[]> => {
+      return httpGet('/api/admin/requests');
+    },
+
+    setRequestRecording: async (enabled: boolean): Promise<RequestRecordingStatus> => {
+      return httpPut('/api/admin/requests/recording', { enabled });
+    },
+
+    clearRecordedRequests: async (): Promise<RequestRecordingStatus> => {
+      return httpDelete('/api/admin/requests');
+    },
+
+    // Without a passphrase, notifier webhook URLs are left out of the bundle
+    exportInstance: async (passphrase?: string): Promise<InstanceExport> => {
+      return httpGet('/api/admin/export', passphrase ? { 'X-Plasma-Export-Passphrase': passphrase } : {});
+    },
+
+    importInstance: async (bundle: InstanceExport, passphrase?: string): Promise<ImportInstanceResponse> => {
+      return httpPost('/api/admin/import', bundle, passphrase ? { 'X-Plasma-Export-Passphrase': passphrase } : {});
+    },
+  },
+
+  // Doctor API
+  doctor: async (): Promise<DoctorResponse> => {
+    return httpGet('/api/doctor');
+  },
+
+  // Onboarding API
+  onboarding: {
+    status: async (): Promise<OnboardingStatus> => {
+      return httpGet('/api/onboarding');
+    },
+
+    setDismissed: async (dismissed: boolean): Promise<OnboardingStatus> => {
+      return httpPut('/api/onboarding', { dismissed });
+    },
+  },
+
+  // Usage analytics API
+  analytics: {
+    usage: async (days?: number): Promise<AnalyticsUsage> => {
+      return httpGet(`/api/analytics/usage${days ? `?days=${days}` : ''}`);
+    },
+
+    // Opting out deletes the usage collected so far
+    setEnabled: async (enabled: boolean): Promise<AnalyticsUsage> => {
+      return httpPut('/api/analytics', { enabled });
+    },
+  },
+
+  // Secrets API; values can be set but never read back
+  secrets: {
+    list: async (): Promise<SecretInfo[]> => {
+      return httpGet('/api/secrets');
+    },
+
+    status: async (): Promise<SecretsStatus> => {
+      return httpGet('/api/secrets/status');
+    },
+
+    set: async (name: string, value: string): Promise<SecretInfo> => {
+      return httpPut(`/api/secrets/${encodeURIComponent(name)}`, { value });
+    },
+
+    delete: async (name: string): Promise<{ success: boolean }> => {
+      return httpDelete(`/api/secrets/${encodeURIComponent(name)}`);
+    },
+  },
+
+  // Feature flags API
+  flags: {
+    list: async (): Promise<FeatureFlag[]> => {
+      return httpGet('/api/flags');
+    },
+
+    // Null clears the override, going back to the server's config
+    set: async (name: FeatureFlagName, enabled: boolean | null): Promise<FeatureFlag> => {
+      return httpPut(`/api/flags/${name}`, { enabled });
+    },
+  },
+
+  // Backend settings, such as feature flags and whether onboarding was dismissed
+  settings: {
+    // Called whenever a setting is set or cleared, by this client or another
+    onChange: (callback: (change: SettingChange) => void): (() => void) => {
+      connectWebSocket();
+      return addWsListener('settings:change', callback as (payload: unknown) => void);
+    },
+  },
+
+  // Profiling API
+  profiling: {
+    launchTimings: async (): Promise<LaunchTimingStats[]> => {
+      return httpGet('/api/profiling/launch-timings');
+    },
+  },
+
+  // Notifications API
+  notifications: {
+    list: async (unreadOnly?: boolean): Promise<ListNotificationsResponse> => {
+      return httpGet(`/api/notifications${unreadOnly ? '?unread=true' : ''}`);
+    },
+
+    markRead: async (id: number): Promise<void> => {
+      await httpPost(`/api/notifications/${id}/read`, {});
+    },
+
+    markAllRead: async (): Promise<void> => {
+      await httpPost('/api/notifications/read-all', {});
+    },
+
+    streamAlerts: async (): Promise<ListStreamAlertsResponse> => {
+      return httpGet('/api/alerts');
+    },
+
+    onNotification: (callback: (notification: NotificationRecord) => void): (() => void) => {
+      connectWebSocket();
+      return addWsListener('notification', callback as (payload: unknown) => void);
+    },
+  },
+
+  // Project notifiers API
+  notifiers: {
+    list: async (projectId: number): Promise<NotifierRecord[]> => {
+      return httpGet(`/api/projects/${projectId}/notifiers`);
+    },
+
+    create: async (projectId: number, request: CreateNotifierRequest): Promise<NotifierRecord> => {
+      return httpPost(`/api/projects/${projectId}/notifiers`, request);
+    },
+
+    remove: async (projectId: number, notifierId: number): Promise<void> => {
+      await httpDelete(`/api/projects/${projectId}/notifiers/${notifierId}`);
+    },
+
+    test: async (projectId: number, notifierId: number): Promise<void> => {
+      await httpPost(`/api/projects/${projectId}/notifiers/${notifierId}/test`, {});
+    },
+  },
+
+  // Plugins API
+  plugins: {
+    list: async (): Promise<PluginInfo[]> => {
+      return httpGet('/api/plugins');
+    },
+
+    runAction: async (plugin: string, action: string, request: RunPluginActionRequest): Promise<unknown> => {
+      return httpPost(`/api/plugins/${plugin}/actions/${action}`, request);
+    },
   },
 };
 
@@ -412,10 +1152,23 @@ if (typeof window !== 'undefined') {
 
 // Re-export types for consumers
 export type {
+  BuildDiagnostic,
   BuildEvent,
   BuildProduct,
+  ConfigIssue,
+  DestinationPreset,
+  DestinationPresetId,
+  DeviceClass,
+  NotificationRecord,
+  NotifierRecord,
+  Platform,
+  PluginInfo,
+  ProjectRecord,
+  SessionCommandRecord,
+  SessionRestartEvent,
   Simulator,
   StreamFrame,
   StreamLogEvent,
+  UnifiedProject,
   XcodeProject,
 };
@@ -6,7 +6,7 @@ import { ScrollArea } from "@/components/ui/scroll-area"
 import { Play, FolderOpen, Loader2, CheckCircle, XCircle, ChevronDown, ChevronUp, Terminal, ArrowLeft } from "lucide-react"
 import { StreamViewer } from "@/components/StreamViewer"
 import { ProjectSelector } from "@/components/ProjectSelector"
-import { api, type BuildEvent, type BuildProduct, type Simulator, type StreamLogEvent, type ProjectRecord } from "@/lib/api"
+import { api, applySimulatorChange, destinationForSimulator, type BuildEvent, type BuildProduct, type Simulator, type StreamLogEvent, type ProjectRecord } from "@/lib/api"
 
 type BuildState =
   | { status: "idle" }
@@ -59,7 +59,7 @@ export function ProjectEditor() {
       const data = await api.xcode.discover({ path })
       setSchemes(data.schemes || [])
       if (data.schemes?.length > 0) {
-        setSelectedScheme(data.schemes[0])
+        setSelectedScheme(data.suggestedScheme || data.schemes[0])
       }
     } catch (err) {
       console.error("Failed to discover schemes:", err)
@@ -88,6 +88,13 @@ export function ProjectEditor() {
       .catch((err) => console.error("Failed to fetch simulators:", err))
   }, [])
 
+  // Keep the simulator list current as devices are added, removed, booted or shut down
+  useEffect(() => {
+    return api.simulator.onDeviceChange((change) => {
+      setSimulators((current) => applySimulatorChange(current, change))
+    })
+  }, [])
+
   // Subscribe to simulator logs when streaming starts
   useEffect(() => {
     if (buildState.status !== "streaming") {
@@ -98,14 +105,14 @@ export function ProjectEditor() {
 
     const formatLogEvent = (event: StreamLogEvent): string => {
       const timestamp = new Date().toLocaleTimeString()
-      switch (event.type) {
-        case "info":
+      switch (event.event) {
+        case "log.info":
           return `[${timestamp}] INFO: ${event.message}`
-        case "error":
+        case "log.error":
           return `[${timestamp}] ERROR: ${event.message}`
-        case "debug":
+        case "log.debug":
           return `[${timestamp}] DEBUG: ${event.message}`
-        case "frame":
+        case "log.frame":
           return `[${timestamp}] FRAME: #${event.frameNumber}`
         default:
           return `[${timestamp}] ${JSON.stringify(event)}`
@@ -137,13 +144,13 @@ export function ProjectEditor() {
 
       const buildResult = await new Promise<{ success: boolean; products: BuildProduct[]; buildDir?: string; error?: string }>((resolve) => {
         const unsubscribeBuild = api.xcode.onBuildEvent((event: BuildEvent) => {
-          if (event.type === "output" && event.line) {
+          if (event.event === "build.output" && event.line) {
             console.log("[BUILD]", event.line)
             lines.push(event.line)
             setBuildState({ status: "building", lines: [...lines] })
-          } else if (event.type === "started") {
+          } else if (event.event === "build.started") {
             console.log("[BUILD] Started:", event.scheme)
-          } else if (event.type === "completed") {
+          } else if (event.event === "build.completed") {
             console.log("[BUILD] Completed:", event.success ? "SUCCESS" : "FAILED")
             unsubscribeBuild()
             resolve({
@@ -151,7 +158,7 @@ export function ProjectEditor() {
               products: event.products || [],
               buildDir: event.buildDir,
             })
-          } else if (event.type === "error") {
+          } else if (event.event === "build.error") {
             console.error("[BUILD] Error:", event.message)
             unsubscribeBuild()
             resolve({
@@ -162,9 +169,11 @@ export function ProjectEditor() {
           }
         })
 
+        const simulator = simulators.find((sim) => sim.udid === selectedSimulator)
         api.xcode.startBuild({
           path: project.path,
           scheme: selectedScheme,
+          destination: destinationForSimulator(simulator),
         })
       })
 
@@ -197,11 +206,8 @@ export function ProjectEditor() {
         appPath: buildProducts[0].path,
       })
 
-      await api.simulator.startStream({
-        udid: selectedSimulator,
-        fps: 60,
-        quality: 0.7,
-      })
+      // Frame rate and quality default to the simulator's device class
+      await api.simulator.startStream({ udid: selectedSimulator })
 
       setBuildState({ status: "streaming", udid: selectedSimulator })
     } catch (err) {
@@ -427,7 +433,10 @@ export function ProjectEditor() {
         {/* Right side - Simulator Stream */}
         <div className="flex-1 flex items-center justify-center min-w-0 min-h-0 overflow-hidden bg-black/20 rounded-xl">
           {buildState.status === "streaming" ? (
-            <StreamViewer udid={(buildState as { udid: string }).udid} />
+            <StreamViewer
+              udid={(buildState as { udid: string }).udid}
+              simulator={simulators.find((sim) => sim.udid === (buildState as { udid: string }).udid)}
+            />
           ) : (
             <div className="flex flex-col items-center justify-center gap-4 text-muted-foreground">
               <div className="w-[200px] h-[400px] border-2 border-dashed border-border rounded-3xl flex items-center justify-center">
@@ -0,0 +1,33 @@
+#!/usr/bin/env node
+/**
+ * Generate the frontend API type definitions from the shared backend types.
+ *
+ * Usage:
+ *   node scripts/generate-types.js          Write frontend/src/lib/api-types.ts
+ *   node scripts/generate-types.js --check  Fail if the generated file is out of date
+ */
+
+const fs = require('fs');
+const path = require('path');
+
+const SOURCE = path.join(__dirname, '../src/shared/ipc-types.ts');
+const TARGET = path.join(__dirname, '../frontend/src/lib/api-types.ts');
+
+const HEADER = `// This file is generated by \`pnpm generate-types\` from app/src/shared/ipc-types.ts.
+// Do not edit it by hand.
+
+`;
+
+const generated = HEADER + fs.readFileSync(SOURCE, 'utf-8');
+
+if (process.argv.includes('--check')) {
+  const current = fs.existsSync(TARGET) ? fs.readFileSync(TARGET, 'utf-8') : '';
+  if (current !== generated) {
+    console.error(`${path.relative(process.cwd(), TARGET)} is out of date. Run \`pnpm generate-types\`.`);
+    process.exit(1);
+  }
+  console.log('API types are up to date');
+} else {
+  fs.writeFileSync(TARGET, generated);
+  console.log(`Wrote ${path.relative(process.cwd(), TARGET)}`);
+}
@@ -0,0 +1,518 @@
+/**
+ * Server configuration, read from environment variables
+ */
+
+import * as fs from 'fs';
+import * as path from 'path';
+import type { LogHistorySettings } from './services/log-history';
+import type { SessionRestartPolicy, StreamSettings } from './services/simulator';
+import type { StreamAlertSettings } from './services/stream-health';
+import type { ResourceGuardSettings } from './services/guardrails';
+import type { StorageSettings } from './services/storage';
+import { S3_SECRET_ACCESS_KEY_SECRET } from './services/storage';
+import type { DatabaseSettings } from './services/data-store';
+import { isPostgresUrl } from './services/data-store';
+import type { SecretsSettings } from './services/secrets';
+import type { RequestRecordingSettings } from './services/request-recorder';
+import type { UrlPolicy } from './services/url-policy';
+import { DEFAULT_RETRYABLE_PATTERNS } from './services/retry';
+import type { RetryPolicy } from './services/retry';
+import { HELPER_SOURCES, DEFAULT_HELPER_ORDER, findHelperBinary, describeHelperLookup } from './services/helper-binaries';
+import type { HelperLayout, HelperLookupSettings, HelperSource } from './services/helper-binaries';
+import { getDatabasePath } from './services/database';
+import { isFeatureFlagName } from './services/feature-flags';
+import { validateLogFilter } from './logger';
+import type { ConfigIssue, FeatureFlagName } from '../shared/ipc-types';
+
+// WebSocket message channels, each with its own buffering limits
+export type ChannelName = 'frames' | 'logs' | 'builds' | 'events';
+
+// What to do when a subscriber's send buffer exceeds the channel capacity:
+// - drop:  skip messages for that subscriber and send a `lagged` notice once it catches up
+// - pause: stop reading from the upstream source until the subscriber drains
+export type LagPolicy = 'drop' | 'pause';
+
+export interface ChannelSettings {
+  capacityBytes: number;
+  lagPolicy: LagPolicy;
+}
+
+const DEFAULT_CHANNELS: Record<ChannelName, ChannelSettings> = {
+  frames: { capacityBytes: 8 * 1024 * 1024, lagPolicy: 'drop' },
+  logs: { capacityBytes: 1024 * 1024, lagPolicy: 'drop' },
+  builds: { capacityBytes: 4 * 1024 * 1024, lagPolicy: 'pause' },
+  events: { capacityBytes: 1024 * 1024, lagPolicy: 'drop' },
+};
+
+export interface PortRange {
+  start: number;
+  end: number;
+}
+
+const AUTO_PORT_COUNT = 100;
+
+export interface Config {
+  // HTTP/WebSocket port ($PLASMA_PORT). Updated to the port actually used after startup.
+  port: number;
+  // Ports to try when `port` is taken ($PLASMA_PORT_RANGE, e.g. `3001-3010`, or $PLASMA_PORT_AUTO=1
+  // for the next 100 ports). Null to fail instead.
+  portSearch: PortRange | null;
+  // Where the running instance's port and PID are written for wrapper apps
+  // ($PLASMA_DISCOVERY_FILE, null for instance.json in the data directory)
+  discoveryFile: string | null;
+  // Base URL used for links sent outside the app, e.g. chat notifications ($PLASMA_PUBLIC_URL)
+  publicUrl: string;
+  // Simulators (UDIDs or names, `*` for all) to keep a streaming session ready for while booted
+  // ($PLASMA_PREWARM_SIMULATORS, comma-separated)
+  prewarmSimulators: string[];
+  // Per-channel WebSocket buffering
+  // ($PLASMA_CHANNEL_<NAME>_CAPACITY in bytes, $PLASMA_CHANNEL_<NAME>_LAG_POLICY)
+  channels: Record<ChannelName, ChannelSettings>;
+  // Simulator log history kept for replay ($PLASMA_LOG_HISTORY_MINUTES,
+  // $PLASMA_LOG_HISTORY_MAX_EVENTS per simulator, $PLASMA_LOG_HISTORY_PERSIST=1 to store in the database)
+  logHistory: LogHistorySettings;
+  // Restarting simulator-server after it exits unexpectedly ($PLASMA_SESSION_RESTART_MAX_ATTEMPTS,
+  // 0 to disable, $PLASMA_SESSION_RESTART_INITIAL_DELAY_MS, $PLASMA_SESSION_RESTART_MAX_DELAY_MS)
+  sessionRestart: SessionRestartPolicy;
+  // Retrying simctl boots, installs and launches that fail with transient errors
+  // ($PLASMA_RETRY_MAX_ATTEMPTS in total, 1 to disable, $PLASMA_RETRY_INITIAL_DELAY_MS,
+  // $PLASMA_RETRY_MAX_DELAY_MS, and $PLASMA_RETRY_PATTERNS, comma-separated substrings of retryable errors)
+  retry: RetryPolicy;
+  // How long responses to requests with an Idempotency-Key are replayed to retries
+  // ($PLASMA_IDEMPOTENCY_WINDOW_MS, 0 to ignore the header)
+  idempotencyWindowMs: number;
+  // Gzip or deflate compression of JSON and event stream responses for clients that accept it
+  // ($PLASMA_COMPRESSION=0 to send them uncompressed, e.g. behind a proxy that compresses)
+  compression: boolean;
+  // Stopping simulator-server once no stream has used its session for this long
+  // ($PLASMA_SESSION_IDLE_TIMEOUT_MS, 0 to keep sessions until Plasma exits)
+  sessionIdleTimeoutMs: number;
+  // Serve synthetic simulators with generated streams instead of real ones, for demos and
+  // development without Xcode (--fake-devices or $PLASMA_FAKE_DEVICES=1)
+  fakeDevices: boolean;
+  // Stream settings used instead of the per-device-class defaults ($PLASMA_STREAM_FPS, 1-120,
+  // $PLASMA_STREAM_QUALITY, 0-1)
+  streamDefaults: Partial<StreamSettings>;
+  // Built frontend loaded by the packaged app ($PLASMA_FRONTEND_DIR)
+  frontendDir: string;
+  // Root for job temp directories ($PLASMA_WORKSPACE_DIR, null for `workspace` in the data directory)
+  workspaceDir: string | null;
+  // Log filter at startup, e.g. `info,simulator=trace` ($PLASMA_LOG). Can be changed at runtime.
+  logFilter: string;
+  // Stream health alerts ($PLASMA_STREAM_ALERTS=0 to disable, $PLASMA_ALERT_MIN_FPS_PERCENT of the
+  // target fps, $PLASMA_ALERT_MAX_FRAME_GAP_MS, $PLASMA_ALERT_MAX_RESTARTS within
+  // $PLASMA_ALERT_RESTART_WINDOW_MINUTES, the `alerts.webhook_url` secret or $PLASMA_ALERT_WEBHOOK_URL
+  // to also POST alerts)
+  streamAlerts: StreamAlertSettings;
+  // Where artifacts like finished build logs are kept ($PLASMA_STORAGE, `local` or `s3`).
+  // Local storage uses $PLASMA_STORAGE_DIR (null for the data directory); S3-compatible storage uses
+  // $PLASMA_S3_ENDPOINT, $PLASMA_S3_BUCKET, $PLASMA_S3_REGION, $PLASMA_S3_PREFIX and
+  // $PLASMA_S3_ACCESS_KEY_ID and the `s3.secret_access_key` secret, or $PLASMA_S3_SECRET_ACCESS_KEY
+  // (or the AWS_ equivalents)
+  storage: StorageSettings;
+  // Where projects, builds, notifications, settings and secrets are kept: SQLite by default, or
+  // Postgres for a shared instance ($PLASMA_DATABASE_URL, e.g. `postgres://plasma@db/plasma`), and
+  // how long a SQLite write waits for another writer's lock ($PLASMA_DATABASE_BUSY_TIMEOUT_MS)
+  database: DatabaseSettings;
+  // Refuse to boot simulators or start builds when free memory is below $PLASMA_GUARD_MIN_FREE_MEMORY_MB
+  // or the load average is above $PLASMA_GUARD_MAX_LOAD_PERCENT of the CPU count (0, the default, disables each)
+  resourceGuards: ResourceGuardSettings;
+  // Watermark template for every stream, e.g. `CONFIDENTIAL {user} {timestamp}` ($PLASMA_WATERMARK).
+  // Clients can't turn it off; without it, each stream can ask for its own.
+  watermark: string | null;
+  // Draw circles where the simulator is touched into every stream's frames ($PLASMA_SHOW_TOUCHES).
+  // Each stream can still turn them on or off with `showTouches`.
+  showTouches: boolean;
+  // URLs clients may open on simulators: comma-separated patterns with `*` wildcards, e.g.
+  // "https://*.example.com/*,myapp:*" ($PLASMA_URL_ALLOW, empty allows all) and $PLASMA_URL_DENY
+  urlPolicy: UrlPolicy;
+  // How often the simulator list is checked for changes to push to clients, while any are
+  // connected ($PLASMA_SIMULATOR_WATCH_INTERVAL_MS, 0 to disable)
+  simulatorWatchIntervalMs: number;
+  // How long a simulator listing is reused before simctl is run again ($PLASMA_SIMULATOR_LIST_TTL_MS,
+  // 0 to always run it)
+  simulatorListTtlMs: number;
+  // Experimental features turned on or off for this instance ($PLASMA_FEATURES, e.g.
+  // "android,webrtc_streaming" or "-proxy"); the flags API can override them
+  featureFlags: Partial<Record<FeatureFlagName, boolean>>;
+  // Where the key encrypting secrets comes from: a master passphrase ($PLASMA_SECRETS_PASSPHRASE),
+  // or else a generated key in the macOS keychain ($PLASMA_SECRETS_KEYCHAIN=0 to disable)
+  secrets: SecretsSettings;
+  // Recording of API requests for debugging, also turned on and off through the admin API
+  // ($PLASMA_REQUEST_RECORDING=1, $PLASMA_REQUEST_RECORDING_CAPACITY, $PLASMA_REQUEST_RECORDING_MAX_BODY_BYTES,
+  // and $PLASMA_REQUEST_RECORDING_REDACT for extra field names to redact)
+  requestRecording: RequestRecordingSettings;
+  // Where simulator-server and AXe are looked for: $PLASMA_HELPER_LAYOUT=libexec for packages that
+  // install them under libexec/plasma (default `dev`), $PLASMA_HELPER_LOOKUP to set the order
+  // (e.g. "env,libexec,path"), and $PLASMA_LIBEXEC_DIR for another directory than libexec/plasma
+  helperLookup: HelperLookupSettings;
+}
+
+// Environment variables that were set but couldn't be parsed, found while reading the config
+const parseIssues: ConfigIssue[] = [];
+
+function invalid(setting: string, message: string): void {
+  parseIssues.push({ setting, message, severity: 'error' });
+}
+
+function parseList(value: string | undefined): string[] {
+  return (value || '')
+    .split(',')
+    .map((item) => item.trim())
+    .filter(Boolean);
+}
+
+function parseChannels(): Record<ChannelName, ChannelSettings> {
+  const channels = { ...DEFAULT_CHANNELS };
+  for (const name of Object.keys(channels) as ChannelName[]) {
+    const prefix = `PLASMA_CHANNEL_${name.toUpperCase()}`;
+    const lagPolicy = process.env[`${prefix}_LAG_POLICY`];
+    if (lagPolicy && lagPolicy !== 'drop' && lagPolicy !== 'pause') {
+      invalid(`${prefix}_LAG_POLICY`, `must be "drop" or "pause", got "${lagPolicy}"`);
+    }
+    channels[name] = {
+      capacityBytes: parseNumber(`${prefix}_CAPACITY`, channels[name].capacityBytes),
+      lagPolicy: lagPolicy === 'drop' || lagPolicy === 'pause' ? lagPolicy : channels[name].lagPolicy,
+    };
+  }
+  return channels;
+}
+
+/**
+ * Integer from an environment variable, or `fallback` (recording an issue) when it's set but invalid
+ */
+function parseNumber(name: string, fallback: number, min: number = 1): number {
+  const value = process.env[name];
+  if (!value) {
+    return fallback;
+  }
+  const parsed = Number(value);
+  if (!Number.isInteger(parsed) || parsed < min) {
+    invalid(name, `must be a whole number of at least ${min}, got "${value}"`);
+    return fallback;
+  }
+  return parsed;
+}
+
+function parsePortSearch(port: number): PortRange | null {
+  const range = process.env.PLASMA_PORT_RANGE;
+  if (range) {
+    const match = /^(\d+)-(\d+)$/.exec(range.trim());
+    if (!match) {
+      invalid('PLASMA_PORT_RANGE', `must look like "3001-3010", got "${range}"`);
+      return null;
+    }
+    return { start: Number(match[1]), end: Number(match[2]) };
+  }
+  if (process.env.PLASMA_PORT_AUTO === '1' || process.env.PLASMA_PORT_AUTO === 'true') {
+    return { start: port + 1, end: Math.min(port + AUTO_PORT_COUNT, 65535) };
+  }
+  return null;
+}
+
+function parseFeatureFlags(): Partial<Record<FeatureFlagName, boolean>> {
+  const flags: Partial<Record<FeatureFlagName, boolean>> = {};
+  for (const item of parseList(process.env.PLASMA_FEATURES)) {
+    const name = item.replace(/^-/, '');
+    if (!isFeatureFlagName(name)) {
+      invalid('PLASMA_FEATURES', `unknown feature flag "${name}"`);
+      continue;
+    }
+    flags[name] = !item.startsWith('-');
+  }
+  return flags;
+}
+
+function parseStorage(): StorageSettings {
+  const backend = process.env.PLASMA_STORAGE || 'local';
+  if (backend !== 'local' && backend !== 's3') {
+    invalid('PLASMA_STORAGE', `must be "local" or "s3", got "${backend}"`);
+  }
+  if (backend !== 's3') {
+    return { backend: 'local', dir: process.env.PLASMA_STORAGE_DIR || null };
+  }
+  return {
+    backend: 's3',
+    s3: {
+      endpoint: process.env.PLASMA_S3_ENDPOINT || '',
+      bucket: process.env.PLASMA_S3_BUCKET || '',
+      region: process.env.PLASMA_S3_REGION || 'us-east-1',
+      accessKeyId: process.env.PLASMA_S3_ACCESS_KEY_ID || process.env.AWS_ACCESS_KEY_ID || '',
+      secretAccessKey: process.env.PLASMA_S3_SECRET_ACCESS_KEY || process.env.AWS_SECRET_ACCESS_KEY || '',
+      prefix: process.env.PLASMA_S3_PREFIX || '',
+    },
+  };
+}
+
+function parseDatabase(): DatabaseSettings {
+  const url = process.env.PLASMA_DATABASE_URL || null;
+  if (url && !isPostgresUrl(url)) {
+    invalid('PLASMA_DATABASE_URL', `must be a postgres:// URL, got "${url}"`);
+  }
+  return {
+    url: url && isPostgresUrl(url) ? url : null,
+    busyTimeoutMs: parseNumber('PLASMA_DATABASE_BUSY_TIMEOUT_MS', 5000, 0),
+  };
+}
+
+function parseHelperLookup(): HelperLookupSettings {
+  let layout: HelperLayout = 'dev';
+  const layoutValue = process.env.PLASMA_HELPER_LAYOUT;
+  if (layoutValue === 'dev' || layoutValue === 'libexec') {
+    layout = layoutValue;
+  } else if (layoutValue) {
+    invalid('PLASMA_HELPER_LAYOUT', `must be "dev" or "libexec", got "${layoutValue}"`);
+  }
+
+  let order = DEFAULT_HELPER_ORDER[layout];
+  if (process.env.PLASMA_HELPER_LOOKUP) {
+    const sources = parseList(process.env.PLASMA_HELPER_LOOKUP);
+    const unknown = sources.filter((source) => !HELPER_SOURCES.includes(source as HelperSource));
+    if (unknown.length > 0 || sources.length === 0) {
+      invalid('PLASMA_HELPER_LOOKUP', `must list sources out of ${HELPER_SOURCES.join(', ')}, got "${process.env.PLASMA_HELPER_LOOKUP}"`);
+    } else {
+      order = Array.from(new Set(sources as HelperSource[]));
+    }
+  }
+
+  return { layout, order, libexecDir: process.env.PLASMA_LIBEXEC_DIR || null };
+}
+
+function parseStreamDefaults(): Partial<StreamSettings> {
+  const defaults: Partial<StreamSettings> = {};
+  if (process.env.PLASMA_STREAM_FPS) {
+    defaults.fps = Number(process.env.PLASMA_STREAM_FPS);
+  }
+  if (process.env.PLASMA_STREAM_QUALITY) {
+    defaults.quality = Number(process.env.PLASMA_STREAM_QUALITY);
+  }
+  return defaults;
+}
+
+let config: Config | null = null;
+
+export function getConfig(): Config {
+  if (!config) {
+    const port = Number(process.env.PLASMA_PORT || '3001');
+    config = {
+      port,
+      portSearch: parsePortSearch(port),
+      discoveryFile: process.env.PLASMA_DISCOVERY_FILE || null,
+      publicUrl: process.env.PLASMA_PUBLIC_URL || `http://localhost:${port}`,
+      prewarmSimulators: parseList(process.env.PLASMA_PREWARM_SIMULATORS),
+      channels: parseChannels(),
+      logHistory: {
+        retentionMinutes: parseNumber('PLASMA_LOG_HISTORY_MINUTES', 10),
+        maxEventsPerSimulator: parseNumber('PLASMA_LOG_HISTORY_MAX_EVENTS', 5000),
+        persist: process.env.PLASMA_LOG_HISTORY_PERSIST === '1' || process.env.PLASMA_LOG_HISTORY_PERSIST === 'true',
+      },
+      sessionRestart: {
+        maxAttempts: parseNumber('PLASMA_SESSION_RESTART_MAX_ATTEMPTS', 5, 0),
+        initialDelayMs: parseNumber('PLASMA_SESSION_RESTART_INITIAL_DELAY_MS', 1000),
+        maxDelayMs: parseNumber('PLASMA_SESSION_RESTART_MAX_DELAY_MS', 30000),
+      },
+      retry: {
+        maxAttempts: parseNumber('PLASMA_RETRY_MAX_ATTEMPTS', 3),
+        initialDelayMs: parseNumber('PLASMA_RETRY_INITIAL_DELAY_MS', 500, 0),
+        maxDelayMs: parseNumber('PLASMA_RETRY_MAX_DELAY_MS', 5000, 0),
+        retryablePatterns: process.env.PLASMA_RETRY_PATTERNS ? parseList(process.env.PLASMA_RETRY_PATTERNS) : DEFAULT_RETRYABLE_PATTERNS,
+      },
+      idempotencyWindowMs: parseNumber('PLASMA_IDEMPOTENCY_WINDOW_MS', 10 * 60 * 1000, 0),
+      compression: process.env.PLASMA_COMPRESSION !== '0' && process.env.PLASMA_COMPRESSION !== 'false',
+      sessionIdleTimeoutMs: parseNumber('PLASMA_SESSION_IDLE_TIMEOUT_MS', 60000, 0),
+      fakeDevices:
+        process.argv.includes('--fake-devices') ||
+        process.env.PLASMA_FAKE_DEVICES === '1' ||
+        process.env.PLASMA_FAKE_DEVICES === 'true',
+      streamDefaults: parseStreamDefaults(),
+      // __dirname is dist/main/ (or src/main/ when run from source)
+      frontendDir: process.env.PLASMA_FRONTEND_DIR || path.join(__dirname, '../../frontend/dist'),
+      workspaceDir: process.env.PLASMA_WORKSPACE_DIR || null,
+      logFilter: process.env.PLASMA_LOG || 'info',
+      streamAlerts: {
+        enabled: process.env.PLASMA_STREAM_ALERTS !== '0' && process.env.PLASMA_STREAM_ALERTS !== 'false',
+        minFpsRatio: parseNumber('PLASMA_ALERT_MIN_FPS_PERCENT', 50) / 100,
+        maxFrameGapMs: parseNumber('PLASMA_ALERT_MAX_FRAME_GAP_MS', 3000),
+        maxRestarts: parseNumber('PLASMA_ALERT_MAX_RESTARTS', 3),
+        restartWindowMinutes: parseNumber('PLASMA_ALERT_RESTART_WINDOW_MINUTES', 10),
+        webhookUrl: process.env.PLASMA_ALERT_WEBHOOK_URL || null,
+      },
+      storage: parseStorage(),
+      database: parseDatabase(),
+      resourceGuards: {
+        minFreeMemoryMb: parseNumber('PLASMA_GUARD_MIN_FREE_MEMORY_MB', 0, 0),
+        maxLoadPercent: parseNumber('PLASMA_GUARD_MAX_LOAD_PERCENT', 0, 0),
+      },
+      watermark: process.env.PLASMA_WATERMARK || null,
+      showTouches: process.env.PLASMA_SHOW_TOUCHES === '1' || process.env.PLASMA_SHOW_TOUCHES === 'true',
+      urlPolicy: {
+        allow: parseList(process.env.PLASMA_URL_ALLOW),
+        deny: parseList(process.env.PLASMA_URL_DENY),
+      },
+      simulatorWatchIntervalMs: parseNumber('PLASMA_SIMULATOR_WATCH_INTERVAL_MS', 5000, 0),
+      simulatorListTtlMs: parseNumber('PLASMA_SIMULATOR_LIST_TTL_MS', 2000, 0),
+      featureFlags: parseFeatureFlags(),
+      secrets: {
+        passphrase: process.env.PLASMA_SECRETS_PASSPHRASE || null,
+        keychain: process.platform === 'darwin' && process.env.PLASMA_SECRETS_KEYCHAIN !== '0',
+      },
+      requestRecording: {
+        enabled: process.env.PLASMA_REQUEST_RECORDING === '1',
+        capacity: parseNumber('PLASMA_REQUEST_RECORDING_CAPACITY', 200),
+        maxBodyBytes: parseNumber('PLASMA_REQUEST_RECORDING_MAX_BODY_BYTES', 16 * 1024),
+        redactKeys: parseList(process.env.PLASMA_REQUEST_RECORDING_REDACT),
+      },
+      helperLookup: parseHelperLookup(),
+    };
+  }
+  return config;
+}
+
+/**
+ * Nearest existing directory at or above `target`
+ */
+function existingAncestor(target: string): string {
+  let current = path.resolve(target);
+  while (!fs.existsSync(current) && path.dirname(current) !== current) {
+    current = path.dirname(current);
+  }
+  return current;
+}
+
+function isWritable(target: string): boolean {
+  try {
+    fs.accessSync(target, fs.constants.W_OK);
+    return true;
+  } catch {
+    return false;
+  }
+}
+
+/**
+ * Check the config for values that would otherwise only fail later, deep inside a handler.
+ * Errors prevent the server from starting; warnings are only reported.
+ */
+export function validateConfig(config: Config = getConfig()): ConfigIssue[] {
+  const issues = [...parseIssues];
+  const error = (setting: string, message: string) => issues.push({ setting, message, severity: 'error' });
+
+  if (!Number.isInteger(config.port) || config.port < 1 || config.port > 65535) {
+    error('PLASMA_PORT', `must be a port number between 1 and 65535, got "${process.env.PLASMA_PORT}"`);
+  }
+
+  const range = config.portSearch;
+  if (range && (range.start < 1 || range.end > 65535 || range.start > range.end)) {
+    error('PLASMA_PORT_RANGE', `must be an ascending range within 1-65535, got ${range.start}-${range.end}`);
+  }
+
+  try {
+    new URL(config.publicUrl);
+  } catch {
+    error('PLASMA_PUBLIC_URL', `must be an absolute URL, got "${config.publicUrl}"`);
+  }
+
+  try {
+    const databasePath = getDatabasePath();
+    if (databasePath !== ':memory:') {
+      const target = fs.existsSync(databasePath) ? databasePath : existingAncestor(path.dirname(databasePath));
+      if (!isWritable(target)) {
+        error('PLASMA_DATABASE_PATH', `${databasePath} is not writable (checked ${target})`);
+      }
+    }
+  } catch (err) {
+    error('PLASMA_DATABASE_PATH', `could not prepare the data directory: ${err instanceof Error ? err.message : err}`);
+  }
+
+  if (config.workspaceDir && !isWritable(existingAncestor(config.workspaceDir))) {
+    error('PLASMA_WORKSPACE_DIR', `${config.workspaceDir} is not writable`);
+  }
+
+  if (config.storage.backend === 'local') {
+    if (config.storage.dir && !isWritable(existingAncestor(config.storage.dir))) {
+      error('PLASMA_STORAGE_DIR', `${config.storage.dir} is not writable`);
+    }
+  } else {
+    const { s3 } = config.storage;
+    try {
+      new URL(s3.endpoint);
+    } catch {
+      error('PLASMA_S3_ENDPOINT', `must be an absolute URL, got "${s3.endpoint}"`);
+    }
+    if (!s3.bucket) {
+      error('PLASMA_S3_BUCKET', 'is required when PLASMA_STORAGE is "s3"');
+    }
+    if (!s3.accessKeyId) {
+      error('PLASMA_S3_ACCESS_KEY_ID', 'is required when PLASMA_STORAGE is "s3"');
+    }
+    // The secret may be in a shared database, which isn't read here
+    if (!s3.secretAccessKey) {
+      issues.push({
+        setting: 'PLASMA_S3_SECRET_ACCESS_KEY',
+        message: `is not set, so S3 requests fail unless a "${S3_SECRET_ACCESS_KEY_SECRET}" secret is stored`,
+        severity: 'warning',
+      });
+    }
+  }
+
+  if (!fs.existsSync(path.join(config.frontendDir, 'index.html'))) {
+    issues.push({
+      setting: 'PLASMA_FRONTEND_DIR',
+      message: `${config.frontendDir} has no index.html; build the frontend before packaging the app`,
+      severity: 'warning',
+    });
+  }
+
+  // A package is expected to ship its helpers; a missing one means a broken install
+  if (config.helperLookup.layout === 'libexec' && process.platform === 'darwin') {
+    for (const name of ['simulator-server', 'axe'] as const) {
+      if (!findHelperBinary(name, config.helperLookup)) {
+        issues.push({
+          setting: 'PLASMA_HELPER_LAYOUT',
+          message: `${name} was not found (looked in ${describeHelperLookup(name, config.helperLookup)})`,
+          severity: 'warning',
+        });
+      }
+    }
+  }
+
+  const { fps, quality } = config.streamDefaults;
+  if (fps !== undefined && (!Number.isInteger(fps) || fps < 1 || fps > 120)) {
+    error('PLASMA_STREAM_FPS', `must be a whole number between 1 and 120, got "${process.env.PLASMA_STREAM_FPS}"`);
+  }
+  if (quality !== undefined && (isNaN(quality) || quality <= 0 || quality > 1)) {
+    error('PLASMA_STREAM_QUALITY', `must be a number above 0 and at most 1, got "${process.env.PLASMA_STREAM_QUALITY}"`);
+  }
+
+  const { minFpsRatio, webhookUrl } = config.streamAlerts;
+  if (minFpsRatio > 1) {
+    error('PLASMA_ALERT_MIN_FPS_PERCENT', `must be at most 100, got "${process.env.PLASMA_ALERT_MIN_FPS_PERCENT}"`);
+  }
+  if (webhookUrl) {
+    try {
+      new URL(webhookUrl);
+    } catch {
+      error('PLASMA_ALERT_WEBHOOK_URL', `must be an absolute URL, got "${webhookUrl}"`);
+    }
+  }
+
+  const logFilterError = validateLogFilter(config.logFilter);
+  if (logFilterError) {
+    error('PLASMA_LOG', logFilterError);
+  }
+
+  if (config.sessionRestart.initialDelayMs > config.sessionRestart.maxDelayMs) {
+    error(
+      'PLASMA_SESSION_RESTART_INITIAL_DELAY_MS',
+      `(${config.sessionRestart.initialDelayMs}) must not exceed PLASMA_SESSION_RESTART_MAX_DELAY_MS (${config.sessionRestart.maxDelayMs})`
+    );
+  }
+
+  return issues;
+}
+
+/**
+ * One line per issue, for logs and startup errors
+ */
+export function formatConfigIssues(issues: ConfigIssue[]): string {
+  return issues.map((issue) => `  - ${issue.setting} ${issue.message}`).join('\n');
+}
@@ -0,0 +1,130 @@
+import * as http from 'http';
+import * as fs from 'fs';
+import * as path from 'path';
+
+const CONTENT_TYPES: Record<string, string> = {
+  '.log': 'text/plain; charset=utf-8',
+  '.txt': 'text/plain; charset=utf-8',
+  '.json': 'application/json',
+  '.mp4': 'video/mp4',
+  '.mov': 'video/quicktime',
+  '.mjpeg': 'video/x-motion-jpeg',
+  '.jpg': 'image/jpeg',
+  '.jpeg': 'image/jpeg',
+  '.png': 'image/png',
+  '.zip': 'application/zip',
+  '.ipa': 'application/octet-stream',
+  '.apk': 'application/vnd.android.package-archive',
+};
+
+export interface SendFileOptions {
+  // Suggested file name for downloads (sets Content-Disposition: attachment)
+  downloadName?: string;
+  contentType?: string;
+}
+
+/**
+ * Detect a content type from a file extension
+ */
+export function contentTypeFor(filePath: string): string {
+  return CONTENT_TYPES[path.extname(filePath).toLowerCase()] || 'application/octet-stream';
+}
+
+/**
+ * Parse a single `Range: bytes=...` header. Returns null when the header is
+ * absent or uses multiple ranges (we then serve the whole file), and 'invalid'
+ * when the range cannot be satisfied.
+ */
+function parseRange(header: string | undefined, size: number): { start: number; end: number } | null | 'invalid' {
+  if (!header) {
+    return null;
+  }
+
+  const match = header.match(/^bytes=(\d*)-(\d*)$/);
+  if (!match) {
+    return null;
+  }
+
+  const [, startText, endText] = match;
+  let start: number;
+  let end: number;
+
+  if (startText === '' && endText === '') {
+    return 'invalid';
+  } else if (startText === '') {
+    // Suffix range: last N bytes
+    start = Math.max(size - parseInt(endText), 0);
+    end = size - 1;
+  } else {
+    start = parseInt(startText);
+    end = endText === '' ? size - 1 : Math.min(parseInt(endText), size - 1);
+  }
+
+  if (start >= size || start > end) {
+    return 'invalid';
+  }
+
+  return { start, end };
+}
+
+/**
+ * Stream a file to the response with Content-Length, content-type detection,
+ * and Range (206 Partial Content) support so large downloads can resume.
+ */
+export function sendFile(
+  req: http.IncomingMessage,
+  res: http.ServerResponse,
+  filePath: string,
+  options: SendFileOptions = {}
+): void {
+  let stats: fs.Stats;
+  try {
+    stats = fs.statSync(filePath);
+  } catch {
+    res.writeHead(404, { 'Content-Type': 'application/json' });
+    res.end(JSON.stringify({ error: 'File not found' }));
+    return;
+  }
+
+  const size = stats.size;
+  const headers: http.OutgoingHttpHeaders = {
+    'Content-Type': options.contentType || contentTypeFor(filePath),
+    'Accept-Ranges': 'bytes',
+    'Last-Modified': stats.mtime.toUTCString(),
+  };
+  if (options.downloadName) {
+    headers['Content-Disposition'] = `attachment; filename="${options.downloadName.replace(/"/g, '')}"`;
+  }
+
+  const range = parseRange(req.headers.range, size);
+
+  if (range === 'invalid') {
+    res.writeHead(416, { ...headers, 'Content-Range': `bytes */${size}` });
+    res.end();
+    return;
+  }
+
+  const start = range ? range.start : 0;
+  const end = range ? range.end : size - 1;
+  const length = size === 0 ? 0 : end - start + 1;
+
+  headers['Content-Length'] = length;
+  if (range) {
+    headers['Content-Range'] = `bytes ${start}-${end}/${size}`;
+  }
+
+  res.writeHead(range ? 206 : 200, headers);
+
+  if (req.method === 'HEAD' || length === 0) {
+    res.end();
+    return;
+  }
+
+  const stream = fs.createReadStream(filePath, { start, end });
+  stream.on('error', (err) => {
+    console.error('[server] File stream error:', err);
+    res.destroy(err);
+  });
+  req.on('close', () => stream.destroy());
+  stream.pipe(res);
+}
@@ -1,6 +1,7 @@
 import { app, BrowserWindow, ipcMain, dialog, nativeImage } from 'electron';
 import * as path from 'path';
 import { startServer } from './server';
+import { getConfig } from './config';
 import { killAllProcesses } from './services/process-manager';
 
 let mainWindow: BrowserWindow | null = null;
@@ -22,7 +23,14 @@ async function createWindow() {
   }
 
   // Start HTTP server (used by both Electron and browser modes)
-  await startServer();
+  let port: number;
+  try {
+    port = await startServer();
+  } catch (err) {
+    dialog.showErrorBox('Plasma could not start', err instanceof Error ? err.message : String(err));
+    app.quit();
+    return;
+  }
   console.log('HTTP server started');
 
   mainWindow = new BrowserWindow({
@@ -43,11 +51,11 @@ async function createWindow() {
     // In development, load from Vite dev server
     // Wait a bit for Vite to start
     await new Promise((resolve) => setTimeout(resolve, 1000));
-    mainWindow.loadURL('http://localhost:5173');
+    mainWindow.loadURL(`http://localhost:5173?apiPort=${port}`);
     mainWindow.webContents.openDevTools();
   } else {
     // In production, load from bundled frontend files
-    mainWindow.loadFile(path.join(__dirname, '../../frontend/dist/index.html'));
+    mainWindow.loadFile(path.join(getConfig().frontendDir, 'index.html'), { query: { apiPort: String(port) } });
   }
 
   mainWindow.on('closed', () => {
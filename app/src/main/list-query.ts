@@ -0,0 +1,185 @@
+/**
+ * Shared pagination, filtering, and sorting for list endpoints
+ *
+ *   ?limit=20              page size
+ *   ?cursor=<opaque>       continue from a previous page (see X-Next-Cursor)
+ *   ?sort=-created_at,name sort fields, `-` prefix for descending
+ *   ?state=Booted,Shutdown filter on an allowed field, comma-separated values match any
+ *
+ * Without `limit` or `cursor`, lists are returned whole, unless the endpoint has a `defaultLimit`,
+ * so clients written before pagination don't silently lose items.
+ *
+ * Sort and filter fields are always snake_case in the query, whatever the case of the items'
+ * fields: `?sort=-started_at` sorts builds by `startedAt`.
+ */
+
+export interface SortField {
+  // Field of the items, e.g. `startedAt`
+  field: string;
+  descending: boolean;
+}
+
+export interface ListQuery {
+  limit: number;
+  offset: number;
+  sort: SortField[];
+  // Lowercased values by field of the items
+  filters: Record<string, string[]>;
+}
+
+export interface ListQueryOptions<T> {
+  // Fields that can be used as `?field=value` filters
+  filterable?: Array<keyof T & string>;
+  // Fields that can be used in `?sort=`
+  sortable?: Array<keyof T & string>;
+  // Page size without `?limit=`; unbounded unless a cursor is passed
+  defaultLimit?: number;
+  maxLimit?: number;
+}
+
+export interface ListPage<T> {
+  items: T[];
+  total: number;
+  nextCursor: string | null;
+}
+
+export class ListQueryError extends Error {}
+
+// Page size when paginating with a cursor but no `?limit=`
+const DEFAULT_LIMIT = 100;
+const MAX_LIMIT = 1000;
+
+function encodeCursor(offset: number): string {
+  return Buffer.from(`offset:${offset}`).toString('base64url');
+}
+
+function decodeCursor(cursor: string): number {
+  const decoded = Buffer.from(cursor, 'base64url').toString('utf-8');
+  const match = decoded.match(/^offset:(\d+)$/);
+  if (!match) {
+    throw new ListQueryError('Invalid cursor');
+  }
+  return parseInt(match[1]);
+}
+
+/**
+ * Name of an item field in the query, e.g. `started_at` for `startedAt`
+ */
+export function toQueryName(field: string): string {
+  return field.replace(/[A-Z]/g, (letter) => `_${letter.toLowerCase()}`);
+}
+
+/**
+ * Parse and validate list query parameters
+ */
+export function parseListQuery<T>(params: URLSearchParams, options: ListQueryOptions<T> = {}): ListQuery {
+  const maxLimit = options.maxLimit ?? MAX_LIMIT;
+
+  const cursor = params.get('cursor');
+  let limit = options.defaultLimit ?? (cursor ? DEFAULT_LIMIT : Infinity);
+  const limitParam = params.get('limit');
+  if (limitParam !== null) {
+    limit = parseInt(limitParam);
+    if (isNaN(limit) || limit < 1) {
+      throw new ListQueryError('limit must be a positive integer');
+    }
+  }
+  if (limitParam !== null || cursor) {
+    limit = Math.min(limit, maxLimit);
+  }
+
+  const offset = cursor ? decodeCursor(cursor) : 0;
+
+  const sortable = new Map((options.sortable || []).map((field) => [toQueryName(field), field as string]));
+  const sort: SortField[] = [];
+  for (const raw of (params.get('sort') || '').split(',')) {
+    const value = raw.trim();
+    if (!value) {
+      continue;
+    }
+    const descending = value.startsWith('-');
+    const name = descending ? value.slice(1) : value;
+    const field = sortable.get(name);
+    if (!field) {
+      throw new ListQueryError(`Cannot sort by "${name}". Sortable fields: ${Array.from(sortable.keys()).join(', ') || 'none'}`);
+    }
+    sort.push({ field, descending });
+  }
+
+  const filters: Record<string, string[]> = {};
+  for (const field of options.filterable || []) {
+    const value = params.get(toQueryName(field));
+    if (value !== null && value !== '') {
+      filters[field] = value.split(',').map((v) => v.trim().toLowerCase());
+    }
+  }
+
+  return { limit, offset, sort, filters };
+}
+
+function compareValues(a: unknown, b: unknown): number {
+  if (a === b) return 0;
+  if (a === null || a === undefined) return 1;
+  if (b === null || b === undefined) return -1;
+  if (typeof a === 'number' && typeof b === 'number') return a - b;
+  return String(a).localeCompare(String(b));
+}
+
+/**
+ * Filter, sort, and paginate an in-memory list. Without `?sort=` the
+ * original order is kept.
+ */
+export function applyListQuery<T>(items: T[], query: ListQuery): ListPage<T> {
+  let result = items.filter((item) =>
+    Object.entries(query.filters).every(([field, values]) => {
+      const value = (item as Record<string, unknown>)[field];
+      return value !== null && value !== undefined && values.includes(String(value).toLowerCase());
+    })
+  );
+
+  if (query.sort.length > 0) {
+    result = [...result].sort((a, b) => {
+      for (const { field, descending } of query.sort) {
+        const cmp = compareValues((a as Record<string, unknown>)[field], (b as Record<string, unknown>)[field]);
+        if (cmp !== 0) {
+          return descending ? -cmp : cmp;
+        }
+      }
+      return 0;
+    });
+  }
+
+  return toListPage(result.slice(query.offset, query.offset + query.limit), result.length, query);
+}
+
+/**
+ * A page of a list that was filtered, sorted and paginated elsewhere, e.g. in SQL. `total` is the
+ * number of items matching the filters.
+ */
+export function toListPage<T>(items: T[], total: number, query: ListQuery): ListPage<T> {
+  const nextOffset = query.offset + items.length;
+  return {
+    items,
+    total,
+    nextCursor: nextOffset < total ? encodeCursor(nextOffset) : null,
+  };
+}
+
+const MAX_WAIT_MS = 60000;
+
+/**
+ * Parse a long-poll `?wait=` duration like `30s`, `500ms`, or `30` (seconds).
+ * Returns 0 when absent and caps at 60 seconds.
+ */
+export function parseWaitDuration(value: string | null): number {
+  if (!value) {
+    return 0;
+  }
+  const match = value.trim().match(/^(\d+)(ms|s)?$/);
+  if (!match) {
+    throw new ListQueryError('wait must be a duration like "30s" or "500ms"');
+  }
+  const amount = parseInt(match[1]);
+  const ms = match[2] === 'ms' ? amount : amount * 1000;
+  return Math.min(ms, MAX_WAIT_MS);
+}
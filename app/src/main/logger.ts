@@ -0,0 +1,120 @@
+/**
+ * Runtime log filtering. Log lines are filtered by the `[module]` prefix they already carry,
+ * with directives like `info,simulator=trace` (default level, then per-module overrides).
+ */
+
+import type { LogLevel } from '../shared/ipc-types';
+
+const LEVELS: LogLevel[] = ['off', 'error', 'warn', 'info', 'debug', 'trace'];
+const MODULE_PATTERN = /^[A-Za-z0-9_-]+$/;
+const PREFIX_PATTERN = /^\[([A-Za-z0-9_-]+)\]/;
+
+export class LogFilterError extends Error {}
+
+interface LogFilter {
+  defaultLevel: LogLevel;
+  modules: Map<string, LogLevel>;
+}
+
+let filter: LogFilter = { defaultLevel: 'info', modules: new Map() };
+let installed = false;
+
+function parseLevel(value: string, directive: string): LogLevel {
+  const level = value.toLowerCase() as LogLevel;
+  if (!LEVELS.includes(level)) {
+    throw new LogFilterError(`Unknown log level "${value}" in "${directive}" (expected ${LEVELS.join(', ')})`);
+  }
+  return level;
+}
+
+/**
+ * Parse a comma-separated filter: a bare level sets the default, `module=level` overrides one module
+ */
+function parseLogFilter(spec: string): LogFilter {
+  const parsed: LogFilter = { defaultLevel: 'info', modules: new Map() };
+  for (const directive of spec.split(',').map((d) => d.trim()).filter(Boolean)) {
+    const [module, level] = directive.split('=');
+    if (level === undefined) {
+      parsed.defaultLevel = parseLevel(module, directive);
+      continue;
+    }
+    if (!MODULE_PATTERN.test(module)) {
+      throw new LogFilterError(`Invalid module name "${module}" in "${directive}"`);
+    }
+    parsed.modules.set(module.toLowerCase(), parseLevel(level, directive));
+  }
+  return parsed;
+}
+
+/**
+ * Check a filter without applying it. Returns the error message, or null if it's valid.
+ */
+export function validateLogFilter(spec: string): string | null {
+  try {
+    parseLogFilter(spec);
+    return null;
+  } catch (err) {
+    return (err as Error).message;
+  }
+}
+
+/**
+ * Replace the active filter. Throws LogFilterError if it doesn't parse.
+ */
+export function setLogFilter(spec: string): void {
+  filter = parseLogFilter(spec);
+}
+
+/**
+ * The active filter in directive form, e.g. `info,simulator=trace`
+ */
+export function getLogFilter(): string {
+  const directives = [filter.defaultLevel];
+  for (const [module, level] of filter.modules) {
+    directives.push(`${module}=${level}`);
+  }
+  return directives.join(',');
+}
+
+function isEnabled(level: LogLevel, args: unknown[]): boolean {
+  const prefix = typeof args[0] === 'string' ? PREFIX_PATTERN.exec(args[0]) : null;
+  const threshold = (prefix && filter.modules.get(prefix[1].toLowerCase())) || filter.defaultLevel;
+  return LEVELS.indexOf(level) <= LEVELS.indexOf(threshold);
+}
+
+/**
+ * Route console output through the filter. `console.log` and `console.info` log at info,
+ * `console.debug` at debug. Safe to call more than once.
+ */
+export function installLogFilter(spec: string): void {
+  setLogFilter(spec);
+  if (installed) {
+    return;
+  }
+  installed = true;
+
+  const methods: Array<['log' | 'info' | 'warn' | 'error' | 'debug', LogLevel]> = [
+    ['error', 'error'],
+    ['warn', 'warn'],
+    ['log', 'info'],
+    ['info', 'info'],
+    ['debug', 'debug'],
+  ];
+  for (const [method, level] of methods) {
+    const original = console[method].bind(console);
+    console[method] = (...args: unknown[]) => {
+      if (isEnabled(level, args)) {
+        original(...args);
+      }
+    };
+  }
+}
+
+/**
+ * Log at trace level, the most verbose. Off unless enabled for the module.
+ */
+export function trace(...args: unknown[]): void {
+  if (isEnabled('trace', args)) {
+    console.log(...args);
+  }
+}
@@ -0,0 +1,226 @@
+import { field, Validator } from './validation';
+import { HARDWARE_BUTTONS, isSimulatorOrientation, isTypeableText, keyCodeFor } from './services/simulator';
+import { BUNDLE_ID_CONFLICT_POLICIES } from './services/app-conflicts';
+import { PRIVACY_ACTIONS, PRIVACY_SERVICES } from './services/privacy';
+import { MAX_ROUTE_SPEED } from './services/location';
+import { CONTENT_SIZE_CATEGORIES } from './services/accessibility';
+import { SETTINGS_PANE_IDS } from './services/settings-panes';
+import { CONDITION_PROFILE_IDS, NETWORK_CONDITIONS, THERMAL_STATES } from './services/conditions';
+import { isBuildOutputLevel, isDestinationPreset } from './services/xcode';
+import type {
+  ApplyConditionsRequest,
+  BuildStreamRequest,
+  ButtonRequest,
+  CancelBuildRequest,
+  CreateSimulatorRequest,
+  DiscoverProjectRequest,
+  EraseSimulatorRequest,
+  GetLaunchableProductsRequest,
+  KeyRequest,
+  LaunchAppRequest,
+  LocationRouteRequest,
+  OpenSettingsPaneRequest,
+  PrivacyRequest,
+  SetAccessibilityRequest,
+  SetLocationRequest,
+  SetOrientationRequest,
+  StartStreamRequest,
+  SwipeRequest,
+  TapRequest,
+  TouchEvent,
+  TypeTextRequest,
+  UninstallAppRequest,
+  UpdateProjectSettingsRequest,
+} from '../shared/ipc-types';
+
+/**
+ * Schemas of the simulator and Xcode request payloads, checked with `validate`
+ */
+
+// Longest text `/api/simulator/type` accepts; AXe types a character at a time
+export const MAX_TYPED_TEXT_LENGTH = 2000;
+
+export const TOUCH_PHASES = ['began', 'moved', 'ended'] as const;
+
+// Screen positions are fractions of the screen's width and height
+const position = () => field.number({ min: 0, max: 1 });
+const screenSize = () => field.number({ min: 1 });
+const latitude = () => field.number({ min: -90, max: 90 });
+const longitude = () => field.number({ min: -180, max: 180 });
+
+export const touchPoint = field.object({ x: position(), y: position() });
+export const touches = field.array(touchPoint, { min: 1, max: 10 });
+
+export const TAP_REQUEST: Validator<TapRequest> = field.object({
+  udid: field.string(),
+  x: position(),
+  y: position(),
+  screenWidth: screenSize(),
+  screenHeight: screenSize(),
+});
+
+export const SWIPE_REQUEST: Validator<SwipeRequest> = field.object({
+  udid: field.string(),
+  startX: position(),
+  startY: position(),
+  endX: position(),
+  endY: position(),
+  screenWidth: screenSize(),
+  screenHeight: screenSize(),
+  // Seconds
+  duration: field.optional(field.number({ min: 0, max: 10 })),
+});
+
+export const TOUCH_REQUEST: Validator<TouchEvent> = field.object({
+  udid: field.string(),
+  type: field.oneOf(TOUCH_PHASES),
+  touches,
+});
+
+// The frame sizing of `simulator:stream:start`; its other fields predate validation
+export const STREAM_START_REQUEST: Validator<Pick<StartStreamRequest, 'udid' | 'scale' | 'maxWidth' | 'adaptive'>> = field.object({
+  udid: field.string(),
+  scale: field.optional(field.check(field.number({ max: 1 }), (scale) => (scale > 0 ? null : 'must be above 0'))),
+  maxWidth: field.optional(field.number({ min: 16, integer: true })),
+  adaptive: field.optional(field.boolean()),
+});
+
+export const TYPE_TEXT_REQUEST: Validator<TypeTextRequest> = field.object({
+  udid: field.string(),
+  text: field.check(field.string({ max: MAX_TYPED_TEXT_LENGTH }), (text) =>
+    isTypeableText(text) ? null : 'can only contain characters on a US keyboard'
+  ),
+});
+
+export const KEY_REQUEST: Validator<KeyRequest> = field.object({
+  udid: field.string(),
+  key: field.is((key): key is KeyRequest['key'] => keyCodeFor(key) !== null, 'must be a key name or a HID usage code'),
+});
+
+export const BUTTON_REQUEST: Validator<ButtonRequest> = field.object({
+  udid: field.string(),
+  button: field.oneOf(HARDWARE_BUTTONS),
+});
+
+export const ORIENTATION_REQUEST: Validator<SetOrientationRequest> = field.object({
+  udid: field.string(),
+  orientation: field.is(isSimulatorOrientation, 'must be portrait, landscape-left, landscape-right or upside-down'),
+});
+
+export const LOCATION_REQUEST: Validator<SetLocationRequest> = field.object({
+  udid: field.string(),
+  latitude: latitude(),
+  longitude: longitude(),
+});
+
+export const LOCATION_ROUTE_REQUEST: Validator<LocationRouteRequest> = field.object({
+  udid: field.string(),
+  gpx: field.string(),
+  speed: field.optional(
+    field.check(field.number({ max: MAX_ROUTE_SPEED }), (speed) => (speed > 0 ? null : 'must be above 0'))
+  ),
+});
+
+export const PRIVACY_REQUEST: Validator<PrivacyRequest> = field.object({
+  udid: field.string(),
+  action: field.oneOf(PRIVACY_ACTIONS),
+  service: field.oneOf(PRIVACY_SERVICES),
+  bundleId: field.optional(field.string()),
+});
+
+export const ACCESSIBILITY_REQUEST: Validator<SetAccessibilityRequest> = field.check(
+  field.object({
+    reduceMotion: field.optional(field.boolean()),
+    reduceTransparency: field.optional(field.boolean()),
+    boldText: field.optional(field.boolean()),
+    increaseContrast: field.optional(field.boolean()),
+    contentSize: field.optional(field.oneOf(CONTENT_SIZE_CATEGORIES)),
+    voiceOver: field.optional(field.boolean()),
+  }),
+  (body) => (Object.values(body).some((value) => value !== undefined) ? null : 'At least one setting is required')
+);
+
+export const SETTINGS_PANE_REQUEST: Validator<OpenSettingsPaneRequest> = field.object({
+  pane: field.oneOf(SETTINGS_PANE_IDS),
+  bundleId: field.optional(field.string()),
+});
+
+export const CONDITIONS_REQUEST: Validator<ApplyConditionsRequest> = field.check(
+  field.object({
+    profile: field.optional(field.oneOf(CONDITION_PROFILE_IDS)),
+    conditions: field.optional(
+      field.object({
+        network: field.optional(field.oneOf(NETWORK_CONDITIONS)),
+        thermalState: field.optional(field.oneOf(THERMAL_STATES)),
+        lowPower: field.optional(field.boolean()),
+        reduceMotion: field.optional(field.boolean()),
+        reduceTransparency: field.optional(field.boolean()),
+      })
+    ),
+  }),
+  (body) => (body.profile || body.conditions ? null : 'A profile or conditions are required')
+);
+
+export const LAUNCH_REQUEST: Validator<LaunchAppRequest> = field.object({
+  udid: field.string(),
+  appPath: field.string(),
+  bundleId: field.optional(field.string()),
+  language: field.optional(field.string()),
+  locale: field.optional(field.string()),
+  onBundleIdConflict: field.optional(field.oneOf(BUNDLE_ID_CONFLICT_POLICIES)),
+});
+
+export const CREATE_SIMULATOR_REQUEST: Validator<CreateSimulatorRequest> = field.object({
+  name: field.check(field.string({ max: 100 }), (name) => (name.trim() ? null : 'is required')),
+  deviceType: field.string(),
+  runtime: field.optional(field.string()),
+});
+
+export const ERASE_SIMULATOR_REQUEST: Validator<EraseSimulatorRequest> = field.object({
+  udid: field.string(),
+  snapshot: field.optional(field.boolean()),
+});
+
+export const UNINSTALL_APP_REQUEST: Validator<UninstallAppRequest> = field.object({
+  udid: field.string(),
+  bundleId: field.string(),
+  snapshot: field.optional(field.boolean()),
+});
+
+export const DISCOVER_PROJECT_REQUEST: Validator<DiscoverProjectRequest> = field.object({
+  path: field.string(),
+});
+
+export const LAUNCHABLE_PRODUCTS_REQUEST: Validator<GetLaunchableProductsRequest> = field.object({
+  buildDir: field.string(),
+});
+
+export const CANCEL_BUILD_REQUEST: Validator<CancelBuildRequest> = field.object({
+  buildId: field.string(),
+});
+
+export const BUILD_STREAM_REQUEST: Validator<BuildStreamRequest> = field.object({
+  path: field.string(),
+  scheme: field.optional(field.string()),
+  destination: field.optional(field.is(isDestinationPreset, 'must be a destination from /api/xcode/destinations')),
+  output: field.optional(field.is(isBuildOutputLevel, 'must be one of all, warnings, errors, phases')),
+  idempotencyKey: field.optional(field.string({ max: 255 })),
+});
+
+const ENVIRONMENT_VARIABLE_NAME = /^[A-Za-z_][A-Za-z0-9_]*$/;
+
+const isEnvironment = (value: unknown): value is Record<string, string> =>
+  !!value && typeof value === 'object' && !Array.isArray(value) && Object.values(value).every((variable) => typeof variable === 'string');
+
+export const PROJECT_SETTINGS_REQUEST: Validator<UpdateProjectSettingsRequest> = field.object({
+  scheme: field.optional(field.nullable(field.string({ max: 255 }))),
+  configuration: field.optional(field.nullable(field.string({ max: 255 }))),
+  simulatorUdid: field.optional(field.nullable(field.string())),
+  environment: field.optional(
+    field.check(field.is(isEnvironment, 'must be an object of string values'), (environment) => {
+      const invalid = Object.keys(environment).find((name) => !ENVIRONMENT_VARIABLE_NAME.test(name));
+      return invalid === undefined ? null : `has an invalid variable name: ${invalid}`;
+    })
+  ),
+  expectedUpdatedAt: field.optional(field.nullable(field.string())),
+});
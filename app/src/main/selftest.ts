@@ -0,0 +1,189 @@
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { randomUUID } from 'crypto';
+import type { AddressInfo } from 'net';
+import { WebSocket } from 'ws';
+import { createServer } from './server';
+import { closeDatabase, getSetting, setSetting, deleteSetting } from './services/database';
+import { setWorkspaceRoot } from './services/workspace';
+import { createLocalStore, setArtifactStore } from './services/storage';
+import { createFakeDevicesBackend } from './services/fake-devices';
+import { setDeviceBackend } from './services/simulator';
+import { AppState } from './services/app-state';
+
+/**
+ * Post-install verification: starts the server on an ephemeral port against a scratch data
+ * directory and exercises the pieces a broken install or package tends to break (native
+ * modules, the HTTP and WebSocket servers, simctl). Run with `server-standalone.js selftest`.
+ */
+
+export type SelfTestStatus = 'pass' | 'fail' | 'skip';
+
+export interface SelfTestCheck {
+  name: string;
+  status: SelfTestStatus;
+  durationMs: number;
+  // What was found, why it failed or why it was skipped
+  detail: string | null;
+}
+
+export interface SelfTestReport {
+  // Whether no check failed; skipped checks don't count
+  passed: boolean;
+  checks: SelfTestCheck[];
+}
+
+// How long to wait for the first frame of the fake device's stream
+const STREAM_TIMEOUT_MS = 5000;
+const STREAM_UDID = 'FAKE-IPHONE-16';
+const PNG_SIGNATURE = Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]);
+
+class SkippedCheck extends Error {}
+
+async function runCheck(name: string, check: () => Promise<string | null>): Promise<SelfTestCheck> {
+  const startedAt = Date.now();
+  try {
+    const detail = await check();
+    return { name, status: 'pass', durationMs: Date.now() - startedAt, detail };
+  } catch (err) {
+    const status = err instanceof SkippedCheck ? 'skip' : 'fail';
+    return { name, status, durationMs: Date.now() - startedAt, detail: err instanceof Error ? err.message : String(err) };
+  }
+}
+
+async function getJson(baseUrl: string, urlPath: string): Promise<any> {
+  const response = await fetch(`${baseUrl}${urlPath}`);
+  if (!response.ok) {
+    throw new Error(`GET ${urlPath} returned ${response.status}`);
+  }
+  return response.json();
+}
+
+/**
+ * Stream a fake device over the WebSocket and wait for a frame
+ */
+function receiveFakeFrame(port: number): Promise<string> {
+  return new Promise((resolve, reject) => {
+    const ws = new WebSocket(`ws://127.0.0.1:${port}`);
+    const finish = (error: Error | null, detail?: string) => {
+      clearTimeout(timer);
+      if (ws.readyState === WebSocket.OPEN) {
+        ws.send(JSON.stringify({ type: 'simulator:stream:stop', payload: { udid: STREAM_UDID } }));
+      }
+      ws.close();
+      if (error) {
+        reject(error);
+      } else {
+        resolve(detail!);
+      }
+    };
+    const timer = setTimeout(() => finish(new Error(`No frame within ${STREAM_TIMEOUT_MS} ms`)), STREAM_TIMEOUT_MS);
+
+    ws.on('open', () => {
+      ws.send(JSON.stringify({ type: 'simulator:stream:start', payload: { udid: STREAM_UDID, fps: 10 } }));
+    });
+    ws.on('message', (data) => {
+      const message = JSON.parse(data.toString());
+      if (message.type !== 'simulator:stream:frame' || message.payload.udid !== STREAM_UDID) {
+        return;
+      }
+      const frame = Buffer.from(message.payload.frame, 'base64');
+      if (frame.subarray(0, PNG_SIGNATURE.length).equals(PNG_SIGNATURE)) {
+        finish(null, `Received a ${frame.length} byte frame`);
+      } else {
+        finish(new Error('Received a frame that is not a PNG'));
+      }
+    });
+    ws.on('error', (err) => finish(err));
+  });
+}
+
+/**
+ * Run every check and report each one's outcome. State goes to a temporary directory that is
+ * removed afterwards, so this is safe to run next to a real instance.
+ */
+export async function runSelfTest(): Promise<SelfTestReport> {
+  const dataDir = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-selftest-'));
+  closeDatabase();
+  process.env.PLASMA_DATABASE_PATH = path.join(dataDir, 'plasma.db');
+  setWorkspaceRoot(path.join(dataDir, 'workspace'));
+  setArtifactStore(createLocalStore(path.join(dataDir, 'artifacts')));
+
+  const state = new AppState();
+  const server = createServer(state);
+  const checks: SelfTestCheck[] = [];
+  try {
+    await new Promise<void>((resolve, reject) => {
+      server.once('error', reject);
+      server.listen(0, '127.0.0.1', resolve);
+    });
+    const { port } = server.address() as AddressInfo;
+    const baseUrl = `http://127.0.0.1:${port}`;
+
+    checks.push(
+      await runCheck('health', async () => {
+        const health = await getJson(baseUrl, '/api/health');
+        if (health.status !== 'ok') {
+          throw new Error(`Health status is ${JSON.stringify(health.status)}`);
+        }
+        return `Listening on port ${port}`;
+      })
+    );
+
+    checks.push(
+      await runCheck('database', async () => {
+        const value = randomUUID();
+        setSetting('selftest.check', value);
+        const stored = getSetting('selftest.check');
+        deleteSetting('selftest.check');
+        if (stored?.value !== value) {
+          throw new Error('Read back a different value than was written');
+        }
+        return process.env.PLASMA_DATABASE_PATH!;
+      })
+    );
+
+    checks.push(
+      await runCheck('simulators', async () => {
+        if (process.platform !== 'darwin') {
+          throw new SkippedCheck('Simulators are only available on macOS');
+        }
+        const simulators = await getJson(baseUrl, '/api/simulator/list?refresh=true');
+        return `Found ${simulators.length} simulators`;
+      })
+    );
+
+    checks.push(
+      await runCheck('stream', async () => {
+        state.run(() => setDeviceBackend(createFakeDevicesBackend()));
+        try {
+          return await receiveFakeFrame(port);
+        } finally {
+          state.run(() => setDeviceBackend(null));
+        }
+      })
+    );
+  } catch (err) {
+    checks.push({ name: 'server', status: 'fail', durationMs: 0, detail: err instanceof Error ? err.message : String(err) });
+  } finally {
+    server.closeAllConnections();
+    await new Promise<void>((resolve) => server.close(() => resolve()));
+    closeDatabase();
+    fs.rmSync(dataDir, { recursive: true, force: true });
+  }
+
+  return { passed: checks.every((check) => check.status !== 'fail'), checks };
+}
+
+/**
+ * One line per check, e.g. `PASS  health (4 ms): Listening on port 52811`
+ */
+export function formatSelfTestReport(report: SelfTestReport): string {
+  const lines = report.checks.map((check) => {
+    const detail = check.detail ? `: ${check.detail}` : '';
+    return `${check.status.toUpperCase().padEnd(4)}  ${check.name} (${check.durationMs} ms)${detail}`;
+  });
+  lines.push(report.passed ? 'Self-test passed' : 'Self-test failed');
+  return lines.join('\n');
+}
@@ -1,29 +1,53 @@
 /**
  * Standalone server entry point for browser mode
  * Run with: pnpm dev:browser
+ *
+ * `server-standalone.js selftest [--json]` checks the install instead of serving, and exits
+ * with 1 when a check fails.
  */
 
 import { startServer } from './server';
+import { killAllProcesses } from './services/process-manager';
+import { runSelfTest, formatSelfTestReport } from './selftest';
 
-console.log('[standalone] Starting Plasma server for browser mode...');
+function serve(): void {
+  console.log('[standalone] Starting Plasma server for browser mode...');
 
-startServer()
-  .then(() => {
-    console.log('[standalone] Server started successfully');
-    console.log('[standalone] Open http://localhost:5173 in your browser');
-  })
-  .catch((err) => {
-    console.error('[standalone] Failed to start server:', err);
-    process.exit(1);
+  startServer()
+    .then(() => {
+      console.log('[standalone] Server started successfully');
+      console.log('[standalone] Open http://localhost:5173 in your browser');
+    })
+    .catch((err) => {
+      console.error('[standalone] Failed to start server:', err);
+      process.exit(1);
+    });
+
+  // Handle shutdown gracefully
+  process.on('SIGINT', () => {
+    console.log('\n[standalone] Shutting down...');
+    killAllProcesses();
+    process.exit(0);
+  });
+
+  process.on('SIGTERM', () => {
+    console.log('\n[standalone] Shutting down...');
+    killAllProcesses();
+    process.exit(0);
   });
+}
 
-// Handle shutdown gracefully
-process.on('SIGINT', () => {
-  console.log('\n[standalone] Shutting down...');
-  process.exit(0);
-});
+async function selfTest(json: boolean): Promise<void> {
+  const report = await runSelfTest();
+  console.log(json ? JSON.stringify(report, null, 2) : formatSelfTestReport(report));
+  process.exit(report.passed ? 0 : 1);
+}
 
-process.on('SIGTERM', () => {
-  console.log('\n[standalone] Shutting down...');
-  process.exit(0);
-});
+if (process.argv[2] === 'selftest') {
+  selfTest(process.argv.includes('--json')).catch((err) => {
+    console.error('[standalone] Self-test crashed:', err);
+    process.exit(1);
+  });
+} else {
+  serve();
+}
@@ -1,94 +1,605 @@
 import * as http from 'http';
 import * as fs from 'fs';
+import { createHash } from 'crypto';
+import { AsyncLocalStorage } from 'async_hooks';
 import { WebSocketServer, WebSocket } from 'ws';
-import { detectProject } from './services/projects';
+import { detectProject, findProjectReadme } from './services/projects';
 import {
   saveProject,
   getRecentProjects,
-  saveUnifiedProject,
-  getRecentUnifiedProjects,
-  getUnifiedProjectById,
-  updateProjectLastOpened,
+  setDatabaseBusyTimeout,
 } from './services/database';
+import {
+  listProjectNotifiers,
+  getProjectNotifier,
+  createProjectNotifier,
+  deleteProjectNotifier,
+  validateNotifierInput,
+  sendTestNotification,
+  dispatchBuildNotifiers,
+  migrateNotifierWebhookUrls,
+} from './services/notifiers';
+import {
+  loadPlugins,
+  listPlugins,
+  getPlugin,
+  findPluginRoute,
+  invokePlugin,
+  runBuildHooks,
+} from './services/plugins';
+import { recordFirstFrame, getLaunchTimingStats } from './services/launch-profiling';
+import { startLogHistory, getLogHistory } from './services/log-history';
+import { calibrate, CalibrationError } from './services/calibration';
+import { createFakeDevicesBackend } from './services/fake-devices';
+import { announceInstance, defaultDiscoveryFile, getInstance } from './services/instance';
+import { setWorkspaceRoot, listJobDirs } from './services/workspace';
+import { configureHelperLookup } from './services/helper-binaries';
+import { createMjpegParser, multipartFrame, MULTIPART_CONTENT_TYPE } from './services/mjpeg';
+import { createThumbnailer, DEFAULT_THUMBNAIL_OPTIONS, MAX_THUMBNAIL_FPS, ThumbnailOptions } from './services/thumbnails';
+import {
+  DEFAULT_VIDEO_OPTIONS,
+  MAX_VIDEO_BITRATE,
+  MAX_VIDEO_FPS,
+  MIN_VIDEO_BITRATE,
+  startVideoEncoder,
+  VideoOptions,
+  VideoUnavailableError,
+} from './services/video';
+import {
+  startRecording,
+  stopRecording,
+  isRecording,
+  listRecordings,
+  getRecording,
+  deleteRecording,
+  recordingKey,
+  recordStreamFrame,
+  RecordingError,
+  STREAM_RECORDING_CONTENT_TYPE,
+} from './services/recordings';
+import { runStartupRecovery, getRecoveryReport } from './services/recovery';
+import { createArtifactStore, getArtifactStore, setArtifactStore } from './services/storage';
+import { createDataStore, getDataStore, setDataStore } from './services/data-store';
+import { compareBuilds, findBuildsByChecksum, getBuildProvenance, listBuilds } from './services/builds';
+import {
+  startRuntimeIssueDetection,
+  getRuntimeIssues,
+  clearRuntimeIssues,
+} from './services/runtime-issues';
+import { listNoiseProfiles, parseNoiseProfiles, DEFAULT_NOISE_PROFILES } from './services/log-noise';
+import { streamSyslog, MAX_PREDICATE_LENGTH } from './services/syslog';
+import { resetFramePacing, recordPacingFrame, getFramePacing } from './services/frame-pacing';
+import { watchAppResources, MIN_SAMPLE_INTERVAL_MS, MAX_SAMPLE_INTERVAL_MS } from './services/resource-usage';
+import { getOnboardingStatus, setOnboardingDismissed } from './services/onboarding';
+import { getFeatureFlags, isFeatureEnabled, isFeatureFlagName, setFeatureFlag, setFeatureFlagDefaults } from './services/feature-flags';
+import { getUsage, setAnalyticsEnabled } from './services/analytics';
+import { exportInstance, importInstance, ImportError } from './services/instance-export';
+import { CONTROL_SOCKET_PATH, handleControlMessage } from './services/simulator-control';
+import {
+  clearRecordedRequests,
+  configureRequestRecording,
+  getRecordedRequests,
+  getRequestRecordingStatus,
+  noteRequestBody,
+  recordExchange,
+  setRequestRecording,
+} from './services/request-recorder';
+import {
+  configureSecrets,
+  deleteSecret,
+  getSecretsStatus,
+  listSecrets,
+  setSecret,
+  InvalidSecretError,
+  SecretsLockedError,
+} from './services/secrets';
+import { holdKeepAwake, releaseKeepAwake, getKeepAwakeHolders } from './services/keep-awake';
+import { setResourceGuards, checkHostResources } from './services/guardrails';
+import { createWatermarker, viewerOf } from './services/watermark';
+import { drawTouches, recordTouch, setTouchOverlay, setTouchOverlayDefault } from './services/touch-overlay';
+import { recognizeText, findText, OcrUnavailableError } from './services/ocr';
+import { sendBiometric, setBiometricEnrollment, BIOMETRIC_ACTIONS, BIOMETRIC_SENSORS } from './services/biometrics';
+import { checkUniversalLinks } from './services/universal-links';
+import { checkUrl, setUrlPolicy } from './services/url-policy';
+import { renderMarkdown } from './services/markdown';
+import { findBundleIdConflict, uninstallConflictingApp } from './services/app-conflicts';
+import { eraseSimulatorWithUndo, listUndos, undo, uninstallAppWithUndo, UndoNotFoundError } from './services/undo';
+import { InstallVerificationError } from './services/app-verification';
+import {
+  clearLocation,
+  parseGpx,
+  routeDistance,
+  setLocation,
+  startLocationRoute,
+  DEFAULT_ROUTE_SPEED,
+  MAX_ROUTE_WAYPOINTS,
+} from './services/location';
+import { setPrivacy } from './services/privacy';
+import {
+  APPEARANCES,
+  clearStatusBar,
+  getAppearance,
+  overrideStatusBar,
+  setAppearance,
+  validateStatusBarOverrides,
+} from './services/appearance';
+import { getAccessibilitySettings, setAccessibilitySettings } from './services/accessibility';
+import { openSettingsPane, SETTINGS_PANES } from './services/settings-panes';
+import { applyConditions, CONDITION_PROFILES, getAppliedConditions } from './services/conditions';
+import { setRetryPolicy } from './services/retry';
+import { handleIdempotencyKey, setIdempotencyWindow, takeBufferedBody } from './services/idempotency';
+import { compressResponse, setCompressionEnabled } from './services/compression';
+import { getLaunchConsole } from './services/app-console';
+import { createEventReplay, findEventReplay, parseLastEventId, EventReplay } from './services/event-replay';
+import { decodePng } from './services/png';
+import { sampleColor, MAX_SAMPLE_RADIUS } from './services/pixel-color';
+import { startSimulatorWatcher } from './services/simulator-watch';
+import {
+  startStreamHealthMonitor,
+  trackStream,
+  untrackStream,
+  recordFrame,
+  recordSessionRestart,
+  getFiringAlerts,
+} from './services/stream-health';
+import { launchMacApp, terminateMacApp, MAC_DEVICE_ID } from './services/macos';
+import {
+  listEmulators,
+  bootEmulator,
+  isEmulatorRunning,
+  installApk,
+  launchAndroidApp,
+  validateAndroidLaunch,
+  AndroidSdkUnavailableError,
+} from './services/android';
+import { buildGradleStream, findApks, isGradleTask, DEFAULT_GRADLE_TASK } from './services/gradle';
 import { validateXcodePath, validateAndroidPath } from './services/project-validation';
 import { scaffoldNewProject } from './services/project-scaffold';
-import { discoverProject, buildSchemeStream, getLaunchableProducts } from './services/xcode';
+import {
+  discoverProject,
+  buildSchemeStream,
+  includeBuildOutput,
+  getLaunchableProducts,
+  getBuildLogPath,
+  buildLogKey,
+  listDestinationPresets,
+  isDestinationPreset,
+  getActiveBuilds,
+  getLastBuildResult,
+  cancelBuild,
+  DEFAULT_DESTINATION,
+  BuildStream,
+} from './services/xcode';
 import {
   listSimulators,
+  listDeviceTypes,
+  validateCreateSimulator,
+  createSimulator,
+  deleteSimulator,
+  eraseSimulator,
   installAndLaunch,
+  validateLaunchOptions,
   sendSessionCommand,
   sendTap,
   sendSwipe,
+  typeText,
+  keyCodeFor,
+  pressButton,
+  pressKey,
   getOrCreateSession,
-  logEmitter,
+  startBackendStream,
+  BackendFrame,
+  setDeviceBackend,
+  startSessionPrewarming,
+  getStreamDefaults,
+  setSimulatorListTtl,
+  setStreamDefaultOverrides,
+  getSimulatorCapabilities,
+  setOrientation,
+  getLaunchedApp,
+  setSessionRestartPolicy,
+  setSessionIdleTimeout,
+  getSessionCommands,
+  getCachedSimulators,
+  captureScreenshot,
+  runSimctl,
+  getActiveSessions,
+  getSessionInfo,
+  listSessions,
+  SessionCapabilityError,
+  acquireSession,
+  releaseSession,
+  stopSession,
 } from './services/simulator';
+import {
+  recordNotification,
+  listNotifications,
+  listNotificationsSince,
+  getUnreadNotificationCount,
+  markNotificationRead,
+  markAllNotificationsRead,
+  startDiskSpaceMonitor,
+} from './services/notifications';
+import {
+  parseListQuery,
+  applyListQuery,
+  parseWaitDuration,
+  ListQueryError,
+  ListQueryOptions,
+} from './list-query';
+import { sendFile } from './file-response';
+import { EVENT_SCHEMA_VERSION } from '../shared/ipc-types';
+import type {
+  BuildOutputLevel,
+  EraseSimulatorResponse,
+  LogNoiseProfileId,
+  OcrResponse,
+  OpenSettingsPaneResponse,
+  ProjectSettings,
+  RuntimeIssue,
+  SettingChange,
+  SimulatorChange,
+  StatusSummary,
+  StreamScaleChange,
+  UndoInfo,
+  UndoResponse,
+  UninstallAppResponse,
+  BuildEvent,
+  SessionRestartEvent,
+  SessionCommandRecord,
+  StreamLogEvent,
+  NotificationRecord,
+  UnifiedProject,
+} from '../shared/ipc-types';
+import { validate, ValidationError } from './validation';
+import {
+  ACCESSIBILITY_REQUEST,
+  BUILD_STREAM_REQUEST,
+  BUTTON_REQUEST,
+  CANCEL_BUILD_REQUEST,
+  CONDITIONS_REQUEST,
+  CREATE_SIMULATOR_REQUEST,
+  DISCOVER_PROJECT_REQUEST,
+  ERASE_SIMULATOR_REQUEST,
+  KEY_REQUEST,
+  LAUNCH_REQUEST,
+  LAUNCHABLE_PRODUCTS_REQUEST,
+  LOCATION_REQUEST,
+  LOCATION_ROUTE_REQUEST,
+  ORIENTATION_REQUEST,
+  PRIVACY_REQUEST,
+  PROJECT_SETTINGS_REQUEST,
+  SETTINGS_PANE_REQUEST,
+  STREAM_START_REQUEST,
+  SWIPE_REQUEST,
+  TAP_REQUEST,
+  TOUCH_REQUEST,
+  UNINSTALL_APP_REQUEST,
+  TYPE_TEXT_REQUEST,
+} from './request-schemas';
+import { getConfig, validateConfig, formatConfigIssues, ChannelName } from './config';
+import { installLogFilter, setLogFilter, getLogFilter, LogFilterError } from './logger';
+import { backlog, channelForMessage, isLagging, waitUntilDrained } from './ws-channels';
+import { StreamScaler } from './services/stream-scaling';
+import { FrameFanout } from './services/frame-fanout';
+import type { WebSocketClient } from './services/event-bus';
+import { AppState } from './services/app-state';
+import {
+  defineMetric,
+  defineHistogram,
+  observeHistogram,
+  incrementCounter,
+  setGauge,
+  removeSeries,
+  renderMetrics,
+} from './services/metrics';
 import fetch from 'node-fetch';
 
-const PORT = 3001;
+const config = getConfig();
 
-interface WebSocketClient {
-  ws: WebSocket;
-  subscriptions: Set<string>;
-}
+// Upper bound of rows loaded for list endpoints before pagination
+const MAX_LIST_ITEMS = 1000;
+
+// How often long-polling list requests re-check for changes
+const LONG_POLL_INTERVAL_MS = 2000;
+
+// How soon an EventSource reconnects after losing its connection
+const SSE_RETRY_MS = 1000;
+
+defineMetric('plasma_ws_dropped_messages_total', 'counter', 'WebSocket messages dropped for lagging subscribers');
+defineMetric('plasma_ws_upstream_pauses_total', 'counter', 'Times an upstream source was paused for a lagging subscriber');
+defineMetric('plasma_ws_buffered_bytes', 'gauge', 'Bytes queued in each subscriber send buffer');
+defineMetric('plasma_ws_subscribers', 'gauge', 'Connected WebSocket subscribers');
+defineHistogram('plasma_http_request_duration_seconds', 'HTTP request latency by route', [
+  0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1, 2.5, 5, 10, 30,
+]);
+defineMetric('plasma_http_requests_total', 'counter', 'HTTP requests by route and status code');
+defineMetric('plasma_http_request_errors_total', 'counter', 'HTTP requests answered with a 4xx or 5xx status');
 
-const clients = new Map<WebSocket, WebSocketClient>();
+// The route pattern a request matched, set by matchRoute, so metrics are labeled with the route
+// instead of the concrete path
+const matchedRoutes = new AsyncLocalStorage<{ route: string | null }>();
 
-// Active MJPEG streams per UDID
-const activeStreams = new Map<string, { abort: AbortController }>();
+// Routes matched by prefix rather than through matchRoute, with their metric labels
+const PREFIX_ROUTE_LABELS: Array<[RegExp, string]> = [
+  [/^\/api\/projects\/unified\/(?!recent$)[^/]+$/, '/api/projects/unified/:id'],
+  [/^\/api\/notifications\/[^/]+\/read$/, '/api/notifications/:id/read'],
+  [/^\/api\/plugins\/[^/]+\/routes\//, '/api/plugins/:name/routes/*'],
+];
+
+// Refresh the cached simulator list in the background when the status summary finds it older than this
+const STATUS_SIMULATORS_MAX_AGE_MS = 30000;
+
+// Longest project notes `/api/projects/:id/notes` accepts
+const MAX_PROJECT_NOTES_LENGTH = 20000;
+
+// Window of the usage analytics endpoint, in days
+const DEFAULT_USAGE_DAYS = 30;
+const MAX_USAGE_DAYS = 365;
 
 /**
- * Start the HTTP + WebSocket server for browser mode
+ * Create the HTTP and WebSocket server without listening or starting background work. Every
+ * request and WebSocket message is handled against `state`, so servers with their own state can
+ * run next to each other in the same process.
  */
-export function startServer(): Promise<void> {
-  return new Promise((resolve) => {
-    const server = http.createServer(handleRequest);
-    const wss = new WebSocketServer({ server });
+export function createServer(state: AppState): http.Server {
+  const { events } = state;
+  const server = http.createServer((req, res) => state.run(() => matchedRoutes.run({ route: null }, () => handleRequest(state, req, res))));
+  const wss = new WebSocketServer({ noServer: true });
+  const controlWss = new WebSocketServer({ noServer: true });
+
+  // The control socket has its own path; every other path is the main socket
+  server.on('upgrade', (req, socket, head) => {
+    const { pathname } = new URL(req.url || '/', 'http://localhost');
+    const target = pathname === CONTROL_SOCKET_PATH ? controlWss : wss;
+    target.handleUpgrade(req, socket, head, (ws) => target.emit('connection', ws, req));
+  });
 
-    wss.on('connection', (ws) => {
-      console.log('[server] WebSocket client connected');
-      clients.set(ws, { ws, subscriptions: new Set() });
+  controlWss.on('connection', (ws, req) => {
+    const udid = new URL(req.url || '/', 'http://localhost').searchParams.get('udid');
+    // Run messages one at a time, so a drag's touches reach the simulator in order
+    let queue = Promise.resolve();
+    ws.on('message', (data) => {
+      queue = queue.then(() => state.run(async () => {
+        const reply = await handleControlMessage(data.toString(), udid);
+        if (reply && ws.readyState === WebSocket.OPEN) {
+          ws.send(JSON.stringify(reply));
+        }
+      }));
+    });
+  });
 
-      ws.on('message', (data) => {
-        handleWebSocketMessage(ws, data.toString());
-      });
+  wss.on('connection', (ws, req) => {
+    console.log('[server] WebSocket client connected');
+    events.addClient(ws, viewerOf(req));
 
-      ws.on('close', () => {
-        console.log('[server] WebSocket client disconnected');
-        const client = clients.get(ws);
-        // Clean up any streams this client was subscribed to
-        if (client) {
-          for (const sub of client.subscriptions) {
-            if (sub.startsWith('stream:')) {
-              const udid = sub.replace('stream:', '');
-              stopStreamForClient(udid, ws);
-            }
+    ws.on('message', (data) => {
+      state.run(() => handleWebSocketMessage(state, ws, data.toString()));
+    });
+
+    ws.on('close', () => state.run(() => {
+      console.log('[server] WebSocket client disconnected');
+      const client = events.clients.get(ws);
+      // Clean up any streams this client was subscribed to
+      if (client) {
+        for (const sub of client.subscriptions) {
+          if (sub.startsWith('stream:')) {
+            const udid = sub.replace('stream:', '');
+            stopStreamForClient(state, udid, ws);
           }
         }
-        clients.delete(ws);
-      });
-    });
+      }
+      if (client) {
+        const subscriber = client.id.toString();
+        removeSeries('plasma_ws_dropped_messages_total', { subscriber });
+        removeSeries('plasma_ws_upstream_pauses_total', { subscriber });
+        removeSeries('plasma_ws_buffered_bytes', { subscriber });
+      }
+      events.clients.delete(ws);
+    }));
+  });
 
-    // Forward simulator logs to WebSocket clients
-    logEmitter.on('log', (logEvent) => {
-      broadcast('simulator:log', logEvent);
-    });
+  return server;
+}
+
+/**
+ * Forward the state's events to its WebSocket clients and start the background work of a
+ * standalone server. Runs inside `state.run`, so timers it starts keep acting on this state.
+ */
+function startServices(state: AppState): void {
+  const { simulators, events } = state;
+
+  // Forward simulator logs to WebSocket clients
+  simulators.logEmitter.on('log', (logEvent) => {
+    broadcast(state, 'simulator:log', logEvent);
+  });
+
+  // Tell clients about session restarts and pick their streams back up once a session is back
+  simulators.sessionEmitter.on('restart', (event: SessionRestartEvent) => state.run(() => {
+    broadcast(state, 'simulator:session:restart', event);
+    if (event.status === 'scheduled') {
+      recordSessionRestart(event.udid);
+    }
+    if (event.status === 'restarted') {
+      resumeStreams(state, event.udid);
+    }
+  }));
+
+  // Stream captured Mac app windows to clients watching `macos`
+  state.macFrames.on('frame', (frame: string) => {
+    const jpeg = Buffer.from(frame, 'base64');
+    for (const client of events.clients.values()) {
+      if (client.subscriptions.has(`stream:${MAC_DEVICE_ID}`)) {
+        sendFrame(state, client.ws, MAC_DEVICE_ID, jpeg, 'jpeg');
+      }
+    }
+  });
+
+  // Echo session commands to clients that asked for them
+  simulators.sessionEmitter.on('command', (record: SessionCommandRecord) => {
+    for (const client of events.clients.values()) {
+      if (client.subscriptions.has(`commands:${record.udid}`)) {
+        sendToClient(state, client.ws, 'simulator:session:command', record);
+      }
+    }
+  });
+
+  // Tell clients about runtime issues found in app logs
+  state.runtimeIssues.emitter.on('issue', (issue: RuntimeIssue) => {
+    broadcast(state, 'simulator:runtime-issue', issue);
+  });
+
+  // Keep device pickers current without polling
+  state.simulatorWatcher.emitter.on('change', (change: SimulatorChange) => {
+    broadcast(state, 'simulator:device:change', change);
+  });
+
+  // Settings changed through any route, e.g. a flag turned on, show up without reloading
+  state.settings.on('change', (change: SettingChange) => {
+    broadcast(state, 'settings:change', change);
+  });
+
+  // Forward notifications to WebSocket clients
+  state.notifications.on('notification', (notification) => {
+    broadcast(state, 'notification', notification);
+  });
+
+  configureHelperLookup(config.helperLookup);
+  setWorkspaceRoot(config.workspaceDir);
+  setArtifactStore(createArtifactStore(config.storage));
+  setDatabaseBusyTimeout(config.database.busyTimeoutMs);
+  setDataStore(createDataStore(config.database));
+  runStartupRecovery();
+  startLogHistory(config.logHistory);
+  setSessionRestartPolicy(config.sessionRestart);
+  setRetryPolicy(config.retry);
+  setIdempotencyWindow(config.idempotencyWindowMs);
+  setCompressionEnabled(config.compression);
+  setSessionIdleTimeout(config.sessionIdleTimeoutMs);
+  setStreamDefaultOverrides(config.streamDefaults);
+  setTouchOverlayDefault(state.touchOverlay, config.showTouches);
+  setUrlPolicy(config.urlPolicy);
+  setSimulatorListTtl(config.simulatorListTtlMs);
+  setFeatureFlagDefaults(config.featureFlags);
+  configureSecrets(config.secrets);
+  migrateNotifierWebhookUrls().catch((err) => console.error('[notifiers] Failed to move webhook URLs to the secrets store:', err));
+  configureRequestRecording(config.requestRecording);
+  startDiskSpaceMonitor();
+  startStreamHealthMonitor(config.streamAlerts);
+  setResourceGuards(config.resourceGuards);
+  startRuntimeIssueDetection(state.runtimeIssues, simulators);
+  loadPlugins(state.plugins);
+  if (config.fakeDevices) {
+    console.log('[server] Using fake devices');
+    setDeviceBackend(createFakeDevicesBackend());
+  } else {
+    startSessionPrewarming(config.prewarmSimulators);
+  }
+  startSimulatorWatcher(state.simulatorWatcher, config.simulatorWatchIntervalMs, () => events.clients.size > 0);
+}
 
-    server.listen(PORT, () => {
-      console.log(`[server] HTTP server running at http://localhost:${PORT}`);
-      console.log(`[server] WebSocket server ready`);
+/**
+ * Start the HTTP + WebSocket server for browser mode. Resolves with the port it listens on.
+ */
+export function startServer(): Promise<number> {
+  return new Promise((resolve, reject) => {
+    const issues = validateConfig();
+    const errors = issues.filter((issue) => issue.severity === 'error');
+    if (errors.length > 0) {
+      reject(new Error(`Invalid configuration:\n${formatConfigIssues(errors)}`));
+      return;
+    }
+    installLogFilter(config.logFilter);
+    for (const warning of issues.filter((issue) => issue.severity === 'warning')) {
+      console.warn(`[server] Config warning: ${warning.setting} ${warning.message}`);
+    }
+
+    const state = new AppState();
+    const server = createServer(state);
+    state.run(() => startServices(state));
+
+    listenOnAvailablePort(server)
+      .then((port) => {
+        config.port = port;
+        if (!process.env.PLASMA_PUBLIC_URL) {
+          config.publicUrl = `http://localhost:${port}`;
+        }
+        announceInstance(port, config.publicUrl, config.discoveryFile || defaultDiscoveryFile());
+        console.log(`[server] HTTP server running at http://localhost:${port}`);
+        console.log(`[server] WebSocket server ready`);
+        resolve(port);
+      })
+      .catch(reject);
+  });
+}
+
+function listen(server: http.Server, port: number): Promise<void> {
+  return new Promise((resolve, reject) => {
+    const onError = (err: Error) => {
+      server.off('listening', onListening);
+      reject(err);
+    };
+    const onListening = () => {
+      server.off('error', onError);
       resolve();
-    });
+    };
+    server.once('error', onError);
+    server.once('listening', onListening);
+    server.listen(port);
   });
 }
 
+/**
+ * Listen on the configured port or, when it's taken and a port search is configured,
+ * on the first free port of the range
+ */
+async function listenOnAvailablePort(server: http.Server): Promise<number> {
+  const candidates = [config.port];
+  if (config.portSearch) {
+    for (let port = config.portSearch.start; port <= config.portSearch.end; port++) {
+      if (port !== config.port) {
+        candidates.push(port);
+      }
+    }
+  }
+
+  for (const port of candidates) {
+    try {
+      await listen(server, port);
+      if (port !== config.port) {
+        console.warn(`[server] Port ${config.port} is in use, using ${port} instead`);
+      }
+      return port;
+    } catch (err) {
+      if ((err as NodeJS.ErrnoException).code !== 'EADDRINUSE') {
+        throw err;
+      }
+    }
+  }
+
+  if (config.portSearch) {
+    throw new Error(
+      `Port ${config.port} and every port in ${config.portSearch.start}-${config.portSearch.end} are in use`
+    );
+  }
+  throw new Error(
+    `Port ${config.port} is already in use. Stop the other instance, choose another PLASMA_PORT, ` +
+      'or set PLASMA_PORT_AUTO=1 to use the next free port.'
+  );
+}
+
 /**
  * Handle HTTP requests
  */
-async function handleRequest(req: http.IncomingMessage, res: http.ServerResponse): Promise<void> {
+async function handleRequest(state: AppState, req: http.IncomingMessage, res: http.ServerResponse): Promise<void> {
   // CORS headers
   res.setHeader('Access-Control-Allow-Origin', '*');
-  res.setHeader('Access-Control-Allow-Methods', 'GET, POST, OPTIONS');
-  res.setHeader('Access-Control-Allow-Headers', 'Content-Type');
+  res.setHeader('Access-Control-Allow-Methods', 'GET, HEAD, POST, PUT, DELETE, OPTIONS');
+  res.setHeader('Access-Control-Allow-Headers', 'Content-Type, If-None-Match, Range, Idempotency-Key, X-Plasma-Export-Passphrase');
+  res.setHeader(
+    'Access-Control-Expose-Headers',
+    'ETag, X-Total-Count, X-Next-Cursor, Content-Length, Content-Range, Accept-Ranges, Idempotent-Replayed'
+  );
 
   if (req.method === 'OPTIONS') {
     res.writeHead(200);
@@ -96,8 +607,15 @@ async function handleRequest(req: http.IncomingMessage, res: http.ServerResponse
     return;
   }
 
-  const url = new URL(req.url || '/', `http://localhost:${PORT}`);
+  const url = new URL(req.url || '/', `http://localhost:${config.port}`);
   const path = url.pathname;
+  // Innermost, so the recorder and idempotency keys see the uncompressed body
+  compressResponse(req, res);
+  recordExchange(req, res, url);
+  trackRequestMetrics(req, res, path);
+  if (await handleIdempotencyKey(req, res, path)) {
+    return;
+  }
 
   try {
     // Projects API
@@ -124,11 +642,14 @@ async function handleRequest(req: http.IncomingMessage, res: http.ServerResponse
 
     if (path === '/api/projects/recent' && req.method === 'GET') {
       const query = url.searchParams.get('query') || undefined;
-      const limit = url.searchParams.get('limit');
-      const projects = getRecentProjects(query, limit ? parseInt(limit) : 10);
+      const projects = getRecentProjects(query, MAX_LIST_ITEMS);
       // Filter out projects that no longer exist
       const result = projects.filter((p) => fs.existsSync(p.path));
-      sendJson(res, result);
+      sendList(res, url, result, {
+        filterable: ['name'],
+        sortable: ['name', 'last_opened_at', 'created_at'],
+        defaultLimit: 10,
+      });
       return;
     }
 
@@ -151,7 +672,7 @@ async function handleRequest(req: http.IncomingMessage, res: http.ServerResponse
         }
 
         // Save to database with the scaffolded paths
-        const project = saveUnifiedProject(name, result.xcodePath, result.androidPath);
+        const project = await getDataStore().projects.save(name, result.xcodePath, result.androidPath);
         sendJson(res, { project, scaffoldResult: result });
         return;
       }
@@ -185,15 +706,18 @@ async function handleRequest(req: http.IncomingMessage, res: http.ServerResponse
       }
 
       // Save to database
-      const project = saveUnifiedProject(name, validatedXcodePath, validatedAndroidPath);
+      const project = await getDataStore().projects.save(name, validatedXcodePath, validatedAndroidPath);
       sendJson(res, { project });
       return;
     }
 
     if (path === '/api/projects/unified/recent' && req.method === 'GET') {
-      const limit = url.searchParams.get('limit');
-      const projects = getRecentUnifiedProjects(limit ? parseInt(limit) : 10);
-      sendJson(res, projects);
+      const projects = await getDataStore().projects.recent(MAX_LIST_ITEMS);
+      sendList(res, url, projects, {
+        filterable: ['name'],
+        sortable: ['name', 'last_opened_at', 'created_at'],
+        defaultLimit: 10,
+      });
       return;
     }
 
@@ -203,235 +727,1911 @@ async function handleRequest(req: http.IncomingMessage, res: http.ServerResponse
         sendJson(res, { error: 'Invalid project ID' }, 400);
         return;
       }
-      const project = getUnifiedProjectById(id);
+      const project = await getDataStore().projects.get(id);
       if (!project) {
         sendJson(res, { error: 'Project not found' }, 404);
         return;
       }
       // Update last opened
-      updateProjectLastOpened(id);
+      await getDataStore().projects.markOpened(id);
       sendJson(res, project);
       return;
     }
 
-    if (path === '/api/validate/xcode' && req.method === 'POST') {
-      const body = await readBody(req);
-      const result = validateXcodePath(body.path || '');
-      sendJson(res, result);
-      return;
+    // Project notifiers (Slack, Discord)
+    let params = matchRoute('/api/projects/:id/notifiers', path);
+    if (params) {
+      const project = await findProject(params.id);
+      if (!project) {
+        sendJson(res, { error: 'Project not found' }, 404);
+        return;
+      }
+
+      if (req.method === 'GET') {
+        sendList(res, url, await listProjectNotifiers(project), {
+          filterable: ['kind'],
+          sortable: ['id', 'kind', 'created_at'],
+        });
+        return;
+      }
+
+      if (req.method === 'POST') {
+        const body = await readBody(req);
+        const error = validateNotifierInput(body);
+        if (error) {
+          sendJson(res, { error }, 400);
+          return;
+        }
+        sendJson(res, await createProjectNotifier(project, body), 201);
+        return;
+      }
     }
 
-    if (path === '/api/validate/android' && req.method === 'POST') {
-      const body = await readBody(req);
-      const result = validateAndroidPath(body.path || '');
-      sendJson(res, result);
+    params = matchRoute('/api/projects/:id/notifiers/:notifierId', path);
+    if (params && req.method === 'DELETE') {
+      const project = await findProject(params.id);
+      if (!project || !(await deleteProjectNotifier(project, parseInt(params.notifierId)))) {
+        sendJson(res, { error: 'Notifier not found' }, 404);
+        return;
+      }
+      sendJson(res, { success: true });
       return;
     }
 
-    // Xcode API
-    if (path === '/api/xcode/discover' && req.method === 'POST') {
-      const body = await readBody(req);
-      const result = await discoverProject(body.path);
-      sendJson(res, result);
+    params = matchRoute('/api/projects/:id/notifiers/:notifierId/test', path);
+    if (params && req.method === 'POST') {
+      const project = await findProject(params.id);
+      const notifier = project && (await getProjectNotifier(project, parseInt(params.notifierId)));
+      if (!project || !notifier) {
+        sendJson(res, { error: 'Notifier not found' }, 404);
+        return;
+      }
+      await sendTestNotification(notifier, project.name);
+      sendJson(res, { success: true });
       return;
     }
 
-    if (path === '/api/xcode/launchable-products' && req.method === 'POST') {
-      const body = await readBody(req);
-      const result = await getLaunchableProducts(body.buildDir);
-      sendJson(res, result);
+    params = matchRoute('/api/projects/:id/notes', path);
+    if (params && (req.method === 'GET' || req.method === 'PUT')) {
+      const project = await findProject(params.id);
+      if (!project) {
+        sendJson(res, { error: 'Project not found' }, 404);
+        return;
+      }
+
+      let notes = project.notes;
+      if (req.method === 'PUT') {
+        const body = await readBody(req);
+        if (body.notes !== null && typeof body.notes !== 'string') {
+          sendJson(res, { error: 'notes must be a string or null' }, 400);
+          return;
+        }
+        if (body.notes && body.notes.length > MAX_PROJECT_NOTES_LENGTH) {
+          sendJson(res, { error: `notes can be at most ${MAX_PROJECT_NOTES_LENGTH} characters` }, 400);
+          return;
+        }
+        notes = body.notes || null;
+        await getDataStore().projects.setNotes(project.id, notes);
+      }
+
+      sendJson(res, { notes, notesHtml: notes ? renderMarkdown(notes) : null });
       return;
     }
 
-    // Simulator API
-    if (path === '/api/simulator/list' && req.method === 'GET') {
-      const result = await listSimulators();
-      sendJson(res, result);
+    params = matchRoute('/api/projects/:id/readme', path);
+    if (params && req.method === 'GET') {
+      const project = await findProject(params.id);
+      if (!project) {
+        sendJson(res, { error: 'Project not found' }, 404);
+        return;
+      }
+      const readme = findProjectReadme([project.xcode_path, project.android_path]);
+      if (!readme) {
+        sendJson(res, { error: 'The project has no README' }, 404);
+        return;
+      }
+      const markdown = fs.readFileSync(readme, 'utf-8');
+      sendJson(res, { path: readme, markdown, html: renderMarkdown(markdown) });
       return;
     }
 
-    if (path === '/api/simulator/launch' && req.method === 'POST') {
+    if (path === '/api/validate/xcode' && req.method === 'POST') {
       const body = await readBody(req);
-      const result = await installAndLaunch(body.udid, body.appPath, body.bundleId);
-      sendJson(res, { message: result });
+      const result = validateXcodePath(body.path || '');
+      sendJson(res, result);
       return;
     }
 
-    if (path === '/api/simulator/tap' && req.method === 'POST') {
+    if (path === '/api/validate/android' && req.method === 'POST') {
       const body = await readBody(req);
-      await sendTap(body.udid, body.x, body.y, body.screenWidth, body.screenHeight);
-      sendJson(res, { success: true });
+      const result = validateAndroidPath(body.path || '');
+      sendJson(res, result);
       return;
     }
 
-    if (path === '/api/simulator/swipe' && req.method === 'POST') {
+    if (path === '/api/universal-links/check' && req.method === 'POST') {
       const body = await readBody(req);
-      await sendSwipe(
-        body.udid,
-        body.startX,
-        body.startY,
-        body.endX,
-        body.endY,
-        body.screenWidth,
-        body.screenHeight,
-        body.duration
-      );
-      sendJson(res, { success: true });
+      if (!body.appPath && !body.associatedDomains) {
+        sendJson(res, { error: 'appPath or associatedDomains is required' }, 400);
+        return;
+      }
+      if (body.associatedDomains !== undefined && !(Array.isArray(body.associatedDomains) && body.associatedDomains.every((entry: unknown) => typeof entry === 'string'))) {
+        sendJson(res, { error: 'associatedDomains must be an array of strings' }, 400);
+        return;
+      }
+      sendJson(res, await checkUniversalLinks({ appPath: body.appPath, appId: body.appId, associatedDomains: body.associatedDomains }));
       return;
     }
 
-    if (path === '/api/simulator/touch' && req.method === 'POST') {
-      const body = await readBody(req);
-      await sendSessionCommand(body.udid, `touch ${body.type} ${body.touches.map((t: any) => `${t.x},${t.y}`).join(' ')}`);
-      sendJson(res, { success: true });
+    // Xcode API
+    if (path === '/api/xcode/discover' && req.method === 'POST') {
+      const body = validate(await readBody(req), DISCOVER_PROJECT_REQUEST);
+      const result = await discoverProject(body.path);
+      sendJson(res, result);
       return;
     }
 
-    // Health check
-    if (path === '/api/health') {
-      sendJson(res, { status: 'ok' });
+    if (path === '/api/xcode/destinations' && req.method === 'GET') {
+      sendList(res, url, listDestinationPresets(), { sortable: ['id', 'name'] });
       return;
     }
 
-    // 404
-    res.writeHead(404, { 'Content-Type': 'application/json' });
-    res.end(JSON.stringify({ error: 'Not found' }));
-  } catch (error) {
-    console.error('[server] Request error:', error);
-    res.writeHead(500, { 'Content-Type': 'application/json' });
-    res.end(JSON.stringify({ error: error instanceof Error ? error.message : 'Internal error' }));
-  }
-}
-
-/**
- * Handle WebSocket messages for streaming APIs
- */
-async function handleWebSocketMessage(ws: WebSocket, message: string): Promise<void> {
-  try {
-    const data = JSON.parse(message);
-    const { type, payload } = data;
-
-    switch (type) {
-      case 'xcode:build:start': {
-        const { path, scheme } = payload;
-        const emitter = buildSchemeStream(path, scheme);
-
-        emitter.on('event', (buildEvent) => {
-          sendToClient(ws, 'xcode:build:event', buildEvent);
-        });
-
-        emitter.on('end', () => {
-          sendToClient(ws, 'xcode:build:end', {});
-        });
-        break;
+    params = matchRoute('/api/projects/:id/destination', path);
+    if (params && (req.method === 'GET' || req.method === 'PUT')) {
+      const project = await findProject(params.id);
+      if (!project) {
+        sendJson(res, { error: 'Project not found' }, 404);
+        return;
       }
 
-      case 'simulator:stream:start': {
-        const { udid, fps = 60, quality = 0.7 } = payload;
-        const client = clients.get(ws);
-        if (client) {
-          client.subscriptions.add(`stream:${udid}`);
-        }
+      const destinations = getDataStore().projectDestinations;
+      const current = async () => {
+        const stored = await destinations.get(project);
+        return { destination: stored?.preset || DEFAULT_DESTINATION, updatedAt: stored?.updatedAt || null };
+      };
 
-        // Stop existing stream if any
-        const existing = activeStreams.get(udid);
-        if (existing) {
-          existing.abort.abort();
+      if (req.method === 'PUT') {
+        const body = await readBody(req);
+        if (!isDestinationPreset(body.destination)) {
+          sendJson(res, { error: `Unknown destination: ${body.destination}` }, 400);
+          return;
+        }
+        if (!(await destinations.set(project, body.destination, body.expectedUpdatedAt))) {
+          sendJson(res, { error: 'The destination was changed by another client', ...(await current()) }, 409);
+          return;
         }
+      }
 
-        // Start new stream
-        const session = await getOrCreateSession(udid, fps, quality);
-        const abortController = new AbortController();
-        activeStreams.set(udid, { abort: abortController });
+      sendJson(res, await current());
+      return;
+    }
 
-        streamFramesToWebSocket(session.streamUrl, udid, ws, abortController.signal);
-        break;
+    params = matchRoute('/api/projects/:id/settings', path);
+    if (params && (req.method === 'GET' || req.method === 'PUT')) {
+      const project = await findProject(params.id);
+      if (!project) {
+        sendJson(res, { error: 'Project not found' }, 404);
+        return;
       }
 
-      case 'simulator:stream:stop': {
-        const { udid } = payload;
-        stopStreamForClient(udid, ws);
-        break;
+      const settings = getDataStore().projectSettings;
+      const current = async (): Promise<ProjectSettings> => {
+        const stored = await settings.get(project);
+        return {
+          scheme: stored?.scheme ?? null,
+          configuration: stored?.configuration ?? null,
+          simulatorUdid: stored?.simulatorUdid ?? null,
+          environment: stored?.environment ?? {},
+          updatedAt: stored?.updatedAt ?? null,
+        };
+      };
+
+      if (req.method === 'PUT') {
+        const { expectedUpdatedAt, ...changes } = validate(await readBody(req), PROJECT_SETTINGS_REQUEST);
+        if (!(await settings.update(project, changes, expectedUpdatedAt))) {
+          sendJson(res, { error: 'The settings were changed by another client', ...(await current()) }, 409);
+          return;
+        }
       }
-    }
-  } catch (error) {
-    console.error('[server] WebSocket message error:', error);
-    sendToClient(ws, 'error', { message: error instanceof Error ? error.message : 'Unknown error' });
-  }
-}
-
-/**
- * Stream MJPEG frames to WebSocket client
- */
-async function streamFramesToWebSocket(
-  streamUrl: string,
-  udid: string,
-  ws: WebSocket,
-  signal: AbortSignal
-): Promise<void> {
-  try {
-    const response = await fetch(streamUrl, { signal: signal as any });
 
-    if (!response.ok) {
-      throw new Error(`Failed to connect to simulator stream: ${response.statusText}`);
+      sendJson(res, await current());
+      return;
     }
 
-    const body = response.body;
-    if (!body) {
-      throw new Error('No response body from simulator stream');
+    if (path === '/api/builds' && req.method === 'GET') {
+      const projectIdParam = url.searchParams.get('project_id');
+      const projectId = projectIdParam ? Number(projectIdParam) : undefined;
+      if (projectId !== undefined && (!Number.isInteger(projectId) || projectId < 1)) {
+        sendJson(res, { error: 'project_id must be a positive whole number' }, 400);
+        return;
+      }
+      const project = projectId === undefined ? undefined : await getDataStore().projects.get(projectId);
+      sendList(res, url, project === null ? [] : await listBuilds({ project }), {
+        filterable: ['scheme', 'configuration', 'destination', 'status'],
+        sortable: ['startedAt', 'finishedAt', 'durationMs', 'warnings', 'errors'],
+      });
+      return;
     }
 
-    let buffer = Buffer.alloc(0);
-    const boundary = '--mjpegstream';
-
-    const nodeStream = body as unknown as NodeJS.ReadableStream & { destroy?: () => void };
-
-    nodeStream.on('data', (chunk: Buffer) => {
-      if (signal.aborted || ws.readyState !== WebSocket.OPEN) {
-        nodeStream.destroy?.();
+    if (path === '/api/builds/compare' && req.method === 'GET') {
+      const a = url.searchParams.get('a');
+      const b = url.searchParams.get('b');
+      if (!a || !b) {
+        sendJson(res, { error: 'Both a and b build IDs are required' }, 400);
         return;
       }
+      const comparison = await compareBuilds(a, b);
+      if (!comparison) {
+        sendJson(res, { error: 'Build not found' }, 404);
+        return;
+      }
+      sendJson(res, comparison);
+      return;
+    }
+
+    // Which recorded builds produced an artifact, by its checksum
+    if (path === '/api/builds/provenance' && req.method === 'GET') {
+      const sha256 = url.searchParams.get('sha256') || '';
+      if (!/^[0-9a-fA-F]{64}$/.test(sha256)) {
+        sendJson(res, { error: 'sha256 must be a SHA-256 checksum in hex' }, 400);
+        return;
+      }
+      sendJson(res, await findBuildsByChecksum(sha256));
+      return;
+    }
+
+    params = matchRoute('/api/builds/:id/provenance', path);
+    if (params && req.method === 'GET') {
+      const provenance = await getBuildProvenance(params.id);
+      if (!provenance) {
+        sendJson(res, { error: 'Build not found' }, 404);
+        return;
+      }
+      sendJson(res, provenance);
+      return;
+    }
+
+    params = matchRoute('/api/builds/:id/log', path);
+    if (params && (req.method === 'GET' || req.method === 'HEAD')) {
+      const download = url.searchParams.get('download') === 'true';
+      const options = { downloadName: download ? `build-${params.id}.log` : undefined };
+      // Running builds write their log locally; finished ones are in the artifact store
+      const logPath = getBuildLogPath(params.id);
+      if (fs.existsSync(logPath)) {
+        sendFile(req, res, logPath, options);
+      } else {
+        await getArtifactStore().send(req, res, buildLogKey(params.id), options);
+      }
+      return;
+    }
+
+    if (path === '/api/xcode/build/cancel' && req.method === 'POST') {
+      const body = validate(await readBody(req), CANCEL_BUILD_REQUEST);
+      if (!cancelBuild(body.buildId)) {
+        sendJson(res, { error: 'Build not found or already finished' }, 404);
+        return;
+      }
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/xcode/launchable-products' && req.method === 'POST') {
+      const body = validate(await readBody(req), LAUNCHABLE_PRODUCTS_REQUEST);
+      const result = await getLaunchableProducts(body.buildDir);
+      sendJson(res, result);
+      return;
+    }
+
+    // Simulator API
+    if (path === '/api/simulator/list' && req.method === 'GET') {
+      const refresh = url.searchParams.get('refresh') === 'true';
+      await sendPolledList(req, res, url, () => listSimulators({ refresh }), {
+        filterable: ['state', 'runtime', 'name', 'deviceClass'],
+        sortable: ['name', 'state', 'runtime', 'deviceClass'],
+      });
+      return;
+    }
+
+    if (path === '/api/simulator/devicetypes' && req.method === 'GET') {
+      sendJson(res, await listDeviceTypes());
+      return;
+    }
+
+    if (path === '/api/simulator/create' && req.method === 'POST') {
+      const body = validate(await readBody(req), CREATE_SIMULATOR_REQUEST);
+      const invalid = validateCreateSimulator(body, await listDeviceTypes());
+      if (invalid) {
+        sendJson(res, { error: invalid }, 400);
+        return;
+      }
+      const udid = await createSimulator(body);
+      sendJson(res, { udid }, 201);
+      return;
+    }
+
+    if (path === '/api/simulator/erase' && req.method === 'POST') {
+      const body = validate(await readBody(req), ERASE_SIMULATOR_REQUEST);
+      if (!(await listSimulators({ refresh: true })).some((simulator) => simulator.udid === body.udid)) {
+        sendJson(res, { error: 'Simulator not found' }, 404);
+        return;
+      }
+      let snapshot: UndoInfo | null = null;
+      if (body.snapshot) {
+        snapshot = await eraseSimulatorWithUndo(body.udid);
+      } else {
+        await eraseSimulator(body.udid);
+      }
+      // Streams of the stopped session end; clients start a new one
+      state.events.frameFanouts.get(body.udid)?.end();
+      untrackStream(body.udid);
+      clearRuntimeIssues(state.runtimeIssues, body.udid);
+      const response: EraseSimulatorResponse = { success: true, undo: snapshot };
+      sendJson(res, response);
+      return;
+    }
+
+    if (path === '/api/simulator/uninstall' && req.method === 'POST') {
+      const body = validate(await readBody(req), UNINSTALL_APP_REQUEST);
+      let snapshot: UndoInfo | null = null;
+      if (body.snapshot) {
+        snapshot = await uninstallAppWithUndo(body.udid, body.bundleId);
+      } else {
+        await runSimctl(['uninstall', body.udid, body.bundleId]);
+      }
+      const response: UninstallAppResponse = { success: true, undo: snapshot };
+      sendJson(res, response);
+      return;
+    }
+
+    if (path === '/api/simulator/undo' && req.method === 'GET') {
+      sendJson(res, listUndos());
+      return;
+    }
+
+    params = matchRoute('/api/simulator/undo/:id', path);
+    if (params && req.method === 'POST') {
+      try {
+        const response: UndoResponse = { udid: await undo(params.id) };
+        sendJson(res, response);
+      } catch (err) {
+        if (err instanceof UndoNotFoundError) {
+          sendJson(res, { error: err.message }, 404);
+          return;
+        }
+        throw err;
+      }
+      return;
+    }
+
+    if (path === '/api/simulator/launch' && req.method === 'POST') {
+      const body = validate(await readBody(req), LAUNCH_REQUEST);
+      const launchOptions = { language: body.language, locale: body.locale };
+      const invalidOptions = validateLaunchOptions(launchOptions);
+      if (invalidOptions) {
+        sendJson(res, { error: invalidOptions }, 400);
+        return;
+      }
+      // Launching boots the simulator first unless it's already booted
+      const simulator = (getCachedSimulators()?.simulators || []).find((candidate) => candidate.udid === body.udid);
+      if (simulator?.state !== 'Booted') {
+        const exhausted = checkHostResources('boot');
+        if (exhausted) {
+          sendJson(res, exhausted, 503);
+          return;
+        }
+      }
+      const onBundleIdConflict = body.onBundleIdConflict ?? 'replace';
+      const conflict = await findBundleIdConflict(body.udid, body.appPath, body.bundleId);
+      if (conflict && onBundleIdConflict === 'fail') {
+        sendJson(res, { error: `A different app with bundle ID ${conflict.bundleId} is installed`, code: 'bundle_id_conflict', conflict }, 409);
+        return;
+      }
+      if (conflict && onBundleIdConflict === 'uninstall') {
+        await uninstallConflictingApp(body.udid, conflict);
+      }
+      clearRuntimeIssues(state.runtimeIssues, body.udid);
+      const result = await installAndLaunch(body.udid, body.appPath, body.bundleId, launchOptions);
+      sendJson(res, conflict ? { ...result, bundleIdConflict: { ...conflict, resolution: onBundleIdConflict } } : result);
+      return;
+    }
+
+    // What a launched app writes to stdout and stderr, from its first line
+    params = matchRoute('/api/simulator/launches/:launchId/console', path);
+    if (params && req.method === 'GET') {
+      const appConsole = getLaunchConsole(params.launchId);
+      if (!appConsole) {
+        sendJson(res, { error: 'Launch not found' }, 404);
+        return;
+      }
+      followEventReplay(req, res, appConsole, parseLastEventId(req.headers['last-event-id']) ?? 0);
+      return;
+    }
+
+    if (path === '/api/simulator/tap' && req.method === 'POST') {
+      const body = validate(await readBody(req), TAP_REQUEST);
+      if (!getSimulatorCapabilities(body.udid).touch) {
+        sendJson(res, { error: 'Touch input is not supported on this simulator' }, 400);
+        return;
+      }
+      await sendTap(body.udid, body.x, body.y, body.screenWidth, body.screenHeight);
+      recordTouch(state.touchOverlay, body.udid, 'began', [{ x: body.x, y: body.y }]);
+      recordTouch(state.touchOverlay, body.udid, 'ended', []);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/swipe' && req.method === 'POST') {
+      const body = validate(await readBody(req), SWIPE_REQUEST);
+      if (!getSimulatorCapabilities(body.udid).touch) {
+        sendJson(res, { error: 'Touch input is not supported on this simulator' }, 400);
+        return;
+      }
+      await sendSwipe(
+        body.udid,
+        body.startX,
+        body.startY,
+        body.endX,
+        body.endY,
+        body.screenWidth,
+        body.screenHeight,
+        body.duration
+      );
+      recordTouch(state.touchOverlay, body.udid, 'began', [{ x: body.startX, y: body.startY }]);
+      recordTouch(state.touchOverlay, body.udid, 'ended', [{ x: body.endX, y: body.endY }]);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/type' && req.method === 'POST') {
+      const body = validate(await readBody(req), TYPE_TEXT_REQUEST);
+      await typeText(body.udid, body.text);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/key' && req.method === 'POST') {
+      const body = validate(await readBody(req), KEY_REQUEST);
+      await pressKey(body.udid, keyCodeFor(body.key)!);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/button' && req.method === 'POST') {
+      const body = validate(await readBody(req), BUTTON_REQUEST);
+      await pressButton(body.udid, body.button);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/orientation' && req.method === 'POST') {
+      const body = validate(await readBody(req), ORIENTATION_REQUEST);
+      if (!getSimulatorCapabilities(body.udid).rotate) {
+        sendJson(res, { error: 'Rotation is not supported on this simulator' }, 400);
+        return;
+      }
+      await setOrientation(body.udid, body.orientation);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/location' && req.method === 'DELETE') {
+      const udid = url.searchParams.get('udid');
+      if (!udid) {
+        sendJson(res, { error: 'udid is required' }, 400);
+        return;
+      }
+      await clearLocation(udid);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/location' && req.method === 'POST') {
+      const body = validate(await readBody(req), LOCATION_REQUEST);
+      await setLocation(body.udid, { latitude: body.latitude, longitude: body.longitude });
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/privacy' && req.method === 'POST') {
+      const body = validate(await readBody(req), PRIVACY_REQUEST);
+      if (!body.bundleId && body.action !== 'reset') {
+        throw new ValidationError([{ path: 'bundleId', message: `is required to ${body.action} permissions` }]);
+      }
+      await setPrivacy(body.udid, body.action, body.service, body.bundleId);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/location/route' && req.method === 'POST') {
+      const body = validate(await readBody(req), LOCATION_ROUTE_REQUEST);
+      const speed = body.speed ?? DEFAULT_ROUTE_SPEED;
+      const waypoints = parseGpx(body.gpx);
+      if (waypoints.length < 2 || waypoints.length > MAX_ROUTE_WAYPOINTS) {
+        sendJson(res, { error: `The GPX file must have 2 to ${MAX_ROUTE_WAYPOINTS} points, it has ${waypoints.length}` }, 400);
+        return;
+      }
+      await startLocationRoute(body.udid, waypoints, speed);
+      const distanceMeters = Math.round(routeDistance(waypoints));
+      sendJson(res, { waypoints: waypoints.length, speed, distanceMeters, durationMs: Math.round((distanceMeters / speed) * 1000) });
+      return;
+    }
+
+    if (path === '/api/simulator/touch' && req.method === 'POST') {
+      const body = validate(await readBody(req), TOUCH_REQUEST);
+      if (!getSimulatorCapabilities(body.udid).touch) {
+        sendJson(res, { error: 'Touch input is not supported on this simulator' }, 400);
+        return;
+      }
+      await sendSessionCommand(body.udid, `touch ${body.type} ${body.touches.map((t) => `${t.x},${t.y}`).join(' ')}`);
+      recordTouch(state.touchOverlay, body.udid, body.type, body.touches);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/simulator/thumbnail' && req.method === 'GET') {
+      const udid = url.searchParams.get('udid');
+      const fps = Number(url.searchParams.get('fps') ?? DEFAULT_THUMBNAIL_OPTIONS.fps);
+      const scale = Number(url.searchParams.get('scale') ?? DEFAULT_THUMBNAIL_OPTIONS.scale);
+      if (!udid) {
+        sendJson(res, { error: 'udid is required' }, 400);
+        return;
+      }
+      if (!(fps > 0 && fps <= MAX_THUMBNAIL_FPS)) {
+        sendJson(res, { error: `fps must be above 0 and at most ${MAX_THUMBNAIL_FPS}` }, 400);
+        return;
+      }
+      if (!(scale > 0 && scale <= 1)) {
+        sendJson(res, { error: 'scale must be above 0 and at most 1' }, 400);
+        return;
+      }
+      if (!(await listSimulators()).some((sim) => sim.udid === udid)) {
+        sendJson(res, { error: 'Simulator not found' }, 404);
+        return;
+      }
+      streamThumbnails(state, req, res, udid, { fps, scale });
+      return;
+    }
+
+    if (path === '/api/simulator/video' && req.method === 'GET') {
+      const udid = url.searchParams.get('udid');
+      const fps = Number(url.searchParams.get('fps') ?? DEFAULT_VIDEO_OPTIONS.fps);
+      const bitrate = Number(url.searchParams.get('bitrate') ?? DEFAULT_VIDEO_OPTIONS.bitrate);
+      if (!udid) {
+        sendJson(res, { error: 'udid is required' }, 400);
+        return;
+      }
+      if (!(Number.isInteger(fps) && fps > 0 && fps <= MAX_VIDEO_FPS)) {
+        sendJson(res, { error: `fps must be a whole number above 0 and at most ${MAX_VIDEO_FPS}` }, 400);
+        return;
+      }
+      if (!(Number.isInteger(bitrate) && bitrate >= MIN_VIDEO_BITRATE && bitrate <= MAX_VIDEO_BITRATE)) {
+        sendJson(res, { error: `bitrate must be a whole number of bits per second from ${MIN_VIDEO_BITRATE} to ${MAX_VIDEO_BITRATE}` }, 400);
+        return;
+      }
+      if (!(await listSimulators()).some((sim) => sim.udid === udid)) {
+        sendJson(res, { error: 'Simulator not found' }, 404);
+        return;
+      }
+      try {
+        streamVideo(state, req, res, udid, { fps, bitrate });
+      } catch (err) {
+        if (err instanceof VideoUnavailableError) {
+          sendJson(res, { error: err.message }, 501);
+          return;
+        }
+        throw err;
+      }
+      return;
+    }
+
+    if (path === '/api/simulator/stream/logs' && req.method === 'GET') {
+      // Replay buffered events after `since` (or the standard Last-Event-ID on reconnect), then follow live
+      const udid = url.searchParams.get('udid');
+      const since = parseInt(url.searchParams.get('since') || (req.headers['last-event-id'] as string) || '');
+      // Clients written before typed event names listen for a single `log` event
+      const legacy = url.searchParams.get('legacy') === 'true';
+      const send = openEventStream(req, res);
+      const sendLog = (event: StreamLogEvent) => send(legacy ? 'log' : event.event, event, event.id);
+
+      if (!isNaN(since)) {
+        getLogHistory(udid, since).forEach(sendLog);
+      }
+
+      const onLog = (event: StreamLogEvent) => {
+        if (!udid || event.udid === udid) {
+          sendLog(event);
+        }
+      };
+      const { logEmitter } = state.simulators;
+      logEmitter.on('log', onLog);
+      req.on('close', () => logEmitter.off('log', onLog));
+      return;
+    }
+
+    // The simulator's system log, with the app's own output, as opposed to Plasma's diagnostics above
+    if (path === '/api/simulator/syslog' && req.method === 'GET') {
+      const udid = url.searchParams.get('udid');
+      const predicate = url.searchParams.get('predicate') || null;
+      if (!udid) {
+        sendJson(res, { error: 'udid is required' }, 400);
+        return;
+      }
+      if (predicate && predicate.length > MAX_PREDICATE_LENGTH) {
+        sendJson(res, { error: `predicate must be at most ${MAX_PREDICATE_LENGTH} characters` }, 400);
+        return;
+      }
+      let profiles: LogNoiseProfileId[];
+      try {
+        profiles = parseNoiseProfiles(url.searchParams.get('noise'));
+      } catch (err) {
+        sendJson(res, { error: (err as Error).message }, 400);
+        return;
+      }
+      const simulator = (await listSimulators()).find((sim) => sim.udid === udid);
+      if (!simulator) {
+        sendJson(res, { error: 'Simulator not found' }, 404);
+        return;
+      }
+      if (simulator.state !== 'Booted') {
+        sendJson(res, { error: 'Simulator must be booted to stream its log' }, 409);
+        return;
+      }
+
+      const send = openEventStream(req, res);
+      const stop = streamSyslog(
+        udid,
+        { predicate, profiles },
+        (entry) => send('syslog', entry),
+        () => res.end()
+      );
+      req.on('close', stop);
+      return;
+    }
+
+    if (path === '/api/simulator/syslog/noise-profiles' && req.method === 'GET') {
+      sendJson(res, { profiles: listNoiseProfiles(), defaults: DEFAULT_NOISE_PROFILES });
+      return;
+    }
+
+    params = matchRoute('/api/simulator/:udid/runtime-issues', path);
+    if (params && req.method === 'GET') {
+      sendList(res, url, getRuntimeIssues(state.runtimeIssues, params.udid), {
+        filterable: ['kind', 'bundleId'],
+        sortable: ['id', 'count', 'lastSeenAt'],
+      });
+      return;
+    }
+
+    params = matchRoute('/api/simulator/:udid/ocr', path);
+    if (params && req.method === 'POST') {
+      const body = await readBody(req);
+      if (body.level !== undefined && body.level !== 'fast' && body.level !== 'accurate') {
+        sendJson(res, { error: 'level must be "fast" or "accurate"' }, 400);
+        return;
+      }
+      try {
+        const observations = await recognizeText(await captureScreenshot(params.udid), body.level, body.languages || []);
+        const response: OcrResponse = { udid: params.udid, observations };
+        if (typeof body.find === 'string') {
+          response.matches = findText(observations, body.find);
+          response.found = response.matches.length > 0;
+        }
+        sendJson(res, response);
+      } catch (err) {
+        if (err instanceof OcrUnavailableError) {
+          sendJson(res, { error: err.message }, 501);
+          return;
+        }
+        throw err;
+      }
+      return;
+    }
+
+    params = matchRoute('/api/simulator/:udid/pixel', path);
+    if (params && req.method === 'GET') {
+      const x = Number(url.searchParams.get('x'));
+      const y = Number(url.searchParams.get('y'));
+      const radius = Number(url.searchParams.get('radius') || 0);
+      if (!url.searchParams.has('x') || !url.searchParams.has('y') || !(x >= 0 && x <= 1 && y >= 0 && y <= 1)) {
+        sendJson(res, { error: 'x and y must be between 0 and 1' }, 400);
+        return;
+      }
+      if (!Number.isInteger(radius) || radius < 0 || radius > MAX_SAMPLE_RADIUS) {
+        sendJson(res, { error: `radius must be a whole number between 0 and ${MAX_SAMPLE_RADIUS}` }, 400);
+        return;
+      }
+      const image = decodePng(await captureScreenshot(params.udid));
+      sendJson(res, sampleColor(image, x, y, radius));
+      return;
+    }
+
+    params = matchRoute('/api/simulator/:udid/biometrics', path);
+    if (params && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!BIOMETRIC_ACTIONS.includes(body.action)) {
+        sendJson(res, { error: `action must be one of ${BIOMETRIC_ACTIONS.join(', ')}` }, 400);
+        return;
+      }
+      if (body.sensor !== undefined && !BIOMETRIC_SENSORS.includes(body.sensor)) {
+        sendJson(res, { error: `sensor must be one of ${BIOMETRIC_SENSORS.join(', ')}` }, 400);
+        return;
+      }
+      await sendBiometric(params.udid, body.action, body.sensor);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    params = matchRoute('/api/simulator/:udid/biometrics/enrollment', path);
+    if (params && req.method === 'PUT') {
+      const body = await readBody(req);
+      if (typeof body.enrolled !== 'boolean') {
+        sendJson(res, { error: 'enrolled must be a boolean' }, 400);
+        return;
+      }
+      await setBiometricEnrollment(params.udid, body.enrolled);
+      sendJson(res, { enrolled: body.enrolled });
+      return;
+    }
+
+    params = matchRoute('/api/simulator/:udid/appearance', path);
+    if (params && (req.method === 'GET' || req.method === 'PUT')) {
+      if (req.method === 'PUT') {
+        const body = await readBody(req);
+        if (!APPEARANCES.includes(body.appearance)) {
+          sendJson(res, { error: `appearance must be one of ${APPEARANCES.join(', ')}` }, 400);
+          return;
+        }
+        await setAppearance(params.udid, body.appearance);
+        sendJson(res, { appearance: body.appearance });
+        return;
+      }
+      sendJson(res, { appearance: await getAppearance(params.udid) });
+      return;
+    }
+
+    params = matchRoute('/api/simulator/:udid/status-bar', path);
+    if (params && req.method === 'PUT') {
+      const body = await readBody(req);
+      const invalid = validateStatusBarOverrides(body);
+      if (invalid) {
+        sendJson(res, { error: invalid }, 400);
+        return;
+      }
+      await overrideStatusBar(params.udid, body);
+      sendJson(res, { success: true });
+      return;
+    }
+    if (params && req.method === 'DELETE') {
+      await clearStatusBar(params.udid);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    params = matchRoute('/api/simulator/:udid/accessibility', path);
+    if (params && req.method === 'PUT') {
+      const { voiceOver, ...settings } = validate(await readBody(req), ACCESSIBILITY_REQUEST);
+      if (voiceOver !== undefined) {
+        sendJson(res, { error: "Simulator doesn't have VoiceOver; use Accessibility Inspector instead" }, 501);
+        return;
+      }
+      await setAccessibilitySettings(params.udid, settings);
+      sendJson(res, await getAccessibilitySettings(params.udid));
+      return;
+    }
+    if (params && req.method === 'GET') {
+      sendJson(res, await getAccessibilitySettings(params.udid));
+      return;
+    }
+
+    // Shortcuts to panes of the Settings app, e.g. for permission testing
+    if (path === '/api/simulator/settings/panes' && req.method === 'GET') {
+      sendJson(res, SETTINGS_PANES);
+      return;
+    }
+
+    params = matchRoute('/api/simulator/:udid/settings', path);
+    if (params && req.method === 'POST') {
+      const body = validate(await readBody(req), SETTINGS_PANE_REQUEST);
+      if (!body.bundleId && SETTINGS_PANES.find((pane) => pane.id === body.pane)!.requiresBundleId) {
+        throw new ValidationError([{ path: 'bundleId', message: `is required to open ${body.pane} settings` }]);
+      }
+      const response: OpenSettingsPaneResponse = { url: await openSettingsPane(params.udid, body.pane, body.bundleId) };
+      sendJson(res, response);
+      return;
+    }
+
+    // Condition profiles, e.g. to test a worst case device
+    if (path === '/api/simulator/conditions/profiles' && req.method === 'GET') {
+      sendJson(res, CONDITION_PROFILES);
+      return;
+    }
+
+    params = matchRoute('/api/simulator/:udid/conditions', path);
+    if (params && req.method === 'POST') {
+      const body = validate(await readBody(req), CONDITIONS_REQUEST);
+      sendJson(res, await applyConditions(params.udid, body.profile ?? null, body.conditions));
+      return;
+    }
+    if (params && req.method === 'GET') {
+      const conditions = getAppliedConditions(params.udid);
+      if (!conditions) {
+        sendJson(res, { error: 'No conditions have been applied to this simulator' }, 404);
+        return;
+      }
+      sendJson(res, conditions);
+      return;
+    }
+
+    params = matchRoute('/api/simulator/:udid/open-url', path);
+    if (params && req.method === 'POST') {
+      const body = await readBody(req);
+      if (typeof body.url !== 'string' || !/^[a-z][a-z0-9+.-]*:/i.test(body.url)) {
+        sendJson(res, { error: 'url must be an absolute URL' }, 400);
+        return;
+      }
+      const blocked = checkUrl(body.url);
+      if (blocked) {
+        sendJson(res, { error: blocked, code: 'url_not_allowed' }, 403);
+        return;
+      }
+      await runSimctl(['openurl', params.udid, body.url]);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    params = matchRoute('/api/simulator/:udid/frame-pacing', path);
+    if (params && req.method === 'GET') {
+      const pacing = getFramePacing(params.udid);
+      if (!pacing) {
+        sendJson(res, { error: 'This simulator has not streamed yet' }, 404);
+        return;
+      }
+      sendJson(res, pacing);
+      return;
+    }
+
+    params = matchRoute('/api/simulator/:udid/frame-pacing/reset', path);
+    if (params && req.method === 'POST') {
+      if (!getFramePacing(params.udid)) {
+        sendJson(res, { error: 'This simulator has not streamed yet' }, 404);
+        return;
+      }
+      resetFramePacing(params.udid);
+      sendJson(res, getFramePacing(params.udid));
+      return;
+    }
+
+    params = matchRoute('/api/simulator/:udid/resources/stream', path);
+    if (params && req.method === 'GET') {
+      const { udid } = params;
+      // Defaults to the app last launched through Plasma
+      const bundleId = url.searchParams.get('bundleId') || getLaunchedApp(udid);
+      if (!bundleId) {
+        sendJson(res, { error: 'No app has been launched on this simulator; pass bundleId' }, 400);
+        return;
+      }
+      const requestedInterval = parseInt(url.searchParams.get('interval') || '') || 1000;
+      const interval = Math.min(Math.max(requestedInterval, MIN_SAMPLE_INTERVAL_MS), MAX_SAMPLE_INTERVAL_MS);
+
+      const send = openEventStream(req, res);
+      const stop = watchAppResources(
+        udid,
+        bundleId,
+        interval,
+        (sample) => send('sample', sample),
+        () => send('app_not_running', { udid, bundleId })
+      );
+      req.on('close', stop);
+      return;
+    }
+
+    params = matchRoute('/api/simulator/:udid/calibrate', path);
+    if (params && req.method === 'POST') {
+      const simulator = (await listSimulators()).find((sim) => sim.udid === params!.udid);
+      if (!simulator) {
+        sendJson(res, { error: 'Simulator not found' }, 404);
+        return;
+      }
+      if (simulator.state !== 'Booted') {
+        sendJson(res, { error: 'Simulator must be booted to calibrate' }, 409);
+        return;
+      }
+      try {
+        sendJson(res, await calibrate(simulator.udid, simulator.deviceType || 'unknown'));
+      } catch (err) {
+        if (err instanceof CalibrationError) {
+          sendJson(res, { error: err.message }, 422);
+          return;
+        }
+        throw err;
+      }
+      return;
+    }
+
+    // Simulator-server sessions
+    if (path === '/api/simulator/sessions' && req.method === 'GET') {
+      sendList(res, url, listSessions(), {
+        filterable: ['udid', 'protocolVersion'],
+        sortable: ['udid', 'fps', 'quality', 'protocolVersion'],
+      });
+      return;
+    }
+
+    // Tear down a simulator's simulator-server session, e.g. to free it without waiting for the idle timeout
+    params = matchRoute('/api/simulator/session/:udid', path);
+    if (params && req.method === 'GET') {
+      const info = getSessionInfo(params.udid);
+      if (!info) {
+        sendJson(res, { error: 'Simulator has no active session' }, 404);
+        return;
+      }
+      sendJson(res, info);
+      return;
+    }
+    if (params && req.method === 'DELETE') {
+      const { udid } = params;
+      if (!stopSession(udid)) {
+        sendJson(res, { error: 'Simulator has no active session' }, 404);
+        return;
+      }
+      state.events.frameFanouts.get(udid)?.end();
+      untrackStream(udid);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    // Screen recordings
+    if ((path === '/api/simulator/recording/start' || path === '/api/simulator/recording/stop') && req.method === 'POST') {
+      const body = await readBody(req);
+      if (typeof body.udid !== 'string' || !body.udid) {
+        sendJson(res, { error: 'udid is required' }, 400);
+        return;
+      }
+      if (path.endsWith('/stop')) {
+        if (!isRecording(body.udid)) {
+          sendJson(res, { error: 'Simulator is not being recorded' }, 404);
+          return;
+        }
+        sendJson(res, await stopRecording(body.udid));
+        return;
+      }
+      if (body.source !== undefined && body.source !== 'simctl' && body.source !== 'stream') {
+        sendJson(res, { error: 'source must be simctl or stream' }, 400);
+        return;
+      }
+      const simulator = (await listSimulators()).find((sim) => sim.udid === body.udid);
+      if (!simulator) {
+        sendJson(res, { error: 'Simulator not found' }, 404);
+        return;
+      }
+      if (simulator.state !== 'Booted') {
+        sendJson(res, { error: 'Simulator must be booted to record' }, 409);
+        return;
+      }
+      // With a configured watermark, recordings are stream recordings with it drawn in, for whoever started them
+      const source = body.source ?? (config.watermark ? 'stream' : 'simctl');
+      const watermark = config.watermark ? createWatermarker(config.watermark, { udid: simulator.udid, user: viewerOf(req) }) : null;
+      sendJson(res, await startRecording(simulator.udid, source, watermark), 201);
+      return;
+    }
+
+    if (path === '/api/recordings' && req.method === 'GET') {
+      sendList(res, url, listRecordings(), {
+        filterable: ['udid', 'source', 'status'],
+        sortable: ['startedAt', 'durationMs', 'sizeBytes'],
+      });
+      return;
+    }
+
+    params = matchRoute('/api/recordings/:id', path);
+    if (params && req.method === 'GET') {
+      const recording = getRecording(params.id);
+      if (!recording) {
+        sendJson(res, { error: 'Recording not found' }, 404);
+        return;
+      }
+      sendJson(res, recording);
+      return;
+    }
+
+    if (params && req.method === 'DELETE') {
+      if (!(await deleteRecording(params.id))) {
+        sendJson(res, { error: 'Recording not found' }, 404);
+        return;
+      }
+      sendJson(res, { success: true });
+      return;
+    }
+
+    params = matchRoute('/api/recordings/:id/video', path);
+    if (params && (req.method === 'GET' || req.method === 'HEAD')) {
+      const recording = getRecording(params.id);
+      if (!recording || recording.status !== 'finished') {
+        sendJson(res, { error: 'Recording not found' }, 404);
+        return;
+      }
+      const download = url.searchParams.get('download') === 'true';
+      const key = recordingKey(recording.id, recording.source);
+      await getArtifactStore().send(req, res, key, {
+        downloadName: download ? `recording-${recording.id}${key.slice(key.lastIndexOf('.'))}` : undefined,
+        contentType: recording.source === 'stream' ? STREAM_RECORDING_CONTENT_TYPE : undefined,
+      });
+      return;
+    }
+
+    params = matchRoute('/api/simulator/sessions/:udid/commands', path);
+    if (params && req.method === 'GET') {
+      sendList(res, url, getSessionCommands(params.udid), { sortable: ['sentAt'] });
+      return;
+    }
+
+    params = matchRoute('/api/simulator/:udid', path);
+    if (params && req.method === 'DELETE') {
+      const { udid } = params;
+      const simulators = await listSimulators({ refresh: true });
+      if (!simulators.some((simulator) => simulator.udid === udid)) {
+        sendJson(res, { error: 'Simulator not found' }, 404);
+        return;
+      }
+      await deleteSimulator(udid);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    // Android emulators, behind the `android` feature flag
+    if (path.startsWith('/api/android/') && !(await isFeatureEnabled('android'))) {
+      sendJson(res, { error: 'Android emulators are disabled, turn on the android feature flag' }, 404);
+      return;
+    }
+
+    if (path === '/api/android/emulators' && req.method === 'GET') {
+      sendList(res, url, await listEmulators(), {
+        filterable: ['state', 'name'],
+        sortable: ['name', 'state'],
+      });
+      return;
+    }
+
+    if (path === '/api/android/boot' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (typeof body.name !== 'string' || !body.name) {
+        sendJson(res, { error: 'name is required' }, 400);
+        return;
+      }
+      const exhausted = checkHostResources('boot');
+      if (exhausted) {
+        sendJson(res, exhausted, 503);
+        return;
+      }
+      sendJson(res, await bootEmulator(body.name));
+      return;
+    }
+
+    if (path === '/api/android/install' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (typeof body.serial !== 'string' || typeof body.apkPath !== 'string' || !body.apkPath) {
+        sendJson(res, { error: 'serial and apkPath are required' }, 400);
+        return;
+      }
+      if (!(await isEmulatorRunning(body.serial))) {
+        sendJson(res, { error: 'Emulator not found, boot it first' }, 404);
+        return;
+      }
+      await installApk(body.serial, body.apkPath);
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/android/launch' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (typeof body.serial !== 'string') {
+        sendJson(res, { error: 'serial is required' }, 400);
+        return;
+      }
+      if (body.apkPath !== undefined && (typeof body.apkPath !== 'string' || !body.apkPath)) {
+        sendJson(res, { error: 'apkPath must be a path to an APK' }, 400);
+        return;
+      }
+      const invalidLaunch = validateAndroidLaunch(body.packageName, body.activity);
+      if (invalidLaunch) {
+        sendJson(res, { error: invalidLaunch }, 400);
+        return;
+      }
+      if (!(await isEmulatorRunning(body.serial))) {
+        sendJson(res, { error: 'Emulator not found, boot it first' }, 404);
+        return;
+      }
+      if (body.apkPath) {
+        await installApk(body.serial, body.apkPath);
+      }
+      sendJson(res, await launchAndroidApp(body.serial, body.packageName, body.activity));
+      return;
+    }
+
+    if (path === '/api/android/build/stream' && req.method === 'GET') {
+      // An EventSource reconnecting after a network blip picks the build up where it left off
+      const lastEventId = parseLastEventId(req.headers['last-event-id']);
+      const resumed = lastEventId !== null ? findEventReplay(lastEventId) : null;
+      if (resumed) {
+        followEventReplay(req, res, resumed, lastEventId!);
+        return;
+      }
+      const projectPath = url.searchParams.get('path');
+      const task = url.searchParams.get('task') || DEFAULT_GRADLE_TASK;
+      if (!projectPath) {
+        sendJson(res, { error: 'path is required' }, 400);
+        return;
+      }
+      if (!isGradleTask(task)) {
+        sendJson(res, { error: `Invalid Gradle task: ${task}` }, 400);
+        return;
+      }
+      const exhausted = checkHostResources('build');
+      if (exhausted) {
+        sendJson(res, exhausted, 503);
+        return;
+      }
+      const build = buildGradleStream(projectPath, task);
+      // Stopped once the client is gone for longer than a reconnect takes
+      const events = createEventReplay(() => build.cancel());
+      build.on('event', (buildEvent: BuildEvent) => events.push(buildEvent.event, buildEvent));
+      build.on('end', () => {
+        events.push('build.end', {});
+        events.end();
+      });
+      followEventReplay(req, res, events, 0);
+      return;
+    }
+
+    if (path === '/api/android/launchable-products' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (typeof body.path !== 'string' || !body.path) {
+        sendJson(res, { error: 'path is required' }, 400);
+        return;
+      }
+      sendJson(res, await findApks(body.path));
+      return;
+    }
+
+    // macOS apps run locally
+    if (path === '/api/macos/launch' && req.method === 'POST') {
+      const body = await readBody(req);
+      clearRuntimeIssues(state.runtimeIssues, MAC_DEVICE_ID);
+      const result = await launchMacApp(body.appPath, state.macFrames, { capture: body.capture, captureFps: body.captureFps });
+      sendJson(res, result);
+      return;
+    }
+
+    if (path === '/api/macos/terminate' && req.method === 'POST') {
+      const body = await readBody(req);
+      if (!terminateMacApp(body.bundleId)) {
+        sendJson(res, { error: 'App is not running' }, 404);
+        return;
+      }
+      sendJson(res, { success: true });
+      return;
+    }
+
+    // Profiling API
+    if (path === '/api/profiling/launch-timings' && req.method === 'GET') {
+      sendList(res, url, getLaunchTimingStats(), {
+        filterable: ['device_type'],
+        sortable: ['device_type', 'launches', 'avg_total_ms', 'max_total_ms', 'last_launch_at'],
+      });
+      return;
+    }
+
+    // Notifications API
+    if (path === '/api/notifications' && req.method === 'GET') {
+      const unreadOnly = url.searchParams.get('unread') === 'true';
+      const query = parseListQuery(url.searchParams, {
+        filterable: ['kind', 'level'],
+        sortable: ['id', 'created_at', 'kind', 'level'],
+        defaultLimit: 50,
+      });
+      const page = await listNotifications(query, unreadOnly);
+      setListHeaders(res, page.total, page.nextCursor);
+      sendJson(res, { notifications: page.items, unreadCount: await getUnreadNotificationCount() });
+      return;
+    }
+
+    if (path === '/api/notifications/stream' && req.method === 'GET') {
+      // Notifications are numbered by the database; a reconnecting client gets the ones it missed
+      const lastEventId = parseLastEventId(req.headers['last-event-id']);
+      const send = openEventStream(req, res);
+      // Skips notifications already sent while catching up with the missed ones
+      let lastSentId = lastEventId ?? 0;
+      const onNotification = (notification: NotificationRecord) => {
+        if (notification.id > lastSentId) {
+          lastSentId = notification.id;
+          send('notification', notification, notification.id);
+        }
+      };
+      const missed = lastEventId !== null ? await listNotificationsSince(lastEventId) : [];
+      missed.forEach(onNotification);
+      state.notifications.on('notification', onNotification);
+      req.on('close', () => state.notifications.off('notification', onNotification));
+      return;
+    }
+
+    if (path === '/api/alerts' && req.method === 'GET') {
+      sendJson(res, { alerts: getFiringAlerts() });
+      return;
+    }
+
+    if (path === '/api/notifications/read-all' && req.method === 'POST') {
+      await markAllNotificationsRead();
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path.startsWith('/api/notifications/') && path.endsWith('/read') && req.method === 'POST') {
+      const id = parseInt(path.split('/')[3] || '');
+      if (isNaN(id)) {
+        sendJson(res, { error: 'Invalid notification ID' }, 400);
+        return;
+      }
+      if (!(await markNotificationRead(id))) {
+        sendJson(res, { error: 'Notification not found' }, 404);
+        return;
+      }
+      sendJson(res, { success: true });
+      return;
+    }
+
+    // Plugins API
+    if (path === '/api/plugins' && req.method === 'GET') {
+      sendList(
+        res,
+        url,
+        listPlugins(state.plugins).map((plugin) => ({
+          name: plugin.manifest.name,
+          version: plugin.manifest.version || null,
+          description: plugin.manifest.description || null,
+          routes: plugin.manifest.routes || [],
+          buildHooks: plugin.manifest.buildHooks || [],
+          deviceActions: plugin.manifest.deviceActions || [],
+        })),
+        { sortable: ['name'] }
+      );
+      return;
+    }
+
+    params = matchRoute('/api/plugins/:name/actions/:action', path);
+    if (params && req.method === 'POST') {
+      const plugin = getPlugin(state.plugins, params.name);
+      const action = plugin?.manifest.deviceActions?.find((a) => a.id === params!.action);
+      if (!plugin || !action) {
+        sendJson(res, { error: 'Plugin action not found' }, 404);
+        return;
+      }
+      const body = await readBody(req);
+      if (!body.udid) {
+        sendJson(res, { error: 'udid is required' }, 400);
+        return;
+      }
+      const result = await invokePlugin(plugin, {
+        type: 'device_action',
+        action: action.id,
+        udid: body.udid,
+        params: body.params || {},
+      });
+      sendJson(res, result.body ?? {}, result.status || 200);
+      return;
+    }
+
+    if (path.startsWith('/api/plugins/')) {
+      // /api/plugins/<name>/routes/<plugin-defined path>
+      const [name, section, ...rest] = path.slice('/api/plugins/'.length).split('/');
+      const plugin = getPlugin(state.plugins, name);
+      const route = plugin && section === 'routes' ? findPluginRoute(plugin, req.method || 'GET', rest.join('/')) : null;
+      if (!plugin || !route) {
+        sendJson(res, { error: 'Plugin route not found' }, 404);
+        return;
+      }
+      const body = req.method === 'GET' ? {} : await readBody(req);
+      const result = await invokePlugin(plugin, {
+        type: 'route',
+        method: req.method || 'GET',
+        path: rest.join('/'),
+        query: Object.fromEntries(url.searchParams),
+        body,
+      });
+      sendJson(res, result.body ?? {}, result.status || 200);
+      return;
+    }
+
+    // Metrics (Prometheus text format)
+    if (path === '/api/metrics' && req.method === 'GET') {
+      const { clients } = state.events;
+      setGauge('plasma_ws_subscribers', {}, clients.size);
+      for (const client of clients.values()) {
+        setGauge('plasma_ws_buffered_bytes', { subscriber: client.id.toString() }, client.ws.bufferedAmount);
+      }
+      res.writeHead(200, { 'Content-Type': 'text/plain; version=0.0.4' });
+      res.end(renderMetrics());
+      return;
+    }
+
+    // Instance export and import
+    if (path === '/api/admin/export' && req.method === 'GET') {
+      const passphrase = req.headers['x-plasma-export-passphrase'];
+      res.setHeader('Content-Disposition', 'attachment; filename="plasma-export.json"');
+      sendJson(res, await exportInstance(typeof passphrase === 'string' && passphrase ? passphrase : null));
+      return;
+    }
+
+    if (path === '/api/admin/import' && req.method === 'POST') {
+      const passphrase = req.headers['x-plasma-export-passphrase'];
+      sendJson(res, await importInstance(await readBody(req), typeof passphrase === 'string' && passphrase ? passphrase : null));
+      return;
+    }
+
+    // Secrets API; values go in but never come back out
+    if (path === '/api/secrets' && req.method === 'GET') {
+      sendList(res, url, await listSecrets(), { sortable: ['name', 'updatedAt'] });
+      return;
+    }
+
+    if (path === '/api/secrets/status' && req.method === 'GET') {
+      sendJson(res, await getSecretsStatus());
+      return;
+    }
+
+    params = matchRoute('/api/secrets/:name', path);
+    if (params && req.method === 'PUT') {
+      const body = await readBody(req);
+      sendJson(res, await setSecret(params.name, body.value));
+      return;
+    }
+
+    if (params && req.method === 'DELETE') {
+      if (!(await deleteSecret(params.name))) {
+        sendJson(res, { error: 'Secret not found' }, 404);
+        return;
+      }
+      sendJson(res, { success: true });
+      return;
+    }
+
+    if (path === '/api/admin/requests' && req.method === 'GET') {
+      sendList(res, url, getRecordedRequests(), {
+        filterable: ['method', 'path', 'status'],
+        sortable: ['startedAt', 'durationMs', 'status'],
+      });
+      return;
+    }
+
+    if (path === '/api/admin/requests/recording' && (req.method === 'GET' || req.method === 'PUT')) {
+      if (req.method === 'PUT') {
+        const body = await readBody(req);
+        if (typeof body.enabled !== 'boolean') {
+          sendJson(res, { error: 'enabled must be a boolean' }, 400);
+          return;
+        }
+        setRequestRecording(body.enabled);
+      }
+      sendJson(res, getRequestRecordingStatus());
+      return;
+    }
+
+    if (path === '/api/admin/requests' && req.method === 'DELETE') {
+      clearRecordedRequests();
+      sendJson(res, getRequestRecordingStatus());
+      return;
+    }
+
+    if (path === '/api/admin/log-level' && (req.method === 'GET' || req.method === 'PUT')) {
+      if (req.method === 'PUT') {
+        const body = await readBody(req);
+        if (typeof body.filter !== 'string') {
+          sendJson(res, { error: 'filter is required' }, 400);
+          return;
+        }
+        setLogFilter(body.filter);
+        console.warn(`[server] Log filter set to ${getLogFilter()}`);
+      }
+      sendJson(res, { filter: getLogFilter() });
+      return;
+    }
+
+    if (path === '/api/recovery' && req.method === 'GET') {
+      const report = getRecoveryReport();
+      if (!report) {
+        sendJson(res, { error: 'Startup recovery has not run' }, 404);
+        return;
+      }
+      sendJson(res, report);
+      return;
+    }
+
+    if (path === '/api/workspace/jobs' && req.method === 'GET') {
+      await sendPolledList(req, res, url, async () => listJobDirs(), {
+        filterable: ['kind'],
+        sortable: ['createdAt', 'sizeBytes'],
+      });
+      return;
+    }
+
+    if (path === '/api/status/summary' && req.method === 'GET') {
+      sendJson(res, getStatusSummary());
+      return;
+    }
+
+    if (path === '/api/about' && req.method === 'GET') {
+      sendJson(res, getInstance(req.socket.localPort || config.port, config.publicUrl));
+      return;
+    }
+
+    if (path === '/api/doctor' && req.method === 'GET') {
+      const issues = validateConfig();
+      sendJson(res, { ok: !issues.some((issue) => issue.severity === 'error'), issues });
+      return;
+    }
+
+    if (path === '/api/onboarding' && (req.method === 'GET' || req.method === 'PUT')) {
+      if (req.method === 'PUT') {
+        const body = await readBody(req);
+        if (typeof body.dismissed !== 'boolean') {
+          sendJson(res, { error: 'dismissed must be a boolean' }, 400);
+          return;
+        }
+        await setOnboardingDismissed(body.dismissed);
+      }
+      sendJson(res, await getOnboardingStatus());
+      return;
+    }
+
+    if (path === '/api/analytics' && req.method === 'PUT') {
+      const body = await readBody(req);
+      if (typeof body.enabled !== 'boolean') {
+        sendJson(res, { error: 'enabled must be a boolean' }, 400);
+        return;
+      }
+      await setAnalyticsEnabled(body.enabled);
+      sendJson(res, await getUsage(DEFAULT_USAGE_DAYS));
+      return;
+    }
+
+    if (path === '/api/analytics/usage' && req.method === 'GET') {
+      const days = Number(url.searchParams.get('days') || DEFAULT_USAGE_DAYS);
+      if (!Number.isInteger(days) || days < 1 || days > MAX_USAGE_DAYS) {
+        sendJson(res, { error: `days must be a whole number between 1 and ${MAX_USAGE_DAYS}` }, 400);
+        return;
+      }
+      sendJson(res, await getUsage(days));
+      return;
+    }
+
+    if (path === '/api/flags' && req.method === 'GET') {
+      sendJson(res, await getFeatureFlags());
+      return;
+    }
 
-      buffer = Buffer.concat([buffer, chunk]);
+    params = matchRoute('/api/flags/:name', path);
+    if (params && req.method === 'PUT') {
+      if (!isFeatureFlagName(params.name)) {
+        sendJson(res, { error: 'Unknown feature flag' }, 404);
+        return;
+      }
+      const body = await readBody(req);
+      if (typeof body.enabled !== 'boolean' && body.enabled !== null) {
+        sendJson(res, { error: 'enabled must be a boolean or null' }, 400);
+        return;
+      }
+      sendJson(res, await setFeatureFlag(params.name, body.enabled));
+      return;
+    }
+
+    // Health check
+    if (path === '/api/health') {
+      sendJson(res, { status: 'ok' });
+      return;
+    }
+
+    // 404
+    res.writeHead(404, { 'Content-Type': 'application/json' });
+    res.end(JSON.stringify({ error: 'Not found' }));
+  } catch (error) {
+    if (error instanceof ValidationError) {
+      sendJson(res, { error: error.message, code: 'invalid_request', fields: error.fields }, 400);
+      return;
+    }
+    if (error instanceof ListQueryError || error instanceof LogFilterError || error instanceof ImportError || error instanceof InvalidSecretError) {
+      sendJson(res, { error: error.message }, 400);
+      return;
+    }
+    if (error instanceof AndroidSdkUnavailableError) {
+      sendJson(res, { error: error.message }, 501);
+      return;
+    }
+    if (error instanceof SecretsLockedError) {
+      sendJson(res, { error: error.message }, 503);
+      return;
+    }
+    if (error instanceof RecordingError) {
+      sendJson(res, { error: error.message }, 409);
+      return;
+    }
+    if (error instanceof SessionCapabilityError) {
+      sendJson(res, { error: error.message, code: 'unsupported_by_simulator_server', capability: error.capability }, 501);
+      return;
+    }
+    if (error instanceof InstallVerificationError) {
+      sendJson(res, { error: error.message, code: 'install_verification_failed', verification: error.verification }, 500);
+      return;
+    }
+    console.error('[server] Request error:', error);
+    res.writeHead(500, { 'Content-Type': 'application/json' });
+    res.end(JSON.stringify({ error: error instanceof Error ? error.message : 'Internal error' }));
+  }
+}
 
-      let boundaryIndex: number;
-      while ((boundaryIndex = buffer.indexOf(boundary)) !== -1) {
-        const headerEnd = buffer.indexOf('\r\n\r\n', boundaryIndex);
-        if (headerEnd === -1) break;
+/**
+ * Summary of what the backend is doing, from cached state only. A stale simulator
+ * list is refreshed in the background so the next call sees it.
+ */
+function getStatusSummary(): StatusSummary {
+  const cached = getCachedSimulators();
+  if (!cached || Date.now() - Date.parse(cached.updatedAt) > STATUS_SIMULATORS_MAX_AGE_MS) {
+    listSimulators({ refresh: true }).catch((err) => console.error('[server] Failed to refresh simulators:', err));
+  }
 
-        const dataStart = headerEnd + 4;
-        const nextBoundary = buffer.indexOf(boundary, dataStart);
-        if (nextBoundary === -1) break;
+  return {
+    activeBuilds: getActiveBuilds(),
+    activeSessions: getActiveSessions(),
+    bootedSimulators: (cached?.simulators || [])
+      .filter((simulator) => simulator.state === 'Booted')
+      .map(({ udid, name }) => ({ udid, name })),
+    simulatorsUpdatedAt: cached?.updatedAt || null,
+    lastBuild: getLastBuildResult(),
+    keepAwake: getKeepAwakeHolders(),
+  };
+}
 
-        const jpegData = buffer.slice(dataStart, nextBoundary - 2);
+/**
+ * Handle WebSocket messages for streaming APIs
+ */
+async function handleWebSocketMessage(state: AppState, ws: WebSocket, message: string): Promise<void> {
+  try {
+    const data = JSON.parse(message);
+    const { type, payload } = data;
 
-        if (jpegData.length > 0) {
-          sendToClient(ws, 'simulator:stream:frame', {
-            udid,
-            frame: jpegData.toString('base64'),
+    switch (type) {
+      case 'xcode:build:start': {
+        const request = validate(payload, BUILD_STREAM_REQUEST);
+        const { path } = request;
+        const output = request.output || 'all';
+        // A start retried with the same key follows the build the first one started
+        const idempotencyKey = request.idempotencyKey || null;
+        const started = idempotencyKey ? getIdempotentBuild(idempotencyKey) : null;
+        if (started) {
+          followBuild(state, ws, started, output);
+          break;
+        }
+        let scheme: string | undefined = request.scheme;
+        if (!scheme) {
+          scheme = (await discoverProject(path)).suggestedScheme || undefined;
+          if (!scheme) {
+            sendToClient(state, ws, 'error', { message: 'The project has no schemes to build' });
+            break;
+          }
+        }
+        // Without an explicit destination, use the one stored for the project
+        const project = await getDataStore().projects.findByPath(path);
+        const destination = request.destination || (project && (await getDataStore().projectDestinations.get(project))?.preset) || DEFAULT_DESTINATION;
+        const exhausted = checkHostResources('build');
+        if (exhausted) {
+          sendToClient(state, ws, 'xcode:build:event', {
+            type: 'error',
+            event: 'build.error',
+            schemaVersion: EVENT_SCHEMA_VERSION,
+            message: `${exhausted.error}: ${exhausted.reasons.join('; ')}`,
+            resourcesExhausted: exhausted,
           });
+          break;
+        }
+        const emitter = buildSchemeStream(path, scheme, destination);
+        const build = idempotencyKey ? rememberIdempotentBuild(idempotencyKey, emitter) : { emitter, finalEvent: null };
+        followBuild(state, ws, build, output);
+
+        emitter.on('event', (buildEvent) => {
+          if (buildEvent.type === 'started') {
+            runBuildHooks(state.plugins, 'build_started', buildEvent);
+          }
+
+          if (buildEvent.type === 'completed' || buildEvent.type === 'error') {
+            const success = buildEvent.type === 'completed' && buildEvent.success;
+            recordNotification({
+              kind: 'build_finished',
+              level: success || buildEvent.cancelled ? 'info' : 'error',
+              title: success
+                ? `Build of ${scheme} succeeded`
+                : buildEvent.cancelled
+                  ? `Build of ${scheme} was cancelled`
+                  : `Build of ${scheme} failed`,
+              message: buildEvent.message,
+              data: { buildId: buildEvent.buildId, scheme, projectPath: path, success },
+            }).catch((err) => console.error('[builds] Failed to record notification:', err));
+
+            dispatchBuildNotifiers(path, {
+              scheme,
+              success,
+              durationMs: buildEvent.durationMs,
+              logUrl: `${config.publicUrl}/api/builds/${buildEvent.buildId}/log`,
+              message: buildEvent.message,
+            });
+
+            runBuildHooks(state.plugins, 'build_completed', { ...buildEvent, projectPath: path, scheme });
+          }
+        });
+        break;
+      }
+
+      case 'simulator:stream:start': {
+        const defaults = getStreamDefaults(payload.udid);
+        const { udid, fps = defaults.fps, quality = defaults.quality } = payload;
+        const { scale, maxWidth, adaptive } = validate(payload, STREAM_START_REQUEST);
+        const client = state.events.clients.get(ws);
+        if (client) {
+          client.subscriptions.add(`stream:${udid}`);
+          const template = config.watermark || payload.watermark;
+          if (template) {
+            client.watermarks.set(udid, createWatermarker(template, { udid, user: client.viewer }));
+          } else {
+            client.watermarks.delete(udid);
+          }
+          if (scale !== undefined || maxWidth !== undefined || adaptive) {
+            client.scalers.set(udid, new StreamScaler({ scale, maxWidth, adaptive }));
+          } else {
+            client.scalers.delete(udid);
+          }
+        }
+
+        // Mac app frames come from window capture, not simulator-server
+        if (udid === MAC_DEVICE_ID) {
+          break;
+        }
+
+        if (payload.keepAwake) {
+          holdKeepAwake(`stream:${udid}`);
+        }
+        trackStream(udid, fps);
+        resetFramePacing(udid, fps);
+        if (typeof payload.showTouches === 'boolean') {
+          setTouchOverlay(state.touchOverlay, udid, payload.showTouches);
+        }
+        if (payload.record && !isRecording(udid)) {
+          await startRecording(udid, 'stream', client?.watermarks.get(udid) || null);
         }
 
-        buffer = buffer.slice(nextBoundary);
+        // Viewers already watching keep their frames; this client joins them
+        if (client) {
+          acquireSession(udid, `client:${client.id}`);
+          addStreamViewer(state, client, udid, fps, quality);
+        }
+        break;
       }
 
-      if (buffer.length > 10 * 1024 * 1024) {
-        buffer = buffer.slice(-1024 * 1024);
+      case 'simulator:stream:stop': {
+        const { udid } = payload;
+        stopStreamForClient(state, udid, ws);
+        break;
       }
-    });
 
+      case 'simulator:commands:subscribe': {
+        state.events.clients.get(ws)?.subscriptions.add(`commands:${payload.udid}`);
+        break;
+      }
+
+      case 'simulator:commands:unsubscribe': {
+        state.events.clients.get(ws)?.subscriptions.delete(`commands:${payload.udid}`);
+        break;
+      }
+    }
+  } catch (error) {
+    if (error instanceof ValidationError) {
+      sendToClient(state, ws, 'error', { message: error.message, code: 'invalid_request', fields: error.fields });
+      return;
+    }
+    console.error('[server] WebSocket message error:', error);
+    sendToClient(state, ws, 'error', { message: error instanceof Error ? error.message : 'Unknown error' });
+  }
+}
+
+/**
+ * The frame fan-out of a simulator, started by its first viewer. `quality` applies when the viewer
+ * starts the simulator's simulator-server session.
+ */
+function fanoutFor(state: AppState, udid: string, quality?: number): FrameFanout {
+  const { frameFanouts } = state.events;
+  let fanout = frameFanouts.get(udid);
+  if (!fanout) {
+    const created: FrameFanout = new FrameFanout(
+      (fps, signal, onFrame, onEnd) => followStreamFrames(state, udid, fps, quality, signal, onFrame, onEnd),
+      () => {
+        if (frameFanouts.get(udid) === created) {
+          frameFanouts.delete(udid);
+        }
+      }
+    );
+    fanout = created;
+    frameFanouts.set(udid, fanout);
+  }
+  return fanout;
+}
+
+/**
+ * Send a simulator's frames to a WebSocket client. Frames it has no room for are skipped and
+ * counted, like messages dropped under the drop policy, so a slow client doesn't hold up others.
+ */
+function addStreamViewer(state: AppState, client: WebSocketClient, udid: string, fps: number, quality?: number): void {
+  fanoutFor(state, udid, quality).add(`client:${client.id}`, {
+    fps,
+    onFrame: ({ frame, format }) => sendFrame(state, client.ws, udid, frame, format),
+    isLagging: () => isLagging(client.ws, 'frames'),
+    onSkip: () => countSkipped(client, 'frames'),
+  });
+}
+
+/**
+ * A simulator's frames for its fan-out. Health and pacing metrics, the touch overlay and stream
+ * recordings are done here, once per frame, rather than once per viewer.
+ */
+function followStreamFrames(
+  state: AppState,
+  udid: string,
+  fps: number,
+  quality: number | undefined,
+  signal: AbortSignal,
+  onFrame: (frame: BackendFrame) => void,
+  onEnd: () => void
+): void {
+  const startedAt = Date.now();
+  let firstFrameReceived = false;
+  followFrames(
+    udid,
+    fps,
+    `stream:${udid}`,
+    signal,
+    ({ frame, format }) => {
+      if (!firstFrameReceived) {
+        firstFrameReceived = true;
+        recordFirstFrame(udid, Date.now() - startedAt);
+      }
+      recordFrame(udid);
+      recordPacingFrame(udid, frame);
+      const shown = drawTouches(state.touchOverlay, udid, frame, format);
+      recordStreamFrame(udid, shown, format);
+      onFrame({ frame: shown, format });
+    },
+    onEnd,
+    quality
+  );
+}
+
+/**
+ * Feed a simulator's frames to `onFrame` until `signal` aborts. Frames come from the device backend,
+ * asked for them at `fps`, or the simulator's simulator-server session, which `consumer` holds like
+ * a WebSocket client does. `onEnd` is called when the stream ends or can't be started.
+ */
+async function followFrames(
+  udid: string,
+  fps: number,
+  consumer: string,
+  signal: AbortSignal,
+  onFrame: (frame: BackendFrame) => void,
+  onEnd: () => void,
+  quality?: number
+): Promise<void> {
+  const stopBackendStream = startBackendStream(udid, fps, onFrame);
+  if (stopBackendStream) {
+    signal.addEventListener('abort', stopBackendStream);
+    return;
+  }
+
+  acquireSession(udid, consumer);
+  signal.addEventListener('abort', () => releaseSession(udid, consumer));
+  try {
+    const session = await getOrCreateSession(udid, fps, quality);
+    const response = await fetch(session.streamUrl, { signal: signal as any });
+    if (!response.ok || !response.body) {
+      throw new Error(`Failed to connect to simulator stream: ${response.statusText}`);
+    }
+    const nodeStream = response.body as unknown as NodeJS.ReadableStream;
+    const parse = createMjpegParser((jpeg) => onFrame({ frame: jpeg, format: 'jpeg' }));
+    nodeStream.on('data', parse);
     nodeStream.on('error', (err: Error) => {
       if (!signal.aborted) {
-        console.error('[server] Stream error:', err);
+        console.error(`[server] Frame stream error for ${consumer}:`, err);
       }
     });
-
-    nodeStream.on('end', () => {
-      activeStreams.delete(udid);
-    });
+    nodeStream.on('end', onEnd);
   } catch (error) {
     if (!signal.aborted) {
-      console.error('[server] Failed to start stream:', error);
+      console.error(`[server] Failed to start frame stream for ${consumer}:`, error);
+      onEnd();
+    }
+  }
+}
+
+/**
+ * Stream thumbnails of a simulator's frames as a multipart response until the client goes away,
+ * with the configured watermark drawn in
+ */
+function streamThumbnails(state: AppState, req: http.IncomingMessage, res: http.ServerResponse, udid: string, options: ThumbnailOptions): void {
+  res.writeHead(200, { 'Content-Type': MULTIPART_CONTENT_TYPE, 'Cache-Control': 'no-cache', Connection: 'keep-alive' });
+
+  const watermark = config.watermark ? createWatermarker(config.watermark, { udid, user: viewerOf(req) }) : null;
+  const thumbnail = createThumbnailer(options, ({ frame, format }) => {
+    const shown = watermark ? watermark(frame, format) : frame;
+    if (shown) {
+      res.write(multipartFrame(shown, format));
+    }
+  });
+  const id = state.events.nextViewerId('thumbnail');
+  const fanout = fanoutFor(state, udid);
+  req.on('close', () => fanout.remove(id));
+  fanout.add(id, {
+    fps: Math.ceil(options.fps),
+    onFrame: ({ frame, format }) => thumbnail(frame, format),
+    // Skip frames rather than queue them for a client that can't keep up
+    isLagging: () => res.writableNeedDrain,
+    onEnd: () => res.end(),
+  });
+}
+
+/**
+ * Stream a simulator's frames as fragmented MP4 video until the client goes away, with the
+ * configured watermark drawn in
+ */
+function streamVideo(state: AppState, req: http.IncomingMessage, res: http.ServerResponse, udid: string, options: VideoOptions): void {
+  const { events } = state;
+  const id = events.nextViewerId('video');
+  const watermark = config.watermark ? createWatermarker(config.watermark, { udid, user: viewerOf(req) }) : null;
+  // Throws before the head is written when ffmpeg is missing
+  const encoder = startVideoEncoder(
+    options,
+    res,
+    () => {
+      events.frameFanouts.get(udid)?.remove(id);
+      res.end();
+    },
+    watermark
+  );
+  res.writeHead(200, { 'Content-Type': 'video/mp4', 'Cache-Control': 'no-cache', Connection: 'keep-alive' });
+
+  const fanout = fanoutFor(state, udid);
+  req.on('close', () => {
+    fanout.remove(id);
+    encoder.stop();
+  });
+  // The encoder skips frames itself while ffmpeg is busy
+  fanout.add(id, {
+    fps: options.fps,
+    onFrame: (frame) => encoder.write(frame),
+    onEnd: () => {
+      encoder.stop();
+      res.end();
+    },
+  });
+}
+
+/**
+ * Restart frame streaming for clients still subscribed to a simulator,
+ * e.g. after its simulator-server was restarted
+ */
+function resumeStreams(state: AppState, udid: string): void {
+  const { events } = state;
+  const fanout = events.frameFanouts.get(udid);
+  if (fanout) {
+    fanout.restart();
+    return;
+  }
+  // The stream ended with the old session; its WebSocket clients are still subscribed
+  for (const client of events.clients.values()) {
+    if (client.subscriptions.has(`stream:${udid}`)) {
+      addStreamViewer(state, client, udid, getStreamDefaults(udid).fps);
     }
   }
 }
@@ -439,50 +2639,283 @@ async function streamFramesToWebSocket(
 /**
  * Stop stream for a specific client
  */
-function stopStreamForClient(udid: string, ws: WebSocket): void {
-  const client = clients.get(ws);
+function stopStreamForClient(state: AppState, udid: string, ws: WebSocket): void {
+  const { events } = state;
+  const client = events.clients.get(ws);
   if (client) {
     client.subscriptions.delete(`stream:${udid}`);
+    client.watermarks.delete(udid);
+    releaseSession(udid, `client:${client.id}`);
   }
 
   // Check if any other client is still subscribed
   let hasOtherSubscribers = false;
-  for (const [otherWs, otherClient] of clients) {
+  for (const [otherWs, otherClient] of events.clients) {
     if (otherWs !== ws && otherClient.subscriptions.has(`stream:${udid}`)) {
       hasOtherSubscribers = true;
       break;
     }
   }
 
-  // Only stop the stream if no other clients are subscribed
+  if (client) {
+    events.frameFanouts.get(udid)?.remove(`client:${client.id}`);
+  }
+
+  // The stream's bookkeeping stops with the last client; thumbnail and video viewers may keep its frames coming
   if (!hasOtherSubscribers) {
-    const stream = activeStreams.get(udid);
-    if (stream) {
-      stream.abort.abort();
-      activeStreams.delete(udid);
+    untrackStream(udid);
+    releaseKeepAwake(`stream:${udid}`);
+    if (isRecording(udid, 'stream')) {
+      stopRecording(udid).catch((err) => console.error('[server] Failed to stop stream recording:', err));
+    }
+  }
+}
+
+/**
+ * Send a stream frame to a client, with the watermark drawn in if its stream asked for one (or
+ * the config requires it). Frames the watermark can't be drawn into aren't sent.
+ */
+function sendFrame(state: AppState, ws: WebSocket, udid: string, frame: Buffer, format: 'jpeg' | 'png'): void {
+  const client = state.events.clients.get(ws);
+  const scaler = client?.scalers.get(udid);
+  if (scaler) {
+    const factor = scaler.update(backlog(ws, 'frames'));
+    if (factor !== null) {
+      const change: StreamScaleChange = { udid, factor };
+      sendToClient(state, ws, 'simulator:stream:scale', change);
     }
+    ({ frame, format } = scaler.apply(frame, format));
+  }
+  const watermark = client?.watermarks.get(udid);
+  const shown = watermark ? watermark(frame, format) : frame;
+  if (!shown) {
+    return;
   }
+  sendToClient(state, ws, 'simulator:stream:frame', { udid, frame: shown.toString('base64'), format });
 }
 
 /**
  * Send message to a specific WebSocket client
  */
-function sendToClient(ws: WebSocket, type: string, payload: unknown): void {
-  if (ws.readyState === WebSocket.OPEN) {
-    ws.send(JSON.stringify({ type, payload }));
+function sendToClient(state: AppState, ws: WebSocket, type: string, payload: unknown): void {
+  if (ws.readyState !== WebSocket.OPEN) {
+    return;
+  }
+
+  const client = state.events.clients.get(ws);
+  const channel = channelForMessage(type);
+
+  // Under the drop policy, lagging subscribers skip messages instead of buffering without bound
+  if (config.channels[channel].lagPolicy === 'drop' && isLagging(ws, channel)) {
+    if (client) {
+      countSkipped(client, channel);
+    }
+    return;
+  }
+
+  const skipped = client?.skipped[channel];
+  if (client && skipped) {
+    client.skipped[channel] = 0;
+    ws.send(JSON.stringify({ type: 'lagged', payload: { channel, skipped } }));
+  }
+
+  ws.send(JSON.stringify({ type, payload }));
+}
+
+/**
+ * Count a message a lagging client skipped, for its next `lagged` notice
+ */
+function countSkipped(client: WebSocketClient, channel: ChannelName): void {
+  client.skipped[channel] = (client.skipped[channel] || 0) + 1;
+  incrementCounter('plasma_ws_dropped_messages_total', { subscriber: client.id.toString(), channel });
+}
+
+/**
+ * Under the pause policy, pause an upstream source while a subscriber lags
+ * and resume it once the subscriber drained
+ */
+function applyBackpressure(state: AppState, ws: WebSocket, type: string, upstream: { pause(): void; resume(): void }): void {
+  const channel = channelForMessage(type);
+  if (config.channels[channel].lagPolicy !== 'pause' || !isLagging(ws, channel)) {
+    return;
+  }
+
+  const client = state.events.clients.get(ws);
+  if (client) {
+    incrementCounter('plasma_ws_upstream_pauses_total', { subscriber: client.id.toString(), channel });
+  }
+
+  upstream.pause();
+  waitUntilDrained(ws, channel).then(() => upstream.resume());
+}
+
+interface FollowedBuild {
+  emitter: BuildStream;
+  // The completed or error event, once the build finished
+  finalEvent: BuildEvent | null;
+}
+
+// Builds started with an idempotency key, kept for the idempotency window
+const idempotentBuilds = new Map<string, FollowedBuild & { expiresAt: number }>();
+
+function getIdempotentBuild(key: string): FollowedBuild | null {
+  const build = idempotentBuilds.get(key);
+  if (build && build.expiresAt <= Date.now()) {
+    idempotentBuilds.delete(key);
+    return null;
+  }
+  return build || null;
+}
+
+function rememberIdempotentBuild(key: string, emitter: BuildStream): FollowedBuild {
+  const build = { emitter, finalEvent: null as BuildEvent | null, expiresAt: Date.now() + config.idempotencyWindowMs };
+  emitter.on('event', (buildEvent: BuildEvent) => {
+    if (buildEvent.type === 'completed' || buildEvent.type === 'error') {
+      build.finalEvent = buildEvent;
+    }
+  });
+  emitter.once('end', () => setTimeout(() => idempotentBuilds.delete(key), Math.max(0, build.expiresAt - Date.now())).unref());
+  if (config.idempotencyWindowMs > 0) {
+    idempotentBuilds.set(key, build);
+  }
+  return build;
+}
+
+/**
+ * Send a build's events to a client, from now on; a finished build's last event and end right away
+ */
+function followBuild(state: AppState, ws: WebSocket, build: FollowedBuild, output: BuildOutputLevel): void {
+  if (build.finalEvent) {
+    sendToClient(state, ws, 'xcode:build:event', build.finalEvent);
+    sendToClient(state, ws, 'xcode:build:end', {});
+    return;
   }
+  build.emitter.on('event', (buildEvent: BuildEvent) => {
+    // Filter output for this subscriber; everything else is always sent
+    if (buildEvent.type !== 'output' || includeBuildOutput(buildEvent.line, output)) {
+      sendToClient(state, ws, 'xcode:build:event', buildEvent);
+      applyBackpressure(state, ws, 'xcode:build:event', build.emitter);
+    }
+  });
+  build.emitter.on('end', () => {
+    sendToClient(state, ws, 'xcode:build:end', {});
+  });
 }
 
 /**
  * Broadcast message to all connected clients
  */
-function broadcast(type: string, payload: unknown): void {
-  const message = JSON.stringify({ type, payload });
-  for (const client of clients.values()) {
-    if (client.ws.readyState === WebSocket.OPEN) {
-      client.ws.send(message);
+function broadcast(state: AppState, type: string, payload: unknown): void {
+  for (const client of state.events.clients.values()) {
+    sendToClient(state, client.ws, type, payload);
+  }
+}
+
+/**
+ * The unified project with the ID from a route param, or null if there's none
+ */
+async function findProject(id: string): Promise<UnifiedProject | null> {
+  const projectId = Number(id);
+  return Number.isInteger(projectId) ? getDataStore().projects.get(projectId) : null;
+}
+
+/**
+ * Match a path against a pattern like `/api/projects/:id` and return its params
+ */
+function matchRoute(pattern: string, path: string): Record<string, string> | null {
+  const patternParts = pattern.split('/');
+  const pathParts = path.split('/');
+  if (patternParts.length !== pathParts.length) {
+    return null;
+  }
+
+  const params: Record<string, string> = {};
+  for (let i = 0; i < patternParts.length; i++) {
+    if (patternParts[i].startsWith(':')) {
+      params[patternParts[i].slice(1)] = decodeURIComponent(pathParts[i]);
+    } else if (patternParts[i] !== pathParts[i]) {
+      return null;
     }
   }
+
+  const matched = matchedRoutes.getStore();
+  if (matched) {
+    matched.route = pattern;
+  }
+  return params;
+}
+
+/**
+ * The route a request belongs to, for metric labels. Parameterized routes are labeled with their
+ * pattern and fixed routes with their path; a 404 that matched no route is "unmatched" so stray
+ * URLs can't add series.
+ */
+function routeLabel(path: string, matchedRoute: string | null, statusCode: number): string {
+  if (matchedRoute) {
+    return matchedRoute;
+  }
+  const prefixRoute = PREFIX_ROUTE_LABELS.find(([pattern]) => pattern.test(path));
+  if (prefixRoute) {
+    return prefixRoute[1];
+  }
+  return statusCode === 404 ? 'unmatched' : path;
+}
+
+/**
+ * Record the latency and status of a request once its response is done. Streaming responses
+ * (SSE, long downloads) are timed until the client disconnects.
+ */
+function trackRequestMetrics(req: http.IncomingMessage, res: http.ServerResponse, path: string): void {
+  const startedAt = process.hrtime.bigint();
+  const matched = matchedRoutes.getStore();
+  res.once('close', () => {
+    const method = req.method || 'GET';
+    const route = routeLabel(path, matched?.route ?? null, res.statusCode);
+    const status = String(res.statusCode);
+    observeHistogram('plasma_http_request_duration_seconds', { method, route }, Number(process.hrtime.bigint() - startedAt) / 1e9);
+    incrementCounter('plasma_http_requests_total', { method, route, status });
+    if (res.statusCode >= 400) {
+      incrementCounter('plasma_http_request_errors_total', { method, route, status });
+    }
+  });
+}
+
+/**
+ * Start a Server-Sent Events response and return a function to send events. Events sent without
+ * an ID are numbered after the client's Last-Event-ID, so IDs keep increasing across reconnects.
+ */
+function openEventStream(
+  req: http.IncomingMessage,
+  res: http.ServerResponse
+): (event: string, data: unknown, id?: number) => void {
+  res.writeHead(200, {
+    'Content-Type': 'text/event-stream',
+    'Cache-Control': 'no-cache',
+    Connection: 'keep-alive',
+  });
+  res.write(`retry: ${SSE_RETRY_MS}\n: connected\n\n`);
+  let nextId = (parseLastEventId(req.headers['last-event-id']) ?? 0) + 1;
+
+  // Keep intermediaries from closing idle connections
+  const keepAlive = setInterval(() => res.write(': keep-alive\n\n'), 15000);
+  req.on('close', () => clearInterval(keepAlive));
+
+  return (event, data, id = nextId++) => {
+    res.write(`id: ${id}\nevent: ${event}\ndata: ${JSON.stringify(data)}\n\n`);
+  };
+}
+
+/**
+ * Send the events of `replay` after `lastEventId` and follow it until it ends or the client leaves
+ */
+function followEventReplay(req: http.IncomingMessage, res: http.ServerResponse, replay: EventReplay, lastEventId: number): void {
+  const send = openEventStream(req, res);
+  const unfollow = replay.follow(
+    lastEventId,
+    (event) => send(event.event, event.data, event.id),
+    () => res.end()
+  );
+  req.on('close', unfollow);
 }
 
 /**
@@ -490,23 +2923,103 @@ function broadcast(type: string, payload: unknown): void {
  */
 function readBody(req: http.IncomingMessage): Promise<any> {
   return new Promise((resolve, reject) => {
-    let data = '';
-    req.on('data', (chunk) => (data += chunk));
-    req.on('end', () => {
+    const parse = (data: string) => {
+      noteRequestBody(req, data);
       try {
         resolve(data ? JSON.parse(data) : {});
       } catch (e) {
         reject(new Error('Invalid JSON body'));
       }
-    });
+    };
+    // Requests with an idempotency key were read already, to compare their body with the first one's
+    const buffered = takeBufferedBody(req);
+    if (buffered !== undefined) {
+      parse(buffered);
+      return;
+    }
+    let data = '';
+    req.on('data', (chunk) => (data += chunk));
+    req.on('end', () => parse(data));
     req.on('error', reject);
   });
 }
 
+/**
+ * Set pagination headers for list responses
+ */
+function setListHeaders(res: http.ServerResponse, total: number, nextCursor: string | null): void {
+  res.setHeader('X-Total-Count', total.toString());
+  if (nextCursor) {
+    res.setHeader('X-Next-Cursor', nextCursor);
+  }
+}
+
+/**
+ * Compute a strong ETag for a JSON response body
+ */
+function computeEtag(data: unknown): string {
+  return `"${createHash('sha1').update(JSON.stringify(data)).digest('base64url')}"`;
+}
+
+/**
+ * Apply the shared list query conventions and send the resulting page
+ */
+function sendList<T>(res: http.ServerResponse, url: URL, items: T[], options: ListQueryOptions<T> = {}): void {
+  const page = applyListQuery(items, parseListQuery(url.searchParams, options));
+  setListHeaders(res, page.total, page.nextCursor);
+  sendJson(res, page.items);
+}
+
+/**
+ * Like sendList, but honors If-None-Match (304) and `?wait=30s` long-polling:
+ * when the client's ETag is still current, the list is reloaded periodically
+ * until it changes or the wait expires.
+ */
+async function sendPolledList<T>(
+  req: http.IncomingMessage,
+  res: http.ServerResponse,
+  url: URL,
+  load: () => Promise<T[]>,
+  options: ListQueryOptions<T> = {}
+): Promise<void> {
+  const query = parseListQuery(url.searchParams, options);
+  const deadline = Date.now() + parseWaitDuration(url.searchParams.get('wait'));
+  const ifNoneMatch = req.headers['if-none-match'];
+
+  let closed = false;
+  req.on('close', () => (closed = true));
+
+  let page = applyListQuery(await load(), query);
+  let etag = computeEtag(page.items);
+
+  while (etag === ifNoneMatch && Date.now() < deadline && !closed) {
+    await new Promise((resolve) => setTimeout(resolve, LONG_POLL_INTERVAL_MS));
+    page = applyListQuery(await load(), query);
+    etag = computeEtag(page.items);
+  }
+
+  if (closed) {
+    return;
+  }
+
+  res.setHeader('ETag', etag);
+  setListHeaders(res, page.total, page.nextCursor);
+
+  if (etag === ifNoneMatch) {
+    res.writeHead(304);
+    res.end();
+    return;
+  }
+
+  sendJson(res, page.items);
+}
+
 /**
  * Send JSON response
  */
 function sendJson(res: http.ServerResponse, data: unknown, statusCode: number = 200): void {
-  res.writeHead(statusCode, { 'Content-Type': 'application/json' });
-  res.end(JSON.stringify(data));
+  const body = JSON.stringify(data);
+  // The length also tells compression whether the body is worth it
+  res.writeHead(statusCode, { 'Content-Type': 'application/json', 'Content-Length': Buffer.byteLength(body) });
+  res.end(body);
 }
@@ -0,0 +1,78 @@
+import { runSimctl } from './simulator';
+import type { AccessibilitySettings, ContentSizeCategory } from '../../shared/ipc-types';
+
+/**
+ * Accessibility settings of a simulator, so they can be flipped without going through the
+ * Settings app in the stream. Increase Contrast and the Dynamic Type size go through `simctl ui`
+ * and apps see them right away; the others are defaults of com.apple.Accessibility, which apps
+ * read when they launch. Simulator has no VoiceOver.
+ */
+
+export const CONTENT_SIZE_CATEGORIES: ContentSizeCategory[] = [
+  'extra-small',
+  'small',
+  'medium',
+  'large',
+  'extra-large',
+  'extra-extra-large',
+  'extra-extra-extra-large',
+  'accessibility-medium',
+  'accessibility-large',
+  'accessibility-extra-large',
+  'accessibility-extra-extra-large',
+  'accessibility-extra-extra-extra-large',
+];
+
+// Keys of the com.apple.Accessibility defaults domain
+const DEFAULTS_KEYS = {
+  reduceMotion: 'ReduceMotionEnabled',
+  reduceTransparency: 'EnhancedBackgroundContrastEnabled',
+  boldText: 'EnhancedTextLegibilityEnabled',
+};
+
+type DefaultsSetting = keyof typeof DEFAULTS_KEYS;
+
+async function readDefault(udid: string, setting: DefaultsSetting): Promise<boolean> {
+  try {
+    const output = await runSimctl(['spawn', udid, 'defaults', 'read', 'com.apple.Accessibility', DEFAULTS_KEYS[setting]]);
+    return output.trim() === '1';
+  } catch {
+    // Settings that were never changed aren't in the domain
+    return false;
+  }
+}
+
+export async function getAccessibilitySettings(udid: string): Promise<AccessibilitySettings> {
+  const [reduceMotion, reduceTransparency, boldText, contrast, contentSize] = await Promise.all([
+    readDefault(udid, 'reduceMotion'),
+    readDefault(udid, 'reduceTransparency'),
+    readDefault(udid, 'boldText'),
+    runSimctl(['ui', udid, 'increase_contrast']),
+    runSimctl(['ui', udid, 'content_size']),
+  ]);
+  return {
+    reduceMotion,
+    reduceTransparency,
+    boldText,
+    increaseContrast: contrast.trim() === 'enabled',
+    // `unsupported` on watchOS and tvOS
+    contentSize: CONTENT_SIZE_CATEGORIES.includes(contentSize.trim() as ContentSizeCategory) ? (contentSize.trim() as ContentSizeCategory) : null,
+  };
+}
+
+/**
+ * Change some accessibility settings; the others keep their current value
+ */
+export async function setAccessibilitySettings(udid: string, settings: Partial<AccessibilitySettings>): Promise<void> {
+  for (const setting of Object.keys(DEFAULTS_KEYS) as DefaultsSetting[]) {
+    if (settings[setting] !== undefined) {
+      await runSimctl(['spawn', udid, 'defaults', 'write', 'com.apple.Accessibility', DEFAULTS_KEYS[setting], '-bool', String(settings[setting])]);
+    }
+  }
+  if (settings.increaseContrast !== undefined) {
+    await runSimctl(['ui', udid, 'increase_contrast', settings.increaseContrast ? 'enabled' : 'disabled']);
+  }
+  if (settings.contentSize) {
+    await runSimctl(['ui', udid, 'content_size', settings.contentSize]);
+  }
+}
@@ -0,0 +1,83 @@
+import { getDatabase } from './database';
+import { clearSetting, readSetting, updateSetting } from './settings';
+import type { AnalyticsUsage, UsageDay, UsageKind, UsageSummary } from '../../shared/ipc-types';
+
+/**
+ * Opt-in usage analytics for the instance: how many builds, launches and streams ran and how long
+ * they took. Only the kind, duration and time are kept, in the local database; nothing is sent anywhere.
+ */
+
+const USAGE_KINDS: UsageKind[] = ['build', 'launch', 'stream'];
+
+export async function isAnalyticsEnabled(): Promise<boolean> {
+  return (await readSetting('analytics.enabled_at')) !== null;
+}
+
+/**
+ * Opt in or out. Opting out also deletes the usage collected so far.
+ */
+export async function setAnalyticsEnabled(enabled: boolean): Promise<void> {
+  if (enabled) {
+    await updateSetting('analytics.enabled_at', (enabledAt) => enabledAt ?? new Date().toISOString());
+    return;
+  }
+  await clearSetting('analytics.enabled_at');
+  getDatabase().prepare('DELETE FROM usage_events').run();
+}
+
+/**
+ * Count a build, launch or stream, if analytics are enabled
+ */
+export async function recordUsage(kind: UsageKind, durationMs: number | null): Promise<void> {
+  try {
+    if (!(await isAnalyticsEnabled())) {
+      return;
+    }
+    getDatabase()
+      .prepare('INSERT INTO usage_events (kind, duration_ms, created_at) VALUES (?, ?, ?)')
+      .run(kind, durationMs === null ? null : Math.round(durationMs), new Date().toISOString());
+  } catch (err) {
+    console.error(`[analytics] Failed to record ${kind}:`, err);
+  }
+}
+
+/**
+ * Usage over the last `days` days, in total and per day
+ */
+export async function getUsage(days: number): Promise<AnalyticsUsage> {
+  const db = getDatabase();
+  const since = new Date(Date.now() - days * 24 * 60 * 60 * 1000).toISOString();
+
+  const rows = db.prepare(`
+    SELECT kind, COUNT(*) AS count, ROUND(AVG(duration_ms)) AS averageDurationMs
+    FROM usage_events
+    WHERE created_at >= ?
+    GROUP BY kind
+  `).all(since) as UsageSummary[];
+  const totals = USAGE_KINDS.map(
+    (kind) => rows.find((row) => row.kind === kind) || { kind, count: 0, averageDurationMs: null }
+  );
+
+  const daily = new Map<string, UsageDay>();
+  const dayRows = db.prepare(`
+    SELECT substr(created_at, 1, 10) AS date, kind, COUNT(*) AS count
+    FROM usage_events
+    WHERE created_at >= ?
+    GROUP BY date, kind
+    ORDER BY date
+  `).all(since) as { date: string; kind: UsageKind; count: number }[];
+  for (const row of dayRows) {
+    const day = daily.get(row.date) || { date: row.date, build: 0, launch: 0, stream: 0 };
+    day[row.kind] = row.count;
+    daily.set(row.date, day);
+  }
+
+  const enabledAt = await readSetting('analytics.enabled_at');
+  return {
+    enabled: enabledAt !== null,
+    enabledAt: enabledAt?.value || null,
+    since,
+    totals,
+    daily: Array.from(daily.values()),
+  };
+}
@@ -0,0 +1,219 @@
+import { spawn } from 'child_process';
+import * as fs from 'fs';
+import * as path from 'path';
+import { runCommand } from './simulator';
+import type { AndroidEmulator, LaunchAndroidAppResponse } from '../../shared/ipc-types';
+
+/**
+ * Android emulators, through the Android SDK's command line tools: AVDs come from `avdmanager`
+ * and are matched with the running emulators `adb devices` reports, APKs are installed with
+ * `adb install` and apps started with `adb shell am start`. The tools are looked up in the SDK
+ * at $ANDROID_HOME (or $ANDROID_SDK_ROOT), then on $PATH.
+ */
+
+// Where each tool is in the SDK
+const SDK_TOOLS = {
+  adb: ['platform-tools', 'adb'],
+  emulator: ['emulator', 'emulator'],
+  avdmanager: ['cmdline-tools', 'latest', 'bin', 'avdmanager'],
+};
+
+type SdkTool = keyof typeof SDK_TOOLS;
+
+// How long booting waits for Android to finish starting
+const BOOT_TIMEOUT_MS = 180_000;
+const BOOT_POLL_INTERVAL_MS = 2000;
+
+// `adb shell` runs its arguments through the device's shell, so names are checked strictly
+const PACKAGE_NAME_PATTERN = /^[A-Za-z][\w]*(\.[A-Za-z][\w]*)+$/;
+const ACTIVITY_PATTERN = /^\.?[A-Za-z_][\w]*(\.[A-Za-z_][\w]*)*$/;
+
+export class AndroidSdkUnavailableError extends Error {}
+
+function sdkToolPath(tool: SdkTool): string {
+  const sdk = process.env.ANDROID_HOME || process.env.ANDROID_SDK_ROOT;
+  if (sdk) {
+    const candidate = path.join(sdk, ...SDK_TOOLS[tool]);
+    if (fs.existsSync(candidate)) {
+      return candidate;
+    }
+  }
+  return tool;
+}
+
+async function runSdkTool(tool: SdkTool, args: string[]): Promise<string> {
+  try {
+    return await runCommand(sdkToolPath(tool), args);
+  } catch (err) {
+    if ((err as NodeJS.ErrnoException).code === 'ENOENT') {
+      throw new AndroidSdkUnavailableError(`${tool} not found, install the Android SDK and set ANDROID_HOME`);
+    }
+    throw err;
+  }
+}
+
+/**
+ * AVD names from `avdmanager list avd -c`
+ */
+export function parseAvdNames(output: string): string[] {
+  return output
+    .split('\n')
+    .map((line) => line.trim())
+    .filter(Boolean);
+}
+
+/**
+ * Emulators from `adb devices`, with adb's state (`device`, `offline`, ...). Physical
+ * devices are left out.
+ */
+export function parseAdbDevices(output: string): Array<{ serial: string; state: string }> {
+  return output
+    .split('\n')
+    .slice(1)
+    .map((line) => line.trim().split(/\s+/))
+    .filter(([serial, state]) => serial?.startsWith('emulator-') && state)
+    .map(([serial, state]) => ({ serial, state }));
+}
+
+/**
+ * AVD a running emulator was started from, or null while it doesn't answer
+ */
+async function avdName(serial: string): Promise<string | null> {
+  try {
+    const output = await runSdkTool('adb', ['-s', serial, 'emu', 'avd', 'name']);
+    return output.split('\n')[0].trim() || null;
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Every AVD, with the serial of those that run, booted ones first
+ */
+export async function listEmulators(): Promise<AndroidEmulator[]> {
+  const [names, devices] = await Promise.all([
+    runSdkTool('avdmanager', ['list', 'avd', '-c']).then(parseAvdNames),
+    runSdkTool('adb', ['devices']).then(parseAdbDevices),
+  ]);
+  const running = await Promise.all(devices.map(async (device) => ({ ...device, name: await avdName(device.serial) })));
+
+  const emulators: AndroidEmulator[] = names.map((name) => {
+    const device = running.find((candidate) => candidate.name === name);
+    return {
+      name,
+      serial: device?.serial ?? null,
+      state: device ? (device.state === 'device' ? 'Booted' : 'Booting') : 'Shutdown',
+    };
+  });
+  // Emulators of AVDs avdmanager doesn't know, e.g. from another SDK
+  for (const device of running) {
+    if (!device.name || !names.includes(device.name)) {
+      emulators.push({ name: device.name || device.serial, serial: device.serial, state: device.state === 'device' ? 'Booted' : 'Booting' });
+    }
+  }
+
+  return emulators.sort((a, b) => {
+    if (a.state !== b.state) {
+      return a.state === 'Booted' ? -1 : b.state === 'Booted' ? 1 : 0;
+    }
+    return a.name.localeCompare(b.name);
+  });
+}
+
+/**
+ * Whether an emulator with this serial is running
+ */
+export async function isEmulatorRunning(serial: string): Promise<boolean> {
+  const devices = parseAdbDevices(await runSdkTool('adb', ['devices']));
+  return devices.some((device) => device.serial === serial);
+}
+
+/**
+ * Start an AVD's emulator and wait for Android to finish booting. The emulator keeps running
+ * when Plasma quits, like booted simulators do. Booting a running AVD returns it as it is.
+ */
+export async function bootEmulator(name: string): Promise<AndroidEmulator> {
+  const emulators = await listEmulators();
+  const emulator = emulators.find((candidate) => candidate.name === name);
+  if (!emulator) {
+    throw new Error(`No AVD named ${name}`);
+  }
+  if (emulator.state === 'Booted') {
+    return emulator;
+  }
+
+  console.log(`Booting emulator ${name}...`);
+  let exited: string | undefined;
+  if (emulator.state === 'Shutdown') {
+    const proc = spawn(sdkToolPath('emulator'), ['-avd', name], { detached: true, stdio: 'ignore' });
+    proc.on('error', (err) => (exited = err.message));
+    proc.on('exit', (code) => (exited = `emulator exited with code ${code}`));
+    proc.unref();
+  }
+
+  const deadline = Date.now() + BOOT_TIMEOUT_MS;
+  while (Date.now() < deadline) {
+    await new Promise((resolve) => setTimeout(resolve, BOOT_POLL_INTERVAL_MS));
+    if (exited) {
+      throw new Error(`Failed to boot ${name}: ${exited}`);
+    }
+    const booting = (await listEmulators()).find((candidate) => candidate.name === name);
+    if (booting?.serial && booting.state === 'Booted') {
+      const completed = await runSdkTool('adb', ['-s', booting.serial, 'shell', 'getprop', 'sys.boot_completed']).catch(() => '');
+      if (completed.trim() === '1') {
+        return booting;
+      }
+    }
+  }
+  throw new Error(`Timed out waiting for ${name} to boot`);
+}
+
+/**
+ * Why a package name or activity can't be launched, or null if they can
+ */
+export function validateAndroidLaunch(packageName: unknown, activity?: unknown): string | null {
+  if (typeof packageName !== 'string' || !PACKAGE_NAME_PATTERN.test(packageName)) {
+    return `packageName must be a package name like "com.example.app", got "${packageName}"`;
+  }
+  if (activity !== undefined && (typeof activity !== 'string' || !ACTIVITY_PATTERN.test(activity))) {
+    return `activity must be a class name like ".MainActivity", got "${activity}"`;
+  }
+  return null;
+}
+
+/**
+ * Install an APK on a running emulator, replacing the app if it's installed
+ */
+export async function installApk(serial: string, apkPath: string): Promise<void> {
+  console.log(`Installing ${apkPath} on ${serial}...`);
+  await runSdkTool('adb', ['-s', serial, 'install', '-r', apkPath]);
+}
+
+/**
+ * Start an app's activity, by default its launcher activity
+ */
+export async function launchAndroidApp(serial: string, packageName: string, activity?: string): Promise<LaunchAndroidAppResponse> {
+  let component: string;
+  if (activity) {
+    component = `${packageName}/${activity}`;
+  } else {
+    const resolved = await runSdkTool('adb', [
+      '-s', serial, 'shell', 'cmd', 'package', 'resolve-activity', '--brief', '-c', 'android.intent.category.LAUNCHER', packageName,
+    ]);
+    // The component is the last line, after details of the match
+    component = resolved.trim().split('\n').pop()!.trim();
+    if (!component.includes('/')) {
+      throw new Error(`${packageName} has no launcher activity, is it installed?`);
+    }
+  }
+
+  console.log(`Launching ${component} on ${serial}...`);
+  const output = await runSdkTool('adb', ['-s', serial, 'shell', 'am', 'start', '-n', component]);
+  // am reports failures on stdout and exits with 0
+  const error = output.split('\n').find((line) => line.startsWith('Error'));
+  if (error) {
+    throw new Error(`Failed to start ${component}: ${error}`);
+  }
+
+  return { message: `App ${packageName} launched successfully`, packageName, component };
+}
@@ -0,0 +1,88 @@
+import { readInfoPlistValue, runSimctl } from './simulator';
+import { readEntitlements } from './universal-links';
+import type { BundleIdConflict, BundleIdConflictPolicy } from '../../shared/ipc-types';
+
+/**
+ * Detection of a different app installed under the bundle ID of one being installed, e.g. a
+ * designer's build of another variant, or one signed by another team. Installing over it would
+ * silently replace it, so launches can report the conflict, refuse to install, or uninstall the
+ * other app first so none of its data is left behind.
+ */
+
+export const BUNDLE_ID_CONFLICT_POLICIES: BundleIdConflictPolicy[] = ['replace', 'fail', 'uninstall'];
+
+export interface AppIdentity {
+  bundleId: string;
+  // CFBundleDisplayName, or CFBundleName without one
+  name: string | null;
+  // Team of the signature's application identifier; builds without signing have none
+  teamId: string | null;
+  version: string | null;
+}
+
+export async function readAppIdentity(appPath: string): Promise<AppIdentity | null> {
+  const bundleId = await readInfoPlistValue(appPath, 'CFBundleIdentifier');
+  if (!bundleId) {
+    return null;
+  }
+  const entitlements = await readEntitlements(appPath).catch(() => null);
+  return {
+    bundleId,
+    name: (await readInfoPlistValue(appPath, 'CFBundleDisplayName')) || (await readInfoPlistValue(appPath, 'CFBundleName')),
+    teamId: entitlements?.appId?.split('.')[0] || null,
+    version: await readInfoPlistValue(appPath, 'CFBundleShortVersionString'),
+  };
+}
+
+/**
+ * Why an installed app is a different app than the one about to replace it, or null if it's the
+ * same app. Other versions of the same app aren't conflicts.
+ */
+export function compareAppIdentities(installed: AppIdentity, incoming: AppIdentity): BundleIdConflict | null {
+  const reasons: string[] = [];
+  if (installed.teamId && incoming.teamId && installed.teamId !== incoming.teamId) {
+    reasons.push(`it's signed by team ${installed.teamId}, the new build by ${incoming.teamId}`);
+  }
+  if (installed.name && incoming.name && installed.name !== incoming.name) {
+    reasons.push(`it's named "${installed.name}", the new build "${incoming.name}"`);
+  }
+  if (reasons.length === 0) {
+    return null;
+  }
+  return {
+    bundleId: incoming.bundleId,
+    installed: { name: installed.name, teamId: installed.teamId, version: installed.version },
+    reasons,
+  };
+}
+
+/**
+ * The conflict installing `appPath` on a simulator would cause, if any. The check is best
+ * effort: when either app can't be read, e.g. because the simulator isn't booted, there's none.
+ */
+export async function findBundleIdConflict(udid: string, appPath: string, bundleId?: string): Promise<BundleIdConflict | null> {
+  const resolvedBundleId = bundleId || (await readInfoPlistValue(appPath, 'CFBundleIdentifier'));
+  if (!resolvedBundleId) {
+    return null;
+  }
+  let installedPath: string;
+  try {
+    installedPath = (await runSimctl(['get_app_container', udid, resolvedBundleId, 'app'])).trim();
+  } catch {
+    // Not installed
+    return null;
+  }
+  if (!installedPath) {
+    return null;
+  }
+  const [installed, incoming] = await Promise.all([readAppIdentity(installedPath), readAppIdentity(appPath)]);
+  return installed && incoming ? compareAppIdentities(installed, { ...incoming, bundleId: resolvedBundleId }) : null;
+}
+
+/**
+ * Uninstall the other app, so the new build starts without its data
+ */
+export async function uninstallConflictingApp(udid: string, conflict: BundleIdConflict): Promise<void> {
+  console.log(`Uninstalling ${conflict.bundleId} from ${udid}, it's a different app: ${conflict.reasons.join(', ')}`);
+  await runSimctl(['uninstall', udid, conflict.bundleId]);
+}
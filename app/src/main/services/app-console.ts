@@ -0,0 +1,120 @@
+import { spawn } from 'child_process';
+import { randomUUID } from 'crypto';
+import { registerProcess } from './process-manager';
+import { createEventReplay, EventReplay } from './event-replay';
+import { EVENT_SCHEMA_VERSION } from '../../shared/ipc-types';
+import type { AppConsoleEvent } from '../../shared/ipc-types';
+
+/**
+ * Console output of apps launched through Plasma, so `print` statements show up without
+ * Console.app. Apps are launched with `simctl launch --console-pty`, which keeps simctl attached
+ * to the app's stdout and stderr until it exits. Each launch gets an ID whose console can be
+ * followed from its first line, even by a client that connects after the app started.
+ */
+
+// Finished consoles are kept this long for clients that open them late
+const FINISHED_RETENTION_MS = 10 * 60 * 1000;
+
+const consoles = new Map<string, EventReplay>();
+
+/**
+ * Output of an attached app, written by `source` until it calls `onExit`. Returns the launch ID.
+ */
+export function attachConsole(
+  udid: string,
+  bundleId: string,
+  source: (onOutput: (chunk: string) => void, onExit: (code: number | null) => void) => void
+): string {
+  const launchId = randomUUID();
+  const events = createEventReplay();
+  let pending = '';
+  let exited = false;
+
+  const push = (type: AppConsoleEvent['type'], fields: Partial<AppConsoleEvent>) =>
+    events.push(`console.${type}`, {
+      event: `console.${type}`,
+      schemaVersion: EVENT_SCHEMA_VERSION,
+      launchId,
+      udid,
+      bundleId,
+      type,
+      timestamp: new Date().toISOString(),
+      ...fields,
+    });
+
+  const onOutput = (chunk: string) => {
+    // The pty ends lines with \r\n
+    const lines = (pending + chunk).replace(/\r/g, '').split('\n');
+    pending = lines.pop()!;
+    lines.forEach((line) => push('output', { line }));
+  };
+  const onExit = (code: number | null) => {
+    if (exited) {
+      return;
+    }
+    exited = true;
+    if (pending) {
+      push('output', { line: pending });
+    }
+    push('exit', { exitCode: code });
+    events.end();
+    setTimeout(() => consoles.delete(launchId), FINISHED_RETENTION_MS).unref();
+  };
+
+  consoles.set(launchId, events);
+  source(onOutput, onExit);
+  return launchId;
+}
+
+/**
+ * Launch an app with its console attached, resolving with the launch ID once simctl reports the
+ * app's PID. Rejects with simctl's error if the app doesn't launch.
+ */
+export function launchWithConsole(udid: string, bundleId: string, launchArgs: string[]): Promise<string> {
+  return new Promise((resolve, reject) => {
+    // A running instance has no console to attach to, so it's replaced
+    const proc = spawn('xcrun', ['simctl', 'launch', '--console-pty', '--terminate-running-process', udid, bundleId, ...launchArgs]);
+    registerProcess(proc, 'app-console');
+
+    let stdout = '';
+    let stderr = '';
+    proc.stderr.on('data', (data) => {
+      stderr += data.toString();
+    });
+
+    const onStdout = (data: Buffer) => {
+      stdout += data.toString();
+      // simctl's first line is "<bundle ID>: <pid>"; the app's output follows
+      const newline = stdout.indexOf('\n');
+      if (newline === -1) {
+        return;
+      }
+      proc.stdout.off('data', onStdout);
+      proc.off('close', onEarlyClose);
+      const rest = stdout.slice(newline + 1);
+      resolve(
+        attachConsole(udid, bundleId, (onOutput, onExit) => {
+          if (rest) {
+            onOutput(rest);
+          }
+          proc.stdout.on('data', (chunk: Buffer) => onOutput(chunk.toString()));
+          proc.on('close', onExit);
+        })
+      );
+    };
+    const onEarlyClose = (code: number | null) => {
+      reject(new Error(stderr.trim() || `simctl launch failed with code ${code}`));
+    };
+
+    proc.stdout.on('data', onStdout);
+    proc.on('close', onEarlyClose);
+    proc.on('error', reject);
+  });
+}
+
+/**
+ * The console of a launch, while it's kept
+ */
+export function getLaunchConsole(launchId: string): EventReplay | null {
+  return consoles.get(launchId) ?? null;
+}
@@ -0,0 +1,74 @@
+import { AsyncLocalStorage } from 'async_hooks';
+import { EventEmitter } from 'events';
+import { SimulatorService, DeviceBackend, getActiveSessions, stopSession } from './simulator';
+import { EventBus } from './event-bus';
+import { createRuntimeIssues } from './runtime-issues';
+import { createSimulatorWatcher, stopSimulatorWatcher } from './simulator-watch';
+import { createTouchOverlay } from './touch-overlay';
+import type { PluginRegistry } from './plugins';
+
+/**
+ * Everything one server keeps in memory: its simulators, WebSocket clients, the emitters its
+ * clients are told about, and what it loaded at startup. `startServer` builds one and hands it to
+ * the request and WebSocket handlers, so several servers can run in one process, e.g. in tests.
+ * Code the handlers call further down, like simulator sessions, finds it with `getAppState`.
+ */
+
+const activeState = new AsyncLocalStorage<AppState>();
+
+export class AppState {
+  readonly simulators: SimulatorService;
+  readonly events = new EventBus();
+  // Emits 'notification' with every NotificationRecord stored
+  readonly notifications = new EventEmitter();
+  // Emits 'change' with a SettingChange whenever a setting is set or cleared
+  readonly settings = new EventEmitter();
+  // Emits 'frame' with a base64-encoded JPEG of a captured Mac app window
+  readonly macFrames = new EventEmitter();
+  readonly runtimeIssues = createRuntimeIssues();
+  readonly simulatorWatcher = createSimulatorWatcher();
+  readonly touchOverlay = createTouchOverlay();
+  readonly plugins: PluginRegistry = new Map();
+
+  constructor(backend: DeviceBackend | null = null) {
+    this.simulators = new SimulatorService(backend);
+  }
+
+  /**
+   * Run `fn`, and everything it starts, for this server
+   */
+  run<T>(fn: () => T): T {
+    return activeState.run(this, fn);
+  }
+
+  /**
+   * Stop the sessions, timers and watchers this server started
+   */
+  stop(): void {
+    this.run(() => getActiveSessions().forEach((udid) => stopSession(udid)));
+    if (this.simulators.prewarmTimer) {
+      clearInterval(this.simulators.prewarmTimer);
+      this.simulators.prewarmTimer = null;
+    }
+    stopSimulatorWatcher(this.simulatorWatcher);
+  }
+}
+
+/**
+ * The state of the server this code runs for. Throws outside of a server's handlers and the
+ * work they start, rather than acting on some other server's simulators.
+ */
+export function getAppState(): AppState {
+  const state = activeState.getStore();
+  if (!state) {
+    throw new Error('Not running for a server; call this from a handler or inside AppState.run');
+  }
+  return state;
+}
+
+/**
+ * The state of the server this code runs for, or null outside of one, e.g. in a script
+ */
+export function findAppState(): AppState | null {
+  return activeState.getStore() || null;
+}
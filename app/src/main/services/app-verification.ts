@@ -0,0 +1,163 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { readInfoPlistValue, runSimctl } from './simulator';
+import type { InstallVerification } from '../../shared/ipc-types';
+
+/**
+ * Checks that an installed app is really there, since `simctl install` occasionally succeeds
+ * without installing it and the launch then fails with "app not found". The installed apps come
+ * from `simctl listapps`, which prints an old-style (OpenStep) property list.
+ */
+
+type PlistValue = string | PlistValue[] | { [key: string]: PlistValue };
+
+export class InstallVerificationError extends Error {
+  constructor(readonly verification: InstallVerification) {
+    super(`The app was not installed correctly: ${verification.problems.join(', ')}`);
+  }
+}
+
+/**
+ * Parse an OpenStep property list, e.g. `{ "dev.plasma.App" = { CFBundleVersion = 1; }; }`.
+ * Numbers and booleans stay strings, as the format doesn't tell them apart.
+ */
+export function parseOpenStepPlist(text: string): PlistValue {
+  let i = 0;
+
+  const fail = (message: string): never => {
+    throw new Error(`Invalid property list at ${i}: ${message}`);
+  };
+  const skipWhitespace = () => {
+    while (i < text.length) {
+      if (/\s/.test(text[i])) {
+        i++;
+      } else if (text.startsWith('//', i)) {
+        i = text.indexOf('\n', i) === -1 ? text.length : text.indexOf('\n', i);
+      } else if (text.startsWith('/*', i)) {
+        i = text.indexOf('*/', i) === -1 ? text.length : text.indexOf('*/', i) + 2;
+      } else {
+        break;
+      }
+    }
+  };
+  const expect = (char: string) => {
+    skipWhitespace();
+    if (text[i] !== char) {
+      fail(`expected "${char}"`);
+    }
+    i++;
+  };
+
+  const parseString = (): string => {
+    skipWhitespace();
+    if (text[i] === '"') {
+      let value = '';
+      i++;
+      while (i < text.length && text[i] !== '"') {
+        if (text[i] === '\\') {
+          i++;
+          const escaped = text[i];
+          value += escaped === 'n' ? '\n' : escaped === 't' ? '\t' : escaped;
+        } else {
+          value += text[i];
+        }
+        i++;
+      }
+      expect('"');
+      return value;
+    }
+    const token = /^[\w$+/:.-]+/.exec(text.slice(i));
+    if (!token) {
+      fail('expected a string');
+    }
+    i += token![0].length;
+    return token![0];
+  };
+
+  const parseValue = (): PlistValue => {
+    skipWhitespace();
+    if (text[i] === '{') {
+      i++;
+      const dict: { [key: string]: PlistValue } = {};
+      skipWhitespace();
+      while (text[i] !== '}') {
+        const key = parseString();
+        expect('=');
+        dict[key] = parseValue();
+        expect(';');
+        skipWhitespace();
+      }
+      i++;
+      return dict;
+    }
+    if (text[i] === '(') {
+      i++;
+      const array: PlistValue[] = [];
+      skipWhitespace();
+      while (text[i] !== ')') {
+        array.push(parseValue());
+        skipWhitespace();
+        if (text[i] === ',') {
+          i++;
+          skipWhitespace();
+        } else if (text[i] !== ')') {
+          fail('expected "," or ")"');
+        }
+      }
+      i++;
+      return array;
+    }
+    return parseString();
+  };
+
+  const value = parseValue();
+  skipWhitespace();
+  if (i < text.length) {
+    fail('unexpected content after the value');
+  }
+  return value;
+}
+
+/**
+ * Check that an app is installed on a simulator with the bundle ID and version of the build at
+ * `appPath`, and that its executable is there to launch
+ */
+export async function verifyInstall(udid: string, appPath: string, bundleId: string): Promise<InstallVerification> {
+  const [expectedVersion, expectedBuild] = await Promise.all([
+    readInfoPlistValue(appPath, 'CFBundleShortVersionString'),
+    readInfoPlistValue(appPath, 'CFBundleVersion'),
+  ]);
+  const apps = parseOpenStepPlist(await runSimctl(['listapps', udid]) || '{}') as Record<string, Record<string, string>>;
+  const app = apps[bundleId];
+
+  const verification: InstallVerification = {
+    bundleId,
+    installed: Boolean(app),
+    installedPath: null,
+    expectedVersion,
+    installedVersion: null,
+    launchable: false,
+    problems: [],
+  };
+  if (!app) {
+    verification.problems.push(`${bundleId} is not in the simulator's installed apps`);
+    return verification;
+  }
+
+  // `Path` is the app bundle; older Xcode versions only have `Bundle`, a file URL
+  const installedPath = app.Path || (app.Bundle?.startsWith('file://') ? decodeURIComponent(new URL(app.Bundle).pathname).replace(/\/$/, '') : null);
+  verification.installedPath = installedPath;
+  verification.installedVersion = app.CFBundleShortVersionString || null;
+  if (expectedVersion && verification.installedVersion !== expectedVersion) {
+    verification.problems.push(`version ${verification.installedVersion} is installed instead of ${expectedVersion}`);
+  } else if (expectedBuild && app.CFBundleVersion && app.CFBundleVersion !== expectedBuild) {
+    verification.problems.push(`build ${app.CFBundleVersion} is installed instead of ${expectedBuild}`);
+  }
+
+  const executable = app.CFBundleExecutable;
+  verification.launchable = Boolean(installedPath && executable && fs.existsSync(path.join(installedPath, executable)));
+  if (!verification.launchable) {
+    verification.problems.push(executable ? `the installed app has no ${executable} executable` : 'the installed app has no executable');
+  }
+  return verification;
+}
@@ -0,0 +1,76 @@
+import { runSimctl } from './simulator';
+import type { Appearance, StatusBarOverrides } from '../../shared/ipc-types';
+
+/**
+ * Light and dark appearance and status bar overrides of a simulator, e.g. for App Store
+ * screenshots with 9:41, a full battery and full bars, through `simctl ui` and `simctl status_bar`.
+ * The appearance is a setting of the simulator; overrides last until cleared or it reboots.
+ */
+
+export const APPEARANCES: Appearance[] = ['light', 'dark'];
+
+// Allowed values of the status bar overrides, in simctl's terms
+const STATUS_BAR_CHOICES = {
+  dataNetwork: ['hide', 'wifi', '3g', '4g', 'lte', 'lte-a', 'lte+', '5g', '5g+', '5g-uwb', '5g-uc'],
+  wifiMode: ['searching', 'failed', 'active'],
+  cellularMode: ['notSupported', 'searching', 'failed', 'active'],
+  batteryState: ['charging', 'charged', 'discharging'],
+};
+const STATUS_BAR_RANGES = {
+  wifiBars: [0, 3],
+  cellularBars: [0, 4],
+  batteryLevel: [0, 100],
+};
+
+export async function getAppearance(udid: string): Promise<Appearance | null> {
+  const output = (await runSimctl(['ui', udid, 'appearance'])).trim();
+  // `unsupported` on watchOS and tvOS
+  return APPEARANCES.includes(output as Appearance) ? (output as Appearance) : null;
+}
+
+export async function setAppearance(udid: string, appearance: Appearance): Promise<void> {
+  await runSimctl(['ui', udid, 'appearance', appearance]);
+}
+
+/**
+ * Why status bar overrides can't be applied, or null if they can
+ */
+export function validateStatusBarOverrides(overrides: Record<string, unknown>): string | null {
+  const keys = Object.keys(overrides);
+  if (keys.length === 0) {
+    return 'At least one override is required';
+  }
+  for (const key of keys) {
+    const value = overrides[key];
+    if (key === 'time' || key === 'operatorName') {
+      if (typeof value !== 'string') {
+        return `${key} must be a string`;
+      }
+    } else if (key in STATUS_BAR_CHOICES) {
+      const choices = STATUS_BAR_CHOICES[key as keyof typeof STATUS_BAR_CHOICES];
+      if (!choices.includes(value as string)) {
+        return `${key} must be one of ${choices.join(', ')}`;
+      }
+    } else if (key in STATUS_BAR_RANGES) {
+      const [min, max] = STATUS_BAR_RANGES[key as keyof typeof STATUS_BAR_RANGES];
+      if (!Number.isInteger(value) || (value as number) < min || (value as number) > max) {
+        return `${key} must be a whole number from ${min} to ${max}`;
+      }
+    } else {
+      return `Unknown status bar override: ${key}`;
+    }
+  }
+  return null;
+}
+
+/**
+ * Override parts of the status bar; the others keep their current value
+ */
+export async function overrideStatusBar(udid: string, overrides: StatusBarOverrides): Promise<void> {
+  const flags = Object.entries(overrides).flatMap(([key, value]) => [`--${key}`, String(value)]);
+  await runSimctl(['status_bar', udid, 'override', ...flags]);
+}
+
+export async function clearStatusBar(udid: string): Promise<void> {
+  await runSimctl(['status_bar', udid, 'clear']);
+}
@@ -0,0 +1,49 @@
+import { spawn } from 'child_process';
+import * as path from 'path';
+import { registerProcess } from './process-manager';
+import { findHelperBinary, describeHelperLookup } from './helper-binaries';
+
+/**
+ * Find the AXe binary
+ */
+export function findAxeBinary(): string | null {
+  return findHelperBinary('axe')?.path ?? null;
+}
+
+/**
+ * Run an AXe command against a simulator and return its stdout
+ */
+export async function runAxe(command: string, args: string[]): Promise<string> {
+  const axePath = findAxeBinary();
+  if (!axePath) {
+    throw new Error(`AXe binary not found (looked in ${describeHelperLookup('axe')})`);
+  }
+
+  const frameworksPath = path.join(path.dirname(axePath), 'Frameworks');
+
+  return new Promise<string>((resolve, reject) => {
+    const proc = spawn(axePath, [command, ...args], {
+      env: { ...process.env, DYLD_FRAMEWORK_PATH: frameworksPath },
+    });
+    registerProcess(proc);
+
+    let stdout = '';
+    let stderr = '';
+    proc.stdout.on('data', (data) => {
+      stdout += data.toString();
+    });
+    proc.stderr.on('data', (data) => {
+      stderr += data.toString();
+    });
+
+    proc.on('close', (code) => {
+      if (code !== 0) {
+        reject(new Error(`AXe ${command} failed: ${stderr}`));
+      } else {
+        resolve(stdout);
+      }
+    });
+
+    proc.on('error', reject);
+  });
+}
@@ -0,0 +1,38 @@
+import { runSimctl } from './simulator';
+import type { BiometricAction, BiometricSensor } from '../../shared/ipc-types';
+
+/**
+ * Face ID and Touch ID of a simulator, like the Simulator app's Features menu: the simulated
+ * BiometricKit listens for Darwin notifications, which `simctl spawn <udid> notifyutil` posts.
+ * A match completes a pending biometric prompt, including the Apple Pay sheet's.
+ */
+
+export const BIOMETRIC_ACTIONS: BiometricAction[] = ['match', 'nonmatch'];
+export const BIOMETRIC_SENSORS: BiometricSensor[] = ['face', 'touch'];
+
+// BiometricKit's names for the sensors, `pearl` being Face ID
+const SENSOR_NOTIFICATION_NAMES: Record<BiometricSensor, string> = {
+  face: 'pearl',
+  touch: 'fingerTouch',
+};
+
+const ENROLLMENT_NOTIFICATION = 'com.apple.BiometricKit.enrollmentChanged';
+
+function notifyutil(udid: string, args: string[]): Promise<string> {
+  return runSimctl(['spawn', udid, 'notifyutil', ...args]);
+}
+
+/**
+ * Present a matching or non-matching face or finger to a pending biometric prompt
+ */
+export async function sendBiometric(udid: string, action: BiometricAction, sensor: BiometricSensor = 'face'): Promise<void> {
+  await notifyutil(udid, ['-p', `com.apple.BiometricKit_Sim.${SENSOR_NOTIFICATION_NAMES[sensor]}.${action}`]);
+}
+
+/**
+ * Enroll or unenroll the simulator's face and fingers. Apps only offer biometrics when enrolled.
+ */
+export async function setBiometricEnrollment(udid: string, enrolled: boolean): Promise<void> {
+  await notifyutil(udid, ['-s', ENROLLMENT_NOTIFICATION, enrolled ? '1' : '0']);
+  await notifyutil(udid, ['-p', ENROLLMENT_NOTIFICATION]);
+}
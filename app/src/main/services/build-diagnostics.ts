@@ -0,0 +1,65 @@
+import type { BuildDiagnostic, BuildDiagnosticSeverity } from '../../shared/ipc-types';
+
+/**
+ * Errors, warnings and notes from xcodebuild output, in the clang/swiftc format:
+ * - `/path/File.swift:12:5: error: cannot find 'foo' in scope`
+ * - `/path/Info.plist:3: warning: ...` or `/path/Project.xcodeproj: warning: ...` (no line or column)
+ * - `error: ...`, `ld: warning: ...`, `xcodebuild: error: ...` (not tied to a file)
+ * Notes not tied to a file are xcodebuild's own chatter (`note: Building targets in parallel`)
+ * and are skipped.
+ * xcodebuild repeats diagnostics (e.g. once per architecture), so duplicates are dropped.
+ */
+
+const FILE_DIAGNOSTIC = /^(\/[^:]+?)(?::(\d+))?(?::(\d+))?: (error|warning|note): (.+)$/;
+const TOOL_DIAGNOSTIC = /^(?:[\w.-]+: )?(error|warning): (.+)$/;
+
+// Enough for any list worth reading; a build spewing more is cut off
+const MAX_DIAGNOSTICS = 500;
+
+export function parseDiagnostic(line: string): BuildDiagnostic | null {
+  const text = line.trim();
+  const fileMatch = FILE_DIAGNOSTIC.exec(text);
+  if (fileMatch) {
+    return {
+      file: fileMatch[1],
+      line: fileMatch[2] ? Number(fileMatch[2]) : null,
+      column: fileMatch[3] ? Number(fileMatch[3]) : null,
+      severity: fileMatch[4] as BuildDiagnosticSeverity,
+      message: fileMatch[5],
+    };
+  }
+  const toolMatch = TOOL_DIAGNOSTIC.exec(text);
+  if (toolMatch) {
+    return { file: null, line: null, column: null, severity: toolMatch[1] as BuildDiagnosticSeverity, message: toolMatch[2] };
+  }
+  return null;
+}
+
+export interface DiagnosticCollector {
+  // Feed an output line of the build
+  observe(line: string): void;
+  // Distinct diagnostics so far, in the order they were first printed
+  diagnostics(): BuildDiagnostic[];
+}
+
+export function createDiagnosticCollector(): DiagnosticCollector {
+  const seen = new Map<string, BuildDiagnostic>();
+
+  return {
+    observe(line) {
+      if (seen.size >= MAX_DIAGNOSTICS) {
+        return;
+      }
+      const diagnostic = parseDiagnostic(line);
+      if (diagnostic) {
+        const key = [diagnostic.file, diagnostic.line, diagnostic.column, diagnostic.severity, diagnostic.message].join('\0');
+        if (!seen.has(key)) {
+          seen.set(key, diagnostic);
+        }
+      }
+    },
+    diagnostics() {
+      return Array.from(seen.values());
+    },
+  };
+}
@@ -0,0 +1,56 @@
+/**
+ * Estimated progress of a running build, from two sources:
+ * - the build plan: xcodebuild prints `Target dependency graph (N targets)` up front, and every
+ *   build step names its target, so targets that have started steps bound how far the build is
+ * - history: elapsed time over the scheme's typical duration
+ * The estimate is the higher of the two, never goes backwards, and stays below 1 until the build ends.
+ */
+
+const TARGET_GRAPH = /^Target dependency graph \((\d+) targets?\)/;
+const STEP_TARGET = /\(in target '([^']+)' from project '[^']+'\)/;
+
+// Held back from 1 so a build that runs long doesn't sit at 100%
+const MAX_ESTIMATE = 0.99;
+
+export interface BuildProgress {
+  // Feed an output line of the build
+  observe(line: string): void;
+  // Estimated fraction done (0-1), or null while there's nothing to base it on
+  estimate(now?: number): number | null;
+}
+
+export function createBuildProgress(startedAt: number, typicalDurationMs: number | null): BuildProgress {
+  let totalTargets: number | null = null;
+  const startedTargets = new Set<string>();
+  let last: number | null = null;
+
+  return {
+    observe(line) {
+      const graph = TARGET_GRAPH.exec(line);
+      if (graph) {
+        totalTargets = Number(graph[1]);
+        return;
+      }
+      const step = STEP_TARGET.exec(line);
+      if (step) {
+        startedTargets.add(step[1]);
+      }
+    },
+    estimate(now = Date.now()) {
+      const estimates: number[] = [];
+      if (totalTargets) {
+        // The most recently started target is still building
+        estimates.push(Math.max(startedTargets.size - 1, 0) / totalTargets);
+      }
+      if (typicalDurationMs) {
+        estimates.push((now - startedAt) / typicalDurationMs);
+      }
+      if (estimates.length === 0) {
+        return last;
+      }
+      const estimate = Math.min(Math.max(...estimates, last ?? 0), MAX_ESTIMATE);
+      last = Math.round(estimate * 100) / 100;
+      return last;
+    },
+  };
+}
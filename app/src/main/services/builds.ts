@@ -0,0 +1,197 @@
+import { getDataStore } from './data-store';
+import type { BuildRow, ProjectKey } from './data-store';
+import type {
+  BuildComparison,
+  BuildEnvironment,
+  BuildProductSize,
+  BuildProvenance,
+  BuildRecord,
+  BuildStatus,
+  NumberDelta,
+} from '../../shared/ipc-types';
+
+export interface NewBuildRecord extends Omit<BuildRecord, 'projectId' | 'success' | 'logUrl'> {
+  // The unified project the build belongs to, if any
+  project: ProjectKey | null;
+  // Build settings of the scheme's first target, from `xcodebuild -showBuildSettings`
+  buildSettings: Record<string, string>;
+  environment?: BuildEnvironment;
+}
+
+/**
+ * Parse the settings of the first target from `xcodebuild -showBuildSettings` output
+ */
+export function parseBuildSettings(output: string): Record<string, string> {
+  const settings: Record<string, string> = {};
+  let sections = 0;
+  for (const line of output.split('\n')) {
+    if (line.startsWith('Build settings for')) {
+      if (++sections > 1) {
+        break;
+      }
+      continue;
+    }
+    const match = /^\s+([A-Za-z0-9_]+) = (.*)$/.exec(line);
+    if (match) {
+      settings[match[1]] = match[2];
+    }
+  }
+  return settings;
+}
+
+function parseBuildRecord(record: BuildRow): BuildRecord {
+  const status = (record.status || (record.success === 1 ? 'succeeded' : 'failed')) as BuildStatus;
+  return {
+    id: record.id,
+    projectId: record.project_id,
+    projectPath: record.project_path,
+    scheme: record.scheme,
+    configuration: record.configuration,
+    destination: record.destination as BuildRecord['destination'],
+    status,
+    success: status === 'succeeded',
+    durationMs: record.duration_ms,
+    warnings: record.warnings,
+    errors: record.errors,
+    products: JSON.parse(record.products) as BuildProductSize[],
+    logUrl: `/api/builds/${encodeURIComponent(record.id)}/log`,
+    startedAt: record.started_at || new Date(Date.parse(record.finished_at) - record.duration_ms).toISOString(),
+    finishedAt: record.finished_at,
+  };
+}
+
+/**
+ * Persist the outcome of a finished or cancelled build
+ */
+export async function recordBuild(build: NewBuildRecord): Promise<void> {
+  await getDataStore().builds.insert({
+    id: build.id,
+    project_path: build.projectPath,
+    scheme: build.scheme,
+    configuration: build.configuration,
+    destination: build.destination,
+    status: build.status,
+    success: build.status === 'succeeded' ? 1 : 0,
+    duration_ms: build.durationMs,
+    warnings: build.warnings,
+    errors: build.errors,
+    products: JSON.stringify(build.products),
+    build_settings: JSON.stringify(build.buildSettings),
+    started_at: build.startedAt,
+    finished_at: build.finishedAt,
+    environment: build.environment ? JSON.stringify(build.environment) : null,
+  }, build.project);
+}
+
+export async function getBuild(id: string): Promise<BuildRecord | null> {
+  const record = await getDataStore().builds.get(id);
+  return record ? parseBuildRecord(record) : null;
+}
+
+function parseBuildProvenance(record: BuildRow): BuildProvenance {
+  const build = parseBuildRecord(record);
+  const environment: BuildEnvironment = record.environment
+    ? JSON.parse(record.environment)
+    : { machine: null, xcodeVersion: null, git: null };
+  return {
+    buildId: build.id,
+    projectPath: build.projectPath,
+    scheme: build.scheme,
+    configuration: build.configuration,
+    destination: build.destination,
+    status: build.status,
+    artifacts: build.products,
+    ...environment,
+    startedAt: build.startedAt,
+    finishedAt: build.finishedAt,
+  };
+}
+
+/**
+ * Checksums of a build's products and what it was built with, or null if it wasn't recorded
+ */
+export async function getBuildProvenance(id: string): Promise<BuildProvenance | null> {
+  const record = await getDataStore().builds.get(id);
+  return record ? parseBuildProvenance(record) : null;
+}
+
+/**
+ * Provenance of the builds that produced an artifact with this SHA-256, newest first
+ */
+export async function findBuildsByChecksum(sha256: string): Promise<BuildProvenance[]> {
+  const checksum = sha256.toLowerCase();
+  // Narrowed in SQL, then checked against the parsed products
+  const records = await getDataStore().builds.findByChecksum(checksum);
+  return records
+    .map(parseBuildProvenance)
+    .filter((provenance) => provenance.artifacts.some((artifact) => artifact.sha256 === checksum));
+}
+
+/**
+ * Recorded builds, newest first, optionally only those of one project
+ */
+export async function listBuilds(options: { project?: ProjectKey } = {}): Promise<BuildRecord[]> {
+  return (await getDataStore().builds.list(options.project)).map(parseBuildRecord);
+}
+
+// Successful builds averaged for the typical duration
+const TYPICAL_DURATION_SAMPLE = 5;
+
+/**
+ * Median duration of the last successful builds of a scheme, or null if it never built
+ */
+export async function getTypicalBuildDuration(projectPath: string, scheme: string, destination: string): Promise<number | null> {
+  const durations = (
+    await getDataStore().builds.recentDurations(projectPath, scheme, destination, TYPICAL_DURATION_SAMPLE)
+  ).sort((a, b) => a - b);
+  if (durations.length === 0) {
+    return null;
+  }
+  return durations[Math.floor(durations.length / 2)];
+}
+
+function delta(a: number, b: number): NumberDelta {
+  return { a, b, delta: b - a };
+}
+
+function totalSize(build: BuildRecord): number {
+  return build.products.reduce((total, product) => total + product.sizeBytes, 0);
+}
+
+/**
+ * Differences going from build `a` to build `b`, or null if either doesn't exist
+ */
+export async function compareBuilds(aId: string, bId: string): Promise<BuildComparison | null> {
+  const store = getDataStore();
+  const [aRecord, bRecord] = await Promise.all([store.builds.get(aId), store.builds.get(bId)]);
+  if (!aRecord || !bRecord) {
+    return null;
+  }
+  const a = parseBuildRecord(aRecord);
+  const b = parseBuildRecord(bRecord);
+
+  const productNames = Array.from(new Set([...a.products, ...b.products].map((product) => product.name))).sort();
+  const products = productNames.map((name) => {
+    const aBytes = a.products.find((product) => product.name === name)?.sizeBytes ?? null;
+    const bBytes = b.products.find((product) => product.name === name)?.sizeBytes ?? null;
+    return { name, aBytes, bBytes, deltaBytes: (bBytes ?? 0) - (aBytes ?? 0) };
+  });
+
+  const aSettings = JSON.parse(aRecord.build_settings) as Record<string, string>;
+  const bSettings = JSON.parse(bRecord.build_settings) as Record<string, string>;
+  const changedSettings = Array.from(new Set([...Object.keys(aSettings), ...Object.keys(bSettings)]))
+    .sort()
+    .filter((key) => aSettings[key] !== bSettings[key])
+    .map((key) => ({ key, a: aSettings[key] ?? null, b: bSettings[key] ?? null }));
+
+  return {
+    a,
+    b,
+    durationMs: delta(a.durationMs, b.durationMs),
+    warnings: delta(a.warnings, b.warnings),
+    errors: delta(a.errors, b.errors),
+    totalSizeBytes: delta(totalSize(a), totalSize(b)),
+    products,
+    changedSettings,
+  };
+}
@@ -0,0 +1,275 @@
+import { getDatabase } from './database';
+import { describeAccessibility } from './simulator';
+import type { TapTransform, CalibrationProbe, TapCalibration } from '../../shared/ipc-types';
+
+interface Frame {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+interface AccessibilityElement {
+  AXLabel?: string | null;
+  frame?: Frame;
+  children?: AccessibilityElement[];
+}
+
+const MAX_PROBES = 9;
+// Probes tried with each orientation to find the one the accessibility frames are in
+const ORIENTATION_PROBES = 3;
+// Share of probes that have to hit their element for a calibration to be stored
+const MIN_HIT_RATIO = 2 / 3;
+
+export class CalibrationError extends Error {}
+
+interface Point {
+  x: number;
+  y: number;
+}
+
+/**
+ * How accessibility frames relate to the display: AXe can report them in the device's portrait
+ * coordinates while the display, which taps and the stream use, is in landscape. `rotate` maps a
+ * point relative to the screen's origin into the display's orientation.
+ */
+interface Orientation {
+  rotate(point: Point, screen: Frame): Point;
+  swapsAxes: boolean;
+}
+
+const ORIENTATIONS: Orientation[] = [
+  { rotate: ({ x, y }) => ({ x, y }), swapsAxes: false },
+  { rotate: ({ x, y }, screen) => ({ x: screen.height - y, y: x }), swapsAxes: true },
+  { rotate: ({ x, y }, screen) => ({ x: y, y: screen.width - x }), swapsAxes: true },
+  { rotate: ({ x, y }, screen) => ({ x: screen.width - x, y: screen.height - y }), swapsAxes: false },
+];
+
+// display = rotated * scale + offset, per axis
+interface AxisFit {
+  scale: number;
+  offset: number;
+}
+
+const UNFITTED: { x: AxisFit; y: AxisFit } = { x: { scale: 1, offset: 0 }, y: { scale: 1, offset: 0 } };
+
+async function describeUi(udid: string, point?: Point): Promise<AccessibilityElement[]> {
+  const parsed = JSON.parse(await describeAccessibility(udid, point));
+  return Array.isArray(parsed) ? parsed : [parsed];
+}
+
+function isUsableFrame(frame: Frame | undefined): frame is Frame {
+  return !!frame && frame.width > 0 && frame.height > 0;
+}
+
+function centerOf(frame: Frame): Point {
+  return { x: frame.x + frame.width / 2, y: frame.y + frame.height / 2 };
+}
+
+function sameFrame(a: Frame, b: Frame): boolean {
+  return Math.abs(a.x - b.x) <= 1 && Math.abs(a.y - b.y) <= 1 && Math.abs(a.width - b.width) <= 1 && Math.abs(a.height - b.height) <= 1;
+}
+
+function collectLeaves(element: AccessibilityElement, leaves: AccessibilityElement[]): void {
+  if (!element.children || element.children.length === 0) {
+    if (isUsableFrame(element.frame)) {
+      leaves.push(element);
+    }
+    return;
+  }
+  for (const child of element.children) {
+    collectLeaves(child, leaves);
+  }
+}
+
+/**
+ * Pick probe targets spread over the screen instead of clustered in one area
+ */
+function pickProbeTargets(leaves: AccessibilityElement[]): AccessibilityElement[] {
+  if (leaves.length <= MAX_PROBES) {
+    return leaves;
+  }
+  const step = leaves.length / MAX_PROBES;
+  return Array.from({ length: MAX_PROBES }, (_, i) => leaves[Math.floor(i * step)]);
+}
+
+function rotated(orientation: Orientation, screen: Frame, point: Point): Point {
+  return orientation.rotate({ x: point.x - screen.x, y: point.y - screen.y }, screen);
+}
+
+function toDisplay(orientation: Orientation, fit: { x: AxisFit; y: AxisFit }, screen: Frame, point: Point): Point {
+  const { x, y } = rotated(orientation, screen, point);
+  return { x: x * fit.x.scale + fit.x.offset, y: y * fit.y.scale + fit.y.offset };
+}
+
+/**
+ * Frame of the element at a display point. Hit-testing rather than tapping, so calibrating doesn't
+ * change the app's state.
+ */
+async function hitTest(udid: string, point: Point): Promise<Frame | null> {
+  const [element] = await describeUi(udid, { x: Math.round(point.x), y: Math.round(point.y) });
+  return isUsableFrame(element?.frame) ? element.frame : null;
+}
+
+/**
+ * Least squares fit of `display = rotated * scale + offset`. Without two distinct positions on the
+ * axis only the offset can be fitted.
+ */
+function fitAxis(pairs: Array<{ rotated: number; display: number }>): AxisFit {
+  const n = pairs.length;
+  const meanRotated = pairs.reduce((sum, pair) => sum + pair.rotated, 0) / n;
+  const meanDisplay = pairs.reduce((sum, pair) => sum + pair.display, 0) / n;
+  const variance = pairs.reduce((sum, pair) => sum + (pair.rotated - meanRotated) ** 2, 0);
+  if (variance < 1) {
+    return { scale: 1, offset: meanDisplay - meanRotated };
+  }
+  const covariance = pairs.reduce((sum, pair) => sum + (pair.rotated - meanRotated) * (pair.display - meanDisplay), 0);
+  const scale = covariance / variance;
+  return { scale, offset: meanDisplay - scale * meanRotated };
+}
+
+interface ProbeResult {
+  target: AccessibilityElement;
+  // Display point that was hit-tested last
+  point: Point;
+  hit: boolean;
+}
+
+/**
+ * Hit-test a target's center as mapped by `orientation` and `fit`. On a miss, `retry` moves the
+ * point by how far the element that was hit is from the target and tries once more.
+ */
+async function probe(
+  udid: string,
+  screen: Frame,
+  orientation: Orientation,
+  fit: { x: AxisFit; y: AxisFit },
+  target: AccessibilityElement,
+  retry: boolean
+): Promise<ProbeResult> {
+  const frame = target.frame!;
+  let point = toDisplay(orientation, fit, screen, centerOf(frame));
+  let hitFrame = await hitTest(udid, point);
+  if (retry && hitFrame && !sameFrame(hitFrame, frame)) {
+    const targetCenter = rotated(orientation, screen, centerOf(frame));
+    const hitCenter = rotated(orientation, screen, centerOf(hitFrame));
+    point = {
+      x: point.x + (targetCenter.x - hitCenter.x) * fit.x.scale,
+      y: point.y + (targetCenter.y - hitCenter.y) * fit.y.scale,
+    };
+    hitFrame = await hitTest(udid, point);
+  }
+  return { target, point, hit: !!hitFrame && sameFrame(hitFrame, frame) };
+}
+
+/**
+ * Calibrate taps on a simulator's device type. The centers of on-screen elements, from the
+ * accessibility tree, are hit-tested at the display points the current mapping gives for them. The
+ * orientation the frames are in is found first, then each probe that misses is retried where the
+ * error to the element that was hit says it should be. The scale and offset are fitted to the
+ * points that hit, and the calibration is rejected when too many probes miss.
+ */
+export async function calibrate(udid: string, deviceType: string): Promise<TapCalibration> {
+  const roots = await describeUi(udid);
+  const screen = roots.map((root) => root.frame).find(isUsableFrame);
+  if (!screen) {
+    throw new CalibrationError('Could not determine the screen bounds from the accessibility tree');
+  }
+
+  const leaves: AccessibilityElement[] = [];
+  roots.forEach((root) => collectLeaves(root, leaves));
+  const targets = pickProbeTargets(leaves);
+  if (targets.length < 2) {
+    throw new CalibrationError('Not enough elements on screen to calibrate; open a screen with a few buttons or labels');
+  }
+
+  let orientation = ORIENTATIONS[0];
+  let mostHits = -1;
+  for (const candidate of ORIENTATIONS) {
+    let hits = 0;
+    for (const target of targets.slice(0, ORIENTATION_PROBES)) {
+      hits += (await probe(udid, screen, candidate, UNFITTED, target, false)).hit ? 1 : 0;
+    }
+    if (hits > mostHits) {
+      orientation = candidate;
+      mostHits = hits;
+    }
+  }
+
+  const results: ProbeResult[] = [];
+  for (const target of targets) {
+    results.push(await probe(udid, screen, orientation, UNFITTED, target, true));
+  }
+  const hits = results.filter((result) => result.hit);
+  if (hits.length < Math.max(2, Math.ceil(targets.length * MIN_HIT_RATIO))) {
+    throw new CalibrationError(`Only ${hits.length} of ${targets.length} calibration probes hit their element; try again on a screen that isn't animating`);
+  }
+
+  const centers = hits.map((result) => rotated(orientation, screen, centerOf(result.target.frame!)));
+  const fit = {
+    x: fitAxis(hits.map((result, i) => ({ rotated: centers[i].x, display: result.point.x }))),
+    y: fitAxis(hits.map((result, i) => ({ rotated: centers[i].y, display: result.point.y }))),
+  };
+  // The stream shows the whole display, which is the screen in the display's orientation
+  const width = orientation.swapsAxes ? screen.height : screen.width;
+  const height = orientation.swapsAxes ? screen.width : screen.height;
+  const transform: TapTransform = {
+    scaleX: width * fit.x.scale,
+    scaleY: height * fit.y.scale,
+    offsetX: fit.x.offset,
+    offsetY: fit.y.offset,
+  };
+
+  const probes: CalibrationProbe[] = results.map((result) => ({
+    label: result.target.AXLabel ?? null,
+    x: (result.point.x - transform.offsetX) / transform.scaleX,
+    y: (result.point.y - transform.offsetY) / transform.scaleY,
+    hit: result.hit,
+  }));
+
+  const calibratedAt = new Date().toISOString();
+  getDatabase().prepare(`
+    INSERT INTO tap_calibrations (device_type, scale_x, scale_y, offset_x, offset_y, probes, calibrated_at)
+    VALUES (?, ?, ?, ?, ?, ?, ?)
+    ON CONFLICT(device_type) DO UPDATE SET
+      scale_x = excluded.scale_x,
+      scale_y = excluded.scale_y,
+      offset_x = excluded.offset_x,
+      offset_y = excluded.offset_y,
+      probes = excluded.probes,
+      calibrated_at = excluded.calibrated_at
+  `).run(
+    deviceType,
+    transform.scaleX,
+    transform.scaleY,
+    transform.offsetX,
+    transform.offsetY,
+    JSON.stringify(probes),
+    calibratedAt
+  );
+
+  return { deviceType, transform, probes, calibratedAt };
+}
+
+/**
+ * Stored tap transform for a device type, if it has been calibrated
+ */
+export function getTapTransform(deviceType: string | null): TapTransform | null {
+  if (!deviceType) {
+    return null;
+  }
+  const row = getDatabase().prepare(
+    'SELECT scale_x, scale_y, offset_x, offset_y FROM tap_calibrations WHERE device_type = ?'
+  ).get(deviceType) as { scale_x: number; scale_y: number; offset_x: number; offset_y: number } | undefined;
+  if (!row) {
+    return null;
+  }
+  return { scaleX: row.scale_x, scaleY: row.scale_y, offsetX: row.offset_x, offsetY: row.offset_y };
+}
+
+export function applyTapTransform(transform: TapTransform, x: number, y: number): { x: number; y: number } {
+  return {
+    x: Math.round(x * transform.scaleX + transform.offsetX),
+    y: Math.round(y * transform.scaleY + transform.offsetY),
+  };
+}
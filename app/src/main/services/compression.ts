@@ -0,0 +1,117 @@
+import type * as http from 'http';
+import * as zlib from 'zlib';
+
+/**
+ * Gzip or deflate compression of JSON and Server-Sent Events responses for clients that accept it,
+ * which mostly helps remote users on slow links with large build results. Event streams are
+ * flushed after every event so they stay live. Everything else, like MJPEG frames and files
+ * served with ranges, is sent as is.
+ */
+
+type Encoding = 'gzip' | 'deflate';
+
+// Smaller bodies aren't worth the overhead
+const MIN_COMPRESSED_LENGTH = 1024;
+
+const COMPRESSED_TYPES = ['application/json', 'text/event-stream'];
+
+let enabled = true;
+
+export function setCompressionEnabled(value: boolean): void {
+  enabled = value;
+}
+
+/**
+ * The encoding to use for an `Accept-Encoding` header, preferring gzip, or null for none
+ */
+export function negotiateEncoding(header: string | undefined): Encoding | null {
+  const accepted = new Map<string, number>();
+  for (const part of (header || '').split(',')) {
+    const [name, ...params] = part.trim().toLowerCase().split(';');
+    const quality = params.map((param) => /^\s*q=([\d.]+)\s*$/.exec(param)).find(Boolean);
+    accepted.set(name, quality ? parseFloat(quality[1]) : 1);
+  }
+  const encodings: Encoding[] = ['gzip', 'deflate'];
+  return encodings.find((encoding) => (accepted.get(encoding) ?? accepted.get('*') ?? 0) > 0) ?? null;
+}
+
+function shouldCompress(statusCode: number, headers: http.OutgoingHttpHeaders): boolean {
+  const type = String(headers['content-type'] || '').split(';')[0].trim();
+  const length = headers['content-length'] !== undefined ? Number(headers['content-length']) : null;
+  return (
+    COMPRESSED_TYPES.includes(type) &&
+    !headers['content-encoding'] &&
+    statusCode !== 204 &&
+    statusCode !== 304 &&
+    (length === null || length >= MIN_COMPRESSED_LENGTH)
+  );
+}
+
+/**
+ * Compress what `res` sends if the client accepts it and the content type is one worth
+ * compressing. The decision is made when the head is written.
+ */
+export function compressResponse(req: http.IncomingMessage, res: http.ServerResponse): void {
+  const encoding = enabled && req.method !== 'HEAD' ? negotiateEncoding(req.headers['accept-encoding'] as string | undefined) : null;
+  if (!encoding) {
+    return;
+  }
+
+  let compressor: zlib.Gzip | zlib.Deflate | null = null;
+  let streaming = false;
+  const writeHead = res.writeHead.bind(res) as (...args: unknown[]) => http.ServerResponse;
+  const write = res.write.bind(res) as (...args: unknown[]) => boolean;
+  const end = res.end.bind(res) as (...args: unknown[]) => http.ServerResponse;
+
+  res.writeHead = ((statusCode: number, ...args: unknown[]) => {
+    const index = args.findIndex((arg) => arg && typeof arg === 'object' && !Array.isArray(arg));
+    const passed = index >= 0 ? Object.fromEntries(Object.entries(args[index] as object).map(([name, value]) => [name.toLowerCase(), value])) : {};
+    const headers: http.OutgoingHttpHeaders = { ...res.getHeaders(), ...passed };
+
+    if (shouldCompress(statusCode, headers)) {
+      streaming = String(headers['content-type']).startsWith('text/event-stream');
+      compressor = encoding === 'gzip' ? zlib.createGzip() : zlib.createDeflate();
+      compressor.on('data', (chunk: Buffer) => write(chunk));
+      compressor.on('end', () => end());
+      res.on('close', () => compressor?.destroy());
+
+      delete passed['content-length'];
+      res.removeHeader('Content-Length');
+      const vary = headers['vary'] ? `${headers['vary']}, Accept-Encoding` : 'Accept-Encoding';
+      const compressed = { ...passed, 'content-encoding': encoding, vary };
+      return writeHead(statusCode, ...(index >= 0 ? args.map((arg, i) => (i === index ? compressed : arg)) : [...args, compressed]));
+    }
+    return writeHead(statusCode, ...args);
+  }) as typeof res.writeHead;
+
+  res.write = ((chunk: unknown, ...args: unknown[]) => {
+    if (!res.headersSent) {
+      res.writeHead(res.statusCode);
+    }
+    if (!compressor) {
+      return write(chunk, ...args);
+    }
+    const callback = args.find((arg) => typeof arg === 'function') as (() => void) | undefined;
+    compressor.write(chunk as Buffer | string);
+    // Events have to reach the client as they happen rather than once the compressor's buffer is full
+    if (streaming) {
+      compressor.flush(zlib.constants.Z_SYNC_FLUSH);
+    }
+    callback?.();
+    return true;
+  }) as typeof res.write;
+
+  res.end = ((chunk?: unknown, ...args: unknown[]) => {
+    if (!res.headersSent) {
+      res.writeHead(res.statusCode);
+    }
+    if (!compressor) {
+      return end(chunk, ...args);
+    }
+    if (chunk !== undefined && chunk !== null && typeof chunk !== 'function') {
+      compressor.write(chunk as Buffer | string);
+    }
+    compressor.end();
+    return res;
+  }) as typeof res.end;
+}
@@ -0,0 +1,134 @@
+import { setAccessibilitySettings } from './accessibility';
+import { clearStatusBar, overrideStatusBar } from './appearance';
+import type {
+  AppliedConditions,
+  ConditionEffect,
+  ConditionProfile,
+  ConditionProfileId,
+  DeviceConditions,
+  NetworkCondition,
+  StatusBarOverrides,
+  ThermalState,
+} from '../../shared/ipc-types';
+
+/**
+ * Condition profiles: named bundles of the network, thermal state, low power mode and motion and
+ * transparency settings a device can be in, so QA can put a simulator in e.g. its worst case with
+ * one call. Simulators share the Mac's network and have no battery or thermal sensors, so only the
+ * accessibility settings change how apps behave; the network and low power mode are shown on the
+ * status bar, and the thermal state is only recorded.
+ */
+
+export const NETWORK_CONDITIONS: NetworkCondition[] = ['wifi', 'lte', '3g', 'offline'];
+export const THERMAL_STATES: ThermalState[] = ['nominal', 'fair', 'serious', 'critical'];
+
+const DEFAULT_CONDITIONS: DeviceConditions = {
+  network: 'wifi',
+  thermalState: 'nominal',
+  lowPower: false,
+  reduceMotion: false,
+  reduceTransparency: false,
+};
+
+export const CONDITION_PROFILES: ConditionProfile[] = [
+  {
+    id: 'default',
+    name: 'Default',
+    description: 'Wi-Fi, a cool device and every setting off',
+    conditions: DEFAULT_CONDITIONS,
+  },
+  {
+    id: 'worst-case',
+    name: 'Worst case',
+    description: 'A 3G connection on a hot device in low power mode, with reduced motion and transparency',
+    conditions: { network: '3g', thermalState: 'critical', lowPower: true, reduceMotion: true, reduceTransparency: true },
+  },
+  {
+    id: 'poor-network',
+    name: 'Poor network',
+    description: 'A 3G connection',
+    conditions: { ...DEFAULT_CONDITIONS, network: '3g' },
+  },
+  {
+    id: 'offline',
+    name: 'Offline',
+    description: 'No connection',
+    conditions: { ...DEFAULT_CONDITIONS, network: 'offline' },
+  },
+  {
+    id: 'low-power',
+    name: 'Low power',
+    description: 'Low power mode on a warm device',
+    conditions: { ...DEFAULT_CONDITIONS, thermalState: 'serious', lowPower: true },
+  },
+  {
+    id: 'reduced-motion',
+    name: 'Reduced motion',
+    description: 'Reduced motion and transparency',
+    conditions: { ...DEFAULT_CONDITIONS, reduceMotion: true, reduceTransparency: true },
+  },
+];
+
+export const CONDITION_PROFILE_IDS = CONDITION_PROFILES.map((profile) => profile.id);
+
+// How each condition reaches the simulator
+const EFFECTS: Record<keyof DeviceConditions, ConditionEffect> = {
+  network: 'status_bar',
+  thermalState: 'unsupported',
+  lowPower: 'status_bar',
+  reduceMotion: 'applied',
+  reduceTransparency: 'applied',
+};
+
+const NETWORK_STATUS_BARS: Record<NetworkCondition, StatusBarOverrides> = {
+  wifi: { dataNetwork: 'wifi', wifiMode: 'active', wifiBars: 3 },
+  lte: { dataNetwork: 'lte', cellularMode: 'active', cellularBars: 3 },
+  '3g': { dataNetwork: '3g', cellularMode: 'active', cellularBars: 1 },
+  offline: { dataNetwork: 'hide', wifiMode: 'failed', cellularMode: 'searching', cellularBars: 0 },
+};
+
+const applied = new Map<string, AppliedConditions>();
+
+export function findConditionProfile(id: string): ConditionProfile | null {
+  return CONDITION_PROFILES.find((profile) => profile.id === id) ?? null;
+}
+
+/**
+ * Put a simulator in the conditions of a profile, with `overrides` replacing some of them. Without
+ * a profile the overrides apply to the default conditions. Conditions shown on the status bar
+ * replace its overrides.
+ */
+export async function applyConditions(
+  udid: string,
+  profileId: ConditionProfileId | null,
+  overrides: Partial<DeviceConditions> = {}
+): Promise<AppliedConditions> {
+  const profile = profileId ? findConditionProfile(profileId)! : null;
+  const conditions = { ...(profile?.conditions ?? DEFAULT_CONDITIONS), ...overrides };
+
+  await setAccessibilitySettings(udid, { reduceMotion: conditions.reduceMotion, reduceTransparency: conditions.reduceTransparency });
+  if (conditions.network === 'wifi' && !conditions.lowPower) {
+    await clearStatusBar(udid);
+  } else {
+    await overrideStatusBar(udid, {
+      ...NETWORK_STATUS_BARS[conditions.network],
+      ...(conditions.lowPower ? { batteryState: 'discharging', batteryLevel: 10 } : { batteryState: 'charged', batteryLevel: 100 }),
+    });
+  }
+
+  const result: AppliedConditions = {
+    profile: profile?.id ?? null,
+    conditions,
+    effects: { ...EFFECTS },
+    appliedAt: new Date().toISOString(),
+  };
+  applied.set(udid, result);
+  return result;
+}
+
+/**
+ * The conditions last applied to a simulator, or null if it's in its own
+ */
+export function getAppliedConditions(udid: string): AppliedConditions | null {
+  return applied.get(udid) ?? null;
+}
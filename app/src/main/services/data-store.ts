@@ -0,0 +1,256 @@
+import { createSqliteDataStore } from './sqlite-store';
+import { createPostgresDataStore } from './postgres-store';
+import type { ListQuery } from '../list-query';
+import type {
+  NotificationRecord,
+  NotifierRecord,
+  SecretInfo,
+  UnifiedProject,
+} from '../../shared/ipc-types';
+
+/**
+ * Repositories for the instance's shared state: projects with their destinations, settings and
+ * notifiers, builds, notifications, backend settings and secrets. They live in the local SQLite
+ * database, or in Postgres when `PLASMA_DATABASE_URL` points at one, so several Plasma instances
+ * can serve one team from the same data and writers don't queue up on SQLite's lock.
+ *
+ * Rows that belong to a project are keyed by the project's UUID in Postgres, which is the same on
+ * every instance; the numeric IDs in URLs come from the store too. What only makes sense on one
+ * machine stays in its SQLite database: running processes, recordings, stream logs, launch
+ * timings, tap calibrations, erase snapshots and usage analytics.
+ */
+
+export type DatabaseBackend = 'sqlite' | 'postgres';
+
+export interface DatabaseSettings {
+  // `postgres://...` for Postgres; null keeps the repositories in SQLite
+  url: string | null;
+  // How long a SQLite write waits for another connection's lock before failing with SQLITE_BUSY
+  busyTimeoutMs: number;
+}
+
+// What the repositories need to find a project's rows
+export type ProjectKey = Pick<UnifiedProject, 'id' | 'uuid'>;
+
+export interface StoredDestination {
+  preset: string;
+  updatedAt: string;
+}
+
+export interface StoredProjectSettings {
+  scheme: string | null;
+  configuration: string | null;
+  simulatorUdid: string | null;
+  environment: Record<string, string>;
+  updatedAt: string;
+}
+
+export interface ProjectSettingsChanges {
+  scheme?: string | null;
+  configuration?: string | null;
+  simulatorUdid?: string | null;
+  environment?: Record<string, string>;
+}
+
+export interface NotificationRow extends Omit<NotificationRecord, 'data'> {
+  // JSON
+  data: string | null;
+}
+
+export type NewNotificationRow = Omit<NotificationRow, 'id' | 'read_at'>;
+
+export interface NotifierRow extends Omit<NotifierRecord, 'events' | 'enabled' | 'webhook_secret'> {
+  // JSON array of NotifierEvent
+  events: string;
+  enabled: number;
+}
+
+export type NewNotifierRow = Omit<NotifierRow, 'id' | 'project_id'>;
+
+export interface BuildRow {
+  id: string;
+  project_id: number | null;
+  project_path: string;
+  scheme: string;
+  configuration: string;
+  destination: string;
+  // Null for builds recorded before statuses were added
+  status: string | null;
+  success: number;
+  duration_ms: number;
+  warnings: number;
+  errors: number;
+  // JSON array of BuildProductSize
+  products: string;
+  // JSON object of the first target's build settings
+  build_settings: string;
+  started_at: string | null;
+  finished_at: string;
+  // JSON BuildEnvironment, null for builds recorded before it was
+  environment: string | null;
+}
+
+export interface SettingRow {
+  key: string;
+  value: string;
+  updated_at: string;
+}
+
+export interface ProjectRepository {
+  // Most recently opened first
+  recent(limit: number): Promise<UnifiedProject[]>;
+  // Oldest first
+  all(): Promise<UnifiedProject[]>;
+  count(): Promise<number>;
+  get(id: number): Promise<UnifiedProject | null>;
+  // The project that owns an Xcode or Android project path
+  findByPath(projectPath: string): Promise<UnifiedProject | null>;
+  // Add a project, or rename and reopen the one with exactly these paths
+  save(name: string, xcodePath: string | null, androidPath: string | null): Promise<UnifiedProject>;
+  setNotes(id: number, notes: string | null): Promise<void>;
+  markOpened(id: number): Promise<void>;
+}
+
+export interface ProjectDestinationRepository {
+  get(project: ProjectKey): Promise<StoredDestination | null>;
+  // When `expectedUpdatedAt` is given (null meaning "nothing stored yet"), the write only happens if
+  // the stored destination hasn't changed since; resolves to false otherwise
+  set(project: ProjectKey, preset: string, expectedUpdatedAt?: string | null): Promise<boolean>;
+}
+
+export interface ProjectSettingsRepository {
+  get(project: ProjectKey): Promise<StoredProjectSettings | null>;
+  // Change some settings, keeping the others. `expectedUpdatedAt` works like it does for destinations.
+  update(project: ProjectKey, changes: ProjectSettingsChanges, expectedUpdatedAt?: string | null): Promise<boolean>;
+}
+
+export interface NotifierRepository {
+  list(project: ProjectKey): Promise<NotifierRow[]>;
+  get(project: ProjectKey, id: number): Promise<NotifierRow | null>;
+  insert(project: ProjectKey, notifier: NewNotifierRow): Promise<NotifierRow>;
+  delete(project: ProjectKey, id: number): Promise<boolean>;
+  // Webhook URLs stored with notifiers created before they moved to the secrets store, by notifier
+  // ID. Only SQLite databases have them.
+  plaintextWebhookUrls(): Promise<Map<number, string>>;
+  clearPlaintextWebhookUrl(id: number): Promise<void>;
+}
+
+export interface NotificationRepository {
+  insert(notification: NewNotificationRow): Promise<NotificationRow>;
+  // A page of notifications and how many match, filtered and sorted by NOTIFICATION_COLUMNS
+  list(query: ListQuery, unreadOnly: boolean): Promise<{ rows: NotificationRow[]; total: number }>;
+  // Recorded after the one with ID `id`, oldest first
+  listSince(id: number): Promise<NotificationRow[]>;
+  countUnread(): Promise<number>;
+  // False if there's no such notification
+  markRead(id: number): Promise<boolean>;
+  markAllRead(): Promise<void>;
+}
+
+export interface BuildRepository {
+  insert(build: Omit<BuildRow, 'project_id'>, project: ProjectKey | null): Promise<void>;
+  get(id: string): Promise<BuildRow | null>;
+  // Newest first, optionally only those of one project
+  list(project?: ProjectKey): Promise<BuildRow[]>;
+  // Builds whose products mention the checksum, newest first
+  findByChecksum(sha256: string): Promise<BuildRow[]>;
+  // Durations of the latest successful builds of a scheme, newest first
+  recentDurations(projectPath: string, scheme: string, destination: string, limit: number): Promise<number[]>;
+  firstSucceeded(): Promise<BuildRow | null>;
+}
+
+export interface SettingRepository {
+  get(key: string): Promise<SettingRow | null>;
+  list(): Promise<SettingRow[]>;
+  // Read and write in one transaction, so another instance can't change the setting in between.
+  // `update` returns the value to store, null to delete it, or the current value to leave it.
+  update(key: string, update: (current: string | null) => string | null): Promise<SettingRow | null>;
+}
+
+export interface SecretRepository {
+  list(): Promise<SecretInfo[]>;
+  getCiphertext(name: string): Promise<string | null>;
+  set(name: string, ciphertext: string): Promise<void>;
+  delete(name: string): Promise<boolean>;
+}
+
+export interface DataStore {
+  readonly backend: DatabaseBackend;
+  readonly projects: ProjectRepository;
+  readonly projectDestinations: ProjectDestinationRepository;
+  readonly projectSettings: ProjectSettingsRepository;
+  readonly notifiers: NotifierRepository;
+  readonly notifications: NotificationRepository;
+  readonly builds: BuildRepository;
+  readonly settings: SettingRepository;
+  readonly secrets: SecretRepository;
+  close(): Promise<void>;
+}
+
+/**
+ * Every write must change updated_at, even within the same millisecond
+ */
+export function nextUpdatedAt(current: string | null): string {
+  return new Date(Math.max(Date.now(), current ? Date.parse(current) + 1 : 0)).toISOString();
+}
+
+// Columns notifications can be filtered and sorted by, by field
+export const NOTIFICATION_COLUMNS: Record<string, string> = {
+  id: 'id',
+  kind: 'kind',
+  level: 'level',
+  created_at: 'created_at',
+};
+
+/**
+ * WHERE and ORDER BY clauses for a notifications query, with `placeholder(n)` for the nth value
+ */
+export function notificationListClauses(
+  query: ListQuery,
+  unreadOnly: boolean,
+  placeholder: (n: number) => string
+): { where: string; orderBy: string; values: unknown[] } {
+  const conditions: string[] = unreadOnly ? ['read_at IS NULL'] : [];
+  const values: unknown[] = [];
+  for (const [field, accepted] of Object.entries(query.filters)) {
+    const list = accepted.map((value) => {
+      values.push(value);
+      return placeholder(values.length);
+    });
+    conditions.push(`LOWER(CAST(${NOTIFICATION_COLUMNS[field]} AS TEXT)) IN (${list.join(', ')})`);
+  }
+  return {
+    where: conditions.length > 0 ? `WHERE ${conditions.join(' AND ')}` : '',
+    orderBy: [
+      ...query.sort.map(({ field, descending }) => `${NOTIFICATION_COLUMNS[field]} ${descending ? 'DESC' : 'ASC'}`),
+      'id DESC',
+    ].join(', '),
+    values,
+  };
+}
+
+export { createSqliteDataStore, createPostgresDataStore };
+
+export function isPostgresUrl(url: string): boolean {
+  return /^postgres(ql)?:\/\//.test(url);
+}
+
+export function createDataStore(settings: DatabaseSettings): DataStore {
+  return settings.url && isPostgresUrl(settings.url) ? createPostgresDataStore(settings.url) : createSqliteDataStore();
+}
+
+let store: DataStore | null = null;
+
+/**
+ * Use a different store, e.g. the one from the config. Defaults to SQLite.
+ */
+export function setDataStore(newStore: DataStore | null): void {
+  store = newStore;
+}
+
+export function getDataStore(): DataStore {
+  if (!store) {
+    store = createSqliteDataStore();
+  }
+  return store;
+}
@@ -1,22 +1,62 @@
 import Database from 'better-sqlite3';
+import { randomUUID } from 'crypto';
 import * as path from 'path';
 import * as os from 'os';
 import * as fs from 'fs';
+import type { Platform, ProjectRecord } from '../../shared/ipc-types';
 
 let db: Database.Database | null = null;
+let busyTimeoutMs = 5000;
 
-function getDbPath(): string {
+/**
+ * Directory where Plasma keeps its database and other persistent files
+ */
+export function getDataDir(): string {
   const dataDir = path.join(os.homedir(), '.local', 'share', 'plasma');
   if (!fs.existsSync(dataDir)) {
     fs.mkdirSync(dataDir, { recursive: true });
   }
-  return path.join(dataDir, 'plasma.db');
+  return dataDir;
+}
+
+let databasePathOverride: string | null = null;
+
+/**
+ * Open the database at `databasePath` instead of `PLASMA_DATABASE_PATH` from the next `getDatabase()` on,
+ * e.g. `:memory:` for test servers. Null goes back to the environment.
+ */
+export function setDatabasePath(databasePath: string | null): void {
+  databasePathOverride = databasePath;
+}
+
+export function getDatabasePath(): string {
+  // `:memory:` keeps everything in memory, e.g. for tests
+  return databasePathOverride || process.env.PLASMA_DATABASE_PATH || path.join(getDataDir(), 'plasma.db');
+}
+
+/**
+ * How long a write waits for another connection's lock before failing with SQLITE_BUSY
+ */
+export function setDatabaseBusyTimeout(ms: number): void {
+  busyTimeoutMs = ms;
+  db?.pragma(`busy_timeout = ${ms}`);
+}
+
+/**
+ * Add a column to a table created by an older version, which `CREATE TABLE IF NOT EXISTS`
+ * leaves as it was
+ */
+function addColumnIfMissing(database: Database.Database, table: string, column: string, definition: string): void {
+  const columns = database.pragma(`table_info(${table})`) as Array<{ name: string }>;
+  if (!columns.some((existing) => existing.name === column)) {
+    database.exec(`ALTER TABLE ${table} ADD COLUMN ${column} ${definition}`);
+  }
 }
 
 export function getDatabase(): Database.Database {
   if (!db) {
-    const dbPath = getDbPath();
-    db = new Database(dbPath);
+    const dbPath = getDatabasePath();
+    db = new Database(dbPath, { timeout: busyTimeoutMs });
     db.pragma('journal_mode = WAL');
 
     // Create new unified projects table
@@ -31,6 +71,14 @@ export function getDatabase(): Database.Database {
       )
     `);
 
+    addColumnIfMissing(db, 'unified_projects', 'notes', 'TEXT');
+    // Identifies the project to every instance sharing its database
+    addColumnIfMissing(db, 'unified_projects', 'uuid', 'TEXT');
+    const setUuid = db.prepare('UPDATE unified_projects SET uuid = ? WHERE id = ?');
+    for (const row of db.prepare('SELECT id FROM unified_projects WHERE uuid IS NULL').all() as { id: number }[]) {
+      setUuid.run(randomUUID(), row.id);
+    }
+
     // Keep old table for backward compatibility during migration
     db.exec(`
       CREATE TABLE IF NOT EXISTS projects (
@@ -42,89 +90,218 @@ export function getDatabase(): Database.Database {
         created_at TEXT
       )
     `);
-  }
-  return db;
-}
 
-export type Platform = 'ios' | 'android';
+    // User-facing notifications recorded by the backend
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS notifications (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        kind TEXT NOT NULL,
+        level TEXT NOT NULL DEFAULT 'info',
+        title TEXT NOT NULL,
+        message TEXT,
+        data TEXT,
+        read_at TEXT,
+        created_at TEXT NOT NULL
+      )
+    `);
 
-// New unified project record
-export interface UnifiedProjectRecord {
-  id: number;
-  name: string;
-  xcode_path: string | null;
-  android_path: string | null;
-  last_opened_at: string | null;
-  created_at: string | null;
-}
+    // Chat notifiers (Slack, Discord) attached to a project
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS project_notifiers (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        project_id INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        webhook_url TEXT NOT NULL,
+        events TEXT NOT NULL DEFAULT '[]',
+        template TEXT,
+        enabled INTEGER NOT NULL DEFAULT 1,
+        created_at TEXT NOT NULL
+      )
+    `);
 
-// Legacy project record (for backward compatibility)
-export interface ProjectRecord {
-  id: number;
-  path: string;
-  name: string;
-  platforms: Platform[];
-  last_opened_at: string | null;
-  created_at: string | null;
-}
+    // Timing spans of the boot/install/launch pipeline
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS launch_timings (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        udid TEXT NOT NULL,
+        device_type TEXT NOT NULL,
+        boot_ms INTEGER,
+        install_ms INTEGER,
+        launch_ms INTEGER,
+        first_frame_ms INTEGER,
+        total_ms INTEGER NOT NULL,
+        created_at TEXT NOT NULL
+      )
+    `);
 
-// Save a unified project
-export function saveUnifiedProject(
-  name: string,
-  xcodePath: string | null,
-  androidPath: string | null
-): UnifiedProjectRecord {
-  const db = getDatabase();
-  const now = new Date().toISOString();
+    // Build destination preset chosen for a project
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS project_build_destinations (
+        project_id INTEGER PRIMARY KEY,
+        preset TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+      )
+    `);
 
-  // Check if a project with these exact paths exists
-  let existing: { id: number } | undefined;
-  if (xcodePath && androidPath) {
-    existing = db.prepare(
-      'SELECT id FROM unified_projects WHERE xcode_path = ? AND android_path = ?'
-    ).get(xcodePath, androidPath) as { id: number } | undefined;
-  } else if (xcodePath) {
-    existing = db.prepare(
-      'SELECT id FROM unified_projects WHERE xcode_path = ? AND android_path IS NULL'
-    ).get(xcodePath) as { id: number } | undefined;
-  } else if (androidPath) {
-    existing = db.prepare(
-      'SELECT id FROM unified_projects WHERE android_path = ? AND xcode_path IS NULL'
-    ).get(androidPath) as { id: number } | undefined;
-  }
+    // Last used build setup per project; `environment` is a JSON object of environment variables
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS project_settings (
+        project_id INTEGER PRIMARY KEY,
+        scheme TEXT,
+        configuration TEXT,
+        simulator_udid TEXT,
+        environment TEXT NOT NULL DEFAULT '{}',
+        updated_at TEXT NOT NULL
+      )
+    `);
 
-  if (existing) {
-    db.prepare(
-      'UPDATE unified_projects SET name = ?, last_opened_at = ? WHERE id = ?'
-    ).run(name, now, existing.id);
-    return getUnifiedProjectById(existing.id)!;
-  } else {
-    const result = db.prepare(
-      'INSERT INTO unified_projects (name, xcode_path, android_path, last_opened_at, created_at) VALUES (?, ?, ?, ?, ?)'
-    ).run(name, xcodePath, androidPath, now, now);
-    return getUnifiedProjectById(result.lastInsertRowid as number)!;
+    // Tap coordinate corrections per simulator device type
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS tap_calibrations (
+        device_type TEXT PRIMARY KEY,
+        scale_x REAL NOT NULL,
+        scale_y REAL NOT NULL,
+        offset_x REAL NOT NULL,
+        offset_y REAL NOT NULL,
+        probes TEXT NOT NULL DEFAULT '[]',
+        calibrated_at TEXT NOT NULL
+      )
+    `);
+
+    // Finished builds, with what's needed to compare them and their provenance; `environment` is
+    // a BuildEnvironment as JSON, null for builds recorded before it was
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS builds (
+        id TEXT PRIMARY KEY,
+        project_id INTEGER,
+        project_path TEXT NOT NULL,
+        scheme TEXT NOT NULL,
+        configuration TEXT NOT NULL DEFAULT 'Debug',
+        destination TEXT NOT NULL,
+        status TEXT,
+        success INTEGER NOT NULL,
+        duration_ms INTEGER NOT NULL,
+        warnings INTEGER NOT NULL DEFAULT 0,
+        errors INTEGER NOT NULL DEFAULT 0,
+        products TEXT NOT NULL DEFAULT '[]',
+        build_settings TEXT NOT NULL DEFAULT '{}',
+        started_at TEXT,
+        finished_at TEXT NOT NULL,
+        environment TEXT
+      )
+    `);
+    addColumnIfMissing(db, 'builds', 'project_id', 'INTEGER');
+    addColumnIfMissing(db, 'builds', 'configuration', "TEXT NOT NULL DEFAULT 'Debug'");
+    addColumnIfMissing(db, 'builds', 'status', 'TEXT');
+    addColumnIfMissing(db, 'builds', 'started_at', 'TEXT');
+    addColumnIfMissing(db, 'builds', 'environment', 'TEXT');
+
+    // Processes spawned by each instance, so a crashed instance's orphans can be found
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS process_registry (
+        pid INTEGER NOT NULL,
+        command TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        owner_pid INTEGER NOT NULL,
+        started_at TEXT NOT NULL,
+        PRIMARY KEY (pid, owner_pid)
+      )
+    `);
+
+    // Clones of erased simulators kept to undo the erase, so those left by an instance that exited can be deleted
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS erase_snapshots (
+        clone_udid TEXT PRIMARY KEY,
+        udid TEXT NOT NULL,
+        owner_pid INTEGER NOT NULL,
+        expires_at TEXT NOT NULL
+      )
+    `);
+
+    // Backend settings, such as whether onboarding was dismissed; their keys and types are in services/settings.ts
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS settings (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+      )
+    `);
+
+    // Simulator stream log history, when persistence is enabled
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS stream_logs (
+        id INTEGER PRIMARY KEY,
+        udid TEXT NOT NULL,
+        type TEXT NOT NULL,
+        message TEXT,
+        created_at TEXT NOT NULL
+      )
+    `);
+
+    // Opt-in usage analytics: only the kind of activity, its duration and when it happened
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS usage_events (
+        id INTEGER PRIMARY KEY,
+        kind TEXT NOT NULL,
+        duration_ms INTEGER,
+        created_at TEXT NOT NULL
+      )
+    `);
+    db.exec('CREATE INDEX IF NOT EXISTS usage_events_created_at ON usage_events (created_at)');
+
+    // Simulator screen recordings; the videos are in the artifact store
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS recordings (
+        id TEXT PRIMARY KEY,
+        udid TEXT NOT NULL,
+        source TEXT NOT NULL DEFAULT 'simctl',
+        status TEXT NOT NULL,
+        error TEXT,
+        size_bytes INTEGER,
+        duration_ms INTEGER,
+        started_at TEXT NOT NULL,
+        finished_at TEXT
+      )
+    `);
+    addColumnIfMissing(db, 'recordings', 'source', "TEXT NOT NULL DEFAULT 'simctl'");
+
+    // Credentials, encrypted with the key from services/secrets.ts
+    db.exec(`
+      CREATE TABLE IF NOT EXISTS secrets (
+        name TEXT PRIMARY KEY,
+        ciphertext TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+      )
+    `);
   }
+  return db;
 }
 
-export function getUnifiedProjectById(id: number): UnifiedProjectRecord | null {
+/**
+ * Close the database. The next `getDatabase()` call opens it again.
+ */
+export function closeDatabase(): void {
+  db?.close();
+  db = null;
+}
+
+export function getSetting(key: string): { value: string; updated_at: string } | null {
   const db = getDatabase();
-  const record = db.prepare('SELECT * FROM unified_projects WHERE id = ?').get(id) as UnifiedProjectRecord | undefined;
-  return record || null;
+  return (db.prepare('SELECT value, updated_at FROM settings WHERE key = ?').get(key) as { value: string; updated_at: string } | undefined) || null;
 }
 
-export function getRecentUnifiedProjects(limit: number = 10): UnifiedProjectRecord[] {
+export function setSetting(key: string, value: string): void {
   const db = getDatabase();
-  return db.prepare(`
-    SELECT * FROM unified_projects
-    ORDER BY last_opened_at DESC
-    LIMIT ?
-  `).all(limit) as UnifiedProjectRecord[];
+  db.prepare(`
+    INSERT INTO settings (key, value, updated_at) VALUES (?, ?, ?)
+    ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at
+  `).run(key, value, new Date().toISOString());
 }
 
-export function updateProjectLastOpened(id: number): void {
+export function deleteSetting(key: string): void {
   const db = getDatabase();
-  const now = new Date().toISOString();
-  db.prepare('UPDATE unified_projects SET last_opened_at = ? WHERE id = ?').run(now, id);
+  db.prepare('DELETE FROM settings WHERE key = ?').run(key);
 }
 
 // Legacy functions for backward compatibility
@@ -0,0 +1,30 @@
+import * as crypto from 'crypto';
+
+/**
+ * AES-256-GCM encryption of short strings such as credentials. Values are encoded as
+ * `iv:tag:ciphertext` in base64, so they can be stored as text.
+ */
+
+export function deriveKey(passphrase: string, salt: Buffer): Buffer {
+  return crypto.scryptSync(passphrase, salt, 32);
+}
+
+export function encrypt(value: string, key: Buffer): string {
+  const iv = crypto.randomBytes(12);
+  const cipher = crypto.createCipheriv('aes-256-gcm', key, iv);
+  const ciphertext = Buffer.concat([cipher.update(value, 'utf-8'), cipher.final()]);
+  return [iv, cipher.getAuthTag(), ciphertext].map((part) => part.toString('base64')).join(':');
+}
+
+/**
+ * Decrypt a value from `encrypt`. Throws when it was encrypted with another key or was modified.
+ */
+export function decrypt(value: string, key: Buffer): string {
+  const [iv, tag, ciphertext] = value.split(':');
+  if (ciphertext === undefined) {
+    throw new Error('Malformed encrypted value');
+  }
+  const decipher = crypto.createDecipheriv('aes-256-gcm', key, Buffer.from(iv, 'base64'));
+  decipher.setAuthTag(Buffer.from(tag, 'base64'));
+  return Buffer.concat([decipher.update(Buffer.from(ciphertext, 'base64')), decipher.final()]).toString('utf-8');
+}
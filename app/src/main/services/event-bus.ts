@@ -0,0 +1,55 @@
+import type { WebSocket } from 'ws';
+import type { ChannelName } from '../config';
+import type { StreamScaler } from './stream-scaling';
+import type { FrameFanout } from './frame-fanout';
+import type { Watermarker } from './watermark';
+
+/**
+ * The WebSocket clients of one server and the frame sources they share, part of its AppState.
+ * Broadcasts go to the clients of one bus, so the clients of two servers in one process, e.g. in
+ * tests, don't see each other's messages.
+ */
+
+export interface WebSocketClient {
+  id: number;
+  ws: WebSocket;
+  subscriptions: Set<string>;
+  // Messages dropped per channel since the last `lagged` notice
+  skipped: Partial<Record<ChannelName, number>>;
+  // Who is connected, from the connection's request (see `viewerOf`), for watermarks
+  viewer: string;
+  // Draws the watermark into the frames of each streamed simulator that has one
+  watermarks: Map<string, Watermarker>;
+  // Scaling of the frames of each streamed simulator that asked for smaller frames
+  scalers: Map<string, StreamScaler>;
+}
+
+export class EventBus {
+  readonly clients = new Map<WebSocket, WebSocketClient>();
+  // One frame source per streamed UDID, shared by its WebSocket, thumbnail and video viewers
+  readonly frameFanouts = new Map<string, FrameFanout>();
+  private nextClientId = 1;
+  // Identify thumbnail and video streams as viewers of their simulator's frames
+  private nextViewerIds = { thumbnail: 1, video: 1 };
+
+  addClient(ws: WebSocket, viewer: string): WebSocketClient {
+    const client: WebSocketClient = {
+      id: this.nextClientId++,
+      ws,
+      viewer,
+      subscriptions: new Set(),
+      skipped: {},
+      watermarks: new Map(),
+      scalers: new Map(),
+    };
+    this.clients.set(ws, client);
+    return client;
+  }
+
+  /**
+   * A new frame viewer ID, e.g. `thumbnail:3`
+   */
+  nextViewerId(kind: 'thumbnail' | 'video'): string {
+    return `${kind}:${this.nextViewerIds[kind]++}`;
+  }
+}
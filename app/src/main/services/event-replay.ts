@@ -0,0 +1,137 @@
+/**
+ * Replay of Server-Sent Events for streams that outlive a connection, like Android builds. Every
+ * event gets an ID from one counter, so a client that reconnects with `Last-Event-ID` is matched
+ * with the stream it was following and gets the events it missed before following it live again.
+ *
+ * Nobody following a stream for the grace period counts as the client going away for good, and
+ * calls the stream's `onAbandoned`, e.g. to cancel the build.
+ */
+
+export interface ReplayedEvent {
+  id: number;
+  event: string;
+  data: unknown;
+}
+
+type Follower = (event: ReplayedEvent) => void;
+
+// Events kept per stream; a build sends an event per line of output, and clients that missed
+// more than this start over
+const MAX_EVENTS = 50000;
+// Finished streams are kept this long for clients that lost the last events
+const RETENTION_MS = 60 * 1000;
+// How long a stream waits for its client to reconnect before it's abandoned
+const GRACE_PERIOD_MS = 10 * 1000;
+
+let nextEventId = 1;
+const replays = new Set<EventReplay>();
+
+export class EventReplay {
+  private events: ReplayedEvent[] = [];
+  private followers = new Set<Follower>();
+  private endListeners = new Set<() => void>();
+  private abandonTimer: ReturnType<typeof setTimeout> | null = null;
+  ended = false;
+
+  constructor(private readonly onAbandoned?: () => void) {}
+
+  /**
+   * Keep an event and send it to the clients following the stream
+   */
+  push(event: string, data: unknown): void {
+    const replayed = { id: nextEventId++, event, data };
+    this.events.push(replayed);
+    if (this.events.length > MAX_EVENTS) {
+      this.events.shift();
+    }
+    this.followers.forEach((follower) => follower(replayed));
+  }
+
+  end(): void {
+    this.ended = true;
+    this.clearAbandonTimer();
+    this.endListeners.forEach((listener) => listener());
+    setTimeout(() => replays.delete(this), RETENTION_MS).unref();
+  }
+
+  /**
+   * Whether `lastEventId` is one of the kept events of this stream. Streams running at the same
+   * time share the counter, so their IDs interleave.
+   */
+  includes(lastEventId: number): boolean {
+    let low = 0;
+    let high = this.events.length - 1;
+    while (low <= high) {
+      const middle = (low + high) >> 1;
+      const id = this.events[middle].id;
+      if (id === lastEventId) {
+        return true;
+      }
+      if (id < lastEventId) {
+        low = middle + 1;
+      } else {
+        high = middle - 1;
+      }
+    }
+    return false;
+  }
+
+  /**
+   * Send the events after `lastEventId` (all of them for 0), then the live ones until the stream
+   * ends. Returns the function that stops following.
+   */
+  follow(lastEventId: number, forward: Follower, onEnd: () => void): () => void {
+    this.events.filter((event) => event.id > lastEventId).forEach(forward);
+    if (this.ended) {
+      onEnd();
+      return () => {};
+    }
+
+    this.clearAbandonTimer();
+    this.followers.add(forward);
+    this.endListeners.add(onEnd);
+    return () => {
+      this.followers.delete(forward);
+      this.endListeners.delete(onEnd);
+      if (this.followers.size === 0 && !this.ended && this.onAbandoned) {
+        this.abandonTimer = setTimeout(this.onAbandoned, GRACE_PERIOD_MS);
+      }
+    };
+  }
+
+  private clearAbandonTimer(): void {
+    if (this.abandonTimer) {
+      clearTimeout(this.abandonTimer);
+      this.abandonTimer = null;
+    }
+  }
+}
+
+/**
+ * Start a stream that can be resumed with the IDs of its events
+ */
+export function createEventReplay(onAbandoned?: () => void): EventReplay {
+  const replay = new EventReplay(onAbandoned);
+  replays.add(replay);
+  return replay;
+}
+
+/**
+ * The stream `lastEventId` was sent for, if it's still kept
+ */
+export function findEventReplay(lastEventId: number): EventReplay | null {
+  for (const replay of replays) {
+    if (replay.includes(lastEventId)) {
+      return replay;
+    }
+  }
+  return null;
+}
+
+/**
+ * The `Last-Event-ID` a reconnecting EventSource sends, or null
+ */
+export function parseLastEventId(header: string | string[] | undefined): number | null {
+  const id = typeof header === 'string' ? parseInt(header) : NaN;
+  return Number.isInteger(id) && id > 0 ? id : null;
+}
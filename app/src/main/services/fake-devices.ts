@@ -0,0 +1,184 @@
+import * as zlib from 'zlib';
+import {
+  deviceClassFor,
+  capabilitiesFor,
+  emitLog,
+  DeviceBackend,
+} from './simulator';
+import { pngChunk } from './png';
+import type { DeviceClass, Simulator } from '../../shared/ipc-types';
+
+const RUNTIMES: Record<DeviceClass, string> = {
+  phone: 'com.apple.CoreSimulator.SimRuntime.iOS-18-0',
+  tablet: 'com.apple.CoreSimulator.SimRuntime.iOS-18-0',
+  watch: 'com.apple.CoreSimulator.SimRuntime.watchOS-11-0',
+  tv: 'com.apple.CoreSimulator.SimRuntime.tvOS-18-0',
+  vision: 'com.apple.CoreSimulator.SimRuntime.xrOS-2-0',
+  unknown: 'com.apple.CoreSimulator.SimRuntime.iOS-18-0',
+};
+
+// Frame sizes with each device's aspect ratio, kept small since frames are generated on the fly
+const FRAME_SIZES: Record<DeviceClass, { width: number; height: number }> = {
+  phone: { width: 393, height: 852 },
+  tablet: { width: 410, height: 590 },
+  watch: { width: 198, height: 242 },
+  tv: { width: 480, height: 270 },
+  vision: { width: 640, height: 360 },
+  unknown: { width: 393, height: 852 },
+};
+
+const MAX_FAKE_FPS = 30;
+
+const FAKE_DEVICES = [
+  { udid: 'FAKE-IPHONE-16', name: 'iPhone 16 (Fake)', state: 'Booted', deviceType: 'iPhone-16' },
+  { udid: 'FAKE-IPAD-AIR', name: 'iPad Air 11-inch (Fake)', state: 'Booted', deviceType: 'iPad-Air-11-inch-M2' },
+  { udid: 'FAKE-APPLE-WATCH', name: 'Apple Watch Series 10 (Fake)', state: 'Shutdown', deviceType: 'Apple-Watch-Series-10-46mm' },
+  { udid: 'FAKE-APPLE-TV', name: 'Apple TV 4K (Fake)', state: 'Shutdown', deviceType: 'Apple-TV-4K-3rd-generation-4K' },
+  { udid: 'FAKE-APPLE-VISION-PRO', name: 'Apple Vision Pro (Fake)', state: 'Shutdown', deviceType: 'Apple-Vision-Pro' },
+];
+
+function hueFor(udid: string): number {
+  let hash = 0;
+  for (const char of udid) {
+    hash = (hash * 31 + char.charCodeAt(0)) >>> 0;
+  }
+  return hash % 360;
+}
+
+function hslToRgb(hue: number, saturation: number, lightness: number): [number, number, number] {
+  const a = saturation * Math.min(lightness, 1 - lightness);
+  const channel = (n: number) => {
+    const k = (n + hue / 30) % 12;
+    return Math.round(255 * (lightness - a * Math.max(-1, Math.min(k - 3, 9 - k, 1))));
+  };
+  return [channel(0), channel(8), channel(4)];
+}
+
+/**
+ * Render frame `frameNumber` of a fake device's stream as a PNG: a background in the
+ * device's color with a band sweeping down the screen. The same inputs always give the same frame.
+ */
+export function renderFakeFrame(udid: string, frameNumber: number, width: number, height: number): Buffer {
+  const hue = hueFor(udid);
+  const background = hslToRgb(hue, 0.45, 0.3);
+  const band = hslToRgb((hue + 180) % 360, 0.6, 0.6);
+  const bandHeight = Math.max(1, Math.round(height / 12));
+  const bandTop = (frameNumber * 4) % (height + bandHeight) - bandHeight;
+
+  // Each row is a filter type byte (0, none) followed by RGB triplets
+  const row = ([r, g, b]: [number, number, number]) => {
+    const buffer = Buffer.alloc(1 + width * 3);
+    for (let x = 0; x < width; x++) {
+      buffer[1 + x * 3] = r;
+      buffer[2 + x * 3] = g;
+      buffer[3 + x * 3] = b;
+    }
+    return buffer;
+  };
+  const backgroundRow = row(background);
+  const bandRow = row(band);
+
+  const rows: Buffer[] = [];
+  for (let y = 0; y < height; y++) {
+    rows.push(y >= bandTop && y < bandTop + bandHeight ? bandRow : backgroundRow);
+  }
+  const pixels = Buffer.concat(rows);
+
+  const header = Buffer.alloc(13);
+  header.writeUInt32BE(width, 0);
+  header.writeUInt32BE(height, 4);
+  header[8] = 8; // bit depth
+  header[9] = 2; // truecolor RGB
+  return Buffer.concat([
+    Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]),
+    pngChunk('IHDR', header),
+    pngChunk('IDAT', zlib.deflateSync(pixels)),
+    pngChunk('IEND', Buffer.alloc(0)),
+  ]);
+}
+
+/**
+ * Device backend with synthetic simulators, for developing and demoing without Xcode.
+ * Launches only log, input and simctl commands are ignored and streams are generated frames.
+ */
+export function createFakeDevicesBackend(): DeviceBackend {
+  const simulators: Simulator[] = FAKE_DEVICES.map((device) => {
+    const deviceType = `com.apple.CoreSimulator.SimDeviceType.${device.deviceType}`;
+    const deviceClass = deviceClassFor(deviceType);
+    return {
+      udid: device.udid,
+      name: device.name,
+      state: device.state,
+      runtime: RUNTIMES[deviceClass],
+      deviceType,
+      deviceClass,
+      capabilities: capabilitiesFor(deviceType),
+    };
+  });
+
+  const find = (udid: string): Simulator => {
+    const simulator = simulators.find((s) => s.udid === udid);
+    if (!simulator) {
+      throw new Error(`Unknown fake simulator ${udid}`);
+    }
+    return simulator;
+  };
+
+  return {
+    async listSimulators() {
+      return simulators.map((simulator) => ({ ...simulator }));
+    },
+
+    async installAndLaunch(udid, appPath, bundleId, launchArgs = []) {
+      const simulator = find(udid);
+      simulator.state = 'Booted';
+      const resolvedBundleId = bundleId || 'dev.plasma.fake';
+      const args = launchArgs.length > 0 ? ` with ${launchArgs.join(' ')}` : '';
+      emitLog(udid, 'info', `Launched ${resolvedBundleId} from ${appPath}${args} (fake device)`);
+      return {
+        message: `App ${resolvedBundleId} launched successfully`,
+        bundleId: resolvedBundleId,
+        timings: { total: 0 },
+      };
+    },
+
+    async sendTap() {},
+
+    async sendSwipe() {},
+
+    async typeText() {},
+
+    async sendSessionCommand() {},
+
+    async simctl() {
+      return '';
+    },
+
+    // Usage wanders between about 5-35% CPU and 110-150 MB, so graphs have something to draw
+    async sampleAppResources(udid, bundleId) {
+      const phase = Date.now() / 2000 + hueFor(udid);
+      return {
+        udid,
+        bundleId,
+        pid: 10000 + hueFor(bundleId),
+        cpuPercent: Math.round((20 + 15 * Math.sin(phase)) * 10) / 10,
+        rssBytes: Math.round((130 + 20 * Math.sin(phase / 3)) * 1024 * 1024),
+        timestamp: new Date().toISOString(),
+      };
+    },
+
+    async screenshot(udid) {
+      const { width, height } = FRAME_SIZES[find(udid).deviceClass];
+      return renderFakeFrame(udid, 0, width, height);
+    },
+
+    streamFrames(udid, fps, onFrame) {
+      const { width, height } = FRAME_SIZES[find(udid).deviceClass];
+      let frameNumber = 0;
+      const timer = setInterval(() => {
+        onFrame({ frame: renderFakeFrame(udid, frameNumber++, width, height), format: 'png' });
+      }, 1000 / Math.min(fps, MAX_FAKE_FPS));
+      return () => clearInterval(timer);
+    },
+  };
+}
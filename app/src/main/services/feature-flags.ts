@@ -0,0 +1,58 @@
+import { readSetting, writeSetting, clearSetting } from './settings';
+import type { FeatureFlag, FeatureFlagName } from '../../shared/ipc-types';
+
+/**
+ * Flags gating experimental subsystems, so they can ship disabled and be turned on per instance.
+ * A flag is off unless the config enables it; a value set through the API overrides both.
+ */
+
+const FLAGS: Record<FeatureFlagName, string> = {
+  webrtc_streaming: 'Stream simulators over WebRTC instead of WebSocket frames',
+  android: 'List and stream Android emulators next to simulators',
+  proxy: 'Route simulator traffic through the built-in HTTP proxy',
+};
+
+let configured: Partial<Record<FeatureFlagName, boolean>> = {};
+
+export function isFeatureFlagName(name: string): name is FeatureFlagName {
+  return Object.hasOwn(FLAGS, name);
+}
+
+/**
+ * Set the flags enabled or disabled by the config
+ */
+export function setFeatureFlagDefaults(flags: Partial<Record<FeatureFlagName, boolean>>): void {
+  configured = flags;
+}
+
+export async function getFeatureFlag(name: FeatureFlagName): Promise<FeatureFlag> {
+  const setting = await readSetting(`flags.${name}`);
+  const base = { name, description: FLAGS[name] };
+  if (setting) {
+    return { ...base, enabled: setting.value, source: 'setting', updatedAt: setting.updatedAt };
+  }
+  if (configured[name] !== undefined) {
+    return { ...base, enabled: configured[name]!, source: 'config', updatedAt: null };
+  }
+  return { ...base, enabled: false, source: 'default', updatedAt: null };
+}
+
+export function getFeatureFlags(): Promise<FeatureFlag[]> {
+  return Promise.all((Object.keys(FLAGS) as FeatureFlagName[]).map(getFeatureFlag));
+}
+
+export async function isFeatureEnabled(name: FeatureFlagName): Promise<boolean> {
+  return (await getFeatureFlag(name)).enabled;
+}
+
+/**
+ * Turn a flag on or off for this instance. Null clears the override, going back to the config.
+ */
+export async function setFeatureFlag(name: FeatureFlagName, enabled: boolean | null): Promise<FeatureFlag> {
+  if (enabled === null) {
+    await clearSetting(`flags.${name}`);
+  } else {
+    await writeSetting(`flags.${name}`, enabled);
+  }
+  return getFeatureFlag(name);
+}
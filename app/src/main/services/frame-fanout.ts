@@ -0,0 +1,120 @@
+import type { BackendFrame } from './simulator';
+
+/**
+ * One upstream frame source per simulator, shared by everyone watching it: WebSocket viewers,
+ * thumbnails and video streams. Before, each of them opened its own connection to
+ * simulator-server, so two browser tabs on a simulator decoded its screen twice. The upstream
+ * starts with the first viewer and stops when the last one leaves. A viewer that falls behind
+ * skips frames until it catches up, without slowing down the upstream for the others.
+ */
+
+/**
+ * Start the upstream at `fps`, calling `onFrame` with each frame until `signal` aborts, and `onEnd`
+ * if it ends on its own
+ */
+export type FrameUpstream = (fps: number, signal: AbortSignal, onFrame: (frame: BackendFrame) => void, onEnd: () => void) => void;
+
+export interface FrameViewer {
+  // Frame rate the viewer asked for; the upstream runs at the highest one
+  fps: number;
+  onFrame(frame: BackendFrame): void;
+  // Whether the viewer is still busy with earlier frames, so it skips this one
+  isLagging?(): boolean;
+  onSkip?(): void;
+  // The upstream ended, e.g. because the simulator shut down
+  onEnd?(): void;
+}
+
+export class FrameFanout {
+  private readonly viewers = new Map<string, FrameViewer>();
+  private upstream: AbortController | null = null;
+  private fps = 0;
+
+  /**
+   * `onIdle` is called once the last viewer left or the upstream ended
+   */
+  constructor(
+    private readonly startUpstream: FrameUpstream,
+    private readonly onIdle: () => void
+  ) {}
+
+  get size(): number {
+    return this.viewers.size;
+  }
+
+  has(id: string): boolean {
+    return this.viewers.has(id);
+  }
+
+  /**
+   * Add a viewer, or replace the one with the same `id`. The upstream restarts at a higher frame
+   * rate when the viewer asks for one.
+   */
+  add(id: string, viewer: FrameViewer): void {
+    this.viewers.set(id, viewer);
+    if (!this.upstream || viewer.fps > this.fps) {
+      this.restart(Math.max(this.fps, viewer.fps));
+    }
+  }
+
+  remove(id: string): void {
+    if (this.viewers.delete(id) && this.viewers.size === 0) {
+      this.stop();
+    }
+  }
+
+  /**
+   * Reconnect the upstream, e.g. after simulator-server restarted, keeping the viewers
+   */
+  restart(fps: number = this.fps): void {
+    this.upstream?.abort();
+    const upstream = new AbortController();
+    this.upstream = upstream;
+    this.fps = fps;
+    // Frames still arriving from a replaced upstream are dropped, and it ending isn't the stream ending
+    this.startUpstream(
+      fps,
+      upstream.signal,
+      (frame) => {
+        if (this.upstream === upstream) {
+          this.publish(frame);
+        }
+      },
+      () => {
+        if (this.upstream === upstream) {
+          this.end();
+        }
+      }
+    );
+  }
+
+  /**
+   * Stop the upstream and tell the viewers the stream ended
+   */
+  end(): void {
+    const viewers = Array.from(this.viewers.values());
+    this.viewers.clear();
+    this.stop();
+    viewers.forEach((viewer) => viewer.onEnd?.());
+  }
+
+  private stop(): void {
+    if (!this.upstream) {
+      return;
+    }
+    this.upstream.abort();
+    this.upstream = null;
+    this.fps = 0;
+    this.onIdle();
+  }
+
+  private publish(frame: BackendFrame): void {
+    for (const viewer of this.viewers.values()) {
+      if (viewer.isLagging?.()) {
+        viewer.onSkip?.();
+        continue;
+      }
+      viewer.onFrame(frame);
+    }
+  }
+}
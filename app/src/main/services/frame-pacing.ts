@@ -0,0 +1,95 @@
+import type { FramePacing } from '../../shared/ipc-types';
+
+/**
+ * Approximate frame pacing of the app on screen, derived from the stream: only frames whose
+ * content changed count, so the app's animation rate shows rather than the capture rate.
+ * The stream's fps caps what can be measured.
+ */
+
+// Gaps between changed frames longer than this mean the screen was idle, not hitching
+const IDLE_GAP_MS = 250;
+// A changed frame later than this many expected intervals is a hitch
+const HITCH_FACTOR = 1.5;
+
+interface PacingState {
+  targetFps: number;
+  since: number;
+  lastFrame: Buffer | null;
+  lastChangeAt: number | null;
+  changedFrames: number;
+  // Frames counted towards the animation rate, excluding the first of each animation
+  animatedFrames: number;
+  animatingMs: number;
+  hitches: number;
+  hitchMs: number;
+  worstIntervalMs: number;
+}
+
+const states = new Map<string, PacingState>();
+
+/**
+ * Start (or restart) measuring a simulator's stream, e.g. when it starts streaming at `targetFps`
+ */
+export function resetFramePacing(udid: string, targetFps: number = states.get(udid)?.targetFps ?? 60): void {
+  states.set(udid, {
+    targetFps,
+    since: Date.now(),
+    lastFrame: null,
+    lastChangeAt: null,
+    changedFrames: 0,
+    animatedFrames: 0,
+    animatingMs: 0,
+    hitches: 0,
+    hitchMs: 0,
+    worstIntervalMs: 0,
+  });
+}
+
+export function recordPacingFrame(udid: string, frame: Buffer, at: number = Date.now()): void {
+  const state = states.get(udid);
+  if (!state) {
+    return;
+  }
+  if (state.lastFrame && frame.equals(state.lastFrame)) {
+    return;
+  }
+  state.lastFrame = frame;
+  state.changedFrames++;
+
+  const interval = state.lastChangeAt === null ? null : at - state.lastChangeAt;
+  state.lastChangeAt = at;
+  if (interval === null || interval > IDLE_GAP_MS) {
+    return;
+  }
+
+  const expectedMs = 1000 / state.targetFps;
+  state.animatedFrames++;
+  state.animatingMs += interval;
+  state.worstIntervalMs = Math.max(state.worstIntervalMs, interval);
+  if (interval > expectedMs * HITCH_FACTOR) {
+    state.hitches++;
+    state.hitchMs += interval - expectedMs;
+  }
+}
+
+/**
+ * Pacing measured since the simulator's stream started or was last reset, or null if it never streamed
+ */
+export function getFramePacing(udid: string): FramePacing | null {
+  const state = states.get(udid);
+  if (!state) {
+    return null;
+  }
+  const animatingSeconds = state.animatingMs / 1000;
+  return {
+    udid,
+    since: new Date(state.since).toISOString(),
+    targetFps: state.targetFps,
+    changedFrames: state.changedFrames,
+    animatingMs: state.animatingMs,
+    fps: animatingSeconds > 0 ? Math.round((state.animatedFrames / animatingSeconds) * 10) / 10 : null,
+    hitches: state.hitches,
+    hitchTimeRatio: animatingSeconds > 0 ? Math.round((state.hitchMs / animatingSeconds) * 10) / 10 : null,
+    worstIntervalMs: state.worstIntervalMs,
+  };
+}
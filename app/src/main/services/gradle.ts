@@ -0,0 +1,161 @@
+import { spawn, ChildProcess } from 'child_process';
+import { EventEmitter } from 'events';
+import { randomUUID } from 'crypto';
+import * as fs from 'fs';
+import * as path from 'path';
+import { detectProject } from './projects';
+import { registerProcess } from './process-manager';
+import { createJobDir } from './workspace';
+import { recordUsage } from './analytics';
+import { getBuildLogPath, storeBuildLog, BuildEventInput } from './xcode';
+import { EVENT_SCHEMA_VERSION } from '../../shared/ipc-types';
+import type { BuildEvent, BuildProduct } from '../../shared/ipc-types';
+
+/**
+ * Builds of Android projects with the project's Gradle wrapper. They send the same events as
+ * Xcode builds and keep their log the same way, but aren't in the build history. The APKs
+ * under the modules' `build/outputs/apk` are the build's launchable products.
+ */
+
+export const DEFAULT_GRADLE_TASK = 'assembleDebug';
+
+// Task names, optionally qualified by project path, e.g. `assembleDebug` or `:app:assembleDebug`
+const TASK_PATTERN = /^(:?[A-Za-z][\w-]*)+$/;
+// Directories not searched for APKs
+const SKIPPED_DIRS = new Set(['node_modules', 'src']);
+
+export interface GradleBuild extends EventEmitter {
+  // Stop Gradle, ending the build with an error event with `cancelled: true`
+  cancel(): void;
+}
+
+export function isGradleTask(task: unknown): task is string {
+  return typeof task === 'string' && TASK_PATTERN.test(task);
+}
+
+/**
+ * The APKs Gradle has built in an Android project, across its modules
+ */
+export async function findApks(projectDir: string): Promise<BuildProduct[]> {
+  const apks: BuildProduct[] = [];
+  const visit = async (dir: string, depth: number) => {
+    let entries: fs.Dirent[];
+    try {
+      entries = await fs.promises.readdir(dir, { withFileTypes: true });
+    } catch {
+      return;
+    }
+    for (const entry of entries) {
+      const entryPath = path.join(dir, entry.name);
+      if (entry.isFile() && entry.name.endsWith('.apk') && entryPath.includes(`${path.sep}build${path.sep}outputs${path.sep}apk${path.sep}`)) {
+        apks.push({ name: entry.name, path: entryPath });
+      } else if (entry.isDirectory() && !entry.name.startsWith('.') && !SKIPPED_DIRS.has(entry.name) && depth < 8) {
+        await visit(entryPath, depth + 1);
+      }
+    }
+  };
+  await visit(projectDir, 0);
+  return apks.sort((a, b) => a.path.localeCompare(b.path));
+}
+
+/**
+ * Run a Gradle task (by default `assembleDebug`) in an Android project, streaming its output
+ */
+export function buildGradleStream(projectPath: string, task: string = DEFAULT_GRADLE_TASK): GradleBuild {
+  const emitter = new EventEmitter() as GradleBuild;
+  const buildId = randomUUID();
+  const startedAt = Date.now();
+  const log = fs.createWriteStream(getBuildLogPath(buildId));
+
+  const emitEvent = (event: BuildEventInput) => {
+    if (event.type === 'output' && event.line) {
+      log.write(`${event.line}\n`);
+    } else if (event.type === 'error' && event.message) {
+      log.write(`error: ${event.message}\n`);
+    }
+    const stamped: BuildEvent = { ...event, buildId, event: `build.${event.type}`, schemaVersion: EVENT_SCHEMA_VERSION };
+    emitter.emit('event', stamped);
+  };
+  emitter.once('end', () => log.end(() => storeBuildLog(buildId)));
+
+  const jobDir = createJobDir('build', buildId);
+  emitter.once('end', () => jobDir.cleanup());
+
+  let buildProcess: ChildProcess | null = null;
+  let cancelled = false;
+  emitter.cancel = () => {
+    cancelled = true;
+    buildProcess?.kill('SIGINT');
+  };
+
+  const fail = (message: string) => {
+    emitEvent({ type: 'error', message });
+    emitter.emit('end');
+  };
+
+  // Started on the next tick, so callers can listen for the first events
+  process.nextTick(() => {
+    const project = detectProject(projectPath);
+    if (!project || project.type !== 'android') {
+      fail('Not an Android project');
+      return;
+    }
+    if (!isGradleTask(task)) {
+      fail(`Invalid Gradle task: ${task}`);
+      return;
+    }
+    // The detected path is the build file; the wrapper is next to it
+    const projectDir = fs.statSync(project.path).isDirectory() ? project.path : path.dirname(project.path);
+    const gradlew = path.join(projectDir, process.platform === 'win32' ? 'gradlew.bat' : 'gradlew');
+    if (!fs.existsSync(gradlew)) {
+      fail(`No Gradle wrapper in ${projectDir}, run \`gradle wrapper\` there first`);
+      return;
+    }
+    if (cancelled) {
+      fail('Build cancelled');
+      return;
+    }
+
+    emitEvent({ type: 'started', scheme: task, projectPath });
+
+    const proc = spawn(gradlew, [task, '--console=plain'], {
+      cwd: projectDir,
+      env: { ...process.env, TMPDIR: jobDir.path },
+    });
+    registerProcess(proc, 'gradle');
+    buildProcess = proc;
+
+    const onData = (data: Buffer) => {
+      for (const line of data.toString().split('\n')) {
+        if (line.trim()) {
+          emitEvent({ type: 'output', line });
+        }
+      }
+    };
+    proc.stdout.on('data', onData);
+    proc.stderr.on('data', onData);
+
+    proc.on('close', async (code) => {
+      if (cancelled) {
+        emitEvent({ type: 'error', message: 'Build cancelled', cancelled: true });
+        emitter.emit('end');
+        return;
+      }
+      const success = code === 0;
+      recordUsage('build', Date.now() - startedAt);
+      emitEvent({
+        type: 'completed',
+        success,
+        buildDir: projectDir,
+        products: success ? await findApks(projectDir) : [],
+        durationMs: Date.now() - startedAt,
+        message: success ? undefined : `Gradle exited with code ${code}`,
+      });
+      emitter.emit('end');
+    });
+
+    proc.on('error', (err) => fail(err.message));
+  });
+
+  return emitter;
+}
@@ -0,0 +1,72 @@
+import * as os from 'os';
+import type { HostResources, ResourcesExhaustedResponse } from '../../shared/ipc-types';
+
+/**
+ * Guards against starting heavy work (booting simulators, builds) on a host that is already
+ * short on memory or CPU, so streams that are running keep their latency.
+ */
+
+export interface ResourceGuardSettings {
+  // Refuse when free memory is below this many megabytes (0 disables)
+  minFreeMemoryMb: number;
+  // Refuse when the 1-minute load average is above this percentage of the CPU count (0 disables)
+  maxLoadPercent: number;
+}
+
+let guards: ResourceGuardSettings = { minFreeMemoryMb: 0, maxLoadPercent: 0 };
+let readResources: () => HostResources = readHostResources;
+
+export function setResourceGuards(settings: ResourceGuardSettings): void {
+  guards = settings;
+}
+
+/**
+ * Replace how host readings are taken, e.g. to simulate a loaded host in tests. Null restores the real readings.
+ */
+export function setHostResourceReader(reader: (() => HostResources) | null): void {
+  readResources = reader || readHostResources;
+}
+
+export function readHostResources(): HostResources {
+  const cpuCount = os.cpus().length || 1;
+  const loadAverage = os.loadavg()[0];
+  return {
+    freeMemoryBytes: os.freemem(),
+    totalMemoryBytes: os.totalmem(),
+    loadAverage,
+    cpuCount,
+    loadPercent: Math.round((loadAverage / cpuCount) * 100),
+  };
+}
+
+/**
+ * Null when the host has room for `action`, otherwise why not, with the current readings
+ */
+export function checkHostResources(action: ResourcesExhaustedResponse['action']): ResourcesExhaustedResponse | null {
+  if (!guards.minFreeMemoryMb && !guards.maxLoadPercent) {
+    return null;
+  }
+
+  const readings = readResources();
+  const reasons: string[] = [];
+  const freeMemoryMb = Math.round(readings.freeMemoryBytes / (1024 * 1024));
+  if (guards.minFreeMemoryMb && freeMemoryMb < guards.minFreeMemoryMb) {
+    reasons.push(`Free memory is ${freeMemoryMb} MB, below the ${guards.minFreeMemoryMb} MB minimum`);
+  }
+  if (guards.maxLoadPercent && readings.loadPercent > guards.maxLoadPercent) {
+    reasons.push(`CPU load is ${readings.loadPercent}%, above the ${guards.maxLoadPercent}% maximum`);
+  }
+  if (reasons.length === 0) {
+    return null;
+  }
+
+  console.warn(`[guardrails] Refusing to ${action}: ${reasons.join('; ')}`);
+  return {
+    error: 'Host resources exhausted',
+    code: 'resources_exhausted',
+    action,
+    reasons,
+    readings,
+    limits: { ...guards },
+  };
+}
@@ -0,0 +1,159 @@
+import { execFileSync } from 'child_process';
+import * as fs from 'fs';
+import * as path from 'path';
+
+/**
+ * Lookup of the helper binaries Plasma runs: simulator-server, AXe and, for video streams, ffmpeg.
+ * Each is looked for in a list of places, in an order that depends on how Plasma was installed. A
+ * development checkout finds them in the source tree; a package such as a Homebrew formula installs
+ * them under `libexec/plasma/` next to the `bin/` it installs Plasma into, and shouldn't look
+ * anywhere else.
+ */
+
+export type HelperName = 'simulator-server' | 'axe' | 'ffmpeg';
+
+// - env:     the helper's environment variable ($SIMULATOR_SERVER, $AXE_BINARY, $FFMPEG_BINARY)
+// - libexec: `libexec/plasma/` of the install prefix, or $PLASMA_LIBEXEC_DIR
+// - dev:     build outputs and checked-in binaries of a source checkout
+// - bundled: resources of the packaged Electron app
+// - path:    $PATH
+export type HelperSource = 'env' | 'libexec' | 'dev' | 'bundled' | 'path';
+
+export const HELPER_SOURCES: HelperSource[] = ['env', 'libexec', 'dev', 'bundled', 'path'];
+
+// - dev:     a source checkout or the Electron app, where helpers can be in the tree or bundled
+// - libexec: an installed package, where helpers are only in libexec (or overridden explicitly)
+export type HelperLayout = 'dev' | 'libexec';
+
+export const DEFAULT_HELPER_ORDER: Record<HelperLayout, HelperSource[]> = {
+  dev: ['env', 'dev', 'bundled', 'libexec', 'path'],
+  libexec: ['env', 'libexec', 'path'],
+};
+
+export interface HelperLookupSettings {
+  layout: HelperLayout;
+  // Places to look, in order
+  order: HelperSource[];
+  // Directory with the helpers, instead of `libexec/plasma` of the install prefix
+  libexecDir: string | null;
+}
+
+interface HelperSpec {
+  envVar: string;
+  devPaths: () => string[];
+  bundledPath: string[];
+}
+
+const HELPERS: Record<HelperName, HelperSpec> = {
+  'simulator-server': {
+    envVar: 'SIMULATOR_SERVER',
+    devPaths: () => [
+      path.join(__dirname, '../../../../bin/simulator-server'),
+      path.join(__dirname, '../../../bin/simulator-server'),
+      path.join(process.cwd(), 'bin/simulator-server'),
+      path.join(process.cwd(), '../swift/.build/release/simulator-server'),
+      path.join(process.cwd(), '../swift/.build/debug/simulator-server'),
+    ],
+    bundledPath: ['bin', 'simulator-server'],
+  },
+  axe: {
+    envVar: 'AXE_BINARY',
+    devPaths: () => [
+      path.join(__dirname, '../../../../binaries/axe'),
+      path.join(__dirname, '../../../binaries/axe'),
+      path.join(process.cwd(), 'binaries/axe'),
+    ],
+    bundledPath: ['binaries', 'axe'],
+  },
+  ffmpeg: {
+    envVar: 'FFMPEG_BINARY',
+    devPaths: () => [
+      path.join(__dirname, '../../../../binaries/ffmpeg'),
+      path.join(__dirname, '../../../binaries/ffmpeg'),
+      path.join(process.cwd(), 'binaries/ffmpeg'),
+    ],
+    bundledPath: ['binaries', 'ffmpeg'],
+  },
+};
+
+let settings: HelperLookupSettings = { layout: 'dev', order: DEFAULT_HELPER_ORDER.dev, libexecDir: null };
+
+export function configureHelperLookup(next: HelperLookupSettings): void {
+  settings = next;
+}
+
+/**
+ * Directories that may hold `libexec/plasma`: the install prefixes of the script that was run
+ * (e.g. /opt/homebrew/bin/plasma, through its symlink) and of the executable running it
+ */
+function libexecDirs(lookup: HelperLookupSettings): string[] {
+  if (lookup.libexecDir) {
+    return [lookup.libexecDir];
+  }
+  const entries = [process.argv[1], process.execPath].filter(Boolean);
+  const dirs = entries.map((entry) => {
+    let resolved = entry;
+    try {
+      resolved = fs.realpathSync(entry);
+    } catch {
+      // Keep the path as given
+    }
+    return path.resolve(path.dirname(resolved), '..', 'libexec', 'plasma');
+  });
+  return Array.from(new Set(dirs));
+}
+
+function findOnPath(name: string): string | null {
+  try {
+    const result = execFileSync('which', [name], { encoding: 'utf-8', stdio: ['ignore', 'pipe', 'ignore'] }).trim();
+    return result && fs.existsSync(result) ? result : null;
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Candidate paths from one source, in the order they're checked
+ */
+function candidates(name: HelperName, source: HelperSource, lookup: HelperLookupSettings): string[] {
+  const spec = HELPERS[name];
+  switch (source) {
+    case 'env':
+      return process.env[spec.envVar] ? [process.env[spec.envVar]!] : [];
+    case 'libexec':
+      return libexecDirs(lookup).map((dir) => path.join(dir, name));
+    case 'dev':
+      return spec.devPaths();
+    case 'bundled':
+      return process.resourcesPath ? [path.join(process.resourcesPath, ...spec.bundledPath)] : [];
+    case 'path': {
+      const found = findOnPath(name);
+      return found ? [found] : [];
+    }
+  }
+}
+
+/**
+ * Find a helper binary, looking in the configured places (or those of `lookup`) in order
+ */
+export function findHelperBinary(
+  name: HelperName,
+  lookup: HelperLookupSettings = settings
+): { path: string; source: HelperSource } | null {
+  for (const source of lookup.order) {
+    const found = candidates(name, source, lookup).find((candidate) => fs.existsSync(candidate));
+    if (found) {
+      return { path: found, source };
+    }
+  }
+  return null;
+}
+
+/**
+ * Places a helper is looked for, for error messages and the configuration checks
+ */
+export function describeHelperLookup(name: HelperName, lookup: HelperLookupSettings = settings): string {
+  return lookup.order
+    .map((source) => (source === 'env' ? `$${HELPERS[name].envVar}` : source === 'libexec' ? libexecDirs(lookup).join(', ') : source))
+    .join(', ');
+}
@@ -0,0 +1,195 @@
+import type * as http from 'http';
+import { createHash } from 'crypto';
+
+/**
+ * Deduplication of retried requests through the `Idempotency-Key` header. The first POST, PUT or
+ * DELETE with a key runs as usual and its response is kept for the window; requests with the same
+ * key get that response again instead of running the operation twice. A retry that arrives while
+ * the first request is still running waits for it. Server errors aren't kept, so those can be
+ * retried for real.
+ *
+ * Keys belong to the client that sent them, so two clients using the same key don't get each
+ * other's responses, and a key reused with another body is rejected rather than replayed.
+ */
+
+export const IDEMPOTENCY_HEADER = 'idempotency-key';
+export const REPLAYED_HEADER = 'Idempotent-Replayed';
+
+const MUTATING_METHODS = ['POST', 'PUT', 'PATCH', 'DELETE'];
+const MAX_KEY_LENGTH = 255;
+
+interface StoredResponse {
+  status: number;
+  headers: http.OutgoingHttpHeaders;
+  body: Buffer;
+}
+
+interface Entry {
+  // Method and path the key was first used for
+  scope: string;
+  // SHA-256 of the body the key was first used with
+  bodyHash: string;
+  expiresAt: number;
+  // Null when the first request failed or was aborted
+  response: Promise<StoredResponse | null>;
+}
+
+let windowMs = 10 * 60 * 1000;
+// By client and key
+const entries = new Map<string, Entry>();
+// Bodies read to compare them with the first request's, for the route handler to parse
+const bufferedBodies = new WeakMap<http.IncomingMessage, string>();
+
+/**
+ * How long responses are kept for retries, 0 to ignore idempotency keys
+ */
+export function setIdempotencyWindow(ms: number): void {
+  windowMs = ms;
+  entries.clear();
+}
+
+function pruneExpired(now: number): void {
+  for (const [key, entry] of entries) {
+    if (entry.expiresAt <= now) {
+      entries.delete(key);
+    }
+  }
+}
+
+/**
+ * Who sent a request. There are no accounts, so an `Authorization` header (e.g. added by a proxy in
+ * front of Plasma) identifies the client, and without one its address and user agent do.
+ */
+function clientOf(req: http.IncomingMessage): string {
+  const authorization = req.headers.authorization;
+  if (authorization) {
+    return `auth:${createHash('sha256').update(authorization).digest('base64url')}`;
+  }
+  return `${req.socket.remoteAddress || ''} ${req.headers['user-agent'] || ''}`;
+}
+
+function readRequestBody(req: http.IncomingMessage): Promise<string> {
+  return new Promise((resolve, reject) => {
+    let data = '';
+    req.on('data', (chunk) => (data += chunk));
+    req.on('end', () => resolve(data));
+    req.on('error', reject);
+  });
+}
+
+/**
+ * The body of a request whose idempotency key was checked, which has been read from the stream
+ * already. Undefined for other requests.
+ */
+export function takeBufferedBody(req: http.IncomingMessage): string | undefined {
+  const body = bufferedBodies.get(req);
+  bufferedBodies.delete(req);
+  return body;
+}
+
+function sendUnprocessable(res: http.ServerResponse, error: string): void {
+  res.writeHead(422, { 'Content-Type': 'application/json' });
+  res.end(JSON.stringify({ error }));
+}
+
+/**
+ * Keep the response `res` sends, resolving once it's finished
+ */
+function captureResponse(res: http.ServerResponse): Promise<StoredResponse | null> {
+  return new Promise((resolve) => {
+    const chunks: Buffer[] = [];
+    let headHeaders: http.OutgoingHttpHeaders = {};
+    const capture = (chunk: unknown) => {
+      if (typeof chunk === 'string') {
+        chunks.push(Buffer.from(chunk));
+      } else if (chunk instanceof Uint8Array) {
+        chunks.push(Buffer.from(chunk.buffer, chunk.byteOffset, chunk.byteLength));
+      }
+    };
+
+    const writeHead = res.writeHead.bind(res) as (...args: unknown[]) => http.ServerResponse;
+    res.writeHead = ((statusCode: number, ...args: unknown[]) => {
+      const headers = args.find((arg) => arg && typeof arg === 'object' && !Array.isArray(arg));
+      if (headers) {
+        headHeaders = headers as http.OutgoingHttpHeaders;
+      }
+      return writeHead(statusCode, ...args);
+    }) as typeof res.writeHead;
+    const write = res.write.bind(res) as (...args: unknown[]) => boolean;
+    const end = res.end.bind(res) as (...args: unknown[]) => http.ServerResponse;
+    res.write = ((chunk: unknown, ...args: unknown[]) => {
+      capture(chunk);
+      return write(chunk, ...args);
+    }) as typeof res.write;
+    res.end = ((chunk?: unknown, ...args: unknown[]) => {
+      capture(chunk);
+      return end(chunk, ...args);
+    }) as typeof res.end;
+
+    res.once('close', () => {
+      if (!res.writableFinished || res.statusCode >= 500) {
+        resolve(null);
+        return;
+      }
+      // The body is kept uncompressed; replays are compressed for the retrying client again
+      const headers = { ...res.getHeaders(), ...headHeaders };
+      delete headers['content-encoding'];
+      resolve({ status: res.statusCode, headers, body: Buffer.concat(chunks) });
+    });
+  });
+}
+
+/**
+ * Answer a request whose idempotency key was seen before. Returns true if the request was
+ * answered, false if it should be handled as usual.
+ */
+export async function handleIdempotencyKey(req: http.IncomingMessage, res: http.ServerResponse, path: string): Promise<boolean> {
+  const key = req.headers[IDEMPOTENCY_HEADER];
+  if (typeof key !== 'string' || !MUTATING_METHODS.includes(req.method || '') || windowMs === 0) {
+    return false;
+  }
+  if (!key || key.length > MAX_KEY_LENGTH) {
+    res.writeHead(400, { 'Content-Type': 'application/json' });
+    res.end(JSON.stringify({ error: `Idempotency-Key must be 1 to ${MAX_KEY_LENGTH} characters` }));
+    return true;
+  }
+
+  const body = await readRequestBody(req);
+  bufferedBodies.set(req, body);
+  const bodyHash = createHash('sha256').update(body).digest('base64url');
+  const scope = `${req.method} ${path}`;
+  const entryKey = `${clientOf(req)}\n${key}`;
+
+  pruneExpired(Date.now());
+  let entry = entries.get(entryKey);
+  while (entry) {
+    if (entry.scope !== scope) {
+      sendUnprocessable(res, `This Idempotency-Key was used for ${entry.scope}`);
+      return true;
+    }
+    if (entry.bodyHash !== bodyHash) {
+      sendUnprocessable(res, 'This Idempotency-Key was used with a different request body');
+      return true;
+    }
+    const stored = await entry.response;
+    if (stored) {
+      res.writeHead(stored.status, { ...stored.headers, [REPLAYED_HEADER]: 'true' });
+      res.end(stored.body);
+      return true;
+    }
+    // The request failed. When several retries waited for it, the first to get here runs the
+    // operation again and the others wait for that one.
+    const current = entries.get(entryKey);
+    entry = current === entry ? undefined : current;
+  }
+
+  // A first request, or a retry of one that failed
+  const response = captureResponse(res);
+  entries.set(entryKey, { scope, bodyHash, expiresAt: Date.now() + windowMs, response });
+  response.then((result) => {
+    if (!result && entries.get(entryKey)?.response === response) {
+      entries.delete(entryKey);
+    }
+  });
+  return false;
+}
@@ -0,0 +1,267 @@
+import * as crypto from 'crypto';
+import { getDataStore, ProjectKey, ProjectSettingsChanges, StoredProjectSettings } from './data-store';
+import { isSettingKey, writeStoredSetting } from './settings';
+import { createProjectNotifier, getNotifierWebhookUrl, listProjectNotifiers, validateNotifierInput } from './notifiers';
+import type { NotifierInput } from './notifiers';
+import { isDestinationPreset } from './xcode';
+import { decrypt, deriveKey, encrypt } from './encryption';
+import { validate, ValidationError } from '../validation';
+import { PROJECT_SETTINGS_REQUEST } from '../request-schemas';
+import type {
+  ExportedNotifier,
+  ExportedProject,
+  ExportedProjectSettings,
+  ImportInstanceResponse,
+  InstanceExport,
+} from '../../shared/ipc-types';
+
+/**
+ * Export and import of an instance's setup (projects, their destinations, settings and notifiers,
+ * and the backend settings) as a JSON bundle, to set up another machine the same way.
+ *
+ * Webhook URLs and projects' environment variables can carry credentials, so they are left out
+ * unless the export is given a passphrase, in which case they are encrypted with a key derived from it.
+ */
+
+export const EXPORT_FORMAT = 'plasma-export';
+export const EXPORT_VERSION = 1;
+
+// Settings that describe this machine rather than the setup, e.g. analytics consent or the
+// salt its secrets are encrypted with
+const MACHINE_SETTING_PREFIXES = ['analytics.', 'secrets.'];
+
+export class ImportError extends Error {}
+
+function encryptSecret(value: string, passphrase: string): string {
+  const salt = crypto.randomBytes(16);
+  return `v1:${salt.toString('base64')}:${encrypt(value, deriveKey(passphrase, salt))}`;
+}
+
+function decryptSecret(value: string, passphrase: string): string {
+  const [version, salt, ...encrypted] = value.split(':');
+  if (version !== 'v1' || !salt) {
+    throw new ImportError('Unsupported encrypted value');
+  }
+  try {
+    return decrypt(encrypted.join(':'), deriveKey(passphrase, Buffer.from(salt, 'base64')));
+  } catch {
+    throw new ImportError('Could not decrypt the bundle\'s secrets, check the passphrase');
+  }
+}
+
+async function exportNotifiers(project: ProjectKey, passphrase: string | null): Promise<ExportedNotifier[]> {
+  return Promise.all((await listProjectNotifiers(project)).map(async (notifier) => {
+    const webhookUrl = passphrase ? await getNotifierWebhookUrl(notifier) : null;
+    return {
+      kind: notifier.kind,
+      webhookUrl: null,
+      encryptedWebhookUrl: passphrase && webhookUrl ? encryptSecret(webhookUrl, passphrase) : null,
+      events: notifier.events,
+      template: notifier.template,
+      enabled: notifier.enabled,
+    };
+  }));
+}
+
+function exportProjectSettings(stored: StoredProjectSettings | null, passphrase: string | null): ExportedProjectSettings | null {
+  if (!stored) {
+    return null;
+  }
+  const hasEnvironment = Object.keys(stored.environment).length > 0;
+  return {
+    scheme: stored.scheme,
+    configuration: stored.configuration,
+    simulatorUdid: stored.simulatorUdid,
+    environment: null,
+    encryptedEnvironment: passphrase && hasEnvironment ? encryptSecret(JSON.stringify(stored.environment), passphrase) : null,
+  };
+}
+
+/**
+ * Serialize the instance's setup. Without a passphrase, webhook URLs and environment variables are left out.
+ */
+export async function exportInstance(passphrase: string | null): Promise<InstanceExport> {
+  const store = getDataStore();
+  const projects = await store.projects.all();
+  const settings = await store.settings.list();
+
+  return {
+    format: EXPORT_FORMAT,
+    version: EXPORT_VERSION,
+    exportedAt: new Date().toISOString(),
+    projects: await Promise.all(projects.map(async (project) => ({
+      name: project.name,
+      xcodePath: project.xcode_path,
+      androidPath: project.android_path,
+      destination: (await store.projectDestinations.get(project))?.preset || null,
+      settings: exportProjectSettings(await store.projectSettings.get(project), passphrase),
+      notifiers: await exportNotifiers(project, passphrase),
+    }))),
+    settings: settings
+      .filter((setting) => !MACHINE_SETTING_PREFIXES.some((prefix) => setting.key.startsWith(prefix)))
+      .map(({ key, value }) => ({ key, value })),
+  };
+}
+
+// A project of the bundle and what's written for it, once the whole bundle has been read
+interface PlannedProject {
+  name: string;
+  xcodePath: string | null;
+  androidPath: string | null;
+  destination?: string;
+  settings?: ProjectSettingsChanges;
+  notifiers: NotifierInput[];
+}
+
+function importProjectSettings(
+  project: ExportedProject,
+  passphrase: string | null,
+  result: ImportInstanceResponse
+): ProjectSettingsChanges | undefined {
+  const settings = project.settings;
+  if (!settings) {
+    return undefined;
+  }
+  const item = `settings of ${project.name}`;
+  let environment = settings.environment || undefined;
+  if (!environment && settings.encryptedEnvironment) {
+    if (!passphrase) {
+      result.skipped.push({ item: `environment variables of ${project.name}`, reason: 'they are encrypted and no passphrase was given' });
+    } else {
+      environment = JSON.parse(decryptSecret(settings.encryptedEnvironment, passphrase));
+    }
+  }
+  try {
+    return validate(
+      { scheme: settings.scheme, configuration: settings.configuration, simulatorUdid: settings.simulatorUdid, environment },
+      PROJECT_SETTINGS_REQUEST
+    );
+  } catch (err) {
+    if (!(err instanceof ValidationError)) {
+      throw err;
+    }
+    result.skipped.push({ item, reason: err.message });
+    return undefined;
+  }
+}
+
+function planProject(
+  project: ExportedProject,
+  passphrase: string | null,
+  result: ImportInstanceResponse
+): PlannedProject | null {
+  if (typeof project.name !== 'string' || (!project.xcodePath && !project.androidPath)) {
+    result.skipped.push({ item: `project ${JSON.stringify(project.name)}`, reason: 'needs a name and a project path' });
+    return null;
+  }
+
+  const planned: PlannedProject = {
+    name: project.name,
+    xcodePath: project.xcodePath || null,
+    androidPath: project.androidPath || null,
+    notifiers: [],
+  };
+  if (isDestinationPreset(project.destination)) {
+    planned.destination = project.destination;
+  } else if (project.destination) {
+    result.skipped.push({ item: `destination of ${project.name}`, reason: `unknown preset "${project.destination}"` });
+  }
+  planned.settings = importProjectSettings(project, passphrase, result);
+
+  for (const notifier of project.notifiers || []) {
+    const item = `${notifier.kind} notifier of ${project.name}`;
+    const encrypted = notifier.encryptedWebhookUrl;
+    if (!notifier.webhookUrl && !encrypted) {
+      result.skipped.push({ item, reason: 'exported without its webhook URL' });
+      continue;
+    }
+    if (!notifier.webhookUrl && !passphrase) {
+      result.skipped.push({ item, reason: 'webhook URL is encrypted and no passphrase was given' });
+      continue;
+    }
+    const webhookUrl = notifier.webhookUrl || decryptSecret(encrypted!, passphrase!);
+    const input = { kind: notifier.kind, webhookUrl, events: notifier.events, template: notifier.template, enabled: notifier.enabled };
+    const invalid = validateNotifierInput(input);
+    if (invalid) {
+      result.skipped.push({ item, reason: invalid });
+      continue;
+    }
+    planned.notifiers.push(input);
+  }
+  return planned;
+}
+
+async function importNotifiers(project: ProjectKey, notifiers: NotifierInput[], result: ImportInstanceResponse): Promise<void> {
+  if (notifiers.length === 0) {
+    return;
+  }
+  const existing = await Promise.all((await listProjectNotifiers(project)).map(async (notifier) => ({
+    kind: notifier.kind,
+    webhookUrl: await getNotifierWebhookUrl(notifier),
+  })));
+  for (const input of notifiers) {
+    // Importing the same bundle twice doesn't duplicate notifiers
+    if (existing.some((candidate) => candidate.kind === input.kind && candidate.webhookUrl === input.webhookUrl)) {
+      continue;
+    }
+    await createProjectNotifier(project, input);
+    existing.push({ kind: input.kind, webhookUrl: input.webhookUrl });
+    result.notifiers++;
+  }
+}
+
+/**
+ * Merge an exported bundle into this instance: projects are matched by path, settings are
+ * overwritten, and notifiers are added unless the project already has them.
+ */
+export async function importInstance(bundle: InstanceExport, passphrase: string | null): Promise<ImportInstanceResponse> {
+  if (bundle?.format !== EXPORT_FORMAT) {
+    throw new ImportError(`Not a Plasma export (format must be "${EXPORT_FORMAT}")`);
+  }
+  if (bundle.version !== EXPORT_VERSION) {
+    throw new ImportError(`Unsupported export version ${bundle.version}, expected ${EXPORT_VERSION}`);
+  }
+  if (!Array.isArray(bundle.projects) || !Array.isArray(bundle.settings)) {
+    throw new ImportError('projects and settings must be arrays');
+  }
+
+  const result: ImportInstanceResponse = { projects: 0, notifiers: 0, settings: 0, skipped: [] };
+  // The whole bundle is read and decrypted before anything is written, so e.g. a webhook URL that
+  // can't be decrypted halfway through leaves the instance as it was
+  const planned = bundle.projects
+    .map((project) => planProject(project, passphrase, result))
+    .filter((project): project is PlannedProject => project !== null);
+
+  const store = getDataStore();
+  for (const plan of planned) {
+    const project = await store.projects.save(plan.name, plan.xcodePath, plan.androidPath);
+    result.projects++;
+    if (plan.destination) {
+      await store.projectDestinations.set(project, plan.destination);
+    }
+    if (plan.settings) {
+      await store.projectSettings.update(project, plan.settings);
+    }
+    await importNotifiers(project, plan.notifiers, result);
+  }
+
+  for (const setting of bundle.settings) {
+    if (typeof setting?.key !== 'string' || typeof setting.value !== 'string') {
+      result.skipped.push({ item: `setting ${JSON.stringify(setting?.key)}`, reason: 'key and value must be strings' });
+      continue;
+    }
+    if (MACHINE_SETTING_PREFIXES.some((prefix) => setting.key.startsWith(prefix))) {
+      continue;
+    }
+    if (!isSettingKey(setting.key)) {
+      result.skipped.push({ item: `setting ${setting.key}`, reason: 'unknown setting' });
+      continue;
+    }
+    if (!(await writeStoredSetting(setting.key, setting.value))) {
+      result.skipped.push({ item: `setting ${setting.key}`, reason: `invalid value ${JSON.stringify(setting.value)}` });
+      continue;
+    }
+    result.settings++;
+  }
+  return result;
+}
@@ -0,0 +1,80 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { getDataDir } from './database';
+import type { AboutResponse } from '../../shared/ipc-types';
+
+/**
+ * Information about this running instance, shared with wrapper apps through
+ * `/api/about`, a line on stdout and a discovery file
+ */
+
+const startedAt = new Date().toISOString();
+let discoveryFile: string | null = null;
+
+export function readVersion(): string | null {
+  try {
+    // app/package.json, from either src/main/services or dist/main/services
+    const pkg = JSON.parse(fs.readFileSync(path.join(__dirname, '../../../package.json'), 'utf-8'));
+    return pkg.version || null;
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Default discovery file location, in the data directory
+ */
+export function defaultDiscoveryFile(): string {
+  return path.join(getDataDir(), 'instance.json');
+}
+
+export function getInstance(port: number, publicUrl: string): AboutResponse {
+  return {
+    version: readVersion(),
+    pid: process.pid,
+    port,
+    url: `http://localhost:${port}`,
+    publicUrl,
+    startedAt,
+  };
+}
+
+/**
+ * Print the port the server ended up listening on in machine-readable form and write
+ * the discovery file. The file is removed when the process exits.
+ */
+export function announceInstance(port: number, publicUrl: string, file: string): AboutResponse {
+  const instance = getInstance(port, publicUrl);
+
+  // One JSON line, bypassing the log filter, for wrapper apps that spawn the server
+  process.stdout.write(`PLASMA_LISTENING ${JSON.stringify(instance)}\n`);
+
+  try {
+    fs.mkdirSync(path.dirname(file), { recursive: true });
+    fs.writeFileSync(file, JSON.stringify(instance, null, 2));
+    discoveryFile = file;
+    process.once('exit', removeDiscoveryFile);
+  } catch (err) {
+    console.error(`[instance] Failed to write discovery file ${file}:`, err);
+  }
+
+  return instance;
+}
+
+/**
+ * Remove the discovery file, unless another instance has replaced it since
+ */
+function removeDiscoveryFile(): void {
+  if (!discoveryFile) {
+    return;
+  }
+  try {
+    const current = JSON.parse(fs.readFileSync(discoveryFile, 'utf-8'));
+    if (current.pid === process.pid) {
+      fs.rmSync(discoveryFile, { force: true });
+    }
+  } catch {
+    // Already gone or unreadable
+  }
+  discoveryFile = null;
+}
@@ -0,0 +1,812 @@
+import type { DecodedPng } from './png';
+
+/**
+ * Minimal baseline JPEG codec for stream frames. Frames are first read into the quantized DCT
+ * coefficients of their 8x8 blocks. Decoding runs the inverse DCT on only the low-frequency
+ * coefficients when a frame is shrunk, at 1/8, 1/4 or 1/2 size, so thumbnails cost little more
+ * than reading the entropy-coded data. Drawing on a frame only transforms the blocks it
+ * touches and writes the others back as they were, so the rest of the frame loses nothing.
+ * Progressive and arithmetic-coded JPEGs aren't supported.
+ */
+
+// Position in an 8x8 block (row * 8 + column) of each coefficient, in the order they're stored
+const ZIGZAG = [
+  0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20, 13, 6, 7, 14, 21,
+  28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59, 52, 45, 38, 31, 39, 46, 53, 60, 61,
+  54, 47, 55, 62, 63,
+];
+
+interface HuffmanTable {
+  // Symbol by code length (upper 16 bits) and code
+  symbols: Map<number, number>;
+  maxCode: number[];
+}
+
+interface Component {
+  id: number;
+  h: number;
+  v: number;
+  quantTable: number;
+  dcTable: number;
+  acTable: number;
+  // Block columns and rows allocated, covering whole MCUs
+  blocksPerLine: number;
+  blocksPerColumn: number;
+  // Quantized coefficients, 64 per block in zigzag order, blocks row by row
+  blocks: Int16Array;
+  dcPredictor: number;
+}
+
+interface JpegBlocks {
+  width: number;
+  height: number;
+  // Largest sampling factors, which make up an MCU
+  maxH: number;
+  maxV: number;
+  mcusPerLine: number;
+  mcusPerColumn: number;
+  // Quantization tables by ID, in zigzag order
+  quantTables: Int32Array[];
+  components: Component[];
+}
+
+/**
+ * A rectangle of a frame, in pixels; `right` and `bottom` are exclusive
+ */
+export interface JpegArea {
+  left: number;
+  top: number;
+  right: number;
+  bottom: number;
+}
+
+function buildHuffmanTable(counts: Buffer, symbols: Buffer): HuffmanTable {
+  const table: HuffmanTable = { symbols: new Map(), maxCode: new Array(17).fill(-1) };
+  let code = 0;
+  let k = 0;
+  for (let length = 1; length <= 16; length++) {
+    for (let i = 0; i < counts[length - 1]; i++) {
+      table.symbols.set((length << 16) | code, symbols[k++]);
+      code++;
+    }
+    table.maxCode[length] = code - 1;
+    code <<= 1;
+  }
+  return table;
+}
+
+class BitReader {
+  private offset: number;
+  private bits = 0;
+  private bitCount = 0;
+
+  constructor(private data: Buffer, offset: number) {
+    this.offset = offset;
+  }
+
+  get position(): number {
+    return this.offset;
+  }
+
+  readBit(): number {
+    if (this.bitCount === 0) {
+      let byte = this.data[this.offset];
+      if (byte === undefined) {
+        throw new Error('Unexpected end of JPEG data');
+      }
+      if (byte === 0xff) {
+        const next = this.data[this.offset + 1];
+        if (next === 0x00) {
+          this.offset++;
+        } else {
+          // A marker inside entropy-coded data; pad with ones like libjpeg
+          byte = 0xff;
+          this.offset--;
+        }
+      }
+      this.offset++;
+      this.bits = byte;
+      this.bitCount = 8;
+    }
+    this.bitCount--;
+    return (this.bits >> this.bitCount) & 1;
+  }
+
+  receive(length: number): number {
+    let value = 0;
+    for (let i = 0; i < length; i++) {
+      value = (value << 1) | this.readBit();
+    }
+    return value;
+  }
+
+  // A `length`-bit value, negative when its top bit is clear
+  receiveExtend(length: number): number {
+    if (length === 0) {
+      return 0;
+    }
+    const value = this.receive(length);
+    return value < 1 << (length - 1) ? value - (1 << length) + 1 : value;
+  }
+
+  decode(table: HuffmanTable): number {
+    let code = 0;
+    for (let length = 1; length <= 16; length++) {
+      code = (code << 1) | this.readBit();
+      if (code <= table.maxCode[length]) {
+        const symbol = table.symbols.get((length << 16) | code);
+        if (symbol !== undefined) {
+          return symbol;
+        }
+      }
+    }
+    throw new Error('Invalid Huffman code in JPEG data');
+  }
+
+  // Skip to the byte after a restart marker
+  restart(): void {
+    this.bitCount = 0;
+    while (this.offset < this.data.length - 1) {
+      if (this.data[this.offset] === 0xff && this.data[this.offset + 1] >= 0xd0 && this.data[this.offset + 1] <= 0xd7) {
+        this.offset += 2;
+        return;
+      }
+      this.offset++;
+    }
+  }
+}
+
+/**
+ * Cosines of the reduced inverse DCT: `size` outputs from the first `size` coefficients
+ */
+function idctBasis(size: number): Float64Array {
+  const basis = new Float64Array(size * size);
+  for (let x = 0; x < size; x++) {
+    for (let u = 0; u < size; u++) {
+      basis[x * size + u] = (u === 0 ? Math.SQRT1_2 : 1) * Math.cos(((2 * x + 1) * u * Math.PI) / (2 * size));
+    }
+  }
+  return basis;
+}
+
+/**
+ * Inverse DCT of the top-left `size` x `size` coefficients of a block into `size` x `size` samples
+ */
+function idctBlock(
+  coefficients: Float64Array,
+  size: number,
+  basis: Float64Array,
+  out: Uint8ClampedArray,
+  outOffset: number,
+  outStride: number
+): void {
+  for (let y = 0; y < size; y++) {
+    for (let x = 0; x < size; x++) {
+      let sum = 0;
+      for (let v = 0; v < size; v++) {
+        const cy = basis[y * size + v];
+        for (let u = 0; u < size; u++) {
+          sum += cy * basis[x * size + u] * coefficients[v * 8 + u];
+        }
+      }
+      out[outOffset + y * outStride + x] = sum / 4 + 128;
+    }
+  }
+}
+
+
+/**
+ * Read a baseline JPEG into the quantized coefficients of its blocks
+ */
+function readBlocks(jpeg: Buffer): JpegBlocks {
+  if (jpeg[0] !== 0xff || jpeg[1] !== 0xd8) {
+    throw new Error('Not a JPEG');
+  }
+
+  const quantTables: Int32Array[] = [];
+  const dcTables: HuffmanTable[] = [];
+  const acTables: HuffmanTable[] = [];
+  let components: Component[] = [];
+  let width = 0;
+  let height = 0;
+  let maxH = 1;
+  let maxV = 1;
+  let mcusPerLine = 0;
+  let mcusPerColumn = 0;
+  let restartInterval = 0;
+
+  let offset = 2;
+  while (offset < jpeg.length) {
+    if (jpeg[offset] !== 0xff) {
+      offset++;
+      continue;
+    }
+    const marker = jpeg[offset + 1];
+    offset += 2;
+    if (marker === 0xd8 || (marker >= 0xd0 && marker <= 0xd7) || marker === 0xff) {
+      continue;
+    }
+    if (marker === 0xd9) {
+      break;
+    }
+    const length = jpeg.readUInt16BE(offset);
+    const segment = jpeg.subarray(offset + 2, offset + length);
+
+    switch (marker) {
+      case 0xdb: {
+        // Quantization tables, 8 or 16 bits per value
+        for (let i = 0; i < segment.length; ) {
+          const precision = segment[i] >> 4;
+          const id = segment[i] & 15;
+          const table = new Int32Array(64);
+          for (let k = 0; k < 64; k++) {
+            table[k] = precision ? segment.readUInt16BE(i + 1 + k * 2) : segment[i + 1 + k];
+          }
+          quantTables[id] = table;
+          i += 1 + 64 * (precision ? 2 : 1);
+        }
+        break;
+      }
+      case 0xc4: {
+        for (let i = 0; i < segment.length; ) {
+          const tableClass = segment[i] >> 4;
+          const id = segment[i] & 15;
+          const counts = segment.subarray(i + 1, i + 17);
+          const total = counts.reduce((sum, count) => sum + count, 0);
+          const table = buildHuffmanTable(counts, segment.subarray(i + 17, i + 17 + total));
+          (tableClass === 0 ? dcTables : acTables)[id] = table;
+          i += 17 + total;
+        }
+        break;
+      }
+      case 0xc0:
+      case 0xc1: {
+        if (segment[0] !== 8) {
+          throw new Error(`Unsupported JPEG precision: ${segment[0]} bits`);
+        }
+        height = segment.readUInt16BE(1);
+        width = segment.readUInt16BE(3);
+        components = [];
+        for (let i = 0; i < segment[5]; i++) {
+          const base = 6 + i * 3;
+          components.push({
+            id: segment[base],
+            h: segment[base + 1] >> 4,
+            v: segment[base + 1] & 15,
+            quantTable: segment[base + 2],
+            dcTable: 0,
+            acTable: 0,
+            blocksPerLine: 0,
+            blocksPerColumn: 0,
+            blocks: new Int16Array(0),
+            dcPredictor: 0,
+          });
+        }
+        maxH = Math.max(...components.map((component) => component.h));
+        maxV = Math.max(...components.map((component) => component.v));
+        mcusPerLine = Math.ceil(width / (8 * maxH));
+        mcusPerColumn = Math.ceil(height / (8 * maxV));
+        for (const component of components) {
+          component.blocksPerLine = mcusPerLine * component.h;
+          component.blocksPerColumn = mcusPerColumn * component.v;
+          component.blocks = new Int16Array(component.blocksPerLine * component.blocksPerColumn * 64);
+        }
+        break;
+      }
+      case 0xdd:
+        restartInterval = segment.readUInt16BE(0);
+        break;
+      case 0xda: {
+        const scanComponents = Array.from({ length: segment[0] }, (_, i) => {
+          const component = components.find((candidate) => candidate.id === segment[1 + i * 2]);
+          if (!component) {
+            throw new Error('JPEG scan refers to an unknown component');
+          }
+          component.dcTable = segment[2 + i * 2] >> 4;
+          component.acTable = segment[2 + i * 2] & 15;
+          component.dcPredictor = 0;
+          return component;
+        });
+        offset = decodeScan(jpeg, offset + length, scanComponents);
+        continue;
+      }
+      default:
+        // Other frame types; application data, comments and the like are skipped
+        if (marker >= 0xc2 && marker <= 0xcf && marker !== 0xc4 && marker !== 0xc8 && marker !== 0xcc) {
+          throw new Error('Only baseline JPEGs are supported');
+        }
+    }
+    offset += length;
+  }
+
+  function decodeBlock(reader: BitReader, component: Component, blockRow: number, blockColumn: number): void {
+    const block = component.blocks.subarray((blockRow * component.blocksPerLine + blockColumn) * 64);
+    component.dcPredictor += reader.receiveExtend(reader.decode(dcTables[component.dcTable]));
+    block[0] = component.dcPredictor;
+    const acTable = acTables[component.acTable];
+    for (let k = 1; k < 64; ) {
+      const symbol = reader.decode(acTable);
+      const run = symbol >> 4;
+      const bits = symbol & 15;
+      if (bits === 0) {
+        if (run !== 15) {
+          break;
+        }
+        k += 16;
+        continue;
+      }
+      k += run;
+      const value = reader.receiveExtend(bits);
+      if (k < 64) {
+        block[k] = value;
+      }
+      k++;
+    }
+  }
+
+  function decodeScan(data: Buffer, start: number, scanComponents: Component[]): number {
+    const reader = new BitReader(data, start);
+    const single = scanComponents.length === 1;
+    const unitsPerLine = single ? singleComponentBlocksPerLine(scanComponents[0]) : mcusPerLine;
+    const unitsPerColumn = single ? singleComponentBlocksPerColumn(scanComponents[0]) : mcusPerColumn;
+    const total = unitsPerLine * unitsPerColumn;
+
+    for (let unit = 0; unit < total; unit++) {
+      if (restartInterval && unit > 0 && unit % restartInterval === 0) {
+        reader.restart();
+        for (const component of scanComponents) {
+          component.dcPredictor = 0;
+        }
+      }
+      const row = Math.floor(unit / unitsPerLine);
+      const column = unit % unitsPerLine;
+      if (single) {
+        decodeBlock(reader, scanComponents[0], row, column);
+        continue;
+      }
+      for (const component of scanComponents) {
+        for (let v = 0; v < component.v; v++) {
+          for (let h = 0; h < component.h; h++) {
+            decodeBlock(reader, component, row * component.v + v, column * component.h + h);
+          }
+        }
+      }
+    }
+
+    // Continue from the next marker after the entropy-coded data
+    let offset = reader.position;
+    const isMarker = (at: number) => data[at] === 0xff && data[at + 1] !== 0 && (data[at + 1] < 0xd0 || data[at + 1] > 0xd7);
+    while (offset < data.length - 1 && !isMarker(offset)) {
+      offset++;
+    }
+    return offset;
+  }
+
+  // A scan with one component isn't interleaved: its blocks go row by row, covering only the image
+  function singleComponentBlocksPerLine(component: Component): number {
+    return Math.ceil(Math.ceil((width * component.h) / maxH) / 8);
+  }
+
+  function singleComponentBlocksPerColumn(component: Component): number {
+    return Math.ceil(Math.ceil((height * component.v) / maxV) / 8);
+  }
+
+  if (!width || !height || components.length === 0) {
+    throw new Error('JPEG has no image data');
+  }
+  for (const component of components) {
+    if (!quantTables[component.quantTable]) {
+      throw new Error('JPEG refers to an unknown quantization table');
+    }
+  }
+  return { width, height, maxH, maxV, mcusPerLine, mcusPerColumn, quantTables, components };
+}
+
+/**
+ * Dimensions of a JPEG from its frame header, without decoding it
+ */
+export function jpegSize(jpeg: Buffer): { width: number; height: number } | null {
+  let offset = 2;
+  while (offset + 9 <= jpeg.length && jpeg[offset] === 0xff) {
+    const marker = jpeg[offset + 1];
+    if (marker >= 0xc0 && marker <= 0xcf && marker !== 0xc4 && marker !== 0xc8 && marker !== 0xcc) {
+      return { width: jpeg.readUInt16BE(offset + 7), height: jpeg.readUInt16BE(offset + 5) };
+    }
+    offset += 2 + jpeg.readUInt16BE(offset + 2);
+  }
+  return null;
+}
+
+/**
+ * Decode a baseline JPEG at `size`/8 of its dimensions (`size` is 1, 2, 4 or 8) into RGB
+ */
+export function decodeJpeg(jpeg: Buffer, size: 1 | 2 | 4 | 8 = 8): DecodedPng {
+  const { width, height, maxH, maxV, quantTables, components } = readBlocks(jpeg);
+  const basis = idctBasis(size);
+  const coefficients = new Float64Array(64);
+
+  // Each component at the reduced size, `blocksPerLine * size` samples per row
+  const samples = components.map((component) => {
+    const quant = quantTables[component.quantTable];
+    const stride = component.blocksPerLine * size;
+    const out = new Uint8ClampedArray(stride * component.blocksPerColumn * size);
+    for (let blockRow = 0; blockRow < component.blocksPerColumn; blockRow++) {
+      for (let blockColumn = 0; blockColumn < component.blocksPerLine; blockColumn++) {
+        const block = (blockRow * component.blocksPerLine + blockColumn) * 64;
+        coefficients.fill(0);
+        for (let k = 0; k < 64; k++) {
+          const position = ZIGZAG[k];
+          if ((position & 7) < size && position >> 3 < size) {
+            coefficients[position] = component.blocks[block + k] * quant[k];
+          }
+        }
+        // The reduced IDCT scales like a full one, so each output sample averages 8/size pixels
+        idctBlock(coefficients, size, basis, out, blockRow * size * stride + blockColumn * size, stride);
+      }
+    }
+    return out;
+  });
+
+  // Components at their reduced size, upsampled and converted to RGB
+  const outWidth = Math.ceil((width * size) / 8);
+  const outHeight = Math.ceil((height * size) / 8);
+  const pixels = Buffer.alloc(outWidth * outHeight * 3);
+  const sample = (index: number, x: number, y: number) =>
+    samples[index][
+      Math.floor((y * components[index].v) / maxV) * components[index].blocksPerLine * size +
+        Math.floor((x * components[index].h) / maxH)
+    ];
+  for (let y = 0; y < outHeight; y++) {
+    for (let x = 0; x < outWidth; x++) {
+      const out = (y * outWidth + x) * 3;
+      const luma = sample(0, x, y);
+      if (components.length < 3) {
+        pixels[out] = pixels[out + 1] = pixels[out + 2] = luma;
+        continue;
+      }
+      const cb = sample(1, x, y) - 128;
+      const cr = sample(2, x, y) - 128;
+      pixels[out] = clamp(luma + 1.402 * cr);
+      pixels[out + 1] = clamp(luma - 0.344136 * cb - 0.714136 * cr);
+      pixels[out + 2] = clamp(luma + 1.772 * cb);
+    }
+  }
+  return { width: outWidth, height: outHeight, channels: 3, pixels };
+}
+
+function clamp(value: number): number {
+  return value < 0 ? 0 : value > 255 ? 255 : Math.round(value);
+}
+
+interface HuffmanSpec {
+  // Number of codes of each length, 1 to 16 bits
+  counts: number[];
+  symbols: Buffer;
+}
+
+// Huffman tables of the JPEG spec (Annex K), which can code any baseline block
+const LUMA_DC: HuffmanSpec = {
+  counts: [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0],
+  symbols: Buffer.from('000102030405060708090a0b', 'hex'),
+};
+const CHROMA_DC: HuffmanSpec = {
+  counts: [0, 3, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0],
+  symbols: Buffer.from('000102030405060708090a0b', 'hex'),
+};
+const LUMA_AC: HuffmanSpec = {
+  counts: [0, 2, 1, 3, 3, 2, 4, 3, 5, 5, 4, 4, 0, 0, 1, 0x7d],
+  symbols: Buffer.from(
+    '01020300041105122131410613516107227114328191a1082342b1c11552d1f02433627282090a161718191a25262728292a3435363738' +
+      '393a434445464748494a535455565758595a636465666768696a737475767778797a838485868788898a92939495969798999aa2a3a4a5' +
+      'a6a7a8a9aab2b3b4b5b6b7b8b9bac2c3c4c5c6c7c8c9cad2d3d4d5d6d7d8d9dae1e2e3e4e5e6e7e8e9eaf1f2f3f4f5f6f7f8f9fa',
+    'hex'
+  ),
+};
+const CHROMA_AC: HuffmanSpec = {
+  counts: [0, 2, 1, 2, 4, 4, 3, 4, 7, 5, 4, 4, 0, 1, 2, 0x77],
+  symbols: Buffer.from(
+    '000102031104052131061241510761711322328108144291a1b1c109233352f0156272d10a162434e125f11718191a262728292a353637' +
+      '38393a434445464748494a535455565758595a636465666768696a737475767778797a82838485868788898a92939495969798999aa2a3' +
+      'a4a5a6a7a8a9aab2b3b4b5b6b7b8b9bac2c3c4c5c6c7c8c9cad2d3d4d5d6d7d8d9dae2e3e4e5e6e7e8e9eaf2f3f4f5f6f7f8f9fa',
+    'hex'
+  ),
+};
+
+// Forward DCT cosines, already scaled: coefficient u of 8 samples is the sum of sample x * FDCT_BASIS[u * 8 + x]
+const FDCT_BASIS = (() => {
+  const basis = new Float64Array(64);
+  for (let u = 0; u < 8; u++) {
+    for (let x = 0; x < 8; x++) {
+      basis[u * 8 + x] = ((u === 0 ? Math.SQRT1_2 : 1) / 2) * Math.cos(((2 * x + 1) * u * Math.PI) / 16);
+    }
+  }
+  return basis;
+})();
+
+/**
+ * Forward DCT of an 8x8 block of samples (0 to 255), quantized into `out` in zigzag order
+ */
+function fdctBlock(samples: ArrayLike<number>, quant: Int32Array, out: Int16Array): void {
+  // Rows, then columns
+  const rows = new Float64Array(64);
+  for (let y = 0; y < 8; y++) {
+    for (let u = 0; u < 8; u++) {
+      let sum = 0;
+      for (let x = 0; x < 8; x++) {
+        sum += (samples[y * 8 + x] - 128) * FDCT_BASIS[u * 8 + x];
+      }
+      rows[y * 8 + u] = sum;
+    }
+  }
+  for (let k = 0; k < 64; k++) {
+    const u = ZIGZAG[k] & 7;
+    const v = ZIGZAG[k] >> 3;
+    let sum = 0;
+    for (let y = 0; y < 8; y++) {
+      sum += rows[y * 8 + u] * FDCT_BASIS[v * 8 + y];
+    }
+    out[k] = Math.round(sum / quant[k]);
+  }
+}
+
+/**
+ * Code of each symbol of a Huffman table, with its length in the upper 16 bits
+ */
+function huffmanCodes(spec: HuffmanSpec): Int32Array {
+  const codes = new Int32Array(256);
+  let code = 0;
+  let k = 0;
+  for (let length = 1; length <= 16; length++) {
+    for (let i = 0; i < spec.counts[length - 1]; i++) {
+      codes[spec.symbols[k++]] = (length << 16) | code++;
+    }
+    code <<= 1;
+  }
+  return codes;
+}
+
+class BitWriter {
+  private bytes = Buffer.alloc(64 * 1024);
+  private length = 0;
+  // Bits not yet written out, at most 7 between writes
+  private bits = 0;
+  private bitCount = 0;
+
+  // `length` (at most 16) low bits of `value`
+  write(value: number, length: number): void {
+    this.bits = (this.bits << length) | (value & ((1 << length) - 1));
+    this.bitCount += length;
+    while (this.bitCount >= 8) {
+      this.bitCount -= 8;
+      const byte = (this.bits >> this.bitCount) & 0xff;
+      this.push(byte);
+      // Stuff a zero after 0xff so it isn't read as a marker
+      if (byte === 0xff) {
+        this.push(0);
+      }
+    }
+    this.bits &= (1 << this.bitCount) - 1;
+  }
+
+  // A code from `huffmanCodes`
+  writeCode(code: number): void {
+    this.write(code & 0xffff, code >> 16);
+  }
+
+  // A `length`-bit value, with negative values stored one's complement style
+  writeValue(value: number, length: number): void {
+    this.write(value < 0 ? value + (1 << length) - 1 : value, length);
+  }
+
+  // Pad the last byte with ones
+  finish(): Buffer {
+    if (this.bitCount > 0) {
+      this.write((1 << (8 - this.bitCount)) - 1, 8 - this.bitCount);
+    }
+    return this.bytes.subarray(0, this.length);
+  }
+
+  private push(byte: number): void {
+    if (this.length === this.bytes.length) {
+      const grown = Buffer.alloc(this.bytes.length * 2);
+      this.bytes.copy(grown);
+      this.bytes = grown;
+    }
+    this.bytes[this.length++] = byte;
+  }
+}
+
+function bitLength(value: number): number {
+  return 32 - Math.clz32(Math.abs(value));
+}
+
+/**
+ * Entropy code the block at `offset`, returning its DC coefficient to predict the next one from
+ */
+function encodeBlock(
+  writer: BitWriter,
+  blocks: Int16Array,
+  offset: number,
+  predictor: number,
+  dcCodes: Int32Array,
+  acCodes: Int32Array
+): number {
+  const diff = blocks[offset] - predictor;
+  const dcLength = bitLength(diff);
+  writer.writeCode(dcCodes[dcLength]);
+  writer.writeValue(diff, dcLength);
+
+  let run = 0;
+  for (let k = 1; k < 64; k++) {
+    const value = blocks[offset + k];
+    if (value === 0) {
+      run++;
+      continue;
+    }
+    while (run > 15) {
+      writer.writeCode(acCodes[0xf0]);
+      run -= 16;
+    }
+    const length = bitLength(value);
+    writer.writeCode(acCodes[(run << 4) | length]);
+    writer.writeValue(value, length);
+    run = 0;
+  }
+  if (run > 0) {
+    writer.writeCode(acCodes[0x00]);
+  }
+  return blocks[offset];
+}
+
+function segment(marker: number, data: Buffer): Buffer {
+  const header = Buffer.from([0xff, marker, 0, 0]);
+  header.writeUInt16BE(data.length + 2, 2);
+  return Buffer.concat([header, data]);
+}
+
+/**
+ * Write blocks back out as a baseline JPEG, with the spec's Huffman tables: the luma ones for
+ * the first component and the chroma ones for the others
+ */
+function writeBlocks(image: JpegBlocks): Buffer {
+  const { components } = image;
+  const codes = [
+    { dc: huffmanCodes(LUMA_DC), ac: huffmanCodes(LUMA_AC) },
+    { dc: huffmanCodes(CHROMA_DC), ac: huffmanCodes(CHROMA_AC) },
+  ];
+
+  const writer = new BitWriter();
+  const predictors = components.map(() => 0);
+  const writeBlock = (index: number, blockRow: number, blockColumn: number) => {
+    const component = components[index];
+    const offset = (blockRow * component.blocksPerLine + blockColumn) * 64;
+    const { dc, ac } = codes[index === 0 ? 0 : 1];
+    predictors[index] = encodeBlock(writer, component.blocks, offset, predictors[index], dc, ac);
+  };
+
+  if (components.length === 1) {
+    // Not interleaved, see singleComponentBlocksPerLine
+    const component = components[0];
+    const blocksPerLine = Math.ceil(Math.ceil((image.width * component.h) / image.maxH) / 8);
+    const blocksPerColumn = Math.ceil(Math.ceil((image.height * component.v) / image.maxV) / 8);
+    for (let row = 0; row < blocksPerColumn; row++) {
+      for (let column = 0; column < blocksPerLine; column++) {
+        writeBlock(0, row, column);
+      }
+    }
+  } else {
+    for (let row = 0; row < image.mcusPerColumn; row++) {
+      for (let column = 0; column < image.mcusPerLine; column++) {
+        components.forEach((component, index) => {
+          for (let v = 0; v < component.v; v++) {
+            for (let h = 0; h < component.h; h++) {
+              writeBlock(index, row * component.v + v, column * component.h + h);
+            }
+          }
+        });
+      }
+    }
+  }
+
+  const quantTables = Array.from(new Set(components.map((component) => component.quantTable))).map((id) => {
+    const table = image.quantTables[id];
+    const wide = table.some((value) => value > 255);
+    const values = Buffer.alloc(64 * (wide ? 2 : 1));
+    table.forEach((value, k) => (wide ? values.writeUInt16BE(value, k * 2) : values.writeUInt8(value, k)));
+    return segment(0xdb, Buffer.concat([Buffer.from([((wide ? 1 : 0) << 4) | id]), values]));
+  });
+  const frameHeader = Buffer.alloc(6 + components.length * 3);
+  frameHeader[0] = 8;
+  frameHeader.writeUInt16BE(image.height, 1);
+  frameHeader.writeUInt16BE(image.width, 3);
+  frameHeader[5] = components.length;
+  components.forEach((component, index) => {
+    frameHeader.set([component.id, (component.h << 4) | component.v, component.quantTable], 6 + index * 3);
+  });
+  const huffmanTable = (tableClass: number, id: number, spec: HuffmanSpec) =>
+    segment(0xc4, Buffer.concat([Buffer.from([(tableClass << 4) | id, ...spec.counts]), spec.symbols]));
+  const scanHeader = Buffer.from([
+    components.length,
+    ...components.flatMap((component, index) => [component.id, index === 0 ? 0x00 : 0x11]),
+    0,
+    63,
+    0,
+  ]);
+
+  return Buffer.concat([
+    Buffer.from([0xff, 0xd8]),
+    ...quantTables,
+    segment(0xc0, frameHeader),
+    huffmanTable(0, 0, LUMA_DC),
+    huffmanTable(1, 0, LUMA_AC),
+    ...(components.length > 1 ? [huffmanTable(0, 1, CHROMA_DC), huffmanTable(1, 1, CHROMA_AC)] : []),
+    segment(0xda, scanHeader),
+    writer.finish(),
+    Buffer.from([0xff, 0xd9]),
+  ]);
+}
+
+/**
+ * Y, Cb and Cr of an RGB color, as stored in JPEGs
+ */
+export function rgbToYCbCr(r: number, g: number, b: number): [number, number, number] {
+  return [
+    0.299 * r + 0.587 * g + 0.114 * b,
+    -0.168736 * r - 0.331264 * g + 0.5 * b + 128,
+    0.5 * r - 0.418688 * g - 0.081312 * b + 128,
+  ];
+}
+
+/**
+ * Draw on a JPEG without decoding all of it: only the blocks overlapping `areas` are
+ * transformed, and `shade` gives each of their samples a new value. It's called with the
+ * component (0 for Y, 1 for Cb, 2 for Cr), the position of the sample's center in pixels, and
+ * its current value. The frame keeps its quantization tables, so blocks elsewhere are
+ * written back unchanged.
+ */
+export function drawOnJpeg(
+  jpeg: Buffer,
+  areas: JpegArea[],
+  shade: (component: number, x: number, y: number, value: number) => number
+): Buffer {
+  const image = readBlocks(jpeg);
+  const basis = idctBasis(8);
+  const coefficients = new Float64Array(64);
+  const samples = new Uint8ClampedArray(64);
+
+  image.components.forEach((component, index) => {
+    const quant = image.quantTables[component.quantTable];
+    // Pixels per sample in each direction
+    const stepX = image.maxH / component.h;
+    const stepY = image.maxV / component.v;
+    const touched = new Set<number>();
+    for (const area of areas) {
+      const firstColumn = Math.max(0, Math.floor(area.left / (8 * stepX)));
+      const lastColumn = Math.min(component.blocksPerLine - 1, Math.floor((area.right - 1) / (8 * stepX)));
+      const firstRow = Math.max(0, Math.floor(area.top / (8 * stepY)));
+      const lastRow = Math.min(component.blocksPerColumn - 1, Math.floor((area.bottom - 1) / (8 * stepY)));
+      for (let row = firstRow; row <= lastRow; row++) {
+        for (let column = firstColumn; column <= lastColumn; column++) {
+          touched.add(row * component.blocksPerLine + column);
+        }
+      }
+    }
+
+    for (const blockIndex of touched) {
+      const block = component.blocks.subarray(blockIndex * 64, blockIndex * 64 + 64);
+      for (let k = 0; k < 64; k++) {
+        coefficients[ZIGZAG[k]] = block[k] * quant[k];
+      }
+      idctBlock(coefficients, 8, basis, samples, 0, 8);
+      const left = (blockIndex % component.blocksPerLine) * 8;
+      const top = Math.floor(blockIndex / component.blocksPerLine) * 8;
+      for (let y = 0; y < 8; y++) {
+        for (let x = 0; x < 8; x++) {
+          samples[y * 8 + x] = shade(index, (left + x + 0.5) * stepX, (top + y + 0.5) * stepY, samples[y * 8 + x]);
+        }
+      }
+      fdctBlock(samples, quant, block);
+    }
+  });
+  return writeBlocks(image);
+}
@@ -0,0 +1,61 @@
+import { spawn, ChildProcess } from 'child_process';
+import { registerProcess } from './process-manager';
+
+/**
+ * Keep the host Mac awake while something needs it, e.g. a stream of a long demo session.
+ * Holds a power assertion through `caffeinate` while at least one holder is active, and
+ * releases it with the last one. `-w` ties caffeinate to this process, so the assertion
+ * also goes away if the server dies without releasing it.
+ */
+
+const holders = new Set<string>();
+let caffeinate: ChildProcess | null = null;
+
+function startAssertion(): void {
+  if (caffeinate || process.platform !== 'darwin') {
+    return;
+  }
+  // -i: no idle sleep, -s: no system sleep on AC power
+  const proc = spawn('caffeinate', ['-i', '-s', '-w', String(process.pid)], { stdio: 'ignore' });
+  registerProcess(proc, 'caffeinate');
+  proc.on('error', (err) => console.error('[keep-awake] Failed to start caffeinate:', err));
+  proc.on('exit', () => {
+    if (caffeinate === proc) {
+      caffeinate = null;
+    }
+  });
+  caffeinate = proc;
+  console.log('[keep-awake] Preventing host sleep');
+}
+
+function stopAssertion(): void {
+  if (!caffeinate) {
+    return;
+  }
+  caffeinate.kill();
+  caffeinate = null;
+  console.log('[keep-awake] Allowing host sleep');
+}
+
+/**
+ * Keep the host awake until `releaseKeepAwake` is called with the same key, e.g. `stream:<udid>`.
+ * Holding a key twice is the same as holding it once.
+ */
+export function holdKeepAwake(key: string): void {
+  holders.add(key);
+  startAssertion();
+}
+
+export function releaseKeepAwake(key: string): void {
+  holders.delete(key);
+  if (holders.size === 0) {
+    stopAssertion();
+  }
+}
+
+/**
+ * Keys currently keeping the host awake
+ */
+export function getKeepAwakeHolders(): string[] {
+  return Array.from(holders);
+}
@@ -0,0 +1,85 @@
+import { getDatabase } from './database';
+import type { LaunchTimings, LaunchTimingStats } from '../../shared/ipc-types';
+
+export type LaunchSpan = Exclude<keyof LaunchTimings, 'total'>;
+
+// First frames arriving later than this after a launch are not attributed to it
+const FIRST_FRAME_WINDOW_MS = 5 * 60 * 1000;
+
+/**
+ * Collects named timing spans for a single pipeline run
+ */
+export class SpanTimer {
+  private readonly startedAt = Date.now();
+  private readonly spans: Partial<Record<LaunchSpan, number>> = {};
+
+  async measure<T>(span: LaunchSpan, fn: () => Promise<T>): Promise<T> {
+    const start = Date.now();
+    try {
+      return await fn();
+    } finally {
+      this.spans[span] = Date.now() - start;
+    }
+  }
+
+  finish(): LaunchTimings {
+    return { ...this.spans, total: Date.now() - this.startedAt };
+  }
+}
+
+/**
+ * Persist the timings of a launch
+ */
+export function recordLaunchTimings(udid: string, deviceType: string | null, timings: LaunchTimings): void {
+  const db = getDatabase();
+  db.prepare(`
+    INSERT INTO launch_timings (udid, device_type, boot_ms, install_ms, launch_ms, total_ms, created_at)
+    VALUES (?, ?, ?, ?, ?, ?, ?)
+  `).run(
+    udid,
+    deviceType || 'unknown',
+    timings.boot ?? null,
+    timings.install ?? null,
+    timings.launch ?? null,
+    timings.total,
+    new Date().toISOString()
+  );
+}
+
+/**
+ * Attach the time until the first streamed frame to the latest launch on a simulator
+ */
+export function recordFirstFrame(udid: string, firstFrameMs: number): void {
+  const db = getDatabase();
+  const since = new Date(Date.now() - FIRST_FRAME_WINDOW_MS).toISOString();
+  db.prepare(`
+    UPDATE launch_timings SET first_frame_ms = ?
+    WHERE id = (
+      SELECT id FROM launch_timings
+      WHERE udid = ? AND first_frame_ms IS NULL AND created_at >= ?
+      ORDER BY id DESC LIMIT 1
+    )
+  `).run(firstFrameMs, udid, since);
+}
+
+/**
+ * Aggregate launch timings per device type
+ */
+export function getLaunchTimingStats(): LaunchTimingStats[] {
+  const db = getDatabase();
+  return db.prepare(`
+    SELECT
+      device_type,
+      COUNT(*) AS launches,
+      ROUND(AVG(boot_ms)) AS avg_boot_ms,
+      ROUND(AVG(install_ms)) AS avg_install_ms,
+      ROUND(AVG(launch_ms)) AS avg_launch_ms,
+      ROUND(AVG(first_frame_ms)) AS avg_first_frame_ms,
+      ROUND(AVG(total_ms)) AS avg_total_ms,
+      MAX(total_ms) AS max_total_ms,
+      MAX(created_at) AS last_launch_at
+    FROM launch_timings
+    GROUP BY device_type
+    ORDER BY device_type
+  `).all() as LaunchTimingStats[];
+}
@@ -0,0 +1,74 @@
+import { runSimctl } from './simulator';
+import type { LocationWaypoint } from '../../shared/ipc-types';
+
+/**
+ * Simulated locations, through `simctl location`: a fixed coordinate, or a route the simulator
+ * moves along in the background at a given speed, like the Simulator app's City Run or Freeway
+ * Drive. Routes come from GPX files and keep running until another location is set or cleared.
+ */
+
+// simctl's default, about 72 km/h
+export const DEFAULT_ROUTE_SPEED = 20;
+export const MAX_ROUTE_SPEED = 300;
+// Waypoints are passed as arguments, which have a size limit
+export const MAX_ROUTE_WAYPOINTS = 2000;
+
+export function isCoordinate(latitude: unknown, longitude: unknown): boolean {
+  return (
+    typeof latitude === 'number' && latitude >= -90 && latitude <= 90 &&
+    typeof longitude === 'number' && longitude >= -180 && longitude <= 180
+  );
+}
+
+/**
+ * Waypoints of a GPX file: its track points, or its route points or waypoints without a track
+ */
+export function parseGpx(gpx: string): LocationWaypoint[] {
+  for (const element of ['trkpt', 'rtept', 'wpt']) {
+    const points = Array.from(gpx.matchAll(new RegExp(`<${element}\\b([^>]*)>`, 'g')), (match) => {
+      const attribute = (name: string) => Number(new RegExp(`\\b${name}\\s*=\\s*["']([^"']*)["']`).exec(match[1])?.[1] ?? NaN);
+      return { latitude: attribute('lat'), longitude: attribute('lon') };
+    });
+    if (points.length > 0) {
+      return points.filter((point) => isCoordinate(point.latitude, point.longitude));
+    }
+  }
+  return [];
+}
+
+/**
+ * Length of a route in meters, along the great circles between its waypoints
+ */
+export function routeDistance(waypoints: LocationWaypoint[]): number {
+  const EARTH_RADIUS_METERS = 6_371_000;
+  const radians = (degrees: number) => (degrees * Math.PI) / 180;
+  let distance = 0;
+  for (let i = 1; i < waypoints.length; i++) {
+    const [a, b] = [waypoints[i - 1], waypoints[i]];
+    const h =
+      Math.sin(radians(b.latitude - a.latitude) / 2) ** 2 +
+      Math.cos(radians(a.latitude)) * Math.cos(radians(b.latitude)) * Math.sin(radians(b.longitude - a.longitude) / 2) ** 2;
+    distance += 2 * EARTH_RADIUS_METERS * Math.asin(Math.sqrt(h));
+  }
+  return distance;
+}
+
+const formatWaypoint = (waypoint: LocationWaypoint) => `${waypoint.latitude},${waypoint.longitude}`;
+
+export async function setLocation(udid: string, waypoint: LocationWaypoint): Promise<void> {
+  await runSimctl(['location', udid, 'set', formatWaypoint(waypoint)]);
+}
+
+/**
+ * Move the simulator along `waypoints` at `speed` meters per second
+ */
+export async function startLocationRoute(udid: string, waypoints: LocationWaypoint[], speed: number = DEFAULT_ROUTE_SPEED): Promise<void> {
+  await runSimctl(['location', udid, 'start', `--speed=${speed}`, ...waypoints.map(formatWaypoint)]);
+}
+
+/**
+ * Stop a route and go back to the simulator's own location
+ */
+export async function clearLocation(udid: string): Promise<void> {
+  await runSimctl(['location', udid, 'clear']);
+}
@@ -0,0 +1,129 @@
+import { getDatabase } from './database';
+import { EVENT_SCHEMA_VERSION } from '../../shared/ipc-types';
+import type { StreamLogEvent } from '../../shared/ipc-types';
+
+export interface LogHistorySettings {
+  // How long events are kept
+  retentionMinutes: number;
+  // Maximum number of events kept per simulator
+  maxEventsPerSimulator: number;
+  // Also write events to the database so history survives restarts
+  persist: boolean;
+}
+
+const PRUNE_INTERVAL_MS = 60000;
+
+let settings: LogHistorySettings = {
+  retentionMinutes: 10,
+  maxEventsPerSimulator: 5000,
+  persist: false,
+};
+
+// Ring buffers, one per UDID, oldest event first
+const buffers = new Map<string, StreamLogEvent[]>();
+let nextEventId = 1;
+let pruneTimer: ReturnType<typeof setInterval> | null = null;
+
+function cutoff(): string {
+  return new Date(Date.now() - settings.retentionMinutes * 60 * 1000).toISOString();
+}
+
+function pruneBuffer(buffer: StreamLogEvent[]): void {
+  const oldest = cutoff();
+  let expired = 0;
+  while (expired < buffer.length && buffer[expired].timestamp < oldest) {
+    expired++;
+  }
+  const overflow = buffer.length - expired - settings.maxEventsPerSimulator;
+  buffer.splice(0, expired + Math.max(0, overflow));
+}
+
+function prune(): void {
+  for (const [udid, buffer] of buffers) {
+    pruneBuffer(buffer);
+    if (buffer.length === 0) {
+      buffers.delete(udid);
+    }
+  }
+  if (settings.persist) {
+    getDatabase().prepare('DELETE FROM stream_logs WHERE created_at < ?').run(cutoff());
+  }
+}
+
+/**
+ * Apply settings, restore persisted history and start pruning expired events
+ */
+export function startLogHistory(newSettings: LogHistorySettings): void {
+  settings = newSettings;
+
+  if (settings.persist) {
+    const db = getDatabase();
+    const rows = db.prepare(`
+      SELECT id, udid, type, message, created_at FROM stream_logs
+      WHERE created_at >= ?
+      ORDER BY id
+    `).all(cutoff()) as Array<{ id: number; udid: string; type: StreamLogEvent['type']; message: string | null; created_at: string }>;
+
+    for (const row of rows) {
+      const buffer = buffers.get(row.udid) || [];
+      buffer.push({
+        id: row.id,
+        event: `log.${row.type}`,
+        schemaVersion: EVENT_SCHEMA_VERSION,
+        udid: row.udid,
+        type: row.type,
+        message: row.message ?? undefined,
+        timestamp: row.created_at,
+      });
+      buffers.set(row.udid, buffer);
+    }
+
+    // Keep event IDs increasing across restarts so clients can resume
+    const { maxId } = db.prepare('SELECT MAX(id) AS maxId FROM stream_logs').get() as { maxId: number | null };
+    nextEventId = Math.max(nextEventId, (maxId || 0) + 1);
+  }
+
+  prune();
+  if (!pruneTimer) {
+    pruneTimer = setInterval(prune, PRUNE_INTERVAL_MS);
+    pruneTimer.unref();
+  }
+}
+
+/**
+ * Assign an ID and typed name to a log event and add it to the simulator's history
+ */
+export function appendLogEvent(
+  event: Omit<StreamLogEvent, 'id' | 'event' | 'schemaVersion' | 'timestamp'>
+): StreamLogEvent {
+  const stored: StreamLogEvent = {
+    ...event,
+    id: nextEventId++,
+    event: `log.${event.type}`,
+    schemaVersion: EVENT_SCHEMA_VERSION,
+    timestamp: new Date().toISOString(),
+  };
+
+  const buffer = buffers.get(stored.udid) || [];
+  buffer.push(stored);
+  pruneBuffer(buffer);
+  buffers.set(stored.udid, buffer);
+
+  if (settings.persist) {
+    getDatabase().prepare(
+      'INSERT INTO stream_logs (id, udid, type, message, created_at) VALUES (?, ?, ?, ?, ?)'
+    ).run(stored.id, stored.udid, stored.type, stored.message ?? null, stored.timestamp);
+  }
+
+  return stored;
+}
+
+/**
+ * Buffered events after `afterId`, for one simulator or all of them
+ */
+export function getLogHistory(udid: string | null, afterId: number = 0): StreamLogEvent[] {
+  const sources = udid ? [buffers.get(udid) || []] : Array.from(buffers.values());
+  return sources
+    .flatMap((buffer) => buffer.filter((event) => event.id > afterId))
+    .sort((a, b) => a.id - b.id);
+}
@@ -0,0 +1,140 @@
+import type { LogNoiseProfile, LogNoiseProfileId, SyslogEntry } from '../../shared/ipc-types';
+
+/**
+ * Built-in filter profiles for the simulator system log. Raw `log stream` output is mostly
+ * chatter from system daemons and Apple frameworks; each profile drops one well-known source of it.
+ */
+
+interface NoiseRules {
+  // Entries from these processes
+  processes?: string[];
+  // Entries from these subsystems, or subsystems under them (`com.apple.network` covers `com.apple.network.connection`)
+  subsystems?: string[];
+  // Entries at these levels, only when they come from an Apple subsystem
+  appleLevels?: SyslogEntry['level'][];
+}
+
+const PROFILES: Array<LogNoiseProfile & { rules: NoiseRules }> = [
+  {
+    id: 'system-daemons',
+    name: 'System daemons',
+    description: 'SpringBoard, duet, RunningBoard and other daemons that log constantly whether or not your app runs',
+    rules: {
+      processes: [
+        'SpringBoard',
+        'backboardd',
+        'runningboardd',
+        'duetexpertd',
+        'dasd',
+        'assertiond',
+        'mediaserverd',
+        'searchd',
+        'cloudd',
+        'nsurlsessiond',
+        'trustd',
+        'locationd',
+        'CommCenter',
+        'symptomsd',
+        'UserEventAgent',
+        'powerd',
+        'lsd',
+        'analyticsd',
+        'aggregated',
+        'routined',
+        'healthd',
+        'contextstored',
+        'coreduetd',
+        'suggestd',
+        'mobileassetd',
+        'timed',
+        'accountsd',
+        'bird',
+        'cfprefsd',
+      ],
+    },
+  },
+  {
+    id: 'framework-chatter',
+    name: 'Framework chatter',
+    description: 'Apple framework logs inside your app that rarely matter: scene lifecycle, networking internals, analytics',
+    rules: {
+      subsystems: [
+        'com.apple.FrontBoard',
+        'com.apple.BackBoardServices',
+        'com.apple.runningboard',
+        'com.apple.coreduet',
+        'com.apple.duetactivityscheduler',
+        'com.apple.network',
+        'com.apple.CFNetwork',
+        'com.apple.coreanalytics',
+        'com.apple.xpc',
+        'com.apple.defaults',
+        'com.apple.TextInput',
+        'com.apple.Accessibility',
+        'com.apple.launchservices',
+      ],
+    },
+  },
+  {
+    id: 'apple-debug',
+    name: 'Apple debug output',
+    description: 'Debug and info messages from Apple subsystems, keeping their defaults, errors and faults',
+    rules: { appleLevels: ['debug', 'info'] },
+  },
+];
+
+export const DEFAULT_NOISE_PROFILES: LogNoiseProfileId[] = ['system-daemons', 'framework-chatter'];
+
+/**
+ * Built-in profiles, without their rules
+ */
+export function listNoiseProfiles(): LogNoiseProfile[] {
+  return PROFILES.map(({ id, name, description }) => ({ id, name, description }));
+}
+
+export function isNoiseProfileId(id: string): id is LogNoiseProfileId {
+  return PROFILES.some((profile) => profile.id === id);
+}
+
+/**
+ * Profiles selected by a subscription's `noise` parameter: the defaults when it's absent,
+ * none for `none` (raw output), every profile for `all`, or a comma-separated list.
+ * Throws for unknown profiles.
+ */
+export function parseNoiseProfiles(value: string | null): LogNoiseProfileId[] {
+  if (value === null || value === '') {
+    return DEFAULT_NOISE_PROFILES;
+  }
+  if (value === 'none') {
+    return [];
+  }
+  if (value === 'all') {
+    return PROFILES.map((profile) => profile.id);
+  }
+  const ids = value.split(',').map((id) => id.trim()).filter(Boolean);
+  const unknown = ids.filter((id) => !isNoiseProfileId(id));
+  if (unknown.length > 0) {
+    throw new Error(`Unknown noise profile: ${unknown.join(', ')} (expected ${PROFILES.map((p) => p.id).join(', ')}, all or none)`);
+  }
+  return ids as LogNoiseProfileId[];
+}
+
+function matchesSubsystem(subsystem: string | null, prefixes: string[]): boolean {
+  return !!subsystem && prefixes.some((prefix) => subsystem === prefix || subsystem.startsWith(`${prefix}.`));
+}
+
+/**
+ * Whether any of the profiles drops the entry
+ */
+export function isNoise(entry: SyslogEntry, profiles: LogNoiseProfileId[]): boolean {
+  return PROFILES.some(({ id, rules }) => {
+    if (!profiles.includes(id)) {
+      return false;
+    }
+    return (
+      !!rules.processes?.includes(entry.process) ||
+      (!!rules.subsystems && matchesSubsystem(entry.subsystem, rules.subsystems)) ||
+      (!!rules.appleLevels && rules.appleLevels.includes(entry.level) && matchesSubsystem(entry.subsystem, ['com.apple']))
+    );
+  });
+}
@@ -0,0 +1,159 @@
+import { spawn, ChildProcess } from 'child_process';
+import { EventEmitter } from 'events';
+import * as fs from 'fs';
+import * as path from 'path';
+import { registerProcess } from './process-manager';
+import { createJobDir } from './workspace';
+import { emitLog, runCommand } from './simulator';
+import { MAC_DEVICE_ID } from '../../shared/ipc-types';
+
+export { MAC_DEVICE_ID };
+
+const DEFAULT_CAPTURE_FPS = 2;
+
+export interface MacLaunchOptions {
+  // Periodically screen-capture the app's front window and stream it as frames
+  capture?: boolean;
+  captureFps?: number;
+}
+
+export interface MacLaunchResult {
+  message: string;
+  bundleId: string;
+  pid: number;
+}
+
+interface RunningMacApp {
+  bundleId: string;
+  process: ChildProcess;
+  captureTimer: ReturnType<typeof setTimeout> | null;
+}
+
+const runningApps = new Map<string, RunningMacApp>();
+
+async function readPlistValue(plistPath: string, key: string): Promise<string> {
+  const stdout = await runCommand('/usr/libexec/PlistBuddy', ['-c', `Print :${key}`, plistPath]);
+  return stdout.trim();
+}
+
+/**
+ * Bounds of a process's front window, via System Events (needs accessibility permission)
+ */
+async function getFrontWindowBounds(pid: number): Promise<string | null> {
+  const script = `tell application "System Events" to tell (first process whose unix id is ${pid}) to get {position, size} of front window`;
+  try {
+    const stdout = await runCommand('osascript', ['-e', script]);
+    const values = stdout.split(',').map((value) => parseInt(value.trim()));
+    if (values.length !== 4 || values.some(isNaN)) {
+      return null;
+    }
+    return values.join(',');
+  } catch {
+    // No window yet, or no accessibility permission
+    return null;
+  }
+}
+
+function startWindowCapture(app: RunningMacApp, fps: number, frames: EventEmitter): void {
+  const pid = app.process.pid!;
+  const jobDir = createJobDir('capture', `${app.bundleId}-${pid}`);
+  const file = path.join(jobDir.path, 'frame.jpg');
+  const interval = 1000 / fps;
+
+  const captureFrame = async () => {
+    const bounds = await getFrontWindowBounds(pid);
+    if (bounds) {
+      try {
+        await runCommand('screencapture', ['-x', '-t', 'jpg', '-R', bounds, file]);
+        frames.emit('frame', fs.readFileSync(file).toString('base64'));
+      } catch (err) {
+        console.error('[macos] Window capture failed:', err);
+      }
+    }
+    if (runningApps.get(app.bundleId) === app) {
+      app.captureTimer = setTimeout(captureFrame, interval);
+    }
+  };
+
+  app.process.once('close', () => jobDir.cleanup());
+  captureFrame();
+}
+
+function forwardOutput(stream: NodeJS.ReadableStream | null, bundleId: string, type: 'info' | 'error'): void {
+  stream?.on('data', (data: Buffer) => {
+    for (const line of data.toString().split('\n')) {
+      if (line.trim()) {
+        emitLog(MAC_DEVICE_ID, type, `[${bundleId}] ${line}`);
+      }
+    }
+  });
+}
+
+/**
+ * Launch a built macOS app, capturing its stdout and stderr into the `macos` logs.
+ * A running instance of the same app is terminated first. Captured windows are emitted on
+ * `frames` as `frame`, with a base64-encoded JPEG.
+ */
+export async function launchMacApp(
+  appPath: string,
+  frames: EventEmitter,
+  options: MacLaunchOptions = {}
+): Promise<MacLaunchResult> {
+  const infoPlist = path.join(appPath, 'Contents', 'Info.plist');
+  if (!fs.existsSync(infoPlist)) {
+    throw new Error(`Not a macOS app bundle: ${appPath}`);
+  }
+
+  const bundleId = await readPlistValue(infoPlist, 'CFBundleIdentifier');
+  const executable = await readPlistValue(infoPlist, 'CFBundleExecutable');
+  terminateMacApp(bundleId);
+
+  // Running the executable directly (rather than through `open`) gives us its output
+  const proc = spawn(path.join(appPath, 'Contents', 'MacOS', executable), [], {
+    stdio: ['ignore', 'pipe', 'pipe'],
+  });
+  registerProcess(proc);
+
+  await new Promise<void>((resolve, reject) => {
+    proc.once('spawn', resolve);
+    proc.once('error', reject);
+  });
+
+  const app: RunningMacApp = { bundleId, process: proc, captureTimer: null };
+  runningApps.set(bundleId, app);
+
+  forwardOutput(proc.stdout, bundleId, 'info');
+  forwardOutput(proc.stderr, bundleId, 'error');
+  proc.on('close', (code) => {
+    emitLog(MAC_DEVICE_ID, code === 0 ? 'info' : 'error', `${bundleId} exited with code ${code}`);
+    if (app.captureTimer) {
+      clearTimeout(app.captureTimer);
+    }
+    if (runningApps.get(bundleId) === app) {
+      runningApps.delete(bundleId);
+    }
+  });
+
+  emitLog(MAC_DEVICE_ID, 'info', `Launched ${bundleId} (pid ${proc.pid})`);
+  if (options.capture) {
+    startWindowCapture(app, options.captureFps || DEFAULT_CAPTURE_FPS, frames);
+  }
+
+  return { message: `App ${bundleId} launched successfully`, bundleId, pid: proc.pid! };
+}
+
+/**
+ * Terminate a Mac app launched by Plasma. Returns false if it isn't running.
+ */
+export function terminateMacApp(bundleId: string): boolean {
+  const app = runningApps.get(bundleId);
+  if (!app) {
+    return false;
+  }
+  if (app.captureTimer) {
+    clearTimeout(app.captureTimer);
+  }
+  runningApps.delete(bundleId);
+  app.process.kill('SIGTERM');
+  return true;
+}
@@ -0,0 +1,163 @@
+/**
+ * A small Markdown to HTML renderer for project READMEs and notes: headings, paragraphs, lists,
+ * block quotes, fenced and indented code, rules, and inline code, emphasis, links and images.
+ * Raw HTML is escaped rather than passed through, so rendered files are safe to show as they are.
+ */
+
+const escapeHtml = (text: string) =>
+  text.replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;').replace(/"/g, '&quot;');
+
+// Link targets that can't run script, e.g. no `javascript:`
+const SAFE_URL = /^(https?:|mailto:|#|\/|\.{0,2}\/|[\w.-]+(\/|$|#|\?))/i;
+
+function renderInline(text: string): string {
+  // Code spans are taken out first so nothing inside them is formatted
+  const spans: string[] = [];
+  const stashed = text.replace(/(`+)([\s\S]*?[^`])\1(?!`)/g, (_, __, code: string) => {
+    spans.push(`<code>${escapeHtml(code.trim())}</code>`);
+    return `\u0000${spans.length - 1}\u0000`;
+  });
+
+  return escapeHtml(stashed)
+    .replace(/!\[([^\]]*)\]\(([^)\s]+)\)/g, (match, alt: string, src: string) =>
+      SAFE_URL.test(src) ? `<img src="${src}" alt="${alt}">` : match
+    )
+    .replace(/\[([^\]]+)\]\(([^)\s]+)\)/g, (match, label: string, href: string) =>
+      SAFE_URL.test(href) ? `<a href="${href}">${label}</a>` : match
+    )
+    .replace(/(^|[\s(])(https?:\/\/[^\s<]+[^\s<.,;:!?)])/g, '$1<a href="$2">$2</a>')
+    .replace(/(\*\*|__)(?=\S)([\s\S]*?\S)\1/g, '<strong>$2</strong>')
+    .replace(/(^|[^\w*])\*(?=\S)([^*]*?\S)\*(?!\*)/g, '$1<em>$2</em>')
+    .replace(/(^|\W)_(?=\S)([^_]*?\S)_(?!\w)/g, '$1<em>$2</em>')
+    .replace(/~~(?=\S)([\s\S]*?\S)~~/g, '<del>$1</del>')
+    .replace(/ {2,}\n/g, '<br>\n')
+    .replace(/\u0000(\d+)\u0000/g, (_, index: string) => spans[Number(index)]);
+}
+
+const LIST_ITEM = /^( {0,3})([-*+]|\d{1,9}[.)])\s+(.*)$/;
+const BLOCK_START = /^( {0,3}(#{1,6}\s|>|```|~~~|([-*_])(\s*\3){2,}\s*$)|\s{0,3}([-*+]|\d{1,9}[.)])\s+)/;
+
+function renderBlocks(lines: string[]): string {
+  const html: string[] = [];
+  let i = 0;
+
+  while (i < lines.length) {
+    const line = lines[i];
+
+    if (!line.trim()) {
+      i++;
+      continue;
+    }
+
+    const fence = /^ {0,3}(```+|~~~+)\s*([\w+-]*)/.exec(line);
+    if (fence) {
+      const code: string[] = [];
+      i++;
+      while (i < lines.length && !lines[i].trimStart().startsWith(fence[1])) {
+        code.push(lines[i]);
+        i++;
+      }
+      i++;
+      const language = fence[2] ? ` class="language-${escapeHtml(fence[2])}"` : '';
+      html.push(`<pre><code${language}>${escapeHtml(code.join('\n'))}</code></pre>`);
+      continue;
+    }
+
+    if (/^( {4}|\t)/.test(line)) {
+      const code: string[] = [];
+      while (i < lines.length && (/^( {4}|\t)/.test(lines[i]) || !lines[i].trim())) {
+        code.push(lines[i].replace(/^( {4}|\t)/, ''));
+        i++;
+      }
+      while (code.length > 0 && !code[code.length - 1].trim()) {
+        code.pop();
+      }
+      html.push(`<pre><code>${escapeHtml(code.join('\n'))}</code></pre>`);
+      continue;
+    }
+
+    const heading = /^ {0,3}(#{1,6})\s+(.*?)\s*#*\s*$/.exec(line);
+    if (heading) {
+      html.push(`<h${heading[1].length}>${renderInline(heading[2])}</h${heading[1].length}>`);
+      i++;
+      continue;
+    }
+
+    if (/^ {0,3}([-*_])(\s*\1){2,}\s*$/.test(line)) {
+      html.push('<hr>');
+      i++;
+      continue;
+    }
+
+    if (/^ {0,3}>/.test(line)) {
+      const quoted: string[] = [];
+      while (i < lines.length && lines[i].trim() && /^ {0,3}>/.test(lines[i])) {
+        quoted.push(lines[i].replace(/^ {0,3}> ?/, ''));
+        i++;
+      }
+      html.push(`<blockquote>\n${renderBlocks(quoted)}\n</blockquote>`);
+      continue;
+    }
+
+    const item = LIST_ITEM.exec(line);
+    if (item) {
+      const ordered = /\d/.test(item[2]);
+      const items: string[][] = [];
+      while (i < lines.length) {
+        const next = LIST_ITEM.exec(lines[i]);
+        if (next && /\d/.test(next[2]) === ordered && next[1].length <= item[1].length) {
+          items.push([next[3]]);
+        } else if (lines[i].trim() && /^\s/.test(lines[i])) {
+          // Indented lines belong to the item, including nested lists
+          items[items.length - 1].push(lines[i].replace(/^ {1,4}/, ''));
+        } else if (!lines[i].trim() && i + 1 < lines.length && /^\s+\S/.test(lines[i + 1])) {
+          items[items.length - 1].push('');
+        } else {
+          break;
+        }
+        i++;
+      }
+      const start = ordered && parseInt(item[2]) !== 1 ? ` start="${parseInt(item[2])}"` : '';
+      const rendered = items.map((content) => {
+        const task = /^\[([ xX])\]\s+/.exec(content[0]);
+        const checkbox = task ? `<input type="checkbox" disabled${task[1] === ' ' ? '' : ' checked'}> ` : '';
+        if (task) {
+          content[0] = content[0].slice(task[0].length);
+        }
+        // Single paragraph items aren't wrapped in <p>
+        const body = renderBlocks(content).replace(/^<p>([\s\S]*?)<\/p>(?=\n<|$)/, '$1');
+        return `<li>${checkbox}${body}</li>`;
+      });
+      html.push(ordered ? `<ol${start}>\n${rendered.join('\n')}\n</ol>` : `<ul>\n${rendered.join('\n')}\n</ul>`);
+      continue;
+    }
+
+    const paragraph: string[] = [];
+    let level = 0;
+    while (i < lines.length && lines[i].trim()) {
+      // Setext headings, a paragraph underlined with = or -
+      const underline = paragraph.length > 0 ? /^ {0,3}(=+|-+)\s*$/.exec(lines[i]) : null;
+      if (underline) {
+        level = underline[1][0] === '=' ? 1 : 2;
+        i++;
+        break;
+      }
+      if (paragraph.length > 0 && BLOCK_START.test(lines[i])) {
+        break;
+      }
+      paragraph.push(lines[i].replace(/^\s+/, ''));
+      i++;
+    }
+    if (level) {
+      html.push(`<h${level}>${renderInline(paragraph.join('\n'))}</h${level}>`);
+    } else {
+      html.push(`<p>${renderInline(paragraph.join('\n'))}</p>`);
+    }
+  }
+
+  return html.join('\n');
+}
+
+export function renderMarkdown(markdown: string): string {
+  return renderBlocks(markdown.replace(/\r\n?/g, '\n').split('\n'));
+}
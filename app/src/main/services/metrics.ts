@@ -0,0 +1,144 @@
+/**
+ * Minimal in-process metrics registry rendered in the Prometheus text format
+ */
+
+export type Labels = Record<string, string>;
+type MetricType = 'counter' | 'gauge' | 'histogram';
+
+interface HistogramSeries {
+  labels: Labels;
+  // Observations per bucket, not cumulative; the last one is +Inf
+  counts: number[];
+  sum: number;
+  count: number;
+}
+
+interface Metric {
+  type: MetricType;
+  help: string;
+  values: Map<string, { labels: Labels; value: number }>;
+  // Upper bounds of a histogram's buckets, ascending
+  buckets: number[];
+  histograms: Map<string, HistogramSeries>;
+}
+
+const metrics = new Map<string, Metric>();
+
+function labelsKey(labels: Labels): string {
+  return Object.keys(labels)
+    .sort()
+    .map((key) => `${key}=${labels[key]}`)
+    .join(',');
+}
+
+function getMetric(name: string, type: MetricType, help: string): Metric {
+  let metric = metrics.get(name);
+  if (!metric) {
+    metric = { type, help, values: new Map(), buckets: [], histograms: new Map() };
+    metrics.set(name, metric);
+  }
+  return metric;
+}
+
+/**
+ * Register a metric so it shows up (with its help text) before it has values
+ */
+export function defineMetric(name: string, type: MetricType, help: string): void {
+  getMetric(name, type, help);
+}
+
+/**
+ * Register a histogram with the upper bounds of its buckets
+ */
+export function defineHistogram(name: string, help: string, buckets: number[]): void {
+  getMetric(name, 'histogram', help).buckets = [...buckets].sort((a, b) => a - b);
+}
+
+export function observeHistogram(name: string, labels: Labels, value: number): void {
+  const metric = getMetric(name, 'histogram', '');
+  const key = labelsKey(labels);
+  let series = metric.histograms.get(key);
+  if (!series) {
+    series = { labels, counts: new Array(metric.buckets.length + 1).fill(0), sum: 0, count: 0 };
+    metric.histograms.set(key, series);
+  }
+  const bucket = metric.buckets.findIndex((bound) => value <= bound);
+  series.counts[bucket === -1 ? metric.buckets.length : bucket]++;
+  series.sum += value;
+  series.count++;
+}
+
+export function incrementCounter(name: string, labels: Labels = {}, by: number = 1): void {
+  const metric = getMetric(name, 'counter', '');
+  const key = labelsKey(labels);
+  const current = metric.values.get(key);
+  metric.values.set(key, { labels, value: (current?.value || 0) + by });
+}
+
+export function setGauge(name: string, labels: Labels, value: number): void {
+  const metric = getMetric(name, 'gauge', '');
+  metric.values.set(labelsKey(labels), { labels, value });
+}
+
+/**
+ * Remove every series of a metric whose labels include `match`
+ * (e.g. all series of a disconnected subscriber)
+ */
+export function removeSeries(name: string, match: Labels): void {
+  const metric = metrics.get(name);
+  if (!metric) {
+    return;
+  }
+  for (const [key, series] of metric.values) {
+    if (Object.entries(match).every(([k, v]) => series.labels[k] === v)) {
+      metric.values.delete(key);
+    }
+  }
+  for (const [key, series] of metric.histograms) {
+    if (Object.entries(match).every(([k, v]) => series.labels[k] === v)) {
+      metric.histograms.delete(key);
+    }
+  }
+}
+
+function escapeLabelValue(value: string): string {
+  return value.replace(/\\/g, '\\\\').replace(/"/g, '\\"').replace(/\n/g, '\\n');
+}
+
+function formatLabels(labels: Labels): string {
+  const text = Object.entries(labels)
+    .map(([key, value]) => `${key}="${escapeLabelValue(value)}"`)
+    .join(',');
+  return text ? `{${text}}` : '';
+}
+
+/**
+ * Render all metrics in the Prometheus exposition format
+ */
+export function renderMetrics(): string {
+  const lines: string[] = [];
+
+  for (const [name, metric] of metrics) {
+    if (metric.help) {
+      lines.push(`# HELP ${name} ${metric.help}`);
+    }
+    lines.push(`# TYPE ${name} ${metric.type}`);
+
+    for (const { labels, value } of metric.values.values()) {
+      lines.push(`${name}${formatLabels(labels)} ${value}`);
+    }
+
+    for (const series of metric.histograms.values()) {
+      let cumulative = 0;
+      metric.buckets.forEach((bound, i) => {
+        cumulative += series.counts[i];
+        lines.push(`${name}_bucket${formatLabels({ ...series.labels, le: String(bound) })} ${cumulative}`);
+      });
+      lines.push(`${name}_bucket${formatLabels({ ...series.labels, le: '+Inf' })} ${series.count}`);
+      lines.push(`${name}_sum${formatLabels(series.labels)} ${series.sum}`);
+      lines.push(`${name}_count${formatLabels(series.labels)} ${series.count}`);
+    }
+  }
+
+  return `${lines.join('\n')}\n`;
+}
@@ -0,0 +1,55 @@
+/**
+ * Multipart (MJPEG) streams: parsing the one simulator-server serves, and writing our own for
+ * stream recordings and thumbnails, which browsers play in an <img>
+ */
+
+// What simulator-server separates its frames with
+const SIMULATOR_SERVER_BOUNDARY = '--mjpegstream';
+// Drop buffered data past this size, e.g. when a frame never ends
+const MAX_BUFFERED_BYTES = 10 * 1024 * 1024;
+
+const BOUNDARY = 'plasmaframe';
+export const MULTIPART_CONTENT_TYPE = `multipart/x-mixed-replace; boundary=${BOUNDARY}`;
+
+/**
+ * Split simulator-server's stream into JPEG frames. Feed the returned function each chunk.
+ */
+export function createMjpegParser(onFrame: (jpeg: Buffer) => void): (chunk: Buffer) => void {
+  let buffer = Buffer.alloc(0);
+
+  return (chunk) => {
+    buffer = Buffer.concat([buffer, chunk]);
+
+    let boundaryIndex: number;
+    while ((boundaryIndex = buffer.indexOf(SIMULATOR_SERVER_BOUNDARY)) !== -1) {
+      const headerEnd = buffer.indexOf('\r\n\r\n', boundaryIndex);
+      if (headerEnd === -1) break;
+
+      const dataStart = headerEnd + 4;
+      const nextBoundary = buffer.indexOf(SIMULATOR_SERVER_BOUNDARY, dataStart);
+      if (nextBoundary === -1) break;
+
+      const jpegData = buffer.slice(dataStart, nextBoundary - 2);
+      if (jpegData.length > 0) {
+        onFrame(jpegData);
+      }
+
+      buffer = buffer.slice(nextBoundary);
+    }
+
+    if (buffer.length > MAX_BUFFERED_BYTES) {
+      buffer = buffer.slice(-1024 * 1024);
+    }
+  };
+}
+
+/**
+ * One frame of a `MULTIPART_CONTENT_TYPE` stream
+ */
+export function multipartFrame(frame: Buffer, format: 'jpeg' | 'png'): Buffer {
+  return Buffer.concat([
+    Buffer.from(`--${BOUNDARY}\r\nContent-Type: image/${format}\r\nContent-Length: ${frame.length}\r\n\r\n`),
+    frame,
+    Buffer.from('\r\n'),
+  ]);
+}
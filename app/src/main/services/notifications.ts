@@ -0,0 +1,115 @@
+import * as fs from 'fs';
+import * as os from 'os';
+import { getDataStore } from './data-store';
+import { findAppState } from './app-state';
+import type { NotificationRow } from './data-store';
+import { toListPage, ListPage, ListQuery } from '../list-query';
+import type { NotificationKind, NotificationLevel, NotificationRecord } from '../../shared/ipc-types';
+
+export interface NewNotification {
+  kind: NotificationKind;
+  level?: NotificationLevel;
+  title: string;
+  message?: string;
+  data?: Record<string, unknown>;
+}
+
+function parseNotificationRecord(record: NotificationRow): NotificationRecord {
+  return {
+    ...record,
+    data: record.data ? (JSON.parse(record.data) as Record<string, unknown>) : null,
+  };
+}
+
+/**
+ * Persist a notification and emit it to live subscribers
+ */
+export async function recordNotification(notification: NewNotification): Promise<NotificationRecord> {
+  const row = await getDataStore().notifications.insert({
+    kind: notification.kind,
+    level: notification.level || 'info',
+    title: notification.title,
+    message: notification.message ?? null,
+    data: notification.data ? JSON.stringify(notification.data) : null,
+    created_at: new Date().toISOString(),
+  });
+  const record = parseNotificationRecord(row);
+  findAppState()?.notifications.emit('notification', record);
+  return record;
+}
+
+/**
+ * A page of notifications, newest first unless the query sorts them. Filtered, sorted and
+ * paginated in SQL, so older notifications can be paged through however many there are.
+ */
+export async function listNotifications(query: ListQuery, unreadOnly: boolean = false): Promise<ListPage<NotificationRecord>> {
+  const { rows, total } = await getDataStore().notifications.list(query, unreadOnly);
+  return toListPage(rows.map(parseNotificationRecord), total, query);
+}
+
+/**
+ * Notifications recorded after the one with ID `id`, oldest first
+ */
+export async function listNotificationsSince(id: number): Promise<NotificationRecord[]> {
+  return (await getDataStore().notifications.listSince(id)).map(parseNotificationRecord);
+}
+
+export function getUnreadNotificationCount(): Promise<number> {
+  return getDataStore().notifications.countUnread();
+}
+
+/**
+ * Mark a single notification as read. Resolves to false if it doesn't exist.
+ */
+export function markNotificationRead(id: number): Promise<boolean> {
+  return getDataStore().notifications.markRead(id);
+}
+
+export function markAllNotificationsRead(): Promise<void> {
+  return getDataStore().notifications.markAllRead();
+}
+
+// Disk space monitoring
+const DISK_CHECK_INTERVAL_MS = 5 * 60 * 1000;
+const DISK_LOW_THRESHOLD_BYTES = 5 * 1024 * 1024 * 1024;
+
+let diskMonitorTimer: ReturnType<typeof setInterval> | null = null;
+let diskLowReported = false;
+
+function checkDiskSpace(): void {
+  try {
+    const stats = fs.statfsSync(os.homedir());
+    const freeBytes = stats.bavail * stats.bsize;
+
+    if (freeBytes < DISK_LOW_THRESHOLD_BYTES) {
+      // Only notify once per crossing of the threshold
+      if (!diskLowReported) {
+        diskLowReported = true;
+        const freeGb = (freeBytes / (1024 * 1024 * 1024)).toFixed(1);
+        recordNotification({
+          kind: 'disk_low',
+          level: 'warning',
+          title: 'Disk space is running low',
+          message: `Only ${freeGb} GB free. Builds and simulators may start failing.`,
+          data: { freeBytes },
+        }).catch((err) => console.error('[notifications] Failed to record low disk space:', err));
+      }
+    } else {
+      diskLowReported = false;
+    }
+  } catch (err) {
+    console.error('[notifications] Failed to check disk space:', err);
+  }
+}
+
+/**
+ * Periodically check free disk space and notify when it drops below the threshold
+ */
+export function startDiskSpaceMonitor(): void {
+  if (diskMonitorTimer) {
+    return;
+  }
+  checkDiskSpace();
+  diskMonitorTimer = setInterval(checkDiskSpace, DISK_CHECK_INTERVAL_MS);
+  diskMonitorTimer.unref();
+}
@@ -0,0 +1,244 @@
+import fetch from 'node-fetch';
+import { getDataStore } from './data-store';
+import type { NotifierRow, ProjectKey } from './data-store';
+import { deleteSecret, getSecret, setSecret, SecretsLockedError } from './secrets';
+import type { NotifierKind, NotifierEvent, NotifierRecord } from '../../shared/ipc-types';
+
+export const NOTIFIER_EVENTS: NotifierEvent[] = ['build_succeeded', 'build_failed'];
+
+export interface NotifierInput {
+  kind: NotifierKind;
+  webhookUrl: string;
+  events?: NotifierEvent[];
+  template?: string | null;
+  enabled?: boolean;
+}
+
+export interface BuildSummary {
+  project: string;
+  scheme: string;
+  success: boolean;
+  durationMs?: number;
+  logUrl?: string;
+  message?: string;
+}
+
+const DEFAULT_TEMPLATE =
+  '{{emoji}} *{{project}}* build of `{{scheme}}` {{status}} in {{duration}}\n{{log_url}}';
+
+/**
+ * Name of the secret a notifier's webhook URL is kept in. The URL carries the service's credentials,
+ * so it's encrypted in the secrets store rather than kept with the notifier.
+ */
+export function notifierSecretName(id: number): string {
+  return `notifiers.${id}.webhook_url`;
+}
+
+function parseNotifierRecord(record: NotifierRow): NotifierRecord {
+  return {
+    ...record,
+    webhook_secret: notifierSecretName(record.id),
+    events: JSON.parse(record.events || '[]') as NotifierEvent[],
+    enabled: record.enabled === 1,
+  };
+}
+
+/**
+ * Validate notifier input. Returns an error message or null.
+ */
+export function validateNotifierInput(input: Partial<NotifierInput>): string | null {
+  if (input.kind !== 'slack' && input.kind !== 'discord') {
+    return 'kind must be "slack" or "discord"';
+  }
+
+  let url: URL;
+  try {
+    url = new URL(input.webhookUrl || '');
+  } catch {
+    return 'webhookUrl must be a valid URL';
+  }
+  if (url.protocol !== 'https:') {
+    return 'webhookUrl must use https';
+  }
+
+  if (input.events !== undefined) {
+    if (!Array.isArray(input.events)) {
+      return `events must be an array of ${NOTIFIER_EVENTS.join(', ')}`;
+    }
+    const unknown = input.events.filter((e) => !NOTIFIER_EVENTS.includes(e));
+    if (unknown.length > 0) {
+      return `Unknown events: ${unknown.map((e) => String(e)).join(', ')}`;
+    }
+  }
+
+  if (input.template !== undefined && input.template !== null && typeof input.template !== 'string') {
+    return 'template must be a string or null';
+  }
+  if (input.enabled !== undefined && typeof input.enabled !== 'boolean') {
+    return 'enabled must be a boolean';
+  }
+
+  return null;
+}
+
+export async function listProjectNotifiers(project: ProjectKey): Promise<NotifierRecord[]> {
+  return (await getDataStore().notifiers.list(project)).map(parseNotifierRecord);
+}
+
+export async function getProjectNotifier(project: ProjectKey, id: number): Promise<NotifierRecord | null> {
+  const row = await getDataStore().notifiers.get(project, id);
+  return row ? parseNotifierRecord(row) : null;
+}
+
+/**
+ * Add a notifier, with its webhook URL in the secrets store. Throws `SecretsLockedError` when
+ * secrets are locked, without adding it.
+ */
+export async function createProjectNotifier(project: ProjectKey, input: NotifierInput): Promise<NotifierRecord> {
+  const store = getDataStore();
+  const row = await store.notifiers.insert(project, {
+    kind: input.kind,
+    events: JSON.stringify(input.events || NOTIFIER_EVENTS),
+    template: input.template || null,
+    enabled: input.enabled === false ? 0 : 1,
+    created_at: new Date().toISOString(),
+  });
+  try {
+    await setSecret(notifierSecretName(row.id), input.webhookUrl);
+  } catch (err) {
+    await store.notifiers.delete(project, row.id);
+    throw err;
+  }
+  return parseNotifierRecord(row);
+}
+
+export async function deleteProjectNotifier(project: ProjectKey, id: number): Promise<boolean> {
+  if (!(await getDataStore().notifiers.delete(project, id))) {
+    return false;
+  }
+  await deleteSecret(notifierSecretName(id));
+  return true;
+}
+
+/**
+ * Decrypted webhook URL of a notifier, or null if it has none
+ */
+export async function getNotifierWebhookUrl(notifier: NotifierRecord): Promise<string | null> {
+  const url = await getSecret(notifier.webhook_secret);
+  if (url) {
+    return url;
+  }
+  // Created before webhook URLs moved to the secrets store and not migrated yet
+  return (await getDataStore().notifiers.plaintextWebhookUrls()).get(notifier.id) || null;
+}
+
+/**
+ * Move webhook URLs that are still stored in plaintext into the secrets store. While secrets are
+ * locked they stay where they are and are tried again at the next start.
+ */
+export async function migrateNotifierWebhookUrls(): Promise<void> {
+  const store = getDataStore();
+  const urls = await store.notifiers.plaintextWebhookUrls();
+  for (const [id, url] of urls) {
+    try {
+      await setSecret(notifierSecretName(id), url);
+    } catch (err) {
+      if (!(err instanceof SecretsLockedError)) {
+        throw err;
+      }
+      console.warn(`[notifiers] ${urls.size} notifier webhook URLs stay unencrypted until secrets are unlocked: ${err.message}`);
+      return;
+    }
+    await store.notifiers.clearPlaintextWebhookUrl(id);
+  }
+}
+
+function formatDuration(durationMs?: number): string {
+  if (durationMs === undefined) {
+    return 'unknown time';
+  }
+  const seconds = Math.round(durationMs / 1000);
+  if (seconds < 60) {
+    return `${seconds}s`;
+  }
+  return `${Math.floor(seconds / 60)}m ${seconds % 60}s`;
+}
+
+/**
+ * Render a message template, replacing {{placeholders}} with build details
+ */
+export function renderTemplate(template: string, summary: BuildSummary): string {
+  const values: Record<string, string> = {
+    project: summary.project,
+    scheme: summary.scheme,
+    status: summary.success ? 'succeeded' : 'failed',
+    emoji: summary.success ? '✅' : '❌',
+    duration: formatDuration(summary.durationMs),
+    log_url: summary.logUrl || '',
+    message: summary.message || '',
+  };
+  return template.replace(/\{\{\s*(\w+)\s*\}\}/g, (match, key: string) => values[key] ?? match).trim();
+}
+
+/**
+ * Send a message to a Slack or Discord incoming webhook
+ */
+async function postToWebhook(notifier: NotifierRecord, text: string): Promise<void> {
+  // Slack expects `text`, Discord expects `content`
+  const body = notifier.kind === 'slack' ? { text } : { content: text };
+  const url = await getNotifierWebhookUrl(notifier);
+  if (!url) {
+    throw new Error(`${notifier.kind} notifier ${notifier.id} has no webhook URL`);
+  }
+
+  const response = await fetch(url, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify(body),
+  });
+
+  if (!response.ok) {
+    throw new Error(`${notifier.kind} webhook returned ${response.status} ${response.statusText}`);
+  }
+}
+
+/**
+ * Send a test message through a notifier
+ */
+export async function sendTestNotification(notifier: NotifierRecord, projectName: string): Promise<void> {
+  const text = renderTemplate(notifier.template || DEFAULT_TEMPLATE, {
+    project: projectName,
+    scheme: 'Test',
+    success: true,
+    durationMs: 0,
+  });
+  await postToWebhook(notifier, text);
+}
+
+/**
+ * Notify all matching notifiers of the project owning `projectPath` about a finished build.
+ * Failures are logged and never affect the build.
+ */
+export async function dispatchBuildNotifiers(
+  projectPath: string,
+  summary: Omit<BuildSummary, 'project'>
+): Promise<void> {
+  const project = await getDataStore().projects.findByPath(projectPath);
+  if (!project) {
+    return;
+  }
+
+  const event: NotifierEvent = summary.success ? 'build_succeeded' : 'build_failed';
+  const notifiers = (await listProjectNotifiers(project)).filter((n) => n.enabled && n.events.includes(event));
+
+  await Promise.all(
+    notifiers.map(async (notifier) => {
+      try {
+        const text = renderTemplate(notifier.template || DEFAULT_TEMPLATE, { ...summary, project: project.name });
+        await postToWebhook(notifier, text);
+      } catch (err) {
+        console.error(`[notifiers] Failed to notify ${notifier.kind} notifier ${notifier.id}:`, err);
+      }
+    })
+  );
+}
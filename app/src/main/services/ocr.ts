@@ -0,0 +1,139 @@
+import { spawn } from 'child_process';
+import { createHash } from 'crypto';
+import * as fs from 'fs';
+import * as path from 'path';
+import { getDataDir } from './database';
+import { createJobDir } from './workspace';
+import { registerProcess } from './process-manager';
+import { runCommand } from './simulator';
+import type { OcrLevel, OcrObservation } from '../../shared/ipc-types';
+
+/**
+ * Text recognition on screenshots with the Vision framework. The helper is a small Swift
+ * program compiled with the local toolchain on first use and cached in the data directory,
+ * keyed by its source, so it's rebuilt only when the source changes.
+ */
+
+const HELPER_SOURCE = `
+import Foundation
+import Vision
+
+let args = CommandLine.arguments
+guard args.count >= 3 else {
+  FileHandle.standardError.write("usage: ocr <image> <fast|accurate> [languages]\\n".data(using: .utf8)!)
+  exit(2)
+}
+let request = VNRecognizeTextRequest()
+request.recognitionLevel = args[2] == "fast" ? .fast : .accurate
+if args.count > 3 {
+  request.recognitionLanguages = args[3].split(separator: ",").map(String.init)
+}
+do {
+  try VNImageRequestHandler(url: URL(fileURLWithPath: args[1])).perform([request])
+} catch {
+  FileHandle.standardError.write("\\(error)\\n".data(using: .utf8)!)
+  exit(1)
+}
+// Vision's boxes are normalized with a bottom-left origin; flip them to top-left
+var results: [[String: Any]] = []
+for observation in request.results ?? [] {
+  guard let candidate = observation.topCandidates(1).first else { continue }
+  let box = observation.boundingBox
+  results.append([
+    "text": candidate.string,
+    "confidence": Double(candidate.confidence),
+    "bounds": ["x": Double(box.minX), "y": Double(1 - box.maxY), "width": Double(box.width), "height": Double(box.height)],
+  ])
+}
+FileHandle.standardOutput.write(try JSONSerialization.data(withJSONObject: results))
+`;
+
+export type TextRecognizer = (png: Buffer, level: OcrLevel, languages: string[]) => Promise<OcrObservation[]>;
+
+export class OcrUnavailableError extends Error {}
+
+let helperBuild: Promise<string> | null = null;
+let recognizer: TextRecognizer = recognizeWithVision;
+
+/**
+ * Replace the recognizer, e.g. with canned results in tests. Null restores Vision.
+ */
+export function setTextRecognizer(replacement: TextRecognizer | null): void {
+  recognizer = replacement || recognizeWithVision;
+}
+
+function buildHelper(): Promise<string> {
+  if (!helperBuild) {
+    helperBuild = (async () => {
+      const hash = createHash('sha256').update(HELPER_SOURCE).digest('hex').slice(0, 12);
+      const dir = path.join(getDataDir(), 'helpers');
+      const binary = path.join(dir, `ocr-${hash}`);
+      if (fs.existsSync(binary)) {
+        return binary;
+      }
+      fs.mkdirSync(dir, { recursive: true });
+      const source = path.join(dir, `ocr-${hash}.swift`);
+      fs.writeFileSync(source, HELPER_SOURCE);
+      console.log('[ocr] Compiling the text recognition helper...');
+      try {
+        await runCommand('xcrun', ['swiftc', '-O', source, '-o', binary]);
+      } finally {
+        fs.rmSync(source, { force: true });
+      }
+      return binary;
+    })();
+    // Let a failed build be retried, e.g. after installing Xcode
+    helperBuild.catch(() => {
+      helperBuild = null;
+    });
+  }
+  return helperBuild;
+}
+
+async function recognizeWithVision(png: Buffer, level: OcrLevel, languages: string[]): Promise<OcrObservation[]> {
+  if (process.platform !== 'darwin') {
+    throw new OcrUnavailableError('Text recognition needs macOS');
+  }
+  let helper: string;
+  try {
+    helper = await buildHelper();
+  } catch (err) {
+    throw new OcrUnavailableError(`Failed to build the text recognition helper, is Xcode installed? ${err instanceof Error ? err.message : err}`);
+  }
+
+  const jobDir = createJobDir('capture');
+  try {
+    const image = path.join(jobDir.path, 'screen.png');
+    fs.writeFileSync(image, png);
+    const args = [image, level, ...(languages.length > 0 ? [languages.join(',')] : [])];
+    const output = await new Promise<string>((resolve, reject) => {
+      const proc = spawn(helper, args);
+      registerProcess(proc);
+      let stdout = '';
+      let stderr = '';
+      proc.stdout.on('data', (data) => (stdout += data.toString()));
+      proc.stderr.on('data', (data) => (stderr += data.toString()));
+      proc.on('error', reject);
+      proc.on('close', (code) => (code === 0 ? resolve(stdout) : reject(new Error(stderr || `OCR helper exited with ${code}`))));
+    });
+    return JSON.parse(output) as OcrObservation[];
+  } finally {
+    jobDir.cleanup();
+  }
+}
+
+/**
+ * Recognize the text in a PNG, top to bottom
+ */
+export async function recognizeText(png: Buffer, level: OcrLevel = 'accurate', languages: string[] = []): Promise<OcrObservation[]> {
+  const observations = await recognizer(png, level, languages);
+  return observations.sort((a, b) => a.bounds.y - b.bounds.y || a.bounds.x - b.bounds.x);
+}
+
+/**
+ * Observations containing `text`, ignoring case
+ */
+export function findText(observations: OcrObservation[], text: string): OcrObservation[] {
+  const needle = text.toLowerCase();
+  return observations.filter((observation) => observation.text.toLowerCase().includes(needle));
+}
@@ -0,0 +1,89 @@
+import { getDataStore } from './data-store';
+import { clearSetting, readSetting, writeSetting } from './settings';
+import { findAxeBinary } from './axe';
+import { findSimulatorServerBinary, listSimulators, runCommand } from './simulator';
+import type { OnboardingStatus, OnboardingStep } from '../../shared/ipc-types';
+
+/**
+ * First-run setup: which steps towards a first running app are done,
+ * so the frontend can guide new users through the rest
+ */
+
+async function checkXcode(): Promise<OnboardingStep> {
+  const step = { id: 'xcode' as const, title: 'Install Xcode' };
+  try {
+    // Fails with only the Command Line Tools selected
+    const version = (await runCommand('xcodebuild', ['-version'])).split('\n')[0].trim();
+    return { ...step, complete: true, detail: version };
+  } catch {
+    return { ...step, complete: false, detail: 'Install Xcode and select it with `xcode-select -s`' };
+  }
+}
+
+async function checkRuntime(): Promise<OnboardingStep> {
+  const step = { id: 'simulator_runtime' as const, title: 'Install a simulator runtime' };
+  try {
+    const simulators = await listSimulators();
+    if (simulators.length > 0) {
+      return { ...step, complete: true, detail: `${simulators.length} simulators available` };
+    }
+  } catch {
+    // Reported as incomplete below
+  }
+  return { ...step, complete: false, detail: 'Install a platform in Xcode > Settings > Components' };
+}
+
+function checkHelpers(): OnboardingStep {
+  const missing = [
+    findSimulatorServerBinary() ? null : 'simulator-server',
+    findAxeBinary() ? null : 'axe',
+  ].filter((name): name is string => !!name);
+  return {
+    id: 'helpers',
+    title: 'Helper binaries',
+    complete: missing.length === 0,
+    detail: missing.length === 0 ? null : `Missing: ${missing.join(', ')}`,
+  };
+}
+
+async function checkFirstProject(): Promise<OnboardingStep> {
+  const count = await getDataStore().projects.count();
+  return {
+    id: 'first_project',
+    title: 'Add a project',
+    complete: count > 0,
+    detail: count > 0 ? `${count} projects` : null,
+  };
+}
+
+async function checkFirstBuild(): Promise<OnboardingStep> {
+  const build = await getDataStore().builds.firstSucceeded();
+  return {
+    id: 'first_build',
+    title: 'Build an app',
+    complete: !!build,
+    detail: build ? `First succeeded at ${build.finished_at}` : null,
+  };
+}
+
+export async function getOnboardingStatus(): Promise<OnboardingStatus> {
+  const steps = [await checkXcode(), await checkRuntime(), checkHelpers(), await checkFirstProject(), await checkFirstBuild()];
+  const dismissed = await readSetting('onboarding.dismissed_at');
+  return {
+    steps,
+    complete: steps.every((step) => step.complete),
+    dismissed: !!dismissed,
+    dismissedAt: dismissed?.value || null,
+  };
+}
+
+/**
+ * Hide onboarding (or bring it back), remembered across restarts
+ */
+export async function setOnboardingDismissed(dismissed: boolean): Promise<void> {
+  if (dismissed) {
+    await writeSetting('onboarding.dismissed_at', new Date().toISOString());
+  } else {
+    await clearSetting('onboarding.dismissed_at');
+  }
+}
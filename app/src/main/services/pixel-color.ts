@@ -0,0 +1,44 @@
+import type { DecodedPng } from './png';
+import type { PixelColor } from '../../shared/ipc-types';
+
+export const MAX_SAMPLE_RADIUS = 50;
+
+function toHex(value: number): string {
+  return value.toString(16).padStart(2, '0');
+}
+
+/**
+ * Color at normalized (`x`, `y`), averaged over the square `radius` pixels around it
+ * (clipped to the image). Alpha is 255 for images without an alpha channel.
+ */
+export function sampleColor(image: DecodedPng, x: number, y: number, radius: number = 0): PixelColor {
+  const pixelX = Math.min(Math.floor(x * image.width), image.width - 1);
+  const pixelY = Math.min(Math.floor(y * image.height), image.height - 1);
+
+  const totals = [0, 0, 0, 0];
+  let samples = 0;
+  for (let row = Math.max(0, pixelY - radius); row <= Math.min(image.height - 1, pixelY + radius); row++) {
+    for (let column = Math.max(0, pixelX - radius); column <= Math.min(image.width - 1, pixelX + radius); column++) {
+      const offset = (row * image.width + column) * image.channels;
+      for (let channel = 0; channel < 4; channel++) {
+        totals[channel] += channel < image.channels ? image.pixels[offset + channel] : 255;
+      }
+      samples++;
+    }
+  }
+
+  const [r, g, b, a] = totals.map((total) => Math.round(total / samples));
+  return {
+    x,
+    y,
+    pixelX,
+    pixelY,
+    radius,
+    samples,
+    r,
+    g,
+    b,
+    a,
+    hex: `#${toHex(r)}${toHex(g)}${toHex(b)}`,
+  };
+}
@@ -0,0 +1,293 @@
+import { spawn } from 'child_process';
+import * as fs from 'fs';
+import * as path from 'path';
+import { randomUUID } from 'crypto';
+import { registerProcess } from './process-manager';
+import { getDataDir } from './database';
+import { createJobDir } from './workspace';
+
+/**
+ * Plugins are executables described by a `plugin.json` manifest living in
+ * their own directory under the plugins directory:
+ *
+ *   ~/.local/share/plasma/plugins/<name>/plugin.json
+ *
+ * Each invocation spawns the executable, writes a single JSON request to its
+ * stdin and reads a single JSON response from its stdout.
+ */
+
+export type PluginBuildHook = 'build_started' | 'build_completed';
+
+export interface PluginRoute {
+  method: string;
+  path: string;
+}
+
+export interface PluginDeviceAction {
+  id: string;
+  title: string;
+}
+
+export interface PluginManifest {
+  name: string;
+  version?: string;
+  description?: string;
+  executable: string;
+  routes?: PluginRoute[];
+  buildHooks?: PluginBuildHook[];
+  deviceActions?: PluginDeviceAction[];
+}
+
+export interface Plugin {
+  manifest: PluginManifest;
+  directory: string;
+  executablePath: string;
+}
+
+export type PluginRequest =
+  | { type: 'route'; method: string; path: string; query: Record<string, string>; body: unknown }
+  | { type: 'build_hook'; event: PluginBuildHook; build: Record<string, unknown> }
+  | { type: 'device_action'; action: string; udid: string; params: Record<string, unknown> };
+
+export interface PluginResponse {
+  status?: number;
+  body?: unknown;
+}
+
+const PLUGIN_TIMEOUT_MS = 30000;
+const PLUGIN_NAME_PATTERN = /^[a-z0-9][a-z0-9-_]*$/;
+const PLUGIN_BUILD_HOOKS: PluginBuildHook[] = ['build_started', 'build_completed'];
+const PLUGIN_ROUTE_METHODS = ['GET', 'POST', 'PUT', 'PATCH', 'DELETE'];
+
+// One server's loaded plugins, by name
+export type PluginRegistry = Map<string, Plugin>;
+
+function getPluginsDir(): string {
+  return process.env.PLASMA_PLUGINS_DIR || path.join(getDataDir(), 'plugins');
+}
+
+const isString = (value: unknown): value is string => typeof value === 'string' && value.length > 0;
+
+/**
+ * Check the optional parts of a manifest. Returns an error message or null.
+ */
+function validateManifestEntries(manifest: PluginManifest): string | null {
+  if (manifest.routes !== undefined) {
+    if (!Array.isArray(manifest.routes)) {
+      return 'routes must be an array';
+    }
+    for (const [index, route] of manifest.routes.entries()) {
+      if (!isString(route?.method) || !PLUGIN_ROUTE_METHODS.includes(route.method.toUpperCase())) {
+        return `routes[${index}].method must be one of ${PLUGIN_ROUTE_METHODS.join(', ')}`;
+      }
+      if (!isString(route.path)) {
+        return `routes[${index}].path must be a string`;
+      }
+    }
+  }
+
+  if (manifest.buildHooks !== undefined) {
+    if (!Array.isArray(manifest.buildHooks) || !manifest.buildHooks.every((hook) => PLUGIN_BUILD_HOOKS.includes(hook))) {
+      return `buildHooks must be an array of ${PLUGIN_BUILD_HOOKS.join(', ')}`;
+    }
+  }
+
+  if (manifest.deviceActions !== undefined) {
+    if (!Array.isArray(manifest.deviceActions)) {
+      return 'deviceActions must be an array';
+    }
+    for (const [index, action] of manifest.deviceActions.entries()) {
+      if (!isString(action?.id)) {
+        return `deviceActions[${index}].id must be a string`;
+      }
+      if (!isString(action.title)) {
+        return `deviceActions[${index}].title must be a string`;
+      }
+    }
+  }
+
+  return null;
+}
+
+/**
+ * Read and validate a plugin manifest. Returns null (and logs) if invalid.
+ */
+function loadPlugin(directory: string): Plugin | null {
+  const manifestPath = path.join(directory, 'plugin.json');
+  if (!fs.existsSync(manifestPath)) {
+    return null;
+  }
+
+  let manifest: PluginManifest;
+  try {
+    manifest = JSON.parse(fs.readFileSync(manifestPath, 'utf-8'));
+  } catch (err) {
+    console.error(`[plugins] Invalid manifest at ${manifestPath}:`, err);
+    return null;
+  }
+
+  if (!manifest || typeof manifest.name !== 'string' || !PLUGIN_NAME_PATTERN.test(manifest.name)) {
+    console.error(`[plugins] Invalid plugin name in ${manifestPath}`);
+    return null;
+  }
+
+  if (!isString(manifest.executable)) {
+    console.error(`[plugins] Missing executable in ${manifestPath}`);
+    return null;
+  }
+
+  const invalid = validateManifestEntries(manifest);
+  if (invalid) {
+    console.error(`[plugins] Invalid manifest at ${manifestPath}: ${invalid}`);
+    return null;
+  }
+
+  const executablePath = path.resolve(directory, manifest.executable);
+  if (!fs.existsSync(executablePath)) {
+    console.error(`[plugins] Executable not found for ${manifest.name}: ${executablePath}`);
+    return null;
+  }
+
+  return { manifest, directory, executablePath };
+}
+
+/**
+ * Discover plugins in the plugins directory. Called once at startup.
+ */
+export function loadPlugins(plugins: PluginRegistry): Plugin[] {
+  plugins.clear();
+
+  const pluginsDir = getPluginsDir();
+  if (!fs.existsSync(pluginsDir)) {
+    return [];
+  }
+
+  for (const entry of fs.readdirSync(pluginsDir, { withFileTypes: true })) {
+    if (!entry.isDirectory()) {
+      continue;
+    }
+
+    const plugin = loadPlugin(path.join(pluginsDir, entry.name));
+    if (!plugin) {
+      continue;
+    }
+
+    if (plugins.has(plugin.manifest.name)) {
+      console.error(`[plugins] Duplicate plugin name ${plugin.manifest.name}, skipping ${plugin.directory}`);
+      continue;
+    }
+
+    plugins.set(plugin.manifest.name, plugin);
+    console.log(`[plugins] Loaded ${plugin.manifest.name} ${plugin.manifest.version || ''}`.trim());
+  }
+
+  return listPlugins(plugins);
+}
+
+export function listPlugins(plugins: PluginRegistry): Plugin[] {
+  return Array.from(plugins.values());
+}
+
+export function getPlugin(plugins: PluginRegistry, name: string): Plugin | null {
+  return plugins.get(name) || null;
+}
+
+/**
+ * Find the plugin route matching a method and sub-path
+ */
+export function findPluginRoute(plugin: Plugin, method: string, routePath: string): PluginRoute | null {
+  const normalized = routePath.replace(/^\/+/, '');
+  return (
+    plugin.manifest.routes?.find(
+      (route) => route.method.toUpperCase() === method.toUpperCase() && route.path.replace(/^\/+/, '') === normalized
+    ) || null
+  );
+}
+
+/**
+ * Run a plugin with a single request and wait for its JSON response
+ */
+export function invokePlugin(plugin: Plugin, request: PluginRequest): Promise<PluginResponse> {
+  return new Promise((resolve, reject) => {
+    // Scratch space for the plugin, removed once it exits
+    const jobDir = createJobDir('automation', `${plugin.manifest.name}-${randomUUID()}`);
+    const proc = spawn(plugin.executablePath, [], {
+      cwd: plugin.directory,
+      stdio: ['pipe', 'pipe', 'pipe'],
+      env: { ...process.env, PLASMA_JOB_DIR: jobDir.path },
+    });
+    registerProcess(proc);
+
+    let stdout = '';
+    let stderr = '';
+
+    const timeout = setTimeout(() => {
+      proc.kill();
+      reject(new Error(`Plugin ${plugin.manifest.name} timed out`));
+    }, PLUGIN_TIMEOUT_MS);
+
+    proc.stdout.on('data', (data) => {
+      stdout += data.toString();
+    });
+
+    proc.stderr.on('data', (data) => {
+      stderr += data.toString();
+    });
+
+    proc.on('close', (code) => {
+      clearTimeout(timeout);
+      jobDir.cleanup();
+      if (code !== 0) {
+        reject(new Error(`Plugin ${plugin.manifest.name} failed: ${stderr || `exit code ${code}`}`));
+        return;
+      }
+
+      if (!stdout.trim()) {
+        resolve({});
+        return;
+      }
+
+      try {
+        resolve(JSON.parse(stdout) as PluginResponse);
+      } catch {
+        reject(new Error(`Plugin ${plugin.manifest.name} returned invalid JSON`));
+      }
+    });
+
+    proc.on('error', (err) => {
+      clearTimeout(timeout);
+      jobDir.cleanup();
+      reject(err);
+    });
+
+    // A plugin that exits or closes stdin before reading its request breaks the pipe
+    proc.stdin.on('error', (err) => {
+      clearTimeout(timeout);
+      proc.kill();
+      reject(new Error(`Plugin ${plugin.manifest.name} didn't read its request: ${err.message}`));
+    });
+
+    proc.stdin.end(JSON.stringify(request));
+  });
+}
+
+/**
+ * Notify every plugin subscribed to a build hook. Failures are logged only.
+ */
+export async function runBuildHooks(
+  plugins: PluginRegistry,
+  event: PluginBuildHook,
+  build: Record<string, unknown>
+): Promise<void> {
+  const subscribers = listPlugins(plugins).filter((p) => p.manifest.buildHooks?.includes(event));
+
+  await Promise.all(
+    subscribers.map(async (plugin) => {
+      try {
+        await invokePlugin(plugin, { type: 'build_hook', event, build });
+      } catch (err) {
+        console.error(`[plugins] Build hook ${event} failed for ${plugin.manifest.name}:`, err);
+      }
+    })
+  );
+}
@@ -0,0 +1,138 @@
+import * as zlib from 'zlib';
+
+/**
+ * Minimal PNG helpers, enough to generate, annotate and read frames without an image library
+ */
+
+const CRC_TABLE = Array.from({ length: 256 }, (_, n) => {
+  let c = n;
+  for (let k = 0; k < 8; k++) {
+    c = c & 1 ? 0xedb88320 ^ (c >>> 1) : c >>> 1;
+  }
+  return c >>> 0;
+});
+
+function crc32(data: Buffer): number {
+  let crc = 0xffffffff;
+  for (const byte of data) {
+    crc = CRC_TABLE[(crc ^ byte) & 0xff] ^ (crc >>> 8);
+  }
+  return (crc ^ 0xffffffff) >>> 0;
+}
+
+/**
+ * Encode a PNG chunk: length, type, data and CRC
+ */
+export function pngChunk(type: string, data: Buffer): Buffer {
+  const length = Buffer.alloc(4);
+  length.writeUInt32BE(data.length);
+  const body = Buffer.concat([Buffer.from(type, 'ascii'), data]);
+  const crc = Buffer.alloc(4);
+  crc.writeUInt32BE(crc32(body));
+  return Buffer.concat([length, body, crc]);
+}
+
+/**
+ * Encode RGB or RGBA pixels (rows of `width * channels` bytes) as an 8-bit PNG
+ */
+export function encodePng(image: DecodedPng): Buffer {
+  const stride = image.width * image.channels;
+  // Each row is a filter type byte (0, none) followed by the row's pixels
+  const filtered = Buffer.alloc((stride + 1) * image.height);
+  for (let y = 0; y < image.height; y++) {
+    image.pixels.copy(filtered, y * (stride + 1) + 1, y * stride, (y + 1) * stride);
+  }
+  const header = Buffer.alloc(13);
+  header.writeUInt32BE(image.width, 0);
+  header.writeUInt32BE(image.height, 4);
+  header[8] = 8; // bit depth
+  header[9] = image.channels === 4 ? 6 : 2; // truecolor RGBA or RGB
+  return Buffer.concat([
+    Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]),
+    pngChunk('IHDR', header),
+    pngChunk('IDAT', zlib.deflateSync(filtered)),
+    pngChunk('IEND', Buffer.alloc(0)),
+  ]);
+}
+
+export interface DecodedPng {
+  width: number;
+  height: number;
+  // 3 for RGB, 4 for RGBA
+  channels: 3 | 4;
+  // Unfiltered rows, `width * channels` bytes each
+  pixels: Buffer;
+}
+
+function paeth(a: number, b: number, c: number): number {
+  const p = a + b - c;
+  const pa = Math.abs(p - a);
+  const pb = Math.abs(p - b);
+  const pc = Math.abs(p - c);
+  return pa <= pb && pa <= pc ? a : pb <= pc ? b : c;
+}
+
+/**
+ * Decode an 8-bit, non-interlaced RGB or RGBA PNG, like simulator screenshots and fake frames
+ */
+export function decodePng(png: Buffer): DecodedPng {
+  if (png.toString('latin1', 1, 4) !== 'PNG') {
+    throw new Error('Not a PNG');
+  }
+
+  let width = 0;
+  let height = 0;
+  let channels: 3 | 4 = 3;
+  const idat: Buffer[] = [];
+  for (let offset = 8; offset < png.length; ) {
+    const length = png.readUInt32BE(offset);
+    const type = png.toString('ascii', offset + 4, offset + 8);
+    const data = png.subarray(offset + 8, offset + 8 + length);
+    if (type === 'IHDR') {
+      width = data.readUInt32BE(0);
+      height = data.readUInt32BE(4);
+      const [bitDepth, colorType, , , interlace] = data.subarray(8);
+      if (bitDepth !== 8 || (colorType !== 2 && colorType !== 6) || interlace !== 0) {
+        throw new Error(`Unsupported PNG: bit depth ${bitDepth}, color type ${colorType}, interlace ${interlace}`);
+      }
+      channels = colorType === 6 ? 4 : 3;
+    } else if (type === 'IDAT') {
+      idat.push(data);
+    } else if (type === 'IEND') {
+      break;
+    }
+    offset += 12 + length;
+  }
+
+  const filtered = zlib.inflateSync(Buffer.concat(idat));
+  const stride = width * channels;
+  const pixels = Buffer.alloc(stride * height);
+  for (let y = 0; y < height; y++) {
+    const filter = filtered[y * (stride + 1)];
+    const row = filtered.subarray(y * (stride + 1) + 1, (y + 1) * (stride + 1));
+    const out = y * stride;
+    const previous = out - stride;
+    for (let x = 0; x < stride; x++) {
+      const left = x >= channels ? pixels[out + x - channels] : 0;
+      const up = y > 0 ? pixels[previous + x] : 0;
+      const upLeft = y > 0 && x >= channels ? pixels[previous + x - channels] : 0;
+      let predictor = 0;
+      switch (filter) {
+        case 1:
+          predictor = left;
+          break;
+        case 2:
+          predictor = up;
+          break;
+        case 3:
+          predictor = (left + up) >> 1;
+          break;
+        case 4:
+          predictor = paeth(left, up, upLeft);
+          break;
+      }
+      pixels[out + x] = (row[x] + predictor) & 0xff;
+    }
+  }
+  return { width, height, channels, pixels };
+}
@@ -0,0 +1,488 @@
+import { randomUUID } from 'crypto';
+import { nextUpdatedAt, notificationListClauses } from './data-store';
+import type {
+  BuildRow,
+  DataStore,
+  NotificationRow,
+  NotifierRow,
+  ProjectKey,
+  SettingRow,
+  StoredDestination,
+  StoredProjectSettings,
+} from './data-store';
+import type { SecretInfo, UnifiedProject } from '../../shared/ipc-types';
+
+/**
+ * The data store in a Postgres database shared by several Plasma instances. Rows that belong to a
+ * project reference it by UUID; the numeric project IDs are Postgres's, so they're the same on
+ * every instance too. Columns mirror the SQLite ones, so the services parse rows the same way.
+ */
+
+// The subset of `pg` used here, so it stays an optional dependency
+interface PgQueryable {
+  query(text: string, values?: unknown[]): Promise<{ rows: any[]; rowCount: number | null }>;
+}
+
+interface PgClient extends PgQueryable {
+  release(): void;
+}
+
+interface PgPool extends PgQueryable {
+  connect(): Promise<PgClient>;
+  end(): Promise<void>;
+}
+
+function loadPg(): { Pool: new (options: { connectionString: string }) => PgPool } {
+  try {
+    return require('pg');
+  } catch {
+    throw new Error('A Postgres PLASMA_DATABASE_URL needs the `pg` package; install it next to Plasma');
+  }
+}
+
+const POSTGRES_SCHEMA = `
+  CREATE TABLE IF NOT EXISTS projects (
+    id SERIAL PRIMARY KEY,
+    uuid TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    xcode_path TEXT,
+    android_path TEXT,
+    notes TEXT,
+    last_opened_at TEXT,
+    created_at TEXT
+  );
+  CREATE TABLE IF NOT EXISTS project_build_destinations (
+    project_uuid TEXT PRIMARY KEY REFERENCES projects (uuid) ON DELETE CASCADE,
+    preset TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+  );
+  CREATE TABLE IF NOT EXISTS project_settings (
+    project_uuid TEXT PRIMARY KEY REFERENCES projects (uuid) ON DELETE CASCADE,
+    scheme TEXT,
+    configuration TEXT,
+    simulator_udid TEXT,
+    environment TEXT NOT NULL DEFAULT '{}',
+    updated_at TEXT NOT NULL
+  );
+  CREATE TABLE IF NOT EXISTS project_notifiers (
+    id SERIAL PRIMARY KEY,
+    project_uuid TEXT NOT NULL REFERENCES projects (uuid) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    events TEXT NOT NULL DEFAULT '[]',
+    template TEXT,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL
+  );
+  CREATE TABLE IF NOT EXISTS notifications (
+    id SERIAL PRIMARY KEY,
+    kind TEXT NOT NULL,
+    level TEXT NOT NULL DEFAULT 'info',
+    title TEXT NOT NULL,
+    message TEXT,
+    data TEXT,
+    read_at TEXT,
+    created_at TEXT NOT NULL
+  );
+  CREATE TABLE IF NOT EXISTS builds (
+    id TEXT PRIMARY KEY,
+    project_uuid TEXT REFERENCES projects (uuid) ON DELETE SET NULL,
+    project_path TEXT NOT NULL,
+    scheme TEXT NOT NULL,
+    configuration TEXT NOT NULL DEFAULT 'Debug',
+    destination TEXT NOT NULL,
+    status TEXT,
+    success INTEGER NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    warnings INTEGER NOT NULL DEFAULT 0,
+    errors INTEGER NOT NULL DEFAULT 0,
+    products TEXT NOT NULL DEFAULT '[]',
+    build_settings TEXT NOT NULL DEFAULT '{}',
+    started_at TEXT,
+    finished_at TEXT NOT NULL,
+    environment TEXT
+  );
+  CREATE TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+  );
+  CREATE TABLE IF NOT EXISTS secrets (
+    name TEXT PRIMARY KEY,
+    ciphertext TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+  );
+`;
+
+// Children of a project come back with the project's numeric ID, like they do from SQLite
+const NOTIFIER_SELECT = `
+  SELECT n.id, p.id AS project_id, n.kind, n.events, n.template, n.enabled, n.created_at
+  FROM project_notifiers n JOIN projects p ON p.uuid = n.project_uuid
+`;
+
+const BUILD_SELECT = `
+  SELECT b.id, p.id AS project_id, b.project_path, b.scheme, b.configuration, b.destination, b.status,
+    b.success, b.duration_ms, b.warnings, b.errors, b.products, b.build_settings, b.started_at,
+    b.finished_at, b.environment
+  FROM builds b LEFT JOIN projects p ON p.uuid = b.project_uuid
+`;
+
+export function createPostgresDataStore(url: string): DataStore {
+  const { Pool } = loadPg();
+  const pool = new Pool({ connectionString: url });
+  let schema: Promise<unknown> | null = null;
+
+  const ready = () => {
+    if (!schema) {
+      schema = pool.query(POSTGRES_SCHEMA).catch((err) => {
+        schema = null;
+        throw err;
+      });
+    }
+    return schema;
+  };
+
+  const query = async (text: string, values?: unknown[]) => {
+    await ready();
+    return pool.query(text, values);
+  };
+
+  // Read and write in one transaction. The advisory lock also covers rows that don't exist yet,
+  // which `SELECT ... FOR UPDATE` can't lock.
+  const locked = async <T>(lockKey: string, work: (client: PgClient) => Promise<T>): Promise<T> => {
+    await ready();
+    const client = await pool.connect();
+    try {
+      await client.query('BEGIN');
+      await client.query('SELECT pg_advisory_xact_lock(hashtext($1))', [lockKey]);
+      const result = await work(client);
+      await client.query('COMMIT');
+      return result;
+    } catch (err) {
+      await client.query('ROLLBACK').catch(() => undefined);
+      throw err;
+    } finally {
+      client.release();
+    }
+  };
+
+  const getProject = async (db: PgQueryable, id: number): Promise<UnifiedProject | null> => {
+    const { rows } = await db.query('SELECT * FROM projects WHERE id = $1', [id]);
+    return rows[0] || null;
+  };
+
+  const getDestination = async (db: PgQueryable, project: ProjectKey): Promise<StoredDestination | null> => {
+    const { rows } = await db.query(
+      'SELECT preset, updated_at FROM project_build_destinations WHERE project_uuid = $1',
+      [project.uuid]
+    );
+    return rows[0] ? { preset: rows[0].preset, updatedAt: rows[0].updated_at } : null;
+  };
+
+  const getProjectSettings = async (db: PgQueryable, project: ProjectKey): Promise<StoredProjectSettings | null> => {
+    const { rows } = await db.query('SELECT * FROM project_settings WHERE project_uuid = $1', [project.uuid]);
+    const row = rows[0];
+    if (!row) {
+      return null;
+    }
+    return {
+      scheme: row.scheme,
+      configuration: row.configuration,
+      simulatorUdid: row.simulator_udid,
+      environment: JSON.parse(row.environment),
+      updatedAt: row.updated_at,
+    };
+  };
+
+  const getSettingRow = async (db: PgQueryable, key: string): Promise<SettingRow | null> => {
+    const { rows } = await db.query('SELECT key, value, updated_at FROM settings WHERE key = $1', [key]);
+    return rows[0] || null;
+  };
+
+  return {
+    backend: 'postgres',
+
+    projects: {
+      async recent(limit) {
+        const { rows } = await query('SELECT * FROM projects ORDER BY last_opened_at DESC NULLS LAST LIMIT $1', [limit]);
+        return rows;
+      },
+      async all() {
+        const { rows } = await query('SELECT * FROM projects ORDER BY id');
+        return rows;
+      },
+      async count() {
+        const { rows } = await query('SELECT COUNT(*)::int AS count FROM projects');
+        return rows[0].count;
+      },
+      async get(id) {
+        await ready();
+        return getProject(pool, id);
+      },
+      async findByPath(projectPath) {
+        const { rows } = await query(
+          'SELECT * FROM projects WHERE xcode_path = $1 OR android_path = $1 ORDER BY last_opened_at DESC NULLS LAST LIMIT 1',
+          [projectPath]
+        );
+        return rows[0] || null;
+      },
+      async save(name, xcodePath, androidPath) {
+        // Locked on the paths, so two instances opening the same project don't both add it
+        return locked(`project:${xcodePath}:${androidPath}`, async (client) => {
+          const now = new Date().toISOString();
+          const { rows } = await client.query(
+            'SELECT id FROM projects WHERE xcode_path IS NOT DISTINCT FROM $1 AND android_path IS NOT DISTINCT FROM $2',
+            [xcodePath, androidPath]
+          );
+          if (rows[0]) {
+            await client.query('UPDATE projects SET name = $1, last_opened_at = $2 WHERE id = $3', [name, now, rows[0].id]);
+            return (await getProject(client, rows[0].id))!;
+          }
+          const inserted = await client.query(
+            'INSERT INTO projects (uuid, name, xcode_path, android_path, last_opened_at, created_at) VALUES ($1, $2, $3, $4, $5, $5) RETURNING *',
+            [randomUUID(), name, xcodePath, androidPath, now]
+          );
+          return inserted.rows[0];
+        });
+      },
+      async setNotes(id, notes) {
+        await query('UPDATE projects SET notes = $1 WHERE id = $2', [notes, id]);
+      },
+      async markOpened(id) {
+        await query('UPDATE projects SET last_opened_at = $1 WHERE id = $2', [new Date().toISOString(), id]);
+      },
+    },
+
+    projectDestinations: {
+      async get(project) {
+        await ready();
+        return getDestination(pool, project);
+      },
+      async set(project, preset, expectedUpdatedAt) {
+        return locked(`destination:${project.uuid}`, async (client) => {
+          const current = await getDestination(client, project);
+          if (expectedUpdatedAt !== undefined && (current?.updatedAt ?? null) !== expectedUpdatedAt) {
+            return false;
+          }
+          await client.query(`
+            INSERT INTO project_build_destinations (project_uuid, preset, updated_at) VALUES ($1, $2, $3)
+            ON CONFLICT (project_uuid) DO UPDATE SET preset = excluded.preset, updated_at = excluded.updated_at
+          `, [project.uuid, preset, nextUpdatedAt(current?.updatedAt ?? null)]);
+          return true;
+        });
+      },
+    },
+
+    projectSettings: {
+      async get(project) {
+        await ready();
+        return getProjectSettings(pool, project);
+      },
+      async update(project, changes, expectedUpdatedAt) {
+        return locked(`settings:${project.uuid}`, async (client) => {
+          const current = await getProjectSettings(client, project);
+          if (expectedUpdatedAt !== undefined && (current?.updatedAt ?? null) !== expectedUpdatedAt) {
+            return false;
+          }
+          const pick = <T>(change: T | undefined, stored: T): T => (change === undefined ? stored : change);
+          await client.query(`
+            INSERT INTO project_settings (project_uuid, scheme, configuration, simulator_udid, environment, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (project_uuid) DO UPDATE SET
+              scheme = excluded.scheme,
+              configuration = excluded.configuration,
+              simulator_udid = excluded.simulator_udid,
+              environment = excluded.environment,
+              updated_at = excluded.updated_at
+          `, [
+            project.uuid,
+            pick(changes.scheme, current?.scheme ?? null),
+            pick(changes.configuration, current?.configuration ?? null),
+            pick(changes.simulatorUdid, current?.simulatorUdid ?? null),
+            JSON.stringify(pick(changes.environment, current?.environment ?? {})),
+            nextUpdatedAt(current?.updatedAt ?? null),
+          ]);
+          return true;
+        });
+      },
+    },
+
+    notifiers: {
+      async list(project) {
+        const { rows } = await query(`${NOTIFIER_SELECT} WHERE n.project_uuid = $1 ORDER BY n.id`, [project.uuid]);
+        return rows as NotifierRow[];
+      },
+      async get(project, id) {
+        const { rows } = await query(`${NOTIFIER_SELECT} WHERE n.project_uuid = $1 AND n.id = $2`, [project.uuid, id]);
+        return (rows[0] as NotifierRow | undefined) || null;
+      },
+      async insert(project, notifier) {
+        const { rows } = await query(
+          'INSERT INTO project_notifiers (project_uuid, kind, events, template, enabled, created_at) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id',
+          [project.uuid, notifier.kind, notifier.events, notifier.template, notifier.enabled, notifier.created_at]
+        );
+        return { ...notifier, id: rows[0].id, project_id: project.id };
+      },
+      async delete(project, id) {
+        const { rowCount } = await query('DELETE FROM project_notifiers WHERE project_uuid = $1 AND id = $2', [project.uuid, id]);
+        return (rowCount ?? 0) > 0;
+      },
+      // Postgres databases are newer than plaintext webhook URLs
+      async plaintextWebhookUrls() {
+        return new Map();
+      },
+      async clearPlaintextWebhookUrl() {},
+    },
+
+    notifications: {
+      async insert(notification) {
+        const { rows } = await query(
+          'INSERT INTO notifications (kind, level, title, message, data, created_at) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *',
+          [notification.kind, notification.level, notification.title, notification.message, notification.data, notification.created_at]
+        );
+        return rows[0];
+      },
+      async list(listQuery, unreadOnly) {
+        const { where, orderBy, values } = notificationListClauses(listQuery, unreadOnly, (n) => `$${n}`);
+        const counted = await query(`SELECT COUNT(*)::int AS total FROM notifications ${where}`, values);
+        const { rows } = await query(`
+          SELECT * FROM notifications
+          ${where}
+          ORDER BY ${orderBy}
+          LIMIT $${values.length + 1} OFFSET $${values.length + 2}
+        `, [...values, Number.isFinite(listQuery.limit) ? listQuery.limit : null, listQuery.offset]);
+        return { rows, total: counted.rows[0].total };
+      },
+      async listSince(id) {
+        const { rows } = await query('SELECT * FROM notifications WHERE id > $1 ORDER BY id', [id]);
+        return rows;
+      },
+      async countUnread() {
+        const { rows } = await query('SELECT COUNT(*)::int AS count FROM notifications WHERE read_at IS NULL');
+        return rows[0].count;
+      },
+      async markRead(id) {
+        const { rowCount } = await query(
+          'UPDATE notifications SET read_at = COALESCE(read_at, $1) WHERE id = $2',
+          [new Date().toISOString(), id]
+        );
+        return (rowCount ?? 0) > 0;
+      },
+      async markAllRead() {
+        await query('UPDATE notifications SET read_at = $1 WHERE read_at IS NULL', [new Date().toISOString()]);
+      },
+    },
+
+    builds: {
+      async insert(build, project) {
+        await query(`
+          INSERT INTO builds (
+            id, project_uuid, project_path, scheme, configuration, destination, status, success,
+            duration_ms, warnings, errors, products, build_settings, started_at, finished_at, environment
+          )
+          VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+        `, [
+          build.id,
+          project?.uuid ?? null,
+          build.project_path,
+          build.scheme,
+          build.configuration,
+          build.destination,
+          build.status,
+          build.success,
+          build.duration_ms,
+          build.warnings,
+          build.errors,
+          build.products,
+          build.build_settings,
+          build.started_at,
+          build.finished_at,
+          build.environment,
+        ]);
+      },
+      async get(id) {
+        const { rows } = await query(`${BUILD_SELECT} WHERE b.id = $1`, [id]);
+        return (rows[0] as BuildRow | undefined) || null;
+      },
+      async list(project) {
+        const { rows } = project
+          ? await query(`${BUILD_SELECT} WHERE b.project_uuid = $1 ORDER BY b.finished_at DESC`, [project.uuid])
+          : await query(`${BUILD_SELECT} ORDER BY b.finished_at DESC`);
+        return rows;
+      },
+      async findByChecksum(sha256) {
+        const { rows } = await query(`${BUILD_SELECT} WHERE b.products LIKE $1 ORDER BY b.finished_at DESC`, [`%${sha256}%`]);
+        return rows;
+      },
+      async recentDurations(projectPath, scheme, destination, limit) {
+        const { rows } = await query(`
+          SELECT duration_ms FROM builds
+          WHERE project_path = $1 AND scheme = $2 AND destination = $3 AND success = 1
+          ORDER BY finished_at DESC LIMIT $4
+        `, [projectPath, scheme, destination, limit]);
+        return rows.map((row) => row.duration_ms as number);
+      },
+      async firstSucceeded() {
+        const { rows } = await query(`${BUILD_SELECT} WHERE b.success = 1 ORDER BY b.finished_at ASC LIMIT 1`);
+        return (rows[0] as BuildRow | undefined) || null;
+      },
+    },
+
+    settings: {
+      async get(key) {
+        await ready();
+        return getSettingRow(pool, key);
+      },
+      async list() {
+        const { rows } = await query('SELECT key, value, updated_at FROM settings ORDER BY key');
+        return rows;
+      },
+      async update(key, update) {
+        return locked(`setting:${key}`, async (client) => {
+          const current = (await getSettingRow(client, key))?.value ?? null;
+          const next = update(current);
+          if (next === current) {
+            return getSettingRow(client, key);
+          }
+          if (next === null) {
+            await client.query('DELETE FROM settings WHERE key = $1', [key]);
+          } else {
+            await client.query(`
+              INSERT INTO settings (key, value, updated_at) VALUES ($1, $2, $3)
+              ON CONFLICT (key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at
+            `, [key, next, new Date().toISOString()]);
+          }
+          return getSettingRow(client, key);
+        });
+      },
+    },
+
+    secrets: {
+      async list() {
+        const { rows } = await query(
+          'SELECT name, created_at AS "createdAt", updated_at AS "updatedAt" FROM secrets ORDER BY name'
+        );
+        return rows as SecretInfo[];
+      },
+      async getCiphertext(name) {
+        const { rows } = await query('SELECT ciphertext FROM secrets WHERE name = $1', [name]);
+        return rows[0]?.ciphertext ?? null;
+      },
+      async set(name, ciphertext) {
+        const now = new Date().toISOString();
+        await query(`
+          INSERT INTO secrets (name, ciphertext, created_at, updated_at) VALUES ($1, $2, $3, $3)
+          ON CONFLICT (name) DO UPDATE SET ciphertext = excluded.ciphertext, updated_at = excluded.updated_at
+        `, [name, ciphertext, now]);
+      },
+      async delete(name) {
+        const { rowCount } = await query('DELETE FROM secrets WHERE name = $1', [name]);
+        return (rowCount ?? 0) > 0;
+      },
+    },
+
+    async close() {
+      await pool.end();
+    },
+  };
+}
@@ -0,0 +1,35 @@
+import { runSimctl } from './simulator';
+import type { PrivacyAction, PrivacyService } from '../../shared/ipc-types';
+
+/**
+ * Privacy permissions of apps on a simulator, through `simctl privacy`, so automated flows don't
+ * stop at a permission dialog. Granting or revoking before the app asks answers the dialog ahead
+ * of time; resetting makes the app ask again.
+ */
+
+export const PRIVACY_ACTIONS: PrivacyAction[] = ['grant', 'revoke', 'reset'];
+
+// The services simctl knows; the camera isn't one of them
+export const PRIVACY_SERVICES: PrivacyService[] = [
+  'all',
+  'calendar',
+  'contacts-limited',
+  'contacts',
+  'location',
+  'location-always',
+  'photos-add',
+  'photos',
+  'media-library',
+  'microphone',
+  'motion',
+  'reminders',
+  'siri',
+];
+
+/**
+ * Change the permission of `bundleId` to `service`. Only resets work without a bundle ID, for
+ * every app on the simulator.
+ */
+export async function setPrivacy(udid: string, action: PrivacyAction, service: PrivacyService, bundleId?: string): Promise<void> {
+  await runSimctl(['privacy', udid, action, service, ...(bundleId ? [bundleId] : [])]);
+}
@@ -1,4 +1,7 @@
-import { ChildProcess } from 'child_process';
+import { ChildProcess, execFileSync } from 'child_process';
+import * as path from 'path';
+import { getDatabase } from './database';
+import type { OrphanedProcess } from '../../shared/ipc-types';
 
 /**
  * Process manager to track all spawned child processes
@@ -9,13 +12,19 @@ const activeProcesses = new Set<ChildProcess>();
 
 /**
  * Register a child process for tracking
- * The process will be automatically removed when it exits
+ * The process will be automatically removed when it exits.
+ * Long-running processes pass a `kind` to also be recorded in the database,
+ * so they can be killed on the next startup if Plasma crashes while they run.
  */
-export function registerProcess(proc: ChildProcess): void {
+export function registerProcess(proc: ChildProcess, kind?: string): void {
   activeProcesses.add(proc);
+  const persisted = kind && proc.pid ? persistProcess(proc.pid, path.basename(proc.spawnfile), kind) : false;
 
   const cleanup = () => {
     activeProcesses.delete(proc);
+    if (persisted) {
+      getDatabase().prepare('DELETE FROM process_registry WHERE pid = ? AND owner_pid = ?').run(proc.pid, process.pid);
+    }
   };
 
   proc.on('close', cleanup);
@@ -47,6 +56,79 @@ export function killAllProcesses(): void {
   }
 
   activeProcesses.clear();
+
+  // Everything was killed, so there is nothing for the next startup to clean up
+  try {
+    getDatabase().prepare('DELETE FROM process_registry WHERE owner_pid = ?').run(process.pid);
+  } catch (err) {
+    console.error('[process-manager] Failed to clear the process registry:', err);
+  }
+}
+
+function persistProcess(pid: number, command: string, kind: string): boolean {
+  try {
+    getDatabase().prepare(`
+      INSERT OR REPLACE INTO process_registry (pid, command, kind, owner_pid, started_at)
+      VALUES (?, ?, ?, ?, ?)
+    `).run(pid, command, kind, process.pid, new Date().toISOString());
+    return true;
+  } catch (err) {
+    console.error('[process-manager] Failed to record process:', err);
+    return false;
+  }
+}
+
+export function isAlive(pid: number): boolean {
+  try {
+    process.kill(pid, 0);
+    return true;
+  } catch (err) {
+    return (err as NodeJS.ErrnoException).code === 'EPERM';
+  }
+}
+
+/**
+ * Command name of a running process, to make sure a recorded PID wasn't reused by something else
+ */
+function commandOf(pid: number): string | null {
+  try {
+    return path.basename(execFileSync('ps', ['-o', 'comm=', '-p', String(pid)]).toString().trim()) || null;
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * Kill processes recorded by a previous Plasma instance that is no longer running.
+ * Returns what was found, whether or not it was still running.
+ */
+export function killOrphanedProcesses(): OrphanedProcess[] {
+  const db = getDatabase();
+  const rows = db.prepare(
+    'SELECT pid, command, kind, owner_pid, started_at FROM process_registry WHERE owner_pid != ?'
+  ).all(process.pid) as Array<{ pid: number; command: string; kind: string; owner_pid: number; started_at: string }>;
+
+  const orphans: OrphanedProcess[] = [];
+  for (const row of rows) {
+    if (isAlive(row.owner_pid)) {
+      // Another Plasma instance still owns it
+      continue;
+    }
+
+    let killed = false;
+    if (isAlive(row.pid) && commandOf(row.pid) === row.command) {
+      try {
+        process.kill(row.pid, 'SIGKILL');
+        killed = true;
+      } catch (err) {
+        console.error(`[process-manager] Failed to kill orphaned ${row.command} (${row.pid}):`, err);
+      }
+    }
+    orphans.push({ pid: row.pid, command: row.command, kind: row.kind, startedAt: row.started_at, killed });
+    db.prepare('DELETE FROM process_registry WHERE pid = ? AND owner_pid = ?').run(row.pid, row.owner_pid);
+  }
+
+  return orphans;
 }
 
 /**
@@ -1,18 +1,6 @@
 import * as fs from 'fs';
 import * as path from 'path';
-
-export interface XcodeValidationResult {
-  valid: boolean;
-  path: string | null;
-  type: 'workspace' | 'project' | null;
-  error?: string;
-}
-
-export interface AndroidValidationResult {
-  valid: boolean;
-  path: string | null;
-  error?: string;
-}
+import type { XcodeValidationResult, AndroidValidationResult } from '../../shared/ipc-types';
 
 /**
  * Validate an Xcode project path
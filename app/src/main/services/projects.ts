@@ -1,14 +1,6 @@
 import * as fs from 'fs';
 import * as path from 'path';
-import type { Platform, ProjectType } from '../../shared/ipc-types';
-
-export interface Project {
-  path: string;
-  name: string;
-  type: ProjectType;
-  platforms: Platform[];
-  valid: boolean;
-}
+import type { Platform, ProjectType, Project } from '../../shared/ipc-types';
 
 /**
  * Check if a path points directly to a project file/bundle
@@ -146,3 +138,33 @@ export function detectProject(projectPath: string): Project | null {
 
   return null;
 }
+
+// README names, in order of preference
+const README_NAMES = ['README.md', 'Readme.md', 'readme.md', 'README.markdown', 'README'];
+// How many directories above a project file its README is looked for, e.g. for `ios/App.xcodeproj`
+const README_MAX_PARENTS = 2;
+
+/**
+ * The README closest to a project's Xcode or Android project: next to it, or in a parent
+ * directory up to the repository root
+ */
+export function findProjectReadme(projectPaths: Array<string | null>): string | null {
+  for (const projectPath of projectPaths) {
+    if (!projectPath || !fs.existsSync(projectPath)) {
+      continue;
+    }
+    // Project bundles and build files are inside the directory to start from
+    let dir = isProjectPath(projectPath) || !fs.statSync(projectPath).isDirectory() ? path.dirname(projectPath) : projectPath;
+    for (let level = 0; level <= README_MAX_PARENTS; level++) {
+      const readme = README_NAMES.map((name) => path.join(dir, name)).find((candidate) => fs.existsSync(candidate));
+      if (readme) {
+        return readme;
+      }
+      if (fs.existsSync(path.join(dir, '.git')) || path.dirname(dir) === dir) {
+        break;
+      }
+      dir = path.dirname(dir);
+    }
+  }
+  return null;
+}
@@ -0,0 +1,99 @@
+import { createHash } from 'crypto';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { runCommand } from './simulator';
+import { readVersion } from './instance';
+import type { BuildEnvironment, BuildGitInfo } from '../../shared/ipc-types';
+
+/**
+ * Provenance of build products, so QA can confirm the .app they test is exactly the one a
+ * build produced: checksums of the products, and the machine, Xcode and commit they were built with.
+ *
+ * An .app is a directory, so its checksum is that of a listing of its files, the one
+ *
+ *   cd App.app && find . -type f | LC_ALL=C sort | xargs shasum -a 256 | shasum -a 256
+ *
+ * prints: a line of `<sha256 of the file>  ./<path>` per regular file, sorted by path. Symlinks
+ * aren't followed. A plain file's checksum is that of its content.
+ */
+
+function hashFile(file: string): Promise<string> {
+  return new Promise((resolve, reject) => {
+    const hash = createHash('sha256');
+    fs.createReadStream(file)
+      .on('data', (chunk) => hash.update(chunk))
+      .on('end', () => resolve(hash.digest('hex')))
+      .on('error', reject);
+  });
+}
+
+async function listFiles(dir: string, prefix: string): Promise<string[]> {
+  const files: string[] = [];
+  for (const entry of await fs.promises.readdir(dir, { withFileTypes: true })) {
+    const relative = `${prefix}/${entry.name}`;
+    if (entry.isDirectory()) {
+      files.push(...(await listFiles(path.join(dir, entry.name), relative)));
+    } else if (entry.isFile()) {
+      files.push(relative);
+    }
+  }
+  return files;
+}
+
+/**
+ * SHA-256 of a build product, a bundle directory or a plain file, as hex
+ */
+export async function hashArtifact(artifactPath: string): Promise<string> {
+  if (!(await fs.promises.stat(artifactPath)).isDirectory()) {
+    return hashFile(artifactPath);
+  }
+  // Byte order, like `LC_ALL=C sort`
+  const files = (await listFiles(artifactPath, '.')).sort((a, b) => Buffer.compare(Buffer.from(a), Buffer.from(b)));
+  const listing = createHash('sha256');
+  for (const file of files) {
+    listing.update(`${await hashFile(path.join(artifactPath, file))}  ${file}\n`);
+  }
+  return listing.digest('hex');
+}
+
+async function readGitInfo(projectPath: string): Promise<BuildGitInfo | null> {
+  // The project is a .xcodeproj or .xcworkspace; git runs in the directory containing it
+  const dir = path.dirname(projectPath);
+  try {
+    const commit = (await runCommand('git', ['-C', dir, 'rev-parse', 'HEAD'])).trim();
+    const branch = (await runCommand('git', ['-C', dir, 'rev-parse', '--abbrev-ref', 'HEAD'])).trim();
+    const status = await runCommand('git', ['-C', dir, 'status', '--porcelain', '--untracked-files=no']);
+    return { commit, branch: branch === 'HEAD' ? null : branch, dirty: status.trim() !== '' };
+  } catch {
+    // Not in a repository, or git isn't installed
+    return null;
+  }
+}
+
+async function readXcodeVersion(): Promise<string | null> {
+  try {
+    // "Xcode 16.0\nBuild version 16A242d"
+    return (await runCommand('xcodebuild', ['-version'])).trim().split('\n').join(' ');
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * What a build of `projectPath` runs with, read when it starts so the commit is the one built
+ */
+export async function collectBuildEnvironment(projectPath: string): Promise<BuildEnvironment> {
+  const [xcodeVersion, git] = await Promise.all([readXcodeVersion(), readGitInfo(projectPath)]);
+  return {
+    machine: {
+      hostname: os.hostname(),
+      platform: process.platform,
+      osRelease: os.release(),
+      arch: os.arch(),
+      plasmaVersion: readVersion(),
+    },
+    xcodeVersion,
+    git,
+  };
+}
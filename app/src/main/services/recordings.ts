@@ -0,0 +1,270 @@
+import { spawn } from 'child_process';
+import * as fs from 'fs';
+import * as path from 'path';
+import { randomUUID } from 'crypto';
+import { getDatabase } from './database';
+import { startBackendRecording } from './simulator';
+import { registerProcess } from './process-manager';
+import { holdKeepAwake, releaseKeepAwake } from './keep-awake';
+import { createJobDir, JobDir } from './workspace';
+import { getArtifactStore } from './storage';
+import { multipartFrame, MULTIPART_CONTENT_TYPE } from './mjpeg';
+import type { Watermarker } from './watermark';
+import type { Recording, RecordingSource, RecordingStatus } from '../../shared/ipc-types';
+
+/**
+ * Screen recordings of simulators, for sharing bug repros from the web UI. A recording runs
+ * `simctl io recordVideo` into a job directory until it's stopped, then the video is moved
+ * to the artifact store as `recordings/<id>.mp4`. Recordings are listed in the database.
+ *
+ * A `stream` recording instead captures the frames streamed to the browser, artifacts and
+ * all, as `recordings/<id>.mjpeg`. It ends when it's stopped or the stream stops.
+ */
+
+interface DbRecording {
+  id: string;
+  udid: string;
+  source: string;
+  status: string;
+  error: string | null;
+  size_bytes: number | null;
+  duration_ms: number | null;
+  started_at: string;
+  finished_at: string | null;
+}
+
+interface ActiveRecording {
+  id: string;
+  source: RecordingSource;
+  startedAt: number;
+  jobDir: JobDir;
+  file: string;
+  // Null until the recorder reports that it started
+  stop: (() => Promise<void>) | null;
+}
+
+// simctl prints this to stderr once frames are being written
+const RECORDING_STARTED = /Recording started/;
+// Give up waiting for that line after this long and assume it's recording
+const START_TIMEOUT_MS = 10000;
+
+// Stream recordings are stored as a multipart stream, so a browser can play them back in an <img>
+export const STREAM_RECORDING_CONTENT_TYPE = MULTIPART_CONTENT_TYPE;
+
+const activeRecordings = new Map<string, ActiveRecording>();
+// Files that frames streamed from a simulator are written to, and the watermark drawn into them, by UDID
+const streamCaptures = new Map<string, { out: fs.WriteStream; watermark: Watermarker | null }>();
+
+export class RecordingError extends Error {}
+
+export function recordingKey(id: string, source: RecordingSource = 'simctl'): string {
+  return `recordings/${path.basename(id)}.${source === 'stream' ? 'mjpeg' : 'mp4'}`;
+}
+
+function parseRecording(record: DbRecording): Recording {
+  return {
+    id: record.id,
+    udid: record.udid,
+    source: record.source as RecordingSource,
+    status: record.status as RecordingStatus,
+    error: record.error,
+    sizeBytes: record.size_bytes,
+    durationMs: record.duration_ms,
+    startedAt: record.started_at,
+    finishedAt: record.finished_at,
+  };
+}
+
+/**
+ * Run `simctl io recordVideo` until the returned function is called
+ */
+async function startSimctlRecording(udid: string, file: string): Promise<() => Promise<void>> {
+  const proc = spawn('xcrun', ['simctl', 'io', udid, 'recordVideo', '--codec=h264', '--force', file]);
+  registerProcess(proc, 'recording');
+
+  let stderr = '';
+  const exited = new Promise<number | null>((resolve) => {
+    proc.on('close', resolve);
+    proc.on('error', (err) => {
+      stderr += err.message;
+      resolve(null);
+    });
+  });
+
+  await new Promise<void>((resolve, reject) => {
+    const timer = setTimeout(resolve, START_TIMEOUT_MS);
+    proc.stderr.on('data', (data) => {
+      stderr += data.toString();
+      if (RECORDING_STARTED.test(stderr)) {
+        clearTimeout(timer);
+        resolve();
+      }
+    });
+    exited.then(() => {
+      clearTimeout(timer);
+      reject(new Error(stderr.trim() || 'simctl exited before recording started'));
+    });
+  });
+
+  return async () => {
+    // SIGINT makes simctl finish writing the file
+    proc.kill('SIGINT');
+    await exited;
+    if (!fs.existsSync(file)) {
+      throw new Error(stderr.trim() || 'simctl did not write a video');
+    }
+  };
+}
+
+/**
+ * Write frames streamed from a simulator to `file`, with `watermark` drawn into them, until the
+ * returned function is called
+ */
+function startStreamCapture(udid: string, file: string, watermark: Watermarker | null): () => Promise<void> {
+  const out = fs.createWriteStream(file);
+  out.on('error', (err) => console.error(`[recordings] Failed to write stream recording of ${udid}:`, err));
+  streamCaptures.set(udid, { out, watermark });
+  return async () => {
+    streamCaptures.delete(udid);
+    await new Promise<void>((resolve) => out.end(resolve));
+  };
+}
+
+/**
+ * Append a frame to the simulator's stream recording, if it has one. Called with every frame
+ * received from the simulator; the recording's own watermark is drawn into it here, and frames
+ * it can't be drawn into are left out.
+ */
+export function recordStreamFrame(udid: string, frame: Buffer, format: 'jpeg' | 'png'): void {
+  const capture = streamCaptures.get(udid);
+  if (!capture) {
+    return;
+  }
+  const recorded = capture.watermark ? capture.watermark(frame, format) : frame;
+  if (recorded) {
+    capture.out.write(multipartFrame(recorded, format));
+  }
+}
+
+export function isRecording(udid: string, source?: RecordingSource): boolean {
+  const active = activeRecordings.get(udid);
+  return !!active && (!source || active.source === source);
+}
+
+/**
+ * Start recording a simulator's screen. Only one recording runs per simulator. `watermark` is
+ * drawn into every recorded frame, which only stream recordings can do.
+ */
+export async function startRecording(
+  udid: string,
+  source: RecordingSource = 'simctl',
+  watermark: Watermarker | null = null
+): Promise<Recording> {
+  if (activeRecordings.has(udid)) {
+    throw new RecordingError(`${udid} is already being recorded`);
+  }
+  if (watermark && source !== 'stream') {
+    throw new RecordingError('Only stream recordings can be watermarked; record with source "stream"');
+  }
+
+  const id = randomUUID();
+  const jobDir = createJobDir('recording', id);
+  const file = path.join(jobDir.path, source === 'stream' ? 'recording.mjpeg' : 'recording.mp4');
+  const startedAt = new Date();
+  const placeholder: ActiveRecording = { id, source, startedAt: startedAt.getTime(), jobDir, file, stop: null };
+  // Claim the simulator before awaiting so concurrent starts can't both begin
+  activeRecordings.set(udid, placeholder);
+  getDatabase()
+    .prepare('INSERT INTO recordings (id, udid, source, status, started_at) VALUES (?, ?, ?, ?, ?)')
+    .run(id, udid, source, 'recording', startedAt.toISOString());
+
+  try {
+    placeholder.stop =
+      source === 'stream'
+        ? startStreamCapture(udid, file, watermark)
+        : await (startBackendRecording(udid, file) ?? startSimctlRecording(udid, file));
+  } catch (err) {
+    activeRecordings.delete(udid);
+    getDatabase().prepare('DELETE FROM recordings WHERE id = ?').run(id);
+    jobDir.cleanup();
+    throw err;
+  }
+
+  holdKeepAwake(`recording:${udid}`);
+  console.log(`[recordings] Recording ${udid} from ${source} (${id})`);
+  return getRecording(id)!;
+}
+
+/**
+ * Stop a simulator's recording and store the video. A recording whose video couldn't be
+ * written is kept as `failed` with the error.
+ */
+export async function stopRecording(udid: string): Promise<Recording> {
+  const active = activeRecordings.get(udid);
+  if (!active) {
+    throw new RecordingError(`${udid} is not being recorded`);
+  }
+  if (!active.stop) {
+    throw new RecordingError(`The recording of ${udid} is still starting`);
+  }
+  const { stop } = active;
+  activeRecordings.delete(udid);
+  releaseKeepAwake(`recording:${udid}`);
+
+  const finishedAt = new Date();
+  const durationMs = finishedAt.getTime() - active.startedAt;
+  try {
+    await stop();
+    const sizeBytes = fs.statSync(active.file).size;
+    await getArtifactStore().importFile(recordingKey(active.id, active.source), active.file);
+    getDatabase()
+      .prepare('UPDATE recordings SET status = ?, size_bytes = ?, duration_ms = ?, finished_at = ? WHERE id = ?')
+      .run('finished', sizeBytes, durationMs, finishedAt.toISOString(), active.id);
+  } catch (err) {
+    console.error(`[recordings] Failed to finish recording ${active.id}:`, err);
+    getDatabase()
+      .prepare('UPDATE recordings SET status = ?, error = ?, duration_ms = ?, finished_at = ? WHERE id = ?')
+      .run('failed', err instanceof Error ? err.message : String(err), durationMs, finishedAt.toISOString(), active.id);
+  } finally {
+    active.jobDir.cleanup();
+  }
+  return getRecording(active.id)!;
+}
+
+/**
+ * Mark a recording the previous instance didn't finish as failed. Called by startup recovery.
+ */
+export function markRecordingInterrupted(id: string): void {
+  getDatabase()
+    .prepare("UPDATE recordings SET status = 'failed', error = ?, finished_at = ? WHERE id = ? AND status = 'recording'")
+    .run('Recording interrupted because Plasma exited unexpectedly', new Date().toISOString(), id);
+}
+
+/**
+ * Recordings, newest first
+ */
+export function listRecordings(): Recording[] {
+  const records = getDatabase().prepare('SELECT * FROM recordings ORDER BY started_at DESC').all() as DbRecording[];
+  return records.map(parseRecording);
+}
+
+export function getRecording(id: string): Recording | null {
+  const record = getDatabase().prepare('SELECT * FROM recordings WHERE id = ?').get(id) as DbRecording | undefined;
+  return record ? parseRecording(record) : null;
+}
+
+/**
+ * Delete a recording and its video. Recordings in progress are stopped first.
+ */
+export async function deleteRecording(id: string): Promise<boolean> {
+  const recording = getRecording(id);
+  if (!recording) {
+    return false;
+  }
+  if (activeRecordings.get(recording.udid)?.id === id) {
+    await stopRecording(recording.udid);
+  }
+  await getArtifactStore().delete(recordingKey(id, recording.source));
+  getDatabase().prepare('DELETE FROM recordings WHERE id = ?').run(id);
+  return true;
+}
@@ -0,0 +1,63 @@
+import * as fs from 'fs';
+import { killOrphanedProcesses } from './process-manager';
+import { recoverJobDirs } from './workspace';
+import { getBuildLogPath, storeBuildLog } from './xcode';
+import { recordNotification } from './notifications';
+import { markRecordingInterrupted } from './recordings';
+import { discardLeftoverSnapshots } from './undo';
+import type { RecoveryReport } from '../../shared/ipc-types';
+
+let lastReport: RecoveryReport | null = null;
+
+/**
+ * Reconcile state left behind by an unclean shutdown: kill processes the previous
+ * instance spawned and remove the directories of jobs it didn't finish. Builds that
+ * were running are marked as interrupted in their logs, and recordings as failed. Simulators it
+ * cloned to undo erases with are deleted. Called once at startup.
+ */
+export function runStartupRecovery(): RecoveryReport {
+  const orphanedProcesses = killOrphanedProcesses();
+  const interruptedJobs = recoverJobDirs()
+    .filter((dir) => dir.kind && dir.id)
+    .map((dir) => ({ kind: dir.kind!, id: dir.id!, createdAt: dir.createdAt }));
+  const discardedSnapshots = discardLeftoverSnapshots();
+
+  for (const job of interruptedJobs) {
+    if (job.kind === 'build') {
+      try {
+        fs.appendFileSync(getBuildLogPath(job.id), 'error: Build interrupted because Plasma exited unexpectedly\n');
+        storeBuildLog(job.id);
+      } catch (err) {
+        console.error(`[recovery] Failed to mark build ${job.id} as interrupted:`, err);
+      }
+    }
+    if (job.kind === 'recording') {
+      markRecordingInterrupted(job.id);
+    }
+  }
+
+  lastReport = { recoveredAt: new Date().toISOString(), orphanedProcesses, interruptedJobs, discardedSnapshots };
+
+  const killed = orphanedProcesses.filter((orphan) => orphan.killed).length;
+  if (killed > 0 || interruptedJobs.length > 0 || discardedSnapshots.length > 0) {
+    console.warn(
+      `[recovery] Killed ${killed} orphaned processes, ${interruptedJobs.length} jobs were interrupted, deleted ${discardedSnapshots.length} undo snapshots`
+    );
+    recordNotification({
+      kind: 'startup_recovery',
+      level: 'warning',
+      title: 'Recovered from an unclean shutdown',
+      message: `Killed ${killed} orphaned processes; ${interruptedJobs.length} jobs were interrupted; deleted ${discardedSnapshots.length} undo snapshots`,
+      data: { ...lastReport },
+    }).catch((err) => console.error('[recovery] Failed to record notification:', err));
+  }
+
+  return lastReport;
+}
+
+/**
+ * Report of the recovery run at startup, null before it ran
+ */
+export function getRecoveryReport(): RecoveryReport | null {
+  return lastReport;
+}
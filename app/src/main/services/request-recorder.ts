@@ -0,0 +1,212 @@
+import type * as http from 'http';
+import type { RecordedRequest, RequestRecordingStatus } from '../../shared/ipc-types';
+
+/**
+ * Records API requests and their responses in a ring buffer while turned on, to see what a
+ * client actually sent when something "didn't work". Credentials are redacted before anything
+ * is kept: auth headers, JSON fields and query parameters with sensitive names, and secret values.
+ */
+
+export interface RequestRecordingSettings {
+  // Record from startup instead of waiting to be turned on through the API
+  enabled: boolean;
+  // Exchanges kept; older ones are dropped
+  capacity: number;
+  // Bytes of each request and response body kept
+  maxBodyBytes: number;
+  // Field names to redact besides the built-in ones, compared ignoring case, `_` and `-`
+  redactKeys: string[];
+}
+
+const REDACTED = '[redacted]';
+
+const REDACTED_HEADERS = ['authorization', 'cookie', 'set-cookie', 'proxy-authorization', 'x-plasma-export-passphrase'];
+
+const REDACTED_KEYS = [
+  'password',
+  'passphrase',
+  'secret',
+  'token',
+  'apikey',
+  'privatekey',
+  'authorization',
+  'webhookurl',
+  'encryptedwebhookurl',
+];
+
+// Routes whose request bodies are nothing but credentials
+const REDACTED_BODY_PATHS = [/^\/api\/secrets\//];
+
+// The recorder's own endpoints would fill the buffer with reads of the buffer
+const SKIPPED_PATHS = [/^\/api\/admin\/requests/];
+
+const TEXT_CONTENT_TYPES = /^(application\/json|text\/(?!event-stream))/;
+
+let settings: RequestRecordingSettings = { enabled: false, capacity: 200, maxBodyBytes: 16 * 1024, redactKeys: [] };
+let enabled = false;
+let recorded: RecordedRequest[] = [];
+let nextId = 1;
+
+const requestBodies = new WeakMap<http.IncomingMessage, string>();
+
+const normalizeKey = (key: string) => key.toLowerCase().replace(/[-_]/g, '');
+
+function isRedactedKey(key: string): boolean {
+  const normalized = normalizeKey(key);
+  return REDACTED_KEYS.includes(normalized) || settings.redactKeys.some((extra) => normalizeKey(extra) === normalized);
+}
+
+function redactValue(value: unknown): unknown {
+  if (Array.isArray(value)) {
+    return value.map(redactValue);
+  }
+  if (value && typeof value === 'object') {
+    return Object.fromEntries(
+      Object.entries(value).map(([key, nested]) => [key, isRedactedKey(key) ? REDACTED : redactValue(nested)])
+    );
+  }
+  return value;
+}
+
+/**
+ * Redact a body: JSON is parsed and sensitive fields replaced. Text that doesn't parse, like a
+ * truncated body, has the string values of sensitive fields replaced where they can be found.
+ */
+export function redactBody(body: string): string {
+  try {
+    return JSON.stringify(redactValue(JSON.parse(body)));
+  } catch {
+    return body.replace(/"([^"\\]+)"(\s*:\s*)"(?:[^"\\]|\\.)*("|$)/g, (match, key: string, separator: string, close: string) =>
+      isRedactedKey(key) ? `"${key}"${separator}"${REDACTED}${close}` : match
+    );
+  }
+}
+
+function redactHeaders(headers: http.IncomingHttpHeaders | http.OutgoingHttpHeaders): Record<string, string> {
+  const result: Record<string, string> = {};
+  for (const [name, value] of Object.entries(headers)) {
+    if (value === undefined) {
+      continue;
+    }
+    result[name] = REDACTED_HEADERS.includes(name.toLowerCase()) ? REDACTED : Array.isArray(value) ? value.join(', ') : String(value);
+  }
+  return result;
+}
+
+export function configureRequestRecording(next: RequestRecordingSettings): void {
+  settings = next;
+  enabled = next.enabled;
+  recorded = recorded.slice(-next.capacity);
+}
+
+export function setRequestRecording(on: boolean): RequestRecordingStatus {
+  enabled = on;
+  return getRequestRecordingStatus();
+}
+
+export function getRequestRecordingStatus(): RequestRecordingStatus {
+  return { enabled, capacity: settings.capacity, count: recorded.length };
+}
+
+/**
+ * Recorded exchanges, newest first
+ */
+export function getRecordedRequests(): RecordedRequest[] {
+  return [...recorded].reverse();
+}
+
+export function clearRecordedRequests(): void {
+  recorded = [];
+}
+
+/**
+ * Keep the raw body of a request for its recording. Bodies are read by the route handlers,
+ * so they hand them over here instead of the recorder reading the stream itself.
+ */
+export function noteRequestBody(req: http.IncomingMessage, body: string): void {
+  if (enabled) {
+    requestBodies.set(req, body);
+  }
+}
+
+/**
+ * Start recording an exchange if recording is on. The response body is captured as it's written
+ * and the exchange is stored once the response is done.
+ */
+export function recordExchange(req: http.IncomingMessage, res: http.ServerResponse, url: URL): void {
+  if (!enabled || SKIPPED_PATHS.some((pattern) => pattern.test(url.pathname))) {
+    return;
+  }
+
+  const startedAt = new Date();
+  const chunks: Buffer[] = [];
+  let size = 0;
+
+  const capture = (chunk: unknown) => {
+    if (typeof chunk !== 'string' && !Buffer.isBuffer(chunk) && !(chunk instanceof Uint8Array)) {
+      return;
+    }
+    const buffer = typeof chunk === 'string' ? Buffer.from(chunk) : Buffer.from(chunk.buffer, chunk.byteOffset, chunk.byteLength);
+    if (size < settings.maxBodyBytes) {
+      chunks.push(buffer.subarray(0, settings.maxBodyBytes - size));
+    }
+    size += buffer.length;
+  };
+
+  // Headers passed to writeHead, which getHeaders() doesn't include
+  let headHeaders: http.OutgoingHttpHeaders = {};
+  const writeHead = res.writeHead.bind(res) as (...args: unknown[]) => http.ServerResponse;
+  res.writeHead = ((statusCode: number, ...args: unknown[]) => {
+    const headers = args.find((arg) => arg && typeof arg === 'object' && !Array.isArray(arg));
+    if (headers) {
+      headHeaders = Object.fromEntries(
+        Object.entries(headers as http.OutgoingHttpHeaders).map(([name, value]) => [name.toLowerCase(), value])
+      );
+    }
+    return writeHead(statusCode, ...args);
+  }) as typeof res.writeHead;
+  const write = res.write.bind(res) as (...args: unknown[]) => boolean;
+  const end = res.end.bind(res) as (...args: unknown[]) => http.ServerResponse;
+  res.write = ((chunk: unknown, ...args: unknown[]) => {
+    capture(chunk);
+    return write(chunk, ...args);
+  }) as typeof res.write;
+  res.end = ((chunk?: unknown, ...args: unknown[]) => {
+    capture(chunk);
+    return end(chunk, ...args);
+  }) as typeof res.end;
+
+  res.once('close', () => {
+    const rawRequestBody = requestBodies.get(req) ?? null;
+    const redactRequestBody = REDACTED_BODY_PATHS.some((pattern) => pattern.test(url.pathname));
+    const responseHeaders = { ...res.getHeaders(), ...headHeaders };
+    const contentType = String(responseHeaders['content-type'] || '');
+    const requestTruncated = rawRequestBody !== null && Buffer.byteLength(rawRequestBody) > settings.maxBodyBytes;
+
+    recorded.push({
+      id: nextId++,
+      method: req.method || 'GET',
+      path: url.pathname,
+      query: Object.fromEntries(
+        Array.from(url.searchParams).map(([key, value]) => [key, isRedactedKey(key) ? REDACTED : value])
+      ),
+      status: res.statusCode,
+      durationMs: Date.now() - startedAt.getTime(),
+      startedAt: startedAt.toISOString(),
+      requestHeaders: redactHeaders(req.headers),
+      requestBody:
+        rawRequestBody === null || rawRequestBody === ''
+          ? null
+          : redactRequestBody
+            ? REDACTED
+            : Buffer.from(redactBody(rawRequestBody)).subarray(0, settings.maxBodyBytes).toString('utf-8'),
+      responseHeaders: redactHeaders(responseHeaders),
+      // Binary and streamed responses are only described by their headers
+      responseBody: TEXT_CONTENT_TYPES.test(contentType) && size > 0 ? redactBody(Buffer.concat(chunks).toString('utf-8')) : null,
+      truncated: requestTruncated || size > settings.maxBodyBytes,
+    });
+    if (recorded.length > settings.capacity) {
+      recorded.splice(0, recorded.length - settings.capacity);
+    }
+  });
+}
@@ -0,0 +1,103 @@
+import { runCommand, sampleBackendResources } from './simulator';
+import type { AppResourceSample } from '../../shared/ipc-types';
+
+export const MIN_SAMPLE_INTERVAL_MS = 250;
+export const MAX_SAMPLE_INTERVAL_MS = 10000;
+
+/**
+ * PID of an app running on a simulator. Simulator apps are host processes,
+ * registered with the simulator's launchd as `UIKitApplication:<bundle id>[...]`.
+ */
+async function findAppPid(udid: string, bundleId: string): Promise<number | null> {
+  const output = await runCommand('xcrun', ['simctl', 'spawn', udid, 'launchctl', 'list']);
+  for (const line of output.split('\n')) {
+    const [pid, , label] = line.trim().split(/\s+/);
+    if (label?.startsWith(`UIKitApplication:${bundleId}[`) && /^\d+$/.test(pid)) {
+      return Number(pid);
+    }
+  }
+  return null;
+}
+
+/**
+ * CPU and resident memory of a host process, or null once it's gone
+ */
+async function sampleProcess(pid: number): Promise<{ cpuPercent: number; rssBytes: number } | null> {
+  try {
+    const [cpu, rssKb] = (await runCommand('ps', ['-o', '%cpu=,rss=', '-p', String(pid)])).trim().split(/\s+/);
+    return { cpuPercent: Number(cpu), rssBytes: Number(rssKb) * 1024 };
+  } catch {
+    // ps exits with 1 when there's no such process
+    return null;
+  }
+}
+
+/**
+ * Sample the CPU and memory usage of the app with `bundleId` on a simulator.
+ * Returns null when the app isn't running.
+ */
+export async function sampleAppResources(udid: string, bundleId: string, knownPid?: number): Promise<AppResourceSample | null> {
+  const backendSample = sampleBackendResources(udid, bundleId);
+  if (backendSample) {
+    return backendSample;
+  }
+
+  const pid = knownPid ?? (await findAppPid(udid, bundleId));
+  if (pid === null) {
+    return null;
+  }
+  const usage = await sampleProcess(pid);
+  if (!usage) {
+    return null;
+  }
+  return { udid, bundleId, pid, ...usage, timestamp: new Date().toISOString() };
+}
+
+/**
+ * Sample an app every `intervalMs` until the returned function is called. `onMissing` is
+ * called for every sample taken while the app isn't running, e.g. between relaunches.
+ */
+export function watchAppResources(
+  udid: string,
+  bundleId: string,
+  intervalMs: number,
+  onSample: (sample: AppResourceSample) => void,
+  onMissing: () => void
+): () => void {
+  let stopped = false;
+  let timer: ReturnType<typeof setTimeout> | null = null;
+  let pid: number | undefined;
+
+  const tick = async () => {
+    try {
+      // Reuse the PID until the process goes away, then look the app up again
+      let sample = await sampleAppResources(udid, bundleId, pid);
+      if (!sample && pid !== undefined) {
+        pid = undefined;
+        sample = await sampleAppResources(udid, bundleId);
+      }
+      if (stopped) {
+        return;
+      }
+      if (sample) {
+        pid = sample.pid;
+        onSample(sample);
+      } else {
+        onMissing();
+      }
+    } catch (err) {
+      console.error(`[resources] Failed to sample ${bundleId} on ${udid}:`, err);
+    }
+    if (!stopped) {
+      timer = setTimeout(tick, intervalMs);
+    }
+  };
+  tick();
+
+  return () => {
+    stopped = true;
+    if (timer) {
+      clearTimeout(timer);
+    }
+  };
+}
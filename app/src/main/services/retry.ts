@@ -0,0 +1,65 @@
+import { trace } from '../logger';
+
+/**
+ * Retries of simctl operations that fail with transient errors. CoreSimulator sometimes loses its
+ * connection or times out on a boot, install or launch that works on a second attempt. Errors are
+ * retried when their message contains one of the policy's patterns, with exponential backoff.
+ */
+
+export interface RetryPolicy {
+  // Attempts in total, 1 disables retries
+  maxAttempts: number;
+  // Delay before the second attempt, doubled for every following one
+  initialDelayMs: number;
+  maxDelayMs: number;
+  // Case-insensitive substrings of retryable error messages
+  retryablePatterns: string[];
+}
+
+export const DEFAULT_RETRYABLE_PATTERNS = [
+  'CoreSimulatorService connection became invalid',
+  'connection interrupted',
+  'Mach error -308',
+  'timed out',
+  'FBSOpenApplicationServiceErrorDomain',
+];
+
+let retryPolicy: RetryPolicy = {
+  maxAttempts: 3,
+  initialDelayMs: 500,
+  maxDelayMs: 5000,
+  retryablePatterns: DEFAULT_RETRYABLE_PATTERNS,
+};
+
+export function setRetryPolicy(policy: RetryPolicy): void {
+  retryPolicy = policy;
+}
+
+export function getRetryPolicy(): RetryPolicy {
+  return retryPolicy;
+}
+
+export function isRetryableError(err: unknown, policy: RetryPolicy = retryPolicy): boolean {
+  const message = (err instanceof Error ? err.message : String(err)).toLowerCase();
+  return policy.retryablePatterns.some((pattern) => message.includes(pattern.toLowerCase()));
+}
+
+/**
+ * Run `operation`, trying again while it fails with a retryable error, up to the policy's attempts
+ */
+export async function withRetry<T>(name: string, operation: () => Promise<T>): Promise<T> {
+  const { maxAttempts, initialDelayMs, maxDelayMs } = retryPolicy;
+  for (let attempt = 1; ; attempt++) {
+    try {
+      return await operation();
+    } catch (err) {
+      if (attempt >= maxAttempts || !isRetryableError(err)) {
+        throw err;
+      }
+      const delayMs = Math.min(initialDelayMs * 2 ** (attempt - 1), maxDelayMs);
+      console.log(`[retry] ${name} failed with a transient error, retrying in ${delayMs}ms (attempt ${attempt + 1}/${maxAttempts})`);
+      trace(`[retry] ${name} error: ${err instanceof Error ? err.message : err}`);
+      await new Promise((resolve) => setTimeout(resolve, delayMs));
+    }
+  }
+}
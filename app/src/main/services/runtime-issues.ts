@@ -0,0 +1,106 @@
+import { EventEmitter } from 'events';
+import type { SimulatorService } from './simulator';
+import type { RuntimeIssue, RuntimeIssueKind, StreamLogEvent } from '../../shared/ipc-types';
+
+/**
+ * Runtime issues found in an app's log output, like Xcode's runtime issues pane.
+ * Repeats of an issue are grouped, and a simulator's issues are cleared when an app is launched on it.
+ */
+
+const PATTERNS: Array<{ kind: RuntimeIssueKind; pattern: RegExp }> = [
+  { kind: 'auto_layout', pattern: /Unable to simultaneously satisfy constraints|Will attempt to recover by breaking constraint/ },
+  { kind: 'main_thread_checker', pattern: /Main Thread Checker: UI API called on a background thread/ },
+  {
+    kind: 'swiftui',
+    pattern: /Publishing changes from background threads is not allowed|Modifying state during view update|AttributeGraph: cycle detected/,
+  },
+  { kind: 'core_data', pattern: /CoreData: (warning|error|fault):/ },
+  { kind: 'memory', pattern: /Received memory warning|EXC_RESOURCE/ },
+];
+
+// Issues kept per simulator; repeats beyond this only update counts
+const MAX_ISSUES_PER_SIMULATOR = 200;
+
+// The runtime issues of one server's simulators
+export interface RuntimeIssues {
+  // Emits 'issue' with a RuntimeIssue whenever one is found or seen again
+  emitter: EventEmitter;
+  bySimulator: Map<string, RuntimeIssue[]>;
+  nextId: number;
+}
+
+export function createRuntimeIssues(): RuntimeIssues {
+  return { emitter: new EventEmitter(), bySimulator: new Map(), nextId: 1 };
+}
+
+/**
+ * Kind of runtime issue a log line reports, if any
+ */
+export function detectRuntimeIssue(message: string): RuntimeIssueKind | null {
+  return PATTERNS.find(({ pattern }) => pattern.test(message))?.kind || null;
+}
+
+/**
+ * Check a log line from a simulator, running the app `bundleId`, for runtime issues
+ */
+export function recordLogLine(
+  issues: RuntimeIssues,
+  udid: string,
+  message: string,
+  bundleId: string | null = null
+): RuntimeIssue | null {
+  const kind = detectRuntimeIssue(message);
+  if (!kind) {
+    return null;
+  }
+
+  const now = new Date().toISOString();
+  // Group repeats by their first line, which holds the message without per-occurrence details
+  const summary = message.split('\n')[0].trim();
+  const list = issues.bySimulator.get(udid) || [];
+  let issue = list.find((existing) => existing.kind === kind && existing.message === summary);
+  if (issue) {
+    issue.count++;
+    issue.lastSeenAt = now;
+  } else if (list.length < MAX_ISSUES_PER_SIMULATOR) {
+    issue = {
+      id: issues.nextId++,
+      udid,
+      bundleId,
+      kind,
+      message: summary,
+      count: 1,
+      firstSeenAt: now,
+      lastSeenAt: now,
+    };
+    list.push(issue);
+    issues.bySimulator.set(udid, list);
+  } else {
+    return null;
+  }
+
+  issues.emitter.emit('issue', issue);
+  return issue;
+}
+
+/**
+ * Runtime issues found on a simulator since the last app launch, oldest first
+ */
+export function getRuntimeIssues(issues: RuntimeIssues, udid: string): RuntimeIssue[] {
+  return issues.bySimulator.get(udid) || [];
+}
+
+export function clearRuntimeIssues(issues: RuntimeIssues, udid: string): void {
+  issues.bySimulator.delete(udid);
+}
+
+/**
+ * Check every log event of `simulators` for runtime issues
+ */
+export function startRuntimeIssueDetection(issues: RuntimeIssues, simulators: SimulatorService): void {
+  simulators.logEmitter.on('log', (event: StreamLogEvent) => {
+    if (event.message) {
+      recordLogLine(issues, event.udid, event.message, simulators.launchedApps.get(event.udid) || null);
+    }
+  });
+}
@@ -0,0 +1,172 @@
+import * as crypto from 'crypto';
+import { execFile } from 'child_process';
+import { getDataStore } from './data-store';
+import { updateSetting } from './settings';
+import { decrypt, deriveKey, encrypt } from './encryption';
+import type { SecretInfo, SecretsStatus } from '../../shared/ipc-types';
+
+/**
+ * Credentials such as App Store Connect keys, webhook secrets and download auth headers,
+ * encrypted at rest. Unlike settings, values can't be read back through the API; only the
+ * backend decrypts them, when it uses them.
+ *
+ * The key is derived from a master passphrase when one is configured, otherwise it's a random
+ * key kept in the macOS keychain. Without either, secrets are locked. Instances sharing a Postgres
+ * database share its secrets, so they need the same passphrase: each keychain has its own key.
+ */
+
+export interface SecretsSettings {
+  // Master passphrase to derive the key from
+  passphrase: string | null;
+  // Keep a generated key in the macOS keychain when there's no passphrase
+  keychain: boolean;
+}
+
+// A known value encrypted with the key and kept as the secrets.check setting, to tell a wrong
+// passphrase from a working one
+const CHECK_VALUE = 'plasma-secrets';
+
+const KEYCHAIN_SERVICE = 'dev.plasma.secrets';
+const KEYCHAIN_ACCOUNT = 'master-key';
+
+const NAME_PATTERN = /^[A-Za-z0-9_.-]{1,128}$/;
+
+let settings: SecretsSettings = { passphrase: null, keychain: process.platform === 'darwin' };
+let pendingKey: Promise<{ key: Buffer; source: 'passphrase' | 'keychain' }> | null = null;
+
+export class SecretsLockedError extends Error {}
+
+export class InvalidSecretError extends Error {}
+
+export function configureSecrets(next: SecretsSettings): void {
+  settings = next;
+  pendingKey = null;
+}
+
+// Exit status of `security find-generic-password` when there's no such item
+const ITEM_NOT_FOUND = 44;
+
+function security(args: string[], input?: string): Promise<string> {
+  return new Promise((resolve, reject) => {
+    const child = execFile('security', args, (error, stdout) => (error ? reject(error) : resolve(stdout.trim())));
+    child.stdin?.end(input);
+  });
+}
+
+const findKey = () => security(['find-generic-password', '-s', KEYCHAIN_SERVICE, '-a', KEYCHAIN_ACCOUNT, '-w']);
+
+/**
+ * The key in the keychain, generating one only when there's none. Any other failure, e.g. access
+ * denied or a locked keychain, is an error: replacing the key would make every stored secret
+ * undecryptable.
+ */
+async function keychainKey(): Promise<Buffer> {
+  try {
+    return Buffer.from(await findKey(), 'hex');
+  } catch (err) {
+    if ((err as { code?: unknown }).code !== ITEM_NOT_FOUND) {
+      throw err;
+    }
+  }
+
+  // Sent as a command on stdin rather than in argv, where `ps` would show the key. Without `-U`,
+  // so a key another instance stored in the meantime isn't overwritten.
+  const key = crypto.randomBytes(32);
+  await security(['-i'], `add-generic-password -s ${KEYCHAIN_SERVICE} -a ${KEYCHAIN_ACCOUNT} -w ${key.toString('hex')}\n`);
+  console.log('[secrets] Stored a new secrets key in the keychain');
+  // Whichever key made it into the keychain
+  return Buffer.from(await findKey(), 'hex');
+}
+
+async function loadKey(): Promise<{ key: Buffer; source: 'passphrase' | 'keychain' }> {
+  let key: Buffer;
+  let source: 'passphrase' | 'keychain';
+  if (settings.passphrase) {
+    // Another instance on the same database may be creating the salt at the same time
+    const salt = (await updateSetting('secrets.salt', (current) => current ?? crypto.randomBytes(16).toString('base64')))!;
+    key = deriveKey(settings.passphrase, Buffer.from(salt, 'base64'));
+    source = 'passphrase';
+  } else if (settings.keychain) {
+    try {
+      key = await keychainKey();
+    } catch (err) {
+      throw new SecretsLockedError(`Could not read the secrets key from the keychain: ${err instanceof Error ? err.message : err}`);
+    }
+    source = 'keychain';
+  } else {
+    throw new SecretsLockedError('Secrets are locked: set PLASMA_SECRETS_PASSPHRASE');
+  }
+
+  const check = (await updateSetting('secrets.check', (current) => current ?? encrypt(CHECK_VALUE, key)))!;
+  try {
+    decrypt(check, key);
+  } catch {
+    throw new SecretsLockedError(`Secrets are locked: the ${source} key isn't the one they were stored with`);
+  }
+  return { key, source };
+}
+
+/**
+ * The key secrets are encrypted with, loaded once. A failed load is retried on the next call.
+ */
+function getKey(): Promise<{ key: Buffer; source: 'passphrase' | 'keychain' }> {
+  if (!pendingKey) {
+    const pending = loadKey();
+    pending.catch(() => {
+      if (pendingKey === pending) {
+        pendingKey = null;
+      }
+    });
+    pendingKey = pending;
+  }
+  return pendingKey;
+}
+
+function validateName(name: string): void {
+  if (!NAME_PATTERN.test(name)) {
+    throw new InvalidSecretError('Secret names may only contain letters, digits, ".", "_" and "-" (up to 128)');
+  }
+}
+
+export async function getSecretsStatus(): Promise<SecretsStatus> {
+  try {
+    const { source } = await getKey();
+    return { unlocked: true, keySource: source, error: null };
+  } catch (err) {
+    return { unlocked: false, keySource: null, error: err instanceof Error ? err.message : String(err) };
+  }
+}
+
+export async function listSecrets(): Promise<SecretInfo[]> {
+  return getDataStore().secrets.list();
+}
+
+export async function setSecret(name: string, value: string): Promise<SecretInfo> {
+  validateName(name);
+  if (typeof value !== 'string' || value === '') {
+    throw new InvalidSecretError('value must be a non-empty string');
+  }
+  const { key } = await getKey();
+  await getDataStore().secrets.set(name, encrypt(value, key));
+  return (await listSecrets()).find((secret) => secret.name === name)!;
+}
+
+export async function hasSecret(name: string): Promise<boolean> {
+  return (await getDataStore().secrets.getCiphertext(name)) !== null;
+}
+
+/**
+ * Decrypted value of a secret, for the backend's own use. Null if there's no such secret.
+ */
+export async function getSecret(name: string): Promise<string | null> {
+  const ciphertext = await getDataStore().secrets.getCiphertext(name);
+  if (ciphertext === null) {
+    return null;
+  }
+  const { key } = await getKey();
+  return decrypt(ciphertext, key);
+}
+
+export async function deleteSecret(name: string): Promise<boolean> {
+  return getDataStore().secrets.delete(name);
+}
@@ -0,0 +1,42 @@
+import { runSimctl } from './simulator';
+import type { SettingsPane, SettingsPaneId } from '../../shared/ipc-types';
+
+/**
+ * Shortcuts to panes of the Settings app, e.g. an app's notification settings, which take a lot of
+ * tapping through the stream to reach. They're opened with `simctl openurl` and the `App-prefs:`
+ * URLs Settings handles; panes of an app need its bundle ID.
+ */
+
+const PANES: (SettingsPane & { url: (bundleId: string) => string })[] = [
+  { id: 'app', name: "An app's settings", requiresBundleId: true, url: (bundleId) => `App-prefs:root=${bundleId}` },
+  {
+    id: 'notifications',
+    name: "An app's notifications",
+    requiresBundleId: true,
+    url: (bundleId) => `App-prefs:root=NOTIFICATIONS_ID&path=${bundleId}`,
+  },
+  { id: 'privacy', name: 'Privacy & Security', requiresBundleId: false, url: () => 'App-prefs:root=Privacy' },
+  { id: 'privacy-photos', name: 'Photos access', requiresBundleId: false, url: () => 'App-prefs:root=Privacy&path=PHOTOS' },
+  { id: 'privacy-location', name: 'Location Services', requiresBundleId: false, url: () => 'App-prefs:root=Privacy&path=LOCATION' },
+  { id: 'privacy-contacts', name: 'Contacts access', requiresBundleId: false, url: () => 'App-prefs:root=Privacy&path=CONTACTS' },
+  { id: 'privacy-calendars', name: 'Calendars access', requiresBundleId: false, url: () => 'App-prefs:root=Privacy&path=CALENDARS' },
+  { id: 'wifi', name: 'Wi-Fi', requiresBundleId: false, url: () => 'App-prefs:root=WIFI' },
+  { id: 'general', name: 'General', requiresBundleId: false, url: () => 'App-prefs:root=General' },
+  { id: 'language', name: 'Language & Region', requiresBundleId: false, url: () => 'App-prefs:root=General&path=INTERNATIONAL' },
+  { id: 'accessibility', name: 'Accessibility', requiresBundleId: false, url: () => 'App-prefs:root=ACCESSIBILITY' },
+  { id: 'developer', name: 'Developer', requiresBundleId: false, url: () => 'App-prefs:root=DEVELOPER_SETTINGS' },
+];
+
+export const SETTINGS_PANES: SettingsPane[] = PANES.map(({ id, name, requiresBundleId }) => ({ id, name, requiresBundleId }));
+
+export const SETTINGS_PANE_IDS = PANES.map((pane) => pane.id);
+
+/**
+ * Open a pane of the Settings app. `bundleId` is required for the panes of an app.
+ */
+export async function openSettingsPane(udid: string, id: SettingsPaneId, bundleId?: string): Promise<string> {
+  const pane = PANES.find((candidate) => candidate.id === id)!;
+  const url = pane.url(bundleId ?? '');
+  await runSimctl(['openurl', udid, url]);
+  return url;
+}
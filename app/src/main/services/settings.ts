@@ -0,0 +1,148 @@
+import { getDataStore } from './data-store';
+import { findAppState } from './app-state';
+import type { FeatureFlagName, SettingChange, SettingKey, SettingValues } from '../../shared/ipc-types';
+
+/**
+ * Typed access to the settings table. Every key is declared here with the type of its value, so a
+ * mistyped key fails to compile instead of reading a setting nothing ever writes, and values are
+ * checked before they're stored. Changes are emitted as they're made, and pushed to WebSocket
+ * clients as `settings:change`.
+ */
+
+interface SettingType<T> {
+  // Null for a stored value that isn't of the type
+  parse(raw: string): T | null;
+  serialize(value: T): string;
+  check(value: unknown): value is T;
+}
+
+const string: SettingType<string> = {
+  parse: (raw) => raw,
+  serialize: (value) => value,
+  check: (value): value is string => typeof value === 'string' && value.length > 0,
+};
+
+const timestamp: SettingType<string> = {
+  parse: (raw) => (Number.isNaN(Date.parse(raw)) ? null : raw),
+  serialize: (value) => value,
+  check: (value): value is string => typeof value === 'string' && !Number.isNaN(Date.parse(value)),
+};
+
+// Stored as 'true' and 'false'
+const boolean: SettingType<boolean> = {
+  parse: (raw) => (raw === 'true' ? true : raw === 'false' ? false : null),
+  serialize: (value) => String(value),
+  check: (value): value is boolean => typeof value === 'boolean',
+};
+
+interface SettingDefinition<T> {
+  type: SettingType<T>;
+  // Left out of change events, e.g. the secrets salt
+  private?: boolean;
+}
+
+const FLAG_SETTINGS: { [K in FeatureFlagName as `flags.${K}`]: SettingDefinition<boolean> } = {
+  'flags.webrtc_streaming': { type: boolean },
+  'flags.android': { type: boolean },
+  'flags.proxy': { type: boolean },
+};
+
+const SETTINGS: { [K in SettingKey]: SettingDefinition<SettingValues[K]> } = {
+  'analytics.enabled_at': { type: timestamp },
+  'onboarding.dismissed_at': { type: timestamp },
+  'secrets.salt': { type: string, private: true },
+  'secrets.check': { type: string, private: true },
+  ...FLAG_SETTINGS,
+};
+
+export class SettingValueError extends Error {}
+
+export function isSettingKey(key: string): key is SettingKey {
+  return Object.hasOwn(SETTINGS, key);
+}
+
+export interface StoredSetting<T> {
+  value: T;
+  updatedAt: string;
+}
+
+/**
+ * Parse a stored setting; a value of the wrong type, e.g. written by hand, reads as unset
+ */
+function parseStored<K extends SettingKey>(key: K, raw: string): SettingValues[K] | null {
+  const value = SETTINGS[key].type.parse(raw);
+  if (value === null) {
+    console.warn(`[settings] Ignoring the stored value of ${key}, it isn't valid: ${raw}`);
+  }
+  return value;
+}
+
+/**
+ * A setting's value and when it was set, or null if it isn't set
+ */
+export async function readSetting<K extends SettingKey>(key: K): Promise<StoredSetting<SettingValues[K]> | null> {
+  const stored = await getDataStore().settings.get(key);
+  if (!stored) {
+    return null;
+  }
+  const value = parseStored(key, stored.value);
+  return value === null ? null : { value, updatedAt: stored.updated_at };
+}
+
+/**
+ * Set a setting from its current value. The read and the write happen in one transaction, so
+ * another instance on the same database can't change it in between. `update` returns null to
+ * clear the setting; returning the current value leaves it as it is.
+ */
+export async function updateSetting<K extends SettingKey>(
+  key: K,
+  update: (current: SettingValues[K] | null) => SettingValues[K] | null
+): Promise<SettingValues[K] | null> {
+  const definition = SETTINGS[key];
+  let changed = false;
+  let value: SettingValues[K] | null = null;
+  const stored = await getDataStore().settings.update(key, (raw) => {
+    const current = raw === null ? null : parseStored(key, raw);
+    value = update(current);
+    changed = value !== current;
+    if (!changed) {
+      return raw;
+    }
+    if (value === null) {
+      return null;
+    }
+    if (!definition.type.check(value)) {
+      throw new SettingValueError(`Invalid value for the ${key} setting: ${JSON.stringify(value)}`);
+    }
+    return definition.type.serialize(value);
+  });
+  if (changed) {
+    const change: SettingChange = stored
+      ? { key, value: definition.private ? null : value, updatedAt: stored.updated_at }
+      : { key, value: null, updatedAt: null };
+    // Outside of a server, e.g. in a script, nobody is listening
+    findAppState()?.settings.emit('change', change);
+  }
+  return value;
+}
+
+export async function writeSetting<K extends SettingKey>(key: K, value: SettingValues[K]): Promise<void> {
+  await updateSetting(key, () => value);
+}
+
+export async function clearSetting(key: SettingKey): Promise<void> {
+  await updateSetting(key, () => null);
+}
+
+/**
+ * Set a setting from its stored form, e.g. from an export. Resolves to false, leaving the setting
+ * as it is, when the value isn't of the setting's type.
+ */
+export async function writeStoredSetting(key: SettingKey, raw: string): Promise<boolean> {
+  const value = SETTINGS[key].type.parse(raw);
+  if (value === null) {
+    return false;
+  }
+  await updateSetting(key, () => value);
+  return true;
+}
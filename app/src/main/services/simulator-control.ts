@@ -0,0 +1,137 @@
+import { getSessionInfo, getSimulatorCapabilities, sendSessionCommand, sendSwipe, sendTap, SessionCapabilityError } from './simulator';
+import { field, validate, ValidationError } from '../validation';
+import { SWIPE_REQUEST, TAP_REQUEST, TOUCH_PHASES, touches } from '../request-schemas';
+import type { SimulatorControlMessage, SimulatorControlReply } from '../../shared/ipc-types';
+
+/**
+ * Input sent over the `/api/simulator/ws` control socket. A drag is dozens of touch events a second,
+ * too many for one HTTP request each, so a client keeps one socket open and sends them as messages.
+ * Touch and key events go to the simulator-server session's stdin, like their HTTP counterparts,
+ * and so do taps and swipes, as touch sequences, instead of starting an AXe process for each one.
+ * AXe remains the fallback for a simulator-server that doesn't take touches.
+ */
+
+export const CONTROL_SOCKET_PATH = '/api/simulator/ws';
+
+const KEY_DIRECTIONS = ['down', 'up'] as const;
+
+// Interval between the interpolated moves of a swipe, about one per frame
+const SWIPE_STEP_MS = 16;
+
+export class ControlMessageError extends Error {}
+
+// Without a udid, which can come from the socket URL instead
+const TOUCH_MESSAGE = field.object({ phase: field.oneOf(TOUCH_PHASES), touches });
+const KEY_MESSAGE = field.object({ code: field.number({ min: 0, integer: true }), direction: field.oneOf(KEY_DIRECTIONS) });
+
+function requireTouch(udid: string): void {
+  if (!getSimulatorCapabilities(udid).touch) {
+    throw new ControlMessageError('Touch input is not supported on this simulator');
+  }
+}
+
+function takesSessionTouches(udid: string): boolean {
+  return getSessionInfo(udid)?.capabilities.touch ?? false;
+}
+
+const touchCommand = (phase: (typeof TOUCH_PHASES)[number], x: number, y: number) =>
+  `touch ${phase} ${Number(x.toFixed(4))},${Number(y.toFixed(4))}`;
+
+async function sendSessionTap(udid: string, x: number, y: number): Promise<void> {
+  await sendSessionCommand(udid, touchCommand('began', x, y));
+  await sendSessionCommand(udid, touchCommand('ended', x, y));
+}
+
+/**
+ * A swipe as a touch moving from start to end over `duration` seconds
+ */
+async function sendSessionSwipe(udid: string, startX: number, startY: number, endX: number, endY: number, duration: number): Promise<void> {
+  const steps = Math.max(1, Math.round((duration * 1000) / SWIPE_STEP_MS));
+  const interval = (duration * 1000) / steps;
+  await sendSessionCommand(udid, touchCommand('began', startX, startY));
+  for (let step = 1; step < steps; step++) {
+    await new Promise((resolve) => setTimeout(resolve, interval));
+    const progress = step / steps;
+    await sendSessionCommand(udid, touchCommand('moved', startX + (endX - startX) * progress, startY + (endY - startY) * progress));
+  }
+  await new Promise((resolve) => setTimeout(resolve, interval));
+  await sendSessionCommand(udid, touchCommand('ended', endX, endY));
+}
+
+/**
+ * Run one control message. `defaultUdid` is the simulator the socket was opened for, used when
+ * the message doesn't name one.
+ */
+export async function runControlMessage(message: SimulatorControlMessage, defaultUdid: string | null): Promise<void> {
+  const udid = message.udid || defaultUdid;
+  if (!udid) {
+    throw new ControlMessageError('udid is required, in the message or the socket URL');
+  }
+
+  switch (message.type) {
+    case 'touch': {
+      validate(message, TOUCH_MESSAGE);
+      requireTouch(udid);
+      await sendSessionCommand(udid, `touch ${message.phase} ${message.touches.map((t) => `${t.x},${t.y}`).join(' ')}`);
+      return;
+    }
+
+    case 'tap': {
+      validate({ ...message, udid }, TAP_REQUEST);
+      requireTouch(udid);
+      if (takesSessionTouches(udid)) {
+        await sendSessionTap(udid, message.x, message.y);
+      } else {
+        await sendTap(udid, message.x, message.y, message.screenWidth, message.screenHeight);
+      }
+      return;
+    }
+
+    case 'swipe': {
+      const { startX, startY, endX, endY, screenWidth, screenHeight } = validate({ ...message, udid }, SWIPE_REQUEST);
+      requireTouch(udid);
+      if (takesSessionTouches(udid)) {
+        await sendSessionSwipe(udid, startX, startY, endX, endY, message.duration ?? 0.3);
+      } else {
+        await sendSwipe(udid, startX, startY, endX, endY, screenWidth, screenHeight, message.duration);
+      }
+      return;
+    }
+
+    case 'key': {
+      validate(message, KEY_MESSAGE);
+      await sendSessionCommand(udid, `key ${message.code} ${message.direction}`);
+      return;
+    }
+
+    default:
+      throw new ControlMessageError(`Unknown control message type "${(message as { type?: unknown }).type}"`);
+  }
+}
+
+/**
+ * Handle a raw message from a control socket. Returns the reply to send back: an acknowledgement
+ * when the message had an `id`, an error whenever it failed, or null.
+ */
+export async function handleControlMessage(raw: string, defaultUdid: string | null): Promise<SimulatorControlReply | null> {
+  let message: SimulatorControlMessage;
+  try {
+    message = JSON.parse(raw);
+  } catch {
+    return { id: null, ok: false, error: 'Invalid JSON' };
+  }
+
+  const id = message?.id ?? null;
+  try {
+    await runControlMessage(message, defaultUdid);
+    return id === null ? null : { id, ok: true, error: null };
+  } catch (err) {
+    if (err instanceof ValidationError) {
+      return { id, ok: false, error: err.message, fields: err.fields };
+    }
+    if (!(err instanceof ControlMessageError || err instanceof SessionCapabilityError)) {
+      console.error('[simulator] Control message failed:', err);
+    }
+    return { id, ok: false, error: err instanceof Error ? err.message : String(err) };
+  }
+}
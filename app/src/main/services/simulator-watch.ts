@@ -0,0 +1,89 @@
+import { EventEmitter } from 'events';
+import { listSimulators } from './simulator';
+import type { SimulatorChange, Simulator } from '../../shared/ipc-types';
+
+/**
+ * Pushes simulator list changes instead of having clients poll: the list is polled here,
+ * while someone is listening, and compared with the previous one.
+ */
+
+// Watches the simulators of one server
+export interface SimulatorWatcher {
+  // Emits 'change' with a SimulatorChange for every added, removed, booted or shut down simulator
+  emitter: EventEmitter;
+  timer: ReturnType<typeof setInterval> | null;
+  previous: Simulator[] | null;
+}
+
+export function createSimulatorWatcher(): SimulatorWatcher {
+  return { emitter: new EventEmitter(), timer: null, previous: null };
+}
+
+/**
+ * Changes going from one simulator list to the next, in the order of the new list (removals last)
+ */
+export function diffSimulators(before: Simulator[], after: Simulator[]): SimulatorChange[] {
+  const beforeByUdid = new Map(before.map((simulator) => [simulator.udid, simulator]));
+  const afterUdids = new Set(after.map((simulator) => simulator.udid));
+  const changes: SimulatorChange[] = [];
+
+  for (const simulator of after) {
+    const old = beforeByUdid.get(simulator.udid);
+    if (!old) {
+      changes.push({ kind: 'added', udid: simulator.udid, simulator, previousState: null });
+    } else if (old.state !== simulator.state) {
+      const kind = simulator.state === 'Booted' ? 'booted' : simulator.state === 'Shutdown' ? 'shutdown' : 'state_changed';
+      changes.push({ kind, udid: simulator.udid, simulator, previousState: old.state });
+    }
+  }
+  for (const simulator of before) {
+    if (!afterUdids.has(simulator.udid)) {
+      changes.push({ kind: 'removed', udid: simulator.udid, simulator, previousState: simulator.state });
+    }
+  }
+  return changes;
+}
+
+/**
+ * Compare the current simulator list with the last one seen and emit the changes
+ */
+export async function checkSimulators(watcher: SimulatorWatcher): Promise<SimulatorChange[]> {
+  const current = await listSimulators({ refresh: true });
+  const changes = watcher.previous ? diffSimulators(watcher.previous, current) : [];
+  watcher.previous = current;
+  for (const change of changes) {
+    watcher.emitter.emit('change', change);
+  }
+  return changes;
+}
+
+/**
+ * Poll the simulator list every `intervalMs` while `hasListeners` returns true. A poll after a
+ * pause compares with the list from before it, so changes made meanwhile are still reported.
+ */
+export function startSimulatorWatcher(watcher: SimulatorWatcher, intervalMs: number, hasListeners: () => boolean): void {
+  if (watcher.timer || intervalMs <= 0) {
+    return;
+  }
+  let checking = false;
+  watcher.timer = setInterval(() => {
+    if (checking || !hasListeners()) {
+      return;
+    }
+    checking = true;
+    checkSimulators(watcher)
+      .catch((err) => console.error('[simulators] Failed to check for changes:', err))
+      .finally(() => {
+        checking = false;
+      });
+  }, intervalMs);
+  watcher.timer.unref();
+}
+
+export function stopSimulatorWatcher(watcher: SimulatorWatcher): void {
+  if (watcher.timer) {
+    clearInterval(watcher.timer);
+    watcher.timer = null;
+  }
+  watcher.previous = null;
+}
@@ -4,23 +4,90 @@ import * as fs from 'fs';
 import * as path from 'path';
 import fetch from 'node-fetch';
 import { registerProcess } from './process-manager';
+import { recordNotification } from './notifications';
+import { SpanTimer, recordLaunchTimings } from './launch-profiling';
+import { recordUsage } from './analytics';
+import { appendLogEvent } from './log-history';
+import { runAxe } from './axe';
+import { createJobDir } from './workspace';
+import { getTapTransform, applyTapTransform } from './calibration';
+import { trace } from '../logger';
+import { findHelperBinary, describeHelperLookup } from './helper-binaries';
+import { verifyInstall, InstallVerificationError } from './app-verification';
+import { withRetry } from './retry';
+import { attachConsole, launchWithConsole } from './app-console';
+import { getAppliedConditions } from './conditions';
+import { getAppState } from './app-state';
+import type {
+  AppResourceSample,
+  CreateSimulatorRequest,
+  HardwareButton,
+  InstallVerification,
+  NamedKey,
+  SessionCapabilities,
+  SessionCapability,
+  SessionInfo,
+  SimulatorDeviceTypes,
+  SimulatorOrientation,
+  DeviceClass,
+  SimulatorCapabilities,
+  Simulator,
+  StreamLogEvent,
+  SessionRestartEvent,
+  SessionCommandRecord,
+  LaunchTimings,
+} from '../../shared/ipc-types';
+
+export interface StreamSettings {
+  fps: number;
+  quality: number;
+}
 
-// Types
-export interface Simulator {
-  udid: string;
-  name: string;
-  state: string;
-  runtime: string;
+// Tablet frames have about four times the pixels of phone frames, so they are
+// encoded at a lower quality to keep the stream responsive
+// visionOS is driven by gaze and pinch, and tvOS by a remote, neither by touches
+const CAPABILITIES: Record<DeviceClass, SimulatorCapabilities> = {
+  phone: { stream: true, touch: true, rotate: true },
+  tablet: { stream: true, touch: true, rotate: true },
+  watch: { stream: true, touch: true, rotate: false },
+  tv: { stream: true, touch: false, rotate: false },
+  vision: { stream: true, touch: false, rotate: false },
+  unknown: { stream: true, touch: true, rotate: true },
+};
+
+
+const STREAM_DEFAULTS: Record<DeviceClass, StreamSettings> = {
+  phone: { fps: 60, quality: 0.7 },
+  tablet: { fps: 60, quality: 0.5 },
+  watch: { fps: 30, quality: 0.7 },
+  tv: { fps: 30, quality: 0.5 },
+  vision: { fps: 30, quality: 0.5 },
+  unknown: { fps: 60, quality: 0.7 },
+};
+
+export interface LaunchResult {
+  message: string;
+  bundleId: string;
+  timings: LaunchTimings;
+  // Not checked for device backends
+  verification?: InstallVerification;
+  // The launch's console, see app-console
+  launchId?: string;
 }
 
-export interface StreamLogEvent {
-  type: 'info' | 'error' | 'debug' | 'frame';
-  message?: string;
-  frameNumber?: number;
+export interface SessionRestartPolicy {
+  // Restart attempts after an unexpected exit, 0 disables restarts
+  maxAttempts: number;
+  // Delay before the first attempt, doubled for every following one
+  initialDelayMs: number;
+  maxDelayMs: number;
 }
 
-// Global log emitter for SSE
-export const logEmitter = new EventEmitter();
+// What a simulator-server binary supports, see negotiateProtocol
+export interface SessionProtocol {
+  protocolVersion: number;
+  capabilities: SessionCapabilities;
+}
 
 // Session cache - one per UDID
 interface SimulatorSession {
@@ -28,91 +95,158 @@ interface SimulatorSession {
   process: ChildProcess;
   streamUrl: string;
   stdin: NodeJS.WritableStream;
+  fps: number;
+  quality: number;
+  protocol: SessionProtocol;
 }
 
-const sessionCache = new Map<string, SimulatorSession>();
+export const SESSION_CAPABILITIES: SessionCapability[] = ['touch', 'key', 'rotate', 'button', 'audio'];
+
+// Binaries from before the `capabilities` command speak protocol 1: the commands in
+// docs/simulator-server.md, without audio
+const LEGACY_PROTOCOL: SessionProtocol = {
+  protocolVersion: 1,
+  capabilities: { touch: true, key: true, rotate: true, button: true, audio: false },
+};
+
+const CURRENT_PROTOCOL: SessionProtocol = {
+  protocolVersion: 2,
+  capabilities: { touch: true, key: true, rotate: true, button: true, audio: true },
+};
+
+// How long simulator-server has to answer `capabilities` before it's taken for a legacy binary
+const CAPABILITIES_TIMEOUT_MS = 1000;
+
+// The capability each gated stdin command needs
+const COMMAND_CAPABILITIES: Record<string, SessionCapability> = {
+  touch: 'touch',
+  key: 'key',
+  rotate: 'rotate',
+  button: 'button',
+};
 
 /**
- * Find the simulator-server binary
+ * Thrown instead of writing a command the simulator's simulator-server doesn't support, which it
+ * would ignore
  */
-function findSimulatorServerBinary(): string | null {
-  // 1. Environment variable override
-  if (process.env.SIMULATOR_SERVER) {
-    if (fs.existsSync(process.env.SIMULATOR_SERVER)) {
-      return process.env.SIMULATOR_SERVER;
-    }
+export class SessionCapabilityError extends Error {
+  constructor(readonly capability: SessionCapability, protocolVersion: number) {
+    super(`The simulator-server in use (protocol ${protocolVersion}) doesn't support ${capability} commands, update it to use them`);
   }
+}
 
-  // 2. Development: Check relative paths
-  const possiblePaths = [
-    path.join(__dirname, '../../../../bin/simulator-server'),
-    path.join(__dirname, '../../../bin/simulator-server'),
-    path.join(process.cwd(), 'bin/simulator-server'),
-    path.join(process.cwd(), '../swift/.build/release/simulator-server'),
-    path.join(process.cwd(), '../swift/.build/debug/simulator-server'),
-  ];
-
-  for (const p of possiblePaths) {
-    if (fs.existsSync(p)) {
-      return p;
-    }
-  }
+// Commands kept of each simulator's session
+const MAX_COMMAND_HISTORY = 500;
 
-  // 3. Bundled binary in resources
-  if (process.resourcesPath) {
-    const bundled = path.join(process.resourcesPath, 'bin', 'simulator-server');
-    if (fs.existsSync(bundled)) {
-      return bundled;
-    }
-  }
+/**
+ * Replaces simctl and AXe for listing, launching and input, e.g. with fake devices in tests
+ */
+export interface DeviceBackend {
+  listSimulators(): Promise<Simulator[]>;
+  installAndLaunch(udid: string, appPath: string, bundleId?: string, launchArgs?: string[]): Promise<LaunchResult>;
+  sendTap(udid: string, x: number, y: number): Promise<void>;
+  sendSwipe(udid: string, start: { x: number; y: number }, end: { x: number; y: number }, duration: number): Promise<void>;
+  typeText(udid: string, text: string): Promise<void>;
+  // Commands that would otherwise be written to simulator-server's stdin
+  sendSessionCommand(udid: string, command: string): Promise<void>;
+  // Generate frames instead of streaming from simulator-server. Returns a function that stops the stream.
+  streamFrames?(udid: string, fps: number, onFrame: (frame: BackendFrame) => void): () => void;
+  // Report an app's CPU and memory usage instead of looking up its process
+  sampleAppResources?(udid: string, bundleId: string): Promise<AppResourceSample | null>;
+  // Capture the screen as a PNG instead of running `simctl io screenshot`
+  screenshot?(udid: string): Promise<Buffer>;
+  // Record the screen to `file` instead of running `simctl io recordVideo`. Resolves once recording
+  // started, with a function that stops it and resolves once the file is written.
+  recordVideo?(udid: string, file: string): Promise<() => Promise<void>>;
+  // Run simctl commands that have no method of their own instead of `xcrun simctl`, resolving with stdout
+  simctl?(args: string[]): Promise<string>;
+  // Produce `log stream --style json` output instead of spawning it in the simulator. Returns a
+  // function that stops the stream; `onExit` is called when it ends on its own.
+  streamSystemLog?(udid: string, predicate: string | null, onOutput: (chunk: string) => void, onExit: () => void): () => void;
+  // Write the console output of an app it launched instead of `simctl launch --console-pty`,
+  // calling `onExit` once the app exits
+  appConsole?(udid: string, bundleId: string, onOutput: (chunk: string) => void, onExit: (code: number | null) => void): void;
+  // Press home, lock or Siri instead of running AXe; the volume buttons are session commands
+  pressButton?(udid: string, button: HardwareButton): Promise<void>;
+  // Produce `axe describe-ui` output instead of running AXe, for the element at `point` when given
+  describeUi?(udid: string, point?: { x: number; y: number }): Promise<string>;
+  // What the stand-in for simulator-server supports, everything by default
+  sessionProtocol?(udid: string): SessionProtocol;
+}
 
-  return null;
+export interface BackendFrame {
+  frame: Buffer;
+  format: 'jpeg' | 'png';
 }
 
 /**
- * Find the AXe binary
+ * Simulator state of one server: its device backend, simulator-server sessions, the last listing
+ * and the emitters its clients listen to. It's part of the server's AppState, and the functions
+ * in this module act on the service of the server they run for.
  */
-function findAxeBinary(): string | null {
-  // 1. Environment variable override
-  if (process.env.AXE_BINARY) {
-    if (fs.existsSync(process.env.AXE_BINARY)) {
-      return process.env.AXE_BINARY;
-    }
-  }
-
-  // 2. Development paths
-  const possiblePaths = [
-    path.join(__dirname, '../../../../binaries/axe'),
-    path.join(__dirname, '../../../binaries/axe'),
-    path.join(process.cwd(), 'binaries/axe'),
-  ];
+export class SimulatorService {
+  // Stream log events, forwarded to WebSocket and SSE clients
+  readonly logEmitter = new EventEmitter();
+  // Emits `restart` with a SessionRestartEvent as crashed sessions are brought back,
+  // and `command` with a SessionCommandRecord for every command written to a session
+  readonly sessionEmitter = new EventEmitter();
+
+  restartPolicy: SessionRestartPolicy = {
+    maxAttempts: 5,
+    initialDelayMs: 1000,
+    maxDelayMs: 30000,
+  };
+  // How long a session without consumers is kept before simulator-server is stopped (0 keeps it)
+  sessionIdleTimeoutMs = 60_000;
+  // How long a listing is served without checking simctl again (0 always checks)
+  simulatorListTtlMs = 2000;
+
+  readonly sessionCache = new Map<string, SimulatorSession>();
+  readonly pendingSessions = new Map<string, Promise<SimulatorSession>>();
+  // What is using each simulator's session, e.g. `client:3` for a WebSocket client streaming it
+  readonly sessionConsumers = new Map<string, Set<string>>();
+  readonly idleTimers = new Map<string, ReturnType<typeof setTimeout>>();
+  // Sessions kept ready by pre-warming, which are never stopped for being idle
+  readonly prewarmedSessions = new Set<string>();
+  // Commands sent to each simulator's session, oldest first, kept across restarts
+  readonly commandHistory = new Map<string, SessionCommandRecord[]>();
+  // Device type of every simulator seen by listSimulators, used to look up tap calibrations
+  readonly deviceTypes = new Map<string, string>();
+  // Bundle ID of the app most recently launched on each simulator
+  readonly launchedApps = new Map<string, string>();
+
+  // Result of the last simulator listing, for callers that can't afford to shell out to simctl
+  cachedSimulators: { simulators: Simulator[]; updatedAt: string } | null = null;
+  // Bumped when simulators are booted, created or deleted; listings from older generations aren't served
+  simulatorListGeneration = 0;
+  cachedSimulatorsGeneration = 0;
+  pendingSimulatorList: { generation: number; promise: Promise<Simulator[]> } | null = null;
+
+  // Stream settings that apply to every device class, e.g. from the config
+  streamDefaultOverrides: Partial<StreamSettings> = {};
+  prewarmTimer: ReturnType<typeof setInterval> | null = null;
+
+  constructor(public backend: DeviceBackend | null = null) {}
+}
 
-  for (const p of possiblePaths) {
-    if (fs.existsSync(p)) {
-      return p;
-    }
-  }
+/**
+ * The simulator service of the server this code runs for
+ */
+export function getSimulatorService(): SimulatorService {
+  return getAppState().simulators;
+}
 
-  // 3. Bundled binary
-  if (process.resourcesPath) {
-    const bundled = path.join(process.resourcesPath, 'binaries', 'axe');
-    if (fs.existsSync(bundled)) {
-      return bundled;
-    }
-  }
+// Name suffix of the clones undo keeps of erased simulators, which are left out of the list
+export const UNDO_SNAPSHOT_SUFFIX = ' (Plasma undo snapshot)';
 
-  // 4. Check PATH
-  const { execSync } = require('child_process');
-  try {
-    const result = execSync('which axe', { encoding: 'utf-8' }).trim();
-    if (result && fs.existsSync(result)) {
-      return result;
-    }
-  } catch {
-    // Not in PATH
-  }
+// A listing older than the TTL is still served while a refresh runs, up to this age
+const SIMULATOR_LIST_MAX_STALE_MS = 60_000;
 
-  return null;
+/**
+ * Find the simulator-server binary
+ */
+export function findSimulatorServerBinary(): string | null {
+  return findHelperBinary('simulator-server')?.path ?? null;
 }
 
 /**
@@ -123,12 +257,13 @@ async function startSession(
   fps: number,
   quality: number
 ): Promise<SimulatorSession> {
+  const service = getSimulatorService();
   const serverPath = findSimulatorServerBinary();
   if (!serverPath) {
-    throw new Error('simulator-server binary not found');
+    throw new Error(`simulator-server binary not found (looked in ${describeHelperLookup('simulator-server')})`);
   }
 
-  emitLog('info', `Spawning simulator-server for ${udid}`);
+  emitLog(udid, 'info', `Spawning simulator-server for ${udid}`);
 
   const proc = spawn(serverPath, [
     '--udid', udid,
@@ -137,7 +272,7 @@ async function startSession(
   ], {
     stdio: ['pipe', 'pipe', 'pipe'],
   });
-  registerProcess(proc);
+  registerProcess(proc, 'simulator-server');
 
   const stdin = proc.stdin!;
 
@@ -160,14 +295,14 @@ async function startSession(
             const msg = d.toString().trim();
             if (msg) {
               console.log(`[simulator-server stdout] ${msg}`);
-              emitLog('debug', `simulator-server stdout: ${msg}`);
+              emitLog(udid, 'debug', `simulator-server stdout: ${msg}`);
             }
           });
           return;
         }
         if (trimmed) {
           console.log(`[simulator-server stdout] ${trimmed}`);
-          emitLog('debug', `simulator-server stdout: ${trimmed}`);
+          emitLog(udid, 'debug', `simulator-server stdout: ${trimmed}`);
         }
       }
     };
@@ -178,7 +313,7 @@ async function startSession(
       const msg = data.toString().trim();
       if (msg) {
         console.log(`[simulator-server stderr] ${msg}`);
-        emitLog('debug', `simulator-server stderr: ${msg}`);
+        emitLog(udid, 'debug', `simulator-server stderr: ${msg}`);
       }
     });
 
@@ -203,46 +338,353 @@ async function startSession(
     }, 10000);
   });
 
-  emitLog('info', `simulator-server ready at ${streamUrl}`);
+  emitLog(udid, 'info', `simulator-server ready at ${streamUrl}`);
+  const protocol = await negotiateProtocol(udid, proc);
+
+  // Drop the cached session if simulator-server goes away on its own
+  proc.on('close', (code, signal) => {
+    if (service.sessionCache.get(udid)?.process !== proc) {
+      return;
+    }
+    service.sessionCache.delete(udid);
+    emitLog(udid, 'error', `simulator-server for ${udid} exited with code ${code}`);
+
+    // A signal means we killed it ourselves (e.g. app shutdown)
+    if (!signal) {
+      recordNotification({
+        kind: 'simulator_crashed',
+        level: 'error',
+        title: 'Simulator stream stopped',
+        message: `simulator-server for ${udid} exited unexpectedly with code ${code}`,
+        data: { udid, code },
+      }).catch((err) => console.error('[simulator] Failed to record notification:', err));
+
+      if (service.restartPolicy.maxAttempts > 0) {
+        scheduleRestart(udid, fps, quality, 1);
+      }
+    }
+  });
 
   return {
     udid,
     process: proc,
     streamUrl,
     stdin,
+    fps,
+    quality,
+    protocol,
   };
 }
 
+/**
+ * Parse simulator-server's answer to `capabilities`, e.g.
+ * `{"protocol":2,"features":["touch","key","rotate","button","audio"]}`
+ */
+export function parseCapabilities(reply: string): SessionProtocol {
+  try {
+    const { protocol, features } = JSON.parse(reply);
+    const supported = Array.isArray(features) ? features : [];
+    return {
+      protocolVersion: Number.isInteger(protocol) ? protocol : LEGACY_PROTOCOL.protocolVersion,
+      capabilities: Object.fromEntries(SESSION_CAPABILITIES.map((capability) => [capability, supported.includes(capability)])) as SessionCapabilities,
+    };
+  } catch {
+    return LEGACY_PROTOCOL;
+  }
+}
+
+/**
+ * Ask a new simulator-server which protocol version and commands it supports. Older binaries
+ * ignore the question and are assumed to speak protocol 1.
+ */
+function negotiateProtocol(udid: string, proc: ChildProcess): Promise<SessionProtocol> {
+  return new Promise((resolve) => {
+    const finish = (protocol: SessionProtocol) => {
+      clearTimeout(timer);
+      proc.stdout?.off('data', onData);
+      emitLog(udid, 'info', `simulator-server for ${udid} speaks protocol ${protocol.protocolVersion}`);
+      resolve(protocol);
+    };
+    const onData = (data: Buffer) => {
+      const reply = data.toString().split('\n').find((line) => line.startsWith('capabilities '));
+      if (reply) {
+        finish(parseCapabilities(reply.slice('capabilities '.length).trim()));
+      }
+    };
+    const timer = setTimeout(() => finish(LEGACY_PROTOCOL), CAPABILITIES_TIMEOUT_MS);
+    proc.stdout?.on('data', onData);
+    proc.stdin?.write('capabilities\n');
+  });
+}
+
+/**
+ * Protocol of a simulator's session, or null without one
+ */
+function getSessionProtocol(udid: string): SessionProtocol | null {
+  const service = getSimulatorService();
+  if (service.backend) {
+    return service.backend.sessionProtocol?.(udid) ?? CURRENT_PROTOCOL;
+  }
+  return service.sessionCache.get(udid)?.protocol ?? null;
+}
+
+/**
+ * A simulator's session, with what its simulator-server supports. Null if it has none.
+ */
+export function getSessionInfo(udid: string): SessionInfo | null {
+  const service = getSimulatorService();
+  const session = service.sessionCache.get(udid);
+  if (session) {
+    return { udid, fps: session.fps, quality: session.quality, ...session.protocol, conditions: getAppliedConditions(udid) };
+  }
+  if (service.backend) {
+    const { fps, quality } = getStreamDefaults(udid);
+    return { udid, fps, quality, ...getSessionProtocol(udid)!, conditions: getAppliedConditions(udid) };
+  }
+  return null;
+}
+
+/**
+ * The simulators' running sessions
+ */
+export function listSessions(): SessionInfo[] {
+  return getActiveSessions().map((udid) => getSessionInfo(udid)!);
+}
+
+/**
+ * Set how crashed simulator-server processes are restarted
+ */
+export function setSessionRestartPolicy(policy: SessionRestartPolicy): void {
+  getSimulatorService().restartPolicy = policy;
+}
+
+/**
+ * Respawn a crashed session, backing off exponentially between failed attempts
+ */
+function scheduleRestart(udid: string, fps: number, quality: number, attempt: number): void {
+  const service = getSimulatorService();
+  const { maxAttempts, initialDelayMs, maxDelayMs } = service.restartPolicy;
+  if (attempt > maxAttempts) {
+    emitLog(udid, 'error', `Giving up restarting simulator-server for ${udid} after ${maxAttempts} attempts`);
+    service.sessionEmitter.emit('restart', { udid, status: 'gave_up', attempt: maxAttempts, maxAttempts });
+    return;
+  }
+
+  const delayMs = Math.min(initialDelayMs * 2 ** (attempt - 1), maxDelayMs);
+  emitLog(udid, 'info', `Restarting simulator-server for ${udid} in ${delayMs}ms (attempt ${attempt}/${maxAttempts})`);
+  service.sessionEmitter.emit('restart', { udid, status: 'scheduled', attempt, maxAttempts, delayMs });
+
+  const timer = setTimeout(async () => {
+    // A stream request may have started a new session in the meantime
+    if (service.sessionCache.has(udid) || service.pendingSessions.has(udid)) {
+      return;
+    }
+    try {
+      await getOrCreateSession(udid, fps, quality);
+      service.sessionEmitter.emit('restart', { udid, status: 'restarted', attempt, maxAttempts });
+    } catch (err) {
+      const error = err instanceof Error ? err.message : String(err);
+      emitLog(udid, 'error', `Restart attempt ${attempt} for ${udid} failed: ${error}`);
+      service.sessionEmitter.emit('restart', { udid, status: 'failed', attempt, maxAttempts, error });
+      scheduleRestart(udid, fps, quality, attempt + 1);
+    }
+  }, delayMs);
+  timer.unref();
+}
+
+/**
+ * Set how long a session without consumers is kept, in milliseconds (0 keeps sessions forever)
+ */
+export function setSessionIdleTimeout(timeoutMs: number): void {
+  getSimulatorService().sessionIdleTimeoutMs = timeoutMs;
+}
+
+/**
+ * (Re)start the idle timer of a session that nothing is using
+ */
+function scheduleIdleStop(udid: string): void {
+  const service = getSimulatorService();
+  clearTimeout(service.idleTimers.get(udid));
+  service.idleTimers.delete(udid);
+  if (service.sessionIdleTimeoutMs === 0 || service.prewarmedSessions.has(udid) || service.sessionConsumers.get(udid)?.size) {
+    return;
+  }
+  const timer = setTimeout(() => {
+    service.idleTimers.delete(udid);
+    if (service.sessionCache.has(udid)) {
+      emitLog(udid, 'info', `Stopping idle simulator-server for ${udid}`);
+      stopSession(udid);
+    }
+  }, service.sessionIdleTimeoutMs);
+  timer.unref();
+  service.idleTimers.set(udid, timer);
+}
+
+/**
+ * Mark a session as in use by `consumer` (e.g. `client:<id>`), so it isn't stopped for being idle.
+ * Acquiring the same consumer twice is the same as acquiring it once.
+ */
+export function acquireSession(udid: string, consumer: string): void {
+  const service = getSimulatorService();
+  const consumers = service.sessionConsumers.get(udid) || new Set<string>();
+  consumers.add(consumer);
+  service.sessionConsumers.set(udid, consumers);
+  clearTimeout(service.idleTimers.get(udid));
+  service.idleTimers.delete(udid);
+}
+
+/**
+ * Stop using a session. The last consumer to release it starts its idle timer.
+ */
+export function releaseSession(udid: string, consumer: string): void {
+  const service = getSimulatorService();
+  const consumers = service.sessionConsumers.get(udid);
+  if (!consumers?.delete(consumer)) {
+    return;
+  }
+  if (consumers.size === 0) {
+    service.sessionConsumers.delete(udid);
+  }
+  if (service.sessionCache.has(udid)) {
+    scheduleIdleStop(udid);
+  }
+}
+
+/**
+ * Stop a simulator's session and its simulator-server process. Returns false when it had none.
+ */
+export function stopSession(udid: string): boolean {
+  const service = getSimulatorService();
+  const session = service.sessionCache.get(udid);
+  clearTimeout(service.idleTimers.get(udid));
+  service.idleTimers.delete(udid);
+  service.sessionConsumers.delete(udid);
+  service.prewarmedSessions.delete(udid);
+  if (!session) {
+    return false;
+  }
+  // Removed first, so its exit isn't treated as a crash
+  service.sessionCache.delete(udid);
+  session.process.kill('SIGTERM');
+  emitLog(udid, 'info', `Stopped simulator-server for ${udid}`);
+  return true;
+}
+
 /**
  * Get or create a simulator session
  */
 export async function getOrCreateSession(
   udid: string,
-  fps: number = 60,
-  quality: number = 0.7
+  fps: number = getStreamDefaults(udid).fps,
+  quality: number = getStreamDefaults(udid).quality
 ): Promise<SimulatorSession> {
-  const existing = sessionCache.get(udid);
+  const service = getSimulatorService();
+  const existing = service.sessionCache.get(udid);
   if (existing) {
-    emitLog('info', `Reusing cached session for ${udid}`);
+    emitLog(udid, 'info', `Reusing cached session for ${udid}`);
     return existing;
   }
 
-  const session = await startSession(udid, fps, quality);
-  sessionCache.set(udid, session);
-  return session;
+  // Share an in-flight start so concurrent callers don't spawn twice
+  const pending = service.pendingSessions.get(udid);
+  if (pending) {
+    return pending;
+  }
+
+  const starting = startSession(udid, fps, quality)
+    .then((session) => {
+      service.sessionCache.set(udid, session);
+      scheduleIdleStop(udid);
+      return session;
+    })
+    .finally(() => service.pendingSessions.delete(udid));
+  service.pendingSessions.set(udid, starting);
+  return starting;
+}
+
+// Pre-warming keeps sessions ready for booted simulators so the first stream
+// request doesn't pay the spawn + handshake cost
+const PREWARM_INTERVAL_MS = 30000;
+
+async function prewarmSessions(allowlist: string[]): Promise<void> {
+  const service = getSimulatorService();
+  const allowAll = allowlist.includes('*');
+  let simulators: Simulator[];
+  try {
+    simulators = await listSimulators();
+  } catch (err) {
+    console.error('[simulator] Failed to list simulators for pre-warming:', err);
+    return;
+  }
+
+  for (const simulator of simulators) {
+    if (simulator.state !== 'Booted' || service.sessionCache.has(simulator.udid)) {
+      continue;
+    }
+    if (!allowAll && !allowlist.includes(simulator.udid) && !allowlist.includes(simulator.name)) {
+      continue;
+    }
+
+    emitLog(simulator.udid, 'info', `Pre-warming session for ${simulator.name} (${simulator.udid})`);
+    service.prewarmedSessions.add(simulator.udid);
+    try {
+      await getOrCreateSession(simulator.udid);
+    } catch (err) {
+      emitLog(simulator.udid, 'error', `Failed to pre-warm session for ${simulator.udid}: ${err instanceof Error ? err.message : err}`);
+    }
+  }
+}
+
+/**
+ * Periodically spawn sessions for booted simulators matching the allowlist
+ * (UDIDs or names, `*` for all). Does nothing when the allowlist is empty.
+ */
+export function startSessionPrewarming(allowlist: string[]): void {
+  const service = getSimulatorService();
+  if (allowlist.length === 0 || service.prewarmTimer) {
+    return;
+  }
+  prewarmSessions(allowlist);
+  service.prewarmTimer = setInterval(() => prewarmSessions(allowlist), PREWARM_INTERVAL_MS);
+  service.prewarmTimer.unref();
 }
 
 /**
  * Send a command to a session via stdin
  */
 export async function sendSessionCommand(udid: string, command: string): Promise<void> {
-  const session = sessionCache.get(udid);
+  const service = getSimulatorService();
+  trace(`[simulator] Session command for ${udid}: ${command}`);
+  const capability = COMMAND_CAPABILITIES[command.split(' ')[0]];
+  const protocol = getSessionProtocol(udid);
+  if (capability && protocol && !protocol.capabilities[capability]) {
+    throw new SessionCapabilityError(capability, protocol.protocolVersion);
+  }
+  if (service.backend) {
+    const sentAt = new Date().toISOString();
+    try {
+      await service.backend.sendSessionCommand(udid, command);
+      recordCommand({ udid, command, sentAt });
+    } catch (err) {
+      recordCommand({ udid, command, sentAt, error: err instanceof Error ? err.message : String(err) });
+      throw err;
+    }
+    return;
+  }
+
+  const session = service.sessionCache.get(udid);
   if (!session) {
     throw new Error(`No active session for simulator ${udid}`);
   }
+  // Input counts as use, so a session controlled without a stream isn't stopped under the user
+  if (service.idleTimers.has(udid)) {
+    scheduleIdleStop(udid);
+  }
 
   return new Promise((resolve, reject) => {
+    const sentAt = new Date().toISOString();
     session.stdin.write(`${command}\n`, (err) => {
+      recordCommand({ udid, command, sentAt, error: err?.message });
       if (err) {
         reject(new Error(`Failed to write command: ${err.message}`));
       } else {
@@ -252,17 +694,209 @@ export async function sendSessionCommand(udid: string, command: string): Promise
   });
 }
 
+function recordCommand(record: SessionCommandRecord): void {
+  const service = getSimulatorService();
+  const history = service.commandHistory.get(record.udid) || [];
+  history.push(record);
+  if (history.length > MAX_COMMAND_HISTORY) {
+    history.splice(0, history.length - MAX_COMMAND_HISTORY);
+  }
+  service.commandHistory.set(record.udid, history);
+  service.sessionEmitter.emit('command', record);
+}
+
+/**
+ * Commands written to a simulator's session, oldest first
+ */
+export function getSessionCommands(udid: string): SessionCommandRecord[] {
+  return getSimulatorService().commandHistory.get(udid) || [];
+}
+
 /**
  * Emit a log event
  */
-function emitLog(type: StreamLogEvent['type'], message: string) {
-  logEmitter.emit('log', { type, message });
+export function emitLog(udid: string, type: StreamLogEvent['type'], message: string) {
+  getSimulatorService().logEmitter.emit('log', appendLogEvent({ udid, type, message }));
+}
+
+/**
+ * List all available simulators (iOS, iPadOS, watchOS, tvOS and visionOS).
+ * A listing younger than the TTL is reused; an older one is returned while it refreshes
+ * in the background. Pass `refresh` to wait for a fresh listing.
+ */
+export async function listSimulators(options: { refresh?: boolean } = {}): Promise<Simulator[]> {
+  const service = getSimulatorService();
+  const cached = service.cachedSimulators;
+  if (options.refresh || !cached || service.cachedSimulatorsGeneration !== service.simulatorListGeneration) {
+    return refreshSimulators();
+  }
+
+  const age = Date.now() - Date.parse(cached.updatedAt);
+  if (age >= SIMULATOR_LIST_MAX_STALE_MS) {
+    return refreshSimulators();
+  }
+  if (age >= service.simulatorListTtlMs) {
+    refreshSimulators().catch((err) => console.error('[simulator] Failed to refresh simulators:', err));
+  }
+  return cached.simulators.map((simulator) => ({ ...simulator }));
+}
+
+/**
+ * List simulators from simctl and cache the result. Concurrent calls share one simctl run.
+ */
+function refreshSimulators(): Promise<Simulator[]> {
+  const service = getSimulatorService();
+  if (service.pendingSimulatorList?.generation === service.simulatorListGeneration) {
+    return service.pendingSimulatorList.promise;
+  }
+
+  const generation = service.simulatorListGeneration;
+  const pending: { generation: number; promise: Promise<Simulator[]> } = {
+    generation,
+    promise: listSimulatorsUncached()
+      .then((all) => {
+        const simulators = all.filter((simulator) => !simulator.name.endsWith(UNDO_SNAPSHOT_SUFFIX));
+        // A listing started before an invalidation may be older than one that finished first
+        if (generation >= service.cachedSimulatorsGeneration) {
+          service.cachedSimulators = { simulators, updatedAt: new Date().toISOString() };
+          service.cachedSimulatorsGeneration = generation;
+        }
+        return simulators.map((simulator) => ({ ...simulator }));
+      })
+      .finally(() => {
+        if (service.pendingSimulatorList === pending) {
+          service.pendingSimulatorList = null;
+        }
+      }),
+  };
+  service.pendingSimulatorList = pending;
+  return pending.promise;
+}
+
+/**
+ * Make the next listing wait for simctl, after simulators were booted, created or deleted
+ */
+export function invalidateSimulatorList(): void {
+  getSimulatorService().simulatorListGeneration++;
+}
+
+/**
+ * Set how long a simulator listing is reused before simctl is checked again
+ */
+export function setSimulatorListTtl(ttlMs: number): void {
+  getSimulatorService().simulatorListTtlMs = ttlMs;
+}
+
+/**
+ * Simulators from the last listing, without running simctl. Null if they were never listed.
+ */
+export function getCachedSimulators(): { simulators: Simulator[]; updatedAt: string } | null {
+  return getSimulatorService().cachedSimulators;
+}
+
+/**
+ * Device types and runtimes simulators can be created with, from `simctl list -j devicetypes runtimes`
+ */
+export async function listDeviceTypes(): Promise<SimulatorDeviceTypes> {
+  const json = JSON.parse((await runSimctl(['list', '-j', 'devicetypes', 'runtimes'])) || '{}');
+  return {
+    deviceTypes: ((json.devicetypes || []) as any[]).map((deviceType) => ({
+      identifier: deviceType.identifier,
+      name: deviceType.name,
+      productFamily: deviceType.productFamily || null,
+      deviceClass: deviceClassFor(deviceType.identifier),
+    })),
+    runtimes: ((json.runtimes || []) as any[]).map((runtime) => ({
+      identifier: runtime.identifier,
+      name: runtime.name,
+      version: runtime.version,
+      platform: runtime.platform || null,
+      available: runtime.isAvailable !== false,
+      supportedDeviceTypes: ((runtime.supportedDeviceTypes || []) as any[]).map((deviceType) => deviceType.identifier),
+    })),
+  };
+}
+
+/**
+ * Why a simulator can't be created with `request`, or null if it can. Device types and runtimes
+ * can be given by identifier or name.
+ */
+export function validateCreateSimulator(request: CreateSimulatorRequest, available: SimulatorDeviceTypes): string | null {
+  const deviceType = available.deviceTypes.find((candidate) => candidate.identifier === request.deviceType || candidate.name === request.deviceType);
+  if (!deviceType) {
+    return `Unknown device type: ${request.deviceType}`;
+  }
+  if (request.runtime === undefined) {
+    return null;
+  }
+  const runtime = available.runtimes.find((candidate) => candidate.identifier === request.runtime || candidate.name === request.runtime);
+  if (!runtime || !runtime.available) {
+    return `Unknown or unavailable runtime: ${request.runtime}`;
+  }
+  if (runtime.supportedDeviceTypes.length > 0 && !runtime.supportedDeviceTypes.includes(deviceType.identifier)) {
+    return `${runtime.name} doesn't support ${deviceType.name}`;
+  }
+  return null;
+}
+
+/**
+ * Create a simulator and return its UDID. Without a runtime, simctl picks the newest one that
+ * supports the device type.
+ */
+export async function createSimulator(request: CreateSimulatorRequest): Promise<string> {
+  const args = ['create', request.name.trim(), request.deviceType, ...(request.runtime ? [request.runtime] : [])];
+  const udid = (await runSimctl(args)).trim();
+  invalidateSimulatorList();
+  return udid;
+}
+
+/**
+ * Delete a simulator, shutting it down and stopping its session first
+ */
+export async function deleteSimulator(udid: string): Promise<void> {
+  stopSession(udid);
+  // simctl can't delete a booted simulator; shutting down one that isn't booted fails harmlessly
+  await runSimctl(['shutdown', udid]).catch(() => undefined);
+  await runSimctl(['delete', udid]);
+  getSimulatorService().deviceTypes.delete(udid);
+  invalidateSimulatorList();
+}
+
+/**
+ * Erase a simulator's content and settings, like a new one. It's shut down first and its session
+ * stopped, as simulator-server can't stream it through the shutdown; the next stream starts a new one.
+ * `beforeErase` runs once it's shut down, e.g. to snapshot it.
+ */
+export async function eraseSimulator(udid: string, beforeErase?: () => Promise<void>): Promise<void> {
+  stopSession(udid);
+  // simctl can only erase a shut down simulator
+  await runSimctl(['shutdown', udid]).catch(() => undefined);
+  await beforeErase?.();
+  await runSimctl(['erase', udid]);
+  getSimulatorService().launchedApps.delete(udid);
+  invalidateSimulatorList();
+  emitLog(udid, 'info', `Erased simulator ${udid}`);
 }
 
 /**
- * List all available iOS simulators
+ * UDIDs of simulators with a running simulator-server session
  */
-export async function listSimulators(): Promise<Simulator[]> {
+export function getActiveSessions(): string[] {
+  return Array.from(getSimulatorService().sessionCache.keys());
+}
+
+async function listSimulatorsUncached(): Promise<Simulator[]> {
+  const service = getSimulatorService();
+  if (service.backend) {
+    const simulators = await service.backend.listSimulators();
+    for (const simulator of simulators) {
+      if (simulator.deviceType) {
+        service.deviceTypes.set(simulator.udid, simulator.deviceType);
+      }
+    }
+    return simulators;
+  }
+
   return new Promise((resolve, reject) => {
     const proc = spawn('xcrun', ['simctl', 'list', 'devices', '-j']);
     registerProcess(proc);
@@ -292,11 +926,17 @@ export async function listSimulators(): Promise<Simulator[]> {
             if (Array.isArray(devices)) {
               for (const device of devices as any[]) {
                 if (device.udid && device.state !== 'Unavailable') {
+                  if (device.deviceTypeIdentifier) {
+                    service.deviceTypes.set(device.udid, device.deviceTypeIdentifier);
+                  }
                   simulators.push({
                     udid: device.udid,
                     name: device.name || '',
                     state: device.state || '',
                     runtime,
+                    deviceType: device.deviceTypeIdentifier || null,
+                    deviceClass: deviceClassFor(device.deviceTypeIdentifier || null),
+                    capabilities: CAPABILITIES[deviceClassFor(device.deviceTypeIdentifier || null)],
                   });
                 }
               }
@@ -321,43 +961,139 @@ export async function listSimulators(): Promise<Simulator[]> {
   });
 }
 
+export interface LaunchOptions {
+  // e.g. `fr` or `pt-BR`
+  language?: string;
+  // e.g. `fr_FR`
+  locale?: string;
+}
+
+/**
+ * Why a language or locale can't be used for a launch, or null if they can
+ */
+export function validateLaunchOptions(options: LaunchOptions): string | null {
+  if (options.language !== undefined && !/^[a-z]{2,3}(-[A-Za-z0-9]{2,8})*$/.test(options.language)) {
+    return `language must be a language code like "fr" or "pt-BR", got "${options.language}"`;
+  }
+  if (options.locale !== undefined && !/^[a-z]{2,3}(_[A-Za-z0-9]{2,8})*(@[A-Za-z0-9=;-]+)?$/.test(options.locale)) {
+    return `locale must be a locale identifier like "fr_FR", got "${options.locale}"`;
+  }
+  return null;
+}
+
+/**
+ * Arguments that make an app use a language and locale, overriding the simulator's for that
+ * launch only. Apps read them through NSArgumentDomain, which takes precedence over user defaults.
+ */
+export function launchArgsFor(options: LaunchOptions): string[] {
+  const args: string[] = [];
+  if (options.language) {
+    args.push('-AppleLanguages', `(${options.language})`);
+  }
+  if (options.locale) {
+    args.push('-AppleLocale', options.locale);
+  }
+  return args;
+}
+
 /**
  * Boot, install, and launch an app on a simulator
  */
 export async function installAndLaunch(
   udid: string,
   appPath: string,
-  bundleId?: string
-): Promise<string> {
+  bundleId?: string,
+  options: LaunchOptions = {}
+): Promise<LaunchResult> {
+  const service = getSimulatorService();
+  const { backend } = service;
+  const launchArgs = launchArgsFor(options);
+  if (backend) {
+    const result = await backend.installAndLaunch(udid, appPath, bundleId, launchArgs);
+    invalidateSimulatorList();
+    service.launchedApps.set(udid, result.bundleId);
+    recordUsage('launch', result.timings.total);
+    if (backend.appConsole) {
+      const launchId = attachConsole(udid, result.bundleId, (onOutput, onExit) =>
+        backend.appConsole!(udid, result.bundleId, onOutput, onExit)
+      );
+      return { ...result, launchId };
+    }
+    return result;
+  }
+
+  const timer = new SpanTimer();
+
   // Boot simulator
   console.log(`Booting simulator ${udid}...`);
-  await runCommand('xcrun', ['simctl', 'boot', udid]).catch((err) => {
-    // Ignore if already booted
-    if (!err.message.includes('current state: Booted')) {
-      console.log(`Boot warning: ${err.message}`);
-    }
-  });
+  await timer.measure('boot', () =>
+    withRetry('boot', () => runCommand('xcrun', ['simctl', 'boot', udid])).catch((err) => {
+      // Ignore if already booted
+      if (!err.message.includes('current state: Booted')) {
+        console.log(`Boot warning: ${err.message}`);
+      }
+      return '';
+    })
+  );
+  invalidateSimulatorList();
 
   // Install app
   console.log(`Installing app at ${appPath}...`);
-  await runCommand('xcrun', ['simctl', 'install', udid, appPath]);
+  await timer.measure('install', () => withRetry('install', () => runCommand('xcrun', ['simctl', 'install', udid, appPath])));
 
   // Get bundle ID if not provided
-  if (!bundleId) {
-    bundleId = await extractBundleId(appPath);
+  const resolvedBundleId = bundleId || (await timer.measure('resolveBundleId', () => extractBundleId(appPath)));
+
+  // simctl install sometimes succeeds without installing the app
+  const verification = await timer.measure('verify', () => verifyInstall(udid, appPath, resolvedBundleId));
+  if (verification.problems.length > 0) {
+    throw new InstallVerificationError(verification);
   }
 
-  // Launch app
-  console.log(`Launching app with bundle ID ${bundleId}...`);
-  await runCommand('xcrun', ['simctl', 'launch', udid, bundleId]);
+  // Launch app, replacing a running instance so its console can be attached and it picks up
+  // the launch language
+  console.log(`Launching app with bundle ID ${resolvedBundleId}...`);
+  const launchId = await timer.measure('launch', () =>
+    withRetry('launch', () => launchWithConsole(udid, resolvedBundleId, launchArgs))
+  );
+
+  const timings = timer.finish();
+  recordTimingsForDevice(udid, timings);
+  service.launchedApps.set(udid, resolvedBundleId);
+  recordUsage('launch', timings.total);
+
+  return {
+    message: `App ${resolvedBundleId} launched successfully`,
+    bundleId: resolvedBundleId,
+    timings,
+    verification,
+    launchId,
+  };
+}
+
+/**
+ * Bundle ID of the app last launched on a simulator through Plasma, if any
+ */
+export function getLaunchedApp(udid: string): string | null {
+  return getSimulatorService().launchedApps.get(udid) || null;
+}
 
-  return `App ${bundleId} launched successfully`;
+/**
+ * Persist launch timings keyed by the simulator's device type.
+ * Runs off the launch path since resolving the device type shells out to simctl.
+ */
+function recordTimingsForDevice(udid: string, timings: LaunchTimings): void {
+  listSimulators()
+    .then((simulators) => simulators.find((s) => s.udid === udid)?.deviceType || null)
+    .catch(() => null)
+    .then((deviceType) => recordLaunchTimings(udid, deviceType, timings))
+    .catch((err) => console.error('[simulator] Failed to record launch timings:', err));
 }
 
 /**
  * Run a command and return stdout
  */
-function runCommand(cmd: string, args: string[]): Promise<string> {
+export function runCommand(cmd: string, args: string[]): Promise<string> {
   return new Promise((resolve, reject) => {
     const proc = spawn(cmd, args);
     registerProcess(proc);
@@ -384,6 +1120,29 @@ function runCommand(cmd: string, args: string[]): Promise<string> {
   });
 }
 
+/**
+ * Run `xcrun simctl` with `args`, or the device backend's stand-in, and return stdout
+ */
+export function runSimctl(args: string[]): Promise<string> {
+  const { backend } = getSimulatorService();
+  if (backend?.simctl) {
+    return backend.simctl(args);
+  }
+  return runCommand('xcrun', ['simctl', ...args]);
+}
+
+/**
+ * A value of an app's Info.plist, or null if it has none or can't be read
+ */
+export async function readInfoPlistValue(appPath: string, key: string): Promise<string | null> {
+  try {
+    const value = await runCommand('/usr/libexec/PlistBuddy', ['-c', `Print :${key}`, path.join(appPath, 'Info.plist')]);
+    return value.trim() || null;
+  } catch {
+    return null;
+  }
+}
+
 /**
  * Extract bundle ID from an app's Info.plist
  */
@@ -398,53 +1157,179 @@ async function extractBundleId(appPath: string): Promise<string> {
 }
 
 /**
- * Send a tap using AXe
+ * Device class from a CoreSimulator device type identifier,
+ * e.g. com.apple.CoreSimulator.SimDeviceType.iPad-Pro-11-inch-M4-8GB
  */
-export async function sendTap(
+export function deviceClassFor(deviceType: string | null): DeviceClass {
+  const name = (deviceType || '').split('.').pop() || '';
+  if (name.startsWith('iPhone') || name.startsWith('iPod')) return 'phone';
+  if (name.startsWith('iPad')) return 'tablet';
+  if (name.startsWith('Apple-Watch')) return 'watch';
+  if (name.startsWith('Apple-TV')) return 'tv';
+  if (name.startsWith('Apple-Vision')) return 'vision';
+  return 'unknown';
+}
+
+/**
+ * Default stream settings for a simulator, based on its device class
+ */
+export function getStreamDefaults(udid: string): StreamSettings {
+  const service = getSimulatorService();
+  return { ...STREAM_DEFAULTS[deviceClassFor(service.deviceTypes.get(udid) || null)], ...service.streamDefaultOverrides };
+}
+
+/**
+ * Stream settings that apply to every device class, e.g. from the config
+ */
+export function setStreamDefaultOverrides(overrides: Partial<StreamSettings>): void {
+  getSimulatorService().streamDefaultOverrides = overrides;
+}
+
+/**
+ * What a simulator supports, based on its device class
+ */
+export function getSimulatorCapabilities(udid: string): SimulatorCapabilities {
+  return CAPABILITIES[deviceClassFor(getSimulatorService().deviceTypes.get(udid) || null)];
+}
+
+/**
+ * Route device operations through `backend` instead of simctl and AXe. Pass null to restore the default.
+ */
+export function setDeviceBackend(backend: DeviceBackend | null): void {
+  const service = getSimulatorService();
+  service.backend = backend;
+  service.deviceTypes.clear();
+  service.cachedSimulators = null;
+  service.pendingSimulatorList = null;
+}
+
+/**
+ * Start a stream generated by the device backend. Returns null when frames should come from simulator-server.
+ */
+export function startBackendStream(udid: string, fps: number, onFrame: (frame: BackendFrame) => void): (() => void) | null {
+  const { backend } = getSimulatorService();
+  return backend?.streamFrames ? backend.streamFrames(udid, fps, onFrame) : null;
+}
+
+/**
+ * Sample an app's resource usage through the device backend. Returns null when the
+ * app's process should be looked up on the simulator instead.
+ */
+export function sampleBackendResources(udid: string, bundleId: string): Promise<AppResourceSample | null> | null {
+  const { backend } = getSimulatorService();
+  return backend?.sampleAppResources ? backend.sampleAppResources(udid, bundleId) : null;
+}
+
+/**
+ * Record with the device backend, or null when simctl should record
+ */
+export function startBackendRecording(udid: string, file: string): Promise<() => Promise<void>> | null {
+  const { backend } = getSimulatorService();
+  return backend?.recordVideo ? backend.recordVideo(udid, file) : null;
+}
+
+/**
+ * Stream the system log with the device backend, or null when simctl should
+ */
+export function startBackendSystemLog(
+  udid: string,
+  predicate: string | null,
+  onOutput: (chunk: string) => void,
+  onExit: () => void
+): (() => void) | null {
+  const { backend } = getSimulatorService();
+  return backend?.streamSystemLog ? backend.streamSystemLog(udid, predicate, onOutput, onExit) : null;
+}
+
+/**
+ * Capture a simulator's screen as a PNG, at its full resolution
+ */
+export async function captureScreenshot(udid: string): Promise<Buffer> {
+  const { backend } = getSimulatorService();
+  if (backend?.screenshot) {
+    return backend.screenshot(udid);
+  }
+  const jobDir = createJobDir('capture');
+  try {
+    const file = path.join(jobDir.path, 'screenshot.png');
+    await runCommand('xcrun', ['simctl', 'io', udid, 'screenshot', '--type=png', file]);
+    return fs.readFileSync(file);
+  } finally {
+    jobDir.cleanup();
+  }
+}
+
+/**
+ * Capabilities for a device type, for backends that build their own simulator list
+ */
+export function capabilitiesFor(deviceType: string | null): SimulatorCapabilities {
+  return CAPABILITIES[deviceClassFor(deviceType)];
+}
+
+/**
+ * Map normalized stream coordinates to simulator points, using the device type's
+ * calibrated transform when there is one
+ */
+function toPoints(
   udid: string,
   x: number,
   y: number,
   screenWidth: number,
   screenHeight: number
-): Promise<void> {
-  const axePath = findAxeBinary();
-  if (!axePath) {
-    throw new Error('AXe binary not found');
+): { x: number; y: number } {
+  const service = getSimulatorService();
+  const transform = getTapTransform(service.deviceTypes.get(udid) || null);
+  if (transform) {
+    // Calibrations hold for either orientation; swap axes when the stream is rotated
+    const streamIsLandscape = screenWidth > screenHeight;
+    const calibratedLandscape = transform.scaleX > transform.scaleY;
+    const oriented = streamIsLandscape === calibratedLandscape
+      ? transform
+      : { scaleX: transform.scaleY, scaleY: transform.scaleX, offsetX: transform.offsetY, offsetY: transform.offsetX };
+    return applyTapTransform(oriented, x, y);
   }
 
-  // Calculate scale factor and point coordinates
-  const scaleFactor = screenWidth > 1000 ? 3.0 : screenWidth > 700 ? 2.0 : 1.0;
+  // iPads are all 2x. For phones, guess the scale factor from the short side so
+  // that landscape frames map the same way as portrait ones.
+  const shortSide = Math.min(screenWidth, screenHeight);
+  const deviceClass = deviceClassFor(service.deviceTypes.get(udid) || null);
+  const scaleFactor = deviceClass === 'tablet' ? 2.0 : shortSide > 1000 ? 3.0 : shortSide > 700 ? 2.0 : 1.0;
   const pointWidth = screenWidth / scaleFactor;
   const pointHeight = screenHeight / scaleFactor;
 
-  const pointX = Math.round(x * pointWidth);
-  const pointY = Math.round(y * pointHeight);
-
-  console.log(`Tap: normalized(${x.toFixed(3)}, ${y.toFixed(3)}) -> points(${pointX}, ${pointY})`);
-
-  const frameworksPath = path.join(path.dirname(axePath), 'Frameworks');
+  return { x: Math.round(x * pointWidth), y: Math.round(y * pointHeight) };
+}
 
-  await new Promise<void>((resolve, reject) => {
-    const proc = spawn(axePath, ['tap', '-x', pointX.toString(), '-y', pointY.toString(), '--udid', udid], {
-      env: { ...process.env, DYLD_FRAMEWORK_PATH: frameworksPath },
-    });
-    registerProcess(proc);
+/**
+ * The accessibility tree of the screen as AXe's describe-ui JSON, or the element at a point
+ */
+export async function describeAccessibility(udid: string, point?: { x: number; y: number }): Promise<string> {
+  const { backend } = getSimulatorService();
+  if (backend?.describeUi) {
+    return backend.describeUi(udid, point);
+  }
+  return runAxe('describe-ui', point ? ['--point', `${point.x},${point.y}`, '--udid', udid] : ['--udid', udid]);
+}
 
-    let stderr = '';
-    proc.stderr.on('data', (data) => {
-      stderr += data.toString();
-    });
+/**
+ * Send a tap using AXe
+ */
+export async function sendTap(
+  udid: string,
+  x: number,
+  y: number,
+  screenWidth: number,
+  screenHeight: number
+): Promise<void> {
+  const { backend } = getSimulatorService();
+  const point = toPoints(udid, x, y, screenWidth, screenHeight);
 
-    proc.on('close', (code) => {
-      if (code !== 0) {
-        reject(new Error(`AXe tap failed: ${stderr}`));
-      } else {
-        resolve();
-      }
-    });
+  console.log(`Tap: normalized(${x.toFixed(3)}, ${y.toFixed(3)}) -> points(${point.x}, ${point.y})`);
 
-    proc.on('error', reject);
-  });
+  if (backend) {
+    return backend.sendTap(udid, point.x, point.y);
+  }
+  await runAxe('tap', ['-x', point.x.toString(), '-y', point.y.toString(), '--udid', udid]);
 }
 
 /**
@@ -460,51 +1345,122 @@ export async function sendSwipe(
   screenHeight: number,
   duration: number = 0.3
 ): Promise<void> {
-  const axePath = findAxeBinary();
-  if (!axePath) {
-    throw new Error('AXe binary not found');
+  const { backend } = getSimulatorService();
+  const start = toPoints(udid, startX, startY, screenWidth, screenHeight);
+  const end = toPoints(udid, endX, endY, screenWidth, screenHeight);
+
+  if (backend) {
+    return backend.sendSwipe(udid, start, end, duration);
   }
+  await runAxe('swipe', [
+    '--start-x', start.x.toString(),
+    '--start-y', start.y.toString(),
+    '--end-x', end.x.toString(),
+    '--end-y', end.y.toString(),
+    '--duration', duration.toString(),
+    '--udid', udid,
+  ]);
+}
 
-  const scaleFactor = screenWidth > 1000 ? 3.0 : screenWidth > 700 ? 2.0 : 1.0;
-  const pointWidth = screenWidth / scaleFactor;
-  const pointHeight = screenHeight / scaleFactor;
+// HID keyboard usage codes of the keys that can be pressed by name
+const KEY_CODES: Record<NamedKey, number> = {
+  return: 0x28,
+  escape: 0x29,
+  delete: 0x2a,
+  tab: 0x2b,
+  space: 0x2c,
+  home: 0x4a,
+  pageUp: 0x4b,
+  forwardDelete: 0x4c,
+  end: 0x4d,
+  pageDown: 0x4e,
+  right: 0x4f,
+  left: 0x50,
+  down: 0x51,
+  up: 0x52,
+};
+
+// What AXe can type: the printable characters of a US keyboard, newlines and tabs
+const TYPEABLE_TEXT = /^[\x20-\x7e\n\t]*$/;
+
+export function isTypeableText(text: string): boolean {
+  return TYPEABLE_TEXT.test(text);
+}
 
-  const pointStartX = Math.round(startX * pointWidth);
-  const pointStartY = Math.round(startY * pointHeight);
-  const pointEndX = Math.round(endX * pointWidth);
-  const pointEndY = Math.round(endY * pointHeight);
-
-  const frameworksPath = path.join(path.dirname(axePath), 'Frameworks');
-
-  await new Promise<void>((resolve, reject) => {
-    const proc = spawn(axePath, [
-      'swipe',
-      '--start-x', pointStartX.toString(),
-      '--start-y', pointStartY.toString(),
-      '--end-x', pointEndX.toString(),
-      '--end-y', pointEndY.toString(),
-      '--duration', duration.toString(),
-      '--udid', udid,
-    ], {
-      env: { ...process.env, DYLD_FRAMEWORK_PATH: frameworksPath },
-    });
-    registerProcess(proc);
+/**
+ * HID usage code of a key given by name or code, or null when it isn't one
+ */
+export function keyCodeFor(key: unknown): number | null {
+  if (typeof key === 'number') {
+    return Number.isInteger(key) && key >= 0 && key <= 0xffff ? key : null;
+  }
+  return typeof key === 'string' && Object.prototype.hasOwnProperty.call(KEY_CODES, key) ? KEY_CODES[key as NamedKey] : null;
+}
 
-    let stderr = '';
-    proc.stderr.on('data', (data) => {
-      stderr += data.toString();
-    });
+/**
+ * Type text into the focused field using AXe
+ */
+export async function typeText(udid: string, text: string): Promise<void> {
+  const { backend } = getSimulatorService();
+  if (backend) {
+    return backend.typeText(udid, text);
+  }
+  await runAxe('type', [text, '--udid', udid]);
+}
 
-    proc.on('close', (code) => {
-      if (code !== 0) {
-        reject(new Error(`AXe swipe failed: ${stderr}`));
-      } else {
-        resolve();
-      }
-    });
+// simulator-server's names for the orientations
+const ROTATIONS: Record<SimulatorOrientation, string> = {
+  portrait: 'Portrait',
+  'landscape-left': 'LandscapeLeft',
+  'landscape-right': 'LandscapeRight',
+  'upside-down': 'PortraitUpsideDown',
+};
 
-    proc.on('error', reject);
-  });
+export function isSimulatorOrientation(value: unknown): value is SimulatorOrientation {
+  return typeof value === 'string' && Object.prototype.hasOwnProperty.call(ROTATIONS, value);
+}
+
+/**
+ * Rotate the device, like the Simulator app's Rotate Left and Rotate Right. Apps that don't
+ * support the orientation stay as they are, as on a real device.
+ */
+export async function setOrientation(udid: string, orientation: SimulatorOrientation): Promise<void> {
+  await sendSessionCommand(udid, `rotate ${ROTATIONS[orientation]}`);
+}
+
+/**
+ * Press and release a key, through the simulator-server session like keys from the control socket
+ */
+export async function pressKey(udid: string, code: number): Promise<void> {
+  await sendSessionCommand(udid, `key ${code} down`);
+  await sendSessionCommand(udid, `key ${code} up`);
+}
+
+export const HARDWARE_BUTTONS: HardwareButton[] = ['home', 'lock', 'volume-up', 'volume-down', 'siri'];
+
+// simulator-server's names for the buttons AXe can't press
+const SESSION_BUTTONS: Partial<Record<HardwareButton, string>> = {
+  'volume-up': 'volumeUp',
+  'volume-down': 'volumeDown',
+};
+
+/**
+ * Press and release a hardware button. Home, lock and Siri go through AXe; the volume buttons
+ * through the simulator-server session, so they need the simulator to be streaming.
+ */
+export async function pressButton(udid: string, button: HardwareButton): Promise<void> {
+  const { backend } = getSimulatorService();
+  const sessionButton = SESSION_BUTTONS[button];
+  if (sessionButton) {
+    await sendSessionCommand(udid, `button ${sessionButton} down`);
+    await sendSessionCommand(udid, `button ${sessionButton} up`);
+    return;
+  }
+  if (backend) {
+    await backend.pressButton?.(udid, button);
+    return;
+  }
+  await runAxe('button', [button, '--udid', udid]);
 }
 
 /**
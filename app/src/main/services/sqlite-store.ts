@@ -0,0 +1,343 @@
+import { randomUUID } from 'crypto';
+import { getDatabase, getSetting, setSetting, deleteSetting } from './database';
+import { nextUpdatedAt, notificationListClauses } from './data-store';
+import type {
+  BuildRow,
+  DataStore,
+  NotificationRow,
+  NotifierRow,
+  ProjectKey,
+  SettingRow,
+  StoredDestination,
+  StoredProjectSettings,
+} from './data-store';
+import type { SecretInfo, UnifiedProject } from '../../shared/ipc-types';
+
+/**
+ * The data store in the local SQLite database, shared with the rest of Plasma
+ */
+
+interface ProjectSettingsRow {
+  scheme: string | null;
+  configuration: string | null;
+  simulator_udid: string | null;
+  environment: string;
+  updated_at: string;
+}
+
+const NOTIFIER_COLUMNS = 'id, project_id, kind, events, template, enabled, created_at';
+
+function getProject(id: number): UnifiedProject | null {
+  return (getDatabase().prepare('SELECT * FROM unified_projects WHERE id = ?').get(id) as UnifiedProject | undefined) || null;
+}
+
+function getDestination(project: ProjectKey): StoredDestination | null {
+  const row = getDatabase().prepare(
+    'SELECT preset, updated_at FROM project_build_destinations WHERE project_id = ?'
+  ).get(project.id) as { preset: string; updated_at: string } | undefined;
+  return row ? { preset: row.preset, updatedAt: row.updated_at } : null;
+}
+
+function getProjectSettings(project: ProjectKey): StoredProjectSettings | null {
+  const row = getDatabase().prepare('SELECT * FROM project_settings WHERE project_id = ?').get(project.id) as ProjectSettingsRow | undefined;
+  if (!row) {
+    return null;
+  }
+  return {
+    scheme: row.scheme,
+    configuration: row.configuration,
+    simulatorUdid: row.simulator_udid,
+    environment: JSON.parse(row.environment),
+    updatedAt: row.updated_at,
+  };
+}
+
+function getNotifier(project: ProjectKey, id: number): NotifierRow | null {
+  return (getDatabase().prepare(
+    `SELECT ${NOTIFIER_COLUMNS} FROM project_notifiers WHERE project_id = ? AND id = ?`
+  ).get(project.id, id) as NotifierRow | undefined) || null;
+}
+
+function getNotification(id: number): NotificationRow | null {
+  return (getDatabase().prepare('SELECT * FROM notifications WHERE id = ?').get(id) as NotificationRow | undefined) || null;
+}
+
+function getSettingRow(key: string): SettingRow | null {
+  const stored = getSetting(key);
+  return stored ? { key, value: stored.value, updated_at: stored.updated_at } : null;
+}
+
+export function createSqliteDataStore(): DataStore {
+  return {
+    backend: 'sqlite',
+
+    projects: {
+      async recent(limit) {
+        return getDatabase().prepare('SELECT * FROM unified_projects ORDER BY last_opened_at DESC LIMIT ?').all(limit) as UnifiedProject[];
+      },
+      async all() {
+        return getDatabase().prepare('SELECT * FROM unified_projects ORDER BY id').all() as UnifiedProject[];
+      },
+      async count() {
+        return (getDatabase().prepare('SELECT COUNT(*) AS count FROM unified_projects').get() as { count: number }).count;
+      },
+      async get(id) {
+        return getProject(id);
+      },
+      async findByPath(projectPath) {
+        return (getDatabase().prepare(
+          'SELECT * FROM unified_projects WHERE xcode_path = ? OR android_path = ? ORDER BY last_opened_at DESC LIMIT 1'
+        ).get(projectPath, projectPath) as UnifiedProject | undefined) || null;
+      },
+      async save(name, xcodePath, androidPath) {
+        const db = getDatabase();
+        const now = new Date().toISOString();
+        // A project with these exact paths
+        const existing = db.prepare(
+          'SELECT id FROM unified_projects WHERE xcode_path IS ? AND android_path IS ?'
+        ).get(xcodePath, androidPath) as { id: number } | undefined;
+        if (existing) {
+          db.prepare('UPDATE unified_projects SET name = ?, last_opened_at = ? WHERE id = ?').run(name, now, existing.id);
+          return getProject(existing.id)!;
+        }
+        const result = db.prepare(
+          'INSERT INTO unified_projects (uuid, name, xcode_path, android_path, last_opened_at, created_at) VALUES (?, ?, ?, ?, ?, ?)'
+        ).run(randomUUID(), name, xcodePath, androidPath, now, now);
+        return getProject(result.lastInsertRowid as number)!;
+      },
+      async setNotes(id, notes) {
+        getDatabase().prepare('UPDATE unified_projects SET notes = ? WHERE id = ?').run(notes, id);
+      },
+      async markOpened(id) {
+        getDatabase().prepare('UPDATE unified_projects SET last_opened_at = ? WHERE id = ?').run(new Date().toISOString(), id);
+      },
+    },
+
+    projectDestinations: {
+      async get(project) {
+        return getDestination(project);
+      },
+      async set(project, preset, expectedUpdatedAt) {
+        const db = getDatabase();
+        const write = db.transaction(() => {
+          const current = getDestination(project);
+          if (expectedUpdatedAt !== undefined && (current?.updatedAt ?? null) !== expectedUpdatedAt) {
+            return false;
+          }
+          db.prepare(`
+            INSERT INTO project_build_destinations (project_id, preset, updated_at) VALUES (?, ?, ?)
+            ON CONFLICT(project_id) DO UPDATE SET preset = excluded.preset, updated_at = excluded.updated_at
+          `).run(project.id, preset, nextUpdatedAt(current?.updatedAt ?? null));
+          return true;
+        });
+        return write.immediate();
+      },
+    },
+
+    projectSettings: {
+      async get(project) {
+        return getProjectSettings(project);
+      },
+      async update(project, changes, expectedUpdatedAt) {
+        const db = getDatabase();
+        const write = db.transaction(() => {
+          const current = getProjectSettings(project);
+          if (expectedUpdatedAt !== undefined && (current?.updatedAt ?? null) !== expectedUpdatedAt) {
+            return false;
+          }
+          const pick = <T>(change: T | undefined, stored: T): T => (change === undefined ? stored : change);
+          db.prepare(`
+            INSERT INTO project_settings (project_id, scheme, configuration, simulator_udid, environment, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(project_id) DO UPDATE SET
+              scheme = excluded.scheme,
+              configuration = excluded.configuration,
+              simulator_udid = excluded.simulator_udid,
+              environment = excluded.environment,
+              updated_at = excluded.updated_at
+          `).run(
+            project.id,
+            pick(changes.scheme, current?.scheme ?? null),
+            pick(changes.configuration, current?.configuration ?? null),
+            pick(changes.simulatorUdid, current?.simulatorUdid ?? null),
+            JSON.stringify(pick(changes.environment, current?.environment ?? {})),
+            nextUpdatedAt(current?.updatedAt ?? null)
+          );
+          return true;
+        });
+        return write.immediate();
+      },
+    },
+
+    notifiers: {
+      async list(project) {
+        return getDatabase().prepare(
+          `SELECT ${NOTIFIER_COLUMNS} FROM project_notifiers WHERE project_id = ? ORDER BY id`
+        ).all(project.id) as NotifierRow[];
+      },
+      async get(project, id) {
+        return getNotifier(project, id);
+      },
+      async insert(project, notifier) {
+        const result = getDatabase().prepare(
+          'INSERT INTO project_notifiers (project_id, kind, webhook_url, events, template, enabled, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)'
+        ).run(project.id, notifier.kind, '', notifier.events, notifier.template, notifier.enabled, notifier.created_at);
+        return getNotifier(project, result.lastInsertRowid as number)!;
+      },
+      async delete(project, id) {
+        return getDatabase().prepare('DELETE FROM project_notifiers WHERE project_id = ? AND id = ?').run(project.id, id).changes > 0;
+      },
+      async plaintextWebhookUrls() {
+        const rows = getDatabase().prepare(
+          "SELECT id, webhook_url FROM project_notifiers WHERE webhook_url != ''"
+        ).all() as { id: number; webhook_url: string }[];
+        return new Map(rows.map((row) => [row.id, row.webhook_url]));
+      },
+      async clearPlaintextWebhookUrl(id) {
+        getDatabase().prepare("UPDATE project_notifiers SET webhook_url = '' WHERE id = ?").run(id);
+      },
+    },
+
+    notifications: {
+      async insert(notification) {
+        const result = getDatabase().prepare(
+          'INSERT INTO notifications (kind, level, title, message, data, created_at) VALUES (?, ?, ?, ?, ?, ?)'
+        ).run(notification.kind, notification.level, notification.title, notification.message, notification.data, notification.created_at);
+        return getNotification(result.lastInsertRowid as number)!;
+      },
+      async list(query, unreadOnly) {
+        const db = getDatabase();
+        const { where, orderBy, values } = notificationListClauses(query, unreadOnly, () => '?');
+        const { total } = db.prepare(`SELECT COUNT(*) AS total FROM notifications ${where}`).get(...values) as { total: number };
+        const rows = db.prepare(`
+          SELECT * FROM notifications
+          ${where}
+          ORDER BY ${orderBy}
+          LIMIT ? OFFSET ?
+        `).all(...values, Number.isFinite(query.limit) ? query.limit : -1, query.offset) as NotificationRow[];
+        return { rows, total };
+      },
+      async listSince(id) {
+        return getDatabase().prepare('SELECT * FROM notifications WHERE id > ? ORDER BY id').all(id) as NotificationRow[];
+      },
+      async countUnread() {
+        return (getDatabase().prepare('SELECT COUNT(*) AS count FROM notifications WHERE read_at IS NULL').get() as { count: number }).count;
+      },
+      async markRead(id) {
+        const result = getDatabase().prepare(
+          'UPDATE notifications SET read_at = COALESCE(read_at, ?) WHERE id = ?'
+        ).run(new Date().toISOString(), id);
+        return result.changes > 0;
+      },
+      async markAllRead() {
+        getDatabase().prepare('UPDATE notifications SET read_at = ? WHERE read_at IS NULL').run(new Date().toISOString());
+      },
+    },
+
+    builds: {
+      async insert(build, project) {
+        getDatabase().prepare(`
+          INSERT INTO builds (
+            id, project_id, project_path, scheme, configuration, destination, status, success,
+            duration_ms, warnings, errors, products, build_settings, started_at, finished_at, environment
+          )
+          VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        `).run(
+          build.id,
+          project?.id ?? null,
+          build.project_path,
+          build.scheme,
+          build.configuration,
+          build.destination,
+          build.status,
+          build.success,
+          build.duration_ms,
+          build.warnings,
+          build.errors,
+          build.products,
+          build.build_settings,
+          build.started_at,
+          build.finished_at,
+          build.environment
+        );
+      },
+      async get(id) {
+        return (getDatabase().prepare('SELECT * FROM builds WHERE id = ?').get(id) as BuildRow | undefined) || null;
+      },
+      async list(project) {
+        const db = getDatabase();
+        return (
+          project
+            ? db.prepare('SELECT * FROM builds WHERE project_id = ? ORDER BY finished_at DESC').all(project.id)
+            : db.prepare('SELECT * FROM builds ORDER BY finished_at DESC').all()
+        ) as BuildRow[];
+      },
+      async findByChecksum(sha256) {
+        return getDatabase().prepare('SELECT * FROM builds WHERE products LIKE ? ORDER BY finished_at DESC').all(`%${sha256}%`) as BuildRow[];
+      },
+      async recentDurations(projectPath, scheme, destination, limit) {
+        const rows = getDatabase().prepare(`
+          SELECT duration_ms FROM builds
+          WHERE project_path = ? AND scheme = ? AND destination = ? AND success = 1
+          ORDER BY finished_at DESC LIMIT ?
+        `).all(projectPath, scheme, destination, limit) as Array<{ duration_ms: number }>;
+        return rows.map((row) => row.duration_ms);
+      },
+      async firstSucceeded() {
+        return (getDatabase().prepare(
+          'SELECT * FROM builds WHERE success = 1 ORDER BY finished_at ASC LIMIT 1'
+        ).get() as BuildRow | undefined) || null;
+      },
+    },
+
+    settings: {
+      async get(key) {
+        return getSettingRow(key);
+      },
+      async list() {
+        return getDatabase().prepare('SELECT key, value, updated_at FROM settings ORDER BY key').all() as SettingRow[];
+      },
+      async update(key, update) {
+        const write = getDatabase().transaction(() => {
+          const current = getSetting(key)?.value ?? null;
+          const next = update(current);
+          if (next === current) {
+            return getSettingRow(key);
+          }
+          if (next === null) {
+            deleteSetting(key);
+          } else {
+            setSetting(key, next);
+          }
+          return getSettingRow(key);
+        });
+        return write.immediate();
+      },
+    },
+
+    secrets: {
+      async list() {
+        return getDatabase().prepare(
+          'SELECT name, created_at AS createdAt, updated_at AS updatedAt FROM secrets ORDER BY name'
+        ).all() as SecretInfo[];
+      },
+      async getCiphertext(name) {
+        const row = getDatabase().prepare('SELECT ciphertext FROM secrets WHERE name = ?').get(name) as { ciphertext: string } | undefined;
+        return row?.ciphertext ?? null;
+      },
+      async set(name, ciphertext) {
+        const now = new Date().toISOString();
+        getDatabase().prepare(`
+          INSERT INTO secrets (name, ciphertext, created_at, updated_at) VALUES (?, ?, ?, ?)
+          ON CONFLICT(name) DO UPDATE SET ciphertext = excluded.ciphertext, updated_at = excluded.updated_at
+        `).run(name, ciphertext, now, now);
+      },
+      async delete(name) {
+        return getDatabase().prepare('DELETE FROM secrets WHERE name = ?').run(name).changes > 0;
+      },
+    },
+
+    // The SQLite connection is shared with the rest of Plasma and closed with it
+    async close() {},
+  };
+}
@@ -0,0 +1,260 @@
+import * as http from 'http';
+import * as fs from 'fs';
+import * as path from 'path';
+import { createHash, createHmac } from 'crypto';
+import fetch, { Response } from 'node-fetch';
+import { getDataDir } from './database';
+import { getSecret } from './secrets';
+import { sendFile, contentTypeFor, SendFileOptions } from '../file-response';
+
+/**
+ * Storage for artifacts like build logs, recordings and screenshots. Artifacts are addressed
+ * by keys such as `build-logs/<id>.log` and live either on the local disk or in an
+ * S3-compatible bucket (AWS S3, MinIO).
+ */
+
+export type StorageBackend = 'local' | 's3';
+
+// Name of the secret holding the S3 secret access key
+export const S3_SECRET_ACCESS_KEY_SECRET = 's3.secret_access_key';
+
+export interface S3Settings {
+  // e.g. `https://s3.eu-west-1.amazonaws.com` or `http://minio.local:9000`. Objects are addressed path-style.
+  endpoint: string;
+  bucket: string;
+  region: string;
+  accessKeyId: string;
+  // From the environment; the `s3.secret_access_key` secret takes precedence
+  secretAccessKey: string;
+  // Prepended to every key, e.g. `plasma/`
+  prefix: string;
+}
+
+export type StorageSettings = { backend: 'local'; dir: string | null } | { backend: 's3'; s3: S3Settings };
+
+export interface ArtifactInfo {
+  size: number;
+  modifiedAt: Date;
+}
+
+export interface ArtifactStore {
+  readonly backend: StorageBackend;
+  // Move a finished file into the store. The original is removed once it's stored.
+  importFile(key: string, filePath: string): Promise<void>;
+  stat(key: string): Promise<ArtifactInfo | null>;
+  delete(key: string): Promise<void>;
+  // Respond with the artifact, answering HEAD and Range requests like `sendFile`
+  send(req: http.IncomingMessage, res: http.ServerResponse, key: string, options?: SendFileOptions): Promise<void>;
+}
+
+/**
+ * Reject keys that could escape the store's root
+ */
+function checkKey(key: string): string {
+  const segments = key.split('/');
+  if (!key || segments.some((segment) => segment === '' || segment === '.' || segment === '..')) {
+    throw new Error(`Invalid artifact key "${key}"`);
+  }
+  return key;
+}
+
+export function createLocalStore(root: string): ArtifactStore {
+  const pathFor = (key: string) => path.join(root, checkKey(key));
+
+  return {
+    backend: 'local',
+
+    async importFile(key, filePath) {
+      const target = pathFor(key);
+      if (path.resolve(target) === path.resolve(filePath)) {
+        return;
+      }
+      fs.mkdirSync(path.dirname(target), { recursive: true });
+      fs.copyFileSync(filePath, target);
+      fs.rmSync(filePath, { force: true });
+    },
+
+    async stat(key) {
+      try {
+        const stats = fs.statSync(pathFor(key));
+        return { size: stats.size, modifiedAt: stats.mtime };
+      } catch {
+        return null;
+      }
+    },
+
+    async delete(key) {
+      fs.rmSync(pathFor(key), { force: true });
+    },
+
+    async send(req, res, key, options) {
+      sendFile(req, res, pathFor(key), options);
+    },
+  };
+}
+
+function sha256(data: string): string {
+  return createHash('sha256').update(data).digest('hex');
+}
+
+function hmac(key: Buffer | string, data: string): Buffer {
+  return createHmac('sha256', key).update(data).digest();
+}
+
+// RFC 3986 encoding, as required for SigV4 canonical URIs
+function encodeSegment(segment: string): string {
+  return encodeURIComponent(segment).replace(/[!'()*]/g, (c) => `%${c.charCodeAt(0).toString(16).toUpperCase()}`);
+}
+
+/**
+ * Sign a request with AWS Signature Version 4. The payload is left unsigned so
+ * uploads can be streamed from disk.
+ */
+export function signS3Request(
+  settings: S3Settings,
+  method: string,
+  url: URL,
+  now: Date = new Date()
+): Record<string, string> {
+  const amzDate = now.toISOString().replace(/[:-]|\.\d{3}/g, '');
+  const date = amzDate.slice(0, 8);
+  const payloadHash = 'UNSIGNED-PAYLOAD';
+  const scope = `${date}/${settings.region}/s3/aws4_request`;
+
+  const canonicalRequest = [
+    method,
+    url.pathname,
+    '',
+    `host:${url.host}\nx-amz-content-sha256:${payloadHash}\nx-amz-date:${amzDate}\n`,
+    'host;x-amz-content-sha256;x-amz-date',
+    payloadHash,
+  ].join('\n');
+  const stringToSign = ['AWS4-HMAC-SHA256', amzDate, scope, sha256(canonicalRequest)].join('\n');
+
+  const signingKey = ['s3', 'aws4_request'].reduce(
+    (key, part) => hmac(key, part),
+    hmac(hmac(`AWS4${settings.secretAccessKey}`, date), settings.region)
+  );
+  const signature = createHmac('sha256', signingKey).update(stringToSign).digest('hex');
+
+  return {
+    'x-amz-content-sha256': payloadHash,
+    'x-amz-date': amzDate,
+    Authorization: `AWS4-HMAC-SHA256 Credential=${settings.accessKeyId}/${scope}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature=${signature}`,
+  };
+}
+
+export function createS3Store(settings: S3Settings): ArtifactStore {
+  const urlFor = (key: string) => {
+    const objectPath = [settings.bucket, ...`${settings.prefix}${checkKey(key)}`.split('/')].map(encodeSegment).join('/');
+    return new URL(`${settings.endpoint.replace(/\/+$/, '')}/${objectPath}`);
+  };
+
+  const request = async (method: string, key: string, init: { headers?: Record<string, string>; body?: fs.ReadStream } = {}): Promise<Response> => {
+    const url = urlFor(key);
+    const secretAccessKey = (await getSecret(S3_SECRET_ACCESS_KEY_SECRET)) || settings.secretAccessKey;
+    return fetch(url.toString(), {
+      method,
+      headers: { ...init.headers, ...signS3Request({ ...settings, secretAccessKey }, method, url) },
+      body: init.body,
+    });
+  };
+
+  const fail = async (response: Response, action: string, key: string): Promise<never> => {
+    throw new Error(`Failed to ${action} ${key} in ${settings.bucket}: ${response.status} ${await response.text()}`);
+  };
+
+  return {
+    backend: 's3',
+
+    async importFile(key, filePath) {
+      const { size } = fs.statSync(filePath);
+      const response = await request('PUT', key, {
+        headers: { 'Content-Length': String(size), 'Content-Type': contentTypeFor(key) },
+        body: fs.createReadStream(filePath),
+      });
+      if (!response.ok) {
+        await fail(response, 'upload', key);
+      }
+      fs.rmSync(filePath, { force: true });
+    },
+
+    async stat(key) {
+      const response = await request('HEAD', key);
+      if (response.status === 404) {
+        return null;
+      }
+      if (!response.ok) {
+        await fail(response, 'look up', key);
+      }
+      return {
+        size: Number(response.headers.get('content-length') || 0),
+        modifiedAt: new Date(response.headers.get('last-modified') || Date.now()),
+      };
+    },
+
+    async delete(key) {
+      const response = await request('DELETE', key);
+      if (!response.ok && response.status !== 404) {
+        await fail(response, 'delete', key);
+      }
+    },
+
+    async send(req, res, key, options = {}) {
+      const method = req.method === 'HEAD' ? 'HEAD' : 'GET';
+      const response = await request(method, key, { headers: req.headers.range ? { Range: req.headers.range } : {} });
+
+      if (response.status === 404) {
+        res.writeHead(404, { 'Content-Type': 'application/json' });
+        res.end(JSON.stringify({ error: 'File not found' }));
+        return;
+      }
+      if (!response.ok && response.status !== 416) {
+        await fail(response, 'download', key);
+      }
+
+      const headers: http.OutgoingHttpHeaders = {
+        'Content-Type': options.contentType || contentTypeFor(key),
+        'Accept-Ranges': 'bytes',
+      };
+      for (const name of ['content-length', 'content-range', 'last-modified']) {
+        const value = response.headers.get(name);
+        if (value) {
+          headers[name] = value;
+        }
+      }
+      if (options.downloadName) {
+        headers['Content-Disposition'] = `attachment; filename="${options.downloadName.replace(/"/g, '')}"`;
+      }
+
+      res.writeHead(response.status, headers);
+      if (method === 'HEAD' || !response.body) {
+        res.end();
+        return;
+      }
+      const body = response.body as unknown as NodeJS.ReadableStream & { destroy?: () => void };
+      req.on('close', () => body.destroy?.());
+      body.pipe(res);
+    },
+  };
+}
+
+let store: ArtifactStore | null = null;
+
+export function createArtifactStore(settings: StorageSettings): ArtifactStore {
+  return settings.backend === 's3' ? createS3Store(settings.s3) : createLocalStore(settings.dir || getDataDir());
+}
+
+/**
+ * Use a different store, e.g. the one from the config. Defaults to the local data directory.
+ */
+export function setArtifactStore(newStore: ArtifactStore | null): void {
+  store = newStore;
+}
+
+export function getArtifactStore(): ArtifactStore {
+  if (!store) {
+    store = createLocalStore(getDataDir());
+  }
+  return store;
+}
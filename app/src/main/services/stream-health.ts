@@ -0,0 +1,193 @@
+import fetch from 'node-fetch';
+import { recordNotification } from './notifications';
+import { defineMetric, setGauge, removeSeries } from './metrics';
+import { recordUsage } from './analytics';
+import { getSecret } from './secrets';
+import type { StreamAlert, StreamAlertRule } from '../../shared/ipc-types';
+
+export interface StreamAlertSettings {
+  enabled: boolean;
+  // Alert when a stream delivers less than this fraction of its target fps
+  minFpsRatio: number;
+  // Alert when no frame arrived for this long
+  maxFrameGapMs: number;
+  // Alert when a simulator's session restarts this many times within the window
+  maxRestarts: number;
+  restartWindowMinutes: number;
+  // Also POST alerts as JSON to this URL, from the environment; the `alerts.webhook_url` secret takes precedence
+  webhookUrl: string | null;
+}
+
+export const ALERT_WEBHOOK_SECRET = 'alerts.webhook_url';
+
+interface MonitoredStream {
+  targetFps: number;
+  startedAt: number;
+  frames: number[];
+}
+
+const EVALUATION_INTERVAL_MS = 5000;
+// Window the fps is measured over
+const FPS_WINDOW_MS = 10000;
+
+defineMetric('plasma_stream_fps', 'gauge', 'Frames per second delivered over the last 10 seconds');
+defineMetric('plasma_stream_target_fps', 'gauge', 'Frames per second requested for a stream');
+
+let settings: StreamAlertSettings = {
+  enabled: false,
+  minFpsRatio: 0.5,
+  maxFrameGapMs: 3000,
+  maxRestarts: 3,
+  restartWindowMinutes: 10,
+  webhookUrl: null,
+};
+
+const streams = new Map<string, MonitoredStream>();
+const restarts = new Map<string, number[]>();
+// Alerts currently firing, keyed by `${rule}:${udid}`
+const firing = new Map<string, StreamAlert>();
+let evaluationTimer: ReturnType<typeof setInterval> | null = null;
+
+/**
+ * Apply settings and start evaluating alert rules
+ */
+export function startStreamHealthMonitor(newSettings: StreamAlertSettings): void {
+  settings = newSettings;
+  if (!settings.enabled || evaluationTimer) {
+    return;
+  }
+  evaluationTimer = setInterval(() => evaluateStreamHealth(), EVALUATION_INTERVAL_MS);
+  evaluationTimer.unref();
+}
+
+export function trackStream(udid: string, targetFps: number): void {
+  streams.set(udid, { targetFps, startedAt: Date.now(), frames: [] });
+  setGauge('plasma_stream_target_fps', { udid }, targetFps);
+}
+
+export function untrackStream(udid: string): void {
+  const stream = streams.get(udid);
+  if (stream) {
+    recordUsage('stream', Date.now() - stream.startedAt);
+  }
+  streams.delete(udid);
+  removeSeries('plasma_stream_fps', { udid });
+  removeSeries('plasma_stream_target_fps', { udid });
+  // A stopped stream can't be degraded
+  resolve('fps_low', udid);
+  resolve('frame_gap', udid);
+}
+
+export function recordFrame(udid: string): void {
+  streams.get(udid)?.frames.push(Date.now());
+}
+
+export function recordSessionRestart(udid: string): void {
+  const history = restarts.get(udid) || [];
+  history.push(Date.now());
+  restarts.set(udid, history);
+}
+
+/**
+ * Alerts that are currently firing
+ */
+export function getFiringAlerts(): StreamAlert[] {
+  return Array.from(firing.values());
+}
+
+/**
+ * Check every rule, firing new alerts and resolving cleared ones. Runs periodically once started.
+ */
+export function evaluateStreamHealth(now: number = Date.now()): void {
+  for (const [udid, stream] of streams) {
+    stream.frames = stream.frames.filter((time) => now - time <= FPS_WINDOW_MS);
+    const fps = stream.frames.length / (FPS_WINDOW_MS / 1000);
+    setGauge('plasma_stream_fps', { udid }, fps);
+
+    // Give a new stream a full window before judging it
+    if (now - stream.startedAt < FPS_WINDOW_MS) {
+      continue;
+    }
+
+    const minFps = stream.targetFps * settings.minFpsRatio;
+    if (fps < minFps) {
+      fire('fps_low', udid, `Stream at ${fps.toFixed(1)} fps, below ${minFps.toFixed(1)} (target ${stream.targetFps})`, fps, minFps);
+    } else {
+      resolve('fps_low', udid);
+    }
+
+    const lastFrame = stream.frames[stream.frames.length - 1] ?? stream.startedAt;
+    const gap = now - lastFrame;
+    if (gap > settings.maxFrameGapMs) {
+      fire('frame_gap', udid, `No frames for ${(gap / 1000).toFixed(1)}s`, gap, settings.maxFrameGapMs);
+    } else {
+      resolve('frame_gap', udid);
+    }
+  }
+
+  const windowMs = settings.restartWindowMinutes * 60 * 1000;
+  for (const [udid, history] of restarts) {
+    const recent = history.filter((time) => now - time <= windowMs);
+    if (recent.length === 0) {
+      restarts.delete(udid);
+    } else {
+      restarts.set(udid, recent);
+    }
+
+    if (recent.length >= settings.maxRestarts) {
+      fire(
+        'session_restarts',
+        udid,
+        `Session restarted ${recent.length} times in ${settings.restartWindowMinutes} minutes`,
+        recent.length,
+        settings.maxRestarts
+      );
+    } else {
+      resolve('session_restarts', udid);
+    }
+  }
+}
+
+function fire(rule: StreamAlertRule, udid: string, message: string, value: number, threshold: number): void {
+  const key = `${rule}:${udid}`;
+  if (firing.has(key)) {
+    return;
+  }
+
+  const alert: StreamAlert = { rule, udid, status: 'firing', message, value, threshold, since: new Date().toISOString() };
+  firing.set(key, alert);
+  console.warn(`[stream-health] ${udid}: ${message}`);
+  recordNotification({
+    kind: 'stream_degraded',
+    level: 'warning',
+    title: `Degraded stream on ${udid}`,
+    message,
+    data: { ...alert },
+  }).catch((err) => console.error('[stream-health] Failed to record notification:', err));
+  postAlert(alert).catch((err) => console.error('[stream-health] Failed to post alert:', err));
+}
+
+function resolve(rule: StreamAlertRule, udid: string): void {
+  const key = `${rule}:${udid}`;
+  const alert = firing.get(key);
+  if (!alert) {
+    return;
+  }
+  firing.delete(key);
+  postAlert({ ...alert, status: 'resolved' }).catch((err) => console.error('[stream-health] Failed to post alert:', err));
+}
+
+async function postAlert(alert: StreamAlert): Promise<void> {
+  const webhookUrl = (await getSecret(ALERT_WEBHOOK_SECRET)) || settings.webhookUrl;
+  if (!webhookUrl) {
+    return;
+  }
+  const response = await fetch(webhookUrl, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify(alert),
+  });
+  if (!response.ok) {
+    throw new Error(`webhook returned ${response.status} ${response.statusText}`);
+  }
+}
@@ -0,0 +1,96 @@
+import { makeThumbnail } from './thumbnails';
+import { jpegSize } from './jpeg';
+
+/**
+ * Smaller frames for a streaming client that asked for them, or that can't keep up. A client can
+ * set a fixed `scale` or `maxWidth`; with `adaptive`, frames are halved while the client's send
+ * buffer fills up, down to a quarter, and grow back once it has kept up for a while. Scaled frames
+ * are decoded at a fraction of their size like thumbnails and sent as PNG.
+ */
+
+export interface StreamScaleOptions {
+  // Fraction of the frame's width and height, above 0 and at most 1
+  scale?: number;
+  // Widest frame to send, in pixels
+  maxWidth?: number;
+  adaptive?: boolean;
+}
+
+// Factors adaptive scaling steps through, on top of the requested scale
+const ADAPTIVE_STEPS = [1, 0.5, 0.25];
+// Share of the channel's capacity queued for the client above which frames get smaller
+const BACKLOG_THRESHOLD = 0.25;
+// A step down has this long to drain the queue before the next one
+const STEP_DOWN_INTERVAL_MS = 1000;
+// How long the client has to keep up before frames grow a step
+const STEP_UP_AFTER_MS = 5000;
+
+export class StreamScaler {
+  private step = 0;
+  private changedAt = 0;
+  private backloggedAt = 0;
+  private warned = false;
+
+  constructor(private readonly options: StreamScaleOptions) {}
+
+  /**
+   * Follow how much of the channel is queued for the client, from 0 up. Returns the new adaptive
+   * factor when it changed, or null.
+   */
+  update(backlog: number, now: number = Date.now()): number | null {
+    if (!this.options.adaptive) {
+      return null;
+    }
+    if (backlog > BACKLOG_THRESHOLD) {
+      this.backloggedAt = now;
+      if (this.step < ADAPTIVE_STEPS.length - 1 && now - this.changedAt >= STEP_DOWN_INTERVAL_MS) {
+        this.step++;
+        this.changedAt = now;
+        return ADAPTIVE_STEPS[this.step];
+      }
+    } else if (this.step > 0 && now - Math.max(this.backloggedAt, this.changedAt) >= STEP_UP_AFTER_MS) {
+      this.step--;
+      this.changedAt = now;
+      return ADAPTIVE_STEPS[this.step];
+    }
+    return null;
+  }
+
+  /**
+   * Scale for a frame `width` pixels wide
+   */
+  scaleFor(width: number | null): number {
+    let scale = this.options.scale ?? 1;
+    if (this.options.maxWidth && width) {
+      scale = Math.min(scale, this.options.maxWidth / width);
+    }
+    return scale * ADAPTIVE_STEPS[this.step];
+  }
+
+  /**
+   * The frame to send; full size frames, and those that can't be scaled, are sent as they are
+   */
+  apply(frame: Buffer, format: 'jpeg' | 'png'): { frame: Buffer; format: 'jpeg' | 'png' } {
+    const scale = this.scaleFor(frameWidth(frame, format));
+    if (scale >= 1) {
+      return { frame, format };
+    }
+    try {
+      return { frame: makeThumbnail(frame, format, scale), format: 'png' };
+    } catch (err) {
+      if (!this.warned) {
+        this.warned = true;
+        console.warn('[stream] Sending full size frames, the stream\'s frames could not be scaled:', err);
+      }
+      return { frame, format };
+    }
+  }
+}
+
+function frameWidth(frame: Buffer, format: 'jpeg' | 'png'): number | null {
+  if (format === 'jpeg') {
+    return jpegSize(frame)?.width ?? null;
+  }
+  // The IHDR chunk comes right after the signature
+  return frame.length >= 24 ? frame.readUInt32BE(16) : null;
+}
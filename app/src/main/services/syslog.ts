@@ -0,0 +1,154 @@
+import { spawn } from 'child_process';
+import * as path from 'path';
+import { registerProcess } from './process-manager';
+import { startBackendSystemLog } from './simulator';
+import { isNoise } from './log-noise';
+import type { LogNoiseProfileId, SyslogEntry } from '../../shared/ipc-types';
+
+/**
+ * The system log of a simulator, with the app's own `os_log` and `print` output, through
+ * `simctl spawn <udid> log stream --style json`. That prints one JSON array over the life of
+ * the stream, so entries are picked out of it as their objects complete.
+ */
+
+// Longest predicate passed to `log stream`
+export const MAX_PREDICATE_LENGTH = 1000;
+
+const LEVELS: Record<string, SyslogEntry['level']> = {
+  Default: 'default',
+  Info: 'info',
+  Debug: 'debug',
+  Error: 'error',
+  Fault: 'fault',
+};
+
+/**
+ * Splits the JSON array `log stream` prints into its objects as chunks of it arrive
+ */
+export class JsonObjectSplitter {
+  private pending = '';
+  private depth = 0;
+  private inString = false;
+  private escaped = false;
+
+  /**
+   * The objects completed by `chunk`, as JSON text
+   */
+  push(chunk: string): string[] {
+    const objects: string[] = [];
+    for (const char of chunk) {
+      if (this.depth > 0) {
+        this.pending += char;
+      }
+      if (this.inString) {
+        if (this.escaped) {
+          this.escaped = false;
+        } else if (char === '\\') {
+          this.escaped = true;
+        } else if (char === '"') {
+          this.inString = false;
+        }
+      } else if (char === '"' && this.depth > 0) {
+        this.inString = true;
+      } else if (char === '{') {
+        if (this.depth++ === 0) {
+          this.pending = char;
+        }
+      } else if (char === '}' && this.depth > 0 && --this.depth === 0) {
+        objects.push(this.pending);
+        this.pending = '';
+      }
+    }
+    return objects;
+  }
+}
+
+/**
+ * `2024-05-01 10:20:30.123456-0700` as an ISO 8601 timestamp
+ */
+function parseLogTimestamp(value: unknown): string {
+  const match = typeof value === 'string' ? /^(\d{4}-\d{2}-\d{2}) (\d{2}:\d{2}:\d{2})(\.\d{1,3})?\d*([+-]\d{2})(\d{2})$/.exec(value) : null;
+  const date = match ? new Date(`${match[1]}T${match[2]}${match[3] || ''}${match[4]}:${match[5]}`) : null;
+  return date && !isNaN(date.getTime()) ? date.toISOString() : new Date().toISOString();
+}
+
+/**
+ * An entry of `log stream --style json`, or null for activities, signposts and anything that
+ * isn't a log message
+ */
+export function parseSyslogEntry(udid: string, json: string): SyslogEntry | null {
+  let raw: Record<string, unknown>;
+  try {
+    raw = JSON.parse(json);
+  } catch {
+    return null;
+  }
+  if (raw.eventType !== 'logEvent' || typeof raw.eventMessage !== 'string') {
+    return null;
+  }
+  return {
+    udid,
+    timestamp: parseLogTimestamp(raw.timestamp),
+    process: typeof raw.processImagePath === 'string' ? path.basename(raw.processImagePath) : '',
+    pid: typeof raw.processID === 'number' ? raw.processID : null,
+    subsystem: typeof raw.subsystem === 'string' && raw.subsystem ? raw.subsystem : null,
+    category: typeof raw.category === 'string' && raw.category ? raw.category : null,
+    level: LEVELS[String(raw.messageType)] ?? 'default',
+    message: raw.eventMessage,
+  };
+}
+
+/**
+ * Run `log stream` in the simulator, writing its output to `onOutput`
+ */
+function startSimctlSystemLog(udid: string, predicate: string | null, onOutput: (chunk: string) => void, onExit: () => void): () => void {
+  const args = ['simctl', 'spawn', udid, 'log', 'stream', '--style', 'json', '--level', 'debug'];
+  if (predicate) {
+    args.push('--predicate', predicate);
+  }
+  const proc = spawn('xcrun', args);
+  registerProcess(proc, 'syslog');
+  proc.stdout.setEncoding('utf-8');
+  proc.stdout.on('data', onOutput);
+  proc.on('close', onExit);
+  proc.on('error', onExit);
+  return () => proc.kill();
+}
+
+/**
+ * Follow a simulator's system log, optionally narrowed by an NSPredicate like
+ * `subsystem == "dev.plasma.app"`, without the entries of the noise `profiles`. Returns a
+ * function that stops following; `onEnd` is called when the log ends on its own, e.g. because
+ * the simulator shut down.
+ */
+export function streamSyslog(
+  udid: string,
+  options: { predicate: string | null; profiles: LogNoiseProfileId[] },
+  onEntry: (entry: SyslogEntry) => void,
+  onEnd: () => void
+): () => void {
+  const splitter = new JsonObjectSplitter();
+  let stopped = false;
+  const onOutput = (chunk: string) => {
+    for (const json of splitter.push(chunk)) {
+      const entry = parseSyslogEntry(udid, json);
+      if (entry && !isNoise(entry, options.profiles)) {
+        onEntry(entry);
+      }
+    }
+  };
+  const onExit = () => {
+    if (!stopped) {
+      stopped = true;
+      onEnd();
+    }
+  };
+
+  const stop =
+    startBackendSystemLog(udid, options.predicate, onOutput, onExit) ??
+    startSimctlSystemLog(udid, options.predicate, onOutput, onExit);
+  return () => {
+    stopped = true;
+    stop();
+  };
+}
@@ -0,0 +1,104 @@
+import { decodePng, encodePng, DecodedPng } from './png';
+import { decodeJpeg } from './jpeg';
+
+/**
+ * Low frame rate, scaled down copies of a simulator's stream, for dashboards showing many
+ * simulators at once. Frames are sampled from the stream at the thumbnail rate and shrunk:
+ * JPEG frames are decoded at 1/8, 1/4 or 1/2 size straight from their DCT coefficients, then
+ * averaged down to the exact scale and sent as PNG.
+ */
+
+export interface ThumbnailOptions {
+  // Frames per second, at most MAX_THUMBNAIL_FPS
+  fps: number;
+  // Fraction of the frame's width and height, above 0 and at most 1
+  scale: number;
+}
+
+export const DEFAULT_THUMBNAIL_OPTIONS: ThumbnailOptions = { fps: 2, scale: 0.25 };
+export const MAX_THUMBNAIL_FPS = 10;
+
+/**
+ * Average `image` down to `width` x `height` RGB pixels
+ */
+function shrink(image: DecodedPng, width: number, height: number): DecodedPng {
+  if (width === image.width && height === image.height && image.channels === 3) {
+    return image;
+  }
+  const pixels = Buffer.alloc(width * height * 3);
+  for (let y = 0; y < height; y++) {
+    const top = Math.floor((y * image.height) / height);
+    const bottom = Math.max(top + 1, Math.floor(((y + 1) * image.height) / height));
+    for (let x = 0; x < width; x++) {
+      const left = Math.floor((x * image.width) / width);
+      const right = Math.max(left + 1, Math.floor(((x + 1) * image.width) / width));
+      const sums = [0, 0, 0];
+      for (let sy = top; sy < bottom; sy++) {
+        for (let sx = left; sx < right; sx++) {
+          const source = (sy * image.width + sx) * image.channels;
+          sums[0] += image.pixels[source];
+          sums[1] += image.pixels[source + 1];
+          sums[2] += image.pixels[source + 2];
+        }
+      }
+      const count = (bottom - top) * (right - left);
+      const out = (y * width + x) * 3;
+      pixels[out] = Math.round(sums[0] / count);
+      pixels[out + 1] = Math.round(sums[1] / count);
+      pixels[out + 2] = Math.round(sums[2] / count);
+    }
+  }
+  return { width, height, channels: 3, pixels };
+}
+
+/**
+ * Scale a stream frame by `scale`, as a PNG
+ */
+export function makeThumbnail(frame: Buffer, format: 'jpeg' | 'png', scale: number): Buffer {
+  let image: DecodedPng;
+  // How much smaller the decoded image already is than the frame
+  let decodedScale = 1;
+  if (format === 'jpeg') {
+    // The smallest decode that's still at least as big as the thumbnail
+    const size = ([1, 2, 4, 8] as const).find((candidate) => candidate / 8 >= scale) ?? 8;
+    image = decodeJpeg(frame, size);
+    decodedScale = size / 8;
+  } else {
+    image = decodePng(frame);
+  }
+  const factor = scale / decodedScale;
+  const width = Math.max(1, Math.round(image.width * factor));
+  const height = Math.max(1, Math.round(image.height * factor));
+  return encodePng(shrink(image, width, height));
+}
+
+/**
+ * Turn a stream's frames into thumbnails: feed the returned function every frame, and
+ * `onThumbnail` is called at most `fps` times a second. Frames that can't be decoded (e.g.
+ * progressive JPEGs) are passed on at full size.
+ */
+export function createThumbnailer(
+  options: ThumbnailOptions,
+  onThumbnail: (thumbnail: { frame: Buffer; format: 'jpeg' | 'png' }) => void
+): (frame: Buffer, format: 'jpeg' | 'png') => void {
+  const intervalMs = 1000 / options.fps;
+  let lastAt = -Infinity;
+  let warned = false;
+
+  return (frame, format) => {
+    const now = Date.now();
+    if (now - lastAt < intervalMs) {
+      return;
+    }
+    lastAt = now;
+    try {
+      onThumbnail({ frame: makeThumbnail(frame, format, options.scale), format: 'png' });
+    } catch (err) {
+      if (!warned) {
+        warned = true;
+        console.warn('[thumbnails] Sending full size frames, the stream\'s frames could not be scaled:', err);
+      }
+      onThumbnail({ frame, format });
+    }
+  };
+}
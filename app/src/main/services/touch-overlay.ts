@@ -0,0 +1,225 @@
+import { decodePng, encodePng } from './png';
+import { drawOnJpeg, jpegSize, rgbToYCbCr, JpegArea } from './jpeg';
+
+/**
+ * Touch indicators drawn into a simulator's stream frames on the server, like iOS's "Show
+ * Touches", so stream recordings and everyone watching the stream see what the controller
+ * does. Touches come from the touch, tap and swipe endpoints, in coordinates normalized to the
+ * screen. A circle stays while its finger is down, then fades out. Frames without a visible
+ * circle are passed on as they are, and in JPEG frames only the blocks under a circle are
+ * re-encoded.
+ */
+
+export interface TouchPoint {
+  x: number;
+  y: number;
+}
+
+interface Paint {
+  rgb: [number, number, number];
+  opacity: number;
+}
+
+interface Circle extends TouchPoint {
+  // 1 while the finger is down, fading to 0
+  opacity: number;
+}
+
+// How long a circle takes to fade out once its finger lifts
+export const TOUCH_FADE_MS = 500;
+// A finger down without news for this long is treated as lifted, in case its `ended` was lost
+const TOUCH_STALE_MS = 10000;
+// Circle radius, as a fraction of the frame's shorter side
+const RADIUS = 0.055;
+// Width of the circle's outline, as a fraction of its radius
+const OUTLINE_WIDTH = 0.12;
+const FILL: Paint = { rgb: [255, 255, 255], opacity: 0.45 };
+const OUTLINE: Paint = { rgb: [40, 40, 40], opacity: 0.6 };
+
+// The touches of one server's simulators and whether they are shown
+export interface TouchOverlay {
+  enabledByDefault: boolean;
+  overrides: Map<string, boolean>;
+  touches: Map<string, { down: TouchPoint[]; downAt: number; lifted: Array<TouchPoint & { liftedAt: number }> }>;
+  // Simulators whose frames could not be drawn on, warned about once
+  warned: Set<string>;
+}
+
+export function createTouchOverlay(): TouchOverlay {
+  return { enabledByDefault: false, overrides: new Map(), touches: new Map(), warned: new Set() };
+}
+
+/**
+ * Whether frames get touch indicators when their stream doesn't say, e.g. from the config
+ */
+export function setTouchOverlayDefault(overlay: TouchOverlay, enabled: boolean): void {
+  overlay.enabledByDefault = enabled;
+}
+
+/**
+ * Turn touch indicators on or off for a simulator's frames
+ */
+export function setTouchOverlay(overlay: TouchOverlay, udid: string, enabled: boolean): void {
+  overlay.overrides.set(udid, enabled);
+  if (!enabled) {
+    overlay.touches.delete(udid);
+  }
+}
+
+export function isTouchOverlayEnabled(overlay: TouchOverlay, udid: string): boolean {
+  return overlay.overrides.get(udid) ?? overlay.enabledByDefault;
+}
+
+/**
+ * Note a touch sent to a simulator. `began` and `moved` put fingers down at `points`;
+ * `ended` lifts them there.
+ */
+export function recordTouch(
+  overlay: TouchOverlay,
+  udid: string,
+  type: 'began' | 'moved' | 'ended',
+  points: TouchPoint[],
+  now: number = Date.now()
+): void {
+  if (!isTouchOverlayEnabled(overlay, udid)) {
+    return;
+  }
+  const state = overlay.touches.get(udid) || { down: [], downAt: now, lifted: [] };
+  if (type === 'ended') {
+    const lifted = points.length > 0 ? points : state.down;
+    state.lifted.push(...lifted.map((point) => ({ x: point.x, y: point.y, liftedAt: now })));
+    state.down = [];
+  } else {
+    state.down = points.map((point) => ({ x: point.x, y: point.y }));
+    state.downAt = now;
+  }
+  overlay.touches.set(udid, state);
+}
+
+/**
+ * Circles to draw on a simulator's frame at `now`
+ */
+function visibleCircles(overlay: TouchOverlay, udid: string, now: number): Circle[] {
+  const state = overlay.touches.get(udid);
+  if (!state) {
+    return [];
+  }
+  state.lifted = state.lifted.filter((touch) => now - touch.liftedAt < TOUCH_FADE_MS);
+  if (state.down.length > 0 && now - state.downAt >= TOUCH_STALE_MS) {
+    state.down = [];
+  }
+  if (state.down.length === 0 && state.lifted.length === 0) {
+    overlay.touches.delete(udid);
+    return [];
+  }
+  return [
+    ...state.down.map((touch) => ({ ...touch, opacity: 1 })),
+    ...state.lifted.map((touch) => ({ x: touch.x, y: touch.y, opacity: 1 - (now - touch.liftedAt) / TOUCH_FADE_MS })),
+  ];
+}
+
+interface CircleLayout {
+  // Bounding boxes of the circles, in pixels
+  areas: JpegArea[];
+  // How much of the fill and outline colors to mix into the pixel centered at `x`, `y`
+  coverage: (x: number, y: number) => [number, number];
+}
+
+function layoutCircles(circles: Circle[], width: number, height: number): CircleLayout {
+  const radius = Math.max(4, Math.min(width, height) * RADIUS);
+  const outlineWidth = Math.max(1, radius * OUTLINE_WIDTH);
+  const centers = circles.map((circle) => ({ x: circle.x * width, y: circle.y * height, opacity: circle.opacity }));
+  return {
+    areas: centers.map((center) => ({
+      left: Math.max(0, Math.floor(center.x - radius - 1)),
+      top: Math.max(0, Math.floor(center.y - radius - 1)),
+      right: Math.min(width, Math.ceil(center.x + radius + 1)),
+      bottom: Math.min(height, Math.ceil(center.y + radius + 1)),
+    })),
+    coverage: (x, y) => {
+      let fill = 0;
+      let outline = 0;
+      for (const center of centers) {
+        const distance = Math.hypot(x - center.x, y - center.y);
+        // Edges are antialiased over a pixel
+        const inside = Math.min(1, Math.max(0, radius - distance + 0.5));
+        const inOutline = inside * Math.min(1, Math.max(0, distance - (radius - outlineWidth) + 0.5));
+        fill = Math.max(fill, (inside - inOutline) * center.opacity);
+        outline = Math.max(outline, inOutline * center.opacity);
+      }
+      return [fill * FILL.opacity, outline * OUTLINE.opacity];
+    },
+  };
+}
+
+function mix(value: number, target: number, amount: number): number {
+  return value + (target - value) * amount;
+}
+
+function drawOnJpegFrame(frame: Buffer, circles: Circle[]): Buffer {
+  const size = jpegSize(frame);
+  if (!size) {
+    throw new Error('JPEG has no frame header');
+  }
+  const layout = layoutCircles(circles, size.width, size.height);
+  const fill = rgbToYCbCr(...FILL.rgb);
+  const outline = rgbToYCbCr(...OUTLINE.rgb);
+  return drawOnJpeg(frame, layout.areas, (component, x, y, value) => {
+    const [fillAmount, outlineAmount] = layout.coverage(x, y);
+    return mix(mix(value, fill[component], fillAmount), outline[component], outlineAmount);
+  });
+}
+
+function drawOnPngFrame(frame: Buffer, circles: Circle[]): Buffer {
+  const image = decodePng(frame);
+  const layout = layoutCircles(circles, image.width, image.height);
+  // One box around every circle, so pixels where circles overlap are drawn once
+  const left = Math.min(...layout.areas.map((area) => area.left));
+  const top = Math.min(...layout.areas.map((area) => area.top));
+  const right = Math.max(...layout.areas.map((area) => area.right));
+  const bottom = Math.max(...layout.areas.map((area) => area.bottom));
+  for (let y = top; y < bottom; y++) {
+    for (let x = left; x < right; x++) {
+      const [fillAmount, outlineAmount] = layout.coverage(x + 0.5, y + 0.5);
+      if (fillAmount === 0 && outlineAmount === 0) {
+        continue;
+      }
+      const offset = (y * image.width + x) * image.channels;
+      for (let channel = 0; channel < 3; channel++) {
+        const value = mix(image.pixels[offset + channel], FILL.rgb[channel], fillAmount);
+        image.pixels[offset + channel] = Math.round(mix(value, OUTLINE.rgb[channel], outlineAmount));
+      }
+    }
+  }
+  return encodePng(image);
+}
+
+/**
+ * A frame of a simulator's stream with its visible touches drawn on, in the same format.
+ * Returns the frame itself when the overlay is off, nothing is visible, or the frame can't be
+ * drawn on (e.g. a progressive JPEG).
+ */
+export function drawTouches(
+  overlay: TouchOverlay,
+  udid: string,
+  frame: Buffer,
+  format: 'jpeg' | 'png',
+  now: number = Date.now()
+): Buffer {
+  if (!isTouchOverlayEnabled(overlay, udid)) {
+    return frame;
+  }
+  const circles = visibleCircles(overlay, udid, now);
+  if (circles.length === 0) {
+    return frame;
+  }
+  try {
+    return format === 'jpeg' ? drawOnJpegFrame(frame, circles) : drawOnPngFrame(frame, circles);
+  } catch (err) {
+    if (!overlay.warned.has(udid)) {
+      overlay.warned.add(udid);
+      console.warn(`[touch-overlay] Sending frames of ${udid} without touches, they could not be drawn on:`, err);
+    }
+    return frame;
+  }
+}
@@ -0,0 +1,192 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { randomUUID } from 'crypto';
+import { getDatabase } from './database';
+import { isAlive } from './process-manager';
+import { createJobDir, JobDir } from './workspace';
+import { deleteSimulator, eraseSimulator, invalidateSimulatorList, listSimulators, runSimctl, UNDO_SNAPSHOT_SUFFIX } from './simulator';
+import type { UndoAction, UndoInfo } from '../../shared/ipc-types';
+
+/**
+ * Undo of erasing a simulator or uninstalling an app, for when it was the wrong simulator in a
+ * shared UI. With a snapshot asked for, the state is kept before it's destroyed: erasing clones
+ * the shut down simulator with `simctl clone`, uninstalling copies the app and its data container
+ * to the workspace. Snapshots are kept for the undo window and removed after it.
+ *
+ * Snapshots can only be undone by the instance that took them. Clones are also recorded in the
+ * erase_snapshots table, so those of an instance that exited before removing them are deleted by
+ * the next startup's recovery; copied apps are removed with the other job directories.
+ */
+
+let windowMs = 10 * 60 * 1000;
+
+interface Snapshot {
+  info: UndoInfo;
+  restore(): Promise<string>;
+  // Set while the snapshot is being restored, so it's only restored once
+  restoring: Promise<string> | null;
+  timer: NodeJS.Timeout;
+}
+
+const snapshots = new Map<string, Snapshot>();
+
+export class UndoNotFoundError extends Error {
+  constructor(id: string) {
+    super(`Nothing to undo for ${id}, it was undone already or its window has passed`);
+  }
+}
+
+/**
+ * How long snapshots are kept to undo with. Applies to snapshots taken from now on.
+ */
+export function setUndoWindow(ms: number): void {
+  windowMs = ms;
+}
+
+function keep(action: UndoAction, udid: string, bundleId: string | null, restore: () => Promise<string>, discard: () => void): UndoInfo {
+  const id = randomUUID();
+  const now = Date.now();
+  const info: UndoInfo = {
+    id,
+    action,
+    udid,
+    bundleId,
+    createdAt: new Date(now).toISOString(),
+    expiresAt: new Date(now + windowMs).toISOString(),
+  };
+  const timer = setTimeout(() => {
+    snapshots.delete(id);
+    discard();
+  }, windowMs);
+  timer.unref();
+  snapshots.set(id, { info, restore, restoring: null, timer });
+  return info;
+}
+
+function discardClone(cloneUdid: string): void {
+  deleteSimulator(cloneUdid)
+    .then(() => getDatabase().prepare('DELETE FROM erase_snapshots WHERE clone_udid = ?').run(cloneUdid))
+    .catch((err) => console.error(`[undo] Failed to delete snapshot ${cloneUdid}:`, err));
+}
+
+/**
+ * Erase a simulator, cloning it first so the erase can be undone
+ */
+export async function eraseSimulatorWithUndo(udid: string): Promise<UndoInfo> {
+  const name = (await listSimulators()).find((simulator) => simulator.udid === udid)?.name || udid;
+  const expiresAt = new Date(Date.now() + windowMs).toISOString();
+  let cloneUdid = '';
+  try {
+    // simctl can only clone a shut down simulator, which erasing shuts it down to
+    await eraseSimulator(udid, async () => {
+      cloneUdid = (await runSimctl(['clone', udid, `${name}${UNDO_SNAPSHOT_SUFFIX}`])).trim();
+      getDatabase()
+        .prepare('INSERT INTO erase_snapshots (clone_udid, udid, owner_pid, expires_at) VALUES (?, ?, ?, ?)')
+        .run(cloneUdid, udid, process.pid, expiresAt);
+    });
+  } catch (err) {
+    if (cloneUdid) {
+      discardClone(cloneUdid);
+    }
+    throw err;
+  }
+  invalidateSimulatorList();
+
+  return keep(
+    'erase',
+    udid,
+    null,
+    async () => {
+      // A simulator's content can't be put back under its UDID, so the clone takes its place
+      await deleteSimulator(udid);
+      await runSimctl(['rename', cloneUdid, name]);
+      getDatabase().prepare('DELETE FROM erase_snapshots WHERE clone_udid = ?').run(cloneUdid);
+      invalidateSimulatorList();
+      return cloneUdid;
+    },
+    () => discardClone(cloneUdid)
+  );
+}
+
+/**
+ * Uninstall an app, copying it and its data first so the uninstall can be undone
+ */
+export async function uninstallAppWithUndo(udid: string, bundleId: string): Promise<UndoInfo> {
+  const appPath = (await runSimctl(['get_app_container', udid, bundleId, 'app'])).trim();
+  const dataPath = (await runSimctl(['get_app_container', udid, bundleId, 'data'])).trim();
+  const jobDir = createJobDir('snapshot', `${udid}-${bundleId}`);
+  const appCopy = path.join(jobDir.path, path.basename(appPath));
+  const dataCopy = path.join(jobDir.path, 'data');
+  try {
+    fs.cpSync(appPath, appCopy, { recursive: true, verbatimSymlinks: true });
+    fs.cpSync(dataPath, dataCopy, { recursive: true, verbatimSymlinks: true });
+    await runSimctl(['uninstall', udid, bundleId]);
+  } catch (err) {
+    jobDir.cleanup();
+    throw err;
+  }
+
+  return keep('uninstall', udid, bundleId, () => restoreApp(udid, bundleId, appCopy, dataCopy, jobDir), () => jobDir.cleanup());
+}
+
+async function restoreApp(udid: string, bundleId: string, appCopy: string, dataCopy: string, jobDir: JobDir): Promise<string> {
+  await runSimctl(['install', udid, appCopy]);
+  // Installing creates an empty data container, which gets the app's data back
+  const dataPath = (await runSimctl(['get_app_container', udid, bundleId, 'data'])).trim();
+  for (const entry of fs.readdirSync(dataPath)) {
+    fs.rmSync(path.join(dataPath, entry), { recursive: true, force: true });
+  }
+  fs.cpSync(dataCopy, dataPath, { recursive: true, verbatimSymlinks: true });
+  jobDir.cleanup();
+  return udid;
+}
+
+/**
+ * Snapshots that can still be undone, oldest first
+ */
+export function listUndos(): UndoInfo[] {
+  return Array.from(snapshots.values(), (snapshot) => snapshot.info);
+}
+
+/**
+ * Put back what a snapshot kept, returning the UDID of the restored simulator. After an erase,
+ * that's the clone's UDID.
+ */
+export async function undo(id: string): Promise<string> {
+  const snapshot = snapshots.get(id);
+  if (!snapshot) {
+    throw new UndoNotFoundError(id);
+  }
+  if (!snapshot.restoring) {
+    snapshot.restoring = snapshot.restore().then(
+      (udid) => {
+        snapshots.delete(id);
+        clearTimeout(snapshot.timer);
+        return udid;
+      },
+      (err) => {
+        // Kept for another try until the window passes
+        snapshot.restoring = null;
+        throw err;
+      }
+    );
+  }
+  return snapshot.restoring;
+}
+
+/**
+ * Delete the clones of erase snapshots that can't be undone anymore: those of an instance that
+ * exited, which kept their undo in memory, and expired ones. Returns the UDIDs of the clones.
+ */
+export function discardLeftoverSnapshots(): string[] {
+  const rows = getDatabase().prepare('SELECT clone_udid, owner_pid, expires_at FROM erase_snapshots WHERE owner_pid != ?').all(process.pid) as Array<{
+    clone_udid: string;
+    owner_pid: number;
+    expires_at: string;
+  }>;
+  const leftover = rows.filter((row) => !isAlive(row.owner_pid) || Date.parse(row.expires_at) <= Date.now());
+  for (const row of leftover) {
+    discardClone(row.clone_udid);
+  }
+  return leftover.map((row) => row.clone_udid);
+}
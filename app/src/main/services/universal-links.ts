@@ -0,0 +1,213 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import fetch from 'node-fetch';
+import { runCommand } from './simulator';
+import type { UniversalLinkDomainReport, UniversalLinksReport } from '../../shared/ipc-types';
+
+/**
+ * Checks of an app's universal links: the `applinks:` domains of its associated domains
+ * entitlement, and for each, whether the domain serves an apple-app-site-association (AASA) file
+ * that lists the app. Devices get the file through Apple's CDN, which refreshes it about daily,
+ * so a fixed file can take a while to reach them; developer mode domains are fetched directly.
+ */
+
+const ASSOCIATED_DOMAINS_KEY = 'com.apple.developer.associated-domains';
+const APPLICATION_IDENTIFIER_KEY = 'application-identifier';
+// Apple ignores larger files
+const MAX_AASA_BYTES = 128 * 1024;
+const FETCH_TIMEOUT_MS = 10_000;
+
+export interface AppEntitlements {
+  // `TEAMID.bundle.id`, when the build has one
+  appId: string | null;
+  // e.g. `applinks:example.com` or `applinks:staging.example.com?mode=developer`
+  associatedDomains: string[];
+}
+
+/**
+ * Entitlements relevant to universal links, from an entitlements plist's XML
+ */
+export function parseEntitlements(xml: string): AppEntitlements {
+  const valueOf = (key: string) => new RegExp(`<key>${key.replace(/\./g, '\\.')}</key>\\s*(<array>[\\s\\S]*?</array>|<array/>|<string>[^<]*</string>)`).exec(xml)?.[1] ?? '';
+  const strings = (value: string) => Array.from(value.matchAll(/<string>([^<]*)<\/string>/g), (match) => match[1].trim());
+  return {
+    appId: strings(valueOf(APPLICATION_IDENTIFIER_KEY))[0] || null,
+    associatedDomains: strings(valueOf(ASSOCIATED_DOMAINS_KEY)),
+  };
+}
+
+/**
+ * Entitlements of a built app. Signed apps have them in their signature; simulator builds
+ * without signing embed them in the executable instead.
+ */
+export async function readEntitlements(appPath: string): Promise<AppEntitlements> {
+  try {
+    const signed = await runCommand('codesign', ['-d', '--entitlements', '-', '--xml', appPath]);
+    if (signed.includes('<plist')) {
+      return parseEntitlements(signed);
+    }
+  } catch {
+    // Not signed
+  }
+  const executable = (await runCommand('/usr/libexec/PlistBuddy', ['-c', 'Print :CFBundleExecutable', path.join(appPath, 'Info.plist')])).trim();
+  const binary = fs.readFileSync(path.join(appPath, executable)).toString('latin1');
+  const embedded = /<\?xml[\s\S]*?<\/plist>/g;
+  for (const match of binary.matchAll(embedded)) {
+    if (match[0].includes(ASSOCIATED_DOMAINS_KEY) || match[0].includes(APPLICATION_IDENTIFIER_KEY)) {
+      return parseEntitlements(match[0]);
+    }
+  }
+  return { appId: null, associatedDomains: [] };
+}
+
+/**
+ * Split `applinks:host?mode=developer` into the host and whether it's in developer mode.
+ * Null for other services (`webcredentials:`, `activitycontinuation:`).
+ */
+export function parseApplinksDomain(entry: string): { domain: string; developerMode: boolean } | null {
+  const match = /^applinks:([^?]+)(\?mode=(developer|managed|developer\+managed))?$/.exec(entry.trim());
+  if (!match) {
+    return null;
+  }
+  return { domain: match[1], developerMode: (match[3] || '').includes('developer') };
+}
+
+export function associationFileUrl(domain: string): string {
+  // Wildcard domains (`*.example.com`) are served by the parent domain
+  return `https://${domain.replace(/^\*\./, '')}/.well-known/apple-app-site-association`;
+}
+
+/**
+ * Problems with an AASA file's contents for `appId`, and the paths it routes to the app
+ */
+export function validateAssociationFile(
+  body: string,
+  appId: string | null
+): { errors: string[]; warnings: string[]; paths: string[] } {
+  const errors: string[] = [];
+  const warnings: string[] = [];
+  const paths: string[] = [];
+
+  if (Buffer.byteLength(body) > MAX_AASA_BYTES) {
+    errors.push(`The file is ${Buffer.byteLength(body)} bytes, iOS ignores files over ${MAX_AASA_BYTES}`);
+  }
+  let json: any;
+  try {
+    json = JSON.parse(body);
+  } catch (err) {
+    errors.push(`The file is not valid JSON: ${err instanceof Error ? err.message : err}`);
+    return { errors, warnings, paths };
+  }
+  const details = json?.applinks?.details;
+  if (!Array.isArray(details)) {
+    errors.push('The file has no applinks.details array');
+    return { errors, warnings, paths };
+  }
+  if (Array.isArray(json.applinks.apps) && json.applinks.apps.length > 0) {
+    warnings.push('applinks.apps should be an empty array');
+  }
+  if (!appId) {
+    warnings.push('The app ID is unknown, so the file was not checked for the app; pass appId');
+    return { errors, warnings, paths };
+  }
+
+  const appIdsOf = (detail: any): string[] => (Array.isArray(detail?.appIDs) ? detail.appIDs : detail?.appID ? [detail.appID] : []);
+  const matching = details.filter((detail: any) => appIdsOf(detail).includes(appId));
+  if (matching.length === 0) {
+    const listed = details.flatMap(appIdsOf);
+    errors.push(`No applinks.details entry lists ${appId}${listed.length > 0 ? `, only ${listed.join(', ')}` : ''}`);
+    return { errors, warnings, paths };
+  }
+  for (const detail of matching) {
+    if (Array.isArray(detail.components)) {
+      for (const component of detail.components) {
+        const rule = [component['/'] ?? '*', component['?'] ? `?${JSON.stringify(component['?'])}` : '', component['#'] ? `#${component['#']}` : ''].join('');
+        paths.push(component.exclude ? `NOT ${rule}` : rule);
+      }
+    } else if (Array.isArray(detail.paths)) {
+      paths.push(...detail.paths);
+    }
+  }
+  if (paths.length === 0) {
+    errors.push(`The entry for ${appId} has no paths or components, so no links open the app`);
+  }
+  return { errors, warnings, paths };
+}
+
+async function checkDomain(entry: string, appId: string | null): Promise<UniversalLinkDomainReport> {
+  const parsed = parseApplinksDomain(entry)!;
+  const url = associationFileUrl(parsed.domain);
+  const report: UniversalLinkDomainReport = {
+    entry,
+    domain: parsed.domain,
+    developerMode: parsed.developerMode,
+    url,
+    status: null,
+    ok: false,
+    errors: [],
+    warnings: [],
+    paths: [],
+  };
+
+  let response;
+  try {
+    // Apple doesn't follow redirects when it fetches the file
+    response = await fetch(url, { redirect: 'manual', signal: AbortSignal.timeout(FETCH_TIMEOUT_MS) });
+  } catch (err) {
+    report.errors.push(`Could not fetch the file: ${err instanceof Error ? err.message : err}`);
+    return report;
+  }
+  report.status = response.status;
+  if (response.status >= 300 && response.status < 400) {
+    report.errors.push(`The file redirects to ${response.headers.get('location')}, it has to be served directly`);
+    return report;
+  }
+  if (response.status !== 200) {
+    report.errors.push(`The server responded with ${response.status} instead of 200`);
+    return report;
+  }
+  const contentType = response.headers.get('content-type') || '';
+  if (!contentType.includes('application/json')) {
+    report.warnings.push(`The file is served as ${contentType || 'no content type'}, application/json is expected`);
+  }
+
+  const result = validateAssociationFile(await response.text(), appId);
+  report.errors.push(...result.errors);
+  report.warnings.push(...result.warnings);
+  report.paths = result.paths;
+  report.ok = report.errors.length === 0;
+  return report;
+}
+
+/**
+ * Check every `applinks:` domain of an app, from its build or as given
+ */
+export async function checkUniversalLinks(options: {
+  appPath?: string;
+  appId?: string;
+  associatedDomains?: string[];
+}): Promise<UniversalLinksReport> {
+  const entitlements = options.appPath ? await readEntitlements(options.appPath) : { appId: null, associatedDomains: [] };
+  const appId = options.appId || entitlements.appId;
+  const associatedDomains = options.associatedDomains || entitlements.associatedDomains;
+  const applinks = associatedDomains.filter((entry) => parseApplinksDomain(entry));
+
+  const issues: string[] = [];
+  if (applinks.length === 0) {
+    issues.push(
+      options.appPath
+        ? 'The app has no applinks: domains in its associated domains entitlement'
+        : 'No applinks: domains to check'
+    );
+  }
+  if (!appId) {
+    issues.push('The build has no application identifier (builds without signing don\'t); pass appId as TEAMID.bundle.id');
+  }
+
+  return {
+    appId,
+    associatedDomains,
+    issues,
+    domains: await Promise.all(applinks.map((entry) => checkDomain(entry, appId))),
+  };
+}
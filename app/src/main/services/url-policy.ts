@@ -0,0 +1,56 @@
+/**
+ * Which URLs API clients may open on simulators, so users of a shared instance can't send its
+ * simulators anywhere. Patterns match whole URLs, with `*` for any run of characters, e.g.
+ * `https://*.example.com/*` or `myapp:*`. A URL matching a deny pattern is refused; with allow
+ * patterns, so is one matching none of them. Without patterns, every URL is allowed.
+ */
+
+export interface UrlPolicy {
+  allow: string[];
+  deny: string[];
+}
+
+let allowPatterns: RegExp[] = [];
+let denyPatterns: RegExp[] = [];
+
+const escape = (text: string) => text.replace(/[.+?^${}()|[\]\\]/g, '\\$&');
+
+function toRegExp(pattern: string): RegExp {
+  // In the scheme and host, `*` stays within the host, so `https://*.example.com/*` doesn't match
+  // `https://evil.test/?https://www.example.com/`
+  const hostStart = pattern.indexOf('://');
+  let pathStart = hostStart === -1 ? 0 : pattern.indexOf('/', hostStart + 3);
+  if (pathStart === -1) {
+    pathStart = pattern.length;
+  }
+  const host = pattern.slice(0, pathStart).split('*').map(escape).join('[^/?#@]*');
+  const rest = pattern.slice(pathStart).split('*').map(escape).join('.*');
+  return new RegExp(`^${host}${rest}$`, 'i');
+}
+
+export function setUrlPolicy(next: UrlPolicy): void {
+  allowPatterns = next.allow.map(toRegExp);
+  denyPatterns = next.deny.map(toRegExp);
+}
+
+/**
+ * Why a URL can't be opened, or null if it can
+ */
+export function checkUrl(url: string): string | null {
+  // Compared the way browsers would write it, e.g. with a lowercase host
+  let normalized = url;
+  try {
+    normalized = new URL(url).href;
+  } catch {
+    // Not parseable, compared as given
+  }
+  const candidates = Array.from(new Set([url, normalized]));
+  if (denyPatterns.some((pattern) => candidates.some((candidate) => pattern.test(candidate)))) {
+    return 'The URL is blocked on this instance';
+  }
+  // Only the normalized form can be allowed, so spelling a URL differently can't sneak it through
+  if (allowPatterns.length > 0 && !allowPatterns.some((pattern) => pattern.test(normalized))) {
+    return 'The URL is not on this instance\'s allowlist';
+  }
+  return null;
+}
@@ -0,0 +1,133 @@
+import { spawn } from 'child_process';
+import { registerProcess } from './process-manager';
+import { findHelperBinary, describeHelperLookup } from './helper-binaries';
+import type { BackendFrame } from './simulator';
+import type { Watermarker } from './watermark';
+
+/**
+ * H.264 video of a simulator's stream as fragmented MP4, for clients on slow links: MJPEG sends
+ * every frame as a whole JPEG, a video codec mostly sends what changed. Frames are piped into
+ * ffmpeg, which encodes them with VideoToolbox on macOS, and the fragments it writes are sent as
+ * they come, so the response plays in a `<video>` element or through Media Source Extensions.
+ */
+
+export interface VideoOptions {
+  // Frames per second, at most MAX_VIDEO_FPS
+  fps: number;
+  // Bits per second
+  bitrate: number;
+}
+
+export const DEFAULT_VIDEO_OPTIONS: VideoOptions = { fps: 30, bitrate: 2_000_000 };
+export const MAX_VIDEO_FPS = 60;
+export const MIN_VIDEO_BITRATE = 100_000;
+export const MAX_VIDEO_BITRATE = 50_000_000;
+
+export class VideoUnavailableError extends Error {
+  constructor() {
+    super(`ffmpeg is required for video streams and was not found (looked in ${describeHelperLookup('ffmpeg')})`);
+  }
+}
+
+/**
+ * ffmpeg's arguments to encode the images on its stdin as fragmented MP4 on its stdout
+ */
+export function ffmpegArgs(options: VideoOptions, platform: NodeJS.Platform = process.platform): string[] {
+  // The hardware encoder elsewhere than macOS depends on the GPU, so x264 is used there
+  const encoder =
+    platform === 'darwin'
+      ? ['-c:v', 'h264_videotoolbox', '-realtime', '1']
+      : ['-c:v', 'libx264', '-preset', 'ultrafast', '-tune', 'zerolatency'];
+  return [
+    '-hide_banner',
+    '-loglevel', 'error',
+    // Frames arrive when the screen changes, so they're timed by when they arrive
+    '-use_wallclock_as_timestamps', '1',
+    '-f', 'image2pipe',
+    '-i', 'pipe:0',
+    '-an',
+    // H.264 needs even dimensions, and browsers 4:2:0 chroma
+    '-vf', 'scale=trunc(iw/2)*2:trunc(ih/2)*2',
+    '-pix_fmt', 'yuv420p',
+    ...encoder,
+    '-b:v', String(options.bitrate),
+    '-maxrate', String(options.bitrate),
+    '-bufsize', String(options.bitrate),
+    // A keyframe a second, so players can start soon after they connect
+    '-g', String(options.fps),
+    '-f', 'mp4',
+    '-movflags', 'frag_keyframe+empty_moov+default_base_moof',
+    '-frag_duration', '100000',
+    'pipe:1',
+  ];
+}
+
+export interface VideoEncoder {
+  // Feed a frame; frames above the frame rate, or while ffmpeg is busy, are skipped
+  write(frame: BackendFrame): void;
+  stop(): void;
+}
+
+/**
+ * Start encoding frames, writing the MP4 to `output`. `onEnd` is called if ffmpeg exits on its own.
+ * `watermark` is drawn into the frames that are encoded; frames it can't be drawn into are skipped.
+ */
+export function startVideoEncoder(
+  options: VideoOptions,
+  output: NodeJS.WritableStream,
+  onEnd: () => void,
+  watermark: Watermarker | null = null
+): VideoEncoder {
+  const ffmpegPath = findHelperBinary('ffmpeg')?.path;
+  if (!ffmpegPath) {
+    throw new VideoUnavailableError();
+  }
+
+  const proc = spawn(ffmpegPath, ffmpegArgs(options));
+  registerProcess(proc, 'video');
+  let stopped = false;
+  let stderr = '';
+  proc.stderr.on('data', (data) => {
+    stderr += data.toString();
+  });
+  // Backpressure from a slow client reaches the frames through ffmpeg's stdin
+  proc.stdout.pipe(output, { end: false });
+  proc.stdin.on('error', () => {});
+  const onExit = (code: number | null) => {
+    if (!stopped) {
+      stopped = true;
+      if (code !== 0 && stderr) {
+        console.error(`[video] ffmpeg exited with code ${code}: ${stderr.trim()}`);
+      }
+      onEnd();
+    }
+  };
+  proc.on('close', onExit);
+  proc.on('error', () => onExit(null));
+
+  const interval = 1000 / options.fps;
+  let lastFrameAt = -Infinity;
+  return {
+    write({ frame, format }) {
+      const now = Date.now();
+      // Some slack, so a stream at the same rate isn't halved by jitter
+      if (stopped || proc.stdin.writableNeedDrain || now - lastFrameAt < interval * 0.8) {
+        return;
+      }
+      const encoded = watermark ? watermark(frame, format) : frame;
+      if (!encoded) {
+        return;
+      }
+      lastFrameAt = now;
+      proc.stdin.write(encoded);
+    },
+    stop() {
+      if (!stopped) {
+        stopped = true;
+        proc.stdout.unpipe(output);
+        proc.stdin.end();
+        proc.kill();
+      }
+    },
+  };
+}
@@ -0,0 +1,214 @@
+import type * as http from 'http';
+import * as os from 'os';
+import { decodePng, encodePng } from './png';
+import { drawOnJpeg, jpegSize, rgbToYCbCr, JpegArea } from './jpeg';
+
+/**
+ * Watermarks for streamed and recorded frames, so frames shared outside the team can be traced
+ * back to who viewed them and when. The text is drawn into the frame's pixels on the server, in
+ * a small built-in bitmap font, a few times down the frame so cropping doesn't remove it. In
+ * JPEG frames only the blocks under the text are re-encoded.
+ */
+
+// Longest watermark drawn into a frame
+const MAX_WATERMARK_LENGTH = 256;
+
+interface Paint {
+  rgb: [number, number, number];
+  opacity: number;
+}
+
+const FILL: Paint = { rgb: [255, 255, 255], opacity: 0.5 };
+// Offset by one font pixel, so the text reads on light screens too
+const SHADOW: Paint = { rgb: [0, 0, 0], opacity: 0.35 };
+// Lines of text drawn down the frame
+const ROWS = 3;
+
+// 5x7 glyphs, one hex byte per row with the leftmost pixel in bit 4. Lowercase letters are
+// drawn as uppercase ones and characters without a glyph as `?`.
+const GLYPH_WIDTH = 5;
+const GLYPH_HEIGHT = 7;
+const ADVANCE = GLYPH_WIDTH + 1;
+const GLYPHS: Record<string, string> = {
+  ' ': '00000000000000', 'A': '0e11111f111111', 'B': '1e11111e11111e', 'C': '0e11101010110e',
+  'D': '1c12111111121c', 'E': '1f10101e10101f', 'F': '1f10101e101010', 'G': '0e11101711110f',
+  'H': '1111111f111111', 'I': '0e04040404040e', 'J': '0702020202120c', 'K': '11121418141211',
+  'L': '1010101010101f', 'M': '111b1515111111', 'N': '11111915131111', 'O': '0e11111111110e',
+  'P': '1e11111e101010', 'Q': '0e11111115120d', 'R': '1e11111e141211', 'S': '0f10100e01011e',
+  'T': '1f040404040404', 'U': '1111111111110e', 'V': '11111111110a04', 'W': '1111111515150a',
+  'X': '11110a040a1111', 'Y': '11110a04040404', 'Z': '1f01020408101f', '0': '0e11131519110e',
+  '1': '040c040404040e', '2': '0e11010204081f', '3': '1f02040201110e', '4': '02060a121f0202',
+  '5': '1f101e0101110e', '6': '0608101e11110e', '7': '1f010204080808', '8': '0e11110e11110e',
+  '9': '0e11110f01020c', '.': '00000000000c0c', ',': '000000000c0408', ':': '000c0c000c0c00',
+  ';': '000c0c000c0408', '-': '0000001f000000', '_': '0000000000001f', '+': '0004041f040400',
+  '=': '00001f001f0000', '/': '00010204081000', '\\': '00100804020100', '@': '0e11010d15150e',
+  '#': '0a0a1f0a1f0a0a', '(': '02040808080402', ')': '08040202020408', '[': '0e08080808080e',
+  ']': '0e02020202020e', '!': '04040404040004', '?': '0e110102040004', '\'': '04040800000000',
+  '"': '0a0a0a00000000', '*': '0004150e150400', '&': '0c12140815120d', '%': '18190204081303',
+  '$': '040f140e051e04', '<': '02040810080402', '>': '08040201020408', '|': '04040404040404',
+};
+const FONT = new Map(Object.entries(GLYPHS).map(([char, rows]) => [char, Buffer.from(rows, 'hex')]));
+
+export interface WatermarkContext {
+  udid: string;
+  // Who is viewing, from their connection (see `viewerOf`); the host's user name otherwise
+  user?: string | null;
+  now?: Date;
+}
+
+/**
+ * Fill in a watermark template: `{user}`, `{udid}`, `{timestamp}` (UTC, to the second) and `{date}`
+ */
+export function formatWatermark(template: string, context: WatermarkContext): string {
+  const now = context.now || new Date();
+  const timestamp = now.toISOString().replace(/\.\d{3}Z$/, 'Z');
+  const values: Record<string, string> = {
+    user: context.user || os.userInfo().username,
+    udid: context.udid,
+    timestamp,
+    date: timestamp.slice(0, 10),
+  };
+  return template
+    .replace(/\{(user|udid|timestamp|date)\}/g, (_, key: string) => values[key])
+    .slice(0, MAX_WATERMARK_LENGTH);
+}
+
+/**
+ * Who is on the other end of a request or WebSocket connection, for `{user}`. There are no
+ * accounts, so it's the user name of the request's Basic credentials (checked by a proxy in front
+ * of Plasma) or, without them, the client's address. Clients can't name someone else in a message.
+ */
+export function viewerOf(req: http.IncomingMessage): string {
+  const [scheme, credentials] = (req.headers.authorization || '').split(' ');
+  if (scheme?.toLowerCase() === 'basic' && credentials) {
+    const user = Buffer.from(credentials, 'base64').toString('utf-8').split(':')[0];
+    if (user) {
+      return user;
+    }
+  }
+  return req.socket.remoteAddress || 'unknown';
+}
+
+interface TextLayout {
+  // Bounding boxes of the lines of text and their shadows, in pixels
+  areas: JpegArea[];
+  // How much of the fill and shadow colors to mix into the pixel centered at `x`, `y`
+  coverage: (x: number, y: number) => [number, number];
+}
+
+function layoutText(text: string, width: number, height: number): TextLayout {
+  const glyphs = Array.from(text, (char) => FONT.get(char.toUpperCase()) || FONT.get('?')!);
+  const columns = glyphs.length * ADVANCE - 1;
+  // Font pixels are scaled up with the frame, as long as the text fits across it
+  const scale = Math.max(1, Math.min(Math.floor(Math.min(width, height) / 120), Math.floor((width * 0.9) / columns)));
+  const textWidth = columns * scale;
+  const textHeight = GLYPH_HEIGHT * scale;
+  const left = Math.floor((width - textWidth) / 2);
+  const tops = Array.from({ length: ROWS }, (_, row) => Math.floor((height * (row + 0.5)) / ROWS - textHeight / 2));
+
+  // Whether the font pixel under `x`, `y` is set in the line of text starting at `top`
+  const isSet = (x: number, y: number, top: number): boolean => {
+    const column = Math.floor((x - left) / scale);
+    const row = Math.floor((y - top) / scale);
+    if (column < 0 || column >= columns || row < 0 || row >= GLYPH_HEIGHT || column % ADVANCE >= GLYPH_WIDTH) {
+      return false;
+    }
+    return ((glyphs[Math.floor(column / ADVANCE)][row] >> (GLYPH_WIDTH - 1 - (column % ADVANCE))) & 1) === 1;
+  };
+
+  return {
+    areas: tops
+      .map((top) => ({
+        left: Math.max(0, left),
+        top: Math.max(0, top),
+        right: Math.min(width, left + textWidth + scale),
+        bottom: Math.min(height, top + textHeight + scale),
+      }))
+      .filter((area) => area.left < area.right && area.top < area.bottom),
+    coverage: (x, y) => {
+      if (tops.some((top) => isSet(x, y, top))) {
+        return [FILL.opacity, 0];
+      }
+      return [0, tops.some((top) => isSet(x - scale, y - scale, top)) ? SHADOW.opacity : 0];
+    },
+  };
+}
+
+function mix(value: number, target: number, amount: number): number {
+  return value + (target - value) * amount;
+}
+
+function drawOnJpegFrame(frame: Buffer, text: string): Buffer {
+  const size = jpegSize(frame);
+  if (!size) {
+    throw new Error('JPEG has no frame header');
+  }
+  const layout = layoutText(text, size.width, size.height);
+  const fill = rgbToYCbCr(...FILL.rgb);
+  const shadow = rgbToYCbCr(...SHADOW.rgb);
+  return drawOnJpeg(frame, layout.areas, (component, x, y, value) => {
+    const [fillAmount, shadowAmount] = layout.coverage(x, y);
+    return mix(mix(value, fill[component], fillAmount), shadow[component], shadowAmount);
+  });
+}
+
+function drawOnPngFrame(frame: Buffer, text: string): Buffer {
+  const image = decodePng(frame);
+  const layout = layoutText(text, image.width, image.height);
+  // Lines of text may overlap on short frames; pixels in both are drawn once
+  const drawn = new Set<number>();
+  for (const area of layout.areas) {
+    for (let y = area.top; y < area.bottom; y++) {
+      for (let x = area.left; x < area.right; x++) {
+        const pixel = y * image.width + x;
+        const [fillAmount, shadowAmount] = layout.coverage(x + 0.5, y + 0.5);
+        if ((fillAmount === 0 && shadowAmount === 0) || drawn.has(pixel)) {
+          continue;
+        }
+        drawn.add(pixel);
+        const offset = pixel * image.channels;
+        for (let channel = 0; channel < 3; channel++) {
+          const value = mix(image.pixels[offset + channel], FILL.rgb[channel], fillAmount);
+          image.pixels[offset + channel] = Math.round(mix(value, SHADOW.rgb[channel], shadowAmount));
+        }
+      }
+    }
+  }
+  return encodePng(image);
+}
+
+/**
+ * Copy of a frame with `text` drawn into it, in the same format. Throws when the frame can't be
+ * drawn on (e.g. a progressive JPEG), so it isn't shown without its watermark.
+ */
+export function drawWatermark(frame: Buffer, format: 'jpeg' | 'png', text: string): Buffer {
+  if (text.length === 0) {
+    return frame;
+  }
+  return format === 'jpeg' ? drawOnJpegFrame(frame, text) : drawOnPngFrame(frame, text);
+}
+
+/**
+ * Draws a watermark into frames of a simulator
+ */
+export type Watermarker = (frame: Buffer, format: 'jpeg' | 'png') => Buffer | null;
+
+/**
+ * Draw `template`, filled in for `context` at the time of each frame, into frames. Frames it
+ * can't be drawn into come back as null, to be dropped rather than sent without it; the first
+ * one is logged.
+ */
+export function createWatermarker(template: string, context: { udid: string; user: string }): Watermarker {
+  let warned = false;
+  return (frame, format) => {
+    try {
+      return drawWatermark(frame, format, formatWatermark(template, context));
+    } catch (err) {
+      if (!warned) {
+        warned = true;
+        console.warn(`[watermark] Dropping frames of ${context.udid} the watermark could not be drawn into:`, err);
+      }
+      return null;
+    }
+  };
+}
@@ -0,0 +1,129 @@
+import * as fs from 'fs';
+import * as path from 'path';
+import { randomUUID } from 'crypto';
+import { getDataDir } from './database';
+import type { JobDirInfo, JobKind } from '../../shared/ipc-types';
+
+const OWNER_FILE = '.owner.json';
+
+let workspaceRoot: string | null = null;
+
+export interface JobDir {
+  path: string;
+  // Remove the directory and everything in it. Safe to call more than once.
+  cleanup(): void;
+}
+
+interface JobDirOwner {
+  kind: JobKind;
+  id: string;
+  pid: number;
+  createdAt: string;
+}
+
+/**
+ * Set the workspace root, e.g. from the config. Defaults to `workspace` in the data directory.
+ */
+export function setWorkspaceRoot(root: string | null): void {
+  workspaceRoot = root;
+}
+
+function tempRoot(): string {
+  const root = path.join(workspaceRoot || path.join(getDataDir(), 'workspace'), 'tmp');
+  fs.mkdirSync(root, { recursive: true });
+  return root;
+}
+
+/**
+ * Create a temporary directory for a build, recording or automation job.
+ * Call `cleanup()` when the job ends; directories left behind by a crash are
+ * removed by `recoverJobDirs()` on the next startup.
+ */
+export function createJobDir(kind: JobKind, id: string = randomUUID()): JobDir {
+  const dir = path.join(tempRoot(), `${kind}-${path.basename(id)}`);
+  fs.mkdirSync(dir, { recursive: true });
+
+  const owner: JobDirOwner = { kind, id, pid: process.pid, createdAt: new Date().toISOString() };
+  fs.writeFileSync(path.join(dir, OWNER_FILE), JSON.stringify(owner));
+
+  return {
+    path: dir,
+    cleanup: () => fs.rmSync(dir, { recursive: true, force: true }),
+  };
+}
+
+function readOwner(dir: string): JobDirOwner | null {
+  try {
+    return JSON.parse(fs.readFileSync(path.join(dir, OWNER_FILE), 'utf-8'));
+  } catch {
+    return null;
+  }
+}
+
+function isProcessAlive(pid: number): boolean {
+  try {
+    process.kill(pid, 0);
+    return true;
+  } catch (err) {
+    // EPERM means the process exists but belongs to someone else
+    return (err as NodeJS.ErrnoException).code === 'EPERM';
+  }
+}
+
+/**
+ * Total size in bytes of the files in a directory, recursively
+ */
+export function directorySize(dir: string): number {
+  let size = 0;
+  for (const entry of fs.readdirSync(dir, { withFileTypes: true })) {
+    const entryPath = path.join(dir, entry.name);
+    size += entry.isDirectory() ? directorySize(entryPath) : fs.statSync(entryPath).size;
+  }
+  return size;
+}
+
+/**
+ * Job directories currently in the workspace, including ones left behind by crashed processes
+ */
+export function listJobDirs(): JobDirInfo[] {
+  const root = tempRoot();
+  return fs
+    .readdirSync(root, { withFileTypes: true })
+    .filter((entry) => entry.isDirectory())
+    .map((entry) => {
+      const dir = path.join(root, entry.name);
+      const owner = readOwner(dir);
+      return {
+        path: dir,
+        kind: owner?.kind || null,
+        id: owner?.id || null,
+        pid: owner?.pid || null,
+        createdAt: owner?.createdAt || null,
+        active: !!owner && (owner.pid === process.pid || isProcessAlive(owner.pid)),
+        sizeBytes: directorySize(dir),
+      };
+    });
+}
+
+/**
+ * Remove job directories whose owning process is gone. Called once at startup.
+ * Returns the directories that were removed.
+ */
+export function recoverJobDirs(): JobDirInfo[] {
+  const removed: JobDirInfo[] = [];
+  for (const dir of listJobDirs()) {
+    if (dir.active) {
+      continue;
+    }
+    try {
+      fs.rmSync(dir.path, { recursive: true, force: true });
+      removed.push(dir);
+    } catch (err) {
+      console.error(`[workspace] Failed to remove leftover directory ${dir.path}:`, err);
+    }
+  }
+  if (removed.length > 0) {
+    console.log(`[workspace] Removed ${removed.length} leftover job directories`);
+  }
+  return removed;
+}
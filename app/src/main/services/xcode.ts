@@ -1,34 +1,168 @@
 import { spawn, ChildProcess } from 'child_process';
 import { EventEmitter } from 'events';
+import { randomUUID } from 'crypto';
 import * as fs from 'fs';
 import * as path from 'path';
 import { detectProject } from './projects';
 import { registerProcess } from './process-manager';
+import { getDataDir } from './database';
+import { getDataStore } from './data-store';
+import { createJobDir, directorySize } from './workspace';
+import { recordBuild, parseBuildSettings, getTypicalBuildDuration } from './builds';
+import { collectBuildEnvironment, hashArtifact } from './provenance';
+import { recordUsage } from './analytics';
+import { createBuildProgress } from './build-progress';
+import { createDiagnosticCollector } from './build-diagnostics';
+import { getArtifactStore } from './storage';
+import { EVENT_SCHEMA_VERSION } from '../../shared/ipc-types';
+import type {
+  ActiveBuild,
+  BuildDiagnostic,
+  BuildOutputLevel,
+  LastBuildResult,
+  XcodeProjectType,
+  XcodeProject,
+  DestinationPresetId,
+  DestinationPreset,
+  BuildProduct,
+  BuildEvent,
+} from '../../shared/ipc-types';
+
+export const DEFAULT_DESTINATION: DestinationPresetId = 'ios-simulator';
+
+// Configuration every build uses
+const BUILD_CONFIGURATION = 'Debug';
+
+// The -sdk/-destination mapping of the named build destinations lives only here
+const DESTINATION_PRESETS: Record<DestinationPresetId, DestinationPreset> = {
+  'ios-simulator': {
+    id: 'ios-simulator',
+    name: 'iOS Simulator',
+    sdk: 'iphonesimulator',
+    destination: 'generic/platform=iOS Simulator',
+  },
+  'visionos-simulator': {
+    id: 'visionos-simulator',
+    name: 'visionOS Simulator',
+    sdk: 'xrsimulator',
+    destination: 'generic/platform=visionOS Simulator',
+  },
+  'tvos-simulator': {
+    id: 'tvos-simulator',
+    name: 'tvOS Simulator',
+    sdk: 'appletvsimulator',
+    destination: 'generic/platform=tvOS Simulator',
+  },
+  'watchos-simulator': {
+    id: 'watchos-simulator',
+    name: 'watchOS Simulator',
+    sdk: 'watchsimulator',
+    destination: 'generic/platform=watchOS Simulator',
+  },
+  macos: {
+    id: 'macos',
+    name: 'macOS',
+    sdk: 'macosx',
+    destination: 'generic/platform=macOS',
+  },
+  'mac-catalyst': {
+    id: 'mac-catalyst',
+    name: 'Mac Catalyst',
+    destination: 'generic/platform=macOS,variant=Mac Catalyst',
+  },
+};
+
+export function listDestinationPresets(): DestinationPreset[] {
+  return Object.values(DESTINATION_PRESETS);
+}
 
-export type XcodeProjectType = 'project' | 'workspace';
+export function isDestinationPreset(id: unknown): id is DestinationPresetId {
+  return typeof id === 'string' && id in DESTINATION_PRESETS;
+}
 
-export interface XcodeProject {
-  path: string;
-  projectType: XcodeProjectType;
-  schemes: string[];
-  targets: string[];
-  configurations: string[];
+/**
+ * xcodebuild arguments selecting `destination`. `-sdk macosx` would force SDKROOT on a UIKit target,
+ * so Catalyst builds only pass the destination, whose variant selects the SDK.
+ */
+export function destinationArgs(destination: DestinationPresetId): string[] {
+  const preset = DESTINATION_PRESETS[destination];
+  return [...(preset.sdk ? ['-sdk', preset.sdk] : []), '-destination', preset.destination];
+}
+
+/**
+ * Directories holding shared schemes: the container's own, and for a workspace,
+ * those of the projects it references
+ */
+function sharedSchemeDirs(containerPath: string): string[] {
+  const dirs = [path.join(containerPath, 'xcshareddata', 'xcschemes')];
+  if (containerPath.endsWith('.xcworkspace')) {
+    try {
+      const contents = fs.readFileSync(path.join(containerPath, 'contents.xcworkspacedata'), 'utf-8');
+      for (const match of contents.matchAll(/location = "(?:group|container):([^"]+\.xcodeproj)"/g)) {
+        dirs.push(path.join(path.dirname(containerPath), match[1], 'xcshareddata', 'xcschemes'));
+      }
+    } catch {
+      // A workspace without contents has no projects to look into
+    }
+  }
+  return dirs;
 }
 
-export interface BuildProduct {
-  name: string;
-  path: string;
+/**
+ * Product built by a shared scheme's launch action (or first build entry), e.g. `Plasma.app`
+ */
+function sharedSchemeProducts(containerPath: string): Map<string, string | null> {
+  const products = new Map<string, string | null>();
+  for (const dir of sharedSchemeDirs(containerPath)) {
+    let files: string[];
+    try {
+      files = fs.readdirSync(dir).filter((file) => file.endsWith('.xcscheme'));
+    } catch {
+      continue;
+    }
+    for (const file of files) {
+      const contents = fs.readFileSync(path.join(dir, file), 'utf-8');
+      const launch = /<LaunchAction[\s\S]*?BuildableName = "([^"]+)"[\s\S]*?<\/LaunchAction>/.exec(contents);
+      const build = /BuildableName = "([^"]+)"/.exec(contents);
+      products.set(path.basename(file, '.xcscheme'), (launch || build)?.[1] || null);
+    }
+  }
+  return products;
 }
 
-export interface BuildEvent {
-  type: 'started' | 'output' | 'completed' | 'error';
-  scheme?: string;
-  projectPath?: string;
-  line?: string;
-  success?: boolean;
-  buildDir?: string;
-  products?: BuildProduct[];
-  message?: string;
+/**
+ * Pick the scheme most likely to build and run the app, so a project can be run with
+ * only its path. Prefers shared schemes that build an `.app`, then schemes named after
+ * the project, and avoids test schemes. Falls back to the first scheme.
+ */
+export function suggestScheme(containerPath: string, schemes: string[]): string | null {
+  if (schemes.length === 0) {
+    return null;
+  }
+  const products = sharedSchemeProducts(containerPath);
+  const name = path.basename(containerPath).replace(/\.(xcworkspace|xcodeproj)$/, '').toLowerCase();
+
+  const score = (scheme: string) => {
+    let total = 0;
+    if (products.has(scheme)) {
+      total += 1;
+    }
+    if (products.get(scheme)?.endsWith('.app')) {
+      total += 4;
+    }
+    if (scheme.toLowerCase() === name) {
+      total += 2;
+    } else if (scheme.toLowerCase().startsWith(name)) {
+      total += 1;
+    }
+    if (/tests?$/i.test(scheme)) {
+      total -= 4;
+    }
+    return total;
+  };
+
+  // Stable: on a tie, the scheme xcodebuild lists first wins
+  return schemes.reduce((best, scheme) => (score(scheme) > score(best) ? scheme : best));
 }
 
 /**
@@ -81,12 +215,14 @@ export async function discoverProject(projectPath: string): Promise<XcodeProject
           return;
         }
 
+        const schemes: string[] = info.schemes || [];
         resolve({
           path: project.path,
           projectType,
-          schemes: info.schemes || [],
+          schemes,
           targets: info.targets || [],
           configurations: info.configurations || [],
+          suggestedScheme: suggestScheme(project.path, schemes),
         });
       } catch (err) {
         reject(new Error(`Failed to parse xcodebuild output: ${err}`));
@@ -136,8 +272,9 @@ async function findBuildProducts(buildDir: string): Promise<BuildProduct[]> {
  */
 async function getBuildSettings(
   projectPath: string,
-  scheme: string
-): Promise<{ buildDir: string; isWorkspace: boolean }> {
+  scheme: string,
+  destination: DestinationPresetId = DEFAULT_DESTINATION
+): Promise<{ buildDir: string; isWorkspace: boolean; settings: Record<string, string> }> {
   const project = detectProject(projectPath);
 
   if (!project || project.type !== 'xcode') {
@@ -152,11 +289,8 @@ async function getBuildSettings(
     '-scheme',
     scheme,
     '-configuration',
-    'Debug',
-    '-sdk',
-    'iphonesimulator',
-    '-destination',
-    'generic/platform=iOS Simulator',
+    BUILD_CONFIGURATION,
+    ...destinationArgs(destination),
     'CODE_SIGN_IDENTITY=',
     'CODE_SIGNING_REQUIRED=NO',
     'CODE_SIGNING_ALLOWED=NO',
@@ -184,40 +318,258 @@ async function getBuildSettings(
         return;
       }
 
-      resolve({ buildDir, isWorkspace });
+      resolve({ buildDir, isWorkspace, settings: parseBuildSettings(stdout) });
     });
   });
 }
 
+const BUILD_OUTPUT_LEVELS: BuildOutputLevel[] = ['all', 'warnings', 'errors', 'phases'];
+
+export function isBuildOutputLevel(value: unknown): value is BuildOutputLevel {
+  return BUILD_OUTPUT_LEVELS.includes(value as BuildOutputLevel);
+}
+
+const ERROR_LINE = /(^|: )(fatal )?error: |^\*\* BUILD FAILED \*\*/;
+const WARNING_LINE = /(^|: )warning: /;
+// Unindented step headers such as `CompileSwiftSources normal arm64 ...`, `Ld ...`, `=== BUILD TARGET ...`
+// and `** BUILD SUCCEEDED **`. The indented lines under them are the commands they run.
+const PHASE_LINE = /^(===|\*\*|[A-Z][A-Za-z]+ )/;
+
+/**
+ * Whether an output line is sent to subscribers at `level`
+ */
+export function includeBuildOutput(line: string, level: BuildOutputLevel): boolean {
+  switch (level) {
+    case 'all':
+      return true;
+    case 'warnings':
+      return ERROR_LINE.test(line) || WARNING_LINE.test(line);
+    case 'errors':
+      return ERROR_LINE.test(line);
+    case 'phases':
+      return ERROR_LINE.test(line) || (PHASE_LINE.test(line) && !WARNING_LINE.test(line));
+  }
+}
+
+/**
+ * A build event before the build stream adds the build ID, event name and schema version
+ */
+export type BuildEventInput = Omit<BuildEvent, 'event' | 'schemaVersion' | 'buildId'>;
+
+/**
+ * Build event emitter whose upstream xcodebuild output can be paused when
+ * consumers can't keep up
+ */
+export interface BuildStream extends EventEmitter {
+  pause(): void;
+  resume(): void;
+}
+
+const activeBuilds = new Map<string, ActiveBuild>();
+// Stops a running build, by build ID
+const buildCancellers = new Map<string, () => void>();
+let lastBuildResult: LastBuildResult | null = null;
+
+/**
+ * Builds that haven't finished yet, oldest first
+ */
+export function getActiveBuilds(): ActiveBuild[] {
+  return Array.from(activeBuilds.values());
+}
+
+/**
+ * Stop a running build. xcodebuild gets SIGINT, so it finishes writing derived data before it
+ * exits; the build then ends with an error event with `cancelled: true`, and is recorded in the
+ * build history as cancelled if xcodebuild had started. Returns false for unknown or finished builds.
+ */
+export function cancelBuild(buildId: string): boolean {
+  const cancel = buildCancellers.get(buildId);
+  if (!cancel) {
+    return false;
+  }
+  cancel();
+  return true;
+}
+
+/**
+ * Outcome of the most recently finished build, if any
+ */
+export function getLastBuildResult(): LastBuildResult | null {
+  return lastBuildResult;
+}
+
+/**
+ * Key of a finished build's log in the artifact store
+ */
+export function buildLogKey(buildId: string): string {
+  return `build-logs/${path.basename(buildId)}.log`;
+}
+
+/**
+ * Path of the log a build writes while it runs. Once it ends, the log is moved to the artifact store.
+ */
+export function getBuildLogPath(buildId: string): string {
+  const logsDir = path.join(getDataDir(), 'build-logs');
+  if (!fs.existsSync(logsDir)) {
+    fs.mkdirSync(logsDir, { recursive: true });
+  }
+  return path.join(logsDir, `${path.basename(buildId)}.log`);
+}
+
+/**
+ * Move a finished build's log to the artifact store
+ */
+export function storeBuildLog(buildId: string): void {
+  getArtifactStore()
+    .importFile(buildLogKey(buildId), getBuildLogPath(buildId))
+    .catch((err) => console.error(`[xcode] Failed to store the log of build ${buildId}:`, err));
+}
+
 /**
  * Stream build output line by line for live updates
  */
 export function buildSchemeStream(
   projectPath: string,
-  scheme: string
-): EventEmitter {
-  const emitter = new EventEmitter();
+  scheme: string,
+  destination: DestinationPresetId = DEFAULT_DESTINATION
+): BuildStream {
+  const emitter = new EventEmitter() as BuildStream;
+  const buildId = randomUUID();
+  const startedAt = Date.now();
+  const log = fs.createWriteStream(getBuildLogPath(buildId));
+
+  let warnings = 0;
+  let errors = 0;
+
+  // Looked up while the build settings load; progress is estimated from the build plan until then
+  const typicalDurationMs = getTypicalBuildDuration(projectPath, scheme, destination).catch((err) => {
+    console.error('[xcode] Failed to look up past build durations:', err);
+    return null;
+  });
+  let progress = createBuildProgress(startedAt, null);
+  const diagnostics = createDiagnosticCollector();
+
+  // Every event carries the build ID, its typed name and schema version,
+  // and output is mirrored to the build log
+  const emitEvent = (event: BuildEventInput) => {
+    if (event.type === 'output' && event.line) {
+      log.write(`${event.line}\n`);
+      if (event.line.includes(': warning: ')) {
+        warnings++;
+      } else if (event.line.includes(': error: ')) {
+        errors++;
+      }
+      progress.observe(event.line);
+      diagnostics.observe(event.line);
+      event.progress = progress.estimate();
+      const active = activeBuilds.get(buildId);
+      if (active) {
+        active.progress = event.progress;
+      }
+    } else if (event.type === 'started') {
+      event.progress = 0;
+    } else if (event.type === 'completed') {
+      event.progress = 1;
+    } else if (event.type === 'error' && event.message) {
+      log.write(`error: ${event.message}\n`);
+    }
+    const stamped: BuildEvent = { ...event, buildId, event: `build.${event.type}`, schemaVersion: EVENT_SCHEMA_VERSION };
+    emitter.emit('event', stamped);
+  };
+  emitter.once('end', () => log.end(() => storeBuildLog(buildId)));
+
+  // xcodebuild's temporary files go to the build's job directory, removed when the build ends
+  const jobDir = createJobDir('build', buildId);
+  emitter.once('end', () => jobDir.cleanup());
+
+  activeBuilds.set(buildId, {
+    buildId,
+    scheme,
+    projectPath,
+    destination,
+    startedAt: new Date(startedAt).toISOString(),
+    progress: null,
+  });
+  emitter.on('event', (event: BuildEvent) => {
+    if (event.type === 'completed' || event.type === 'error') {
+      lastBuildResult = {
+        buildId,
+        scheme,
+        projectPath,
+        success: event.type === 'completed' && !!event.success,
+        message: event.message,
+        durationMs: Date.now() - startedAt,
+        finishedAt: new Date().toISOString(),
+        diagnostics: diagnostics.diagnostics(),
+      };
+    }
+  });
+  emitter.once('end', () => {
+    activeBuilds.delete(buildId);
+    buildCancellers.delete(buildId);
+  });
+
+  let buildProcess: ChildProcess | null = null;
+  let paused = false;
+  let cancelled = false;
+  buildCancellers.set(buildId, () => {
+    cancelled = true;
+    buildProcess?.kill('SIGINT');
+  });
+  emitter.pause = () => {
+    paused = true;
+    buildProcess?.stdout?.pause();
+    buildProcess?.stderr?.pause();
+  };
+  emitter.resume = () => {
+    paused = false;
+    buildProcess?.stdout?.resume();
+    buildProcess?.stderr?.resume();
+  };
 
   (async () => {
     try {
       const project = detectProject(projectPath);
 
       if (!project || project.type !== 'xcode') {
-        emitter.emit('event', {
+        emitEvent({
           type: 'error',
           message: 'Not an Xcode project',
-        } as BuildEvent);
+        });
+        emitter.emit('end');
+        return;
+      }
+
+      if (!isDestinationPreset(destination)) {
+        emitEvent({
+          type: 'error',
+          message: `Unknown build destination: ${destination}`,
+        });
         emitter.emit('end');
         return;
       }
 
-      const { buildDir, isWorkspace } = await getBuildSettings(projectPath, scheme);
+      const { buildDir, isWorkspace, settings } = await getBuildSettings(projectPath, scheme, destination);
+      progress = createBuildProgress(startedAt, await typicalDurationMs);
 
-      emitter.emit('event', {
+      // Cancelled while the build settings were loading
+      if (cancelled) {
+        emitEvent({
+          type: 'error',
+          message: 'Build cancelled',
+          cancelled: true,
+        });
+        emitter.emit('end');
+        return;
+      }
+
+      emitEvent({
         type: 'started',
         scheme,
         projectPath,
-      } as BuildEvent);
+      });
+      // Read while xcodebuild runs, before the commit can change
+      const environment = collectBuildEnvironment(project.path);
 
       const args = [
         isWorkspace ? '-workspace' : '-project',
@@ -225,24 +577,22 @@ export function buildSchemeStream(
         '-scheme',
         scheme,
         '-configuration',
-        'Debug',
-        '-sdk',
-        'iphonesimulator',
-        '-destination',
-        'generic/platform=iOS Simulator',
+        BUILD_CONFIGURATION,
+        ...destinationArgs(destination),
         'CODE_SIGN_IDENTITY=',
         'CODE_SIGNING_REQUIRED=NO',
         'CODE_SIGNING_ALLOWED=NO',
       ];
 
-      const proc = spawn('xcodebuild', args);
-      registerProcess(proc);
+      const proc = spawn('xcodebuild', args, { env: { ...process.env, TMPDIR: jobDir.path } });
+      registerProcess(proc, 'xcodebuild');
+      buildProcess = proc;
 
       proc.stdout.on('data', (data) => {
         const lines = data.toString().split('\n');
         for (const line of lines) {
           if (line.trim()) {
-            emitter.emit('event', { type: 'output', line } as BuildEvent);
+            emitEvent({ type: 'output', line });
           }
         }
       });
@@ -251,37 +601,86 @@ export function buildSchemeStream(
         const lines = data.toString().split('\n');
         for (const line of lines) {
           if (line.trim()) {
-            emitter.emit('event', { type: 'output', line } as BuildEvent);
+            emitEvent({ type: 'output', line });
           }
         }
       });
 
+      // Honor a pause requested before xcodebuild started
+      if (paused) {
+        emitter.pause();
+      }
+
       proc.on('close', async (code) => {
-        const success = code === 0;
+        const success = !cancelled && code === 0;
+        // Before hashing the products, which doesn't count towards the build's duration
+        const finishedAt = Date.now();
         const products = success ? await findBuildProducts(buildDir) : [];
 
-        emitter.emit('event', {
+        try {
+          const productRecords = await Promise.all(
+            products.map(async (product) => ({
+              name: product.name,
+              sizeBytes: directorySize(product.path),
+              sha256: await hashArtifact(product.path),
+            }))
+          );
+          await recordBuild({
+            id: buildId,
+            project: await getDataStore().projects.findByPath(projectPath),
+            projectPath,
+            scheme,
+            configuration: BUILD_CONFIGURATION,
+            destination,
+            status: cancelled ? 'cancelled' : success ? 'succeeded' : 'failed',
+            durationMs: finishedAt - startedAt,
+            warnings,
+            errors,
+            products: productRecords,
+            buildSettings: settings,
+            startedAt: new Date(startedAt).toISOString(),
+            finishedAt: new Date(finishedAt).toISOString(),
+            environment: await environment,
+          });
+        } catch (err) {
+          console.error(`[xcode] Failed to record build ${buildId}:`, err);
+        }
+
+        if (cancelled) {
+          emitEvent({
+            type: 'error',
+            message: 'Build cancelled',
+            cancelled: true,
+          });
+          emitter.emit('end');
+          return;
+        }
+        recordUsage('build', Date.now() - startedAt);
+
+        emitEvent({
           type: 'completed',
           success,
           buildDir,
           products,
-        } as BuildEvent);
+          durationMs: Date.now() - startedAt,
+          diagnostics: diagnostics.diagnostics(),
+        });
 
         emitter.emit('end');
       });
 
       proc.on('error', (err) => {
-        emitter.emit('event', {
+        emitEvent({
           type: 'error',
           message: err.message,
-        } as BuildEvent);
+        });
         emitter.emit('end');
       });
     } catch (err) {
-      emitter.emit('event', {
+      emitEvent({
         type: 'error',
         message: err instanceof Error ? err.message : String(err),
-      } as BuildEvent);
+      });
       emitter.emit('end');
     }
   })();
@@ -0,0 +1,212 @@
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import type { AddressInfo } from 'net';
+import { createServer } from './server';
+import { closeDatabase, setDatabasePath } from './services/database';
+import { loadPlugins } from './services/plugins';
+import { setWorkspaceRoot } from './services/workspace';
+import { renderFakeFrame } from './services/fake-devices';
+import { AppState } from './services/app-state';
+import {
+  listSimulators,
+  deviceClassFor,
+  capabilitiesFor,
+  DeviceBackend,
+} from './services/simulator';
+import type { HardwareButton, Simulator } from '../shared/ipc-types';
+
+/**
+ * Helpers for testing route handlers against a real server with in-memory state.
+ * Plugin authors can use these to exercise their plugin through `/api/plugins`.
+ */
+
+export interface FakeDevice {
+  udid: string;
+  name: string;
+  state?: string;
+  runtime?: string;
+  deviceType?: string;
+}
+
+export type FakeDeviceCall =
+  | { kind: 'launch'; udid: string; appPath: string; bundleId?: string; launchArgs?: string[] }
+  | { kind: 'tap'; udid: string; x: number; y: number }
+  | { kind: 'swipe'; udid: string; start: { x: number; y: number }; end: { x: number; y: number }; duration: number }
+  | { kind: 'type'; udid: string; text: string }
+  | { kind: 'command'; udid: string; command: string }
+  | { kind: 'button'; udid: string; button: HardwareButton }
+  | { kind: 'simctl'; args: string[] };
+
+export interface FakeDeviceBackend extends DeviceBackend {
+  // Every launch, input, session command and simctl call, in order
+  calls: FakeDeviceCall[];
+}
+
+export interface TestServerOptions {
+  devices?: FakeDevice[];
+  // Use this backend instead of the recording one built from `devices`, e.g. `createFakeDevicesBackend()`
+  backend?: DeviceBackend;
+  // Directory to load plugins from. No plugins are loaded without it.
+  pluginsDir?: string;
+}
+
+export interface TestResponse {
+  status: number;
+  headers: Headers;
+  body: any;
+}
+
+export interface TestServer {
+  baseUrl: string;
+  devices: FakeDeviceBackend;
+  // The server's simulators, clients, emitters and plugins
+  state: AppState;
+  // Run `fn` for this server, e.g. to call simulator functions the way its routes do
+  run<T>(fn: () => T): T;
+  request(method: string, path: string, body?: unknown, headers?: Record<string, string>): Promise<TestResponse>;
+  close(): Promise<void>;
+}
+
+/**
+ * A device backend that serves `devices` as simulators and records input instead of sending it
+ */
+export function createFakeDeviceBackend(devices: FakeDevice[] = []): FakeDeviceBackend {
+  const calls: FakeDeviceCall[] = [];
+
+  return {
+    calls,
+
+    async listSimulators(): Promise<Simulator[]> {
+      return devices.map((device) => {
+        const deviceType = device.deviceType || 'com.apple.CoreSimulator.SimDeviceType.iPhone-16';
+        return {
+          udid: device.udid,
+          name: device.name,
+          state: device.state || 'Booted',
+          runtime: device.runtime || 'com.apple.CoreSimulator.SimRuntime.iOS-18-0',
+          deviceType,
+          deviceClass: deviceClassFor(deviceType),
+          capabilities: capabilitiesFor(deviceType),
+        };
+      });
+    },
+
+    async installAndLaunch(udid, appPath, bundleId, launchArgs) {
+      if (!devices.some((device) => device.udid === udid)) {
+        throw new Error(`Unknown simulator ${udid}`);
+      }
+      calls.push({ kind: 'launch', udid, appPath, bundleId, ...(launchArgs?.length ? { launchArgs } : {}) });
+      const resolvedBundleId = bundleId || 'dev.plasma.fake';
+      return {
+        message: `App ${resolvedBundleId} launched successfully`,
+        bundleId: resolvedBundleId,
+        timings: { total: 0 },
+      };
+    },
+
+    async sendTap(udid, x, y) {
+      calls.push({ kind: 'tap', udid, x, y });
+    },
+
+    async sendSwipe(udid, start, end, duration) {
+      calls.push({ kind: 'swipe', udid, start, end, duration });
+    },
+
+    async typeText(udid, text) {
+      calls.push({ kind: 'type', udid, text });
+    },
+
+    async sendSessionCommand(udid, command) {
+      calls.push({ kind: 'command', udid, command });
+    },
+
+    async pressButton(udid, button) {
+      calls.push({ kind: 'button', udid, button });
+    },
+
+    async simctl(args) {
+      calls.push({ kind: 'simctl', args });
+      return '';
+    },
+
+    // The fake devices' first stream frame, small enough to keep tests fast
+    async screenshot(udid) {
+      if (!devices.some((device) => device.udid === udid)) {
+        throw new Error(`Unknown simulator ${udid}`);
+      }
+      return renderFakeFrame(udid, 0, 40, 80);
+    },
+
+    // Writes a placeholder instead of a video when the recording stops
+    async recordVideo(udid, file) {
+      if (!devices.some((device) => device.udid === udid)) {
+        throw new Error(`Unknown simulator ${udid}`);
+      }
+      return async () => fs.writeFileSync(file, `fake recording of ${udid}`);
+    },
+  };
+}
+
+// Test servers running at the same time share one in-memory database
+let runningServers = 0;
+
+/**
+ * Start the server on a random port with an in-memory database and fake devices.
+ * Only the HTTP and WebSocket handlers run; background jobs are not started. Each server has its
+ * own simulators and clients, so several can run at once.
+ */
+export async function startTestServer(options: TestServerOptions = {}): Promise<TestServer> {
+  if (runningServers++ === 0) {
+    closeDatabase();
+    setDatabasePath(':memory:');
+  }
+  setWorkspaceRoot(fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-workspace-')));
+
+  const devices = createFakeDeviceBackend(options.devices);
+  const state = new AppState(options.backend || devices);
+  const run = <T>(fn: () => T): T => state.run(fn);
+  process.env.PLASMA_PLUGINS_DIR = options.pluginsDir || fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-plugins-'));
+  loadPlugins(state.plugins);
+  // Populate device types so capability checks work before the first list request
+  await run(() => listSimulators());
+
+  const server = createServer(state);
+  await new Promise<void>((resolve) => server.listen(0, '127.0.0.1', resolve));
+  const { port } = server.address() as AddressInfo;
+  const baseUrl = `http://127.0.0.1:${port}`;
+
+  return {
+    baseUrl,
+    devices,
+    state,
+    run,
+
+    async request(method, path, body, headers = {}) {
+      const response = await fetch(`${baseUrl}${path}`, {
+        method,
+        headers: body === undefined ? headers : { 'Content-Type': 'application/json', ...headers },
+        body: body === undefined ? undefined : JSON.stringify(body),
+      });
+      const text = await response.text();
+      let parsed: unknown = text;
+      try {
+        parsed = text ? JSON.parse(text) : null;
+      } catch {
+        // Not JSON; return the raw text
+      }
+      return { status: response.status, headers: response.headers, body: parsed };
+    },
+
+    async close() {
+      await new Promise<void>((resolve) => {
+        server.closeAllConnections();
+        server.close(() => resolve());
+      });
+      state.stop();
+      if (--runningServers === 0) {
+        closeDatabase();
+      }
+    },
+  };
+}
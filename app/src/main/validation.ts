@@ -0,0 +1,162 @@
+import type { FieldError } from '../shared/ipc-types';
+
+/**
+ * Validation of request payloads that reports every invalid field with its path, e.g.
+ * `touches[0].x must be from 0 to 1`, so clients can show errors next to the fields they belong to.
+ *
+ *   const body = validate(await readBody(req), field.object({
+ *     udid: field.string(),
+ *     touches: field.array(field.object({ x: field.number({ min: 0, max: 1 }) }), { min: 1 }),
+ *   }));
+ *
+ * Fields that aren't in an object's shape are left alone.
+ */
+
+export class ValidationError extends Error {
+  constructor(readonly fields: FieldError[]) {
+    super(fields.map((error) => (error.path ? `${error.path} ${error.message}` : error.message)).join('; '));
+  }
+}
+
+// Checks `value`, adding its problems to `errors`, and returns it typed
+export type Validator<T> = (value: unknown, path: string, errors: FieldError[]) => T;
+
+type Shape = Record<string, Validator<unknown>>;
+type ShapeOf<S extends Shape> = { [K in keyof S]: ReturnType<S[K]> };
+
+interface RangeOptions {
+  min?: number;
+  max?: number;
+}
+
+function describeRange({ min, max }: RangeOptions, unit = ''): string {
+  if (min !== undefined && max !== undefined) {
+    return `from ${min} to ${max}${unit}`;
+  }
+  return min !== undefined ? `at least ${min}${unit}` : `at most ${max}${unit}`;
+}
+
+function inRange(value: number, { min, max }: RangeOptions): boolean {
+  return (min === undefined || value >= min) && (max === undefined || value <= max);
+}
+
+const childPath = (path: string, key: string) => (path ? `${path}.${key}` : key);
+
+export const field = {
+  string(options: RangeOptions & { allowEmpty?: boolean } = {}): Validator<string> {
+    return (value, path, errors) => {
+      if (typeof value !== 'string') {
+        errors.push({ path, message: value === undefined || value === null ? 'is required' : 'must be a string' });
+      } else if (!value && !options.allowEmpty) {
+        errors.push({ path, message: 'is required' });
+      } else if (!inRange(value.length, options)) {
+        errors.push({ path, message: `must be ${describeRange(options, ' characters')}` });
+      }
+      return value as string;
+    };
+  },
+
+  number(options: RangeOptions & { integer?: boolean } = {}): Validator<number> {
+    return (value, path, errors) => {
+      if (value === undefined || value === null) {
+        errors.push({ path, message: 'is required' });
+      } else if (typeof value !== 'number' || !Number.isFinite(value)) {
+        errors.push({ path, message: options.integer ? 'must be a whole number' : 'must be a number' });
+      } else if (options.integer && !Number.isInteger(value)) {
+        errors.push({ path, message: 'must be a whole number' });
+      } else if (!inRange(value, options)) {
+        errors.push({ path, message: `must be ${describeRange(options)}` });
+      }
+      return value as number;
+    };
+  },
+
+  boolean(): Validator<boolean> {
+    return (value, path, errors) => {
+      if (typeof value !== 'boolean') {
+        errors.push({ path, message: value === undefined || value === null ? 'is required' : 'must be true or false' });
+      }
+      return value as boolean;
+    };
+  },
+
+  oneOf<T extends string>(values: readonly T[]): Validator<T> {
+    return (value, path, errors) => {
+      if (!values.includes(value as T)) {
+        errors.push({ path, message: value === undefined || value === null ? 'is required' : `must be one of ${values.join(', ')}` });
+      }
+      return value as T;
+    };
+  },
+
+  array<T>(item: Validator<T>, options: RangeOptions = {}): Validator<T[]> {
+    return (value, path, errors) => {
+      if (!Array.isArray(value)) {
+        errors.push({ path, message: value === undefined || value === null ? 'is required' : 'must be an array' });
+        return value as T[];
+      }
+      if (!inRange(value.length, options)) {
+        const empty = value.length === 0 && options.min === 1 && options.max === undefined;
+        errors.push({ path, message: empty ? "can't be empty" : `must have ${describeRange(options, ' items')}` });
+      }
+      return value.map((element, index) => item(element, `${path}[${index}]`, errors));
+    };
+  },
+
+  object<S extends Shape>(shape: S): Validator<ShapeOf<S>> {
+    return (value, path, errors) => {
+      if (!value || typeof value !== 'object' || Array.isArray(value)) {
+        errors.push({ path, message: value === undefined || value === null ? 'is required' : 'must be an object' });
+        return value as ShapeOf<S>;
+      }
+      const object = value as Record<string, unknown>;
+      for (const [key, validator] of Object.entries(shape)) {
+        validator(object[key], childPath(path, key), errors);
+      }
+      return value as ShapeOf<S>;
+    };
+  },
+
+  optional<T>(validator: Validator<T>): Validator<T | undefined> {
+    return (value, path, errors) => (value === undefined ? undefined : validator(value, path, errors));
+  },
+
+  nullable<T>(validator: Validator<T>): Validator<T | null> {
+    return (value, path, errors) => (value === null ? null : validator(value, path, errors));
+  },
+
+  // A value `guard` accepts, with `message` for anything else
+  is<T>(guard: (value: unknown) => value is T, message: string): Validator<T> {
+    return (value, path, errors) => {
+      if (!guard(value)) {
+        errors.push({ path, message: value === undefined || value === null ? 'is required' : message });
+      }
+      return value as T;
+    };
+  },
+
+  // `validator` with a check of its own, returning why a valid value is still invalid or null
+  check<T>(validator: Validator<T>, problem: (value: T) => string | null): Validator<T> {
+    return (value, path, errors) => {
+      const before = errors.length;
+      const checked = validator(value, path, errors);
+      const message = errors.length === before ? problem(checked) : null;
+      if (message) {
+        errors.push({ path, message });
+      }
+      return checked;
+    };
+  },
+};
+
+/**
+ * Return `value` typed by `validator`, or throw a ValidationError with every invalid field
+ */
+export function validate<T>(value: unknown, validator: Validator<T>): T {
+  const errors: FieldError[] = [];
+  const checked = validator(value, '', errors);
+  if (errors.length > 0) {
+    throw new ValidationError(errors);
+  }
+  return checked;
+}
@@ -0,0 +1,50 @@
+import { WebSocket } from 'ws';
+import { ChannelName, getConfig } from './config';
+
+// How often a paused upstream re-checks whether the subscriber drained
+const DRAIN_POLL_INTERVAL_MS = 50;
+
+const CHANNEL_BY_MESSAGE: Record<string, ChannelName> = {
+  'simulator:stream:frame': 'frames',
+  'simulator:log': 'logs',
+  'xcode:build:event': 'builds',
+};
+
+/**
+ * Channel a WebSocket message type belongs to
+ */
+export function channelForMessage(type: string): ChannelName {
+  return CHANNEL_BY_MESSAGE[type] || 'events';
+}
+
+/**
+ * Whether a subscriber has more data queued than the channel allows
+ */
+export function isLagging(ws: WebSocket, channel: ChannelName): boolean {
+  return ws.bufferedAmount > getConfig().channels[channel].capacityBytes;
+}
+
+/**
+ * How much of the channel's capacity is queued for a subscriber, 1 when it's full
+ */
+export function backlog(ws: WebSocket, channel: ChannelName): number {
+  return ws.bufferedAmount / getConfig().channels[channel].capacityBytes;
+}
+
+/**
+ * Resolve once the subscriber's queue is back under half the channel
+ * capacity, or the socket closed
+ */
+export function waitUntilDrained(ws: WebSocket, channel: ChannelName): Promise<void> {
+  const threshold = getConfig().channels[channel].capacityBytes / 2;
+  return new Promise((resolve) => {
+    const check = () => {
+      if (ws.readyState !== WebSocket.OPEN || ws.bufferedAmount <= threshold) {
+        resolve();
+      } else {
+        setTimeout(check, DRAIN_POLL_INTERVAL_MS);
+      }
+    };
+    check();
+  });
+}
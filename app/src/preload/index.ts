@@ -1,7 +1,7 @@
 import { contextBridge, ipcRenderer } from 'electron';
 
 // Expose only essential Electron-specific methods to the renderer process
-// All API calls go through HTTP to localhost:3001
+// All API calls go through HTTP to the local backend (port 3001 unless it was taken)
 contextBridge.exposeInMainWorld('electron', {
   // App info
   getVersion: () => ipcRenderer.invoke('get-version'),
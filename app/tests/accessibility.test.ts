@@ -0,0 +1,77 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, createFakeDeviceBackend, TestServer } from '../src/main/testing';
+
+let server: TestServer;
+const fake = createFakeDeviceBackend([{ udid: 'PHONE', name: 'iPhone 16' }]);
+const contrast = new Map<string, string>();
+
+before(async () => {
+  server = await startTestServer({
+    backend: {
+      ...fake,
+      // Bold Text is on and the Dynamic Type size is the default
+      async simctl(args) {
+        await fake.simctl(args);
+        if (args[0] === 'ui' && args[2] === 'increase_contrast') {
+          if (args[3]) {
+            contrast.set(args[1], args[3]);
+          }
+          return contrast.get(args[1]) ?? 'disabled';
+        }
+        if (args[0] === 'ui' && args[2] === 'content_size') {
+          return 'large\n';
+        }
+        if (args.includes('read')) {
+          return args.at(-1) === 'EnhancedTextLegibilityEnabled' ? '1\n' : '0\n';
+        }
+        return '';
+      },
+    },
+  });
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('reads the accessibility settings of a simulator', async () => {
+  const response = await server.request('GET', '/api/simulator/PHONE/accessibility');
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(response.body, {
+    reduceMotion: false,
+    reduceTransparency: false,
+    boldText: true,
+    increaseContrast: false,
+    contentSize: 'large',
+  });
+});
+
+test('changes only the settings it is given', async () => {
+  const response = await server.request('PUT', '/api/simulator/PHONE/accessibility', {
+    reduceMotion: true,
+    increaseContrast: true,
+    contentSize: 'accessibility-large',
+  });
+
+  assert.equal(response.status, 200);
+  assert.equal(response.body.increaseContrast, true);
+  const writes = fake.calls.filter((call) => call.kind === 'simctl' && (call.args.includes('write') || call.args.length === 4));
+  assert.deepEqual(writes, [
+    { kind: 'simctl', args: ['spawn', 'PHONE', 'defaults', 'write', 'com.apple.Accessibility', 'ReduceMotionEnabled', '-bool', 'true'] },
+    { kind: 'simctl', args: ['ui', 'PHONE', 'increase_contrast', 'enabled'] },
+    { kind: 'simctl', args: ['ui', 'PHONE', 'content_size', 'accessibility-large'] },
+  ]);
+});
+
+test('rejects VoiceOver and invalid settings', async () => {
+  const voiceOver = await server.request('PUT', '/api/simulator/PHONE/accessibility', { voiceOver: true });
+  assert.equal(voiceOver.status, 501);
+
+  const size = await server.request('PUT', '/api/simulator/PHONE/accessibility', { contentSize: 'huge' });
+  assert.equal(size.status, 400);
+
+  const empty = await server.request('PUT', '/api/simulator/PHONE/accessibility', {});
+  assert.equal(empty.status, 400);
+});
@@ -0,0 +1,39 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer();
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('changes the log filter at runtime', async () => {
+  const updated = await server.request('PUT', '/api/admin/log-level', { filter: 'warn, Simulator=trace' });
+  assert.equal(updated.status, 200);
+  assert.equal(updated.body.filter, 'warn,simulator=trace');
+
+  const current = await server.request('GET', '/api/admin/log-level');
+  assert.equal(current.body.filter, 'warn,simulator=trace');
+});
+
+test('rejects an invalid log filter and keeps the active one', async () => {
+  const response = await server.request('PUT', '/api/admin/log-level', { filter: 'info,simulator=loud' });
+  assert.equal(response.status, 400);
+  assert.match(response.body.error, /Unknown log level "loud"/);
+
+  const current = await server.request('GET', '/api/admin/log-level');
+  assert.equal(current.body.filter, 'warn,simulator=trace');
+});
+
+test('reports the port the server listens on', async () => {
+  const response = await server.request('GET', '/api/about');
+
+  assert.equal(response.status, 200);
+  assert.equal(response.body.url, server.baseUrl.replace('127.0.0.1', 'localhost'));
+  assert.equal(response.body.pid, process.pid);
+});
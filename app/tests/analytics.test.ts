@@ -0,0 +1,62 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { recordUsage } from '../src/main/services/analytics';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({
+    devices: [{ udid: 'PHONE', name: 'iPhone 16' }],
+  });
+});
+
+after(async () => {
+  await server.close();
+});
+
+const launch = () =>
+  server.request('POST', '/api/simulator/launch', { udid: 'PHONE', appPath: '/tmp/Plasma.app', bundleId: 'dev.plasma.app' });
+
+test('records nothing until analytics are enabled', async () => {
+  await launch();
+  const response = await server.request('GET', '/api/analytics/usage');
+
+  assert.equal(response.status, 200);
+  assert.equal(response.body.enabled, false);
+  assert.deepEqual(
+    response.body.totals.map((total: { kind: string; count: number }) => [total.kind, total.count]),
+    [['build', 0], ['launch', 0], ['stream', 0]]
+  );
+});
+
+test('counts usage with average durations once enabled', async () => {
+  const enabled = await server.request('PUT', '/api/analytics', { enabled: true });
+  assert.equal(enabled.body.enabled, true);
+
+  await launch();
+  await recordUsage('build', 1000);
+  await recordUsage('build', 3000);
+
+  const response = await server.request('GET', '/api/analytics/usage?days=7');
+  assert.deepEqual(response.body.totals, [
+    { kind: 'build', count: 2, averageDurationMs: 2000 },
+    { kind: 'launch', count: 1, averageDurationMs: 0 },
+    { kind: 'stream', count: 0, averageDurationMs: null },
+  ]);
+  assert.equal(response.body.daily.length, 1);
+  assert.equal(response.body.daily[0].build, 2);
+});
+
+test('deletes collected usage when opting out', async () => {
+  await server.request('PUT', '/api/analytics', { enabled: false });
+  await server.request('PUT', '/api/analytics', { enabled: true });
+
+  const response = await server.request('GET', '/api/analytics/usage');
+  assert.deepEqual(response.body.daily, []);
+});
+
+test('rejects invalid windows', async () => {
+  const response = await server.request('GET', '/api/analytics/usage?days=0');
+  assert.equal(response.status, 400);
+});
@@ -0,0 +1,163 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { setFeatureFlagDefaults } from '../src/main/services/feature-flags';
+
+let server: TestServer;
+let sdk: string;
+
+// An SDK whose tools answer like one running Pixel_8 emulator, and log what adb was asked to do
+function writeFakeSdk(): string {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-android-sdk-'));
+  const tools: Record<string, string> = {
+    'cmdline-tools/latest/bin/avdmanager': `printf 'Pixel_8\\nPixel_Tablet\\n'`,
+    'platform-tools/adb': `echo "$*" >> "${dir}/adb.log"
+case "$*" in
+  devices) printf 'List of devices attached\\nemulator-5554\\tdevice\\n\\n' ;;
+  "-s emulator-5554 emu avd name") printf 'Pixel_8\\r\\nOK\\r\\n' ;;
+  *resolve-activity*) printf 'priority=0 preferredOrder=0 match=0x108000\\ncom.example.app/.MainActivity\\n' ;;
+  *"am start"*) printf 'Starting: Intent { cmp=com.example.app/.MainActivity }\\n' ;;
+esac`,
+  };
+  for (const [tool, script] of Object.entries(tools)) {
+    fs.mkdirSync(path.join(dir, path.dirname(tool)), { recursive: true });
+    fs.writeFileSync(path.join(dir, tool), `#!/bin/sh\n${script}\n`, { mode: 0o755 });
+  }
+  return dir;
+}
+
+// Server-Sent Events in `text`, without comments and the retry delay
+function parseEvents(text: string): Array<{ id: number; name: string; data: any }> {
+  return text
+    .split('\n\n')
+    .map((block) => Object.fromEntries(block.split('\n').map((line) => [line.slice(0, line.indexOf(': ')), line.slice(line.indexOf(': ') + 2)])))
+    .filter((fields) => fields.event)
+    .map((fields) => ({ id: Number(fields.id), name: fields.event, data: JSON.parse(fields.data) }));
+}
+
+function adbCalls(): string[] {
+  return fs.readFileSync(path.join(sdk, 'adb.log'), 'utf-8').trim().split('\n');
+}
+
+before(async () => {
+  server = await startTestServer();
+  sdk = writeFakeSdk();
+  process.env.ANDROID_HOME = sdk;
+});
+
+after(async () => {
+  setFeatureFlagDefaults({});
+  delete process.env.ANDROID_HOME;
+  await server.close();
+});
+
+test('hides Android endpoints unless the android flag is on', async () => {
+  setFeatureFlagDefaults({});
+  assert.equal((await server.request('GET', '/api/android/emulators')).status, 404);
+
+  setFeatureFlagDefaults({ android: true });
+  assert.equal((await server.request('GET', '/api/android/emulators')).status, 200);
+});
+
+test('lists AVDs with the serial of running emulators', async () => {
+  setFeatureFlagDefaults({ android: true });
+  const response = await server.request('GET', '/api/android/emulators');
+
+  assert.deepEqual(response.body, [
+    { name: 'Pixel_8', serial: 'emulator-5554', state: 'Booted' },
+    { name: 'Pixel_Tablet', serial: null, state: 'Shutdown' },
+  ]);
+});
+
+test('installs and launches apps on an emulator', async () => {
+  setFeatureFlagDefaults({ android: true });
+  const response = await server.request('POST', '/api/android/launch', {
+    serial: 'emulator-5554',
+    packageName: 'com.example.app',
+    apkPath: '/tmp/app-debug.apk',
+  });
+
+  assert.equal(response.status, 200);
+  assert.equal(response.body.component, 'com.example.app/.MainActivity');
+  assert.deepEqual(adbCalls().slice(-3), [
+    '-s emulator-5554 install -r /tmp/app-debug.apk',
+    '-s emulator-5554 shell cmd package resolve-activity --brief -c android.intent.category.LAUNCHER com.example.app',
+    '-s emulator-5554 shell am start -n com.example.app/.MainActivity',
+  ]);
+});
+
+test('rejects launches of unknown emulators or invalid packages', async () => {
+  setFeatureFlagDefaults({ android: true });
+  const invalid = await server.request('POST', '/api/android/launch', { serial: 'emulator-5554', packageName: 'app; reboot' });
+  assert.equal(invalid.status, 400);
+
+  const missing = await server.request('POST', '/api/android/launch', { serial: 'emulator-5556', packageName: 'com.example.app' });
+  assert.equal(missing.status, 404);
+});
+
+test('builds Android projects with the Gradle wrapper', async () => {
+  setFeatureFlagDefaults({ android: true });
+  const project = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-android-project-'));
+  fs.writeFileSync(path.join(project, 'build.gradle.kts'), '');
+  fs.writeFileSync(
+    path.join(project, 'gradlew'),
+    `#!/bin/sh
+echo "> Task :app:$1"
+mkdir -p app/build/outputs/apk/debug && touch app/build/outputs/apk/debug/app-debug.apk
+`,
+    { mode: 0o755 }
+  );
+
+  const response = await fetch(`${server.baseUrl}/api/android/build/stream?path=${encodeURIComponent(project)}`);
+  const events = parseEvents(await response.text());
+
+  assert.deepEqual(
+    events.map((event) => event.name),
+    ['build.started', 'build.output', 'build.completed', 'build.end']
+  );
+  assert.equal(events[1].data.line, '> Task :app:assembleDebug');
+  assert.deepEqual(events[2].data.products, [
+    { name: 'app-debug.apk', path: path.join(project, 'app', 'build', 'outputs', 'apk', 'debug', 'app-debug.apk') },
+  ]);
+});
+
+test('resumes a build stream after a reconnect instead of building again', async () => {
+  setFeatureFlagDefaults({ android: true });
+  const project = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-android-project-'));
+  fs.writeFileSync(path.join(project, 'build.gradle.kts'), '');
+  fs.writeFileSync(
+    path.join(project, 'gradlew'),
+    `#!/bin/sh
+echo run >> runs.log
+echo "> Task :app:preBuild"
+sleep 0.5
+echo "> Task :app:$1"
+`,
+    { mode: 0o755 }
+  );
+  const url = `${server.baseUrl}/api/android/build/stream?path=${encodeURIComponent(project)}`;
+
+  // Drop the connection once the build has started
+  const first = await fetch(url);
+  const reader = first.body!.getReader();
+  let received = '';
+  while (!received.includes('event: build.output')) {
+    received += new TextDecoder().decode((await reader.read()).value);
+  }
+  await reader.cancel();
+  const seen = parseEvents(received);
+
+  const resumed = await fetch(url, { headers: { 'Last-Event-ID': String(seen[seen.length - 1].id) } });
+  const events = [...seen, ...parseEvents(await resumed.text())];
+
+  assert.deepEqual(
+    events.map((event) => event.name),
+    ['build.started', 'build.output', 'build.output', 'build.completed', 'build.end']
+  );
+  assert.equal(events[2].data.line, '> Task :app:assembleDebug');
+  assert.deepEqual(events.map((event) => event.id), [...events.map((event) => event.id)].sort((a, b) => a - b));
+  assert.equal(fs.readFileSync(path.join(project, 'runs.log'), 'utf-8'), 'run\n');
+});
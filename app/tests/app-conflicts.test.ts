@@ -0,0 +1,57 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { compareAppIdentities } from '../src/main/services/app-conflicts';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({ devices: [{ udid: 'PHONE', name: 'iPhone 16' }] });
+});
+
+after(async () => {
+  await server.close();
+});
+
+const app = { bundleId: 'dev.plasma.App', name: 'Plasma', teamId: 'TEAM1', version: '1.0' };
+
+test('another version of the same app is not a conflict', () => {
+  assert.equal(compareAppIdentities(app, { ...app, version: '1.1' }), null);
+  // Builds without signing have no team to compare
+  assert.equal(compareAppIdentities(app, { ...app, teamId: null }), null);
+});
+
+test('reports apps of another team or with another name', () => {
+  const conflict = compareAppIdentities(app, { ...app, name: 'Plasma Beta', teamId: 'TEAM2' });
+
+  assert.deepEqual(conflict, {
+    bundleId: 'dev.plasma.App',
+    installed: { name: 'Plasma', teamId: 'TEAM1', version: '1.0' },
+    reasons: ["it's signed by team TEAM1, the new build by TEAM2", 'it\'s named "Plasma", the new build "Plasma Beta"'],
+  });
+});
+
+test('rejects unknown conflict policies', async () => {
+  const response = await server.request('POST', '/api/simulator/launch', {
+    udid: 'PHONE',
+    appPath: '/tmp/Plasma.app',
+    bundleId: 'dev.plasma.App',
+    onBundleIdConflict: 'ignore',
+  });
+
+  assert.equal(response.status, 400);
+  assert.equal(response.body.error, 'onBundleIdConflict must be one of replace, fail, uninstall');
+});
+
+test('launches without a conflict when the app is not installed', async () => {
+  const response = await server.request('POST', '/api/simulator/launch', {
+    udid: 'PHONE',
+    appPath: '/tmp/Plasma.app',
+    bundleId: 'dev.plasma.App',
+    onBundleIdConflict: 'fail',
+  });
+
+  assert.equal(response.status, 200);
+  assert.equal(response.body.bundleIdConflict, undefined);
+  assert.deepEqual(server.devices.calls.at(-2), { kind: 'simctl', args: ['get_app_container', 'PHONE', 'dev.plasma.App', 'app'] });
+});
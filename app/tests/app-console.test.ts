@@ -0,0 +1,62 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, createFakeDeviceBackend, TestServer } from '../src/main/testing';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({
+    backend: {
+      ...createFakeDeviceBackend([{ udid: 'PHONE', name: 'iPhone 16' }]),
+      // Prints like an app on a pty, in chunks that split lines, then exits
+      appConsole(_udid, _bundleId, onOutput, onExit) {
+        setTimeout(() => {
+          onOutput('Launching\r\nLoaded 1');
+          onOutput('2 items\r\nno newline');
+          onExit(0);
+        }, 10);
+      },
+    },
+  });
+});
+
+after(async () => {
+  await server.close();
+});
+
+function consoleEvents(text: string): Array<{ name: string; data: any }> {
+  return text
+    .split('\n\n')
+    .filter((block) => block.includes('event: console.'))
+    .map((block) => ({
+      name: /event: (.*)/.exec(block)![1],
+      data: JSON.parse(block.slice(block.indexOf('data: ') + 'data: '.length)),
+    }));
+}
+
+test('streams the console of a launch from its first line', async () => {
+  const launch = await server.request('POST', '/api/simulator/launch', { udid: 'PHONE', appPath: '/tmp/Plasma.app', bundleId: 'dev.plasma.app' });
+  assert.equal(launch.status, 200);
+  assert.ok(launch.body.launchId);
+
+  // Connects after the app printed everything and exited
+  await new Promise((resolve) => setTimeout(resolve, 50));
+  const response = await fetch(`${server.baseUrl}/api/simulator/launches/${launch.body.launchId}/console`);
+  const events = consoleEvents(await response.text());
+
+  assert.deepEqual(
+    events.map((event) => event.name),
+    ['console.output', 'console.output', 'console.output', 'console.exit']
+  );
+  assert.deepEqual(
+    events.slice(0, 3).map((event) => event.data.line),
+    ['Launching', 'Loaded 12 items', 'no newline']
+  );
+  assert.equal(events[0].data.bundleId, 'dev.plasma.app');
+  assert.equal(events[3].data.exitCode, 0);
+});
+
+test('returns 404 for unknown launches', async () => {
+  const response = await server.request('GET', '/api/simulator/launches/unknown/console');
+  assert.equal(response.status, 404);
+});
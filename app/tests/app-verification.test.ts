@@ -0,0 +1,90 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { createFakeDevicesBackend } from '../src/main/services/fake-devices';
+import { parseOpenStepPlist, verifyInstall } from '../src/main/services/app-verification';
+
+let server: TestServer;
+let installedApp: string;
+
+const listApps = (appPath: string) => `{
+    "com.apple.mobilesafari" =     {
+        ApplicationType = System;
+        CFBundleExecutable = MobileSafari;
+        CFBundleIdentifier = "com.apple.mobilesafari";
+    };
+    "dev.plasma.App" =     {
+        ApplicationType = User;
+        Bundle = "file://${appPath}/";
+        CFBundleDisplayName = "Plasma \\"Dev\\"";
+        CFBundleExecutable = Plasma;
+        CFBundleIdentifier = "dev.plasma.App";
+        CFBundleShortVersionString = "1.2";
+        CFBundleVersion = 42;
+        GroupContainers =         {
+        };
+        Path = "${appPath}";
+        SBAppTags =         (
+            hidden,
+            "test"
+        );
+    };
+}
+`;
+
+before(async () => {
+  installedApp = path.join(fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-installed-')), 'Plasma.app');
+  fs.mkdirSync(installedApp);
+  fs.writeFileSync(path.join(installedApp, 'Plasma'), '');
+  server = await startTestServer({
+    backend: {
+      ...createFakeDevicesBackend(),
+      async simctl(args) {
+        return args[0] === 'listapps' ? listApps(installedApp) : '';
+      },
+    },
+  });
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('parses the installed apps simctl lists', () => {
+  const apps = parseOpenStepPlist(listApps('/Apps/Plasma.app')) as any;
+
+  assert.deepEqual(Object.keys(apps), ['com.apple.mobilesafari', 'dev.plasma.App']);
+  assert.equal(apps['dev.plasma.App'].CFBundleDisplayName, 'Plasma "Dev"');
+  assert.equal(apps['dev.plasma.App'].CFBundleVersion, '42');
+  assert.deepEqual(apps['dev.plasma.App'].SBAppTags, ['hidden', 'test']);
+  assert.deepEqual(apps['dev.plasma.App'].GroupContainers, {});
+});
+
+test('verifies an installed app', async () => {
+  const verification = await server.run(() => verifyInstall('PHONE', '/tmp/Plasma.app', 'dev.plasma.App'));
+
+  assert.equal(verification.installed, true);
+  assert.equal(verification.installedPath, installedApp);
+  assert.equal(verification.installedVersion, '1.2');
+  assert.equal(verification.launchable, true);
+  assert.deepEqual(verification.problems, []);
+});
+
+test('reports apps missing from the simulator', async () => {
+  const verification = await server.run(() => verifyInstall('PHONE', '/tmp/Other.app', 'dev.plasma.Other'));
+
+  assert.equal(verification.installed, false);
+  assert.deepEqual(verification.problems, ["dev.plasma.Other is not in the simulator's installed apps"]);
+});
+
+test('reports installed apps without their executable', async () => {
+  fs.rmSync(path.join(installedApp, 'Plasma'));
+
+  const verification = await server.run(() => verifyInstall('PHONE', '/tmp/Plasma.app', 'dev.plasma.App'));
+
+  assert.equal(verification.launchable, false);
+  assert.deepEqual(verification.problems, ['the installed app has no Plasma executable']);
+});
@@ -0,0 +1,50 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({ devices: [{ udid: 'PHONE', name: 'iPhone 16' }] });
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('switches between light and dark appearance', async () => {
+  const response = await server.request('PUT', '/api/simulator/PHONE/appearance', { appearance: 'dark' });
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(server.devices.calls.at(-1), { kind: 'simctl', args: ['ui', 'PHONE', 'appearance', 'dark'] });
+
+  const invalid = await server.request('PUT', '/api/simulator/PHONE/appearance', { appearance: 'sepia' });
+  assert.equal(invalid.status, 400);
+});
+
+test('overrides the status bar for screenshots', async () => {
+  const response = await server.request('PUT', '/api/simulator/PHONE/status-bar', {
+    time: '9:41',
+    batteryLevel: 100,
+    batteryState: 'charged',
+    cellularBars: 4,
+  });
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(server.devices.calls.at(-1), {
+    kind: 'simctl',
+    args: ['status_bar', 'PHONE', 'override', '--time', '9:41', '--batteryLevel', '100', '--batteryState', 'charged', '--cellularBars', '4'],
+  });
+
+  await server.request('DELETE', '/api/simulator/PHONE/status-bar');
+  assert.deepEqual(server.devices.calls.at(-1), { kind: 'simctl', args: ['status_bar', 'PHONE', 'clear'] });
+});
+
+test('rejects invalid status bar overrides', async () => {
+  const level = await server.request('PUT', '/api/simulator/PHONE/status-bar', { batteryLevel: 101 });
+  assert.equal(level.status, 400);
+  assert.equal(level.body.error, 'batteryLevel must be a whole number from 0 to 100');
+
+  const unknown = await server.request('PUT', '/api/simulator/PHONE/status-bar', { signal: 'strong' });
+  assert.equal(unknown.body.error, 'Unknown status bar override: signal');
+});
@@ -0,0 +1,39 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({ devices: [{ udid: 'PHONE', name: 'iPhone 16' }] });
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('matches a face or finger through BiometricKit notifications', async () => {
+  assert.equal((await server.request('POST', '/api/simulator/PHONE/biometrics', { action: 'match' })).status, 200);
+  assert.equal((await server.request('POST', '/api/simulator/PHONE/biometrics', { action: 'nonmatch', sensor: 'touch' })).status, 200);
+
+  assert.deepEqual(server.devices.calls.slice(-2), [
+    { kind: 'simctl', args: ['spawn', 'PHONE', 'notifyutil', '-p', 'com.apple.BiometricKit_Sim.pearl.match'] },
+    { kind: 'simctl', args: ['spawn', 'PHONE', 'notifyutil', '-p', 'com.apple.BiometricKit_Sim.fingerTouch.nonmatch'] },
+  ]);
+});
+
+test('toggles biometric enrollment', async () => {
+  const response = await server.request('PUT', '/api/simulator/PHONE/biometrics/enrollment', { enrolled: true });
+
+  assert.deepEqual(response.body, { enrolled: true });
+  assert.deepEqual(server.devices.calls.slice(-2), [
+    { kind: 'simctl', args: ['spawn', 'PHONE', 'notifyutil', '-s', 'com.apple.BiometricKit.enrollmentChanged', '1'] },
+    { kind: 'simctl', args: ['spawn', 'PHONE', 'notifyutil', '-p', 'com.apple.BiometricKit.enrollmentChanged'] },
+  ]);
+});
+
+test('rejects unknown biometric actions and sensors', async () => {
+  assert.equal((await server.request('POST', '/api/simulator/PHONE/biometrics', { action: 'approve' })).status, 400);
+  assert.equal((await server.request('POST', '/api/simulator/PHONE/biometrics', { action: 'match', sensor: 'iris' })).status, 400);
+  assert.equal((await server.request('PUT', '/api/simulator/PHONE/biometrics/enrollment', { enrolled: 'yes' })).status, 400);
+});
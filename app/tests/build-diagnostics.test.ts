@@ -0,0 +1,50 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { parseDiagnostic, createDiagnosticCollector } from '../src/main/services/build-diagnostics';
+
+test('parses compiler diagnostics with a location', () => {
+  assert.deepEqual(parseDiagnostic("/Users/me/App/ContentView.swift:12:5: error: cannot find 'foo' in scope"), {
+    file: '/Users/me/App/ContentView.swift',
+    line: 12,
+    column: 5,
+    severity: 'error',
+    message: "cannot find 'foo' in scope",
+  });
+  assert.deepEqual(parseDiagnostic('/Users/me/App/Info.plist:3: warning: duplicate key'), {
+    file: '/Users/me/App/Info.plist',
+    line: 3,
+    column: null,
+    severity: 'warning',
+    message: 'duplicate key',
+  });
+  assert.equal(parseDiagnostic('/Users/me/App/App.xcodeproj: note: target uses legacy build settings')?.file, '/Users/me/App/App.xcodeproj');
+});
+
+test('parses diagnostics not tied to a file', () => {
+  assert.deepEqual(parseDiagnostic("ld: warning: directory not found for option '-L/usr/local/lib'"), {
+    file: null,
+    line: null,
+    column: null,
+    severity: 'warning',
+    message: "directory not found for option '-L/usr/local/lib'",
+  });
+  assert.equal(parseDiagnostic('error: Signing for "App" requires a development team.')?.severity, 'error');
+});
+
+test('ignores other output and tool notes', () => {
+  assert.equal(parseDiagnostic('CompileSwiftSources normal arm64 (in target \'App\' from project \'App\')'), null);
+  assert.equal(parseDiagnostic('note: Building targets in dependency order'), null);
+  assert.equal(parseDiagnostic('** BUILD FAILED **'), null);
+});
+
+test('collects distinct diagnostics in order', () => {
+  const collector = createDiagnosticCollector();
+  collector.observe('/App/A.swift:1:1: warning: unused variable');
+  collector.observe('/App/B.swift:2:3: error: type mismatch');
+  collector.observe('/App/A.swift:1:1: warning: unused variable');
+
+  assert.deepEqual(
+    collector.diagnostics().map((diagnostic) => diagnostic.message),
+    ['unused variable', 'type mismatch']
+  );
+});
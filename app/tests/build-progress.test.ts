@@ -0,0 +1,36 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { createBuildProgress } from '../src/main/services/build-progress';
+
+const step = (target: string) => `CompileSwiftSources normal arm64 (in target '${target}' from project 'Plasma')`;
+
+test('estimates progress from the build plan', () => {
+  const progress = createBuildProgress(0, null);
+  assert.equal(progress.estimate(0), null);
+
+  progress.observe('Target dependency graph (4 targets)');
+  progress.observe(step('Core'));
+  assert.equal(progress.estimate(0), 0);
+
+  progress.observe(step('UI'));
+  progress.observe(step('Core'));
+  progress.observe(step('Widgets'));
+  assert.equal(progress.estimate(0), 0.5);
+});
+
+test('estimates progress from the typical duration and never goes backwards', () => {
+  const progress = createBuildProgress(0, 10000);
+  assert.equal(progress.estimate(2500), 0.25);
+  assert.equal(progress.estimate(2000), 0.25);
+  // A build running longer than usual stays below 100%
+  assert.equal(progress.estimate(30000), 0.99);
+});
+
+test('uses the higher of the two estimates', () => {
+  const progress = createBuildProgress(0, 10000);
+  progress.observe('Target dependency graph (2 targets)');
+  progress.observe(step('Core'));
+  progress.observe(step('UI'));
+  assert.equal(progress.estimate(1000), 0.5);
+  assert.equal(progress.estimate(8000), 0.8);
+});
@@ -0,0 +1,62 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, createFakeDeviceBackend, TestServer } from '../src/main/testing';
+
+const IPAD = 'com.apple.CoreSimulator.SimDeviceType.iPad-Pro-11-inch-M4';
+const fake = createFakeDeviceBackend([{ udid: 'IPAD', name: 'iPad Pro', deviceType: IPAD }]);
+let server: TestServer;
+let answersHitTests = true;
+
+// An iPad in landscape whose accessibility frames AXe reports in portrait coordinates: a 3x3 grid
+// of buttons on a 768x1024 screen, displayed rotated to 1024x768
+const screen = { x: 0, y: 0, width: 768, height: 1024 };
+const buttons = Array.from({ length: 9 }, (_, i) => ({
+  AXLabel: `Button ${i}`,
+  frame: { x: (i % 3) * 256, y: Math.floor(i / 3) * (1024 / 3), width: 256, height: 1024 / 3 },
+}));
+
+before(async () => {
+  server = await startTestServer({
+    backend: {
+      ...fake,
+      async describeUi(_udid, point) {
+        if (!point) {
+          return JSON.stringify([{ AXLabel: 'App', frame: screen, children: buttons }]);
+        }
+        if (!answersHitTests) {
+          return '[]';
+        }
+        // Display points back to portrait frame coordinates
+        const x = point.y;
+        const y = screen.height - point.x;
+        const hit = buttons.find(({ frame }) => x >= frame.x && x <= frame.x + frame.width && y >= frame.y && y <= frame.y + frame.height);
+        return JSON.stringify(hit ? [hit] : []);
+      },
+    },
+  });
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('calibrates a screen whose accessibility frames are rotated', async () => {
+  const response = await server.request('POST', '/api/simulator/IPAD/calibrate');
+
+  assert.equal(response.status, 200);
+  assert.ok(response.body.probes.every((probe: { hit: boolean }) => probe.hit));
+  const { scaleX, scaleY, offsetX, offsetY } = response.body.transform;
+  assert.deepEqual([scaleX, scaleY, offsetX, offsetY].map(Math.round), [1024, 768, 0, 0]);
+
+  await server.request('POST', '/api/simulator/tap', { udid: 'IPAD', x: 0.25, y: 0.5, screenWidth: 2048, screenHeight: 1536 });
+  assert.deepEqual(fake.calls.at(-1), { kind: 'tap', udid: 'IPAD', x: 256, y: 384 });
+});
+
+test('rejects a calibration whose probes miss', async () => {
+  answersHitTests = false;
+  const response = await server.request('POST', '/api/simulator/IPAD/calibrate');
+  answersHitTests = true;
+
+  assert.equal(response.status, 422);
+  assert.match(response.body.error, /0 of 9 calibration probes/);
+});
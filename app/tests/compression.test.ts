@@ -0,0 +1,61 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import * as http from 'http';
+import * as zlib from 'zlib';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { negotiateEncoding } from '../src/main/services/compression';
+
+let server: TestServer;
+
+before(async () => {
+  // Enough simulators for the list to be worth compressing
+  server = await startTestServer({
+    devices: Array.from({ length: 30 }, (_, index) => ({ udid: `PHONE-${index}`, name: `iPhone 16 (${index})` })),
+  });
+});
+
+after(async () => {
+  await server.close();
+});
+
+// Fetch `path` without decompressing it, which fetch would do on its own
+function getRaw(path: string, acceptEncoding: string): Promise<{ headers: http.IncomingHttpHeaders; body: Buffer }> {
+  return new Promise((resolve, reject) => {
+    http
+      .get(`${server.baseUrl}${path}`, { headers: { 'Accept-Encoding': acceptEncoding } }, (res) => {
+        const chunks: Buffer[] = [];
+        res.on('data', (chunk) => chunks.push(chunk));
+        res.on('end', () => resolve({ headers: res.headers, body: Buffer.concat(chunks) }));
+      })
+      .on('error', reject);
+  });
+}
+
+test('prefers gzip and honors q=0', () => {
+  assert.equal(negotiateEncoding('gzip, deflate, br'), 'gzip');
+  assert.equal(negotiateEncoding('gzip;q=0, deflate'), 'deflate');
+  assert.equal(negotiateEncoding('br'), null);
+  assert.equal(negotiateEncoding(undefined), null);
+});
+
+test('compresses large JSON responses for clients that accept it', async () => {
+  const gzipped = await getRaw('/api/simulator/list', 'gzip');
+  assert.equal(gzipped.headers['content-encoding'], 'gzip');
+  assert.equal(gzipped.headers['content-length'], undefined);
+  assert.equal(JSON.parse(zlib.gunzipSync(gzipped.body).toString()).length, 30);
+
+  const deflated = await getRaw('/api/simulator/list', 'deflate');
+  assert.equal(deflated.headers['content-encoding'], 'deflate');
+  assert.equal(JSON.parse(zlib.inflateSync(deflated.body).toString()).length, 30);
+
+  const plain = await getRaw('/api/simulator/list', 'identity');
+  assert.equal(plain.headers['content-encoding'], undefined);
+  assert.equal(JSON.parse(plain.body.toString()).length, 30);
+});
+
+test('sends small responses uncompressed', async () => {
+  const response = await getRaw('/api/simulator/PHONE-0/runtime-issues', 'gzip');
+
+  assert.equal(response.headers['content-encoding'], undefined);
+  assert.deepEqual(JSON.parse(response.body.toString()), []);
+});
@@ -0,0 +1,90 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({ devices: [{ udid: 'PHONE', name: 'iPhone 16' }] });
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('applies a condition profile to a simulator', async () => {
+  const response = await server.request('POST', '/api/simulator/PHONE/conditions', { profile: 'worst-case' });
+
+  assert.equal(response.status, 200);
+  assert.equal(response.body.profile, 'worst-case');
+  assert.deepEqual(response.body.conditions, {
+    network: '3g',
+    thermalState: 'critical',
+    lowPower: true,
+    reduceMotion: true,
+    reduceTransparency: true,
+  });
+  assert.equal(response.body.effects.thermalState, 'unsupported');
+  assert.deepEqual(server.devices.calls.slice(-3), [
+    { kind: 'simctl', args: ['spawn', 'PHONE', 'defaults', 'write', 'com.apple.Accessibility', 'ReduceMotionEnabled', '-bool', 'true'] },
+    {
+      kind: 'simctl',
+      args: ['spawn', 'PHONE', 'defaults', 'write', 'com.apple.Accessibility', 'EnhancedBackgroundContrastEnabled', '-bool', 'true'],
+    },
+    {
+      kind: 'simctl',
+      args: [
+        'status_bar',
+        'PHONE',
+        'override',
+        '--dataNetwork',
+        '3g',
+        '--cellularMode',
+        'active',
+        '--cellularBars',
+        '1',
+        '--batteryState',
+        'discharging',
+        '--batteryLevel',
+        '10',
+      ],
+    },
+  ]);
+
+  const session = await server.request('GET', '/api/simulator/session/PHONE');
+  assert.equal(session.body.conditions.profile, 'worst-case');
+});
+
+test('replaces conditions of a profile and resets to the default', async () => {
+  const response = await server.request('POST', '/api/simulator/PHONE/conditions', {
+    profile: 'low-power',
+    conditions: { reduceMotion: true },
+  });
+  assert.equal(response.body.conditions.lowPower, true);
+  assert.equal(response.body.conditions.reduceMotion, true);
+
+  const reset = await server.request('POST', '/api/simulator/PHONE/conditions', { profile: 'default' });
+  assert.equal(reset.body.conditions.reduceMotion, false);
+  assert.deepEqual(server.devices.calls.at(-1), { kind: 'simctl', args: ['status_bar', 'PHONE', 'clear'] });
+
+  const current = await server.request('GET', '/api/simulator/PHONE/conditions');
+  assert.equal(current.body.profile, 'default');
+});
+
+test('rejects unknown profiles and conditions', async () => {
+  const empty = await server.request('POST', '/api/simulator/PHONE/conditions', {});
+  assert.equal(empty.status, 400);
+
+  const profile = await server.request('POST', '/api/simulator/PHONE/conditions', { profile: 'melting' });
+  assert.equal(profile.status, 400);
+
+  const network = await server.request('POST', '/api/simulator/PHONE/conditions', { conditions: { network: '2g' } });
+  assert.equal(network.status, 400);
+});
+
+test('lists the condition profiles', async () => {
+  const response = await server.request('GET', '/api/simulator/conditions/profiles');
+
+  assert.equal(response.status, 200);
+  assert.ok(response.body.some((profile: { id: string }) => profile.id === 'worst-case'));
+});
@@ -0,0 +1,85 @@
+import { test, before, after, afterEach } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import {
+  createDataStore,
+  createSqliteDataStore,
+  DataStore,
+  setDataStore,
+  StoredDestination,
+} from '../src/main/services/data-store';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer();
+});
+
+afterEach(() => {
+  setDataStore(null);
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('keeps repositories in SQLite without a database URL', () => {
+  assert.equal(createDataStore({ url: null, busyTimeoutMs: 5000 }).backend, 'sqlite');
+});
+
+test('rejects writes based on a stale updatedAt', async () => {
+  const store = createSqliteDataStore();
+  const project = await store.projects.save('Stale', '/projects/Stale/Stale.xcodeproj', null);
+
+  assert.equal(await store.projectSettings.update(project, { scheme: 'App' }, null), true);
+  const first = await store.projectSettings.get(project);
+  assert.equal(await store.projectSettings.update(project, { configuration: 'Release' }, first!.updatedAt), true);
+  assert.equal(await store.projectSettings.update(project, { scheme: 'Other' }, first!.updatedAt), false);
+
+  const stored = await store.projectSettings.get(project);
+  assert.equal(stored!.scheme, 'App');
+  assert.equal(stored!.configuration, 'Release');
+});
+
+test('routes read and write destinations through the configured store', async () => {
+  const sqlite = createSqliteDataStore();
+  const project = await sqlite.projects.save('Shared', '/projects/Shared/Shared.xcodeproj', null);
+  // Keyed by UUID, like the Postgres store
+  const destinations = new Map<string, StoredDestination>();
+  const store: DataStore = {
+    ...sqlite,
+    backend: 'postgres',
+    projectDestinations: {
+      async get(key) {
+        return destinations.get(key.uuid) || null;
+      },
+      async set(key, preset) {
+        destinations.set(key.uuid, { preset, updatedAt: '2026-10-15T09:00:00.000Z' });
+        return true;
+      },
+    },
+  };
+  setDataStore(store);
+
+  const put = await server.request('PUT', `/api/projects/${project.id}/destination`, { destination: 'macos' });
+  assert.equal(put.status, 200);
+  assert.deepEqual(put.body, { destination: 'macos', updatedAt: '2026-10-15T09:00:00.000Z' });
+  assert.equal(destinations.get(project.uuid)?.preset, 'macos');
+  // Nothing was written to SQLite
+  assert.equal(await sqlite.projectDestinations.get(project), null);
+
+  const exported = await server.request('GET', '/api/admin/export');
+  const shared = exported.body.projects.find((candidate: { name: string }) => candidate.name === 'Shared');
+  assert.equal(shared.destination, 'macos');
+});
+
+test('gives every project a UUID and finds it by path', async () => {
+  const store = createSqliteDataStore();
+  const project = await store.projects.save('Unique', '/projects/Unique/Unique.xcodeproj', null);
+  const reopened = await store.projects.save('Renamed', '/projects/Unique/Unique.xcodeproj', null);
+
+  assert.match(project.uuid, /^[0-9a-f-]{36}$/);
+  assert.equal(reopened.id, project.id);
+  assert.equal(reopened.uuid, project.uuid);
+  assert.equal((await store.projects.findByPath('/projects/Unique/Unique.xcodeproj'))?.name, 'Renamed');
+});
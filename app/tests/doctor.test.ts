@@ -0,0 +1,21 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer();
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('reports configuration issues', async () => {
+  const response = await server.request('GET', '/api/doctor');
+
+  assert.equal(response.status, 200);
+  assert.equal(response.body.ok, true);
+  assert.ok(response.body.issues.every((issue: { severity: string }) => issue.severity === 'warning'));
+});
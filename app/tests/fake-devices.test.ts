@@ -0,0 +1,99 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import WebSocket from 'ws';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { createFakeDevicesBackend, renderFakeFrame } from '../src/main/services/fake-devices';
+
+const PNG_SIGNATURE = Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]);
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({ backend: createFakeDevicesBackend() });
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('renders deterministic PNG frames', () => {
+  const frame = renderFakeFrame('FAKE-IPHONE-16', 3, 40, 80);
+
+  assert.deepEqual(frame.subarray(0, 8), PNG_SIGNATURE);
+  assert.equal(frame.readUInt32BE(16), 40);
+  assert.equal(frame.readUInt32BE(20), 80);
+  assert.deepEqual(frame, renderFakeFrame('FAKE-IPHONE-16', 3, 40, 80));
+  assert.notDeepEqual(frame, renderFakeFrame('FAKE-IPHONE-16', 4, 40, 80));
+});
+
+test('lists fake simulators of every device class', async () => {
+  const response = await server.request('GET', '/api/simulator/list?sort=device_class');
+
+  assert.deepEqual(
+    response.body.map((simulator: { deviceClass: string }) => simulator.deviceClass),
+    ['phone', 'tablet', 'tv', 'vision', 'watch']
+  );
+});
+
+test('accepts input for fake simulators', async () => {
+  const response = await server.request('POST', '/api/simulator/tap', {
+    udid: 'FAKE-IPHONE-16',
+    x: 0.5,
+    y: 0.5,
+    screenWidth: 393,
+    screenHeight: 852,
+  });
+
+  assert.equal(response.status, 200);
+});
+
+test('streams generated frames over WebSocket', async () => {
+  const ws = new WebSocket(server.baseUrl.replace('http', 'ws'));
+  await new Promise((resolve) => ws.once('open', resolve));
+
+  const frame = new Promise<{ udid: string; frame: string; format: string }>((resolve) => {
+    ws.on('message', (data) => {
+      const message = JSON.parse(data.toString());
+      if (message.type === 'simulator:stream:frame') {
+        resolve(message.payload);
+      }
+    });
+  });
+  ws.send(JSON.stringify({ type: 'simulator:stream:start', payload: { udid: 'FAKE-IPHONE-16' } }));
+
+  const payload = await frame;
+  ws.close();
+  assert.equal(payload.udid, 'FAKE-IPHONE-16');
+  assert.equal(payload.format, 'png');
+  assert.deepEqual(Buffer.from(payload.frame, 'base64').subarray(0, 8), PNG_SIGNATURE);
+});
+
+test('streams resource samples of the launched app', async () => {
+  const missing = await server.request('GET', '/api/simulator/FAKE-IPAD-AIR/resources/stream');
+  assert.equal(missing.status, 400);
+
+  await server.request('POST', '/api/simulator/launch', {
+    udid: 'FAKE-IPAD-AIR',
+    appPath: '/tmp/Plasma.app',
+    bundleId: 'dev.plasma.sample',
+  });
+
+  const controller = new AbortController();
+  const response = await fetch(`${server.baseUrl}/api/simulator/FAKE-IPAD-AIR/resources/stream?interval=250`, {
+    signal: controller.signal,
+  });
+  assert.equal(response.headers.get('content-type'), 'text/event-stream');
+
+  const reader = response.body!.getReader();
+  let received = '';
+  while (!received.includes('event: sample')) {
+    const { value } = await reader.read();
+    received += Buffer.from(value!).toString();
+  }
+  controller.abort();
+
+  const data = JSON.parse(/event: sample\ndata: (.*)\n/.exec(received)![1]);
+  assert.equal(data.bundleId, 'dev.plasma.sample');
+  assert.ok(data.cpuPercent > 0);
+  assert.ok(data.rssBytes > 100 * 1024 * 1024);
+});
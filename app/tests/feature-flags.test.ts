@@ -0,0 +1,46 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { isFeatureEnabled, setFeatureFlagDefaults } from '../src/main/services/feature-flags';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer();
+  setFeatureFlagDefaults({ android: true });
+});
+
+after(async () => {
+  setFeatureFlagDefaults({});
+  await server.close();
+});
+
+test('lists flags with their source', async () => {
+  const response = await server.request('GET', '/api/flags');
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(
+    response.body.map((flag: { name: string; enabled: boolean; source: string }) => [flag.name, flag.enabled, flag.source]),
+    [
+      ['webrtc_streaming', false, 'default'],
+      ['android', true, 'config'],
+      ['proxy', false, 'default'],
+    ]
+  );
+});
+
+test('overrides the config until the override is cleared', async () => {
+  const disabled = await server.request('PUT', '/api/flags/android', { enabled: false });
+  assert.equal(disabled.status, 200);
+  assert.equal(disabled.body.source, 'setting');
+  assert.equal(await isFeatureEnabled('android'), false);
+
+  const cleared = await server.request('PUT', '/api/flags/android', { enabled: null });
+  assert.equal(cleared.body.source, 'config');
+  assert.equal(await isFeatureEnabled('android'), true);
+});
+
+test('rejects unknown flags and invalid values', async () => {
+  assert.equal((await server.request('PUT', '/api/flags/teleport', { enabled: true })).status, 404);
+  assert.equal((await server.request('PUT', '/api/flags/proxy', { enabled: 'yes' })).status, 400);
+});
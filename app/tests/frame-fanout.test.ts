@@ -0,0 +1,117 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import WebSocket from 'ws';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { createFakeDevicesBackend } from '../src/main/services/fake-devices';
+import { FrameFanout, FrameUpstream } from '../src/main/services/frame-fanout';
+import type { BackendFrame } from '../src/main/services/simulator';
+
+const frame: BackendFrame = { frame: Buffer.from('frame'), format: 'jpeg' };
+
+let server: TestServer;
+const upstreams: string[] = [];
+
+before(async () => {
+  const backend = createFakeDevicesBackend();
+  server = await startTestServer({
+    backend: {
+      ...backend,
+      streamFrames(udid, fps, onFrame) {
+        upstreams.push(udid);
+        return backend.streamFrames!(udid, fps, onFrame);
+      },
+    },
+  });
+});
+
+after(async () => {
+  await server.close();
+});
+
+function fakeUpstream() {
+  const started: { fps: number; signal: AbortSignal; onFrame: (frame: BackendFrame) => void; onEnd: () => void }[] = [];
+  const upstream: FrameUpstream = (fps, signal, onFrame, onEnd) => started.push({ fps, signal, onFrame, onEnd });
+  return { started, upstream };
+}
+
+test('shares one upstream between viewers and stops it with the last one', () => {
+  const { started, upstream } = fakeUpstream();
+  let idle = false;
+  const fanout = new FrameFanout(upstream, () => (idle = true));
+  const received: string[] = [];
+
+  fanout.add('a', { fps: 30, onFrame: () => received.push('a') });
+  fanout.add('b', { fps: 30, onFrame: () => received.push('b') });
+  started[0].onFrame(frame);
+
+  assert.equal(started.length, 1);
+  assert.deepEqual(received, ['a', 'b']);
+
+  fanout.remove('a');
+  assert.equal(started[0].signal.aborted, false);
+  fanout.remove('b');
+  assert.equal(started[0].signal.aborted, true);
+  assert.equal(idle, true);
+});
+
+test('skips frames for lagging viewers only', () => {
+  const { started, upstream } = fakeUpstream();
+  const fanout = new FrameFanout(upstream, () => {});
+  const received: string[] = [];
+  let skipped = 0;
+
+  fanout.add('slow', { fps: 30, onFrame: () => received.push('slow'), isLagging: () => true, onSkip: () => skipped++ });
+  fanout.add('fast', { fps: 30, onFrame: () => received.push('fast') });
+  started[0].onFrame(frame);
+  started[0].onFrame(frame);
+
+  assert.deepEqual(received, ['fast', 'fast']);
+  assert.equal(skipped, 2);
+});
+
+test('restarts the upstream for a viewer asking for more frames', () => {
+  const { started, upstream } = fakeUpstream();
+  const fanout = new FrameFanout(upstream, () => {});
+  const ended: string[] = [];
+
+  fanout.add('thumbnail', { fps: 1, onFrame: () => {}, onEnd: () => ended.push('thumbnail') });
+  fanout.add('live', { fps: 30, onFrame: () => {}, onEnd: () => ended.push('live') });
+
+  assert.deepEqual(started.map((start) => start.fps), [1, 30]);
+  assert.equal(started[0].signal.aborted, true);
+  // The replaced upstream ending doesn't end the stream
+  started[0].onEnd();
+  assert.deepEqual(ended, []);
+
+  started[1].onEnd();
+  assert.deepEqual(ended, ['thumbnail', 'live']);
+  assert.equal(fanout.size, 0);
+});
+
+async function watch(udid: string): Promise<{ ws: WebSocket; firstFrame: Promise<void> }> {
+  const ws = new WebSocket(server.baseUrl.replace('http', 'ws'));
+  await new Promise((resolve) => ws.once('open', resolve));
+  const firstFrame = new Promise<void>((resolve) => {
+    ws.on('message', (data) => {
+      if (JSON.parse(data.toString()).type === 'simulator:stream:frame') {
+        resolve();
+      }
+    });
+  });
+  ws.send(JSON.stringify({ type: 'simulator:stream:start', payload: { udid } }));
+  return { ws, firstFrame };
+}
+
+test('streams a simulator to several clients from one upstream', async () => {
+  const first = await watch('FAKE-IPHONE-16');
+  await first.firstFrame;
+  const second = await watch('FAKE-IPHONE-16');
+
+  // The first client keeps its frames when the second one joins
+  const more = new Promise<void>((resolve) => first.ws.once('message', () => resolve()));
+  await Promise.all([second.firstFrame, more]);
+  first.ws.close();
+  second.ws.close();
+
+  assert.deepEqual(upstreams, ['FAKE-IPHONE-16']);
+});
@@ -0,0 +1,29 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { resetFramePacing, recordPacingFrame, getFramePacing } from '../src/main/services/frame-pacing';
+
+test('measures the rate of changed frames and counts late ones as hitches', () => {
+  resetFramePacing('SIM-1', 60);
+  const start = Date.now();
+  const frame = (n: number) => Buffer.from(`frame ${n}`);
+
+  // 10 frames at 60 fps, a frame 50 ms late, then a repeated frame that doesn't count
+  for (let i = 0; i < 10; i++) {
+    recordPacingFrame('SIM-1', frame(i), start + Math.round(i * 16.7));
+  }
+  recordPacingFrame('SIM-1', frame(10), start + 150 + 50);
+  recordPacingFrame('SIM-1', frame(10), start + 217);
+  // After an idle second, the next change starts a new animation instead of counting as a hitch
+  recordPacingFrame('SIM-1', frame(11), start + 1500);
+
+  const pacing = getFramePacing('SIM-1')!;
+  assert.equal(pacing.changedFrames, 12);
+  assert.equal(pacing.hitches, 1);
+  assert.equal(pacing.worstIntervalMs, 50);
+  assert.ok(pacing.fps! > 40 && pacing.fps! < 60);
+  assert.ok(pacing.hitchTimeRatio! > 0);
+
+  resetFramePacing('SIM-1');
+  assert.equal(getFramePacing('SIM-1')!.changedFrames, 0);
+  assert.equal(getFramePacing('SIM-1')!.targetFps, 60);
+});
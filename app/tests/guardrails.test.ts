@@ -0,0 +1,63 @@
+import { test, before, after, afterEach } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { checkHostResources, setHostResourceReader, setResourceGuards } from '../src/main/services/guardrails';
+
+const LOADED_HOST = {
+  freeMemoryBytes: 512 * 1024 * 1024,
+  totalMemoryBytes: 16 * 1024 * 1024 * 1024,
+  loadAverage: 9,
+  cpuCount: 8,
+  loadPercent: 113,
+};
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({
+    devices: [
+      { udid: 'PHONE', name: 'iPhone 16', deviceType: 'com.apple.CoreSimulator.SimDeviceType.iPhone-16' },
+      { udid: 'TABLET', name: 'iPad Air', state: 'Shutdown', deviceType: 'com.apple.CoreSimulator.SimDeviceType.iPad-Air-11-inch-M2' },
+    ],
+  });
+});
+
+after(async () => {
+  await server.close();
+});
+
+afterEach(() => {
+  setResourceGuards({ minFreeMemoryMb: 0, maxLoadPercent: 0 });
+  setHostResourceReader(null);
+});
+
+test('allows everything when no guard is set', () => {
+  setHostResourceReader(() => LOADED_HOST);
+  assert.equal(checkHostResources('build'), null);
+});
+
+test('reports every exceeded guard with the readings', () => {
+  setResourceGuards({ minFreeMemoryMb: 1024, maxLoadPercent: 100 });
+  setHostResourceReader(() => LOADED_HOST);
+
+  const exhausted = checkHostResources('build');
+  assert.equal(exhausted?.code, 'resources_exhausted');
+  assert.deepEqual(exhausted?.reasons, [
+    'Free memory is 512 MB, below the 1024 MB minimum',
+    'CPU load is 113%, above the 100% maximum',
+  ]);
+  assert.deepEqual(exhausted?.readings, LOADED_HOST);
+});
+
+test('refuses to boot a simulator on a loaded host', async () => {
+  setResourceGuards({ minFreeMemoryMb: 1024, maxLoadPercent: 0 });
+  setHostResourceReader(() => LOADED_HOST);
+
+  const refused = await server.request('POST', '/api/simulator/launch', { udid: 'TABLET', appPath: '/tmp/App.app' });
+  assert.equal(refused.status, 503);
+  assert.equal(refused.body.action, 'boot');
+
+  // Already booted, so nothing new would start
+  const launched = await server.request('POST', '/api/simulator/launch', { udid: 'PHONE', appPath: '/tmp/App.app', bundleId: 'dev.plasma.app' });
+  assert.equal(launched.status, 200);
+});
@@ -0,0 +1,53 @@
+import { test, afterEach } from 'node:test';
+import assert from 'node:assert/strict';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import {
+  configureHelperLookup,
+  findHelperBinary,
+  describeHelperLookup,
+  DEFAULT_HELPER_ORDER,
+} from '../src/main/services/helper-binaries';
+
+function libexecWith(...names: string[]): string {
+  const dir = path.join(fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-prefix-')), 'libexec', 'plasma');
+  fs.mkdirSync(dir, { recursive: true });
+  for (const name of names) {
+    fs.writeFileSync(path.join(dir, name), '#!/bin/sh\n', { mode: 0o755 });
+  }
+  return dir;
+}
+
+afterEach(() => {
+  delete process.env.AXE_BINARY;
+  configureHelperLookup({ layout: 'dev', order: DEFAULT_HELPER_ORDER.dev, libexecDir: null });
+});
+
+test('finds helpers in libexec', () => {
+  const libexecDir = libexecWith('simulator-server', 'axe');
+  configureHelperLookup({ layout: 'libexec', order: DEFAULT_HELPER_ORDER.libexec, libexecDir });
+
+  assert.deepEqual(findHelperBinary('simulator-server'), { path: path.join(libexecDir, 'simulator-server'), source: 'libexec' });
+  assert.deepEqual(findHelperBinary('axe'), { path: path.join(libexecDir, 'axe'), source: 'libexec' });
+});
+
+test('looks in the configured order', () => {
+  const libexecDir = libexecWith('axe');
+  const override = path.join(libexecWith('axe'), 'axe');
+  process.env.AXE_BINARY = override;
+
+  configureHelperLookup({ layout: 'libexec', order: ['env', 'libexec'], libexecDir });
+  assert.equal(findHelperBinary('axe')?.source, 'env');
+
+  configureHelperLookup({ layout: 'libexec', order: ['libexec', 'env'], libexecDir });
+  assert.equal(findHelperBinary('axe')?.source, 'libexec');
+});
+
+test('reports where a missing helper was looked for', () => {
+  const libexecDir = libexecWith();
+  configureHelperLookup({ layout: 'libexec', order: ['env', 'libexec'], libexecDir });
+
+  assert.equal(findHelperBinary('simulator-server'), null);
+  assert.equal(describeHelperLookup('simulator-server'), `$SIMULATOR_SERVER, ${libexecDir}`);
+});
@@ -0,0 +1,101 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { createFakeDeviceBackend, startTestServer, TestServer } from '../src/main/testing';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({ devices: [{ udid: 'PHONE', name: 'iPhone 16' }] });
+});
+
+after(async () => {
+  await server.close();
+});
+
+const appearanceCalls = () => server.devices.calls.filter((call) => call.kind === 'simctl' && call.args[0] === 'ui').length;
+
+test('replays the response of a retried request instead of running it again', async () => {
+  const before = appearanceCalls();
+  const headers = { 'Idempotency-Key': 'appearance-1' };
+
+  const first = await server.request('PUT', '/api/simulator/PHONE/appearance', { appearance: 'dark' }, headers);
+  const retry = await server.request('PUT', '/api/simulator/PHONE/appearance', { appearance: 'dark' }, headers);
+
+  assert.equal(first.status, 200);
+  assert.equal(first.headers.get('idempotent-replayed'), null);
+  assert.equal(retry.status, 200);
+  assert.deepEqual(retry.body, first.body);
+  assert.equal(retry.headers.get('idempotent-replayed'), 'true');
+  assert.equal(appearanceCalls(), before + 1);
+});
+
+test('runs requests with different keys or without a key every time', async () => {
+  const before = appearanceCalls();
+
+  await server.request('PUT', '/api/simulator/PHONE/appearance', { appearance: 'light' }, { 'Idempotency-Key': 'appearance-2' });
+  await server.request('PUT', '/api/simulator/PHONE/appearance', { appearance: 'light' }, { 'Idempotency-Key': 'appearance-3' });
+  await server.request('PUT', '/api/simulator/PHONE/appearance', { appearance: 'light' });
+
+  assert.equal(appearanceCalls(), before + 3);
+});
+
+test('rejects a key reused for another endpoint', async () => {
+  await server.request('PUT', '/api/simulator/PHONE/appearance', { appearance: 'dark' }, { 'Idempotency-Key': 'shared' });
+
+  const response = await server.request('DELETE', '/api/simulator/PHONE/status-bar', undefined, { 'Idempotency-Key': 'shared' });
+
+  assert.equal(response.status, 422);
+  assert.equal(response.body.error, 'This Idempotency-Key was used for PUT /api/simulator/PHONE/appearance');
+});
+
+test('keeps the keys of different clients apart', async () => {
+  const before = appearanceCalls();
+
+  const first = await server.request('PUT', '/api/simulator/PHONE/appearance', { appearance: 'dark' }, { 'Idempotency-Key': 'retry-1', Authorization: 'Bearer first' });
+  const second = await server.request('PUT', '/api/simulator/PHONE/appearance', { appearance: 'dark' }, { 'Idempotency-Key': 'retry-1', Authorization: 'Bearer second' });
+
+  assert.equal(first.headers.get('idempotent-replayed'), null);
+  assert.equal(second.headers.get('idempotent-replayed'), null);
+  assert.equal(appearanceCalls(), before + 2);
+});
+
+test('rejects a key reused with another body', async () => {
+  const headers = { 'Idempotency-Key': 'appearance-body' };
+  await server.request('PUT', '/api/simulator/PHONE/appearance', { appearance: 'dark' }, headers);
+
+  const response = await server.request('PUT', '/api/simulator/PHONE/appearance', { appearance: 'light' }, headers);
+
+  assert.equal(response.status, 422);
+  assert.equal(response.body.error, 'This Idempotency-Key was used with a different request body');
+});
+
+test('runs the operation once more when retries were waiting for a request that failed', async () => {
+  const fake = createFakeDeviceBackend([{ udid: 'PHONE', name: 'iPhone 16' }]);
+  let attempts = 0;
+  const failing = await startTestServer({
+    backend: {
+      ...fake,
+      async simctl(args) {
+        attempts++;
+        // The first attempt fails once the retries are waiting for it
+        await new Promise((resolve) => setTimeout(resolve, 50));
+        if (attempts === 1) {
+          throw new Error('simctl crashed');
+        }
+        return fake.simctl!(args);
+      },
+    },
+  });
+  try {
+    const headers = { 'Idempotency-Key': 'appearance-retry' };
+    const responses = await Promise.all(
+      [0, 1, 2].map(() => failing.request('PUT', '/api/simulator/PHONE/appearance', { appearance: 'dark' }, headers))
+    );
+
+    assert.deepEqual(responses.map((response) => response.status).sort(), [200, 200, 500]);
+    assert.equal(responses.filter((response) => response.headers.get('idempotent-replayed') === 'true').length, 1);
+    assert.equal(attempts, 2);
+  } finally {
+    await failing.close();
+  }
+});
@@ -0,0 +1,93 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { closeDatabase, setSetting } from '../src/main/services/database';
+import { getDataStore } from '../src/main/services/data-store';
+import { createProjectNotifier, getNotifierWebhookUrl, listProjectNotifiers } from '../src/main/services/notifiers';
+import { configureSecrets } from '../src/main/services/secrets';
+
+let server: TestServer;
+
+const WEBHOOK_URL = 'https://hooks.slack.com/services/T000/B000/secret';
+
+before(async () => {
+  server = await startTestServer();
+  configureSecrets({ passphrase: 'correct horse', keychain: false });
+  const store = getDataStore();
+  const project = await store.projects.save('Plasma', '/projects/Plasma/Plasma.xcodeproj', null);
+  await store.projectDestinations.set(project, 'visionos-simulator');
+  await store.projectSettings.update(project, { scheme: 'Plasma', configuration: 'Release', environment: { API_TOKEN: 'secret-token' } });
+  await createProjectNotifier(project, { kind: 'slack', webhookUrl: WEBHOOK_URL });
+  setSetting('onboarding.dismissed_at', '2026-10-01T09:00:00.000Z');
+  setSetting('analytics.enabled_at', '2026-10-01T09:00:00.000Z');
+});
+
+after(async () => {
+  configureSecrets({ passphrase: null, keychain: false });
+  await server.close();
+});
+
+test('exports projects and settings without webhook URLs', async () => {
+  const response = await server.request('GET', '/api/admin/export');
+
+  assert.equal(response.status, 200);
+  const [project] = response.body.projects;
+  assert.equal(project.xcodePath, '/projects/Plasma/Plasma.xcodeproj');
+  assert.equal(project.destination, 'visionos-simulator');
+  assert.equal(project.settings.scheme, 'Plasma');
+  assert.equal(project.settings.configuration, 'Release');
+  assert.equal(project.settings.environment, null);
+  assert.equal(project.settings.encryptedEnvironment, null);
+  assert.equal(project.notifiers[0].webhookUrl, null);
+  assert.equal(project.notifiers[0].encryptedWebhookUrl, null);
+  assert.deepEqual(response.body.settings, [{ key: 'onboarding.dismissed_at', value: '2026-10-01T09:00:00.000Z' }]);
+  assert.ok(!JSON.stringify(response.body).includes('secret'));
+});
+
+test('imports an encrypted export on a fresh instance', async () => {
+  const headers = { 'X-Plasma-Export-Passphrase': 'correct horse' };
+  const exported = await server.request('GET', '/api/admin/export', undefined, headers);
+  assert.ok(!JSON.stringify(exported.body).includes('secret'));
+
+  // An in-memory database starts empty again, like a second machine
+  closeDatabase();
+
+  const wrong = await server.request('POST', '/api/admin/import', exported.body, { 'X-Plasma-Export-Passphrase': 'wrong' });
+  assert.equal(wrong.status, 400);
+
+  const imported = await server.request('POST', '/api/admin/import', exported.body, headers);
+  assert.equal(imported.status, 200);
+  assert.deepEqual(imported.body, { projects: 1, notifiers: 1, settings: 1, skipped: [] });
+
+  const again = await server.request('POST', '/api/admin/import', exported.body, headers);
+  assert.equal(again.body.notifiers, 0);
+
+  const project = (await server.request('GET', '/api/admin/export')).body.projects[0];
+  assert.equal(project.destination, 'visionos-simulator');
+  const settings = await server.request('GET', '/api/projects/1/settings');
+  assert.equal(settings.body.configuration, 'Release');
+  assert.deepEqual(settings.body.environment, { API_TOKEN: 'secret-token' });
+  const stored = await getDataStore().projects.get(1);
+  assert.deepEqual(await Promise.all((await listProjectNotifiers(stored!)).map(getNotifierWebhookUrl)), [WEBHOOK_URL]);
+});
+
+test('skips notifiers exported without their webhook URL', async () => {
+  const exported = await server.request('GET', '/api/admin/export');
+  closeDatabase();
+
+  const imported = await server.request('POST', '/api/admin/import', exported.body);
+  assert.equal(imported.body.projects, 1);
+  assert.equal(imported.body.notifiers, 0);
+  assert.deepEqual(imported.body.skipped, [
+    { item: 'slack notifier of Plasma', reason: 'exported without its webhook URL' },
+  ]);
+  // The scheme and configuration came across, the environment variables didn't
+  const settings = await server.request('GET', '/api/projects/1/settings');
+  assert.equal(settings.body.scheme, 'Plasma');
+  assert.deepEqual(settings.body.environment, {});
+});
+
+test('rejects bundles that are not exports', async () => {
+  const response = await server.request('POST', '/api/admin/import', { projects: [] });
+  assert.equal(response.status, 400);
+});
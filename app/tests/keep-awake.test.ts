@@ -0,0 +1,16 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { holdKeepAwake, releaseKeepAwake, getKeepAwakeHolders } from '../src/main/services/keep-awake';
+
+test('keeps the host awake until the last holder releases it', () => {
+  holdKeepAwake('stream:PHONE');
+  holdKeepAwake('stream:PHONE');
+  holdKeepAwake('stream:TV');
+  assert.deepEqual(getKeepAwakeHolders(), ['stream:PHONE', 'stream:TV']);
+
+  releaseKeepAwake('stream:PHONE');
+  assert.deepEqual(getKeepAwakeHolders(), ['stream:TV']);
+
+  releaseKeepAwake('stream:TV');
+  assert.deepEqual(getKeepAwakeHolders(), []);
+});
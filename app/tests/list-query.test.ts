@@ -0,0 +1,44 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { applyListQuery, parseListQuery } from '../src/main/list-query';
+
+const items = Array.from({ length: 250 }, (_, index) => ({ index }));
+
+test('returns whole lists without a limit or cursor', () => {
+  const page = applyListQuery(items, parseListQuery(new URLSearchParams()));
+
+  assert.equal(page.items.length, 250);
+  assert.equal(page.nextCursor, null);
+});
+
+test('pages with a cursor and no limit', () => {
+  const first = applyListQuery(items, parseListQuery(new URLSearchParams('limit=100')));
+  const second = applyListQuery(items, parseListQuery(new URLSearchParams({ cursor: first.nextCursor! })));
+
+  assert.equal(second.items.length, 100);
+  assert.equal(second.items[0].index, 100);
+  assert.ok(second.nextCursor);
+});
+
+test('keeps the default limit of an endpoint', () => {
+  const page = applyListQuery(items, parseListQuery(new URLSearchParams(), { defaultLimit: 10 }));
+
+  assert.equal(page.items.length, 10);
+  assert.equal(page.total, 250);
+});
+
+test('takes sort and filter fields in snake_case', () => {
+  const builds = [
+    { scheme: 'App', startedAt: '2026-10-02', deviceClass: 'phone' },
+    { scheme: 'App', startedAt: '2026-10-01', deviceClass: 'tablet' },
+  ];
+  const options = { sortable: ['startedAt' as const], filterable: ['deviceClass' as const] };
+
+  const sorted = applyListQuery(builds, parseListQuery(new URLSearchParams('sort=started_at'), options));
+  assert.deepEqual(sorted.items.map((build) => build.startedAt), ['2026-10-01', '2026-10-02']);
+
+  const filtered = applyListQuery(builds, parseListQuery(new URLSearchParams('device_class=tablet'), options));
+  assert.equal(filtered.total, 1);
+
+  assert.throws(() => parseListQuery(new URLSearchParams('sort=startedAt'), options), /Sortable fields: started_at/);
+});
@@ -0,0 +1,73 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { parseGpx } from '../src/main/services/location';
+
+let server: TestServer;
+
+const GPX = `<?xml version="1.0"?>
+<gpx version="1.1" creator="Plasma">
+  <wpt lat="0" lon="0"><name>Ignored with a track</name></wpt>
+  <trk><trkseg>
+    <trkpt lat="37.3349" lon="-122.0090"><ele>20</ele></trkpt>
+    <trkpt lon="-122.0080" lat='37.3359'/>
+    <trkpt lat="37.3369" lon="-122.0070"></trkpt>
+  </trkseg></trk>
+</gpx>`;
+
+before(async () => {
+  server = await startTestServer({ devices: [{ udid: 'PHONE', name: 'iPhone 16' }] });
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('reads the track points of GPX files', () => {
+  assert.deepEqual(parseGpx(GPX), [
+    { latitude: 37.3349, longitude: -122.009 },
+    { latitude: 37.3359, longitude: -122.008 },
+    { latitude: 37.3369, longitude: -122.007 },
+  ]);
+  assert.deepEqual(parseGpx('<gpx><wpt lat="1.5" lon="2"/><wpt lat="3" lon="4"/></gpx>'), [
+    { latitude: 1.5, longitude: 2 },
+    { latitude: 3, longitude: 4 },
+  ]);
+});
+
+test('sets a fixed location', async () => {
+  const response = await server.request('POST', '/api/simulator/location', { udid: 'PHONE', latitude: 48.8584, longitude: 2.2945 });
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(server.devices.calls.at(-1), { kind: 'simctl', args: ['location', 'PHONE', 'set', '48.8584,2.2945'] });
+
+  const invalid = await server.request('POST', '/api/simulator/location', { udid: 'PHONE', latitude: 91, longitude: 0 });
+  assert.equal(invalid.status, 400);
+});
+
+test('replays GPX routes at a speed', async () => {
+  const response = await server.request('POST', '/api/simulator/location/route', { udid: 'PHONE', gpx: GPX, speed: 10 });
+
+  assert.equal(response.status, 200);
+  assert.equal(response.body.waypoints, 3);
+  assert.ok(response.body.distanceMeters > 250 && response.body.distanceMeters < 300);
+  assert.equal(response.body.durationMs, response.body.distanceMeters * 100);
+  assert.deepEqual(server.devices.calls.at(-1), {
+    kind: 'simctl',
+    args: ['location', 'PHONE', 'start', '--speed=10', '37.3349,-122.009', '37.3359,-122.008', '37.3369,-122.007'],
+  });
+});
+
+test('rejects routes without enough points', async () => {
+  const response = await server.request('POST', '/api/simulator/location/route', { udid: 'PHONE', gpx: '<gpx><trkpt lat="1" lon="2"/></gpx>' });
+
+  assert.equal(response.status, 400);
+  assert.equal(response.body.error, 'The GPX file must have 2 to 2000 points, it has 1');
+});
+
+test('clears the simulated location', async () => {
+  const response = await server.request('DELETE', '/api/simulator/location?udid=PHONE');
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(server.devices.calls.at(-1), { kind: 'simctl', args: ['location', 'PHONE', 'clear'] });
+});
@@ -0,0 +1,37 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { isNoise, parseNoiseProfiles, DEFAULT_NOISE_PROFILES } from '../src/main/services/log-noise';
+import type { SyslogEntry } from '../src/shared/ipc-types';
+
+const entry = (overrides: Partial<SyslogEntry>): SyslogEntry => ({
+  udid: 'SIM-1',
+  timestamp: new Date().toISOString(),
+  process: 'Plasma',
+  pid: 42,
+  subsystem: null,
+  category: null,
+  level: 'default',
+  message: 'hello',
+  ...overrides,
+});
+
+test('drops daemon and framework chatter with the default profiles', () => {
+  assert.equal(isNoise(entry({ process: 'SpringBoard' }), DEFAULT_NOISE_PROFILES), true);
+  assert.equal(isNoise(entry({ subsystem: 'com.apple.network.connection' }), DEFAULT_NOISE_PROFILES), true);
+  assert.equal(isNoise(entry({ subsystem: 'dev.plasma.app' }), DEFAULT_NOISE_PROFILES), false);
+  assert.equal(isNoise(entry({ subsystem: 'com.apple.networking' }), DEFAULT_NOISE_PROFILES), false);
+});
+
+test('drops Apple debug output but keeps errors', () => {
+  const profiles = parseNoiseProfiles('apple-debug');
+  assert.equal(isNoise(entry({ subsystem: 'com.apple.UIKit', level: 'debug' }), profiles), true);
+  assert.equal(isNoise(entry({ subsystem: 'com.apple.UIKit', level: 'error' }), profiles), false);
+  assert.equal(isNoise(entry({ subsystem: 'dev.plasma.app', level: 'debug' }), profiles), false);
+});
+
+test('parses the noise toggle of a subscription', () => {
+  assert.deepEqual(parseNoiseProfiles(null), DEFAULT_NOISE_PROFILES);
+  assert.deepEqual(parseNoiseProfiles('none'), []);
+  assert.equal(parseNoiseProfiles('all').length, 3);
+  assert.throws(() => parseNoiseProfiles('system-daemons,chatter'), /Unknown noise profile: chatter/);
+});
@@ -0,0 +1,47 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer();
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('records latency and status per route pattern', async () => {
+  await server.request('GET', '/api/health');
+  await server.request('GET', '/api/projects/999/notifiers');
+  await server.request('GET', '/api/projects/998/notifiers');
+  await server.request('GET', '/api/does-not-exist/1234');
+
+  const response = await server.request('GET', '/api/metrics');
+  const text = response.body as string;
+
+  assert.match(text, /# TYPE plasma_http_request_duration_seconds histogram/);
+  assert.match(text, /plasma_http_request_duration_seconds_count\{method="GET",route="\/api\/health"\} 1/);
+  assert.match(text, /plasma_http_request_duration_seconds_bucket\{method="GET",route="\/api\/health",le="\+Inf"\} 1/);
+  assert.match(text, /plasma_http_requests_total\{method="GET",route="\/api\/health",status="200"\} 1/);
+
+  // Both project IDs land in one series
+  assert.match(text, /plasma_http_request_errors_total\{method="GET",route="\/api\/projects\/:id\/notifiers",status="404"\} 2/);
+  assert.doesNotMatch(text, /\/api\/projects\/999/);
+
+  assert.match(text, /plasma_http_request_errors_total\{method="GET",route="unmatched",status="404"\} 1/);
+  assert.doesNotMatch(text, /does-not-exist/);
+});
+
+test('histogram buckets are cumulative', async () => {
+  const response = await server.request('GET', '/api/metrics');
+  const counts = Array.from(
+    (response.body as string).matchAll(/plasma_http_request_duration_seconds_bucket\{method="GET",route="\/api\/health",le="[^"]+"\} (\d+)/g),
+    (match) => Number(match[1])
+  );
+
+  assert.equal(counts.length, 13);
+  assert.deepEqual([...counts].sort((a, b) => a - b), counts);
+  assert.equal(counts[counts.length - 1], 1);
+});
@@ -0,0 +1,41 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { recordNotification } from '../src/main/services/notifications';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer();
+  for (let index = 0; index < 1200; index++) {
+    await recordNotification({ kind: 'build_finished', level: index % 2 === 0 ? 'info' : 'error', title: `Build ${index + 1}` });
+  }
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('pages through every notification, however many there are', async () => {
+  const first = await server.request('GET', '/api/notifications?limit=1000');
+  assert.equal(first.headers.get('x-total-count'), '1200');
+  assert.equal(first.body.notifications.length, 1000);
+  assert.equal(first.body.notifications[0].title, 'Build 1200');
+
+  const cursor = first.headers.get('x-next-cursor')!;
+  const second = await server.request('GET', `/api/notifications?cursor=${cursor}`);
+  assert.equal(second.body.notifications.length, 100);
+  assert.equal(second.body.notifications[0].title, 'Build 200');
+
+  const last = await server.request('GET', `/api/notifications?cursor=${second.headers.get('x-next-cursor')}`);
+  assert.equal(last.body.notifications.length, 100);
+  assert.equal(last.body.notifications.at(-1).title, 'Build 1');
+  assert.equal(last.headers.get('x-next-cursor'), null);
+});
+
+test('filters and sorts notifications in the query', async () => {
+  const response = await server.request('GET', '/api/notifications?level=error&sort=created_at,id&limit=2');
+
+  assert.equal(response.headers.get('x-total-count'), '600');
+  assert.deepEqual(response.body.notifications.map((notification: { title: string }) => notification.title), ['Build 2', 'Build 4']);
+});
@@ -0,0 +1,86 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { getDatabase } from '../src/main/services/database';
+import { getDataStore } from '../src/main/services/data-store';
+import {
+  getNotifierWebhookUrl,
+  getProjectNotifier,
+  migrateNotifierWebhookUrls,
+  validateNotifierInput,
+} from '../src/main/services/notifiers';
+import { configureSecrets, getSecret } from '../src/main/services/secrets';
+
+const WEBHOOK_URL = 'https://hooks.slack.com/services/T000/B000/XXXX';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer();
+  configureSecrets({ passphrase: 'correct horse', keychain: false });
+});
+
+after(async () => {
+  configureSecrets({ passphrase: null, keychain: false });
+  await server.close();
+});
+
+test('rejects events that are not an array of known events', () => {
+  assert.equal(validateNotifierInput({ kind: 'slack', webhookUrl: WEBHOOK_URL, events: ['build_failed'] }), null);
+  assert.match(validateNotifierInput({ kind: 'slack', webhookUrl: WEBHOOK_URL, events: 'build_failed' as any })!, /must be an array/);
+  assert.match(validateNotifierInput({ kind: 'slack', webhookUrl: WEBHOOK_URL, events: { build_failed: true } as any })!, /must be an array/);
+  assert.equal(validateNotifierInput({ kind: 'slack', webhookUrl: WEBHOOK_URL, events: ['deployed' as any] }), 'Unknown events: deployed');
+});
+
+test('creating a notifier with malformed events is a bad request', async () => {
+  const project = await getDataStore().projects.save('Plasma', '/projects/Plasma/Plasma.xcodeproj', null);
+
+  const response = await server.request('POST', `/api/projects/${project.id}/notifiers`, {
+    kind: 'discord',
+    webhookUrl: 'https://discord.com/api/webhooks/1/abc',
+    events: 'build_failed',
+  });
+
+  assert.equal(response.status, 400);
+  assert.match(response.body.error, /events must be an array/);
+});
+
+test('keeps webhook URLs in the secrets store and never returns them', async () => {
+  const project = await getDataStore().projects.save('Plasma', '/projects/Plasma/Plasma.xcodeproj', null);
+
+  const response = await server.request('POST', `/api/projects/${project.id}/notifiers`, { kind: 'slack', webhookUrl: WEBHOOK_URL });
+  assert.equal(response.status, 201);
+  assert.ok(!JSON.stringify(response.body).includes('hooks.slack.com'));
+  assert.equal(await getSecret(response.body.webhook_secret), WEBHOOK_URL);
+
+  const row = getDatabase().prepare('SELECT webhook_url FROM project_notifiers WHERE id = ?').get(response.body.id) as { webhook_url: string };
+  assert.equal(row.webhook_url, '');
+
+  await server.request('DELETE', `/api/projects/${project.id}/notifiers/${response.body.id}`);
+  assert.equal(await getSecret(response.body.webhook_secret), null);
+});
+
+test('moves plaintext webhook URLs into the secrets store', async () => {
+  const project = await getDataStore().projects.save('Legacy', '/projects/Legacy/Legacy.xcodeproj', null);
+  const id = getDatabase().prepare(
+    "INSERT INTO project_notifiers (project_id, kind, webhook_url, created_at) VALUES (?, 'slack', ?, ?)"
+  ).run(project.id, WEBHOOK_URL, new Date().toISOString()).lastInsertRowid as number;
+
+  await migrateNotifierWebhookUrls();
+
+  const row = getDatabase().prepare('SELECT webhook_url FROM project_notifiers WHERE id = ?').get(id) as { webhook_url: string };
+  assert.equal(row.webhook_url, '');
+  assert.equal(await getNotifierWebhookUrl((await getProjectNotifier(project, id))!), WEBHOOK_URL);
+});
+
+test('doesn\'t add notifiers while secrets are locked', async () => {
+  const project = await getDataStore().projects.save('Locked', '/projects/Locked/Locked.xcodeproj', null);
+  configureSecrets({ passphrase: null, keychain: false });
+  try {
+    const response = await server.request('POST', `/api/projects/${project.id}/notifiers`, { kind: 'slack', webhookUrl: WEBHOOK_URL });
+    assert.equal(response.status, 503);
+    assert.deepEqual((await server.request('GET', `/api/projects/${project.id}/notifiers`)).body, []);
+  } finally {
+    configureSecrets({ passphrase: 'correct horse', keychain: false });
+  }
+});
@@ -0,0 +1,57 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { setTextRecognizer } from '../src/main/services/ocr';
+
+let server: TestServer;
+let recognized: { png: Buffer; level: string; languages: string[] } | null = null;
+
+before(async () => {
+  server = await startTestServer({
+    devices: [{ udid: 'PHONE', name: 'iPhone 16' }],
+  });
+  setTextRecognizer(async (png, level, languages) => {
+    recognized = { png, level, languages };
+    return [
+      { text: 'Sign in', confidence: 0.9, bounds: { x: 0.4, y: 0.8, width: 0.2, height: 0.04 } },
+      { text: 'Welcome back', confidence: 1, bounds: { x: 0.3, y: 0.1, width: 0.4, height: 0.05 } },
+    ];
+  });
+});
+
+after(async () => {
+  setTextRecognizer(null);
+  await server.close();
+});
+
+test('recognizes text on a screenshot, top to bottom', async () => {
+  const response = await server.request('POST', '/api/simulator/PHONE/ocr', { level: 'fast', languages: ['en-US'] });
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(
+    response.body.observations.map((observation: { text: string }) => observation.text),
+    ['Welcome back', 'Sign in']
+  );
+  assert.equal(response.body.found, undefined);
+  assert.equal(recognized?.level, 'fast');
+  assert.deepEqual(recognized?.languages, ['en-US']);
+  // The fake device's screenshot is a PNG
+  assert.equal(recognized?.png.toString('ascii', 1, 4), 'PNG');
+});
+
+test('finds text ignoring case', async () => {
+  const found = await server.request('POST', '/api/simulator/PHONE/ocr', { find: 'welcome' });
+  assert.equal(found.body.found, true);
+  assert.deepEqual(
+    found.body.matches.map((match: { text: string }) => match.text),
+    ['Welcome back']
+  );
+
+  const missing = await server.request('POST', '/api/simulator/PHONE/ocr', { find: 'Checkout' });
+  assert.equal(missing.body.found, false);
+});
+
+test('rejects unknown recognition levels', async () => {
+  const response = await server.request('POST', '/api/simulator/PHONE/ocr', { level: 'best' });
+  assert.equal(response.status, 400);
+});
@@ -0,0 +1,42 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer();
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('reports onboarding steps', async () => {
+  const response = await server.request('GET', '/api/onboarding');
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(
+    response.body.steps.map((step: { id: string }) => step.id),
+    ['xcode', 'simulator_runtime', 'helpers', 'first_project', 'first_build']
+  );
+  assert.equal(response.body.dismissed, false);
+});
+
+test('persists onboarding dismissal', async () => {
+  const dismissed = await server.request('PUT', '/api/onboarding', { dismissed: true });
+  assert.equal(dismissed.status, 200);
+  assert.equal(dismissed.body.dismissed, true);
+  assert.ok(dismissed.body.dismissedAt);
+
+  assert.equal((await server.request('GET', '/api/onboarding')).body.dismissed, true);
+
+  const restored = await server.request('PUT', '/api/onboarding', { dismissed: false });
+  assert.equal(restored.body.dismissed, false);
+  assert.equal(restored.body.dismissedAt, null);
+});
+
+test('rejects a non-boolean dismissal', async () => {
+  const response = await server.request('PUT', '/api/onboarding', { dismissed: 'yes' });
+  assert.equal(response.status, 400);
+});
@@ -0,0 +1,65 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import * as zlib from 'zlib';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { decodePng, pngChunk } from '../src/main/services/png';
+import { sampleColor } from '../src/main/services/pixel-color';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({
+    devices: [{ udid: 'PHONE', name: 'iPhone 16' }],
+  });
+});
+
+after(async () => {
+  await server.close();
+});
+
+// 2x2 RGBA image, with every row using a different filter so decoding exercises them
+function checkerboard(): Buffer {
+  const header = Buffer.alloc(13);
+  header.writeUInt32BE(2, 0);
+  header.writeUInt32BE(2, 4);
+  header[8] = 8;
+  header[9] = 6;
+  const rows = Buffer.from([
+    // None: red, green
+    0, 255, 0, 0, 255, 0, 255, 0, 255,
+    // Sub: blue, white (as differences from the pixel to the left)
+    1, 0, 0, 255, 128, 255, 255, 0, 127,
+  ]);
+  return Buffer.concat([
+    Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]),
+    pngChunk('IHDR', header),
+    pngChunk('IDAT', zlib.deflateSync(rows)),
+    pngChunk('IEND', Buffer.alloc(0)),
+  ]);
+}
+
+test('samples single pixels and averages regions', () => {
+  const image = decodePng(checkerboard());
+
+  assert.equal(sampleColor(image, 0, 0).hex, '#ff0000');
+  assert.equal(sampleColor(image, 0.9, 0).hex, '#00ff00');
+  assert.equal(sampleColor(image, 0, 0.9).a, 128);
+  assert.equal(sampleColor(image, 1, 1).hex, '#ffffff');
+
+  const average = sampleColor(image, 0, 0, 1);
+  assert.equal(average.samples, 4);
+  assert.deepEqual([average.r, average.g, average.b], [128, 128, 128]);
+});
+
+test('reads a pixel color from a screenshot', async () => {
+  const response = await server.request('GET', '/api/simulator/PHONE/pixel?x=0.5&y=0.99');
+
+  assert.equal(response.status, 200);
+  assert.match(response.body.hex, /^#[0-9a-f]{6}$/);
+  assert.equal(response.body.a, 255);
+});
+
+test('rejects points outside the screen', async () => {
+  const response = await server.request('GET', '/api/simulator/PHONE/pixel?x=1.5&y=0.5');
+  assert.equal(response.status, 400);
+});
@@ -0,0 +1,58 @@
+import { test, before, after, afterEach } from 'node:test';
+import assert from 'node:assert/strict';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { getPlugin, invokePlugin, loadPlugins } from '../src/main/services/plugins';
+
+let server: TestServer;
+let pluginsDir: string;
+let serverPluginsDir: string | undefined;
+
+function writePlugin(name: string, manifest: Record<string, unknown>, script = 'cat > /dev/null\necho "{}"\n'): void {
+  const dir = path.join(pluginsDir, name);
+  fs.mkdirSync(dir, { recursive: true });
+  fs.writeFileSync(path.join(dir, 'plugin.json'), JSON.stringify({ name, executable: 'run.sh', ...manifest }));
+  fs.writeFileSync(path.join(dir, 'run.sh'), `#!/bin/sh\n${script}`, { mode: 0o755 });
+}
+
+before(async () => {
+  server = await startTestServer();
+  serverPluginsDir = process.env.PLASMA_PLUGINS_DIR;
+});
+
+afterEach(() => {
+  process.env.PLASMA_PLUGINS_DIR = serverPluginsDir;
+  loadPlugins(server.state.plugins);
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('skips plugins whose routes or device actions are malformed', () => {
+  pluginsDir = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-plugins-'));
+  process.env.PLASMA_PLUGINS_DIR = pluginsDir;
+  writePlugin('valid', { routes: [{ method: 'get', path: '/status' }], deviceActions: [{ id: 'reset', title: 'Reset' }] });
+  writePlugin('route-method', { routes: [{ method: 1, path: '/status' }] });
+  writePlugin('route-path', { routes: [{ method: 'GET' }] });
+  writePlugin('routes-object', { routes: { method: 'GET', path: '/status' } });
+  writePlugin('action-title', { deviceActions: [{ id: 'reset', title: null }] });
+
+  assert.deepEqual(loadPlugins(server.state.plugins).map((plugin) => plugin.manifest.name), ['valid']);
+});
+
+test('rejects an invocation when the plugin never reads its request', async () => {
+  pluginsDir = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-plugins-'));
+  process.env.PLASMA_PLUGINS_DIR = pluginsDir;
+  // Closes stdin without reading it, then keeps running
+  writePlugin('deaf', {}, 'exec 0<&-\nsleep 0.5\n');
+  loadPlugins(server.state.plugins);
+
+  const body = 'x'.repeat(1024 * 1024);
+  await assert.rejects(
+    invokePlugin(getPlugin(server.state.plugins, 'deaf')!, { type: 'route', method: 'POST', path: '/', query: {}, body }),
+    /didn't read its request/
+  );
+});
@@ -0,0 +1,33 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({ devices: [{ udid: 'PHONE', name: 'iPhone 16' }] });
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('grants and resets permissions through simctl', async () => {
+  const grant = await server.request('POST', '/api/simulator/privacy', { udid: 'PHONE', action: 'grant', service: 'photos', bundleId: 'dev.plasma.App' });
+
+  assert.equal(grant.status, 200);
+  assert.deepEqual(server.devices.calls.at(-1), { kind: 'simctl', args: ['privacy', 'PHONE', 'grant', 'photos', 'dev.plasma.App'] });
+
+  await server.request('POST', '/api/simulator/privacy', { udid: 'PHONE', action: 'reset', service: 'all' });
+  assert.deepEqual(server.devices.calls.at(-1), { kind: 'simctl', args: ['privacy', 'PHONE', 'reset', 'all'] });
+});
+
+test('rejects unknown services and grants without a bundle ID', async () => {
+  const camera = await server.request('POST', '/api/simulator/privacy', { udid: 'PHONE', action: 'grant', service: 'camera', bundleId: 'dev.plasma.App' });
+  assert.equal(camera.status, 400);
+  assert.match(camera.body.error, /^service must be one of/);
+
+  const revoke = await server.request('POST', '/api/simulator/privacy', { udid: 'PHONE', action: 'revoke', service: 'location' });
+  assert.equal(revoke.status, 400);
+  assert.equal(revoke.body.error, 'bundleId is required to revoke permissions');
+});
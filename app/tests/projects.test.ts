@@ -0,0 +1,134 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { getDataStore } from '../src/main/services/data-store';
+
+const WORKSPACE = path.join(__dirname, 'fixtures', 'xcode', 'Plasma.xcworkspace');
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer();
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('creating a project requires a name and a path', async () => {
+  const response = await server.request('POST', '/api/projects/create', { name: 'Plasma' });
+
+  assert.equal(response.status, 400);
+  assert.equal(response.body.error, 'Name and at least one project path are required');
+});
+
+test('creating a project rejects an invalid Xcode path', async () => {
+  const response = await server.request('POST', '/api/projects/create', {
+    name: 'Plasma',
+    xcodePath: path.join(__dirname, 'fixtures', 'missing'),
+  });
+
+  assert.equal(response.status, 400);
+  assert.equal(response.body.error, 'Xcode: Path does not exist');
+});
+
+test('created projects are listed as recent and can be fetched by ID', async () => {
+  const created = await server.request('POST', '/api/projects/create', { name: 'Plasma', xcodePath: WORKSPACE });
+  assert.equal(created.status, 200);
+  assert.equal(created.body.project.xcode_path, WORKSPACE);
+
+  const recent = await server.request('GET', '/api/projects/unified/recent');
+  assert.equal(recent.status, 200);
+  assert.equal(recent.headers.get('X-Total-Count'), '1');
+  assert.deepEqual(recent.body.map((project: { name: string }) => project.name), ['Plasma']);
+
+  const fetched = await server.request('GET', `/api/projects/unified/${created.body.project.id}`);
+  assert.equal(fetched.status, 200);
+  assert.equal(fetched.body.name, 'Plasma');
+});
+
+test('fetching an unknown project returns 404', async () => {
+  const response = await server.request('GET', '/api/projects/unified/999');
+
+  assert.equal(response.status, 404);
+});
+
+test('stores project notes and renders them', async () => {
+  const project = await getDataStore().projects.save('Notes', WORKSPACE, null);
+
+  const updated = await server.request('PUT', `/api/projects/${project.id}/notes`, { notes: 'Log in as **qa@plasma.dev** <script>' });
+  assert.equal(updated.status, 200);
+  assert.equal(updated.body.notesHtml, '<p>Log in as <strong>qa@plasma.dev</strong> &lt;script&gt;</p>');
+
+  const fetched = await server.request('GET', `/api/projects/${project.id}/notes`);
+  assert.equal(fetched.body.notes, 'Log in as **qa@plasma.dev** <script>');
+
+  const cleared = await server.request('PUT', `/api/projects/${project.id}/notes`, { notes: '' });
+  assert.deepEqual(cleared.body, { notes: null, notesHtml: null });
+
+  const invalid = await server.request('PUT', `/api/projects/${project.id}/notes`, { notes: 42 });
+  assert.equal(invalid.status, 400);
+});
+
+test('renders the README above the Xcode project', async () => {
+  const repo = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-readme-'));
+  fs.mkdirSync(path.join(repo, 'ios', 'App.xcodeproj'), { recursive: true });
+  fs.writeFileSync(path.join(repo, 'README.md'), '# App\n\n- Use the `staging` scheme\n');
+  const project = await getDataStore().projects.save('App', path.join(repo, 'ios', 'App.xcodeproj'), null);
+
+  const response = await server.request('GET', `/api/projects/${project.id}/readme`);
+
+  assert.equal(response.status, 200);
+  assert.equal(response.body.path, path.join(repo, 'README.md'));
+  assert.equal(response.body.html, '<h1>App</h1>\n<ul>\n<li>Use the <code>staging</code> scheme</li>\n</ul>');
+});
+
+test('returns 404 for projects without a README', async () => {
+  const repo = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-readme-'));
+  fs.mkdirSync(path.join(repo, '.git'));
+  fs.mkdirSync(path.join(repo, 'App.xcodeproj'));
+  const project = await getDataStore().projects.save('Bare', path.join(repo, 'App.xcodeproj'), null);
+
+  const response = await server.request('GET', `/api/projects/${project.id}/readme`);
+
+  assert.equal(response.status, 404);
+});
+
+test('stores the last used build settings of a project', async () => {
+  const project = await getDataStore().projects.save('Settings', WORKSPACE, null);
+  const settingsPath = `/api/projects/${project.id}/settings`;
+
+  const empty = await server.request('GET', settingsPath);
+  assert.deepEqual(empty.body, { scheme: null, configuration: null, simulatorUdid: null, environment: {}, updatedAt: null });
+
+  await server.request('PUT', settingsPath, { scheme: 'Plasma', configuration: 'Debug', environment: { API_HOST: 'staging' } });
+  const updated = await server.request('PUT', settingsPath, { configuration: null, simulatorUdid: 'PHONE' });
+
+  assert.equal(updated.status, 200);
+  assert.equal(updated.body.scheme, 'Plasma');
+  assert.equal(updated.body.configuration, null);
+  assert.equal(updated.body.simulatorUdid, 'PHONE');
+  assert.deepEqual(updated.body.environment, { API_HOST: 'staging' });
+  assert.deepEqual((await server.request('GET', settingsPath)).body, updated.body);
+});
+
+test('rejects stale and invalid project settings', async () => {
+  const project = await getDataStore().projects.save('Stale settings', WORKSPACE, null);
+  const settingsPath = `/api/projects/${project.id}/settings`;
+  const first = await server.request('PUT', settingsPath, { scheme: 'Plasma', expectedUpdatedAt: null });
+
+  const stale = await server.request('PUT', settingsPath, { scheme: 'Other', expectedUpdatedAt: null });
+  assert.equal(stale.status, 409);
+  assert.equal(stale.body.scheme, 'Plasma');
+  assert.equal(stale.body.updatedAt, first.body.updatedAt);
+
+  const invalid = await server.request('PUT', settingsPath, { environment: { 'API-HOST': 'staging', DEBUG: 1 } });
+  assert.equal(invalid.status, 400);
+  assert.deepEqual(invalid.body.fields.map((error: { path: string }) => error.path), ['environment']);
+
+  const unknown = await server.request('GET', '/api/projects/999999/settings');
+  assert.equal(unknown.status, 404);
+});
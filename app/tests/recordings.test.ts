@@ -0,0 +1,92 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { createLocalStore, setArtifactStore } from '../src/main/services/storage';
+import { getKeepAwakeHolders } from '../src/main/services/keep-awake';
+import { recordStreamFrame } from '../src/main/services/recordings';
+
+let server: TestServer;
+
+before(async () => {
+  setArtifactStore(createLocalStore(fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-recordings-'))));
+  server = await startTestServer({
+    devices: [
+      { udid: 'SIM-1', name: 'iPhone 16' },
+      { udid: 'SIM-2', name: 'iPhone 16 Pro', state: 'Shutdown' },
+    ],
+  });
+});
+
+after(async () => {
+  await server.close();
+  setArtifactStore(null);
+});
+
+test('records a simulator until stopped and serves the video', async () => {
+  const started = await server.request('POST', '/api/simulator/recording/start', { udid: 'SIM-1' });
+  assert.equal(started.status, 201);
+  assert.equal(started.body.status, 'recording');
+  assert.ok(getKeepAwakeHolders().includes('recording:SIM-1'));
+
+  const again = await server.request('POST', '/api/simulator/recording/start', { udid: 'SIM-1' });
+  assert.equal(again.status, 409);
+
+  const stopped = await server.request('POST', '/api/simulator/recording/stop', { udid: 'SIM-1' });
+  assert.equal(stopped.status, 200);
+  assert.equal(stopped.body.id, started.body.id);
+  assert.equal(stopped.body.status, 'finished');
+  assert.equal(stopped.body.sizeBytes, 'fake recording of SIM-1'.length);
+  assert.ok(!getKeepAwakeHolders().includes('recording:SIM-1'));
+
+  const video = await server.request('GET', `/api/recordings/${started.body.id}/video`);
+  assert.equal(video.status, 200);
+  assert.equal(video.body, 'fake recording of SIM-1');
+
+  const list = await server.request('GET', '/api/recordings?udid=SIM-1');
+  assert.deepEqual(
+    list.body.map((recording: { id: string }) => recording.id),
+    [started.body.id]
+  );
+
+  const deleted = await server.request('DELETE', `/api/recordings/${started.body.id}`);
+  assert.equal(deleted.status, 200);
+  assert.equal((await server.request('GET', `/api/recordings/${started.body.id}`)).status, 404);
+  assert.equal((await server.request('GET', `/api/recordings/${started.body.id}/video`)).status, 404);
+});
+
+test('records the frames streamed from a simulator', async () => {
+  const started = await server.request('POST', '/api/simulator/recording/start', { udid: 'SIM-1', source: 'stream' });
+  assert.equal(started.status, 201);
+  assert.equal(started.body.source, 'stream');
+
+  recordStreamFrame('SIM-1', Buffer.from('frame one'), 'jpeg');
+  recordStreamFrame('SIM-2', Buffer.from('not recorded'), 'jpeg');
+  recordStreamFrame('SIM-1', Buffer.from('frame two'), 'png');
+  const stopped = await server.request('POST', '/api/simulator/recording/stop', { udid: 'SIM-1' });
+  assert.equal(stopped.body.status, 'finished');
+
+  const video = await server.request('GET', `/api/recordings/${started.body.id}/video`);
+  assert.equal(video.headers.get('Content-Type'), 'multipart/x-mixed-replace; boundary=plasmaframe');
+  assert.equal(
+    video.body,
+    '--plasmaframe\r\nContent-Type: image/jpeg\r\nContent-Length: 9\r\n\r\nframe one\r\n' +
+      '--plasmaframe\r\nContent-Type: image/png\r\nContent-Length: 9\r\n\r\nframe two\r\n'
+  );
+
+  await server.request('DELETE', `/api/recordings/${started.body.id}`);
+});
+
+test('rejects recordings with an unknown source', async () => {
+  const response = await server.request('POST', '/api/simulator/recording/start', { udid: 'SIM-1', source: 'screen' });
+  assert.equal(response.status, 400);
+});
+
+test('rejects recordings of unknown, shut down or idle simulators', async () => {
+  assert.equal((await server.request('POST', '/api/simulator/recording/start', {})).status, 400);
+  assert.equal((await server.request('POST', '/api/simulator/recording/start', { udid: 'MISSING' })).status, 404);
+  assert.equal((await server.request('POST', '/api/simulator/recording/start', { udid: 'SIM-2' })).status, 409);
+  assert.equal((await server.request('POST', '/api/simulator/recording/stop', { udid: 'SIM-1' })).status, 404);
+});
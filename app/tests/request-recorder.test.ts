@@ -0,0 +1,74 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { getDataStore } from '../src/main/services/data-store';
+import { configureRequestRecording, redactBody } from '../src/main/services/request-recorder';
+import { configureSecrets } from '../src/main/services/secrets';
+
+let server: TestServer;
+let projectId: number;
+
+before(async () => {
+  server = await startTestServer();
+  configureSecrets({ passphrase: 'correct horse', keychain: false });
+  projectId = (await getDataStore().projects.save('Plasma', '/projects/Plasma/Plasma.xcodeproj', null)).id;
+  configureRequestRecording({ enabled: false, capacity: 3, maxBodyBytes: 1024, redactKeys: ['bundleId'] });
+});
+
+after(async () => {
+  configureRequestRecording({ enabled: false, capacity: 200, maxBodyBytes: 16 * 1024, redactKeys: [] });
+  configureSecrets({ passphrase: null, keychain: false });
+  await server.close();
+});
+
+test('records nothing until turned on', async () => {
+  await server.request('GET', '/api/health');
+  const response = await server.request('GET', '/api/admin/requests');
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(response.body, []);
+});
+
+test('records requests and responses with credentials redacted', async () => {
+  const enabled = await server.request('PUT', '/api/admin/requests/recording', { enabled: true });
+  assert.equal(enabled.body.enabled, true);
+
+  await server.request(
+    'POST',
+    `/api/projects/${projectId}/notifiers`,
+    { kind: 'slack', webhookUrl: 'https://hooks.slack.com/services/T000-hunter2', bundleId: 'dev.plasma.app' },
+    { Authorization: 'Bearer hunter2' }
+  );
+  await server.request('GET', '/api/health?token=hunter2');
+
+  const response = await server.request('GET', '/api/admin/requests');
+  assert.ok(!JSON.stringify(response.body).includes('hunter2'));
+
+  const [health, notifier] = response.body;
+  assert.equal(health.path, '/api/health');
+  assert.deepEqual(health.query, { token: '[redacted]' });
+  assert.equal(health.status, 200);
+  assert.deepEqual(JSON.parse(health.responseBody), { status: 'ok' });
+
+  assert.equal(notifier.method, 'POST');
+  assert.equal(notifier.requestHeaders.authorization, '[redacted]');
+  assert.deepEqual(JSON.parse(notifier.requestBody), { kind: 'slack', webhookUrl: '[redacted]', bundleId: '[redacted]' });
+  assert.equal(notifier.status, 201);
+  assert.match(JSON.parse(notifier.responseBody).webhook_secret, /^notifiers\.\d+\.webhook_url$/);
+});
+
+test('keeps only the latest requests', async () => {
+  for (let i = 0; i < 4; i++) {
+    await server.request('GET', '/api/health');
+  }
+  const response = await server.request('GET', '/api/admin/requests');
+  assert.equal(response.body.length, 3);
+
+  await server.request('PUT', '/api/admin/requests/recording', { enabled: false });
+  const cleared = await server.request('DELETE', '/api/admin/requests');
+  assert.deepEqual(cleared.body, { enabled: false, capacity: 3, count: 0 });
+});
+
+test('redacts sensitive fields of truncated bodies', () => {
+  assert.equal(redactBody('{"name":"x","token":"abc'), '{"name":"x","token":"[redacted]');
+});
@@ -0,0 +1,58 @@
+import { test, afterEach } from 'node:test';
+import assert from 'node:assert/strict';
+import { DEFAULT_RETRYABLE_PATTERNS, isRetryableError, setRetryPolicy, withRetry } from '../src/main/services/retry';
+
+const policy = { maxAttempts: 3, initialDelayMs: 1, maxDelayMs: 5, retryablePatterns: DEFAULT_RETRYABLE_PATTERNS };
+
+afterEach(() => {
+  setRetryPolicy({ ...policy, initialDelayMs: 500, maxDelayMs: 5000 });
+});
+
+test('matches transient CoreSimulator errors case-insensitively', () => {
+  assert.equal(isRetryableError(new Error('An error was encountered processing the command (domain=com.apple.CoreSimulator.SimError, code=405):\nCoreSimulatorService connection became invalid.'), policy), true);
+  assert.equal(isRetryableError(new Error('Operation Timed Out'), policy), true);
+  assert.equal(isRetryableError(new Error('Invalid device: PHONE'), policy), false);
+});
+
+test('retries transient errors until the operation succeeds', async () => {
+  setRetryPolicy(policy);
+  let attempts = 0;
+
+  const result = await withRetry('install', async () => {
+    attempts++;
+    if (attempts < 3) {
+      throw new Error('Mach error -308 - (ipc/mig) server died');
+    }
+    return 'installed';
+  });
+
+  assert.equal(result, 'installed');
+  assert.equal(attempts, 3);
+});
+
+test('gives up after the last attempt', async () => {
+  setRetryPolicy(policy);
+  let attempts = 0;
+
+  await assert.rejects(
+    withRetry('launch', async () => {
+      attempts++;
+      throw new Error('connection interrupted');
+    }),
+    /connection interrupted/
+  );
+  assert.equal(attempts, 3);
+});
+
+test('does not retry other errors', async () => {
+  setRetryPolicy({ ...policy, retryablePatterns: ['busy'] });
+  let attempts = 0;
+
+  await assert.rejects(
+    withRetry('boot', async () => {
+      attempts++;
+      throw new Error('timed out');
+    })
+  );
+  assert.equal(attempts, 1);
+});
@@ -0,0 +1,40 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import {
+  createRuntimeIssues,
+  detectRuntimeIssue,
+  recordLogLine,
+  getRuntimeIssues,
+  clearRuntimeIssues,
+} from '../src/main/services/runtime-issues';
+
+test('detects common runtime issues in log lines', () => {
+  assert.equal(detectRuntimeIssue('[LayoutConstraints] Unable to simultaneously satisfy constraints.'), 'auto_layout');
+  assert.equal(
+    detectRuntimeIssue('Main Thread Checker: UI API called on a background thread: -[UIView setNeedsLayout]'),
+    'main_thread_checker'
+  );
+  assert.equal(detectRuntimeIssue('CoreData: warning: Unable to load class named Item'), 'core_data');
+  assert.equal(detectRuntimeIssue('simulator-server ready at http://127.0.0.1:1234'), null);
+});
+
+test('groups repeated issues and clears them on request', () => {
+  const issues = createRuntimeIssues();
+  const line = 'Unable to simultaneously satisfy constraints.\n  Probably at least one of the constraints...';
+  recordLogLine(issues, 'SIM-1', line);
+  recordLogLine(issues, 'SIM-1', line);
+  recordLogLine(issues, 'SIM-1', 'Publishing changes from background threads is not allowed');
+
+  const found = getRuntimeIssues(issues, 'SIM-1');
+  assert.deepEqual(
+    found.map((issue) => [issue.kind, issue.count]),
+    [
+      ['auto_layout', 2],
+      ['swiftui', 1],
+    ]
+  );
+  assert.equal(found[0].message, 'Unable to simultaneously satisfy constraints.');
+
+  clearRuntimeIssues(issues, 'SIM-1');
+  assert.deepEqual(getRuntimeIssues(issues, 'SIM-1'), []);
+});
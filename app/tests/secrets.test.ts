@@ -0,0 +1,51 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { getDataStore } from '../src/main/services/data-store';
+import { configureSecrets, getSecret } from '../src/main/services/secrets';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer();
+  configureSecrets({ passphrase: 'correct horse', keychain: false });
+});
+
+after(async () => {
+  configureSecrets({ passphrase: null, keychain: false });
+  await server.close();
+});
+
+test('stores secrets encrypted and never returns their values', async () => {
+  const stored = await server.request('PUT', '/api/secrets/asc.api-key', { value: 'super-secret-key' });
+  assert.equal(stored.status, 200);
+  assert.equal(stored.body.name, 'asc.api-key');
+  assert.equal(stored.body.value, undefined);
+
+  const listed = await server.request('GET', '/api/secrets');
+  assert.deepEqual(listed.body.map((secret: { name: string }) => secret.name), ['asc.api-key']);
+  assert.ok(!JSON.stringify(listed.body).includes('super-secret-key'));
+
+  const ciphertext = await getDataStore().secrets.getCiphertext('asc.api-key');
+  assert.ok(ciphertext && !ciphertext.includes('super-secret-key'));
+  assert.equal(await getSecret('asc.api-key'), 'super-secret-key');
+});
+
+test('locks secrets with the wrong passphrase', async () => {
+  configureSecrets({ passphrase: 'wrong', keychain: false });
+  const status = await server.request('GET', '/api/secrets/status');
+  assert.equal(status.body.unlocked, false);
+
+  const response = await server.request('PUT', '/api/secrets/webhook', { value: 'x' });
+  assert.equal(response.status, 503);
+
+  configureSecrets({ passphrase: 'correct horse', keychain: false });
+  assert.equal((await server.request('GET', '/api/secrets/status')).body.keySource, 'passphrase');
+});
+
+test('rejects invalid names and deletes secrets', async () => {
+  assert.equal((await server.request('PUT', '/api/secrets/has%20space', { value: 'x' })).status, 400);
+
+  assert.equal((await server.request('DELETE', '/api/secrets/asc.api-key')).status, 200);
+  assert.equal((await server.request('DELETE', '/api/secrets/asc.api-key')).status, 404);
+});
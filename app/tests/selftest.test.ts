@@ -0,0 +1,28 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { runSelfTest, formatSelfTestReport } from '../src/main/selftest';
+
+test('passes every check it can run on this machine', async () => {
+  const report = await runSelfTest();
+
+  assert.equal(report.passed, true, formatSelfTestReport(report));
+  assert.deepEqual(
+    report.checks.map((check) => check.name),
+    ['health', 'database', 'simulators', 'stream']
+  );
+  const simulators = report.checks.find((check) => check.name === 'simulators')!;
+  assert.equal(simulators.status, process.platform === 'darwin' ? 'pass' : 'skip');
+  assert.match(report.checks.find((check) => check.name === 'stream')!.detail!, /^Received a \d+ byte frame$/);
+});
+
+test('formats one line per check', () => {
+  const text = formatSelfTestReport({
+    passed: false,
+    checks: [
+      { name: 'health', status: 'pass', durationMs: 3, detail: null },
+      { name: 'stream', status: 'fail', durationMs: 5000, detail: 'No frame within 5000 ms' },
+    ],
+  });
+
+  assert.equal(text, 'PASS  health (3 ms)\nFAIL  stream (5000 ms): No frame within 5000 ms\nSelf-test failed');
+});
@@ -0,0 +1,45 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer } from '../src/main/testing';
+import { getDataStore } from '../src/main/services/data-store';
+import { getCachedSimulators } from '../src/main/services/simulator';
+
+test('runs two servers with their own simulators side by side', async () => {
+  const first = await startTestServer({ devices: [{ udid: 'FIRST', name: 'iPhone 16' }] });
+  const second = await startTestServer({ devices: [{ udid: 'SECOND', name: 'iPhone 16 Pro' }] });
+  try {
+    const firstList = await first.request('GET', '/api/simulator/list');
+    const secondList = await second.request('GET', '/api/simulator/list');
+    assert.deepEqual(firstList.body.map((simulator: { udid: string }) => simulator.udid), ['FIRST']);
+    assert.deepEqual(secondList.body.map((simulator: { udid: string }) => simulator.udid), ['SECOND']);
+
+    const tap = { x: 0.5, y: 0.5, screenWidth: 1179, screenHeight: 2556 };
+    await first.request('POST', '/api/simulator/tap', { udid: 'FIRST', ...tap });
+    await second.request('POST', '/api/simulator/tap', { udid: 'SECOND', ...tap });
+    assert.deepEqual(first.devices.calls.flatMap((call) => (call.kind === 'tap' ? [call.udid] : [])), ['FIRST']);
+    assert.deepEqual(second.devices.calls.flatMap((call) => (call.kind === 'tap' ? [call.udid] : [])), ['SECOND']);
+
+    // Settings changes only reach the listeners of the server they were made through
+    const notified: string[] = [];
+    first.state.settings.on('change', () => notified.push('FIRST'));
+    second.state.settings.on('change', () => notified.push('SECOND'));
+    await second.request('PUT', '/api/flags/webrtc_streaming', { enabled: true });
+    assert.deepEqual(notified, ['SECOND']);
+
+    // Each listing was cached by its own server
+    assert.deepEqual(first.run(() => getCachedSimulators())?.simulators.map((simulator) => simulator.udid), ['FIRST']);
+    assert.deepEqual(second.run(() => getCachedSimulators())?.simulators.map((simulator) => simulator.udid), ['SECOND']);
+  } finally {
+    await second.close();
+  }
+
+  try {
+    // Closing the second server left the first one's database open
+    const project = await getDataStore().projects.save('Plasma', '/projects/Plasma/Plasma.xcodeproj', null);
+    const response = await first.request('GET', `/api/projects/${project.id}/destination`);
+    assert.equal(response.status, 200);
+    assert.equal((await first.request('GET', '/api/simulator/list')).body[0].udid, 'FIRST');
+  } finally {
+    await first.close();
+  }
+});
@@ -0,0 +1,59 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, createFakeDeviceBackend, TestServer } from '../src/main/testing';
+import { runControlMessage } from '../src/main/services/simulator-control';
+
+const fake = createFakeDeviceBackend([{ udid: 'PHONE', name: 'iPhone 16' }]);
+let server: TestServer;
+const commands: string[] = [];
+
+before(async () => {
+  // An older simulator-server that streams and rotates but doesn't take touches
+  server = await startTestServer({
+    backend: {
+      ...fake,
+      async sendSessionCommand(_udid, command) {
+        commands.push(command);
+      },
+      sessionProtocol: () => ({
+        protocolVersion: 1,
+        capabilities: { touch: false, key: true, rotate: true, button: true, audio: false },
+      }),
+    },
+  });
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('reports the capabilities of the session', async () => {
+  const response = await server.request('GET', '/api/simulator/session/PHONE');
+
+  assert.equal(response.status, 200);
+  assert.equal(response.body.protocolVersion, 1);
+  assert.equal(response.body.capabilities.touch, false);
+  assert.equal(response.body.capabilities.rotate, true);
+});
+
+test('rejects commands the simulator-server does not support instead of sending them', async () => {
+  const response = await server.request('POST', '/api/simulator/touch', {
+    udid: 'PHONE',
+    type: 'began',
+    touches: [{ x: 0.5, y: 0.5 }],
+  });
+
+  assert.equal(response.status, 501);
+  assert.equal(response.body.code, 'unsupported_by_simulator_server');
+  assert.equal(response.body.capability, 'touch');
+  assert.deepEqual(commands, []);
+});
+
+test('falls back to AXe for control socket taps', () => server.run(async () => {
+  commands.length = 0;
+
+  await runControlMessage({ type: 'tap', x: 0.5, y: 0.5, screenWidth: 1179, screenHeight: 2556 }, 'PHONE');
+
+  assert.deepEqual(commands, []);
+  assert.equal(fake.calls.at(-1)?.kind, 'tap');
+}));
@@ -0,0 +1,45 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({ devices: [{ udid: 'PHONE', name: 'iPhone 16' }] });
+});
+
+after(async () => {
+  await server.close();
+});
+
+test("opens an app's notification settings", async () => {
+  const response = await server.request('POST', '/api/simulator/PHONE/settings', { pane: 'notifications', bundleId: 'dev.plasma.App' });
+
+  assert.equal(response.status, 200);
+  assert.equal(response.body.url, 'App-prefs:root=NOTIFICATIONS_ID&path=dev.plasma.App');
+  assert.deepEqual(server.devices.calls.at(-1), {
+    kind: 'simctl',
+    args: ['openurl', 'PHONE', 'App-prefs:root=NOTIFICATIONS_ID&path=dev.plasma.App'],
+  });
+});
+
+test('opens privacy panes without a bundle ID', async () => {
+  const response = await server.request('POST', '/api/simulator/PHONE/settings', { pane: 'privacy-photos' });
+
+  assert.equal(response.body.url, 'App-prefs:root=Privacy&path=PHOTOS');
+});
+
+test('requires a bundle ID for the panes of an app', async () => {
+  const response = await server.request('POST', '/api/simulator/PHONE/settings', { pane: 'app' });
+  assert.equal(response.status, 400);
+  assert.equal(response.body.fields[0].path, 'bundleId');
+
+  const unknown = await server.request('POST', '/api/simulator/PHONE/settings', { pane: 'bluetooth' });
+  assert.equal(unknown.status, 400);
+});
+
+test('lists the settings panes', async () => {
+  const response = await server.request('GET', '/api/simulator/settings/panes');
+
+  assert.ok(response.body.some((pane: { id: string; requiresBundleId: boolean }) => pane.id === 'notifications' && pane.requiresBundleId));
+});
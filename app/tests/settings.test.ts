@@ -0,0 +1,86 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { setSetting } from '../src/main/services/database';
+import {
+  clearSetting,
+  readSetting,
+  updateSetting,
+  writeSetting,
+  SettingValueError,
+} from '../src/main/services/settings';
+import type { SettingChange } from '../src/shared/ipc-types';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer();
+});
+
+after(async () => {
+  await server.close();
+});
+
+function collectChanges(): SettingChange[] {
+  const changes: SettingChange[] = [];
+  server.state.settings.on('change', (change: SettingChange) => changes.push(change));
+  return changes;
+}
+
+test('reads back typed values', async () => {
+  await writeSetting('flags.proxy', true);
+  assert.equal((await readSetting('flags.proxy'))?.value, true);
+
+  await clearSetting('flags.proxy');
+  assert.equal(await readSetting('flags.proxy'), null);
+});
+
+test('emits changes, without the values of private settings', () => server.run(async () => {
+  const changes = collectChanges();
+
+  await writeSetting('onboarding.dismissed_at', '2026-10-01T09:00:00.000Z');
+  await writeSetting('secrets.salt', 'c2FsdA==');
+  await clearSetting('onboarding.dismissed_at');
+  // Nothing changes
+  await clearSetting('onboarding.dismissed_at');
+
+  assert.deepEqual(
+    changes.map(({ key, value }) => ({ key, value })),
+    [
+      { key: 'onboarding.dismissed_at', value: '2026-10-01T09:00:00.000Z' },
+      { key: 'secrets.salt', value: null },
+      { key: 'onboarding.dismissed_at', value: null },
+    ]
+  );
+  server.state.settings.removeAllListeners('change');
+}));
+
+test('rejects values of the wrong type', async () => {
+  await assert.rejects(writeSetting('analytics.enabled_at', 'yesterday'), SettingValueError);
+  assert.equal(await readSetting('analytics.enabled_at'), null);
+});
+
+test('reads invalid stored values as unset', async () => {
+  setSetting('flags.android', 'yes');
+
+  assert.equal(await readSetting('flags.android'), null);
+});
+
+test('updates from the current value', async () => {
+  const first = await updateSetting('secrets.check', (current) => current ?? 'first');
+  const second = await updateSetting('secrets.check', (current) => current ?? 'second');
+
+  assert.equal(first, 'first');
+  assert.equal(second, 'first');
+});
+
+test('flags set through the API are emitted', async () => {
+  const changes = collectChanges();
+
+  await server.request('PUT', '/api/flags/webrtc_streaming', { enabled: true });
+
+  assert.equal(changes.length, 1);
+  assert.equal(changes[0].key, 'flags.webrtc_streaming');
+  assert.equal(changes[0].value, true);
+  server.state.settings.removeAllListeners('change');
+});
@@ -0,0 +1,57 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { createFakeDevicesBackend } from '../src/main/services/fake-devices';
+import { listSimulators, setSimulatorListTtl, DeviceBackend } from '../src/main/services/simulator';
+
+let server: TestServer;
+let listings = 0;
+
+before(async () => {
+  const backend = createFakeDevicesBackend();
+  const counting: DeviceBackend = {
+    ...backend,
+    async listSimulators() {
+      listings++;
+      return backend.listSimulators();
+    },
+  };
+  server = await startTestServer({ backend: counting });
+  server.run(() => setSimulatorListTtl(60_000));
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('reuses the simulator list within the TTL', async () => {
+  await server.request('GET', '/api/simulator/list');
+  const before = listings;
+
+  await server.request('GET', '/api/simulator/list');
+  assert.equal(listings, before);
+
+  const response = await server.request('GET', '/api/simulator/list?refresh=true');
+  assert.equal(response.status, 200);
+  assert.equal(listings, before + 1);
+});
+
+test('shares one listing between concurrent refreshes', async () => {
+  const before = listings;
+  await server.run(() => Promise.all([listSimulators({ refresh: true }), listSimulators({ refresh: true })]));
+
+  assert.equal(listings, before + 1);
+});
+
+test('lists simulators again after a launch boots one', async () => {
+  await server.request('GET', '/api/simulator/list');
+  const launch = await server.request('POST', '/api/simulator/launch', {
+    udid: 'FAKE-APPLE-WATCH',
+    appPath: '/tmp/Plasma.app',
+  });
+  assert.equal(launch.status, 200);
+
+  const response = await server.request('GET', '/api/simulator/list');
+  const watch = response.body.find((simulator: { udid: string }) => simulator.udid === 'FAKE-APPLE-WATCH');
+  assert.equal(watch.state, 'Booted');
+});
@@ -0,0 +1,109 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import WebSocket from 'ws';
+import { startTestServer, TestServer } from '../src/main/testing';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({
+    devices: [
+      { udid: 'PHONE', name: 'iPhone 16' },
+      { udid: 'TV', name: 'Apple TV', deviceType: 'com.apple.CoreSimulator.SimDeviceType.Apple-TV-4K-3rd-generation-4K' },
+    ],
+  });
+});
+
+after(async () => {
+  await server.close();
+});
+
+async function openControlSocket(query: string): Promise<{ ws: WebSocket; replies: () => Promise<unknown> }> {
+  const ws = new WebSocket(`${server.baseUrl.replace('http', 'ws')}/api/simulator/ws${query}`);
+  await new Promise((resolve) => ws.once('open', resolve));
+  return {
+    ws,
+    replies: () => new Promise((resolve) => ws.once('message', (data) => resolve(JSON.parse(data.toString())))),
+  };
+}
+
+test('sends a drag over one socket in order', async () => {
+  const { ws, replies } = await openControlSocket('?udid=PHONE');
+  server.devices.calls.length = 0;
+
+  ws.send(JSON.stringify({ type: 'touch', phase: 'began', touches: [{ x: 0.5, y: 0.5 }] }));
+  ws.send(JSON.stringify({ type: 'touch', phase: 'moved', touches: [{ x: 0.5, y: 0.6 }] }));
+  const reply = replies();
+  ws.send(JSON.stringify({ id: 1, type: 'touch', phase: 'ended', touches: [{ x: 0.5, y: 0.7 }] }));
+
+  assert.deepEqual(await reply, { id: 1, ok: true, error: null });
+  ws.close();
+  assert.deepEqual(
+    server.devices.calls.map((call) => (call.kind === 'command' ? call.command : call.kind)),
+    ['touch began 0.5,0.5', 'touch moved 0.5,0.6', 'touch ended 0.5,0.7']
+  );
+});
+
+test('sends taps and keys to the simulator named in the message', async () => {
+  const { ws, replies } = await openControlSocket('');
+
+  server.devices.calls.length = 0;
+
+  let reply = replies();
+  ws.send(JSON.stringify({ id: 'tap', type: 'tap', udid: 'PHONE', x: 0.5, y: 0.5, screenWidth: 1179, screenHeight: 2556 }));
+  assert.deepEqual(await reply, { id: 'tap', ok: true, error: null });
+  // Through the session rather than a process per tap
+  assert.deepEqual(server.devices.calls, [
+    { kind: 'command', udid: 'PHONE', command: 'touch began 0.5,0.5' },
+    { kind: 'command', udid: 'PHONE', command: 'touch ended 0.5,0.5' },
+  ]);
+
+  reply = replies();
+  ws.send(JSON.stringify({ id: 'key', type: 'key', udid: 'PHONE', code: 40, direction: 'down' }));
+  await reply;
+  ws.close();
+  assert.deepEqual(server.devices.calls.at(-1), { kind: 'command', udid: 'PHONE', command: 'key 40 down' });
+});
+
+test('sends swipes as interpolated touches', async () => {
+  const { ws, replies } = await openControlSocket('?udid=PHONE');
+  server.devices.calls.length = 0;
+
+  const reply = replies();
+  ws.send(JSON.stringify({ id: 3, type: 'swipe', startX: 0.5, startY: 0.8, endX: 0.5, endY: 0.2, screenWidth: 1179, screenHeight: 2556, duration: 0.05 }));
+  assert.deepEqual(await reply, { id: 3, ok: true, error: null });
+  ws.close();
+
+  const commands = server.devices.calls.map((call) => (call.kind === 'command' ? call.command : call.kind));
+  assert.ok(!commands.includes('swipe'));
+  assert.equal(commands[0], 'touch began 0.5,0.8');
+  assert.ok(commands.slice(1, -1).every((command) => command.startsWith('touch moved 0.5,')));
+  assert.equal(commands.at(-1), 'touch ended 0.5,0.2');
+});
+
+test('answers invalid messages with errors', async () => {
+  const { ws, replies } = await openControlSocket('?udid=TV');
+
+  let reply = replies();
+  ws.send(JSON.stringify({ type: 'touch', phase: 'began', touches: [{ x: 0.5, y: 0.5 }] }));
+  assert.deepEqual(await reply, { id: null, ok: false, error: 'Touch input is not supported on this simulator' });
+
+  reply = replies();
+  ws.send('not json');
+  assert.deepEqual(await reply, { id: null, ok: false, error: 'Invalid JSON' });
+  ws.close();
+});
+
+test('reports the invalid fields of a message', async () => {
+  const { ws, replies } = await openControlSocket('?udid=PHONE');
+
+  const reply = replies();
+  ws.send(JSON.stringify({ id: 2, type: 'touch', phase: 'began', touches: [{ x: 0.5 }] }));
+  assert.deepEqual(await reply, {
+    id: 2,
+    ok: false,
+    error: 'touches[0].y is required',
+    fields: [{ path: 'touches[0].y', message: 'is required' }],
+  });
+  ws.close();
+});
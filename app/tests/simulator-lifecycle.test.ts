@@ -0,0 +1,94 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, createFakeDeviceBackend, TestServer } from '../src/main/testing';
+
+const DEVICE_TYPES = {
+  devicetypes: [
+    { identifier: 'com.apple.CoreSimulator.SimDeviceType.iPhone-16-Pro', name: 'iPhone 16 Pro', productFamily: 'iPhone' },
+    { identifier: 'com.apple.CoreSimulator.SimDeviceType.Apple-TV-4K-3rd-generation-4K', name: 'Apple TV 4K (3rd generation)', productFamily: 'Apple TV' },
+  ],
+  runtimes: [
+    {
+      identifier: 'com.apple.CoreSimulator.SimRuntime.iOS-18-0',
+      name: 'iOS 18.0',
+      version: '18.0',
+      platform: 'iOS',
+      isAvailable: true,
+      supportedDeviceTypes: [{ identifier: 'com.apple.CoreSimulator.SimDeviceType.iPhone-16-Pro', name: 'iPhone 16 Pro' }],
+    },
+  ],
+};
+
+let server: TestServer;
+const simctlCalls: string[][] = [];
+
+before(async () => {
+  server = await startTestServer({
+    backend: {
+      ...createFakeDeviceBackend([{ udid: 'PHONE', name: 'iPhone 16' }]),
+      async simctl(args) {
+        simctlCalls.push(args);
+        if (args[0] === 'list') {
+          return JSON.stringify(DEVICE_TYPES);
+        }
+        return args[0] === 'create' ? 'NEW-UDID\n' : '';
+      },
+    },
+  });
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('lists device types and runtimes', async () => {
+  const response = await server.request('GET', '/api/simulator/devicetypes');
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(response.body.deviceTypes[0], {
+    identifier: 'com.apple.CoreSimulator.SimDeviceType.iPhone-16-Pro',
+    name: 'iPhone 16 Pro',
+    productFamily: 'iPhone',
+    deviceClass: 'phone',
+  });
+  assert.deepEqual(response.body.runtimes[0].supportedDeviceTypes, ['com.apple.CoreSimulator.SimDeviceType.iPhone-16-Pro']);
+});
+
+test('creates a simulator', async () => {
+  const response = await server.request('POST', '/api/simulator/create', { name: 'Test Phone', deviceType: 'iPhone 16 Pro', runtime: 'iOS 18.0' });
+
+  assert.equal(response.status, 201);
+  assert.deepEqual(response.body, { udid: 'NEW-UDID' });
+  assert.deepEqual(simctlCalls.at(-1), ['create', 'Test Phone', 'iPhone 16 Pro', 'iOS 18.0']);
+});
+
+test('rejects device types the runtime does not support', async () => {
+  const unknown = await server.request('POST', '/api/simulator/create', { name: 'Test', deviceType: 'iPhone 99' });
+  assert.equal(unknown.status, 400);
+  assert.equal(unknown.body.error, 'Unknown device type: iPhone 99');
+
+  const unsupported = await server.request('POST', '/api/simulator/create', {
+    name: 'Test TV',
+    deviceType: 'Apple TV 4K (3rd generation)',
+    runtime: 'iOS 18.0',
+  });
+  assert.equal(unsupported.status, 400);
+  assert.equal(unsupported.body.error, "iOS 18.0 doesn't support Apple TV 4K (3rd generation)");
+});
+
+test('shuts a simulator down before erasing it', async () => {
+  const response = await server.request('POST', '/api/simulator/erase', { udid: 'PHONE' });
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(simctlCalls.slice(-2), [['shutdown', 'PHONE'], ['erase', 'PHONE']]);
+});
+
+test('shuts a simulator down before deleting it', async () => {
+  const response = await server.request('DELETE', '/api/simulator/PHONE');
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(simctlCalls.slice(-2), [['shutdown', 'PHONE'], ['delete', 'PHONE']]);
+
+  const missing = await server.request('DELETE', '/api/simulator/MISSING');
+  assert.equal(missing.status, 404);
+});
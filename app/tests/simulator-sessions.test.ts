@@ -0,0 +1,123 @@
+import { test, before, after, afterEach } from 'node:test';
+import assert from 'node:assert/strict';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { startTestServer, TestServer } from '../src/main/testing';
+import {
+  acquireSession,
+  releaseSession,
+  stopSession,
+  getOrCreateSession,
+  getActiveSessions,
+  setSessionIdleTimeout,
+  parseCapabilities,
+} from '../src/main/services/simulator';
+
+let server: TestServer;
+
+// Stands in for simulator-server: reports a stream URL, answers the capabilities question with
+// touch and key support only, and stays up until killed
+function fakeSimulatorServer(): string {
+  const file = path.join(fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-sessions-')), 'simulator-server');
+  fs.writeFileSync(
+    file,
+    '#!/bin/sh\necho "stream_ready http://127.0.0.1:1/stream.mjpeg"\nread line\necho \'capabilities {"protocol":2,"features":["touch","key"]}\'\nexec sleep 60\n',
+    { mode: 0o755 }
+  );
+  return file;
+}
+
+function waitFor(condition: () => boolean, timeoutMs = 2000): Promise<void> {
+  const deadline = Date.now() + timeoutMs;
+  return new Promise((resolve, reject) => {
+    const poll = () => {
+      if (condition()) {
+        resolve();
+      } else if (Date.now() > deadline) {
+        reject(new Error('Timed out'));
+      } else {
+        setTimeout(poll, 10);
+      }
+    };
+    poll();
+  });
+}
+
+before(async () => {
+  process.env.SIMULATOR_SERVER = fakeSimulatorServer();
+  server = await startTestServer({ devices: [{ udid: 'PHONE', name: 'iPhone 16' }] });
+});
+
+afterEach(() => server.run(() => {
+  stopSession('PHONE');
+  setSessionIdleTimeout(60_000);
+}));
+
+after(async () => {
+  await server.close();
+  delete process.env.SIMULATOR_SERVER;
+});
+
+test('stops a session once its last consumer has been idle for the timeout', () => server.run(async () => {
+  setSessionIdleTimeout(50);
+  acquireSession('PHONE', 'client:1');
+  acquireSession('PHONE', 'client:2');
+  const session = await getOrCreateSession('PHONE');
+
+  releaseSession('PHONE', 'client:1');
+  await new Promise((resolve) => setTimeout(resolve, 100));
+  assert.deepEqual(getActiveSessions(), ['PHONE']);
+
+  releaseSession('PHONE', 'client:2');
+  await waitFor(() => session.process.exitCode !== null || session.process.signalCode !== null);
+  assert.deepEqual(getActiveSessions(), []);
+}));
+
+test('keeps sessions when the idle timeout is 0', () => server.run(async () => {
+  setSessionIdleTimeout(0);
+  await getOrCreateSession('PHONE');
+  await new Promise((resolve) => setTimeout(resolve, 50));
+
+  assert.deepEqual(getActiveSessions(), ['PHONE']);
+}));
+
+test('tears down a session on request', () => server.run(async () => {
+  const session = await getOrCreateSession('PHONE');
+
+  const response = await server.request('DELETE', '/api/simulator/session/PHONE');
+  assert.equal(response.status, 200);
+  await waitFor(() => session.process.signalCode === 'SIGTERM');
+  assert.deepEqual(getActiveSessions(), []);
+
+  const again = await server.request('DELETE', '/api/simulator/session/PHONE');
+  assert.equal(again.status, 404);
+}));
+
+test('reports what the session\'s simulator-server supports', () => server.run(async () => {
+  await getOrCreateSession('PHONE');
+
+  const response = await server.request('GET', '/api/simulator/session/PHONE');
+  assert.equal(response.status, 200);
+  assert.equal(response.body.protocolVersion, 2);
+  assert.deepEqual(response.body.capabilities, { touch: true, key: true, rotate: false, button: false, audio: false });
+}));
+
+test('lists the running sessions', () => server.run(async () => {
+  const empty = await server.request('GET', '/api/simulator/sessions');
+  assert.deepEqual(empty.body, []);
+
+  await getOrCreateSession('PHONE');
+
+  const response = await server.request('GET', '/api/simulator/sessions?protocol_version=2');
+  assert.equal(response.status, 200);
+  assert.equal(response.headers.get('x-total-count'), '1');
+  assert.deepEqual(response.body.map((session: { udid: string }) => session.udid), ['PHONE']);
+}));
+
+test('assumes protocol 1 for malformed capability answers', () => {
+  assert.deepEqual(parseCapabilities('not json'), {
+    protocolVersion: 1,
+    capabilities: { touch: true, key: true, rotate: true, button: true, audio: false },
+  });
+});
@@ -0,0 +1,54 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { createFakeDevicesBackend } from '../src/main/services/fake-devices';
+import { checkSimulators, diffSimulators, stopSimulatorWatcher } from '../src/main/services/simulator-watch';
+import type { Simulator } from '../src/shared/ipc-types';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({ backend: createFakeDevicesBackend() });
+  stopSimulatorWatcher(server.state.simulatorWatcher);
+});
+
+after(async () => {
+  stopSimulatorWatcher(server.state.simulatorWatcher);
+  await server.close();
+});
+
+const simulator = (udid: string, state: string) => ({ udid, name: udid, state }) as Simulator;
+
+test('diffs simulator lists', () => {
+  const changes = diffSimulators(
+    [simulator('A', 'Shutdown'), simulator('B', 'Booted'), simulator('C', 'Booted'), simulator('D', 'Shutdown')],
+    [simulator('A', 'Booted'), simulator('B', 'Shutdown'), simulator('D', 'Creating'), simulator('E', 'Shutdown')]
+  );
+
+  assert.deepEqual(
+    changes.map((change) => [change.kind, change.udid, change.previousState]),
+    [
+      ['booted', 'A', 'Shutdown'],
+      ['shutdown', 'B', 'Booted'],
+      ['state_changed', 'D', 'Shutdown'],
+      ['added', 'E', null],
+      ['removed', 'C', 'Booted'],
+    ]
+  );
+});
+
+test('emits changes noticed between checks', () => server.run(async () => {
+  assert.deepEqual(await checkSimulators(server.state.simulatorWatcher), []);
+
+  const emitted: string[] = [];
+  const onChange = (change: { kind: string; udid: string }) => emitted.push(`${change.kind} ${change.udid}`);
+  server.state.simulatorWatcher.emitter.on('change', onChange);
+  try {
+    await server.request('POST', '/api/simulator/launch', { udid: 'FAKE-APPLE-WATCH', appPath: '/tmp/App.app' });
+    await checkSimulators(server.state.simulatorWatcher);
+  } finally {
+    server.state.simulatorWatcher.emitter.off('change', onChange);
+  }
+
+  assert.deepEqual(emitted, ['booted FAKE-APPLE-WATCH']);
+}));
@@ -0,0 +1,186 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({
+    devices: [
+      { udid: 'PHONE', name: 'iPhone 16', deviceType: 'com.apple.CoreSimulator.SimDeviceType.iPhone-16' },
+      { udid: 'TABLET', name: 'iPad Air', state: 'Shutdown', deviceType: 'com.apple.CoreSimulator.SimDeviceType.iPad-Air-11-inch-M2' },
+      { udid: 'TV', name: 'Apple TV', deviceType: 'com.apple.CoreSimulator.SimDeviceType.Apple-TV-4K-3rd-generation-4K' },
+    ],
+  });
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('lists fake devices with their device class', async () => {
+  const response = await server.request('GET', '/api/simulator/list?sort=name');
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(
+    response.body.map((simulator: { udid: string; deviceClass: string }) => [simulator.udid, simulator.deviceClass]),
+    [['TV', 'tv'], ['TABLET', 'tablet'], ['PHONE', 'phone']]
+  );
+});
+
+test('filters simulators by device class', async () => {
+  const response = await server.request('GET', '/api/simulator/list?device_class=tablet');
+
+  assert.deepEqual(response.body.map((simulator: { udid: string }) => simulator.udid), ['TABLET']);
+});
+
+test('returns 304 when the simulator list has not changed', async () => {
+  const first = await server.request('GET', '/api/simulator/list');
+  const etag = first.headers.get('ETag')!;
+
+  const second = await server.request('GET', '/api/simulator/list', undefined, { 'If-None-Match': etag });
+  assert.equal(second.status, 304);
+});
+
+test('launches apps through the device backend', async () => {
+  const response = await server.request('POST', '/api/simulator/launch', {
+    udid: 'PHONE',
+    appPath: '/tmp/Plasma.app',
+    bundleId: 'dev.plasma.app',
+  });
+
+  assert.equal(response.status, 200);
+  assert.equal(response.body.bundleId, 'dev.plasma.app');
+  assert.deepEqual(server.devices.calls.at(-1), {
+    kind: 'launch',
+    udid: 'PHONE',
+    appPath: '/tmp/Plasma.app',
+    bundleId: 'dev.plasma.app',
+  });
+});
+
+test('maps taps to points', async () => {
+  const response = await server.request('POST', '/api/simulator/tap', {
+    udid: 'PHONE',
+    x: 0.5,
+    y: 0.5,
+    screenWidth: 1179,
+    screenHeight: 2556,
+  });
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(server.devices.calls.at(-1), { kind: 'tap', udid: 'PHONE', x: 197, y: 426 });
+});
+
+test('rejects taps on simulators without touch input', async () => {
+  const response = await server.request('POST', '/api/simulator/tap', {
+    udid: 'TV',
+    x: 0.5,
+    y: 0.5,
+    screenWidth: 3840,
+    screenHeight: 2160,
+  });
+
+  assert.equal(response.status, 400);
+  assert.equal(response.body.error, 'Touch input is not supported on this simulator');
+});
+
+test('types text into the focused field', async () => {
+  const response = await server.request('POST', '/api/simulator/type', { udid: 'PHONE', text: 'hello@plasma.dev\n' });
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(server.devices.calls.at(-1), { kind: 'type', udid: 'PHONE', text: 'hello@plasma.dev\n' });
+});
+
+test('rejects text that cannot be typed', async () => {
+  const response = await server.request('POST', '/api/simulator/type', { udid: 'PHONE', text: 'café' });
+
+  assert.equal(response.status, 400);
+  assert.equal(response.body.error, 'text can only contain characters on a US keyboard');
+});
+
+test('presses keys by name or HID usage code', async () => {
+  await server.request('POST', '/api/simulator/key', { udid: 'PHONE', key: 'return' });
+  await server.request('POST', '/api/simulator/key', { udid: 'PHONE', key: 0x3a });
+
+  assert.deepEqual(server.devices.calls.slice(-4), [
+    { kind: 'command', udid: 'PHONE', command: 'key 40 down' },
+    { kind: 'command', udid: 'PHONE', command: 'key 40 up' },
+    { kind: 'command', udid: 'PHONE', command: 'key 58 down' },
+    { kind: 'command', udid: 'PHONE', command: 'key 58 up' },
+  ]);
+
+  const unknown = await server.request('POST', '/api/simulator/key', { udid: 'PHONE', key: 'hyper' });
+  assert.equal(unknown.status, 400);
+});
+
+test('presses hardware buttons', async () => {
+  await server.request('POST', '/api/simulator/button', { udid: 'PHONE', button: 'home' });
+  await server.request('POST', '/api/simulator/button', { udid: 'PHONE', button: 'volume-up' });
+
+  assert.deepEqual(server.devices.calls.slice(-3), [
+    { kind: 'button', udid: 'PHONE', button: 'home' },
+    { kind: 'command', udid: 'PHONE', command: 'button volumeUp down' },
+    { kind: 'command', udid: 'PHONE', command: 'button volumeUp up' },
+  ]);
+
+  const unknown = await server.request('POST', '/api/simulator/button', { udid: 'PHONE', button: 'action' });
+  assert.equal(unknown.status, 400);
+});
+
+test('rotates simulators', async () => {
+  const response = await server.request('POST', '/api/simulator/orientation', { udid: 'PHONE', orientation: 'landscape-left' });
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(server.devices.calls.at(-1), { kind: 'command', udid: 'PHONE', command: 'rotate LandscapeLeft' });
+
+  const invalid = await server.request('POST', '/api/simulator/orientation', { udid: 'PHONE', orientation: 'sideways' });
+  assert.equal(invalid.status, 400);
+
+  const tv = await server.request('POST', '/api/simulator/orientation', { udid: 'TV', orientation: 'portrait' });
+  assert.equal(tv.status, 400);
+  assert.equal(tv.body.error, 'Rotation is not supported on this simulator');
+});
+
+test('launches apps in another language', async () => {
+  const response = await server.request('POST', '/api/simulator/launch', {
+    udid: 'PHONE',
+    appPath: '/tmp/Plasma.app',
+    bundleId: 'dev.plasma.app',
+    language: 'pt-BR',
+    locale: 'pt_BR',
+  });
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(server.devices.calls.at(-1), {
+    kind: 'launch',
+    udid: 'PHONE',
+    appPath: '/tmp/Plasma.app',
+    bundleId: 'dev.plasma.app',
+    launchArgs: ['-AppleLanguages', '(pt-BR)', '-AppleLocale', 'pt_BR'],
+  });
+});
+
+test('rejects invalid launch languages', async () => {
+  const response = await server.request('POST', '/api/simulator/launch', {
+    udid: 'PHONE',
+    appPath: '/tmp/Plasma.app',
+    language: 'French; rm -rf',
+  });
+
+  assert.equal(response.status, 400);
+});
+
+test('summarizes status from the cached simulator list', async () => {
+  await server.request('GET', '/api/simulator/list');
+  const response = await server.request('GET', '/api/status/summary');
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(response.body.activeBuilds, []);
+  assert.deepEqual(response.body.bootedSimulators, [
+    { udid: 'PHONE', name: 'iPhone 16' },
+    { udid: 'TV', name: 'Apple TV' },
+  ]);
+  assert.equal(response.body.lastBuild, null);
+  assert.deepEqual(response.body.keepAwake, []);
+});
@@ -0,0 +1,165 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import * as fs from 'fs';
+import * as http from 'http';
+import * as os from 'os';
+import * as path from 'path';
+import { AddressInfo } from 'net';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { configureSecrets, deleteSecret, setSecret } from '../src/main/services/secrets';
+import {
+  createLocalStore,
+  createS3Store,
+  signS3Request,
+  ArtifactStore,
+  S3Settings,
+  S3_SECRET_ACCESS_KEY_SECRET,
+} from '../src/main/services/storage';
+
+const servers: http.Server[] = [];
+let testServer: TestServer;
+
+before(async () => {
+  testServer = await startTestServer();
+  configureSecrets({ passphrase: 'correct horse', keychain: false });
+});
+
+after(async () => {
+  for (const server of servers) {
+    server.close();
+  }
+  configureSecrets({ passphrase: null, keychain: false });
+  await testServer.close();
+});
+
+function listen(handler: http.RequestListener): Promise<string> {
+  const server = http.createServer(handler);
+  servers.push(server);
+  return new Promise((resolve) => {
+    server.listen(0, '127.0.0.1', () => resolve(`http://127.0.0.1:${(server.address() as AddressInfo).port}`));
+  });
+}
+
+function tempFile(contents: string): string {
+  const file = path.join(fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-storage-')), 'artifact.log');
+  fs.writeFileSync(file, contents);
+  return file;
+}
+
+// Serves artifacts from a store the way the build log endpoint does
+function serve(store: ArtifactStore): Promise<string> {
+  return listen((req, res) => {
+    store.send(req, res, req.url!.slice(1)).catch((err) => {
+      res.writeHead(500);
+      res.end(String(err));
+    });
+  });
+}
+
+test('moves files into the local store', async () => {
+  const store = createLocalStore(fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-artifacts-')));
+  const file = tempFile('hello');
+
+  await store.importFile('build-logs/one.log', file);
+  assert.equal(fs.existsSync(file), false);
+  assert.equal((await store.stat('build-logs/one.log'))?.size, 5);
+
+  const baseUrl = await serve(store);
+  const response = await fetch(`${baseUrl}/build-logs/one.log`, { headers: { Range: 'bytes=1-' } });
+  assert.equal(response.status, 206);
+  assert.equal(await response.text(), 'ello');
+
+  await store.delete('build-logs/one.log');
+  assert.equal(await store.stat('build-logs/one.log'), null);
+});
+
+test('rejects keys outside the store', async () => {
+  const store = createLocalStore(fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-artifacts-')));
+  await assert.rejects(store.stat('../plasma.db'), /Invalid artifact key/);
+});
+
+test('stores artifacts in an S3-compatible bucket', async () => {
+  // Minimal S3 stand-in: path-style objects kept in memory
+  const objects = new Map<string, Buffer>();
+  const authorizations: string[] = [];
+  const endpoint = await listen((req, res) => {
+    authorizations.push(String(req.headers.authorization));
+    const chunks: Buffer[] = [];
+    req.on('data', (chunk) => chunks.push(chunk));
+    req.on('end', () => {
+      const object = objects.get(req.url!);
+      if (req.method === 'PUT') {
+        objects.set(req.url!, Buffer.concat(chunks));
+        res.writeHead(200);
+      } else if (req.method === 'DELETE') {
+        objects.delete(req.url!);
+        res.writeHead(204);
+      } else if (!object) {
+        res.writeHead(404);
+      } else {
+        res.writeHead(200, { 'Content-Length': object.length });
+        if (req.method === 'GET') {
+          res.write(object);
+        }
+      }
+      res.end();
+    });
+  });
+
+  const store = createS3Store({
+    endpoint,
+    bucket: 'artifacts',
+    region: 'us-east-1',
+    accessKeyId: 'PLASMAKEY',
+    secretAccessKey: 'secret',
+    prefix: 'team/',
+  });
+  const file = tempFile('build output');
+
+  await store.importFile('build-logs/two.log', file);
+  assert.equal(fs.existsSync(file), false);
+  assert.equal(objects.get('/artifacts/team/build-logs/two.log')?.toString(), 'build output');
+  assert.match(authorizations[0], /^AWS4-HMAC-SHA256 Credential=PLASMAKEY\/\d{8}\/us-east-1\/s3\/aws4_request/);
+
+  const baseUrl = await serve(store);
+  const response = await fetch(`${baseUrl}/build-logs/two.log`);
+  assert.equal(response.status, 200);
+  assert.equal(response.headers.get('content-type'), 'text/plain; charset=utf-8');
+  assert.equal(await response.text(), 'build output');
+
+  await store.delete('build-logs/two.log');
+  assert.equal(await store.stat('build-logs/two.log'), null);
+  const missing = await fetch(`${baseUrl}/build-logs/two.log`);
+  assert.equal(missing.status, 404);
+});
+
+test('signs S3 requests with the secret access key from the secrets store', async () => {
+  const requests: http.IncomingMessage[] = [];
+  const endpoint = await listen((req, res) => {
+    requests.push(req);
+    res.writeHead(404);
+    res.end();
+  });
+  const settings: S3Settings = {
+    endpoint,
+    bucket: 'artifacts',
+    region: 'us-east-1',
+    accessKeyId: 'PLASMAKEY',
+    secretAccessKey: 'from-the-environment',
+    prefix: '',
+  };
+  await setSecret(S3_SECRET_ACCESS_KEY_SECRET, 'from-the-secrets-store');
+
+  try {
+    await createS3Store(settings).stat('build-logs/three.log');
+  } finally {
+    await deleteSecret(S3_SECRET_ACCESS_KEY_SECRET);
+  }
+
+  const [req] = requests;
+  const amzDate = String(req.headers['x-amz-date']);
+  const now = new Date(amzDate.replace(/^(\d{4})(\d{2})(\d{2})T(\d{2})(\d{2})(\d{2})Z$/, '$1-$2-$3T$4:$5:$6Z'));
+  const url = new URL(`${endpoint}${req.url}`);
+  const expected = signS3Request({ ...settings, secretAccessKey: 'from-the-secrets-store' }, 'HEAD', url, now);
+  assert.equal(req.headers.authorization, expected.Authorization);
+});
@@ -0,0 +1,56 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import {
+  trackStream,
+  untrackStream,
+  recordFrame,
+  recordSessionRestart,
+  evaluateStreamHealth,
+} from '../src/main/services/stream-health';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer();
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('alerts when a stream stops delivering frames and resolves when it stops', async () => {
+  trackStream('SIM-1', 30);
+  recordFrame('SIM-1');
+  evaluateStreamHealth(Date.now() + 15000);
+
+  const firing = await server.request('GET', '/api/alerts');
+  assert.deepEqual(
+    firing.body.alerts.map((alert: { rule: string }) => alert.rule).sort(),
+    ['fps_low', 'frame_gap']
+  );
+
+  const notifications = await server.request('GET', '/api/notifications?kind=stream_degraded');
+  assert.equal(notifications.body.notifications.length, 2);
+
+  // Still degraded on the next check, but no new notifications
+  evaluateStreamHealth(Date.now() + 20000);
+  const repeated = await server.request('GET', '/api/notifications?kind=stream_degraded');
+  assert.equal(repeated.body.notifications.length, 2);
+
+  untrackStream('SIM-1');
+  const resolved = await server.request('GET', '/api/alerts');
+  assert.deepEqual(resolved.body.alerts, []);
+});
+
+test('alerts when a session keeps restarting', async () => {
+  for (let i = 0; i < 3; i++) {
+    recordSessionRestart('SIM-2');
+  }
+  evaluateStreamHealth();
+
+  const response = await server.request('GET', '/api/alerts');
+  assert.equal(response.body.alerts.length, 1);
+  assert.equal(response.body.alerts[0].rule, 'session_restarts');
+  assert.equal(response.body.alerts[0].value, 3);
+});
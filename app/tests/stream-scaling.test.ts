@@ -0,0 +1,79 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import WebSocket from 'ws';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { createFakeDevicesBackend, renderFakeFrame } from '../src/main/services/fake-devices';
+import { StreamScaler } from '../src/main/services/stream-scaling';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({ backend: createFakeDevicesBackend() });
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('scales frames to the maximum width', () => {
+  const scaler = new StreamScaler({ maxWidth: 20 });
+  const { frame, format } = scaler.apply(renderFakeFrame('FAKE-IPHONE-16', 0, 40, 80), 'png');
+
+  assert.equal(format, 'png');
+  assert.equal(frame.readUInt32BE(16), 20);
+  assert.equal(frame.readUInt32BE(20), 40);
+});
+
+test('shrinks frames while the client is behind and grows them back once it keeps up', () => {
+  const scaler = new StreamScaler({ scale: 0.8, adaptive: true });
+
+  assert.equal(scaler.update(0.5, 1000), 0.5);
+  // Not again before the first step had time to drain the queue
+  assert.equal(scaler.update(0.5, 1500), null);
+  assert.equal(scaler.update(0.5, 2000), 0.25);
+  assert.equal(scaler.update(0.9, 3000), null);
+  assert.equal(scaler.scaleFor(100), 0.2);
+
+  assert.equal(scaler.update(0, 6000), null);
+  assert.equal(scaler.update(0, 8000), 0.5);
+  assert.equal(scaler.update(0, 13000), 1);
+  assert.equal(scaler.scaleFor(100), 0.8);
+});
+
+test('sends smaller frames to clients that ask for them', async () => {
+  const ws = new WebSocket(server.baseUrl.replace('http', 'ws'));
+  await new Promise((resolve) => ws.once('open', resolve));
+
+  const frame = new Promise<{ frame: string; format: string }>((resolve) => {
+    ws.on('message', (data) => {
+      const message = JSON.parse(data.toString());
+      if (message.type === 'simulator:stream:frame') {
+        resolve(message.payload);
+      }
+    });
+  });
+  ws.send(JSON.stringify({ type: 'simulator:stream:start', payload: { udid: 'FAKE-IPHONE-16', scale: 0.5 } }));
+
+  const payload = await frame;
+  ws.close();
+  // Fake phones stream 393x852 frames
+  assert.equal(Buffer.from(payload.frame, 'base64').readUInt32BE(16), 197);
+});
+
+test('rejects invalid frame sizes', async () => {
+  const ws = new WebSocket(server.baseUrl.replace('http', 'ws'));
+  await new Promise((resolve) => ws.once('open', resolve));
+
+  const error = new Promise<{ code: string }>((resolve) => {
+    ws.on('message', (data) => {
+      const message = JSON.parse(data.toString());
+      if (message.type === 'error') {
+        resolve(message.payload);
+      }
+    });
+  });
+  ws.send(JSON.stringify({ type: 'simulator:stream:start', payload: { udid: 'FAKE-IPHONE-16', scale: 0 } }));
+
+  assert.equal((await error).code, 'invalid_request');
+  ws.close();
+});
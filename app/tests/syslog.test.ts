@@ -0,0 +1,116 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, createFakeDeviceBackend, TestServer } from '../src/main/testing';
+import { JsonObjectSplitter, parseSyslogEntry } from '../src/main/services/syslog';
+
+// `log stream --style json` output: a SpringBoard entry, an activity and an entry of the app
+const LOG_OUTPUT = `[{
+  "timestamp" : "2024-05-01 10:20:30.123456-0700",
+  "eventType" : "logEvent",
+  "messageType" : "Default",
+  "processImagePath" : "/System/Library/CoreServices/SpringBoard.app/SpringBoard",
+  "processID" : 50,
+  "subsystem" : "com.apple.SpringBoard",
+  "category" : "",
+  "eventMessage" : "Scene {update}"
+},{
+  "timestamp" : "2024-05-01 10:20:30.200000-0700",
+  "eventType" : "activityCreateEvent",
+  "eventMessage" : "activity"
+},{
+  "timestamp" : "2024-05-01 10:20:31.000000-0700",
+  "eventType" : "logEvent",
+  "messageType" : "Error",
+  "processImagePath" : "/Users/me/Library/Developer/CoreSimulator/Devices/PHONE/data/Containers/Bundle/Application/1/MyApp.app/MyApp",
+  "processID" : 4242,
+  "subsystem" : "com.example.app",
+  "category" : "network",
+  "eventMessage" : "Request failed: \\"timeout\\" }"
+}]`;
+
+let server: TestServer;
+const predicates: Array<string | null> = [];
+
+before(async () => {
+  server = await startTestServer({
+    backend: {
+      ...createFakeDeviceBackend([
+        { udid: 'PHONE', name: 'iPhone 16' },
+        { udid: 'OFF', name: 'iPhone 15', state: 'Shutdown' },
+      ]),
+      // Prints the output in uneven chunks, then ends like a simulator shutting down
+      streamSystemLog(_udid, predicate, onOutput, onExit) {
+        predicates.push(predicate);
+        const timer = setTimeout(() => {
+          for (let offset = 0; offset < LOG_OUTPUT.length; offset += 37) {
+            onOutput(LOG_OUTPUT.slice(offset, offset + 37));
+          }
+          onExit();
+        }, 10);
+        return () => clearTimeout(timer);
+      },
+    },
+  });
+});
+
+after(async () => {
+  await server.close();
+});
+
+function syslogEvents(text: string): any[] {
+  return text
+    .split('\n\n')
+    .filter((block) => block.includes('event: syslog\n'))
+    .map((block) => JSON.parse(block.slice(block.indexOf('data: ') + 'data: '.length)));
+}
+
+test('picks the objects out of a streamed JSON array', () => {
+  const splitter = new JsonObjectSplitter();
+  assert.deepEqual(splitter.push('[{"a":"}'), []);
+  assert.deepEqual(splitter.push('","b":{"c":1}},{"d"'), ['{"a":"}","b":{"c":1}}']);
+  assert.deepEqual(splitter.push(':2}]'), ['{"d":2}']);
+});
+
+test('parses log entries and skips other events', () => {
+  const entry = parseSyslogEntry(
+    'PHONE',
+    '{"timestamp":"2024-05-01 10:20:30.123456-0700","eventType":"logEvent","messageType":"Fault","processImagePath":"/bin/MyApp","processID":1,"subsystem":"","category":"","eventMessage":"boom"}'
+  );
+  assert.deepEqual(entry, {
+    udid: 'PHONE',
+    timestamp: '2024-05-01T17:20:30.123Z',
+    process: 'MyApp',
+    pid: 1,
+    subsystem: null,
+    category: null,
+    level: 'fault',
+    message: 'boom',
+  });
+  assert.equal(parseSyslogEntry('PHONE', '{"eventType":"signpostEvent","eventMessage":"x"}'), null);
+});
+
+test('streams the entries of a simulator\'s system log without noise', async () => {
+  const response = await fetch(`${server.baseUrl}/api/simulator/syslog?udid=PHONE&predicate=${encodeURIComponent('subsystem == "com.example.app"')}`);
+  const entries = syslogEvents(await response.text());
+
+  assert.equal(predicates.at(-1), 'subsystem == "com.example.app"');
+  assert.equal(entries.length, 1);
+  assert.equal(entries[0].process, 'MyApp');
+  assert.equal(entries[0].level, 'error');
+  assert.equal(entries[0].category, 'network');
+  assert.equal(entries[0].message, 'Request failed: "timeout" }');
+});
+
+test('keeps the noise with noise=none', async () => {
+  const response = await fetch(`${server.baseUrl}/api/simulator/syslog?udid=PHONE&noise=none`);
+  const entries = syslogEvents(await response.text());
+
+  assert.deepEqual(entries.map((entry) => entry.process), ['SpringBoard', 'MyApp']);
+  assert.equal(predicates.at(-1), null);
+});
+
+test('rejects shut down or unknown simulators and unknown noise profiles', async () => {
+  assert.equal((await server.request('GET', '/api/simulator/syslog?udid=OFF')).status, 409);
+  assert.equal((await server.request('GET', '/api/simulator/syslog?udid=NOPE')).status, 404);
+  assert.equal((await server.request('GET', '/api/simulator/syslog?udid=PHONE&noise=chatter')).status, 400);
+});
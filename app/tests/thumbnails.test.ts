@@ -0,0 +1,77 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import * as fs from 'fs';
+import * as path from 'path';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { createFakeDevicesBackend, renderFakeFrame } from '../src/main/services/fake-devices';
+import { makeThumbnail } from '../src/main/services/thumbnails';
+import { decodeJpeg } from '../src/main/services/jpeg';
+import { decodePng } from '../src/main/services/png';
+
+// 40x24, 4:2:0: red left half, blue top right and green bottom right quadrants
+const QUADRANTS = fs.readFileSync(path.join(__dirname, 'fixtures', 'frames', 'quadrants.jpg'));
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({ backend: createFakeDevicesBackend() });
+});
+
+after(async () => {
+  await server.close();
+});
+
+function assertColor(actual: number[], expected: number[]): void {
+  actual.forEach((value, channel) => assert.ok(Math.abs(value - expected[channel]) <= 12, `${actual} is not close to ${expected}`));
+}
+
+test('decodes JPEG frames at a fraction of their size', () => {
+  for (const size of [1, 2, 4, 8] as const) {
+    const image = decodeJpeg(QUADRANTS, size);
+    assert.equal(image.width, 5 * size);
+    assert.equal(image.height, 3 * size);
+    const pixel = (x: number, y: number) => Array.from(image.pixels.subarray((y * image.width + x) * 3, (y * image.width + x) * 3 + 3));
+    assertColor(pixel(0, 0), [200, 30, 30]);
+    assertColor(pixel(image.width - 1, image.height - 1), [30, 200, 30]);
+  }
+});
+
+test('scales JPEG and PNG frames down to PNG thumbnails', () => {
+  const fromJpeg = decodePng(makeThumbnail(QUADRANTS, 'jpeg', 0.25));
+  assert.deepEqual([fromJpeg.width, fromJpeg.height], [10, 6]);
+  assertColor(Array.from(fromJpeg.pixels.subarray(0, 3)), [200, 30, 30]);
+
+  const fromPng = decodePng(makeThumbnail(renderFakeFrame('FAKE-IPHONE-16', 0, 40, 80), 'png', 0.5));
+  assert.deepEqual([fromPng.width, fromPng.height], [20, 40]);
+});
+
+test('streams thumbnails of a simulator', async () => {
+  const abort = new AbortController();
+  const response = await fetch(`${server.baseUrl}/api/simulator/thumbnail?udid=FAKE-IPHONE-16&fps=5&scale=0.25`, {
+    signal: abort.signal,
+  });
+  assert.equal(response.headers.get('Content-Type'), 'multipart/x-mixed-replace; boundary=plasmaframe');
+
+  // Read until the first frame is complete
+  const reader = response.body!.getReader();
+  let received = Buffer.alloc(0);
+  let match: RegExpExecArray | null = null;
+  while (!match || received.length < received.indexOf('\r\n\r\n') + 4 + Number(match[1])) {
+    const { value } = await reader.read();
+    received = Buffer.concat([received, Buffer.from(value!)]);
+    match = /Content-Length: (\d+)/.exec(received.toString('latin1'));
+  }
+  abort.abort();
+
+  const start = received.indexOf('\r\n\r\n') + 4;
+  const thumbnail = decodePng(received.subarray(start, start + Number(match[1])));
+  // Fake phones stream 393x852 frames
+  assert.deepEqual([thumbnail.width, thumbnail.height], [98, 213]);
+});
+
+test('rejects invalid thumbnail options', async () => {
+  assert.equal((await server.request('GET', '/api/simulator/thumbnail')).status, 400);
+  assert.equal((await server.request('GET', '/api/simulator/thumbnail?udid=FAKE-IPHONE-16&fps=30')).status, 400);
+  assert.equal((await server.request('GET', '/api/simulator/thumbnail?udid=FAKE-IPHONE-16&scale=2')).status, 400);
+  assert.equal((await server.request('GET', '/api/simulator/thumbnail?udid=MISSING')).status, 404);
+});
@@ -0,0 +1,50 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import * as fs from 'fs';
+import * as path from 'path';
+import { createTouchOverlay, drawTouches, recordTouch, setTouchOverlay, TOUCH_FADE_MS } from '../src/main/services/touch-overlay';
+import { decodeJpeg } from '../src/main/services/jpeg';
+import { decodePng, encodePng } from '../src/main/services/png';
+
+// 40x24, 4:2:0: red left half, blue top right and green bottom right quadrants
+const QUADRANTS = fs.readFileSync(path.join(__dirname, 'fixtures', 'frames', 'quadrants.jpg'));
+
+function pixel(image: { width: number; channels: number; pixels: Buffer }, x: number, y: number): number[] {
+  const offset = (y * image.width + x) * image.channels;
+  return Array.from(image.pixels.subarray(offset, offset + 3));
+}
+
+const overlay = createTouchOverlay();
+
+test('leaves frames alone without touches or with the overlay off', () => {
+  setTouchOverlay(overlay, 'TOUCH-OFF', false);
+  recordTouch(overlay, 'TOUCH-OFF', 'began', [{ x: 0.5, y: 0.5 }], 0);
+  assert.equal(drawTouches(overlay, 'TOUCH-OFF', QUADRANTS, 'jpeg', 0), QUADRANTS);
+
+  setTouchOverlay(overlay, 'TOUCH-IDLE', true);
+  assert.equal(drawTouches(overlay, 'TOUCH-IDLE', QUADRANTS, 'jpeg', 0), QUADRANTS);
+});
+
+test('draws touches into JPEG frames until they fade out', () => {
+  setTouchOverlay(overlay, 'TOUCH-JPEG', true);
+  recordTouch(overlay, 'TOUCH-JPEG', 'began', [{ x: 0.25, y: 0.5 }], 1000);
+  const drawn = decodeJpeg(drawTouches(overlay, 'TOUCH-JPEG', QUADRANTS, 'jpeg', 1000), 8);
+  const original = decodeJpeg(QUADRANTS, 8);
+  // The circle lightens the red under the finger, the green far away is untouched
+  const [r, g, b] = pixel(drawn, 10, 12);
+  assert.ok(g > 60 && b > 60, `${[r, g, b]} is not lighter than red`);
+  assert.deepEqual(pixel(drawn, 35, 20), pixel(original, 35, 20));
+
+  recordTouch(overlay, 'TOUCH-JPEG', 'ended', [], 2000);
+  assert.notEqual(drawTouches(overlay, 'TOUCH-JPEG', QUADRANTS, 'jpeg', 2000 + TOUCH_FADE_MS / 2), QUADRANTS);
+  assert.equal(drawTouches(overlay, 'TOUCH-JPEG', QUADRANTS, 'jpeg', 2000 + TOUCH_FADE_MS), QUADRANTS);
+});
+
+test('draws touches into PNG frames', () => {
+  const frame = encodePng({ width: 100, height: 200, channels: 3, pixels: Buffer.alloc(100 * 200 * 3) });
+  setTouchOverlay(overlay, 'TOUCH-PNG', true);
+  recordTouch(overlay, 'TOUCH-PNG', 'began', [{ x: 0.5, y: 0.5 }], 0);
+  const drawn = decodePng(drawTouches(overlay, 'TOUCH-PNG', frame, 'png', 0));
+  assert.ok(pixel(drawn, 50, 100).every((value) => value > 80));
+  assert.deepEqual(pixel(drawn, 5, 5), [0, 0, 0]);
+});
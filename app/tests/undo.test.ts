@@ -0,0 +1,123 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { spawnSync } from 'child_process';
+import { startTestServer, createFakeDeviceBackend, TestServer } from '../src/main/testing';
+import { getDatabase } from '../src/main/services/database';
+import { discardLeftoverSnapshots, setUndoWindow } from '../src/main/services/undo';
+
+const fake = createFakeDeviceBackend([
+  { udid: 'PHONE', name: 'iPhone 16' },
+  { udid: 'OLD-CLONE', name: 'iPhone 15 (Plasma undo snapshot)' },
+]);
+const containers = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-containers-'));
+const appPath = path.join(containers, 'Checkout.app');
+const dataPath = path.join(containers, 'data');
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({
+    backend: {
+      ...fake,
+      async simctl(args) {
+        await fake.simctl!(args);
+        if (args[0] === 'clone') {
+          return 'CLONE\n';
+        }
+        if (args[0] === 'get_app_container') {
+          return args[3] === 'app' ? appPath : dataPath;
+        }
+        return '';
+      },
+    },
+  });
+});
+
+after(async () => {
+  setUndoWindow(10 * 60 * 1000);
+  await server.close();
+  fs.rmSync(containers, { recursive: true, force: true });
+});
+
+test('undoes an erase with the simulator cloned before it', async () => {
+  const erase = await server.request('POST', '/api/simulator/erase', { udid: 'PHONE', snapshot: true });
+  assert.equal(erase.status, 200);
+  assert.equal(erase.body.undo.action, 'erase');
+  const simctl = fake.calls.filter((call) => call.kind === 'simctl').map((call) => call.args);
+  assert.deepEqual(simctl.slice(-3), [
+    ['shutdown', 'PHONE'],
+    ['clone', 'PHONE', 'iPhone 16 (Plasma undo snapshot)'],
+    ['erase', 'PHONE'],
+  ]);
+
+  const undo = await server.request('POST', `/api/simulator/undo/${erase.body.undo.id}`);
+  assert.equal(undo.status, 200);
+  assert.equal(undo.body.udid, 'CLONE');
+  assert.deepEqual(fake.calls.at(-1), { kind: 'simctl', args: ['rename', 'CLONE', 'iPhone 16'] });
+
+  const again = await server.request('POST', `/api/simulator/undo/${erase.body.undo.id}`);
+  assert.equal(again.status, 404);
+});
+
+test('erases without a snapshot by default', async () => {
+  const from = fake.calls.length;
+  const erase = await server.request('POST', '/api/simulator/erase', { udid: 'PHONE' });
+
+  assert.equal(erase.body.undo, null);
+  assert.ok(!fake.calls.slice(from).some((call) => call.kind === 'simctl' && call.args[0] === 'clone'));
+});
+
+test('restores an uninstalled app and its data', async () => {
+  fs.mkdirSync(appPath, { recursive: true });
+  fs.writeFileSync(path.join(appPath, 'Info.plist'), 'plist');
+  fs.mkdirSync(path.join(dataPath, 'Documents'), { recursive: true });
+  fs.writeFileSync(path.join(dataPath, 'Documents', 'cart.json'), '{"items":3}');
+
+  const uninstall = await server.request('POST', '/api/simulator/uninstall', { udid: 'PHONE', bundleId: 'dev.plasma.Checkout', snapshot: true });
+  assert.equal(uninstall.status, 200);
+  assert.deepEqual(fake.calls.at(-1), { kind: 'simctl', args: ['uninstall', 'PHONE', 'dev.plasma.Checkout'] });
+
+  // What a reinstall leaves in the container
+  fs.rmSync(path.join(dataPath, 'Documents'), { recursive: true });
+  fs.writeFileSync(path.join(dataPath, 'fresh'), '');
+
+  const undo = await server.request('POST', `/api/simulator/undo/${uninstall.body.undo.id}`);
+  assert.equal(undo.body.udid, 'PHONE');
+  const install = fake.calls.find((call) => call.kind === 'simctl' && call.args[0] === 'install');
+  assert.ok(install && install.kind === 'simctl' && install.args[2].endsWith('Checkout.app'));
+  assert.equal(fs.readFileSync(path.join(dataPath, 'Documents', 'cart.json'), 'utf-8'), '{"items":3}');
+  assert.ok(!fs.existsSync(path.join(dataPath, 'fresh')));
+});
+
+test('forgets snapshots after the undo window', async () => {
+  setUndoWindow(200);
+  const uninstall = await server.request('POST', '/api/simulator/uninstall', { udid: 'PHONE', bundleId: 'dev.plasma.Checkout', snapshot: true });
+  assert.ok((await server.request('GET', '/api/simulator/undo')).body.some((undo: { id: string }) => undo.id === uninstall.body.undo.id));
+
+  await new Promise((resolve) => setTimeout(resolve, 300));
+  const undo = await server.request('POST', `/api/simulator/undo/${uninstall.body.undo.id}`);
+  assert.equal(undo.status, 404);
+});
+
+test('leaves undo snapshots out of the simulator list', async () => {
+  const list = await server.request('GET', '/api/simulator/list?refresh=true');
+
+  assert.deepEqual(list.body.map((simulator: { udid: string }) => simulator.udid), ['PHONE']);
+});
+
+test('deletes the clones of an instance that exited', () => server.run(async () => {
+  // The PID of a process that already exited
+  const exited = spawnSync(process.execPath, ['-e', '']).pid;
+  const insert = getDatabase().prepare('INSERT INTO erase_snapshots (clone_udid, udid, owner_pid, expires_at) VALUES (?, ?, ?, ?)');
+  const later = new Date(Date.now() + 60_000).toISOString();
+  insert.run('ORPHAN', 'PHONE', exited, later);
+  insert.run('OWN', 'PHONE', process.pid, later);
+
+  assert.deepEqual(discardLeftoverSnapshots(), ['ORPHAN']);
+  await new Promise((resolve) => setTimeout(resolve, 50));
+  assert.ok(fake.calls.some((call) => call.kind === 'simctl' && call.args[0] === 'delete' && call.args[1] === 'ORPHAN'));
+  const remaining = getDatabase().prepare('SELECT clone_udid FROM erase_snapshots').all() as Array<{ clone_udid: string }>;
+  assert.ok(!remaining.some((row) => row.clone_udid === 'ORPHAN'));
+}));
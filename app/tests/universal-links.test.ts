@@ -0,0 +1,82 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { parseApplinksDomain, parseEntitlements, validateAssociationFile } from '../src/main/services/universal-links';
+import { checkUrl, setUrlPolicy } from '../src/main/services/url-policy';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({ devices: [{ udid: 'PHONE', name: 'iPhone 16' }] });
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('reads associated domains and the app ID from entitlements', () => {
+  const entitlements = parseEntitlements(`<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0"><dict>
+  <key>application-identifier</key><string>ABCDE12345.dev.plasma.app</string>
+  <key>com.apple.developer.associated-domains</key>
+  <array><string>applinks:plasma.dev</string><string>webcredentials:plasma.dev</string></array>
+</dict></plist>`);
+
+  assert.deepEqual(entitlements, {
+    appId: 'ABCDE12345.dev.plasma.app',
+    associatedDomains: ['applinks:plasma.dev', 'webcredentials:plasma.dev'],
+  });
+  assert.deepEqual(parseApplinksDomain('applinks:staging.plasma.dev?mode=developer'), { domain: 'staging.plasma.dev', developerMode: true });
+  assert.equal(parseApplinksDomain('webcredentials:plasma.dev'), null);
+});
+
+test('lists the paths an association file routes to the app', () => {
+  const file = JSON.stringify({
+    applinks: {
+      details: [
+        { appIDs: ['ABCDE12345.dev.plasma.app'], components: [{ '/': '/admin/*', exclude: true }, { '/': '/projects/*' }] },
+        { appID: 'ABCDE12345.dev.plasma.other', paths: ['*'] },
+      ],
+    },
+  });
+
+  assert.deepEqual(validateAssociationFile(file, 'ABCDE12345.dev.plasma.app'), {
+    errors: [],
+    warnings: [],
+    paths: ['NOT /admin/*', '/projects/*'],
+  });
+});
+
+test('reports association files that do not list the app', () => {
+  const file = JSON.stringify({ applinks: { details: [{ appID: 'ABCDE12345.dev.plasma.other', paths: ['*'] }] } });
+
+  assert.deepEqual(validateAssociationFile(file, 'ABCDE12345.dev.plasma.app').errors, [
+    'No applinks.details entry lists ABCDE12345.dev.plasma.app, only ABCDE12345.dev.plasma.other',
+  ]);
+  assert.match(validateAssociationFile('<html>', 'ABCDE12345.dev.plasma.app').errors[0], /not valid JSON/);
+});
+
+test('opens URLs on a simulator', async () => {
+  const response = await server.request('POST', '/api/simulator/PHONE/open-url', { url: 'https://plasma.dev/projects/1' });
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(server.devices.calls.at(-1), { kind: 'simctl', args: ['openurl', 'PHONE', 'https://plasma.dev/projects/1'] });
+  assert.equal((await server.request('POST', '/api/simulator/PHONE/open-url', { url: 'plasma.dev' })).status, 400);
+});
+
+test('refuses to open URLs outside the allowlist', async () => {
+  setUrlPolicy({ allow: ['https://*.plasma.dev/*', 'plasma:*'], deny: ['https://admin.plasma.dev/*'] });
+  try {
+    assert.equal(checkUrl('https://docs.plasma.dev/start'), null);
+    assert.equal(checkUrl('HTTPS://Docs.Plasma.dev'), null);
+    assert.equal(checkUrl('plasma://projects/1'), null);
+    assert.notEqual(checkUrl('https://admin.plasma.dev/users'), null);
+    assert.notEqual(checkUrl('https://evil.example/?https://docs.plasma.dev/'), null);
+
+    const response = await server.request('POST', '/api/simulator/PHONE/open-url', { url: 'https://evil.example' });
+    assert.equal(response.status, 403);
+    assert.equal(response.body.code, 'url_not_allowed');
+  } finally {
+    setUrlPolicy({ allow: [], deny: [] });
+  }
+});
@@ -0,0 +1,59 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { field, validate, ValidationError } from '../src/main/validation';
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer({ devices: [{ udid: 'PHONE', name: 'iPhone 16' }] });
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('reports every invalid field with its path', () => {
+  const schema = field.object({
+    udid: field.string(),
+    touches: field.array(field.object({ x: field.number({ min: 0, max: 1 }), y: field.number({ min: 0, max: 1 }) }), { min: 1 }),
+    duration: field.optional(field.number({ min: 0 })),
+  });
+
+  assert.throws(
+    () => validate({ touches: [{ x: 0.5, y: 0.5 }, { x: 1.5, y: 'top' }] }, schema),
+    (err: unknown) => {
+      assert.ok(err instanceof ValidationError);
+      assert.deepEqual(err.fields, [
+        { path: 'udid', message: 'is required' },
+        { path: 'touches[1].x', message: 'must be from 0 to 1' },
+        { path: 'touches[1].y', message: 'must be a number' },
+      ]);
+      assert.equal(err.message, 'udid is required; touches[1].x must be from 0 to 1; touches[1].y must be a number');
+      return true;
+    }
+  );
+  assert.deepEqual(validate({ udid: 'PHONE', touches: [{ x: 0, y: 1 }] }, schema), { udid: 'PHONE', touches: [{ x: 0, y: 1 }] });
+});
+
+test('answers invalid requests with the invalid fields', async () => {
+  const response = await server.request('POST', '/api/simulator/touch', { udid: 'PHONE', type: 'pressed', touches: [{ x: 0.5, y: 2 }] });
+
+  assert.equal(response.status, 400);
+  assert.deepEqual(response.body, {
+    error: 'type must be one of began, moved, ended; touches[0].y must be from 0 to 1',
+    code: 'invalid_request',
+    fields: [
+      { path: 'type', message: 'must be one of began, moved, ended' },
+      { path: 'touches[0].y', message: 'must be from 0 to 1' },
+    ],
+  });
+  assert.notEqual(server.devices.calls.at(-1)?.kind, 'command');
+});
+
+test('validates Xcode requests', async () => {
+  const response = await server.request('POST', '/api/xcode/launchable-products', { buildDir: 42 });
+
+  assert.equal(response.status, 400);
+  assert.deepEqual(response.body.fields, [{ path: 'buildDir', message: 'must be a string' }]);
+});
@@ -0,0 +1,59 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { createFakeDevicesBackend } from '../src/main/services/fake-devices';
+import { ffmpegArgs } from '../src/main/services/video';
+
+const PNG_SIGNATURE = Buffer.from([0x89, 0x50, 0x4e, 0x47]);
+
+let server: TestServer;
+
+before(async () => {
+  // Stands in for ffmpeg by passing the frames through as they are
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-ffmpeg-'));
+  process.env.FFMPEG_BINARY = path.join(dir, 'ffmpeg');
+  fs.writeFileSync(process.env.FFMPEG_BINARY, '#!/bin/sh\nexec cat\n', { mode: 0o755 });
+  server = await startTestServer({ backend: createFakeDevicesBackend() });
+});
+
+after(async () => {
+  delete process.env.FFMPEG_BINARY;
+  await server.close();
+});
+
+test('encodes fragmented MP4 with the hardware encoder on macOS', () => {
+  const option = (args: string[], name: string) => args[args.indexOf(name) + 1];
+  const args = ffmpegArgs({ fps: 30, bitrate: 2000000 }, 'darwin');
+
+  assert.equal(option(args, '-c:v'), 'h264_videotoolbox');
+  assert.equal(option(args, '-movflags'), 'frag_keyframe+empty_moov+default_base_moof');
+  assert.equal(option(args, '-g'), '30');
+  assert.equal(option(ffmpegArgs({ fps: 30, bitrate: 2000000 }, 'linux'), '-c:v'), 'libx264');
+});
+
+test("pipes a simulator's frames through ffmpeg", async () => {
+  const abort = new AbortController();
+  const response = await fetch(`${server.baseUrl}/api/simulator/video?udid=FAKE-IPHONE-16&fps=10`, { signal: abort.signal });
+  assert.equal(response.status, 200);
+  assert.equal(response.headers.get('Content-Type'), 'video/mp4');
+
+  const reader = response.body!.getReader();
+  let received = Buffer.alloc(0);
+  while (received.length < PNG_SIGNATURE.length) {
+    const { value } = await reader.read();
+    received = Buffer.concat([received, Buffer.from(value!)]);
+  }
+  abort.abort();
+
+  assert.deepEqual(received.subarray(0, PNG_SIGNATURE.length), PNG_SIGNATURE);
+});
+
+test('rejects invalid video options', async () => {
+  assert.equal((await server.request('GET', '/api/simulator/video')).status, 400);
+  assert.equal((await server.request('GET', '/api/simulator/video?udid=FAKE-IPHONE-16&fps=120')).status, 400);
+  assert.equal((await server.request('GET', '/api/simulator/video?udid=FAKE-IPHONE-16&bitrate=10')).status, 400);
+  assert.equal((await server.request('GET', '/api/simulator/video?udid=MISSING')).status, 404);
+});
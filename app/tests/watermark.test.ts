@@ -0,0 +1,61 @@
+import { test } from 'node:test';
+import assert from 'node:assert/strict';
+import * as fs from 'fs';
+import type * as http from 'http';
+import * as path from 'path';
+import { createWatermarker, drawWatermark, formatWatermark, viewerOf } from '../src/main/services/watermark';
+import { decodeJpeg } from '../src/main/services/jpeg';
+import { decodePng, encodePng } from '../src/main/services/png';
+
+// 40x24, 4:2:0: red left half, blue top right and green bottom right quadrants
+const QUADRANTS = fs.readFileSync(path.join(__dirname, 'fixtures', 'frames', 'quadrants.jpg'));
+
+function pixel(image: { width: number; channels: number; pixels: Buffer }, x: number, y: number): number[] {
+  const offset = (y * image.width + x) * image.channels;
+  return Array.from(image.pixels.subarray(offset, offset + 3));
+}
+
+test('fills in watermark templates', () => {
+  const text = formatWatermark('CONFIDENTIAL {user} {udid} {timestamp} {date} {unknown}', {
+    udid: 'PHONE',
+    user: 'qa@vendor.example',
+    now: new Date('2026-10-15T09:30:12.345Z'),
+  });
+  assert.equal(text, 'CONFIDENTIAL qa@vendor.example PHONE 2026-10-15T09:30:12Z 2026-10-15 {unknown}');
+});
+
+test('takes the viewer from Basic credentials or the address', () => {
+  const request = (authorization?: string) =>
+    ({ headers: { authorization }, socket: { remoteAddress: '10.0.0.5' } }) as unknown as http.IncomingMessage;
+
+  assert.equal(viewerOf(request(`Basic ${Buffer.from('qa@vendor.example:secret').toString('base64')}`)), 'qa@vendor.example');
+  assert.equal(viewerOf(request('Bearer token')), '10.0.0.5');
+  assert.equal(viewerOf(request()), '10.0.0.5');
+});
+
+test('draws the watermark into JPEG frames', () => {
+  const drawn = decodeJpeg(drawWatermark(QUADRANTS, 'jpeg', 'I'), 8);
+  const original = decodeJpeg(QUADRANTS, 8);
+  // The stem of the I, centered, lightens the red; blocks away from the text are untouched
+  const lightness = (rgb: number[]) => rgb.reduce((sum, value) => sum + value, 0);
+  assert.ok(lightness(pixel(drawn, 19, 3)) > lightness(pixel(original, 19, 3)) + 60);
+  assert.deepEqual(pixel(drawn, 5, 12), pixel(original, 5, 12));
+});
+
+test('draws the watermark and its shadow into PNG frames', () => {
+  const frame = encodePng({ width: 480, height: 480, channels: 3, pixels: Buffer.alloc(480 * 480 * 3, 100) });
+  const drawn = decodePng(drawWatermark(frame, 'png', 'TEST'));
+
+  // Font pixels are 4 pixels wide here; the first line starts at 194, 66 with the bar of the T
+  assert.deepEqual(pixel(drawn, 196, 67), [178, 178, 178]);
+  assert.deepEqual(pixel(drawn, 199, 71), [65, 65, 65]);
+  // The same text further down the frame
+  assert.deepEqual(pixel(drawn, 196, 227), [178, 178, 178]);
+  assert.deepEqual(pixel(drawn, 5, 5), [100, 100, 100]);
+});
+
+test('drops frames the watermark cannot be drawn into', () => {
+  const watermark = createWatermarker('CONFIDENTIAL {user}', { udid: 'PHONE', user: 'qa@vendor.example' });
+  assert.equal(watermark(Buffer.from('not an image'), 'jpeg'), null);
+  assert.ok(watermark(QUADRANTS, 'jpeg'));
+});
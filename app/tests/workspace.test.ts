@@ -0,0 +1,63 @@
+import { test, beforeEach } from 'node:test';
+import assert from 'node:assert/strict';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { startTestServer } from '../src/main/testing';
+import { setWorkspaceRoot, createJobDir, listJobDirs, recoverJobDirs } from '../src/main/services/workspace';
+
+let root: string;
+
+beforeEach(() => {
+  root = fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-workspace-'));
+  setWorkspaceRoot(root);
+});
+
+test('removes job directories on cleanup', () => {
+  const jobDir = createJobDir('build', 'abc');
+  fs.writeFileSync(path.join(jobDir.path, 'scratch.txt'), 'hello');
+
+  const [listed] = listJobDirs();
+  assert.equal(listed.kind, 'build');
+  assert.equal(listed.id, 'abc');
+  assert.equal(listed.active, true);
+  assert.ok(listed.sizeBytes > 0);
+
+  jobDir.cleanup();
+  assert.equal(fs.existsSync(jobDir.path), false);
+});
+
+test('recovers directories left behind by dead processes', () => {
+  const live = createJobDir('automation', 'live');
+  const crashed = createJobDir('build', 'crashed');
+  const owner = JSON.parse(fs.readFileSync(path.join(crashed.path, '.owner.json'), 'utf-8'));
+  // PIDs are capped well below this, so no process can own it
+  fs.writeFileSync(path.join(crashed.path, '.owner.json'), JSON.stringify({ ...owner, pid: 2 ** 30 }));
+  const orphan = path.join(root, 'tmp', 'unknown');
+  fs.mkdirSync(orphan);
+
+  assert.deepEqual(
+    recoverJobDirs().map((dir) => dir.id).sort(),
+    ['crashed', null]
+  );
+  assert.equal(fs.existsSync(live.path), true);
+  assert.equal(fs.existsSync(crashed.path), false);
+  assert.equal(fs.existsSync(orphan), false);
+});
+
+test('long-polls the job list until a directory is created', async () => {
+  const server = await startTestServer();
+  try {
+    const first = await server.request('GET', '/api/workspace/jobs');
+    const etag = first.headers.get('ETag')!;
+    assert.equal((await server.request('GET', '/api/workspace/jobs', undefined, { 'If-None-Match': etag })).status, 304);
+
+    const waiting = server.request('GET', '/api/workspace/jobs?wait=5s', undefined, { 'If-None-Match': etag });
+    createJobDir('capture', 'polled');
+    const changed = await waiting;
+    assert.equal(changed.status, 200);
+    assert.ok(changed.body.some((dir: { id: string }) => dir.id === 'polled'));
+  } finally {
+    await server.close();
+  }
+});
@@ -0,0 +1,253 @@
+import { test, before, after } from 'node:test';
+import assert from 'node:assert/strict';
+import * as fs from 'fs';
+import * as os from 'os';
+import * as path from 'path';
+import { startTestServer, TestServer } from '../src/main/testing';
+import { recordBuild, parseBuildSettings } from '../src/main/services/builds';
+import { destinationArgs, includeBuildOutput, suggestScheme } from '../src/main/services/xcode';
+import { hashArtifact } from '../src/main/services/provenance';
+
+const WORKSPACE = path.join(__dirname, 'fixtures', 'xcode', 'Plasma.xcworkspace');
+
+let server: TestServer;
+
+before(async () => {
+  server = await startTestServer();
+});
+
+after(async () => {
+  await server.close();
+});
+
+test('validates an Xcode workspace', async () => {
+  const response = await server.request('POST', '/api/validate/xcode', { path: WORKSPACE });
+
+  assert.equal(response.status, 200);
+  assert.deepEqual(response.body, { valid: true, path: WORKSPACE, type: 'workspace' });
+});
+
+test('lists build destination presets', async () => {
+  const response = await server.request('GET', '/api/xcode/destinations?sort=id');
+
+  assert.equal(response.status, 200);
+  assert.ok(response.body.some((preset: { id: string }) => preset.id === 'ios-simulator'));
+});
+
+test('leaves the SDK of Mac Catalyst builds to the destination', () => {
+  assert.deepEqual(destinationArgs('macos'), ['-sdk', 'macosx', '-destination', 'generic/platform=macOS']);
+  assert.deepEqual(destinationArgs('mac-catalyst'), ['-destination', 'generic/platform=macOS,variant=Mac Catalyst']);
+});
+
+test('stores a per-project build destination', async () => {
+  const created = await server.request('POST', '/api/projects/create', { name: 'Plasma', xcodePath: WORKSPACE });
+  const destinationPath = `/api/projects/${created.body.project.id}/destination`;
+
+  const initial = await server.request('GET', destinationPath);
+  assert.equal(initial.body.destination, 'ios-simulator');
+
+  const invalid = await server.request('PUT', destinationPath, { destination: 'android' });
+  assert.equal(invalid.status, 400);
+
+  const updated = await server.request('PUT', destinationPath, { destination: 'macos' });
+  assert.equal(updated.status, 200);
+  assert.equal(updated.body.destination, 'macos');
+});
+
+test('rejects destination changes based on a stale version', async () => {
+  const created = await server.request('POST', '/api/projects/create', { name: 'Plasma', xcodePath: WORKSPACE });
+  const destinationPath = `/api/projects/${created.body.project.id}/destination`;
+  const { body: base } = await server.request('GET', destinationPath);
+
+  const first = await server.request('PUT', destinationPath, {
+    destination: 'visionos-simulator',
+    expectedUpdatedAt: base.updatedAt,
+  });
+  assert.equal(first.status, 200);
+  assert.notEqual(first.body.updatedAt, base.updatedAt);
+
+  // A second tab still working from the same version
+  const stale = await server.request('PUT', destinationPath, {
+    destination: 'tvos-simulator',
+    expectedUpdatedAt: base.updatedAt,
+  });
+  assert.equal(stale.status, 409);
+  assert.equal(stale.body.destination, 'visionos-simulator');
+  assert.equal(stale.body.updatedAt, first.body.updatedAt);
+
+  const retried = await server.request('PUT', destinationPath, {
+    destination: 'tvos-simulator',
+    expectedUpdatedAt: stale.body.updatedAt,
+  });
+  assert.equal(retried.status, 200);
+  assert.equal(retried.body.destination, 'tvos-simulator');
+});
+
+test('compares two recorded builds', async () => {
+  const build = (id: string, sizeBytes: number, optimization: string) =>
+    recordBuild({
+      id,
+      project: null,
+      projectPath: WORKSPACE,
+      scheme: 'Plasma',
+      configuration: 'Debug',
+      destination: 'ios-simulator',
+      status: 'succeeded',
+      durationMs: id === 'before' ? 40000 : 45000,
+      warnings: id === 'before' ? 2 : 5,
+      errors: 0,
+      products: [{ name: 'Plasma.app', sizeBytes }],
+      buildSettings: { PRODUCT_NAME: 'Plasma', SWIFT_OPTIMIZATION_LEVEL: optimization },
+      startedAt: new Date().toISOString(),
+      finishedAt: new Date().toISOString(),
+    });
+  await build('before', 1000, '-Onone');
+  await build('after', 9000, '-O');
+
+  const response = await server.request('GET', '/api/builds/compare?a=before&b=after');
+  assert.equal(response.status, 200);
+  assert.deepEqual(response.body.totalSizeBytes, { a: 1000, b: 9000, delta: 8000 });
+  assert.deepEqual(response.body.warnings, { a: 2, b: 5, delta: 3 });
+  assert.equal(response.body.durationMs.delta, 5000);
+  assert.deepEqual(response.body.changedSettings, [{ key: 'SWIFT_OPTIMIZATION_LEVEL', a: '-Onone', b: '-O' }]);
+
+  const missing = await server.request('GET', '/api/builds/compare?a=before&b=unknown');
+  assert.equal(missing.status, 404);
+});
+
+test('lists the build history of a project', async () => {
+  const created = await server.request('POST', '/api/projects/create', { name: 'History', xcodePath: WORKSPACE });
+  const project = created.body.project;
+  const build = (id: string, status: 'succeeded' | 'failed' | 'cancelled', finishedAt: string) =>
+    recordBuild({
+      id,
+      project,
+      projectPath: WORKSPACE,
+      scheme: 'Plasma',
+      configuration: 'Debug',
+      destination: 'ios-simulator',
+      status,
+      durationMs: 1000,
+      warnings: 0,
+      errors: status === 'failed' ? 1 : 0,
+      products: [],
+      buildSettings: {},
+      startedAt: new Date(Date.parse(finishedAt) - 1000).toISOString(),
+      finishedAt,
+    });
+  await build('history-1', 'failed', '2026-01-01T10:00:00.000Z');
+  await build('history-2', 'succeeded', '2026-01-01T11:00:00.000Z');
+  await build('history-3', 'cancelled', '2026-01-01T12:00:00.000Z');
+
+  const response = await server.request('GET', `/api/builds?project_id=${project.id}`);
+  assert.equal(response.status, 200);
+  assert.deepEqual(
+    response.body.map((record: { id: string }) => record.id),
+    ['history-3', 'history-2', 'history-1']
+  );
+  assert.equal(response.body[1].success, true);
+  assert.equal(response.body[2].logUrl, '/api/builds/history-1/log');
+
+  const failed = await server.request('GET', `/api/builds?project_id=${project.id}&status=failed`);
+  assert.deepEqual(failed.body.map((record: { id: string }) => record.id), ['history-1']);
+
+  const invalid = await server.request('GET', '/api/builds?project_id=abc');
+  assert.equal(invalid.status, 400);
+});
+
+test('rejects cancelling unknown builds', async () => {
+  assert.equal((await server.request('POST', '/api/xcode/build/cancel', {})).status, 400);
+
+  const response = await server.request('POST', '/api/xcode/build/cancel', { buildId: 'missing' });
+  assert.equal(response.status, 404);
+});
+
+test('parses the first target of xcodebuild -showBuildSettings', () => {
+  const output = [
+    'Build settings for action build and target Plasma:',
+    '    PRODUCT_NAME = Plasma',
+    '    SDKROOT = /Applications/Xcode.app/iphonesimulator.sdk',
+    '',
+    'Build settings for action build and target PlasmaTests:',
+    '    PRODUCT_NAME = PlasmaTests',
+  ].join('\n');
+
+  assert.deepEqual(parseBuildSettings(output), {
+    PRODUCT_NAME: 'Plasma',
+    SDKROOT: '/Applications/Xcode.app/iphonesimulator.sdk',
+  });
+});
+
+test('filters build output lines by level', () => {
+  const lines = [
+    '=== BUILD TARGET Plasma OF PROJECT Plasma WITH CONFIGURATION Debug ===',
+    'CompileSwiftSources normal arm64 com.apple.xcode.tools.swift.compiler (in target \'Plasma\' from project \'Plasma\')',
+    '    cd /Users/me/Plasma',
+    '/Users/me/Plasma/App.swift:12:9: warning: variable \'x\' was never used',
+    '/Users/me/Plasma/App.swift:20:5: error: cannot find \'y\' in scope',
+    '** BUILD FAILED **',
+  ];
+  const kept = (level: Parameters<typeof includeBuildOutput>[1]) => lines.filter((line) => includeBuildOutput(line, level));
+
+  assert.deepEqual(kept('all'), lines);
+  assert.deepEqual(kept('warnings'), [lines[3], lines[4], lines[5]]);
+  assert.deepEqual(kept('errors'), [lines[4], lines[5]]);
+  assert.deepEqual(kept('phases'), [lines[0], lines[1], lines[4], lines[5]]);
+});
+
+test('suggests the shared scheme that builds the app', () => {
+  // `Plasma Workspace` is shared and builds Plasma.app
+  assert.equal(suggestScheme(WORKSPACE, ['PlasmaTests', 'Plasma Workspace', 'PlasmaKit']), 'Plasma Workspace');
+  // Without shared schemes, the one named after the project wins over tests
+  assert.equal(suggestScheme('/tmp/Missing/Plasma.xcodeproj', ['PlasmaTests', 'Plasma']), 'Plasma');
+  assert.equal(suggestScheme(WORKSPACE, []), null);
+});
+
+test('hashes app bundles like shasum over a sorted listing of their files', async () => {
+  const app = path.join(fs.mkdtempSync(path.join(os.tmpdir(), 'plasma-provenance-')), 'App.app');
+  fs.mkdirSync(path.join(app, 'Frameworks'), { recursive: true });
+  fs.writeFileSync(path.join(app, 'Info.plist'), 'plist');
+  fs.writeFileSync(path.join(app, 'App'), 'binary');
+  fs.writeFileSync(path.join(app, 'Frameworks', 'Lib'), 'framework');
+
+  assert.equal(await hashArtifact(app), '713363c934344dbf59bcabbad6f2de53520633088479b281c95a7c1a1153dd62');
+  assert.equal(await hashArtifact(path.join(app, 'App')), '9a3a45d01531a20e89ac6ae10b0b0beb0492acd7216a368aa062d1a5fecaf9cd');
+});
+
+test('reports the provenance of a build and finds builds by checksum', async () => {
+  const sha256 = 'ab'.repeat(32);
+  await recordBuild({
+    id: 'provenance-1',
+    project: null,
+    projectPath: WORKSPACE,
+    scheme: 'Plasma',
+    configuration: 'Debug',
+    destination: 'ios-simulator',
+    status: 'succeeded',
+    durationMs: 1000,
+    warnings: 0,
+    errors: 0,
+    products: [{ name: 'Plasma.app', sizeBytes: 1000, sha256 }],
+    buildSettings: {},
+    startedAt: '2026-01-01T10:00:00.000Z',
+    finishedAt: '2026-01-01T10:00:01.000Z',
+    environment: {
+      machine: { hostname: 'ci-mac-1', platform: 'darwin', osRelease: '24.0.0', arch: 'arm64', plasmaVersion: '1.0.0' },
+      xcodeVersion: 'Xcode 16.0 Build version 16A242d',
+      git: { commit: 'c0ffee', branch: 'main', dirty: false },
+    },
+  });
+
+  const response = await server.request('GET', '/api/builds/provenance-1/provenance');
+  assert.equal(response.status, 200);
+  assert.deepEqual(response.body.artifacts, [{ name: 'Plasma.app', sizeBytes: 1000, sha256 }]);
+  assert.equal(response.body.machine.hostname, 'ci-mac-1');
+  assert.equal(response.body.git.commit, 'c0ffee');
+
+  const found = await server.request('GET', `/api/builds/provenance?sha256=${sha256.toUpperCase()}`);
+  assert.deepEqual(found.body.map((provenance: { buildId: string }) => provenance.buildId), ['provenance-1']);
+
+  assert.equal((await server.request('GET', '/api/builds/before/provenance')).body.git, null);
+  assert.equal((await server.request('GET', '/api/builds/unknown/provenance')).status, 404);
+  assert.equal((await server.request('GET', '/api/builds/provenance?sha256=abc')).status, 400);
+});